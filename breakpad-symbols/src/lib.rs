@@ -31,26 +31,30 @@
 //!               "vswprintf");
 //! ```
 
-use failure::Error;
+use failure::{err_msg, Error};
 use log::{debug, trace, warn};
-use reqwest::blocking::Client;
+use minidump_common::utils::{basename, pdb_file_name};
+use reqwest::blocking::{Client, Response};
 use reqwest::Url;
 use tempfile::NamedTempFile;
 
 use std::borrow::Cow;
 use std::boxed::Box;
 use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs;
-use std::io::{self, Write};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 pub use minidump_common::traits::Module;
 pub use sym_file::walker;
 
-pub use crate::sym_file::{CfiRules, SymbolFile};
+pub use crate::sym_file::{demangle, CfiRules, SymbolFile, SymbolRecord};
 
 mod sym_file;
 
@@ -59,10 +63,78 @@ mod sym_file;
 pub struct SymbolStats {
     /// If the module's symbols were downloaded, this is the url used.
     pub symbol_url: Option<String>,
+    /// Where the supplier ended up loading the module's symbols from.
+    pub symbol_origin: Option<SymbolOrigin>,
     /// If the symbols were found and loaded into memory.
     pub loaded_symbols: bool,
     /// If we tried to parse the symbols, but failed.
     pub corrupt_symbols: bool,
+    /// If we gave up on acquiring the symbols because it took too long.
+    pub timed_out: bool,
+}
+
+/// Where a [`SymbolFile`] was loaded from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolOrigin {
+    /// Downloaded from this URL.
+    Url(String),
+    /// Read from this file on disk (which may be a cached download).
+    Path(PathBuf),
+    /// Parsed from an in-memory buffer.
+    Memory,
+}
+
+impl fmt::Display for SymbolOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolOrigin::Url(url) => write!(f, "{}", url),
+            SymbolOrigin::Path(path) => write!(f, "{}", path.display()),
+            SymbolOrigin::Memory => write!(f, "<memory>"),
+        }
+    }
+}
+
+/// A symbol found by name, as returned by [`SymbolFile::lookup_by_name`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolEntry {
+    /// The full name of the symbol, as it appears in the symbol file.
+    pub name: String,
+    /// The address of the symbol, relative to the module's load address.
+    pub address: u64,
+    /// The size of the symbol in bytes, if known (only FUNC records have one).
+    pub size: Option<u32>,
+    /// The size of parameters passed to the function.
+    pub parameter_size: u32,
+    /// The name of the source file the symbol starts in, if known.
+    pub source_file: Option<String>,
+    /// The 1-based line number in `source_file` the symbol starts at, if known.
+    pub source_line: Option<u32>,
+    /// Whether this came from a FUNC or PUBLIC record.
+    pub kind: SymbolKind,
+}
+
+/// Which kind of symbol record a [`SymbolResult`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// A FUNC record, which has a known extent and possibly line info.
+    Func,
+    /// A PUBLIC record, the closest one preceding the address.
+    Public,
+}
+
+/// The symbol covering an address, as returned by [`Symbolizer::lookup`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolResult {
+    /// The name of the function.
+    pub function: String,
+    /// The address of the start of `function` (not module-relative).
+    pub function_base: u64,
+    /// The name of the source file containing the address, if known.
+    pub source_file: Option<String>,
+    /// The 1-based line number in `source_file` containing the address, if known.
+    pub source_line: Option<u32>,
+    /// Whether this came from a FUNC or PUBLIC record.
+    pub kind: SymbolKind,
 }
 
 /// A `Module` implementation that holds arbitrary data.
@@ -124,13 +196,6 @@ impl Module for SimpleModule {
     }
 }
 
-/// Like `PathBuf::file_name`, but try to work on Windows or POSIX-style paths.
-fn leafname(path: &str) -> &str {
-    path.rsplit(|c| c == '/' || c == '\\')
-        .next()
-        .unwrap_or(path)
-}
-
 /// If `filename` ends with `match_extension`, remove it. Append `new_extension` to the result.
 fn replace_or_add_extension(filename: &str, match_extension: &str, new_extension: &str) -> String {
     let mut bits = filename.split('.').collect::<Vec<_>>();
@@ -155,6 +220,10 @@ fn replace_or_add_extension(filename: &str, match_extension: &str, new_extension
 /// `extension` is the expected extension for the symbol filename, generally
 /// *sym* if Breakpad text format symbols are expected.
 ///
+/// If the module has no debug filename, the leaf of its code filename is
+/// used instead, with a *.dll* or *.exe* extension swapped for *.pdb* (so
+/// `xul.dll` is looked up as `xul.pdb`).
+///
 /// The debug filename and debug identifier can be found in the
 /// [first line][module_line] of the symbol file output by the dump_syms tool.
 /// You can use [this script][packagesymbols] to run dump_syms and put the
@@ -163,15 +232,69 @@ fn replace_or_add_extension(filename: &str, match_extension: &str, new_extension
 /// [module_line]: https://chromium.googlesource.com/breakpad/breakpad/+/master/docs/symbol_files.md#MODULE-records
 /// [packagesymbols]: https://gist.github.com/luser/2ad32d290f224782fcfc#file-packagesymbols-py
 pub fn relative_symbol_path(module: &dyn Module, extension: &str) -> Option<String> {
-    module.debug_file().and_then(|debug_file| {
-        module.debug_identifier().map(|debug_id| {
-            // Can't use PathBuf::file_name here, it doesn't handle
-            // Windows file paths on non-Windows.
-            let leaf = leafname(&debug_file);
-            let filename = replace_or_add_extension(leaf, "pdb", extension);
-            [leaf, &debug_id[..], &filename[..]].join("/")
-        })
-    })
+    let debug_id = module.debug_identifier()?;
+    // Can't use PathBuf::file_name here, it doesn't handle
+    // Windows file paths on non-Windows.
+    let leaf = match module.debug_file() {
+        Some(debug_file) if !debug_file.is_empty() => basename(&debug_file).to_string(),
+        _ => {
+            let code_file = module.code_file();
+            let leaf = basename(&code_file);
+            if leaf.is_empty() {
+                return None;
+            }
+            pdb_file_name(leaf).unwrap_or_else(|| leaf.to_string())
+        }
+    };
+    let filename = replace_or_add_extension(&leaf, "pdb", extension);
+    Some([&leaf[..], &debug_id[..], &filename[..]].join("/"))
+}
+
+/// Get a relative symbol path at which to locate symbols for `module` by its
+/// code file and code identifier.
+///
+/// This is the secondary layout symbol servers provide for binaries that
+/// have no PDB information:
+/// `<code filename>/<code identifier>/<code filename>.sym`, with a *.dll* or
+/// *.exe* extension swapped for `extension`. For Windows modules the code
+/// identifier is the module's timestamp and image size hex-concatenated,
+/// e.g. `kernel32.dll/59B0D8F3e1000/kernel32.sym`.
+pub fn code_info_symbol_path(module: &dyn Module, extension: &str) -> Option<String> {
+    let code_id = module.code_identifier();
+    if !is_usable_identifier(&code_id) {
+        return None;
+    }
+    let code_file = module.code_file();
+    let leaf = basename(&code_file);
+    if leaf.is_empty() {
+        return None;
+    }
+    let filename = ["dll", "exe"]
+        .iter()
+        .find(|ext| leaf.to_lowercase().ends_with(&format!(".{}", ext)))
+        .map_or_else(
+            || format!("{}.{}", leaf, extension),
+            |ext| replace_or_add_extension(leaf, ext, extension),
+        );
+    Some([leaf, &code_id[..], &filename[..]].join("/"))
+}
+
+/// Whether `id` could actually identify a module (it's not empty or all zeroes).
+fn is_usable_identifier(id: &str) -> bool {
+    !id.is_empty() && !id.chars().all(|c| c == '0')
+}
+
+/// Get the relative symbol path to search for `module`'s symbols at.
+///
+/// This is [`relative_symbol_path`], falling back to [`code_info_symbol_path`]
+/// if the module doesn't have a usable debug identifier.
+fn module_symbol_path(module: &dyn Module, extension: &str) -> Option<String> {
+    match module.debug_identifier() {
+        Some(debug_id) if is_usable_identifier(&debug_id) => {
+            relative_symbol_path(module, extension)
+        }
+        _ => code_info_symbol_path(module, extension),
+    }
 }
 
 /// Possible results of locating symbols for a module.
@@ -210,6 +333,11 @@ pub enum SymbolError {
     /// else). But sometimes we can't make any sense of the symbol file, and
     /// you find yourself here.
     ParseError(Error),
+    /// Acquiring the symbol file took longer than the supplier was willing to wait.
+    ///
+    /// This is treated like [`SymbolError::NotFound`][] when processing, but is
+    /// reported separately in [`SymbolStats`][].
+    Timeout,
 }
 
 /// An error produced by fill_symbol.
@@ -236,6 +364,7 @@ impl PartialEq for SymbolError {
             (SymbolError::NotFound, SymbolError::NotFound)
                 | (SymbolError::LoadError(_), SymbolError::LoadError(_))
                 | (SymbolError::ParseError(_), SymbolError::ParseError(_))
+                | (SymbolError::Timeout, SymbolError::Timeout)
         )
     }
 }
@@ -246,6 +375,7 @@ impl fmt::Display for SymbolError {
             SymbolError::NotFound => write!(f, "Not found"),
             SymbolError::LoadError(e) => write!(f, "Load error: {}", e),
             SymbolError::ParseError(e) => write!(f, "Parse error: {}", e),
+            SymbolError::Timeout => write!(f, "Timed out"),
         }
     }
 }
@@ -257,12 +387,24 @@ pub trait SymbolSupplier {
     /// Implementations may use any strategy for locating and loading
     /// symbols.
     fn locate_symbols(&self, module: &dyn Module) -> Result<SymbolFile, SymbolError>;
+
+    /// Override how long `locate_symbols` may spend on a single module.
+    ///
+    /// `None` removes the override, going back to the supplier's own deadline.
+    /// Suppliers that don't have a deadline ignore this.
+    fn set_symbol_timeout(&self, _timeout: Option<Duration>) {}
+
+    /// The override set by [`SymbolSupplier::set_symbol_timeout`][], if any.
+    fn symbol_timeout(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// An implementation of `SymbolSupplier` that loads Breakpad text-format symbols from local disk
 /// paths.
 ///
-/// See [`relative_symbol_path`] for details on how paths are searched.
+/// See [`relative_symbol_path`] for details on how paths are searched, and
+/// [`code_info_symbol_path`] for modules without a usable debug identifier.
 ///
 /// [`relative_symbol_path`]: fn.relative_symbol_path.html
 pub struct SimpleSymbolSupplier {
@@ -279,7 +421,7 @@ impl SimpleSymbolSupplier {
 
 impl SymbolSupplier for SimpleSymbolSupplier {
     fn locate_symbols(&self, module: &dyn Module) -> Result<SymbolFile, SymbolError> {
-        if let Some(rel_path) = relative_symbol_path(module, "sym") {
+        if let Some(rel_path) = module_symbol_path(module, "sym") {
             for path in self.paths.iter() {
                 let test_path = path.join(&rel_path);
                 if fs::metadata(&test_path).ok().map_or(false, |m| m.is_file()) {
@@ -318,12 +460,17 @@ impl SymbolSupplier for StringSymbolSupplier {
 /// An implementation of `SymbolSupplier` that loads Breakpad text-format symbols from HTTP
 /// URLs.
 ///
-/// See [`relative_symbol_path`] for details on how paths are searched.
+/// See [`relative_symbol_path`] for details on how paths are searched, and
+/// [`code_info_symbol_path`] for modules without a usable debug identifier.
 ///
 /// [`relative_symbol_path`]: fn.relative_symbol_path.html
 pub struct HttpSymbolSupplier {
     /// HTTP Client to use for fetching symbols.
     client: Client,
+    /// How long to spend acquiring the symbols of a single module.
+    timeout: Duration,
+    /// Overrides `timeout`, see [`SymbolSupplier::set_symbol_timeout`][].
+    timeout_override: Mutex<Option<Duration>>,
     /// URLs to search for symbols.
     urls: Vec<Url>,
     /// A `SimpleSymbolSupplier` to use for local symbol paths.
@@ -341,6 +488,54 @@ pub struct HttpSymbolSupplier {
     /// We recommend using `std::env::temp_dir()`, as this will be your OS's
     /// intended location for temporary files.
     tmp: PathBuf,
+    /// How to retry requests that failed in a way that might be transient.
+    retry: RetryPolicy,
+}
+
+/// How [`HttpSymbolSupplier`] retries requests that fail in a way that might
+/// be transient (a 5xx status, or a connection failure).
+///
+/// Other failures (e.g. a 404) are never retried, and retries never extend
+/// past the supplier's per-module deadline.
+///
+/// The default policy makes a single attempt, i.e. it never retries.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// The total number of attempts to make at each url, including the first.
+    pub attempts: u32,
+    /// How long to wait before the first retry. Each subsequent retry waits
+    /// twice as long as the previous one.
+    pub initial_backoff: Duration,
+    /// The longest to wait before any retry.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 1,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retrying after `attempt` (1-based) failed.
+    ///
+    /// This is the exponential backoff with jitter applied: a random duration
+    /// between half and all of it, so concurrent processors don't all retry
+    /// in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .checked_mul(1 << (attempt - 1).min(31))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        let half = backoff / 2;
+        let jitter = RandomState::new().build_hasher().finish() % (half.as_nanos() as u64 + 1);
+        half + Duration::from_nanos(jitter)
+    }
 }
 
 impl HttpSymbolSupplier {
@@ -348,6 +543,10 @@ impl HttpSymbolSupplier {
     ///
     /// Symbols will be searched for in each of `local_paths` and `cache` first, then via HTTP
     /// at each of `urls`. If a symbol file is found via HTTP it will be saved under `cache`.
+    ///
+    /// `timeout` is the deadline for acquiring the symbols of a single module,
+    /// covering connecting to and downloading from all of `urls`. If it's
+    /// exceeded, `locate_symbols` gives up with [`SymbolError::Timeout`][].
     pub fn new(
         urls: Vec<String>,
         cache: PathBuf,
@@ -355,7 +554,7 @@ impl HttpSymbolSupplier {
         mut local_paths: Vec<PathBuf>,
         timeout: Duration,
     ) -> HttpSymbolSupplier {
-        let client = Client::builder().timeout(timeout).build().unwrap();
+        let client = build_client(timeout);
         let urls = urls
             .into_iter()
             .filter_map(|mut u| {
@@ -369,12 +568,64 @@ impl HttpSymbolSupplier {
         let local = SimpleSymbolSupplier::new(local_paths);
         HttpSymbolSupplier {
             client,
+            timeout,
+            timeout_override: Mutex::new(None),
             urls,
             local,
             cache,
             tmp,
+            retry: RetryPolicy::default(),
         }
     }
+
+    /// Use `retry` to decide how to retry failed requests.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> HttpSymbolSupplier {
+        self.retry = retry;
+        self
+    }
+
+    /// The deadline for acquiring the symbols of a single module.
+    fn timeout(&self) -> Duration {
+        self.timeout_override
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .unwrap_or(self.timeout)
+    }
+}
+
+fn build_client(timeout: Duration) -> Client {
+    Client::builder().timeout(timeout).build().unwrap()
+}
+
+/// A `Read` adaptor for a response body that fails with `TimedOut` once
+/// `deadline` has passed.
+///
+/// reqwest reports its own body timeouts as opaque io errors, so those are
+/// also converted to `TimedOut`, letting the parser tell them apart from
+/// other failures.
+struct DeadlineReader<R> {
+    inner: R,
+    deadline: Instant,
+}
+
+impl<R: Read> Read for DeadlineReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let timed_out =
+            || io::Error::new(io::ErrorKind::TimedOut, "symbol download deadline exceeded");
+        if Instant::now() >= self.deadline {
+            return Err(timed_out());
+        }
+        self.inner.read(buf).map_err(|e| {
+            let inner = e
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<reqwest::Error>());
+            if matches!(inner, Some(inner) if inner.is_timeout()) {
+                timed_out()
+            } else {
+                e
+            }
+        })
+    }
 }
 
 fn create_cache_file(tmp_path: &Path, final_path: &Path) -> io::Result<NamedTempFile> {
@@ -416,14 +667,56 @@ fn commit_cache_file(mut temp: NamedTempFile, final_path: &Path, url: &Url) -> i
     Ok(())
 }
 
+/// GET `url`, retrying according to `retry` if it fails in a way that might be transient.
+fn get_with_retries(
+    client: &Client,
+    url: &Url,
+    rel_path: &str,
+    deadline: Instant,
+    retry: &RetryPolicy,
+) -> Result<Response, SymbolError> {
+    let mut attempt = 1;
+    loop {
+        let remaining = deadline
+            .checked_duration_since(Instant::now())
+            .ok_or(SymbolError::Timeout)?;
+        let reason = match client.get(url.clone()).timeout(remaining).send() {
+            Ok(res) if res.status().is_success() => return Ok(res),
+            Ok(res) if res.status().is_server_error() => format!("status {}", res.status()),
+            Ok(_) => return Err(SymbolError::NotFound),
+            Err(e) if e.is_timeout() => return Err(SymbolError::Timeout),
+            Err(e) if e.is_connect() || e.is_request() => e.to_string(),
+            Err(_) => return Err(SymbolError::NotFound),
+        };
+
+        let delay = retry.backoff(attempt);
+        if attempt >= retry.attempts || Instant::now() + delay >= deadline {
+            debug!("Giving up on {} ({})", url, reason);
+            return Err(SymbolError::NotFound);
+        }
+        // The first component of the path is the module's (debug) file name.
+        let module_name = rel_path.split('/').next().unwrap_or(rel_path);
+        warn!(
+            "Retrying symbols for {} from {} in {:?} (attempt {} of {} failed: {})",
+            module_name, url, delay, attempt, retry.attempts, reason
+        );
+        std::thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
 /// Fetch a symbol file from the URL made by combining `base_url` and `rel_path` using `client`,
 /// save the file contents under `cache` + `rel_path` and also return them.
+///
+/// Fails with [`SymbolError::Timeout`][] if this can't be done by `deadline`.
 fn fetch_symbol_file(
     client: &Client,
     base_url: &Url,
     rel_path: &str,
     cache: &Path,
     tmp: &Path,
+    deadline: Instant,
+    retry: &RetryPolicy,
 ) -> Result<SymbolFile, SymbolError> {
     // This function is a bit of a complicated mess because we want to write
     // the input to our symbol cache, but we're a streaming parser. So we
@@ -437,11 +730,11 @@ fn fetch_symbol_file(
     // First try to GET the file from a server
     let url = base_url.join(rel_path).map_err(|_| SymbolError::NotFound)?;
     debug!("Trying {}", url);
-    let res = client
-        .get(url.clone())
-        .send()
-        .and_then(|res| res.error_for_status())
-        .map_err(|_| SymbolError::NotFound)?;
+    let res = get_with_retries(client, &url, rel_path, deadline, retry)?;
+    let res = DeadlineReader {
+        inner: res,
+        deadline,
+    };
 
     // Now try to create the temp cache file (not yet in the cache)
     let final_cache_path = cache.join(rel_path);
@@ -464,6 +757,7 @@ fn fetch_symbol_file(
     })?;
     // Make note of what URL this symbol file was downloaded from.
     symbol_file.url = Some(url.to_string());
+    symbol_file.origin = Some(SymbolOrigin::Url(url.to_string()));
 
     // Try to finish the cache file and atomically swap it into the cache.
     if let Some(temp) = temp {
@@ -484,18 +778,45 @@ impl SymbolSupplier for HttpSymbolSupplier {
             return local_result;
         }
         // Now try urls
-        if let Some(rel_path) = relative_symbol_path(module, "sym") {
+        if let Some(rel_path) = module_symbol_path(module, "sym") {
+            let deadline = Instant::now() + self.timeout();
             for url in &self.urls {
-                if let Ok(file) =
-                    fetch_symbol_file(&self.client, url, &rel_path, &self.cache, &self.tmp)
-                {
-                    return Ok(file);
+                match fetch_symbol_file(
+                    &self.client,
+                    url,
+                    &rel_path,
+                    &self.cache,
+                    &self.tmp,
+                    deadline,
+                    &self.retry,
+                ) {
+                    Ok(file) => return Ok(file),
+                    // The deadline covers all the urls, so there's no point trying the rest.
+                    Err(SymbolError::Timeout) => {
+                        warn!("Timed out fetching symbols for {}", rel_path);
+                        return Err(SymbolError::Timeout);
+                    }
+                    Err(_) => {}
                 }
             }
         }
         // If we get this far, we have failed to find anything
         Err(SymbolError::NotFound)
     }
+
+    fn set_symbol_timeout(&self, timeout: Option<Duration>) {
+        *self
+            .timeout_override
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = timeout;
+    }
+
+    fn symbol_timeout(&self) -> Option<Duration> {
+        *self
+            .timeout_override
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
 }
 
 /// A trait for setting symbol information on something like a stack frame.
@@ -694,6 +1015,75 @@ impl Symbolizer {
             .map_err(|_| FillSymbolError {})
     }
 
+    /// Look up the symbol covering `address` in `module`, without needing a frame.
+    ///
+    /// `address` is an absolute address (like a frame's instruction), so this
+    /// can be used to symbolicate arbitrary values such as pointers found in
+    /// registers or memory, or addresses that come from somewhere other than a
+    /// minidump entirely (profiler samples, sanitizer reports). It uses the same
+    /// supplier and cache as stackwalking, but never unwinds anything.
+    ///
+    /// Returns `None` if no symbols could be found for the module, or if no
+    /// symbol covers the address (including addresses outside of the module).
+    /// Use [`Symbolizer::symbol_error`][] afterwards to tell those apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::set_current_dir(env!("CARGO_MANIFEST_DIR"));
+    /// use breakpad_symbols::{SimpleModule, SimpleSymbolSupplier, SymbolKind, Symbolizer};
+    /// use std::path::PathBuf;
+    /// let paths = vec!(PathBuf::from("../testdata/symbols/"));
+    /// let supplier = SimpleSymbolSupplier::new(paths);
+    /// let symbolizer = Symbolizer::new(supplier);
+    /// let m = SimpleModule::new("test_app.pdb", "5A9832E5287241C1838ED98914E9B7FF1");
+    /// let result = symbolizer.lookup(&m, 0x1010).unwrap();
+    /// assert_eq!(result.function, "vswprintf");
+    /// assert_eq!(result.source_line, Some(51));
+    /// assert_eq!(result.kind, SymbolKind::Func);
+    /// ```
+    #[doc(alias = "symbolize_address")]
+    pub fn lookup(&self, module: &dyn Module, address: u64) -> Option<SymbolResult> {
+        let k = key(module);
+        self.ensure_module(module, &k);
+        match self.symbols.borrow().get(&k) {
+            Some(Ok(sym)) => sym.lookup(module, address),
+            _ => None,
+        }
+    }
+
+    /// Get the error from acquiring `module`'s symbols, if it failed.
+    ///
+    /// This only reports on lookups that have already happened (e.g. by
+    /// [`Symbolizer::fill_symbol`][]), so it returns `None` if `module` was
+    /// never looked up. This is useful to tell apart modules that have no
+    /// symbols from ones whose symbols couldn't be acquired in time.
+    pub fn symbol_error(&self, module: &dyn Module) -> Option<SymbolError> {
+        match self.symbols.borrow().get(&key(module))? {
+            Ok(_) => None,
+            Err(SymbolError::NotFound) => Some(SymbolError::NotFound),
+            Err(SymbolError::LoadError(e)) => Some(SymbolError::LoadError(err_msg(e.to_string()))),
+            Err(SymbolError::ParseError(e)) => {
+                Some(SymbolError::ParseError(err_msg(e.to_string())))
+            }
+            Err(SymbolError::Timeout) => Some(SymbolError::Timeout),
+        }
+    }
+
+    /// Override how long the supplier may spend acquiring a single module's symbols.
+    ///
+    /// See [`SymbolSupplier::set_symbol_timeout`][].
+    pub fn set_symbol_timeout(&self, timeout: Option<Duration>) {
+        self.supplier.set_symbol_timeout(timeout);
+    }
+
+    /// The supplier's current symbol timeout override, if any.
+    ///
+    /// See [`SymbolSupplier::symbol_timeout`][].
+    pub fn symbol_timeout(&self) -> Option<Duration> {
+        self.supplier.symbol_timeout()
+    }
+
     /// Collect various statistics on the symbols.
     ///
     /// Keys are the file name of the module (code_file's file name).
@@ -706,6 +1096,7 @@ impl Symbolizer {
                 match res {
                     Ok(sym) => {
                         stats.symbol_url = sym.url.clone();
+                        stats.symbol_origin = sym.origin.clone();
                         stats.loaded_symbols = true;
                         stats.corrupt_symbols = false;
                     }
@@ -719,10 +1110,23 @@ impl Symbolizer {
                         stats.loaded_symbols = true;
                         stats.corrupt_symbols = true;
                     }
+                    Err(SymbolError::Timeout) => {
+                        stats.loaded_symbols = false;
+                        stats.timed_out = true;
+                    }
                 }
-                (leafname(&k.0).to_string(), stats)
+                (basename(&k.0).to_string(), stats)
+            })
+            .fold(HashMap::new(), |mut all_stats, (name, stats)| {
+                // The same module may have been looked up under several names
+                // (e.g. by a case-insensitive retry), so prefer whichever
+                // lookup actually loaded symbols.
+                let prev_loaded = matches!(all_stats.get(&name), Some(s) if s.loaded_symbols);
+                if !prev_loaded || stats.loaded_symbols {
+                    all_stats.insert(name, stats);
+                }
+                all_stats
             })
-            .collect()
     }
 
     /// Tries to use CFI to walk the stack frame of the FrameWalker
@@ -746,18 +1150,26 @@ impl Symbolizer {
     fn ensure_module(&self, module: &dyn Module, k: &ModuleKey) {
         if !self.symbols.borrow().contains_key(k) {
             let res = self.supplier.locate_symbols(module);
+            match &res {
+                Ok(SymbolFile {
+                    origin: Some(origin),
+                    ..
+                }) => debug!("Loaded symbols for {} from {}", k.0, origin),
+                Ok(_) => debug!("Loaded symbols for {}", k.0),
+                Err(e) => debug!("Couldn't load symbols for {}: {}", k.0, e),
+            }
             self.symbols.borrow_mut().insert(k.clone(), res);
         }
     }
 }
 
 #[test]
-fn test_leafname() {
-    assert_eq!(leafname("c:\\foo\\bar\\test.pdb"), "test.pdb");
-    assert_eq!(leafname("c:/foo/bar/test.pdb"), "test.pdb");
-    assert_eq!(leafname("test.pdb"), "test.pdb");
-    assert_eq!(leafname("test"), "test");
-    assert_eq!(leafname("/path/to/test"), "test");
+fn test_basename() {
+    assert_eq!(basename("c:\\foo\\bar\\test.pdb"), "test.pdb");
+    assert_eq!(basename("c:/foo/bar/test.pdb"), "test.pdb");
+    assert_eq!(basename("test.pdb"), "test.pdb");
+    assert_eq!(basename("test"), "test");
+    assert_eq!(basename("/path/to/test"), "test");
 }
 
 #[test]
@@ -831,6 +1243,52 @@ mod test {
         assert!(relative_symbol_path(&bad3, "sym").is_none());
     }
 
+    #[test]
+    fn test_relative_symbol_path_code_file_fallback() {
+        let m = SimpleModule {
+            code_file: Some("C:\\Program Files\\Mozilla\\xul.dll".to_string()),
+            debug_id: Some("ABCD1234".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &relative_symbol_path(&m, "sym").unwrap(),
+            "xul.pdb/ABCD1234/xul.sym"
+        );
+
+        let m2 = SimpleModule {
+            code_file: Some("C:\\Windows\\System32\\KERNEL32.DLL".to_string()),
+            debug_file: Some("".to_string()),
+            debug_id: Some("ABCD1234".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &relative_symbol_path(&m2, "sym").unwrap(),
+            "KERNEL32.pdb/ABCD1234/KERNEL32.sym"
+        );
+
+        let m3 = SimpleModule {
+            code_file: Some("/usr/lib/libfoo.so".to_string()),
+            debug_id: Some("ABCD1234".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &relative_symbol_path(&m3, "sym").unwrap(),
+            "libfoo.so/ABCD1234/libfoo.so.sym"
+        );
+
+        // The debug file still takes precedence.
+        let m4 = SimpleModule {
+            code_file: Some("C:\\Windows\\System32\\KERNEL32.DLL".to_string()),
+            debug_file: Some("kernel32.pdb".to_string()),
+            debug_id: Some("ABCD1234".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &relative_symbol_path(&m4, "sym").unwrap(),
+            "kernel32.pdb/ABCD1234/kernel32.sym"
+        );
+    }
+
     #[test]
     fn test_relative_symbol_path_abs_paths() {
         {
@@ -858,6 +1316,58 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_code_info_symbol_path() {
+        let m = SimpleModule {
+            code_file: Some("C:\\Windows\\System32\\kernel32.dll".to_string()),
+            code_identifier: Some("59B0D8F3e1000".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &code_info_symbol_path(&m, "sym").unwrap(),
+            "kernel32.dll/59B0D8F3e1000/kernel32.sym"
+        );
+
+        let m2 = SimpleModule {
+            code_file: Some("C:\\Program Files\\Mozilla\\firefox.EXE".to_string()),
+            code_identifier: Some("59B0D8F3e1000".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &code_info_symbol_path(&m2, "sym").unwrap(),
+            "firefox.EXE/59B0D8F3e1000/firefox.sym"
+        );
+
+        let m3 = SimpleModule {
+            code_file: Some("C:\\Windows\\System32\\foo.sys".to_string()),
+            code_identifier: Some("59B0D8F3e1000".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(
+            &code_info_symbol_path(&m3, "sym").unwrap(),
+            "foo.sys/59B0D8F3e1000/foo.sys.sym"
+        );
+
+        let bad = SimpleModule {
+            code_file: Some("kernel32.dll".to_string()),
+            ..SimpleModule::default()
+        };
+        assert!(code_info_symbol_path(&bad, "sym").is_none());
+
+        let bad2 = SimpleModule {
+            code_file: Some("kernel32.dll".to_string()),
+            code_identifier: Some("000000000".to_string()),
+            ..SimpleModule::default()
+        };
+        assert!(code_info_symbol_path(&bad2, "sym").is_none());
+
+        let bad3 = SimpleModule {
+            code_identifier: Some("59B0D8F3e1000".to_string()),
+            ..SimpleModule::default()
+        };
+        assert!(code_info_symbol_path(&bad3, "sym").is_none());
+    }
+
     fn mksubdirs(path: &Path, dirs: &[&str]) -> Vec<PathBuf> {
         dirs.iter()
             .map(|dir| {
@@ -927,6 +1437,45 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_simple_symbol_supplier_code_info_fallback() {
+        let t = TempDir::new("symtest").unwrap();
+        let paths = mksubdirs(t.path(), &["one"]);
+        let supplier = SimpleSymbolSupplier::new(paths.clone());
+
+        // No debug id at all, or a useless one.
+        for debug_id in [None, Some(""), Some("000000000000000000000000000000000")] {
+            let m = SimpleModule {
+                code_file: Some("C:\\Windows\\System32\\kernel32.dll".to_string()),
+                code_identifier: Some("59B0D8F3e1000".to_string()),
+                debug_id: debug_id.map(String::from),
+                ..SimpleModule::default()
+            };
+            assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::NotFound));
+        }
+
+        write_good_symbol_file(&paths[0].join("kernel32.dll/59B0D8F3e1000/kernel32.sym"));
+        for debug_id in [None, Some(""), Some("000000000000000000000000000000000")] {
+            let m = SimpleModule {
+                code_file: Some("C:\\Windows\\System32\\kernel32.dll".to_string()),
+                code_identifier: Some("59B0D8F3e1000".to_string()),
+                debug_id: debug_id.map(String::from),
+                ..SimpleModule::default()
+            };
+            assert!(supplier.locate_symbols(&m).is_ok());
+        }
+
+        // A usable debug id doesn't fall back.
+        let m = SimpleModule {
+            code_file: Some("C:\\Windows\\System32\\kernel32.dll".to_string()),
+            code_identifier: Some("59B0D8F3e1000".to_string()),
+            debug_file: Some("kernel32.pdb".to_string()),
+            debug_id: Some("ABCD1234".to_string()),
+            ..SimpleModule::default()
+        };
+        assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::NotFound));
+    }
+
     #[test]
     fn test_symbolizer() {
         let t = TempDir::new("symtest").unwrap();
@@ -986,4 +1535,349 @@ FUNC 1000 30 10 another func
             .get_symbol_at_address("bar.pdb", "ffff0000", 0x1010)
             .is_none());
     }
+
+    #[test]
+    fn test_symbolizer_lookup() {
+        let t = TempDir::new("symtest").unwrap();
+        let path = t.path();
+
+        let supplier = SimpleSymbolSupplier::new(vec![PathBuf::from(path)]);
+        let symbolizer = Symbolizer::new(supplier);
+        write_symbol_file(
+            &path.join("foo.pdb/abcd1234/foo.sym"),
+            b"MODULE Linux x86 abcd1234 foo
+FILE 1 foo.c
+FUNC 1000 30 10 some func
+1000 10 100 1
+1010 20 101 1
+FUNC 1040 10 0 no lines
+PUBLIC 1030 0 public func
+",
+        );
+        let m = SimpleModule {
+            base_address: Some(0x10000),
+            size: Some(0x2000),
+            ..SimpleModule::new("foo.pdb", "abcd1234")
+        };
+
+        // Inside a FUNC
+        assert_eq!(
+            symbolizer.lookup(&m, 0x11014),
+            Some(SymbolResult {
+                function: String::from("some func"),
+                function_base: 0x11000,
+                source_file: Some(String::from("foo.c")),
+                source_line: Some(101),
+                kind: SymbolKind::Func,
+            })
+        );
+        assert_eq!(
+            symbolizer.lookup(&m, 0x11048),
+            Some(SymbolResult {
+                function: String::from("no lines"),
+                function_base: 0x11040,
+                source_file: None,
+                source_line: None,
+                kind: SymbolKind::Func,
+            })
+        );
+
+        // Between FUNCs, falls back to the PUBLIC
+        assert_eq!(
+            symbolizer.lookup(&m, 0x11034),
+            Some(SymbolResult {
+                function: String::from("public func"),
+                function_base: 0x11030,
+                source_file: None,
+                source_line: None,
+                kind: SymbolKind::Public,
+            })
+        );
+
+        // Before any symbol, or outside the module
+        assert_eq!(symbolizer.lookup(&m, 0x10500), None);
+        assert_eq!(symbolizer.lookup(&m, 0x1010), None);
+        assert_eq!(symbolizer.lookup(&m, 0x12000), None);
+
+        // Agrees with fill_symbol
+        let mut f = SimpleFrame::with_instruction(0x11034);
+        symbolizer.fill_symbol(&m, &mut f).unwrap();
+        assert_eq!(f.function.unwrap(), "public func");
+        assert_eq!(f.function_base.unwrap(), 0x11030);
+
+        // No symbols
+        let m2 = SimpleModule::new("bar.pdb", "ffff0000");
+        assert_eq!(symbolizer.lookup(&m2, 0x1010), None);
+        assert_eq!(symbolizer.symbol_error(&m2), Some(SymbolError::NotFound));
+        assert_eq!(symbolizer.symbol_error(&m), None);
+    }
+
+    /// Start an HTTP "server" that accepts connections and then calls `respond`
+    /// on each of them, returning its base url.
+    fn local_server<F>(respond: F) -> String
+    where
+        F: Fn(std::net::TcpStream) + Send + Sync + 'static,
+    {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let respond = std::sync::Arc::new(respond);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let respond = respond.clone();
+                std::thread::spawn(move || respond(stream));
+            }
+        });
+        url
+    }
+
+    fn timeout_supplier(url: String, timeout: Duration) -> (TempDir, HttpSymbolSupplier) {
+        let t = TempDir::new("symtest").unwrap();
+        let cache = t.path().join("cache");
+        let tmp = t.path().join("tmp");
+        fs::create_dir_all(&tmp).unwrap();
+        let supplier = HttpSymbolSupplier::new(vec![url], cache, tmp, vec![], timeout);
+        (t, supplier)
+    }
+
+    #[test]
+    fn test_http_supplier_timeout_stalled() {
+        // A server that never responds at all.
+        let url = local_server(|stream| {
+            std::thread::sleep(Duration::from_secs(60));
+            drop(stream);
+        });
+        let (_t, supplier) = timeout_supplier(url, Duration::from_millis(200));
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        let start = Instant::now();
+        assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::Timeout));
+        assert!(start.elapsed() < Duration::from_secs(30));
+
+        // Timeouts are reported in the stats, and otherwise look like missing symbols.
+        let symbolizer = Symbolizer::new(supplier);
+        symbolizer.set_symbol_timeout(Some(Duration::from_millis(100)));
+        let mut f = SimpleFrame::with_instruction(0x1010);
+        assert!(symbolizer.fill_symbol(&m, &mut f).is_err());
+        let stats = symbolizer.stats();
+        let stats = stats.values().next().unwrap();
+        assert!(stats.timed_out);
+        assert!(!stats.loaded_symbols);
+        assert!(!stats.corrupt_symbols);
+    }
+
+    #[test]
+    fn test_http_supplier_timeout_override() {
+        let (_t, supplier) =
+            timeout_supplier("http://127.0.0.1:1/".to_string(), Duration::from_secs(30));
+        assert_eq!(supplier.timeout(), Duration::from_secs(30));
+        assert_eq!(supplier.symbol_timeout(), None);
+        supplier.set_symbol_timeout(Some(Duration::from_secs(1)));
+        assert_eq!(supplier.timeout(), Duration::from_secs(1));
+        assert_eq!(supplier.symbol_timeout(), Some(Duration::from_secs(1)));
+        // Removing the override goes back to the constructor's deadline.
+        supplier.set_symbol_timeout(None);
+        assert_eq!(supplier.timeout(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_http_supplier_timeout_trickle() {
+        // A server that responds promptly, but then sends the body so slowly
+        // that no individual read times out.
+        let url = local_server(|mut stream| {
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100000\r\n\r\n");
+            let _ = stream.write_all(b"MODULE Linux x86 abcd1234 foo\n");
+            for _ in 0..1000 {
+                std::thread::sleep(Duration::from_millis(50));
+                if stream.write_all(b"INFO still going\n").is_err() {
+                    return;
+                }
+            }
+        });
+        let (_t, supplier) = timeout_supplier(url, Duration::from_secs(1));
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        let start = Instant::now();
+        assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::Timeout));
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    /// A server that responds with each of `statuses` in turn (repeating the
+    /// last one), serving a valid symbol file for 200s. Also returns a count
+    /// of the requests made.
+    fn status_server(
+        statuses: &'static [u32],
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = local_server(move |mut stream| {
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            let status = statuses[n.min(statuses.len() - 1)];
+            let body: &[u8] = if status == 200 {
+                b"MODULE Linux x86 abcd1234 foo\n"
+            } else {
+                b""
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} Whatever\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            let _ = stream.write_all(body);
+        });
+        (url, requests)
+    }
+
+    fn quick_retries(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn test_http_supplier_retries_server_errors() {
+        use std::sync::atomic::Ordering;
+        let (url, requests) = status_server(&[502, 503, 200]);
+        let (_t, supplier) = timeout_supplier(url, Duration::from_secs(30));
+        let supplier = supplier.with_retry_policy(quick_retries(3));
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        assert!(supplier.locate_symbols(&m).is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_http_supplier_retries_run_out() {
+        use std::sync::atomic::Ordering;
+        let (url, requests) = status_server(&[502, 502, 200]);
+        let (_t, supplier) = timeout_supplier(url, Duration::from_secs(30));
+        let supplier = supplier.with_retry_policy(quick_retries(2));
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::NotFound));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_http_supplier_no_retry_client_errors() {
+        use std::sync::atomic::Ordering;
+        for status in [&[404, 200][..], &[403, 200][..]] {
+            let (url, requests) = status_server(status);
+            let (_t, supplier) = timeout_supplier(url, Duration::from_secs(30));
+            let supplier = supplier.with_retry_policy(quick_retries(3));
+            let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+            assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::NotFound));
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+        }
+    }
+
+    #[test]
+    fn test_http_supplier_default_no_retries() {
+        use std::sync::atomic::Ordering;
+        let (url, requests) = status_server(&[502, 200]);
+        let (_t, supplier) = timeout_supplier(url, Duration::from_secs(30));
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        assert_eq!(supplier.locate_symbols(&m), Err(SymbolError::NotFound));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_symbol_origin() {
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        // Downloaded symbols come from a Url, and then the cache.
+        let (url, _requests) = status_server(&[200]);
+        let (t, supplier) = timeout_supplier(url.clone(), Duration::from_secs(30));
+        let sym_url = format!("{}foo.pdb/abcd1234/foo.sym", url);
+        let downloaded = supplier.locate_symbols(&m).unwrap();
+        assert_eq!(downloaded.origin, Some(SymbolOrigin::Url(sym_url.clone())));
+
+        let cached = supplier.locate_symbols(&m).unwrap();
+        let cache_path = t.path().join("cache").join("foo.pdb/abcd1234/foo.sym");
+        assert_eq!(cached.origin, Some(SymbolOrigin::Path(cache_path.clone())));
+        assert_eq!(cached.url, Some(sym_url.clone()));
+
+        let symbolizer = Symbolizer::new(supplier);
+        symbolizer.lookup(&m, 0x1000);
+        let stats = symbolizer.stats();
+        let stats = stats.values().next().unwrap();
+        assert_eq!(stats.symbol_origin, Some(SymbolOrigin::Path(cache_path)));
+        assert_eq!(stats.symbol_url, Some(sym_url));
+
+        // In-memory symbols are easy to recognize.
+        let mut modules = HashMap::new();
+        modules.insert(
+            String::from("foo.pdb"),
+            String::from("MODULE Linux x86 abcd1234 foo\n"),
+        );
+        let symbolizer = Symbolizer::new(StringSymbolSupplier::new(modules));
+        let m = SimpleModule {
+            code_file: Some(String::from("foo.pdb")),
+            ..SimpleModule::default()
+        };
+        symbolizer.lookup(&m, 0x1000);
+        let stats = symbolizer.stats();
+        let stats = stats.values().next().unwrap();
+        assert_eq!(stats.symbol_origin, Some(SymbolOrigin::Memory));
+        assert_eq!(stats.symbol_url, None);
+        assert_eq!(SymbolOrigin::Memory.to_string(), "<memory>");
+    }
+
+    #[test]
+    fn test_http_supplier_retries_dropped_connections() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // Hang up without responding the first time.
+        let requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = local_server(move |mut stream| {
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                return;
+            }
+            let body = b"MODULE Linux x86 abcd1234 foo\n";
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(body);
+        });
+        let (_t, supplier) = timeout_supplier(url, Duration::from_secs(30));
+        let supplier = supplier.with_retry_policy(quick_retries(3));
+        let m = SimpleModule::new("foo.pdb", "abcd1234");
+
+        assert!(supplier.locate_symbols(&m).is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = RetryPolicy {
+            attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+        for (attempt, full) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (40, 1000),
+        ] {
+            let full = Duration::from_millis(full);
+            let backoff = retry.backoff(attempt);
+            assert!(backoff >= full / 2 && backoff <= full, "{:?}", backoff);
+        }
+    }
 }
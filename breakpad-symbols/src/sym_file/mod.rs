@@ -1,19 +1,49 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
-use crate::{FrameSymbolizer, FrameWalker, Module, SymbolError};
+use crate::{
+    FrameSymbolizer, FrameWalker, Module, SymbolEntry, SymbolError, SymbolKind, SymbolOrigin,
+    SymbolResult,
+};
+use range_map::{Range, RangeMap};
+use std::collections::HashMap;
+use std::fmt;
 
 pub use crate::sym_file::types::*;
 pub use parser::SymbolParser;
+use parser::{read_error, SymbolRecords};
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, BufRead, Read, Write};
 use std::path::Path;
 
 use failure::format_err;
+use log::debug;
 
 mod parser;
 mod types;
 pub mod walker;
 
+impl Default for SymbolFile {
+    /// An empty SymbolFile, for building one up record by record.
+    fn default() -> Self {
+        SymbolFile {
+            module: None,
+            files: HashMap::new(),
+            publics: Vec::new(),
+            functions: RangeMap::new(),
+            cfi_stack_info: RangeMap::new(),
+            win_stack_framedata_info: RangeMap::new(),
+            win_stack_fpo_info: RangeMap::new(),
+            url: None,
+            origin: None,
+            ambiguities_repaired: 0,
+            ambiguities_discarded: 0,
+            corruptions_discarded: 0,
+            cfi_eval_corruptions: 0,
+            name_index: Default::default(),
+        }
+    }
+}
+
 impl SymbolFile {
     /// Parse a SymbolFile from the given Reader.
     ///
@@ -62,9 +92,7 @@ impl SymbolFile {
         let mut fully_consumed = false;
         loop {
             // Read the data in, and tell the circular buffer about the new data
-            let size = input_reader.read(buf.space()).map_err(|e| {
-                SymbolError::LoadError(format_err!("couldn't read input stream {}", e))
-            })?;
+            let size = input_reader.read(buf.space()).map_err(read_error)?;
             buf.fill(size);
 
             // If the reader returned nothing, then we're done. On the previous
@@ -72,7 +100,9 @@ impl SymbolFile {
             // consumed all of those bytes, then the file perfectly parsed!
             if size == 0 {
                 if fully_consumed {
-                    return Ok(parser.finish());
+                    let symbol_file = parser.finish();
+                    debug!("parsed symbol file: {:?}", symbol_file.stats());
+                    return Ok(symbol_file);
                 } else {
                     return Err(SymbolError::ParseError(format_err!(
                         "unexpected EOF during parsing of SymbolFile (or a line was too long?) at line {}", 
@@ -94,108 +124,379 @@ impl SymbolFile {
         }
     }
 
+    /// Parse the records of a symbol file one at a time, without keeping them.
+    ///
+    /// This is for symbol files too big to keep all of, where only some
+    /// records are needed, like the `STACK CFI` of an address range. Records
+    /// are yielded in the order they're in the file, with their sublines
+    /// (see [`SymbolRecord`][]), and the first error ends the iteration.
+    ///
+    /// [`SymbolFile::parse`][] goes through the same parser and keeps every
+    /// record, merging overlapping `STACK WIN` records as it does. This
+    /// doesn't do that, or drop records with invalid address ranges.
+    ///
+    /// ```
+    /// use breakpad_symbols::{SymbolFile, SymbolRecord};
+    ///
+    /// let sym = b"MODULE Linux x86_64 ABCD1234 foo\n\
+    ///             FUNC 1000 30 0 main\n\
+    ///             STACK CFI INIT 1000 30 .cfa: $rsp 8 + .ra: .cfa -8 + ^\n";
+    /// let cfi = SymbolFile::parse_streaming(&sym[..])
+    ///     .filter_map(|record| match record {
+    ///         Ok(SymbolRecord::StackCfi(cfi)) => Some(cfi),
+    ///         _ => None,
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(cfi.len(), 1);
+    /// assert_eq!(cfi[0].init.address, 0x1000);
+    /// ```
+    pub fn parse_streaming<R: BufRead>(
+        reader: R,
+    ) -> impl Iterator<Item = Result<SymbolRecord, SymbolError>> {
+        SymbolRecords::new(reader)
+    }
+
     // Parse a SymbolFile from bytes.
     pub fn from_bytes(bytes: &[u8]) -> Result<SymbolFile, SymbolError> {
-        Self::parse(bytes, |_| ())
+        let mut symbol_file = Self::parse(bytes, |_| ())?;
+        symbol_file.origin = Some(SymbolOrigin::Memory);
+        Ok(symbol_file)
     }
 
     // Parse a SymbolFile from a file.
     pub fn from_file(path: &Path) -> Result<SymbolFile, SymbolError> {
         let file = File::open(path)
             .map_err(|e| SymbolError::LoadError(format_err!("Couldn't open symbol file {}", e)))?;
-        Self::parse(file, |_| ())
+        let mut symbol_file = Self::parse(file, |_| ())?;
+        symbol_file.origin = Some(SymbolOrigin::Path(path.to_owned()));
+        Ok(symbol_file)
+    }
+
+    /// Count the records in this SymbolFile.
+    pub fn stats(&self) -> SymbolFileStats {
+        let functions = self.functions.ranges_values().map(|(_, func)| func);
+        let cfi = self.cfi_stack_info.ranges_values().map(|(_, info)| info);
+        SymbolFileStats {
+            func_count: functions.clone().count(),
+            public_count: self.publics.len(),
+            line_count: functions
+                .map(|func| func.lines.ranges_values().count())
+                .sum(),
+            file_count: self.files.len(),
+            cfi_init_count: cfi.clone().count(),
+            cfi_delta_count: cfi.map(|info| info.add_rules.len()).sum(),
+            inline_count: 0,
+        }
+    }
+
+    /// Make a copy of this SymbolFile with only the records that are needed for
+    /// addresses in `start..end` (relative to the module's load address).
+    ///
+    /// FUNC, STACK CFI and STACK WIN records are kept if they overlap the range,
+    /// along with the FILEs their lines use. PUBLIC records don't have a size, so
+    /// every one in the range is kept, and so is the one before it if it still
+    /// covers `start`. Looking up or unwinding an address in the range gives the
+    /// same result with the copy as with the original, so it's a cheap thing to
+    /// cache when only a few frames of a huge symbol file are needed.
+    pub fn trim_to_range(&self, start: u64, end: u64) -> SymbolFile {
+        let overlaps = |range: &Range<u64>| range.start < end && range.end >= start;
+        fn trim<V: Clone + Eq + fmt::Debug>(
+            map: &RangeMap<u64, V>,
+            overlaps: impl Fn(&Range<u64>) -> bool,
+        ) -> RangeMap<u64, V> {
+            RangeMap::from_sorted_vec(
+                map.ranges_values()
+                    .filter(|(range, _)| overlaps(range))
+                    .map(|(range, value)| (*range, value.clone()))
+                    .collect(),
+            )
+        }
+
+        let functions = trim(&self.functions, overlaps);
+        let files = functions
+            .ranges_values()
+            .flat_map(|(_, func)| func.lines.ranges_values())
+            .filter_map(|(_, line)| Some((line.file, self.files.get(&line.file)?.clone())))
+            .collect();
+
+        // The PUBLIC before the range covers its start, unless a FUNC ends that PUBLIC
+        // before then (see `find_symbol`).
+        let mut first = self
+            .publics
+            .partition_point(|public| public.address < start);
+        if let Some(before) = first.checked_sub(1).map(|i| &self.publics[i]) {
+            let truncated = self
+                .functions
+                .ranges_values()
+                .any(|(range, _)| range.start >= before.address && range.start < start);
+            if start < end && !truncated {
+                first -= 1;
+            }
+        }
+        let publics = self.publics[first..]
+            .iter()
+            .take_while(|public| public.address < end)
+            .cloned()
+            .collect();
+
+        SymbolFile {
+            module: self.module.clone(),
+            files,
+            publics,
+            functions,
+            cfi_stack_info: trim(&self.cfi_stack_info, overlaps),
+            win_stack_framedata_info: trim(&self.win_stack_framedata_info, overlaps),
+            win_stack_fpo_info: trim(&self.win_stack_fpo_info, overlaps),
+            url: self.url.clone(),
+            origin: self.origin.clone(),
+            ambiguities_repaired: 0,
+            ambiguities_discarded: 0,
+            corruptions_discarded: 0,
+            cfi_eval_corruptions: 0,
+            name_index: Default::default(),
+        }
+    }
+
+    /// Write this SymbolFile out in the breakpad text format.
+    ///
+    /// Records are emitted in the standard order: MODULE, INFO, FILE, PUBLIC,
+    /// FUNC with its lines, STACK WIN, then STACK CFI INIT with its STACK CFI
+    /// records. Parsing the output yields an equivalent SymbolFile.
+    ///
+    /// Note that the parser discards some of its input (unknown INFO records,
+    /// the `m` flag on FUNC/PUBLIC records, and any entries it deems
+    /// corrupt), so those won't be present in the output.
+    pub fn write(&self, mut w: impl Write) -> io::Result<()> {
+        if let Some(module) = &self.module {
+            writeln!(
+                w,
+                "MODULE {} {} {} {}",
+                module.os, module.cpu, module.debug_id, module.name
+            )?;
+        }
+        if let Some(url) = &self.url {
+            writeln!(w, "INFO URL {}", url)?;
+        }
+
+        let mut files = self.files.iter().collect::<Vec<_>>();
+        files.sort();
+        for (id, name) in files {
+            writeln!(w, "FILE {} {}", id, name)?;
+        }
+
+        for public in &self.publics {
+            writeln!(
+                w,
+                "PUBLIC {:x} {:x} {}",
+                public.address, public.parameter_size, public.name
+            )?;
+        }
+
+        for (_, func) in self.functions.ranges_values() {
+            writeln!(
+                w,
+                "FUNC {:x} {:x} {:x} {}",
+                func.address, func.size, func.parameter_size, func.name
+            )?;
+            for (_, line) in func.lines.ranges_values() {
+                writeln!(
+                    w,
+                    "{:x} {:x} {} {}",
+                    line.address, line.size, line.line, line.file
+                )?;
+            }
+        }
+
+        // The two kinds of STACK WIN records are stored separately, but
+        // interleave them by address like dump_syms does.
+        let mut win_infos = self
+            .win_stack_fpo_info
+            .ranges_values()
+            .map(|(_, info)| (0, info))
+            .chain(
+                self.win_stack_framedata_info
+                    .ranges_values()
+                    .map(|(_, info)| (4, info)),
+            )
+            .collect::<Vec<_>>();
+        win_infos.sort_by_key(|&(ty, info)| (info.address, ty));
+        for (ty, info) in win_infos {
+            write!(
+                w,
+                "STACK WIN {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} ",
+                ty,
+                info.address,
+                info.size,
+                info.prologue_size,
+                info.epilogue_size,
+                info.parameter_size,
+                info.saved_register_size,
+                info.local_size,
+                info.max_stack_size,
+            )?;
+            match &info.program_string_or_base_pointer {
+                WinStackThing::ProgramString(program) => writeln!(w, "1 {}", program)?,
+                WinStackThing::AllocatesBasePointer(allocates) => {
+                    writeln!(w, "0 {}", *allocates as u8)?
+                }
+            }
+        }
+
+        for (_, info) in self.cfi_stack_info.ranges_values() {
+            writeln!(
+                w,
+                "STACK CFI INIT {:x} {:x} {}",
+                info.init.address, info.size, info.init.rules
+            )?;
+            for rules in &info.add_rules {
+                writeln!(w, "STACK CFI {:x} {}", rules.address, rules.rules)?;
+            }
+        }
+
+        Ok(())
     }
 
     /// Fill in as much source information for `frame` as possible.
     pub fn fill_symbol(&self, module: &dyn Module, frame: &mut dyn FrameSymbolizer) {
-        // Look for a FUNC covering the address first.
         if frame.get_instruction() < module.base_address() {
             return;
         }
         let addr = frame.get_instruction() - module.base_address();
-        if let Some(func) = self.functions.get(addr) {
-            // TODO: although FUNC records have a parameter size, it appears that
-            // they aren't to be trusted? The STACK WIN records are more reliable
-            // when available. This is important precisely because these values
-            // are used to unwind subsequent STACK WIN frames (because certain
-            // calling conventions have the caller push the callee's arguments,
-            // which affects the the stack's size!).
-            //
-            // Need to spend more time thinking about if this is the right approach
-            let parameter_size = if let Some(info) = self.win_stack_framedata_info.get(addr) {
-                info.parameter_size
-            } else if let Some(info) = self.win_stack_fpo_info.get(addr) {
-                info.parameter_size
-            } else {
-                func.parameter_size
-            };
-
-            frame.set_function(
-                &func.name,
-                func.address + module.base_address(),
-                parameter_size,
-            );
-            // See if there's source line info as well.
-            func.lines.get(addr).map(|line| {
-                self.files.get(&line.file).map(|file| {
-                    frame.set_source_file(file, line.line, line.address + module.base_address());
+        match self.find_symbol(addr) {
+            Some(FoundSymbol::Func(func)) => {
+                // TODO: although FUNC records have a parameter size, it appears that
+                // they aren't to be trusted? The STACK WIN records are more reliable
+                // when available. This is important precisely because these values
+                // are used to unwind subsequent STACK WIN frames (because certain
+                // calling conventions have the caller push the callee's arguments,
+                // which affects the the stack's size!).
+                //
+                // Need to spend more time thinking about if this is the right approach
+                let parameter_size = if let Some(info) = self.win_stack_framedata_info.get(addr) {
+                    info.parameter_size
+                } else if let Some(info) = self.win_stack_fpo_info.get(addr) {
+                    info.parameter_size
+                } else {
+                    func.parameter_size
+                };
+
+                frame.set_function(
+                    &func.name,
+                    func.address + module.base_address(),
+                    parameter_size,
+                );
+                // See if there's source line info as well.
+                func.lines.get(addr).map(|line| {
+                    self.files.get(&line.file).map(|file| {
+                        frame.set_source_file(
+                            file,
+                            line.line,
+                            line.address + module.base_address(),
+                        );
+                    })
+                });
+            }
+            Some(FoundSymbol::Public(public)) => {
+                frame.set_function(
+                    &public.name,
+                    public.address + module.base_address(),
+                    public.parameter_size,
+                );
+            }
+            None => {}
+        }
+    }
+
+    /// Look up the symbol covering `address` (an absolute address within `module`).
+    ///
+    /// This uses the same FUNC/PUBLIC resolution as [`fill_symbol`][Self::fill_symbol].
+    /// Addresses past the end of the module yield `None` (unless the module's size
+    /// is unknown, i.e. 0).
+    pub fn lookup(&self, module: &dyn Module, address: u64) -> Option<SymbolResult> {
+        let addr = address.checked_sub(module.base_address())?;
+        if module.size() != 0 && addr >= module.size() {
+            return None;
+        }
+        match self.find_symbol(addr)? {
+            FoundSymbol::Func(func) => {
+                let line = func.lines.get(addr);
+                Some(SymbolResult {
+                    function: func.name.clone(),
+                    function_base: func.address + module.base_address(),
+                    source_file: line.and_then(|line| self.files.get(&line.file).cloned()),
+                    source_line: line.map(|line| line.line),
+                    kind: SymbolKind::Func,
                 })
-            });
-        } else if let Some(public) = self.find_nearest_public(addr) {
-            // We couldn't find a valid FUNC record, but we could find a PUBLIC record.
-            // Unfortauntely, PUBLIC records don't have end-points, so this could be
-            // a random PUBLIC record from the start of the module that isn't at all
-            // applicable. To try limit this problem, we can use the nearest FUNC
-            // record that comes *before* the address we're trying to find a symbol for.
-            //
-            // It is reasonable to assume a PUBLIC record cannot extend *past* a FUNC,
-            // so if the PUBLIC has a smaller base address than the nearest previous FUNC
-            // to our target address, the PUBLIC must actually end before that FUNC and
-            // therefore not actually apply to the target address.
-            //
-            // We get the nearest previous FUNC by getting the raw slice of ranges
-            // and binary searching for our base address. Rust's builtin binary search
-            // will fail to find the value since it uses strict equality *but* the Err
-            // will helpfully contain the index in the slice where our value "should"
-            // be inserted to preserve the sort. The element before this index is
-            // therefore the nearest previous value!
-            //
-            // Case analysis for this -1 because binary search is an off-by-one minefield:
-            //
-            // * if the address we were looking for came *before* every FUNC, binary_search
-            //   would yield "0" because that's where it should go to preserve the sort.
-            //   The checked_sub will then fail and make us just assume the PUBLIC is reasonable,
-            //   which is correct.
-            //
-            // * if we get 1, this saying we actually want element 0, so again -1 is
-            //   correct. (This generalizes to all other "reasonable" values, but 1 is easiest
-            //   to think about given the previous case's analysis.)
-            //
-            // * if the address we were looking for came *after* every FUNC, binary search
-            //   would yield "slice.len()", and the nearest FUNC is indeed at `len-1`, so
-            //   again correct.
-            let funcs_slice = self.functions.ranges_values().as_slice();
-            let prev_func = funcs_slice
-                .binary_search_by_key(&addr, |(range, _)| range.start)
-                .err()
-                .and_then(|idx| idx.checked_sub(1))
-                .and_then(|idx| funcs_slice.get(idx));
-
-            if let Some(prev_func) = prev_func {
-                if public.address <= prev_func.1.address {
-                    // This PUBLIC is truncated by a FUNC before it gets to `addr`,
-                    // so we shouldn't use it.
-                    return;
-                }
             }
+            FoundSymbol::Public(public) => Some(SymbolResult {
+                function: public.name.clone(),
+                function_base: public.address + module.base_address(),
+                source_file: None,
+                source_line: None,
+                kind: SymbolKind::Public,
+            }),
+        }
+    }
+
+    /// Find the record covering the module-relative address `addr`.
+    fn find_symbol(&self, addr: u64) -> Option<FoundSymbol<'_>> {
+        // Look for a FUNC covering the address first. FUNCs always take
+        // precedence over PUBLICs, even one at the exact same address,
+        // because they also know their size and line info.
+        if let Some(func) = self.functions.get(addr) {
+            return Some(FoundSymbol::Func(func));
+        }
+
+        let public = self.find_nearest_public(addr)?;
+        // We couldn't find a valid FUNC record, but we could find a PUBLIC record.
+        // Unfortauntely, PUBLIC records don't have end-points, so this could be
+        // a random PUBLIC record from the start of the module that isn't at all
+        // applicable. To try limit this problem, we can use the nearest FUNC
+        // record that comes *before* the address we're trying to find a symbol for.
+        //
+        // It is reasonable to assume a PUBLIC record cannot extend *past* a FUNC,
+        // so if the PUBLIC has a smaller base address than the nearest previous FUNC
+        // to our target address, the PUBLIC must actually end before that FUNC and
+        // therefore not actually apply to the target address.
+        //
+        // We get the nearest previous FUNC by getting the raw slice of ranges
+        // and binary searching for our base address. Rust's builtin binary search
+        // will fail to find the value since it uses strict equality *but* the Err
+        // will helpfully contain the index in the slice where our value "should"
+        // be inserted to preserve the sort. The element before this index is
+        // therefore the nearest previous value!
+        //
+        // Case analysis for this -1 because binary search is an off-by-one minefield:
+        //
+        // * if the address we were looking for came *before* every FUNC, binary_search
+        //   would yield "0" because that's where it should go to preserve the sort.
+        //   The checked_sub will then fail and make us just assume the PUBLIC is reasonable,
+        //   which is correct.
+        //
+        // * if we get 1, this saying we actually want element 0, so again -1 is
+        //   correct. (This generalizes to all other "reasonable" values, but 1 is easiest
+        //   to think about given the previous case's analysis.)
+        //
+        // * if the address we were looking for came *after* every FUNC, binary search
+        //   would yield "slice.len()", and the nearest FUNC is indeed at `len-1`, so
+        //   again correct.
+        let funcs_slice = self.functions.ranges_values().as_slice();
+        let prev_func = funcs_slice
+            .binary_search_by_key(&addr, |(range, _)| range.start)
+            .err()
+            .and_then(|idx| idx.checked_sub(1))
+            .and_then(|idx| funcs_slice.get(idx));
 
-            // Settle for a PUBLIC.
-            frame.set_function(
-                &public.name,
-                public.address + module.base_address(),
-                public.parameter_size,
-            );
+        if let Some(prev_func) = prev_func {
+            if public.address <= prev_func.1.address {
+                // This PUBLIC is truncated by a FUNC before it gets to `addr`,
+                // so we shouldn't use it.
+                return None;
+            }
         }
+
+        // Settle for a PUBLIC.
+        Some(FoundSymbol::Public(public))
     }
 
     pub fn walk_frame(&self, module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()> {
@@ -241,6 +542,144 @@ impl SymbolFile {
 
         None
     }
+
+    /// Find every FUNC and PUBLIC record named `name`, ordered by address.
+    ///
+    /// `name` can either be the full name as it appears in the symbol file
+    /// (e.g. `ns::Foo::bar(int) const`, or a mangled name for PUBLICs that
+    /// have one), or the name without its parameter list (e.g. `ns::Foo::bar`),
+    /// which will match every overload. Mangled names can also be found by
+    /// their demangled name (`_ZN2ns3FooC1Ev` by `ns::Foo::Foo`).
+    ///
+    /// The index this uses is built on the first call, so that call is
+    /// comparatively slow.
+    pub fn lookup_by_name(&self, name: &str) -> Vec<SymbolEntry> {
+        let mut index = self
+            .name_index
+            .0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let index = index.get_or_insert_with(|| self.build_name_index());
+        let records = match index.get(name) {
+            Some(records) => records,
+            None => return Vec::new(),
+        };
+
+        let funcs = self.functions.ranges_values().as_slice();
+        let mut entries: Vec<SymbolEntry> = records
+            .iter()
+            .filter_map(|record| match *record {
+                NamedRecord::Func(idx) => {
+                    let func = &funcs.get(idx)?.1;
+                    let line = func
+                        .lines
+                        .ranges_values()
+                        .as_slice()
+                        .first()
+                        .map(|(_, line)| line);
+                    Some(SymbolEntry {
+                        name: func.name.clone(),
+                        address: func.address,
+                        size: Some(func.size),
+                        parameter_size: func.parameter_size,
+                        source_file: line.and_then(|line| self.files.get(&line.file).cloned()),
+                        source_line: line.map(|line| line.line),
+                        kind: SymbolKind::Func,
+                    })
+                }
+                NamedRecord::Public(idx) => {
+                    let public = self.publics.get(idx)?;
+                    Some(SymbolEntry {
+                        name: public.name.clone(),
+                        address: public.address,
+                        size: None,
+                        parameter_size: public.parameter_size,
+                        source_file: None,
+                        source_line: None,
+                        kind: SymbolKind::Public,
+                    })
+                }
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.address);
+        entries
+    }
+
+    fn build_name_index(&self) -> HashMap<String, Vec<NamedRecord>> {
+        let mut index: HashMap<String, Vec<NamedRecord>> = HashMap::new();
+        let mut add_name = |name: &str, record| {
+            index.entry(name.to_string()).or_default().push(record);
+            if let Some(base) = strip_parameters(name) {
+                index.entry(base.to_string()).or_default().push(record);
+            }
+        };
+        let mut add = |name: &str, record| {
+            if name.is_empty() {
+                return;
+            }
+            add_name(name, record);
+            if let Some(demangled) = demangle(name) {
+                add_name(&demangled, record);
+            }
+        };
+        for (idx, (_, func)) in self.functions.ranges_values().enumerate() {
+            add(&func.name, NamedRecord::Func(idx));
+        }
+        for (idx, public) in self.publics.iter().enumerate() {
+            add(&public.name, NamedRecord::Public(idx));
+        }
+        index
+    }
+}
+
+/// Demangle `name` if it's a mangled Rust, Itanium (C++) or MSVC symbol.
+///
+/// FUNC names are usually already demangled, but PUBLICs often aren't.
+/// Returns `None` if `name` isn't mangled (or is mangled in a way we can't
+/// make sense of).
+pub fn demangle(name: &str) -> Option<String> {
+    // Legacy Rust symbols are also valid Itanium symbols, so try Rust first
+    // to get rid of their hashes.
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return Some(format!("{:#}", demangled));
+    }
+    if name.starts_with("_Z") {
+        let symbol = cpp_demangle::Symbol::new(name).ok()?;
+        return symbol.demangle(&Default::default()).ok();
+    }
+    if name.starts_with('?') {
+        return msvc_demangler::demangle(name, msvc_demangler::DemangleFlags::llvm()).ok();
+    }
+    None
+}
+
+/// Remove the trailing parameter list (and any qualifiers after it) from a
+/// demangled function name, e.g. `ns::Foo::bar(int) const` => `ns::Foo::bar`.
+///
+/// Returns `None` if the name has no parameter list.
+fn strip_parameters(name: &str) -> Option<&str> {
+    let close = name.rfind(')')?;
+    let mut depth = 0;
+    for (idx, c) in name[..=close].char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' => {
+                depth -= 1;
+                if depth == 0 {
+                    let base = name[..idx].trim_end();
+                    return if base.is_empty() { None } else { Some(base) };
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A symbol record found by [`SymbolFile::find_symbol`].
+enum FoundSymbol<'a> {
+    Func(&'a Function),
+    Public(&'a PublicSymbol),
 }
 
 #[cfg(test)]
@@ -330,6 +769,123 @@ FUNC 1000 30 10 another func
         );
     }
 
+    fn test_symbolfile_round_trip(symbolfile_bytes: &[u8]) {
+        let sym = SymbolFile::from_bytes(symbolfile_bytes).unwrap();
+        let mut written = Vec::new();
+        sym.write(&mut written).unwrap();
+        let reparsed = SymbolFile::from_bytes(&written).unwrap();
+        assert_eq!(sym, reparsed);
+
+        // Writing is deterministic, so a second round trip is byte-identical.
+        let mut rewritten = Vec::new();
+        reparsed.write(&mut rewritten).unwrap();
+        assert_eq!(written, rewritten);
+    }
+
+    #[test]
+    fn test_symbolfile_write_bytes() {
+        let bytes = b"MODULE Windows x86 D3096ED481217FD4C16B29CD9BC208BA0 firefox.pdb
+INFO CODE_ID 5A9832E5 firefox.exe
+INFO URL https://symbols.example.com/firefox.pdb
+FILE 100 bar.c
+FILE 0 foo.c
+FUNC m 1000 30 10 some func
+1000 10 42 0
+1010 10 52 100
+1020 0 53 100
+1020 10 62 0
+FUNC 900 30 10 some other func
+PUBLIC ff00 3 func 2
+PUBLIC m abcd 10 func 1
+STACK WIN 4 900 30 a1 b2 c3 d4 e5 f6 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =
+STACK WIN 0 1000 30 a1 b2 c3 d4 e5 f6 0 1
+STACK WIN 0 1030 30 a1 b2 c3 d4 e5 f6 0 0
+STACK CFI INIT badf00d abc .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI badf00e .cfa: $esp 8 +
+STACK CFI deadbeef .cfa: $ebp 8 + $ebp: .cfa 8 - ^
+STACK CFI INIT f00f f0 .cfa: $rsp 8 + .ra: .cfa 8 - ^
+";
+        test_symbolfile_round_trip(bytes);
+
+        let sym = SymbolFile::from_bytes(bytes).unwrap();
+        let mut written = Vec::new();
+        sym.write(&mut written).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&written).unwrap(),
+            "MODULE Windows x86 D3096ED481217FD4C16B29CD9BC208BA0 firefox.pdb
+INFO URL https://symbols.example.com/firefox.pdb
+FILE 0 foo.c
+FILE 100 bar.c
+PUBLIC abcd 10 func 1
+PUBLIC ff00 3 func 2
+FUNC 900 30 10 some other func
+FUNC 1000 30 10 some func
+1000 10 42 0
+1010 10 52 100
+1020 10 62 0
+STACK WIN 4 900 30 a1 b2 c3 d4 e5 f6 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =
+STACK WIN 0 1000 30 a1 b2 c3 d4 e5 f6 0 1
+STACK WIN 0 1030 30 a1 b2 c3 d4 e5 f6 0 0
+STACK CFI INIT f00f f0 .cfa: $rsp 8 + .ra: .cfa 8 - ^
+STACK CFI INIT badf00d abc .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI badf00e .cfa: $esp 8 +
+STACK CFI deadbeef .cfa: $ebp 8 + $ebp: .cfa 8 - ^
+"
+        );
+    }
+
+    #[test]
+    fn test_symbolfile_write_file() {
+        let mut path = std::env::current_dir().unwrap();
+        if path.file_name() == Some(OsStr::new("rust-minidump")) {
+            path.push("breakpad-symbols");
+        }
+        path.push("testdata/symbols/test_app.pdb/5A9832E5287241C1838ED98914E9B7FF1/test_app.sym");
+        let bytes = std::fs::read(&path).unwrap();
+        test_symbolfile_round_trip(&bytes);
+    }
+
+    #[test]
+    fn test_symbolfile_write_record_order() {
+        // Records are grouped by kind, whatever order they were read in.
+        let bytes = b"MODULE Linux x86_64 ffff0000 bar
+STACK CFI INIT 2000 10 .cfa: $rsp 8 + .ra: .cfa 8 - ^
+STACK CFI 2004 .cfa: $rsp 16 +
+FUNC 1000 30 0 a func
+1000 30 7 53
+PUBLIC 3000 0 a public
+FILE 53 bar.c
+FUNC 2000 10 0 another func
+PUBLIC 1800 0 another public
+FILE 1 foo.c
+";
+        test_symbolfile_round_trip(bytes);
+
+        let sym = SymbolFile::from_bytes(bytes).unwrap();
+        let mut written = Vec::new();
+        sym.write(&mut written).unwrap();
+        let order = [
+            "MODULE",
+            "FILE",
+            "PUBLIC",
+            "FUNC",
+            "STACK CFI INIT",
+            "STACK CFI",
+        ];
+        let kinds = std::str::from_utf8(&written)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                order
+                    .iter()
+                    .position(|kind| line.starts_with(kind))
+                    // LINE records belong to the FUNC before them.
+                    .unwrap_or(3)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [0, 1, 1, 2, 2, 3, 3, 3, 4, 5]);
+    }
+
     #[test]
     fn test_symbolfile_from_bytes_with_crlf() {
         test_symbolfile_from_bytes(
@@ -341,4 +897,333 @@ FUNC 1000 30 10 another func
 ",
         );
     }
+
+    fn lookup_str(symbols: &[u8], addr: u64) -> Option<SymbolResult> {
+        let sym = SymbolFile::from_bytes(symbols).unwrap();
+        let module = crate::SimpleModule::default();
+        sym.lookup(&module, addr)
+    }
+
+    #[test]
+    fn test_func_beats_public_at_same_address() {
+        for symbols in [
+            &b"PUBLIC 1000 0 the public\nFUNC 1000 30 10 the func\n"[..],
+            &b"FUNC 1000 30 10 the func\nPUBLIC 1000 0 the public\n"[..],
+        ] {
+            let result = lookup_str(symbols, 0x1010).unwrap();
+            assert_eq!(result.function, "the func");
+            assert_eq!(result.kind, SymbolKind::Func);
+
+            let mut frame = crate::SimpleFrame::with_instruction(0x1000);
+            let sym = SymbolFile::from_bytes(symbols).unwrap();
+            sym.fill_symbol(&crate::SimpleModule::default(), &mut frame);
+            assert_eq!(frame.function.as_deref(), Some("the func"));
+            assert_eq!(frame.parameter_size, Some(0x10));
+        }
+    }
+
+    #[test]
+    fn test_named_public_beats_unnamed_public() {
+        for symbols in [
+            &b"PUBLIC 1000 0 \nPUBLIC 1000 4 the public\n"[..],
+            &b"PUBLIC 1000 4 the public\nPUBLIC 1000 0 \n"[..],
+        ] {
+            let sym = SymbolFile::from_bytes(symbols).unwrap();
+            assert_eq!(sym.publics.len(), 1);
+            assert_eq!(sym.publics[0].parameter_size, 4);
+
+            let result = lookup_str(symbols, 0x1010).unwrap();
+            assert_eq!(result.function, "the public");
+            assert_eq!(result.kind, SymbolKind::Public);
+        }
+    }
+
+    #[test]
+    fn test_first_named_public_wins() {
+        let symbols = b"PUBLIC 2000 0 second\nPUBLIC 1000 0 zzz\nPUBLIC 1000 0 aaa\n";
+        let sym = SymbolFile::from_bytes(symbols).unwrap();
+        assert_eq!(sym.publics.len(), 2);
+        assert_eq!(sym.publics[0].name, "zzz");
+        assert_eq!(sym.publics[1].name, "second");
+        assert_eq!(lookup_str(symbols, 0x1010).unwrap().function, "zzz");
+        assert_eq!(lookup_str(symbols, 0x2010).unwrap().function, "second");
+    }
+
+    #[test]
+    fn test_parse_streaming() {
+        let symbols = b"MODULE Linux x86 ffff0000 bar
+INFO CODE_ID abc
+FILE 53 bar.c
+FUNC 1000 30 10 some func
+1000 10 7 53
+1010 20 8 54
+PUBLIC 3000 10 some public
+STACK CFI INIT 1000 30 .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI 1001 .cfa: $esp 8 +
+STACK WIN 4 2000 10 1 0 0 0 0 0 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =
+";
+        let records = SymbolFile::parse_streaming(&symbols[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 7);
+        assert!(matches!(&records[0], SymbolRecord::Module(module) if module.name == "bar"));
+        assert_eq!(records[1], SymbolRecord::Info(Info::Unknown));
+        assert_eq!(records[2], SymbolRecord::File(53, "bar.c".to_string()));
+        match &records[3] {
+            SymbolRecord::Function(func) => {
+                assert_eq!(func.name, "some func");
+                assert_eq!(func.lines.ranges_values().count(), 2);
+            }
+            record => panic!("expected a FUNC, got {:?}", record),
+        }
+        assert!(matches!(&records[4], SymbolRecord::Public(public) if public.address == 0x3000));
+        match &records[5] {
+            SymbolRecord::StackCfi(cfi) => {
+                assert_eq!(cfi.init.address, 0x1000);
+                assert_eq!(cfi.add_rules.len(), 1);
+            }
+            record => panic!("expected a STACK CFI INIT, got {:?}", record),
+        }
+        assert!(matches!(
+            &records[6],
+            SymbolRecord::StackWin(WinFrameType::FrameData(info)) if info.address == 0x2000
+        ));
+
+        // The whole file parses the same records.
+        let sym = SymbolFile::from_bytes(symbols).unwrap();
+        assert_eq!(sym.functions.ranges_values().count(), 1);
+        assert_eq!(sym.cfi_stack_info.ranges_values().count(), 1);
+        assert_eq!(sym.win_stack_framedata_info.ranges_values().count(), 1);
+    }
+
+    #[test]
+    fn test_parse_streaming_errors() {
+        // Records before a corrupt line are still yielded, and then the error.
+        let mut records = SymbolFile::parse_streaming(&b"FILE 1 a.c\nGARBAGE\nFILE 2 b.c\n"[..]);
+        assert_eq!(
+            records.next().unwrap().unwrap(),
+            SymbolRecord::File(1, "a.c".to_string())
+        );
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+
+        // Like SymbolFile::parse, a missing newline at the end is an error.
+        let records = SymbolFile::parse_streaming(&b"FILE 1 a.c"[..]).collect::<Vec<_>>();
+        assert_eq!(records.len(), 1);
+        assert!(records[0].is_err());
+        assert!(SymbolFile::from_bytes(b"FILE 1 a.c").is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let sym = SymbolFile::from_bytes(
+            b"MODULE Linux x86 ffff0000 bar
+FILE 53 bar.c
+FILE 54 baz.c
+FUNC 1000 30 10 some func
+1000 10 7 53
+1010 20 8 54
+FUNC 2000 10 0 another func
+2000 10 9 53
+PUBLIC 3000 10 some public
+STACK CFI INIT 1000 30 .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI 1001 .cfa: $esp 8 +
+STACK CFI 1002 .cfa: $esp 12 +
+STACK CFI INIT 2000 10 .cfa: $esp 4 + .ra: .cfa 4 - ^
+",
+        )
+        .unwrap();
+        assert_eq!(
+            sym.stats(),
+            SymbolFileStats {
+                func_count: 2,
+                public_count: 1,
+                line_count: 3,
+                file_count: 2,
+                cfi_init_count: 2,
+                cfi_delta_count: 2,
+                inline_count: 0,
+            }
+        );
+        assert_eq!(
+            SymbolFile::from_bytes(b"MODULE Linux x86 ffff0000 bar\n")
+                .unwrap()
+                .stats(),
+            Default::default()
+        );
+    }
+
+    #[test]
+    fn test_trim_to_range() {
+        let sym = SymbolFile::from_bytes(
+            b"MODULE Linux x86 ffff0000 bar
+FILE 53 bar.c
+FILE 54 baz.c
+FILE 55 unused.c
+FUNC 1000 30 10 first
+1000 30 7 53
+PUBLIC 1800 0 between
+FUNC 2000 10 0 second
+2000 10 9 54
+PUBLIC 2800 0 after second
+FUNC 3000 10 0 third
+3000 10 9 55
+PUBLIC 4000 0 last
+STACK CFI INIT 1000 30 .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI INIT 2000 10 .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI 2004 .cfa: $esp 8 +
+STACK CFI INIT 3000 10 .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK WIN 4 2000 10 1 0 0 0 0 0 1 $eip 4 + ^ =
+",
+        )
+        .unwrap();
+
+        let trimmed = sym.trim_to_range(0x2000, 0x3000);
+        assert_eq!(trimmed.module, sym.module);
+        let functions = trimmed
+            .functions
+            .ranges_values()
+            .map(|(_, func)| func.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(functions, ["second"]);
+        assert_eq!(trimmed.files, HashMap::from([(54, String::from("baz.c"))]));
+        let publics = trimmed
+            .publics
+            .iter()
+            .map(|public| public.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(publics, ["between", "after second"]);
+        assert_eq!(
+            trimmed.stats(),
+            SymbolFileStats {
+                func_count: 1,
+                public_count: 2,
+                line_count: 1,
+                file_count: 1,
+                cfi_init_count: 1,
+                cfi_delta_count: 1,
+                inline_count: 0,
+            }
+        );
+        assert_eq!(trimmed.win_stack_framedata_info.ranges_values().count(), 1);
+
+        // Everything in the range is looked up the same way as before, even
+        // when the PUBLIC covering it starts before the range.
+        let module = crate::SimpleModule::default();
+        for (start, end) in [
+            (0x2000, 0x3000),
+            (0x1900, 0x1a00),
+            (0x2900, 0x2a00),
+            (0x3008, 0x3100),
+            (0x0, 0x5000),
+        ] {
+            let trimmed = sym.trim_to_range(start, end);
+            for addr in (start..end).step_by(4) {
+                assert_eq!(
+                    trimmed.lookup(&module, addr),
+                    sym.lookup(&module, addr),
+                    "{:#x} in {:#x}..{:#x}",
+                    addr,
+                    start,
+                    end
+                );
+            }
+        }
+        assert_eq!(sym.trim_to_range(0x3100, 0x3200).publics, []);
+        assert_eq!(sym.trim_to_range(0x0, 0x5000), sym);
+        assert_eq!(
+            sym.trim_to_range(0x2000, 0x2000).stats(),
+            Default::default()
+        );
+    }
+
+    #[test]
+    fn test_lookup_by_name() {
+        let sym = SymbolFile::from_bytes(
+            b"FILE 0 foo.c
+FUNC 3000 20 4 ns::Foo::bar(char*) const
+3000 20 12 0
+FUNC 1000 30 8 ns::Foo::bar(int)
+1000 10 42 0
+1010 20 43 0
+PUBLIC 2000 4 ns::Foo::bar(int)
+PUBLIC 4000 0 _ZN2ns3FooC1Ev
+PUBLIC 5000 0 operator()(int)
+",
+        )
+        .unwrap();
+
+        let overloads = sym.lookup_by_name("ns::Foo::bar");
+        assert_eq!(
+            overloads
+                .iter()
+                .map(|entry| (entry.address, entry.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                (0x1000, SymbolKind::Func),
+                (0x2000, SymbolKind::Public),
+                (0x3000, SymbolKind::Func),
+            ]
+        );
+        assert_eq!(
+            overloads[0],
+            SymbolEntry {
+                name: String::from("ns::Foo::bar(int)"),
+                address: 0x1000,
+                size: Some(0x30),
+                parameter_size: 8,
+                source_file: Some(String::from("foo.c")),
+                source_line: Some(42),
+                kind: SymbolKind::Func,
+            }
+        );
+        assert_eq!(overloads[1].size, None);
+        assert_eq!(overloads[1].source_file, None);
+
+        let exact = sym.lookup_by_name("ns::Foo::bar(int)");
+        assert_eq!(exact.len(), 2);
+        assert_eq!(sym.lookup_by_name("ns::Foo::bar(char*) const").len(), 1);
+        assert_eq!(sym.lookup_by_name("_ZN2ns3FooC1Ev")[0].address, 0x4000);
+        // Mangled names are also indexed by their demangled form.
+        let ctor = sym.lookup_by_name("ns::Foo::Foo");
+        assert_eq!(ctor.len(), 1);
+        assert_eq!(ctor[0].address, 0x4000);
+        assert_eq!(ctor[0].name, "_ZN2ns3FooC1Ev");
+        assert_eq!(sym.lookup_by_name("ns::Foo::Foo()").len(), 1);
+        assert_eq!(sym.lookup_by_name("operator()")[0].address, 0x5000);
+        assert!(sym.lookup_by_name("ns::Foo").is_empty());
+        assert!(sym.lookup_by_name("").is_empty());
+    }
+
+    #[test]
+    fn test_default_symbol_file() {
+        // SymbolFiles built by hand still get a name index.
+        let mut sym = SymbolFile::default();
+        assert_eq!(sym.stats(), SymbolFileStats::default());
+        sym.publics.push(PublicSymbol {
+            address: 0x1000,
+            parameter_size: 0,
+            name: String::from("_ZN2ns3FooC1Ev"),
+        });
+        assert_eq!(sym.lookup_by_name("ns::Foo::Foo")[0].address, 0x1000);
+    }
+
+    #[test]
+    fn test_strip_parameters() {
+        assert_eq!(strip_parameters("foo(int)"), Some("foo"));
+        assert_eq!(
+            strip_parameters("ns::foo(int, char*) const"),
+            Some("ns::foo")
+        );
+        assert_eq!(
+            strip_parameters("std::vector<int>::push_back(int const&)"),
+            Some("std::vector<int>::push_back")
+        );
+        assert_eq!(
+            strip_parameters("Foo::operator()(void (*)(int))"),
+            Some("Foo::operator()")
+        );
+        assert_eq!(strip_parameters("foo"), None);
+        assert_eq!(strip_parameters("(anonymous)"), None);
+    }
 }
@@ -7,8 +7,9 @@ use nom::IResult::*;
 use nom::*;
 use range_map::{Range, RangeMap};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
+use std::io::{self, BufRead};
 use std::str;
 use std::str::FromStr;
 
@@ -19,7 +20,7 @@ use crate::SymbolError;
 
 #[derive(Debug)]
 enum Line {
-    Module,
+    Module(ModuleInfo),
     Info(Info),
     File(u32, String),
     Public(PublicSymbol),
@@ -42,23 +43,26 @@ named!(hex_str_u64<&[u8], u64>,
 named!(decimal_u32<&[u8], u32>, map_res!(map_res!(digit, str::from_utf8), FromStr::from_str));
 
 // Matches a MODULE record.
-named!(module_line<&[u8], ()>,
+named!(module_line<&[u8], ModuleInfo>,
   chain!(
     tag!("MODULE") ~
           space     ~
-          // os
-    alphanumeric ~
+    os: map_res!(alphanumeric, str::from_utf8) ~
           space ~
-          // cpu
-    take_until!(" ") ~
+    cpu: map_res!(take_until!(" "), str::from_utf8) ~
           space ~
-          // debug id
-    hex_digit ~
+    debug_id: map_res!(hex_digit, str::from_utf8) ~
           space ~
-          // filename
-    not_line_ending ~
+    name: map_res!(not_line_ending, str::from_utf8) ~
     my_eol ,
-    || {}
+    || {
+        ModuleInfo {
+            os: os.to_string(),
+            cpu: cpu.to_string(),
+            debug_id: debug_id.to_string(),
+            name: name.to_string(),
+        }
+    }
 ));
 
 // Matches an INFO URL record.
@@ -285,44 +289,26 @@ named!(line<&[u8], Line>,
     func_line => { |f| Line::Function(f, Vec::new()) } |
     stack_win_line => { Line::StackWin } |
     stack_cfi_init => { Line::StackCfi } |
-    module_line => { |_| Line::Module }
+    module_line => { Line::Module }
 ));
 
-/// A parser for SymbolFiles.
-///
-/// This is basically just a SymbolFile but with some extra state
-/// to handle streaming parsing.
-///
-/// Use this by repeatedly calling [`parse_more`][] until the
-/// whole input is consumed. Then call [`finish`][].
+/// The line-by-line half of [`SymbolParser`], which turns the input into
+/// [`SymbolRecord`]s without keeping them.
 #[derive(Debug, Default)]
-pub struct SymbolParser {
-    files: HashMap<u32, String>,
-    publics: Vec<PublicSymbol>,
-
-    // When building a RangeMap when need to sort an array of this
-    // format anyway, so we might as well construct it directly and
-    // save a giant allocation+copy.
-    functions: Vec<(Range<u64>, Function)>,
-    cfi_stack_info: Vec<(Range<u64>, StackInfoCfi)>,
-    win_stack_framedata_info: Vec<(Range<u64>, StackInfoWin)>,
-    win_stack_fpo_info: Vec<(Range<u64>, StackInfoWin)>,
-    url: Option<String>,
-    pub lines: u64,
+struct RecordParser {
+    lines: u64,
     cur_item: Option<Line>,
 }
 
-impl SymbolParser {
-    /// Creates a new SymbolParser.
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    /// Parses as much of the input as it can, and then returns
-    /// how many bytes of the input was used. The *unused* portion of the
-    /// input must be resubmitted on subsequent calls to parse_more
-    /// (along with more data so we can make progress on the parse).
-    pub fn parse_more(&mut self, mut input: &[u8]) -> Result<usize, SymbolError> {
+impl RecordParser {
+    /// Parses as much of the input as it can, passing each complete record
+    /// to `emit`, and then returns how many bytes of the input was used.
+    /// See [`SymbolParser::parse_more`][].
+    fn parse_more(
+        &mut self,
+        mut input: &[u8],
+        emit: &mut dyn FnMut(SymbolRecord),
+    ) -> Result<usize, SymbolError> {
         // We parse the input line-by-line, so trim away any part of the input
         // that comes after the last newline (this is necessary for streaming
         // parsing, as it can otherwise be impossible to tell if a line is
@@ -367,7 +353,7 @@ impl SymbolParser {
                         continue;
                     }
                     Error(_) | Incomplete(_) => {
-                        self.finish_item(Line::Function(cur, lines));
+                        emit(finish_item(Line::Function(cur, lines)));
                         continue;
                     }
                 },
@@ -380,7 +366,7 @@ impl SymbolParser {
                         continue;
                     }
                     Error(_) | Incomplete(_) => {
-                        self.finish_item(Line::StackCfi(cur));
+                        emit(finish_item(Line::StackCfi(cur)));
                         continue;
                     }
                 },
@@ -413,84 +399,22 @@ impl SymbolParser {
                 }
             };
 
-            // Now store the item in our partial SymbolFile (or make it the cur_item
-            // if it has potential sublines we need to parse first).
+            // Now emit the record (or make it the cur_item if it has potential
+            // sublines we need to parse first).
             match line {
-                Line::Module => {
-                    // We don't use this but it MUST be the first line
+                Line::Module(module) => {
+                    // This MUST be the first line
                     if self.lines != 0 {
                         return Err(SymbolError::ParseError(format_err!(
                             "MODULE line found after the start of the file"
                         )));
                     }
+                    emit(SymbolRecord::Module(module));
                 }
-                Line::Info(Info::Url(cached_url)) => {
-                    self.url = Some(cached_url);
-                }
-                Line::Info(Info::Unknown) => {
-                    // Don't care
-                }
-                Line::File(id, filename) => {
-                    self.files.insert(id, filename.to_string());
-                }
-                Line::Public(p) => {
-                    self.publics.push(p);
-                }
-                Line::StackWin(frame_type) => {
-                    // PDB files contain lots of overlapping unwind info, so we have to filter
-                    // some of it out.
-                    fn insert_win_stack_info(
-                        stack_win: &mut Vec<(Range<u64>, StackInfoWin)>,
-                        info: StackInfoWin,
-                    ) {
-                        if let Some(memory_range) = info.memory_range() {
-                            if let Some((last_range, last_info)) = stack_win.last_mut() {
-                                if last_range.intersects(&memory_range) {
-                                    if info.address > last_info.address {
-                                        // Sometimes we get STACK WIN directives where each line
-                                        // has an accurate starting point, but the length just
-                                        // covers the entire function, like so:
-                                        //
-                                        // addr: 0, len: 10
-                                        // addr: 1, len: 9
-                                        // addr: 4, len: 6
-                                        //
-                                        // In this case, the next instruction is the one that
-                                        // really defines the length of the previous one. So
-                                        // we need to fixup the lengths like so:
-                                        //
-                                        // addr: 0, len: 1
-                                        // addr: 1, len: 2
-                                        // addr: 4, len: 6
-                                        last_info.size = (info.address - last_info.address) as u32;
-                                        *last_range = last_info.memory_range().unwrap();
-                                    } else if *last_range != memory_range {
-                                        // We silently drop identical ranges because sometimes
-                                        // duplicates happen, but we complain for non-trivial duplicates.
-                                        warn!(
-                                            "STACK WIN entry had bad intersections, dropping it {:?}",
-                                            info
-                                        );
-                                        return;
-                                    }
-                                }
-                            }
-                            stack_win.push((memory_range, info));
-                        } else {
-                            warn!("STACK WIN entry had invalid range, dropping it {:?}", info);
-                        }
-                    }
-                    match frame_type {
-                        WinFrameType::FrameData(s) => {
-                            insert_win_stack_info(&mut self.win_stack_framedata_info, s);
-                        }
-                        WinFrameType::Fpo(s) => {
-                            insert_win_stack_info(&mut self.win_stack_fpo_info, s);
-                        }
-                        // Just ignore other types.
-                        _ => {}
-                    }
-                }
+                Line::Info(info) => emit(SymbolRecord::Info(info)),
+                Line::File(id, filename) => emit(SymbolRecord::File(id, filename)),
+                Line::Public(p) => emit(SymbolRecord::Public(p)),
+                Line::StackWin(frame_type) => emit(SymbolRecord::StackWin(frame_type)),
                 item @ Line::Function(_, _) => {
                     // More sublines to parse
                     self.cur_item = Some(item);
@@ -506,40 +430,256 @@ impl SymbolParser {
         }
     }
 
-    /// Finish processing an item (cur_item) which had sublines.
-    /// We now have all the sublines, so it's complete.
-    fn finish_item(&mut self, item: Line) {
-        match item {
-            Line::Function(mut cur, lines) => {
-                cur.lines = lines
-                    .into_iter()
-                    .map(|l| {
-                        // Line data from PDB files often has a zero-size line entry, so just
-                        // filter those out.
-                        if l.size > 0 {
-                            (
-                                Some(Range::new(l.address, l.address + l.size as u64 - 1)),
-                                l,
-                            )
-                        } else {
-                            (None, l)
-                        }
-                    })
-                    .into_rangemap_safe();
+    /// Emit the pending multi-line item, if there is one.
+    ///
+    /// Call this when the parser has consumed all the input.
+    fn finish(&mut self, emit: &mut dyn FnMut(SymbolRecord)) {
+        if let Some(item) = self.cur_item.take() {
+            emit(finish_item(item));
+        }
+    }
+}
 
+/// Finish processing an item (cur_item) which had sublines.
+/// We now have all the sublines, so it's complete.
+fn finish_item(item: Line) -> SymbolRecord {
+    match item {
+        Line::Function(mut cur, lines) => {
+            cur.lines = lines
+                .into_iter()
+                .map(|l| {
+                    // Line data from PDB files often has a zero-size line entry, so just
+                    // filter those out.
+                    if l.size > 0 {
+                        (
+                            Some(Range::new(l.address, l.address + l.size as u64 - 1)),
+                            l,
+                        )
+                    } else {
+                        (None, l)
+                    }
+                })
+                .into_rangemap_safe();
+            SymbolRecord::Function(cur)
+        }
+        Line::StackCfi(mut cur) => {
+            cur.add_rules.sort();
+            SymbolRecord::StackCfi(cur)
+        }
+        _ => {
+            unreachable!()
+        }
+    }
+}
+
+/// The records of a symbol file, read one line at a time.
+///
+/// See [`SymbolFile::parse_streaming`][].
+pub(crate) struct SymbolRecords<R> {
+    reader: R,
+    parser: RecordParser,
+    line: Vec<u8>,
+    pending: VecDeque<SymbolRecord>,
+    done: bool,
+}
+
+impl<R: BufRead> SymbolRecords<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        SymbolRecords {
+            reader,
+            parser: RecordParser::default(),
+            line: Vec::new(),
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Parse the next line, or finish the parse at the end of the input.
+    fn parse_line(&mut self) -> Result<(), SymbolError> {
+        self.line.clear();
+        let pending = &mut self.pending;
+        let mut emit = |record| pending.push_back(record);
+        if self
+            .reader
+            .read_until(b'\n', &mut self.line)
+            .map_err(read_error)?
+            == 0
+        {
+            self.done = true;
+            self.parser.finish(&mut emit);
+            return Ok(());
+        }
+        // A whole line is always consumed, so only a last line without a
+        // newline is left over, which `SymbolFile::parse` also rejects.
+        if self.parser.parse_more(&self.line, &mut emit)? != self.line.len() {
+            return Err(SymbolError::ParseError(format_err!(
+                "unexpected EOF during parsing of SymbolFile at line {}",
+                self.parser.lines
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for SymbolRecords<R> {
+    type Item = Result<SymbolRecord, SymbolError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Some(Ok(record));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.parse_line() {
+                // There's no recovering from a corrupt line.
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// The error for a failed read of a symbol file's input.
+pub(crate) fn read_error(e: io::Error) -> SymbolError {
+    if e.kind() == io::ErrorKind::TimedOut {
+        SymbolError::Timeout
+    } else {
+        SymbolError::LoadError(format_err!("couldn't read input stream {}", e))
+    }
+}
+
+/// A parser for SymbolFiles.
+///
+/// This is basically just a SymbolFile but with some extra state
+/// to handle streaming parsing.
+///
+/// Use this by repeatedly calling [`parse_more`][] until the
+/// whole input is consumed. Then call [`finish`][].
+#[derive(Debug, Default)]
+pub struct SymbolParser {
+    module: Option<ModuleInfo>,
+    files: HashMap<u32, String>,
+    publics: Vec<PublicSymbol>,
+
+    // When building a RangeMap when need to sort an array of this
+    // format anyway, so we might as well construct it directly and
+    // save a giant allocation+copy.
+    functions: Vec<(Range<u64>, Function)>,
+    cfi_stack_info: Vec<(Range<u64>, StackInfoCfi)>,
+    win_stack_framedata_info: Vec<(Range<u64>, StackInfoWin)>,
+    win_stack_fpo_info: Vec<(Range<u64>, StackInfoWin)>,
+    url: Option<String>,
+    pub lines: u64,
+    record_parser: RecordParser,
+}
+
+impl SymbolParser {
+    /// Creates a new SymbolParser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses as much of the input as it can, and then returns
+    /// how many bytes of the input was used. The *unused* portion of the
+    /// input must be resubmitted on subsequent calls to parse_more
+    /// (along with more data so we can make progress on the parse).
+    pub fn parse_more(&mut self, input: &[u8]) -> Result<usize, SymbolError> {
+        let mut records = Vec::new();
+        let consumed = self
+            .record_parser
+            .parse_more(input, &mut |record| records.push(record));
+        self.lines = self.record_parser.lines;
+        for record in records {
+            self.add_record(record);
+        }
+        consumed
+    }
+
+    /// Store a record in our partial SymbolFile.
+    fn add_record(&mut self, record: SymbolRecord) {
+        match record {
+            SymbolRecord::Module(module) => {
+                self.module = Some(module);
+            }
+            SymbolRecord::Info(Info::Url(cached_url)) => {
+                self.url = Some(cached_url);
+            }
+            SymbolRecord::Info(Info::Unknown) => {
+                // Don't care
+            }
+            SymbolRecord::File(id, filename) => {
+                self.files.insert(id, filename);
+            }
+            SymbolRecord::Public(p) => {
+                self.publics.push(p);
+            }
+            SymbolRecord::StackWin(frame_type) => {
+                // PDB files contain lots of overlapping unwind info, so we have to filter
+                // some of it out.
+                fn insert_win_stack_info(
+                    stack_win: &mut Vec<(Range<u64>, StackInfoWin)>,
+                    info: StackInfoWin,
+                ) {
+                    if let Some(memory_range) = info.memory_range() {
+                        if let Some((last_range, last_info)) = stack_win.last_mut() {
+                            if last_range.intersects(&memory_range) {
+                                if info.address > last_info.address {
+                                    // Sometimes we get STACK WIN directives where each line
+                                    // has an accurate starting point, but the length just
+                                    // covers the entire function, like so:
+                                    //
+                                    // addr: 0, len: 10
+                                    // addr: 1, len: 9
+                                    // addr: 4, len: 6
+                                    //
+                                    // In this case, the next instruction is the one that
+                                    // really defines the length of the previous one. So
+                                    // we need to fixup the lengths like so:
+                                    //
+                                    // addr: 0, len: 1
+                                    // addr: 1, len: 2
+                                    // addr: 4, len: 6
+                                    last_info.size = (info.address - last_info.address) as u32;
+                                    *last_range = last_info.memory_range().unwrap();
+                                } else if *last_range != memory_range {
+                                    // We silently drop identical ranges because sometimes
+                                    // duplicates happen, but we complain for non-trivial duplicates.
+                                    warn!(
+                                        "STACK WIN entry had bad intersections, dropping it {:?}",
+                                        info
+                                    );
+                                    return;
+                                }
+                            }
+                        }
+                        stack_win.push((memory_range, info));
+                    } else {
+                        warn!("STACK WIN entry had invalid range, dropping it {:?}", info);
+                    }
+                }
+                match frame_type {
+                    WinFrameType::FrameData(s) => {
+                        insert_win_stack_info(&mut self.win_stack_framedata_info, s);
+                    }
+                    WinFrameType::Fpo(s) => {
+                        insert_win_stack_info(&mut self.win_stack_fpo_info, s);
+                    }
+                    // Just ignore other types.
+                    _ => {}
+                }
+            }
+            SymbolRecord::Function(cur) => {
                 if let Some(range) = cur.memory_range() {
                     self.functions.push((range, cur));
                 }
             }
-            Line::StackCfi(mut cur) => {
-                cur.add_rules.sort();
+            SymbolRecord::StackCfi(cur) => {
                 if let Some(range) = cur.memory_range() {
                     self.cfi_stack_info.push((range, cur));
                 }
             }
-            _ => {
-                unreachable!()
-            }
         }
     }
 
@@ -548,14 +688,30 @@ impl SymbolParser {
     /// Call this when the parser has consumed all the input.
     pub fn finish(mut self) -> SymbolFile {
         // If there's a pending multiline item, finish it now.
-        if let Some(item) = self.cur_item.take() {
-            self.finish_item(item);
+        let mut records = Vec::new();
+        self.record_parser
+            .finish(&mut |record| records.push(record));
+        for record in records {
+            self.add_record(record);
         }
 
         // Now sort everything and bundle it up in its final format.
-        self.publics.sort();
+        self.publics.sort_by_key(|public| public.address);
+        // Only keep one PUBLIC per address. A PUBLIC with a name beats one
+        // without, and otherwise the first one in the file wins. (FUNCs at
+        // the same address beat both; see `SymbolFile::find_symbol`.)
+        self.publics.dedup_by(|later, earlier| {
+            if later.address != earlier.address {
+                return false;
+            }
+            if earlier.name.is_empty() && !later.name.is_empty() {
+                std::mem::swap(earlier, later);
+            }
+            true
+        });
 
         SymbolFile {
+            module: self.module,
             files: self.files,
             publics: self.publics,
             functions: into_rangemap_safe(self.functions),
@@ -564,10 +720,13 @@ impl SymbolParser {
             win_stack_fpo_info: into_rangemap_safe(self.win_stack_fpo_info),
             // Will get filled in by the caller
             url: self.url,
+            // Also filled in by the caller, if it knows
+            origin: None,
             ambiguities_repaired: 0,
             ambiguities_discarded: 0,
             corruptions_discarded: 0,
             cfi_eval_corruptions: 0,
+            name_index: Default::default(),
         }
     }
 }
@@ -597,18 +756,50 @@ fn parse_symbol_bytes(data: &[u8]) -> Result<SymbolFile, SymbolError> {
     SymbolFile::parse(data, |_| ())
 }
 
+#[cfg(test)]
+fn module_info(os: &str, cpu: &str, debug_id: &str, name: &str) -> ModuleInfo {
+    ModuleInfo {
+        os: os.to_string(),
+        cpu: cpu.to_string(),
+        debug_id: debug_id.to_string(),
+        name: name.to_string(),
+    }
+}
+
 #[test]
 fn test_module_line() {
     let line = b"MODULE Linux x86 D3096ED481217FD4C16B29CD9BC208BA0 firefox-bin\n";
     let rest = &b""[..];
-    assert_eq!(module_line(line), Done(rest, ()));
+    assert_eq!(
+        module_line(line),
+        Done(
+            rest,
+            module_info(
+                "Linux",
+                "x86",
+                "D3096ED481217FD4C16B29CD9BC208BA0",
+                "firefox-bin"
+            )
+        )
+    );
 }
 
 #[test]
 fn test_module_line_filename_spaces() {
     let line = b"MODULE Windows x86_64 D3096ED481217FD4C16B29CD9BC208BA0 firefox x y z\n";
     let rest = &b""[..];
-    assert_eq!(module_line(line), Done(rest, ()));
+    assert_eq!(
+        module_line(line),
+        Done(
+            rest,
+            module_info(
+                "Windows",
+                "x86_64",
+                "D3096ED481217FD4C16B29CD9BC208BA0",
+                "firefox x y z"
+            )
+        )
+    );
 }
 
 /// Sometimes dump_syms on Windows does weird things and produces multiple carriage returns
@@ -617,7 +808,18 @@ fn test_module_line_filename_spaces() {
 fn test_module_line_crcrlf() {
     let line = b"MODULE Windows x86_64 D3096ED481217FD4C16B29CD9BC208BA0 firefox\r\r\n";
     let rest = &b""[..];
-    assert_eq!(module_line(line), Done(rest, ()));
+    assert_eq!(
+        module_line(line),
+        Done(
+            rest,
+            module_info(
+                "Windows",
+                "x86_64",
+                "D3096ED481217FD4C16B29CD9BC208BA0",
+                "firefox"
+            )
+        )
+    );
 }
 
 #[test]
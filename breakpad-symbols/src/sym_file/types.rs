@@ -1,12 +1,29 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
+use crate::SymbolOrigin;
 use range_map::{Range, RangeMap};
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// The contents of a MODULE record, which identifies the module a symbol
+/// file describes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleInfo {
+    /// The operating system the module was built for (e.g. "Linux", "windows").
+    pub os: String,
+    /// The CPU architecture the module was built for (e.g. "x86_64").
+    pub cpu: String,
+    /// The module's debug identifier, as an uppercase hex string.
+    pub debug_id: String,
+    /// The name of the module's debug file.
+    pub name: String,
+}
 
 /// A publicly visible linker symbol.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PublicSymbol {
     /// The symbol's address relative to the module's load address.
     pub address: u64,
@@ -178,9 +195,38 @@ impl StackInfoWin {
     }
 }
 
+/// A single record of a .sym file, see [`SymbolFile::parse_streaming`][].
+///
+/// Records with sublines are whole: a `FUNC`'s line records are in its
+/// [`Function::lines`], and a `STACK CFI INIT`'s `STACK CFI` records are in
+/// its [`StackInfoCfi::add_rules`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum SymbolRecord {
+    /// The MODULE record, which is always first.
+    Module(ModuleInfo),
+    /// An INFO record.
+    Info(Info),
+    /// A FILE record, with the file's id and name.
+    File(u32, String),
+    /// A PUBLIC record.
+    Public(PublicSymbol),
+    /// A FUNC record and its line records.
+    Function(Function),
+    /// A STACK WIN record.
+    StackWin(WinFrameType),
+    /// A STACK CFI INIT record and its STACK CFI records.
+    StackCfi(StackInfoCfi),
+}
+
 /// A parsed .sym file containing debug symbols.
+///
+/// This has a private cache, so outside of this crate it can't be built with
+/// a struct literal. Start from [`SymbolFile::default`][] and fill in the
+/// fields instead.
 #[derive(Debug, PartialEq)]
 pub struct SymbolFile {
+    /// The MODULE record at the start of the file, if there was one.
+    pub module: Option<ModuleInfo>,
     /// The set of source files involved in compilation.
     pub files: HashMap<u32, String>,
     /// Publicly visible symbols.
@@ -199,6 +245,8 @@ pub struct SymbolFile {
     // log an event.
     /// If the symbol file was loaded from a URL, this is the url
     pub url: Option<String>,
+    /// Where this symbol file was loaded from, if known.
+    pub origin: Option<SymbolOrigin>,
     /// The number of times the parser found that the symbol file was
     /// strictly ambiguous but simple heuristics repaired it. (e.g.
     /// two STACK WIN entries overlapped, but the second was a suffix of
@@ -243,4 +291,60 @@ pub struct SymbolFile {
     /// was not mapped memory). In these situations the cfi entry *may*
     /// still be correct.
     pub cfi_eval_corruptions: u64,
+
+    /// Index of symbols by name, built on the first call to
+    /// [`SymbolFile::lookup_by_name`].
+    pub(crate) name_index: NameIndex,
+}
+
+/// How many of each kind of record a [`SymbolFile`] contains.
+///
+/// These count what the parser kept, so records it merged, deduplicated,
+/// or discarded as corrupt aren't included.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SymbolFileStats {
+    /// The number of FUNC records.
+    pub func_count: usize,
+    /// The number of PUBLIC records.
+    pub public_count: usize,
+    /// The number of line records, across all functions.
+    pub line_count: usize,
+    /// The number of FILE records.
+    pub file_count: usize,
+    /// The number of STACK CFI INIT records.
+    pub cfi_init_count: usize,
+    /// The number of STACK CFI (delta) records, across all STACK CFI INIT records.
+    pub cfi_delta_count: usize,
+    /// The number of INLINE records.
+    ///
+    /// The parser doesn't support INLINE records yet, so this is always 0.
+    pub inline_count: usize,
+}
+
+/// A record in [`NameIndex`], as an index into `SymbolFile::functions` or
+/// `SymbolFile::publics`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NamedRecord {
+    Func(usize),
+    Public(usize),
+}
+
+/// A lazily built map from symbol names to the records with that name.
+///
+/// Most users never look up symbols by name, so we don't want to pay for
+/// this while parsing. It's a cache, so it's ignored when comparing
+/// SymbolFiles.
+#[derive(Default)]
+pub(crate) struct NameIndex(pub(crate) Mutex<Option<HashMap<String, Vec<NamedRecord>>>>);
+
+impl fmt::Debug for NameIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NameIndex")
+    }
+}
+
+impl PartialEq for NameIndex {
+    fn eq(&self, _other: &NameIndex) -> bool {
+        true
+    }
 }
@@ -269,6 +269,12 @@
 //! But I believe `$` is "supposed" to be used on every platform except for
 //! ARM variants.
 //!
+//! Dereferences can be nested, e.g. `.cfa 8 - ^ 16 + ^` loads a pointer to
+//! a register save area and then loads from that. To bound how much stack
+//! memory a corrupt rule can chase through, values that took more than
+//! [`DEFAULT_MAX_CFI_DEREF_DEPTH`][] nested dereferences to compute make
+//! evaluation fail. [`StackCfiEvaluator`][] can be used to change that limit.
+//!
 //!
 //!
 //! # STACK WIN
@@ -483,74 +489,180 @@
 //! ```
 //!
 //! Giving a final output of `ebp=(*16)`, `esp=24`, `eip=(*20)`.
+//!
+//! # DWARF expressions
+//!
+//! Breakpad's STACK CFI expressions are a lossy translation of the DWARF
+//! expressions found in `.eh_frame`/`.debug_frame` (`DW_CFA_expression` and
+//! `DW_CFA_val_expression` rules). When unwinding with raw DWARF CFI we instead
+//! need to evaluate the original bytecode, which [eval_dwarf_expr][] does.
+//!
+//! Decoding is handled by [gimli::Operation][], evaluation is done here
+//! so that registers and memory can be read through the same [FrameWalker][]
+//! as STACK CFI. Like STACK CFI, this is a stack machine, and the result
+//! is the value left on top of the stack.
+//!
+//! Values are address-sized, as specified by the `gimli::Encoding`, and all
+//! arithmetic wraps/truncates accordingly. Following the DWARF spec,
+//! `DW_OP_div`, `DW_OP_shra`, `DW_OP_abs`, `DW_OP_neg` and the comparison
+//! operators treat their inputs as signed, everything else is unsigned.
+//!
+//! Registers are identified by DWARF register number, so the caller must
+//! provide a mapping to the names its FrameWalker understands (e.g.
+//! `gimli::X86_64::register_name`).
+//!
+//! Supported operations are:
+//!
+//! * constants: `DW_OP_lit*`, `DW_OP_const*`, `DW_OP_addr`
+//! * registers: `DW_OP_breg*`, `DW_OP_bregx`, `DW_OP_call_frame_cfa`
+//! * memory: `DW_OP_deref`, `DW_OP_deref_size`
+//! * stack manipulation: `DW_OP_dup`, `DW_OP_drop`, `DW_OP_over`, `DW_OP_pick`,
+//!   `DW_OP_swap`, `DW_OP_rot`
+//! * arithmetic and logic: `DW_OP_abs`, `DW_OP_and`, `DW_OP_div`, `DW_OP_minus`,
+//!   `DW_OP_mod`, `DW_OP_mul`, `DW_OP_neg`, `DW_OP_not`, `DW_OP_or`, `DW_OP_plus`,
+//!   `DW_OP_plus_uconst`, `DW_OP_shl`, `DW_OP_shr`, `DW_OP_shra`, `DW_OP_xor`
+//! * control flow: `DW_OP_eq`, `DW_OP_ge`, `DW_OP_gt`, `DW_OP_le`, `DW_OP_lt`,
+//!   `DW_OP_ne`, `DW_OP_skip`, `DW_OP_bra`, `DW_OP_nop`
+//! * `DW_OP_stack_value`: terminates evaluation
+//! * `DW_OP_entry_value`: see below
+//!
+//! Anything else (typed operations, TLS, pieces, references to DIEs, ...)
+//! makes evaluation fail. These all require debuginfo we don't have, and
+//! shouldn't appear in CFI anyway.
+//!
+//! We don't know what registers held when the function was entered, so
+//! the operand of `DW_OP_entry_value` is evaluated against the callee's
+//! *current* registers. This is only correct if the registers involved
+//! haven't been modified since entry, which is usually what compilers are
+//! trying to describe when they emit it in CFI (e.g. an unclobbered `$sp`).
+//!
+//! Since expressions can contain loops, evaluation gives up after a fixed
+//! number of operations.
 
 use super::{CfiRules, StackInfoWin, WinStackThing};
 use crate::FrameWalker;
+use gimli::{Encoding, EndianSlice, LittleEndian, Operation, Reader, Register, UnitOffset};
 use log::{debug, trace};
 use std::collections::HashMap;
 use std::str::FromStr;
 
-pub fn walk_with_stack_cfi(
-    init: &CfiRules,
-    additional: &[CfiRules],
-    walker: &mut dyn FrameWalker,
-) -> Option<()> {
-    trace!("unwind: trying STACK CFI exprs");
-    trace!("unwind:   {}", init.rules);
-    for line in additional {
-        trace!("unwind:   {}", line.rules);
+/// The default for [`StackCfiEvaluator::set_max_deref_depth`][].
+pub const DEFAULT_MAX_CFI_DEREF_DEPTH: usize = 8;
+
+/// Evaluates STACK CFI rules, with some configurable limits.
+///
+/// [`walk_with_stack_cfi`][] uses the defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackCfiEvaluator {
+    max_deref_depth: usize,
+}
+
+/// Why a STACK CFI expression couldn't be evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CfiExprError {
+    /// The expression is malformed, or needs something that isn't available
+    /// (an unknown register, unreadable memory, the CFA, ...).
+    Invalid,
+    /// A value needed more nested `^` dereferences than the limit.
+    DerefTooDeep,
+}
+
+impl Default for StackCfiEvaluator {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    // First we must collect up all the `REG: EXPR` pairs in these lines.
-    // If a REG occurs twice, we prefer the one that comes later. This allows
-    // STACK CFI records to apply incremental updates to the instructions.
-    let mut exprs = HashMap::new();
-    parse_cfi_exprs(&init.rules, &mut exprs)?;
-    for line in additional {
-        parse_cfi_exprs(&line.rules, &mut exprs)?;
+impl StackCfiEvaluator {
+    /// An evaluator with the default limits.
+    pub fn new() -> Self {
+        StackCfiEvaluator {
+            max_deref_depth: DEFAULT_MAX_CFI_DEREF_DEPTH,
+        }
     }
-    trace!("unwind: STACK CFI parse successful");
-
-    // These two are special and *must* always be present
-    let cfa_expr = exprs.remove(&CfiReg::Cfa)?;
-    let ra_expr = exprs.remove(&CfiReg::Ra)?;
-    trace!("unwind: STACK CFI seems reasonable, evaluating");
-
-    // Evaluating the CFA cannot itself use the CFA
-    let cfa = eval_cfi_expr(cfa_expr, walker, None)?;
-    trace!("unwind: successfully evaluated .cfa (frame address)");
-    let ra = eval_cfi_expr(ra_expr, walker, Some(cfa))?;
-    trace!("unwind: successfully evaluated .ra (return address)");
-
-    walker.set_cfa(cfa)?;
-    walker.set_ra(ra)?;
-
-    for (reg, expr) in exprs {
-        if let CfiReg::Other(reg) = reg {
-            // If this eval fails, just don't emit this particular register
-            // and keep going on. It's fine to lose some general purpose regs,
-            // but make sure to clear it in case it would have been implicitly
-            // forwarded from the callee.
-            match eval_cfi_expr(expr, walker, Some(cfa)) {
-                Some(val) => {
-                    walker.set_caller_register(reg, val);
-                    trace!("unwind: successfully evaluated {}", reg);
-                }
-                None => {
-                    walker.clear_caller_register(reg);
-                    trace!(
-                        "unwind: optional register {} failed to evaluate, dropping it",
-                        reg
-                    );
+
+    /// Set how many `^` dereferences can be nested to compute a single value,
+    /// e.g. `.cfa 8 - ^ 16 + ^` needs 2.
+    ///
+    /// Rules that need more fail to evaluate. Defaults to
+    /// [`DEFAULT_MAX_CFI_DEREF_DEPTH`][].
+    pub fn set_max_deref_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_deref_depth = depth;
+        self
+    }
+
+    /// See [`walk_with_stack_cfi`][].
+    pub fn walk(
+        &self,
+        init: &CfiRules,
+        additional: &[CfiRules],
+        walker: &mut dyn FrameWalker,
+    ) -> Option<()> {
+        trace!("unwind: trying STACK CFI exprs");
+        trace!("unwind:   {}", init.rules);
+        for line in additional {
+            trace!("unwind:   {}", line.rules);
+        }
+
+        // First we must collect up all the `REG: EXPR` pairs in these lines.
+        // If a REG occurs twice, we prefer the one that comes later. This allows
+        // STACK CFI records to apply incremental updates to the instructions.
+        let mut exprs = HashMap::new();
+        parse_cfi_exprs(&init.rules, &mut exprs)?;
+        for line in additional {
+            parse_cfi_exprs(&line.rules, &mut exprs)?;
+        }
+        trace!("unwind: STACK CFI parse successful");
+
+        // These two are special and *must* always be present
+        let cfa_expr = exprs.remove(&CfiReg::Cfa)?;
+        let ra_expr = exprs.remove(&CfiReg::Ra)?;
+        trace!("unwind: STACK CFI seems reasonable, evaluating");
+
+        // Evaluating the CFA cannot itself use the CFA
+        let cfa = self.eval_expr(cfa_expr, walker, None).ok()?;
+        trace!("unwind: successfully evaluated .cfa (frame address)");
+        let ra = self.eval_expr(ra_expr, walker, Some(cfa)).ok()?;
+        trace!("unwind: successfully evaluated .ra (return address)");
+
+        walker.set_cfa(cfa)?;
+        walker.set_ra(ra)?;
+
+        for (reg, expr) in exprs {
+            if let CfiReg::Other(reg) = reg {
+                // If this eval fails, just don't emit this particular register
+                // and keep going on. It's fine to lose some general purpose regs,
+                // but make sure to clear it in case it would have been implicitly
+                // forwarded from the callee.
+                match self.eval_expr(expr, walker, Some(cfa)) {
+                    Ok(val) => {
+                        walker.set_caller_register(reg, val);
+                        trace!("unwind: successfully evaluated {}", reg);
+                    }
+                    Err(_) => {
+                        walker.clear_caller_register(reg);
+                        trace!(
+                            "unwind: optional register {} failed to evaluate, dropping it",
+                            reg
+                        );
+                    }
                 }
+            } else {
+                // All special registers should already have been removed??
+                unreachable!()
             }
-        } else {
-            // All special registers should already have been removed??
-            unreachable!()
         }
+
+        Some(())
     }
+}
 
-    Some(())
+pub fn walk_with_stack_cfi(
+    init: &CfiRules,
+    additional: &[CfiRules],
+    walker: &mut dyn FrameWalker,
+) -> Option<()> {
+    StackCfiEvaluator::new().walk(init, additional, walker)
 }
 
 fn parse_cfi_exprs<'a>(input: &'a str, output: &mut HashMap<CfiReg<'a>, &'a str>) -> Option<()> {
@@ -614,119 +726,412 @@ fn parse_cfi_exprs<'a>(input: &'a str, output: &mut HashMap<CfiReg<'a>, &'a str>
     Some(())
 }
 
-fn eval_cfi_expr(expr: &str, walker: &mut dyn FrameWalker, cfa: Option<u64>) -> Option<u64> {
-    // FIXME: this should be an ArrayVec or something, most exprs are simple.
-    let mut stack: Vec<u64> = Vec::new();
-    for token in expr.split_ascii_whitespace() {
-        match token {
-            // FIXME?: not sure what overflow/sign semantics are, but haven't run into
-            // something where it actually matters (I wouldn't expect it to come up
-            // normally?).
-            "+" => {
-                // Add
-                let rhs = stack.pop()?;
-                let lhs = stack.pop()?;
-                stack.push(lhs.wrapping_add(rhs));
-            }
-            "-" => {
-                // Subtract
-                let rhs = stack.pop()?;
-                let lhs = stack.pop()?;
-                stack.push(lhs.wrapping_sub(rhs));
+impl StackCfiEvaluator {
+    fn eval_expr(
+        &self,
+        expr: &str,
+        walker: &mut dyn FrameWalker,
+        cfa: Option<u64>,
+    ) -> Result<u64, CfiExprError> {
+        use CfiExprError::*;
+        // Every value is paired with how many nested derefs computed it.
+        // FIXME: this should be an ArrayVec or something, most exprs are simple.
+        let mut stack: Vec<(u64, usize)> = Vec::new();
+        for token in expr.split_ascii_whitespace() {
+            match token {
+                "+" | "-" | "*" | "/" | "%" | "@" => {
+                    // For binary operators the rhs is on top of the stack.
+                    let (rhs, rhs_depth) = stack.pop().ok_or(Invalid)?;
+                    let (lhs, lhs_depth) = stack.pop().ok_or(Invalid)?;
+                    let val = match token {
+                        // FIXME?: not sure what overflow/sign semantics are, but haven't run into
+                        // something where it actually matters (I wouldn't expect it to come up
+                        // normally?).
+                        "+" => lhs.wrapping_add(rhs),
+                        "-" => lhs.wrapping_sub(rhs),
+                        "*" => lhs.wrapping_mul(rhs),
+                        // Div by 0 fails.
+                        "/" => lhs.checked_div(rhs).ok_or(Invalid)?,
+                        "%" => lhs.checked_rem(rhs).ok_or(Invalid)?,
+                        _ => {
+                            // Align (truncate)
+                            if rhs == 0 || !rhs.is_power_of_two() {
+                                return Err(Invalid);
+                            }
+
+                            // ~Bit Magic Corner~
+                            //
+                            // A power of two has only one bit set (e.g. 4 is 0b100), and
+                            // subtracting 1 from that gets you all 1's below that bit (e.g. 0b011).
+                            // -1 is all 1's.
+                            //
+                            // So XORing -1 with (power_of_2 - 1) gets you all ones except
+                            // for the bits lower than the power of 2. ANDing that value
+                            // to a number consequently makes it a multiple of that power
+                            // of two (all the bits smaller than the power are cleared).
+                            lhs & (-1i64 as u64 ^ (rhs - 1))
+                        }
+                    };
+                    stack.push((val, lhs_depth.max(rhs_depth)));
+                }
+                "^" => {
+                    // Deref the value
+                    let (ptr, depth) = stack.pop().ok_or(Invalid)?;
+                    if depth >= self.max_deref_depth {
+                        debug!(
+                            "STACK CFI expression eval failed - more than {} nested derefs: {}",
+                            self.max_deref_depth, expr
+                        );
+                        return Err(DerefTooDeep);
+                    }
+                    let val = walker.get_register_at_address(ptr).ok_or(Invalid)?;
+                    stack.push((val, depth + 1));
+                }
+                ".cfa" => {
+                    // Push the CFA. Note the CFA shouldn't be used to compute
+                    // itself, so this fails if that happens.
+                    stack.push((cfa.ok_or(Invalid)?, 0));
+                }
+                ".undef" => {
+                    // This register is explicitly undefined!
+                    return Err(Invalid);
+                }
+                _ => {
+                    // More complex cases
+                    let val = if let Some((_, reg)) = token.split_once('$') {
+                        // Push a register
+                        walker.get_callee_register(reg).ok_or(Invalid)?
+                    } else if let Ok(value) = i64::from_str(token) {
+                        // Push a constant
+                        // FIXME?: We do everything in wrapping arithmetic, so it's
+                        // probably fine to squash i64's into u64's, but it seems sketchy?
+                        // Division/remainder in particular seem concerning, but also
+                        // it would be surprising to see negatives for those..?
+                        value as u64
+                    } else if let Some(reg) = walker.get_callee_register(token) {
+                        // Maybe the register just didn't have a $ prefix?
+                        // (seems to be how ARM syntax works).
+                        reg
+                    } else {
+                        // Unknown expr
+                        debug!(
+                            "STACK CFI expression eval failed - unknown token: {}",
+                            token
+                        );
+                        return Err(Invalid);
+                    };
+                    stack.push((val, 0));
+                }
             }
-            "*" => {
-                // Multiply
-                let rhs = stack.pop()?;
-                let lhs = stack.pop()?;
-                stack.push(lhs.wrapping_mul(rhs));
+        }
+
+        match stack[..] {
+            [(val, _)] => Ok(val),
+            _ => Err(Invalid),
+        }
+    }
+}
+
+/// The maximum number of operations [eval_dwarf_expr][] will evaluate
+/// before giving up (expressions can loop).
+const MAX_DWARF_EXPR_OPS: usize = 1000;
+
+/// Evaluates a DWARF expression (in little-endian bytecode form) and returns
+/// the value left on top of the stack.
+///
+/// `register_name` maps DWARF register numbers to names the walker
+/// recognizes, and `cfa` is the value to use for `DW_OP_call_frame_cfa`
+/// (`None` if the CFA is what's being computed). For `DW_CFA_expression`
+/// rules the result is the address the register is saved at, for
+/// `DW_CFA_val_expression` it's the register's value.
+///
+/// See the [module-level docs](self#dwarf-expressions) for details.
+pub fn eval_dwarf_expr(
+    bytecode: &[u8],
+    encoding: Encoding,
+    register_name: fn(Register) -> Option<&'static str>,
+    walker: &mut dyn FrameWalker,
+    cfa: Option<u64>,
+) -> Option<u64> {
+    // Values are stored in a u64, so address sizes beyond that (or zero,
+    // which is nonsense) can't be evaluated.
+    if encoding.address_size == 0 || encoding.address_size > 8 {
+        debug!(
+            "DWARF expression eval failed - bad address size: {}",
+            encoding.address_size
+        );
+        return None;
+    }
+    let mut evaluator = DwarfExprEvaluator {
+        encoding,
+        register_name,
+        walker,
+        cfa,
+        ops_remaining: MAX_DWARF_EXPR_OPS,
+    };
+    let result = evaluator.eval(bytecode);
+    if result.is_none() {
+        debug!("DWARF expression eval failed: {:02x?}", bytecode);
+    }
+    result
+}
+
+struct DwarfExprEvaluator<'a> {
+    encoding: Encoding,
+    register_name: fn(Register) -> Option<&'static str>,
+    walker: &'a mut dyn FrameWalker,
+    cfa: Option<u64>,
+    ops_remaining: usize,
+}
+
+impl<'a> DwarfExprEvaluator<'a> {
+    fn bits(&self) -> u64 {
+        self.encoding.address_size as u64 * 8
+    }
+
+    /// Truncate a value to the address size.
+    fn truncate(&self, val: u64) -> u64 {
+        let bits = self.bits();
+        if bits >= 64 {
+            val
+        } else {
+            val & ((1 << bits) - 1)
+        }
+    }
+
+    /// Interpret an address-sized value as signed.
+    fn signed(&self, val: u64) -> i64 {
+        let shift = 64u64.saturating_sub(self.bits());
+        ((val << shift) as i64) >> shift
+    }
+
+    fn callee_register(&self, register: Register) -> Option<u64> {
+        let name = (self.register_name)(register)?;
+        self.walker.get_callee_register(name)
+    }
+
+    fn eval(&mut self, bytecode: &[u8]) -> Option<u64> {
+        let mut stack: Vec<u64> = Vec::new();
+        let mut bytes = EndianSlice::new(bytecode, LittleEndian);
+        while !bytes.is_empty() {
+            self.ops_remaining = self.ops_remaining.checked_sub(1)?;
+            let op = Operation::parse(&mut bytes, self.encoding).ok()?;
+            match op {
+                Operation::UnsignedConstant { value } | Operation::Address { address: value } => {
+                    stack.push(self.truncate(value));
+                }
+                Operation::SignedConstant { value } => {
+                    stack.push(self.truncate(value as u64));
+                }
+                Operation::RegisterOffset {
+                    register,
+                    offset,
+                    base_type: UnitOffset(0),
+                } => {
+                    // DW_OP_breg*
+                    let val = self.callee_register(register)?;
+                    stack.push(self.truncate(val.wrapping_add(offset as u64)));
+                }
+                Operation::CallFrameCFA => {
+                    // The CFA shouldn't be used to compute itself.
+                    stack.push(self.cfa?);
+                }
+                Operation::Deref {
+                    size,
+                    space: false,
+                    base_type: UnitOffset(0),
+                } => {
+                    // The walker can only load register-sized values, so
+                    // smaller loads are done by masking (we assume little-endian).
+                    if size == 0 || size > self.encoding.address_size {
+                        return None;
+                    }
+                    let addr = stack.pop()?;
+                    let val = self.walker.get_register_at_address(addr)?;
+                    let val = if size >= 8 {
+                        val
+                    } else {
+                        val & ((1 << (size as u64 * 8)) - 1)
+                    };
+                    stack.push(self.truncate(val));
+                }
+                Operation::Drop => {
+                    stack.pop()?;
+                }
+                Operation::Pick { index } => {
+                    // DW_OP_dup, DW_OP_over, and DW_OP_pick
+                    let idx = stack.len().checked_sub(index as usize + 1)?;
+                    stack.push(stack[idx]);
+                }
+                Operation::Swap => {
+                    let top = stack.pop()?;
+                    let second = stack.pop()?;
+                    stack.push(top);
+                    stack.push(second);
+                }
+                Operation::Rot => {
+                    // The top becomes the third entry, shifting the other two up.
+                    let top = stack.pop()?;
+                    let second = stack.pop()?;
+                    let third = stack.pop()?;
+                    stack.push(top);
+                    stack.push(third);
+                    stack.push(second);
+                }
+                Operation::Abs => {
+                    let val = self.signed(stack.pop()?);
+                    stack.push(self.truncate(val.wrapping_abs() as u64));
+                }
+                Operation::Neg => {
+                    let val = self.signed(stack.pop()?);
+                    stack.push(self.truncate(val.wrapping_neg() as u64));
+                }
+                Operation::Not => {
+                    let val = stack.pop()?;
+                    stack.push(self.truncate(!val));
+                }
+                Operation::PlusConstant { value } => {
+                    let val = stack.pop()?;
+                    stack.push(self.truncate(val.wrapping_add(value)));
+                }
+                Operation::And
+                | Operation::Or
+                | Operation::Xor
+                | Operation::Plus
+                | Operation::Minus
+                | Operation::Mul
+                | Operation::Div
+                | Operation::Mod
+                | Operation::Shl
+                | Operation::Shr
+                | Operation::Shra => {
+                    let rhs = stack.pop()?;
+                    let lhs = stack.pop()?;
+                    let val = self.eval_binary_op(&op, lhs, rhs)?;
+                    stack.push(self.truncate(val));
+                }
+                Operation::Eq
+                | Operation::Ne
+                | Operation::Ge
+                | Operation::Gt
+                | Operation::Le
+                | Operation::Lt => {
+                    let rhs = self.signed(stack.pop()?);
+                    let lhs = self.signed(stack.pop()?);
+                    let result = match op {
+                        Operation::Eq => lhs == rhs,
+                        Operation::Ne => lhs != rhs,
+                        Operation::Ge => lhs >= rhs,
+                        Operation::Gt => lhs > rhs,
+                        Operation::Le => lhs <= rhs,
+                        _ => lhs < rhs,
+                    };
+                    stack.push(result as u64);
+                }
+                Operation::Bra { target } => {
+                    if stack.pop()? != 0 {
+                        bytes = Self::jump(bytecode, bytes, target)?;
+                    }
+                }
+                Operation::Skip { target } => {
+                    bytes = Self::jump(bytecode, bytes, target)?;
+                }
+                Operation::Nop => {}
+                Operation::StackValue => {
+                    // This just says the result is a value and not a location,
+                    // it's up to the caller to know which they wanted. It must
+                    // be the last operation.
+                    if !bytes.is_empty() {
+                        return None;
+                    }
+                }
+                Operation::EntryValue { expression } => {
+                    // See the module docs for why this is just evaluated "now".
+                    let mut sub_expr = expression;
+                    let val = match Operation::parse(&mut sub_expr, self.encoding).ok()? {
+                        // The common case: DW_OP_entry_value(DW_OP_regN)
+                        Operation::Register { register } if sub_expr.is_empty() => {
+                            self.callee_register(register)?
+                        }
+                        _ => self.eval(expression.slice())?,
+                    };
+                    stack.push(val);
+                }
+                _ => {
+                    // Register locations, pieces, typed values, TLS, DIE references...
+                    debug!("DWARF expression eval failed - unsupported op: {:?}", op);
+                    return None;
+                }
             }
-            "/" => {
-                // Divide
-                let rhs = stack.pop()?;
-                let lhs = stack.pop()?;
+        }
+
+        stack.pop()
+    }
+
+    fn eval_binary_op(
+        &self,
+        op: &Operation<EndianSlice<'_, LittleEndian>>,
+        lhs: u64,
+        rhs: u64,
+    ) -> Option<u64> {
+        let val = match op {
+            Operation::And => lhs & rhs,
+            Operation::Or => lhs | rhs,
+            Operation::Xor => lhs ^ rhs,
+            Operation::Plus => lhs.wrapping_add(rhs),
+            Operation::Minus => lhs.wrapping_sub(rhs),
+            Operation::Mul => lhs.wrapping_mul(rhs),
+            Operation::Div => {
+                let rhs = self.signed(rhs);
                 if rhs == 0 {
                     // Div by 0
                     return None;
                 }
-                stack.push(lhs.wrapping_div(rhs));
+                self.signed(lhs).wrapping_div(rhs) as u64
             }
-            "%" => {
-                // Remainder
-                let rhs = stack.pop()?;
-                let lhs = stack.pop()?;
+            Operation::Mod => {
                 if rhs == 0 {
                     // Div by 0
                     return None;
                 }
-                stack.push(lhs.wrapping_rem(rhs));
+                lhs % rhs
             }
-            "@" => {
-                // Align (truncate)
-                let rhs = stack.pop()?;
-                let lhs = stack.pop()?;
-
-                if rhs == 0 || !rhs.is_power_of_two() {
-                    return None;
+            Operation::Shl => {
+                if rhs >= self.bits() {
+                    0
+                } else {
+                    lhs << rhs
                 }
-
-                // ~Bit Magic Corner~
-                //
-                // A power of two has only one bit set (e.g. 4 is 0b100), and
-                // subtracting 1 from that gets you all 1's below that bit (e.g. 0b011).
-                // -1 is all 1's.
-                //
-                // So XORing -1 with (power_of_2 - 1) gets you all ones except
-                // for the bits lower than the power of 2. ANDing that value
-                // to a number consequently makes it a multiple of that power
-                // of two (all the bits smaller than the power are cleared).
-                stack.push(lhs & (-1i64 as u64 ^ (rhs - 1)))
-            }
-            "^" => {
-                // Deref the value
-                let ptr = stack.pop()?;
-                stack.push(walker.get_register_at_address(ptr)?);
             }
-            ".cfa" => {
-                // Push the CFA. Note the CFA shouldn't be used to compute
-                // itself, so this returns None if that happens.
-                stack.push(cfa?);
-            }
-            ".undef" => {
-                // This register is explicitly undefined!
-                return None;
-            }
-            _ => {
-                // More complex cases
-                if let Some((_, reg)) = token.split_once('$') {
-                    // Push a register
-                    stack.push(walker.get_callee_register(reg)?);
-                } else if let Ok(value) = i64::from_str(token) {
-                    // Push a constant
-                    // FIXME?: We do everything in wrapping arithmetic, so it's
-                    // probably fine to squash i64's into u64's, but it seems sketchy?
-                    // Division/remainder in particular seem concerning, but also
-                    // it would be surprising to see negatives for those..?
-                    stack.push(value as u64)
-                } else if let Some(reg) = walker.get_callee_register(token) {
-                    // Maybe the register just didn't have a $ prefix?
-                    // (seems to be how ARM syntax works).
-                    stack.push(reg);
+            Operation::Shr => {
+                if rhs >= self.bits() {
+                    0
                 } else {
-                    // Unknown expr
-                    debug!(
-                        "STACK CFI expression eval failed - unknown token: {}",
-                        token
-                    );
-                    return None;
+                    lhs >> rhs
                 }
             }
-        }
+            Operation::Shra => {
+                // Shifting by the full width or more just leaves the sign bits.
+                let shift = rhs.min(63);
+                (self.signed(lhs) >> shift) as u64
+            }
+            _ => unreachable!(),
+        };
+        Some(val)
     }
 
-    if stack.len() == 1 {
-        stack.pop()
-    } else {
-        None
+    /// Apply a `DW_OP_skip`/`DW_OP_bra` offset (relative to the end of the
+    /// operation) to the remaining bytecode.
+    fn jump<'b>(
+        bytecode: &'b [u8],
+        remaining: EndianSlice<'b, LittleEndian>,
+        target: i16,
+    ) -> Option<EndianSlice<'b, LittleEndian>> {
+        let pos = (bytecode.len() - remaining.len()) as i64 + target as i64;
+        if pos < 0 || pos as usize > bytecode.len() {
+            return None;
+        }
+        Some(EndianSlice::new(&bytecode[pos as usize..], LittleEndian))
     }
 }
 
@@ -763,10 +1168,10 @@ fn eval_win_expr(expr: &str, info: &StackInfoWin, walker: &mut dyn FrameWalker)
         // (so the caller's $ebp was pushed right after the return address,
         // and now $ebp points to that.)
         trace!("unwind: program used @ operator, using $ebp instead of $esp for return addr");
-        callee_ebp + 4
+        callee_ebp.wrapping_add(4)
     } else {
         // $esp should be reasonable, get the return address from that
-        callee_esp + frame_size
+        callee_esp.wrapping_add(frame_size)
     };
 
     trace!(
@@ -925,7 +1330,10 @@ fn eval_win_expr(expr: &str, info: &StackInfoWin, walker: &mut dyn FrameWalker)
 }
 
 fn win_frame_size(info: &StackInfoWin, grand_callee_param_size: u32) -> u32 {
-    info.local_size + info.saved_register_size + grand_callee_param_size
+    // These all come from the symbol file, so don't trust them not to overflow.
+    info.local_size
+        .wrapping_add(info.saved_register_size)
+        .wrapping_add(grand_callee_param_size)
 }
 
 enum WinVal<'a> {
@@ -977,15 +1385,16 @@ pub fn walk_with_stack_win_fpo(info: &StackInfoWin, walker: &mut dyn FrameWalker
         let frame_size = win_frame_size(info, grand_callee_param_size) as u64;
 
         let callee_esp = walker.get_callee_register("esp")?;
-        let eip_address = callee_esp + frame_size;
+        let eip_address = callee_esp.checked_add(frame_size)?;
         let caller_eip = walker.get_register_at_address(eip_address)?;
-        let caller_esp = callee_esp + frame_size + 4;
+        let caller_esp = eip_address.checked_add(4)?;
 
         trace!("unwind: found caller $eip and $esp");
 
         let caller_ebp = if allocates_base_pointer {
             let ebp_address =
-                callee_esp + grand_callee_param_size as u64 + info.saved_register_size as u64 - 8;
+                (callee_esp + grand_callee_param_size as u64 + info.saved_register_size as u64)
+                    .checked_sub(8)?;
             walker.get_register_at_address(ebp_address)?
         } else {
             // Per Breakpad: We also propagate %ebx through, as it is commonly unmodifed after
@@ -1013,7 +1422,7 @@ pub fn walk_with_stack_win_fpo(info: &StackInfoWin, walker: &mut dyn FrameWalker
 
 /// STACK WIN doesn't want implicit register forwarding
 fn clear_stack_win_caller_registers(walker: &mut dyn FrameWalker) {
-    let output_regs = ["$eip", "$esp", "$ebp", "$ebx", "$esi", "$edi"];
+    let output_regs = ["eip", "esp", "ebp", "ebx", "esi", "edi"];
     for reg in output_regs {
         walker.clear_caller_register(reg);
     }
@@ -1022,16 +1431,24 @@ fn clear_stack_win_caller_registers(walker: &mut dyn FrameWalker) {
 #[cfg(test)]
 mod test {
     use super::super::types::{CfiRules, StackInfoWin, WinStackThing};
-    use super::{eval_win_expr, walk_with_stack_cfi};
+    use super::{
+        eval_win_expr, walk_with_stack_cfi, walk_with_stack_win_fpo, CfiExprError,
+        StackCfiEvaluator,
+    };
     use crate::FrameWalker;
     use std::collections::HashMap;
 
+    fn eval_cfi_expr(expr: &str, walker: &mut dyn FrameWalker, cfa: Option<u64>) -> Option<u64> {
+        StackCfiEvaluator::new().eval_expr(expr, walker, cfa).ok()
+    }
+
     // Eugh, need this to memoize register names to static
-    static STATIC_REGS: [&str; 14] = [
+    static STATIC_REGS: [&str; 18] = [
         "cfa", "ra", "esp", "eip", "ebp", "eax", "ebx", "rsp", "rip", "rbp", "rax", "rbx", "x11",
-        "x12",
+        "x12", "x19", "x20", "x21", "x22",
     ];
 
+    #[derive(Debug)]
     struct TestFrameWalker<Reg> {
         instruction: Reg,
         grand_callee_param_size: u32,
@@ -1086,7 +1503,7 @@ mod test {
         fn get_register_at_address(&self, address: u64) -> Option<u64> {
             let addr = address as usize;
             self.stack
-                .get(addr..addr + Reg::BYTES)
+                .get(addr..addr.checked_add(Reg::BYTES)?)
                 .map(|slice| Reg::from_bytes(slice).into_u64())
         }
         /// Get the value of a register from the callee's frame.
@@ -1770,4 +2187,872 @@ mod test {
         assert_eq!(walker.caller_regs["x11"], 16);
         assert_eq!(walker.caller_regs["x12"], 1608);
     }
+
+    #[test]
+    fn test_stack_cfi_register_bases() {
+        // Register rules can compute with the callee's registers, not just copy them.
+        const SAVED: u64 = 0x0123_4567_89AB_CDEF;
+        let input = vec![("sp", 64u64), ("x30", 32), ("x0", 100), ("x1", 200)]
+            .into_iter()
+            .collect();
+        let mut stack = vec![0; 1600];
+        stack[200..208].copy_from_slice(&SAVED.to_le_bytes());
+        stack[308..316].copy_from_slice(&SAVED.to_le_bytes());
+
+        let mut walker = TestFrameWalker::new(stack, input);
+        let (init, additional) = build_cfi_rules(
+            ".cfa: sp 16 + .ra: x30 x19: x0 8 + x20: x1 ^ x21: x0 x1 + 8 + ^ x22: x0",
+            &[],
+        );
+        walk_with_stack_cfi(&init, &additional, &mut walker).unwrap();
+
+        assert_eq!(walker.caller_regs.len(), 6);
+        assert_eq!(walker.caller_regs["cfa"], 80);
+        assert_eq!(walker.caller_regs["ra"], 32);
+        assert_eq!(walker.caller_regs["x19"], 108);
+        assert_eq!(walker.caller_regs["x20"], SAVED);
+        assert_eq!(walker.caller_regs["x21"], SAVED);
+        assert_eq!(walker.caller_regs["x22"], 100);
+
+        // An unknown base register only drops that rule.
+        walker.caller_regs.clear();
+        let (init, additional) = build_cfi_rules(".cfa: sp 16 + .ra: x30 x19: x9 8 +", &[]);
+        walk_with_stack_cfi(&init, &additional, &mut walker).unwrap();
+        assert_eq!(walker.caller_regs.len(), 2);
+    }
+
+    #[test]
+    fn test_stack_cfi_nested_deref() {
+        // A function that saved its registers in a save area, and only
+        // stored a pointer to that area in its frame:
+        //
+        // cfa = callee_sp + 32
+        // save_area = *(cfa - 8)
+        // ra = *(save_area + 16)
+        // x12 = *(*(cfa - 8) + 8)
+        let input = vec![("sp", 64u64), ("pc", 1600)].into_iter().collect();
+        let mut stack = vec![0; 1600];
+
+        const CFA: usize = 64 + 32;
+        const SAVE_AREA: usize = 400;
+        const RA: u64 = 0xFA1E_F2E6_A2DF_2B68;
+        const X12: u64 = 0xB3EF_04CE_4321_FE2A;
+
+        stack[CFA - 8..CFA].copy_from_slice(&(SAVE_AREA as u64).to_le_bytes());
+        stack[SAVE_AREA + 8..SAVE_AREA + 16].copy_from_slice(&X12.to_le_bytes());
+        stack[SAVE_AREA + 16..SAVE_AREA + 24].copy_from_slice(&RA.to_le_bytes());
+
+        let mut walker = TestFrameWalker::new(stack, input);
+        let (init, additional) = build_cfi_rules(
+            ".cfa: sp 32 + .ra: .cfa 8 - ^ 16 + ^ x12: .cfa 8 - ^ 8 + ^",
+            &[],
+        );
+        walk_with_stack_cfi(&init, &additional, &mut walker).unwrap();
+
+        assert_eq!(walker.caller_regs.len(), 3);
+        assert_eq!(walker.caller_regs["cfa"], CFA as u64);
+        assert_eq!(walker.caller_regs["ra"], RA);
+        assert_eq!(walker.caller_regs["x12"], X12);
+
+        // Each level has to be readable (RA is not a valid pointer).
+        walker.caller_regs.clear();
+        let (init, additional) = build_cfi_rules(".cfa: sp 32 + .ra: .cfa 8 - ^ 16 + ^ ^", &[]);
+        assert!(walk_with_stack_cfi(&init, &additional, &mut walker).is_none());
+    }
+
+    #[test]
+    fn test_stack_cfi_deref_depth_limit() {
+        // A linked list of pointers: the value at address 8*i is 8*(i+1).
+        let input = vec![("sp", 0u64), ("pc", 1600)].into_iter().collect();
+        let stack = (1..=16u64).flat_map(|i| (i * 8).to_le_bytes()).collect();
+        let mut walker = TestFrameWalker::new(stack, input);
+        let derefs = |depth: usize| format!("sp{}", " ^".repeat(depth));
+
+        let evaluator = StackCfiEvaluator::new();
+        assert_eq!(evaluator.eval_expr(&derefs(8), &mut walker, None), Ok(64));
+        assert_eq!(
+            evaluator.eval_expr(&derefs(9), &mut walker, None),
+            Err(CfiExprError::DerefTooDeep)
+        );
+        // Derefs that aren't nested don't add up.
+        let expr = format!("{} {} +", derefs(8), derefs(8));
+        assert_eq!(evaluator.eval_expr(&expr, &mut walker, None), Ok(128));
+
+        let rules =
+            |depth: usize| build_cfi_rules(&format!(".cfa: sp .ra: {}", derefs(depth)), &[]);
+        let (init, additional) = rules(8);
+        walk_with_stack_cfi(&init, &additional, &mut walker).unwrap();
+        assert_eq!(walker.caller_regs["ra"], 64);
+        walker.caller_regs.clear();
+        let (init, additional) = rules(9);
+        assert!(walk_with_stack_cfi(&init, &additional, &mut walker).is_none());
+
+        // The limit can be raised...
+        let mut evaluator = StackCfiEvaluator::new();
+        evaluator.set_max_deref_depth(9);
+        evaluator.walk(&init, &additional, &mut walker).unwrap();
+        assert_eq!(walker.caller_regs["ra"], 72);
+
+        // ...or lowered.
+        evaluator.set_max_deref_depth(1);
+        assert_eq!(evaluator.eval_expr(&derefs(1), &mut walker, None), Ok(8));
+        assert_eq!(
+            evaluator.eval_expr(&derefs(2), &mut walker, None),
+            Err(CfiExprError::DerefTooDeep)
+        );
+    }
+
+    mod dwarf {
+        use super::super::eval_dwarf_expr;
+        use super::*;
+        use gimli::constants::*;
+        use gimli::{Encoding, Format, Register};
+
+        fn dwarf_encoding(address_size: u8) -> Encoding {
+            Encoding {
+                address_size,
+                format: Format::Dwarf32,
+                version: 4,
+            }
+        }
+
+        /// Like gimli::X86_64::register_name, but with the names our walkers use.
+        fn x86_64_register_name(register: Register) -> Option<&'static str> {
+            match register.0 {
+                16 => Some("rip"),
+                _ => gimli::X86_64::register_name(register),
+            }
+        }
+
+        fn eval_dwarf64(
+            bytecode: &[u8],
+            walker: &mut TestFrameWalker<u64>,
+            cfa: Option<u64>,
+        ) -> Option<u64> {
+            eval_dwarf_expr(
+                bytecode,
+                dwarf_encoding(8),
+                x86_64_register_name,
+                walker,
+                cfa,
+            )
+        }
+
+        fn eval_dwarf32(bytecode: &[u8], walker: &mut TestFrameWalker<u32>) -> Option<u64> {
+            eval_dwarf_expr(
+                bytecode,
+                dwarf_encoding(4),
+                gimli::X86::register_name,
+                walker,
+                None,
+            )
+        }
+
+        #[test]
+        fn test_dwarf_expr_constants() {
+            let mut walker = TestFrameWalker::<u64>::new(vec![], HashMap::new());
+            let eval = |bytecode: &[u8], walker: &mut TestFrameWalker<u64>| {
+                eval_dwarf64(bytecode, walker, None)
+            };
+
+            assert_eq!(eval(&[DW_OP_lit0.0], &mut walker), Some(0));
+            assert_eq!(eval(&[DW_OP_lit31.0], &mut walker), Some(31));
+            assert_eq!(eval(&[DW_OP_const1u.0, 0xff], &mut walker), Some(0xff));
+            assert_eq!(eval(&[DW_OP_const1s.0, 0xff], &mut walker), Some(!0));
+            assert_eq!(
+                eval(&[DW_OP_const2u.0, 0x34, 0x12], &mut walker),
+                Some(0x1234)
+            );
+            assert_eq!(
+                eval(&[DW_OP_const2s.0, 0xfe, 0xff], &mut walker),
+                Some(-2i64 as u64)
+            );
+            assert_eq!(
+                eval(&[DW_OP_const4u.0, 0x78, 0x56, 0x34, 0x12], &mut walker),
+                Some(0x1234_5678)
+            );
+            assert_eq!(
+                eval(&[DW_OP_const4s.0, 0xfd, 0xff, 0xff, 0xff], &mut walker),
+                Some(-3i64 as u64)
+            );
+            let mut const8 = vec![DW_OP_const8u.0];
+            const8.extend_from_slice(&0x0123_4567_89ab_cdefu64.to_le_bytes());
+            assert_eq!(eval(&const8, &mut walker), Some(0x0123_4567_89ab_cdef));
+            let mut addr = vec![DW_OP_addr.0];
+            addr.extend_from_slice(&0xfeed_f00du64.to_le_bytes());
+            assert_eq!(eval(&addr, &mut walker), Some(0xfeed_f00d));
+            assert_eq!(
+                eval(&[DW_OP_constu.0, 0xe5, 0x8e, 0x26], &mut walker),
+                Some(624485)
+            );
+            assert_eq!(
+                eval(&[DW_OP_consts.0, 0xc0, 0xbb, 0x78], &mut walker),
+                Some(-123456i64 as u64)
+            );
+
+            // The result is simply the top of the stack.
+            assert_eq!(eval(&[DW_OP_lit1.0, DW_OP_lit2.0], &mut walker), Some(2));
+        }
+
+        #[test]
+        fn test_dwarf_expr_stack_ops() {
+            let mut walker = TestFrameWalker::<u64>::new(vec![], HashMap::new());
+            let eval = |bytecode: &[u8], walker: &mut TestFrameWalker<u64>| {
+                eval_dwarf64(bytecode, walker, None)
+            };
+
+            // Every test is "push 1 2 3, do something, compute 100*a + 10*b + c"
+            // of the remaining top three entries, to check their order.
+            let check = |ops: &[u8], walker: &mut TestFrameWalker<u64>| {
+                let mut bytecode = vec![DW_OP_lit1.0, DW_OP_lit2.0, DW_OP_lit3.0];
+                bytecode.extend_from_slice(ops);
+                bytecode.extend_from_slice(&[
+                    DW_OP_rot.0,
+                    DW_OP_rot.0,
+                    DW_OP_const1u.0,
+                    100,
+                    DW_OP_mul.0,
+                    DW_OP_rot.0,
+                    DW_OP_swap.0,
+                    DW_OP_lit10.0,
+                    DW_OP_mul.0,
+                    DW_OP_plus.0,
+                    DW_OP_plus.0,
+                ]);
+                eval(&bytecode, walker)
+            };
+
+            assert_eq!(check(&[], &mut walker), Some(123));
+            assert_eq!(check(&[DW_OP_dup.0], &mut walker), Some(233));
+            assert_eq!(check(&[DW_OP_drop.0, DW_OP_lit4.0], &mut walker), Some(124));
+            assert_eq!(check(&[DW_OP_over.0], &mut walker), Some(232));
+            assert_eq!(check(&[DW_OP_pick.0, 2], &mut walker), Some(231));
+            assert_eq!(check(&[DW_OP_swap.0], &mut walker), Some(132));
+            assert_eq!(check(&[DW_OP_rot.0], &mut walker), Some(312));
+            assert_eq!(check(&[DW_OP_nop.0], &mut walker), Some(123));
+
+            // Not enough values
+            assert_eq!(eval(&[DW_OP_drop.0], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_dup.0], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_lit1.0, DW_OP_over.0], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_lit1.0, DW_OP_pick.0, 1], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_lit1.0, DW_OP_swap.0], &mut walker), None);
+            assert_eq!(
+                eval(&[DW_OP_lit1.0, DW_OP_lit2.0, DW_OP_rot.0], &mut walker),
+                None
+            );
+            assert_eq!(eval(&[DW_OP_lit1.0, DW_OP_drop.0], &mut walker), None);
+            assert_eq!(eval(&[], &mut walker), None);
+        }
+
+        #[test]
+        fn test_dwarf_expr_arithmetic() {
+            let mut walker = TestFrameWalker::<u64>::new(vec![], HashMap::new());
+            let binop = |lhs: i8, rhs: i8, op: DwOp, walker: &mut TestFrameWalker<u64>| {
+                eval_dwarf64(
+                    &[DW_OP_const1s.0, lhs as u8, DW_OP_const1s.0, rhs as u8, op.0],
+                    walker,
+                    None,
+                )
+            };
+            let unop = |val: i8, op: DwOp, walker: &mut TestFrameWalker<u64>| {
+                eval_dwarf64(&[DW_OP_const1s.0, val as u8, op.0], walker, None)
+            };
+
+            assert_eq!(binop(7, 5, DW_OP_plus, &mut walker), Some(12));
+            assert_eq!(binop(7, 5, DW_OP_minus, &mut walker), Some(2));
+            assert_eq!(binop(5, 7, DW_OP_minus, &mut walker), Some(-2i64 as u64));
+            assert_eq!(binop(7, 5, DW_OP_mul, &mut walker), Some(35));
+            assert_eq!(binop(0b1100, 0b1010, DW_OP_and, &mut walker), Some(0b1000));
+            assert_eq!(binop(0b1100, 0b1010, DW_OP_or, &mut walker), Some(0b1110));
+            assert_eq!(binop(0b1100, 0b1010, DW_OP_xor, &mut walker), Some(0b0110));
+            assert_eq!(binop(3, 4, DW_OP_shl, &mut walker), Some(48));
+            assert_eq!(binop(3, 64, DW_OP_shl, &mut walker), Some(0));
+            assert_eq!(binop(48, 4, DW_OP_shr, &mut walker), Some(3));
+            assert_eq!(binop(-16, 60, DW_OP_shr, &mut walker), Some(0xf));
+            assert_eq!(binop(-16, 64, DW_OP_shr, &mut walker), Some(0));
+            assert_eq!(binop(-16, 2, DW_OP_shra, &mut walker), Some(-4i64 as u64));
+            assert_eq!(binop(-16, 100, DW_OP_shra, &mut walker), Some(!0));
+            assert_eq!(binop(16, 100, DW_OP_shra, &mut walker), Some(0));
+
+            // div is signed, mod is unsigned
+            assert_eq!(binop(7, 2, DW_OP_div, &mut walker), Some(3));
+            assert_eq!(binop(-7, 2, DW_OP_div, &mut walker), Some(-3i64 as u64));
+            assert_eq!(binop(7, 0, DW_OP_div, &mut walker), None);
+            assert_eq!(binop(7, 4, DW_OP_mod, &mut walker), Some(3));
+            assert_eq!(binop(-1, 16, DW_OP_mod, &mut walker), Some(15));
+            assert_eq!(binop(7, 0, DW_OP_mod, &mut walker), None);
+
+            assert_eq!(unop(-5, DW_OP_abs, &mut walker), Some(5));
+            assert_eq!(unop(5, DW_OP_abs, &mut walker), Some(5));
+            assert_eq!(unop(5, DW_OP_neg, &mut walker), Some(-5i64 as u64));
+            assert_eq!(unop(-5, DW_OP_neg, &mut walker), Some(5));
+            assert_eq!(unop(0, DW_OP_not, &mut walker), Some(!0));
+            assert_eq!(
+                eval_dwarf64(
+                    &[DW_OP_lit5.0, DW_OP_plus_uconst.0, 0x80, 0x01],
+                    &mut walker,
+                    None
+                ),
+                Some(133)
+            );
+
+            // Comparisons are signed
+            assert_eq!(binop(1, 1, DW_OP_eq, &mut walker), Some(1));
+            assert_eq!(binop(1, 2, DW_OP_eq, &mut walker), Some(0));
+            assert_eq!(binop(1, 2, DW_OP_ne, &mut walker), Some(1));
+            assert_eq!(binop(1, 1, DW_OP_ne, &mut walker), Some(0));
+            assert_eq!(binop(-1, 1, DW_OP_lt, &mut walker), Some(1));
+            assert_eq!(binop(1, -1, DW_OP_lt, &mut walker), Some(0));
+            assert_eq!(binop(1, 1, DW_OP_le, &mut walker), Some(1));
+            assert_eq!(binop(2, 1, DW_OP_le, &mut walker), Some(0));
+            assert_eq!(binop(1, -1, DW_OP_gt, &mut walker), Some(1));
+            assert_eq!(binop(1, 1, DW_OP_gt, &mut walker), Some(0));
+            assert_eq!(binop(1, 1, DW_OP_ge, &mut walker), Some(1));
+            assert_eq!(binop(-2, 1, DW_OP_ge, &mut walker), Some(0));
+
+            // Not enough values
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_lit1.0, DW_OP_plus.0], &mut walker, None),
+                None
+            );
+            assert_eq!(eval_dwarf64(&[DW_OP_neg.0], &mut walker, None), None);
+        }
+
+        #[test]
+        fn test_dwarf_expr_32bit() {
+            // Values should be truncated and sign-extended at 32 bits.
+            let input = vec![("esp", 0xffff_fff0u32)].into_iter().collect();
+            let mut walker = TestFrameWalker::new(vec![0; 16], input);
+
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_breg4.0, 0x20], &mut walker),
+                Some(0x10)
+            );
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_const1s.0, 0xff], &mut walker),
+                Some(0xffff_ffff)
+            );
+            assert_eq!(
+                eval_dwarf32(
+                    &[DW_OP_lit1.0, DW_OP_const1u.0, 31, DW_OP_shl.0],
+                    &mut walker
+                ),
+                Some(0x8000_0000)
+            );
+            assert_eq!(
+                eval_dwarf32(
+                    &[DW_OP_lit1.0, DW_OP_const1u.0, 32, DW_OP_shl.0],
+                    &mut walker
+                ),
+                Some(0)
+            );
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_breg4.0, 0, DW_OP_lit4.0, DW_OP_shra.0], &mut walker),
+                Some(0xffff_ffff)
+            );
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_breg4.0, 0, DW_OP_lit4.0, DW_OP_shr.0], &mut walker),
+                Some(0x0fff_ffff)
+            );
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_breg4.0, 0, DW_OP_lit0.0, DW_OP_lt.0], &mut walker),
+                Some(1)
+            );
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_const1s.0, 0xfe, DW_OP_abs.0], &mut walker),
+                Some(2)
+            );
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_lit0.0, DW_OP_not.0], &mut walker),
+                Some(0xffff_ffff)
+            );
+
+            // An 8-byte load doesn't fit in a 32-bit value.
+            assert_eq!(
+                eval_dwarf32(&[DW_OP_lit0.0, DW_OP_deref_size.0, 8], &mut walker),
+                None
+            );
+        }
+
+        #[test]
+        fn test_dwarf_expr_registers_and_memory() {
+            let input = vec![("rsp", 32u64), ("rbp", 64), ("rip", 0x1234)]
+                .into_iter()
+                .collect();
+            let mut stack = vec![0; 128];
+            stack[40..48].copy_from_slice(&0x1122_3344_5566_7788u64.to_le_bytes());
+            let mut walker = TestFrameWalker::new(stack, input);
+
+            // DW_OP_breg7 (rsp)
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_breg7.0, 0x08], &mut walker, None),
+                Some(40)
+            );
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_breg7.0, 0x78], &mut walker, None),
+                Some(24)
+            );
+            // DW_OP_bregx (rbp)
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_bregx.0, 6, 0x10], &mut walker, None),
+                Some(80)
+            );
+            // Unknown to the walker, unknown to gimli
+            assert_eq!(eval_dwarf64(&[DW_OP_breg3.0, 0], &mut walker, None), None);
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_bregx.0, 0x7f, 0], &mut walker, None),
+                None
+            );
+
+            // DW_OP_call_frame_cfa
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_call_frame_cfa.0], &mut walker, Some(48)),
+                Some(48)
+            );
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_call_frame_cfa.0], &mut walker, None),
+                None
+            );
+
+            // Loads
+            let load = |size: u8, walker: &mut TestFrameWalker<u64>| {
+                eval_dwarf64(
+                    &[DW_OP_breg7.0, 0x08, DW_OP_deref_size.0, size],
+                    walker,
+                    None,
+                )
+            };
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_breg7.0, 0x08, DW_OP_deref.0], &mut walker, None),
+                Some(0x1122_3344_5566_7788)
+            );
+            assert_eq!(load(1, &mut walker), Some(0x88));
+            assert_eq!(load(2, &mut walker), Some(0x7788));
+            assert_eq!(load(4, &mut walker), Some(0x5566_7788));
+            assert_eq!(load(8, &mut walker), Some(0x1122_3344_5566_7788));
+            assert_eq!(load(0, &mut walker), None);
+            assert_eq!(load(16, &mut walker), None);
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_const2u.0, 0, 1, DW_OP_deref.0], &mut walker, None),
+                None
+            );
+            assert_eq!(eval_dwarf64(&[DW_OP_deref.0], &mut walker, None), None);
+
+            // DW_OP_entry_value
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_entry_value.0, 1, DW_OP_reg7.0], &mut walker, None),
+                Some(32)
+            );
+            assert_eq!(
+                eval_dwarf64(
+                    &[
+                        DW_OP_entry_value.0,
+                        2,
+                        DW_OP_breg6.0,
+                        0x04,
+                        DW_OP_lit1.0,
+                        DW_OP_plus.0
+                    ],
+                    &mut walker,
+                    None
+                ),
+                Some(69)
+            );
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_entry_value.0, 1, DW_OP_reg3.0], &mut walker, None),
+                None
+            );
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_entry_value.0, 0], &mut walker, None),
+                None
+            );
+
+            // Register locations aren't values.
+            assert_eq!(eval_dwarf64(&[DW_OP_reg7.0], &mut walker, None), None);
+        }
+
+        #[test]
+        fn test_dwarf_expr_control_flow() {
+            let input = vec![("rsp", 32u64)].into_iter().collect();
+            let mut walker = TestFrameWalker::new(vec![], input);
+
+            // if rsp == 32 { 10 } else { 20 }
+            let branchy = |walker: &mut TestFrameWalker<u64>, rsp_cmp: u8| {
+                eval_dwarf64(
+                    &[
+                        DW_OP_breg7.0,
+                        0,
+                        DW_OP_const1u.0,
+                        rsp_cmp,
+                        DW_OP_eq.0,
+                        DW_OP_bra.0,
+                        4,
+                        0,
+                        DW_OP_lit20.0,
+                        DW_OP_skip.0,
+                        1,
+                        0,
+                        DW_OP_lit10.0,
+                    ],
+                    walker,
+                    None,
+                )
+            };
+            assert_eq!(branchy(&mut walker, 32), Some(10));
+            assert_eq!(branchy(&mut walker, 33), Some(20));
+
+            // Sum 5 + 4 + 3 + 2 + 1 with a backwards branch.
+            let sum = [
+                DW_OP_lit0.0, // sum
+                DW_OP_lit5.0, // counter
+                // loop: sum += counter
+                DW_OP_dup.0,
+                DW_OP_rot.0,
+                DW_OP_plus.0,
+                DW_OP_swap.0,
+                // counter -= 1
+                DW_OP_lit1.0,
+                DW_OP_minus.0,
+                // if counter != 0 goto loop
+                DW_OP_dup.0,
+                DW_OP_bra.0,
+                (-10i16) as u8,
+                ((-10i16) >> 8) as u8,
+                DW_OP_drop.0,
+            ];
+            assert_eq!(eval_dwarf64(&sum, &mut walker, None), Some(15));
+
+            // Jumping to the very end is fine, jumping outside the expression isn't.
+            assert_eq!(
+                eval_dwarf64(
+                    &[DW_OP_lit1.0, DW_OP_skip.0, 1, 0, DW_OP_drop.0],
+                    &mut walker,
+                    None
+                ),
+                Some(1)
+            );
+            assert_eq!(
+                eval_dwarf64(
+                    &[DW_OP_lit1.0, DW_OP_skip.0, 2, 0, DW_OP_drop.0],
+                    &mut walker,
+                    None
+                ),
+                None
+            );
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_lit1.0, DW_OP_skip.0, 0xfa, 0xff], &mut walker, None),
+                None
+            );
+            assert_eq!(eval_dwarf64(&[DW_OP_bra.0, 0, 0], &mut walker, None), None);
+
+            // Infinite loops eventually give up.
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_lit1.0, DW_OP_skip.0, 0xfd, 0xff], &mut walker, None),
+                None
+            );
+
+            // DW_OP_stack_value must be the final op.
+            assert_eq!(
+                eval_dwarf64(&[DW_OP_lit1.0, DW_OP_stack_value.0], &mut walker, None),
+                Some(1)
+            );
+            assert_eq!(
+                eval_dwarf64(
+                    &[DW_OP_lit1.0, DW_OP_stack_value.0, DW_OP_lit2.0],
+                    &mut walker,
+                    None
+                ),
+                None
+            );
+        }
+
+        #[test]
+        fn test_dwarf_expr_unsupported() {
+            let mut walker = TestFrameWalker::<u64>::new(vec![0; 16], HashMap::new());
+            let eval = |bytecode: &[u8], walker: &mut TestFrameWalker<u64>| {
+                eval_dwarf64(bytecode, walker, None)
+            };
+
+            assert_eq!(eval(&[DW_OP_fbreg.0, 0], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_push_object_address.0], &mut walker), None);
+            assert_eq!(
+                eval(&[DW_OP_lit0.0, DW_OP_form_tls_address.0], &mut walker),
+                None
+            );
+            assert_eq!(eval(&[DW_OP_lit0.0, DW_OP_piece.0, 8], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_implicit_value.0, 1, 0], &mut walker), None);
+            assert_eq!(eval(&[DW_OP_call2.0, 0, 0], &mut walker), None);
+            assert_eq!(
+                eval(&[DW_OP_lit0.0, DW_OP_lit0.0, DW_OP_xderef.0], &mut walker),
+                None
+            );
+            assert_eq!(eval(&[DW_OP_lit0.0, DW_OP_convert.0, 1], &mut walker), None);
+            // Truncated operands, invalid opcodes
+            assert_eq!(eval(&[DW_OP_const4u.0, 0], &mut walker), None);
+            assert_eq!(eval(&[0xff], &mut walker), None);
+
+            // Address sizes that don't fit (or aren't) a value.
+            for address_size in [0, 16] {
+                assert_eq!(
+                    eval_dwarf_expr(
+                        &[DW_OP_lit1.0, DW_OP_neg.0, DW_OP_lit1.0, DW_OP_shra.0],
+                        dwarf_encoding(address_size),
+                        x86_64_register_name,
+                        &mut walker,
+                        None,
+                    ),
+                    None
+                );
+            }
+        }
+
+        #[test]
+        fn test_dwarf_expr_real_world() {
+            // Expressions gcc/glibc actually emit in .eh_frame on x86_64.
+            let input = vec![("rsp", 0x100u64), ("rip", 0x401026)]
+                .into_iter()
+                .collect();
+            let mut stack = vec![0; 0x200];
+            stack[0x1a0..0x1a8].copy_from_slice(&0x180u64.to_le_bytes());
+            let mut walker = TestFrameWalker::new(stack, input);
+
+            // PLT entries: .cfa = rsp + 8 + ((((rip & 15) >= 11) ? 1 : 0) << 3)
+            let plt = [
+                DW_OP_breg7.0,
+                8,
+                DW_OP_breg16.0,
+                0,
+                DW_OP_lit15.0,
+                DW_OP_and.0,
+                DW_OP_lit11.0,
+                DW_OP_ge.0,
+                DW_OP_lit3.0,
+                DW_OP_shl.0,
+                DW_OP_plus.0,
+            ];
+            assert_eq!(eval_dwarf64(&plt, &mut walker, None), Some(0x108));
+            walker.callee_regs.insert("rip", 0x40102b);
+            assert_eq!(eval_dwarf64(&plt, &mut walker, None), Some(0x110));
+
+            // Signal trampolines (__restore_rt): .cfa = *(rsp + 160)
+            assert_eq!(
+                eval_dwarf64(
+                    &[DW_OP_breg7.0, 0xa0, 0x01, DW_OP_deref.0],
+                    &mut walker,
+                    None
+                ),
+                Some(0x180)
+            );
+
+            // Stack realignment (DW_CFA_expression for rbp): *(cfa - 16)
+            assert_eq!(
+                eval_dwarf64(
+                    &[DW_OP_call_frame_cfa.0, DW_OP_lit16.0, DW_OP_minus.0],
+                    &mut walker,
+                    Some(0x1b0)
+                ),
+                Some(0x1a0)
+            );
+        }
+    }
+
+    // Property tests for the postfix evaluators. Random well-formed CFI
+    // programs are checked against a simple tree-walking interpreter, and
+    // random token soup (for both CFI and WIN programs) must never panic,
+    // no matter how it overflows or underflows the stack.
+
+    use proptest::prelude::*;
+
+    /// The registers the property tests' walkers have values for.
+    const PROP_REGS: [&str; 3] = ["rsp", "rbp", "rax"];
+    /// How much "stack" memory the property tests' walkers have.
+    const PROP_STACK_SIZE: usize = 64;
+
+    /// A CFI expression as a tree, which is what the postfix form encodes.
+    #[derive(Clone, Debug)]
+    enum CfiTree {
+        Const(i64),
+        Reg(&'static str),
+        Cfa,
+        Deref(Box<CfiTree>),
+        Binary(&'static str, Box<CfiTree>, Box<CfiTree>),
+    }
+
+    impl CfiTree {
+        fn to_postfix(&self) -> String {
+            match self {
+                CfiTree::Const(val) => val.to_string(),
+                CfiTree::Reg(reg) => format!("${}", reg),
+                CfiTree::Cfa => ".cfa".to_string(),
+                CfiTree::Deref(ptr) => format!("{} ^", ptr.to_postfix()),
+                CfiTree::Binary(op, lhs, rhs) => {
+                    format!("{} {} {}", lhs.to_postfix(), rhs.to_postfix(), op)
+                }
+            }
+        }
+
+        /// The reference interpreter.
+        fn eval(&self, regs: &HashMap<&str, u64>, stack: &[u8], cfa: Option<u64>) -> Option<u64> {
+            match self {
+                CfiTree::Const(val) => Some(*val as u64),
+                CfiTree::Reg(reg) => regs.get(reg).copied(),
+                CfiTree::Cfa => cfa,
+                CfiTree::Deref(ptr) => {
+                    let ptr = ptr.eval(regs, stack, cfa)?;
+                    if ptr > (PROP_STACK_SIZE - 8) as u64 {
+                        return None;
+                    }
+                    let mut bytes = [0; 8];
+                    bytes.copy_from_slice(&stack[ptr as usize..ptr as usize + 8]);
+                    Some(u64::from_le_bytes(bytes))
+                }
+                CfiTree::Binary(op, lhs, rhs) => {
+                    let lhs = lhs.eval(regs, stack, cfa)?;
+                    let rhs = rhs.eval(regs, stack, cfa)?;
+                    match *op {
+                        "+" => Some(lhs.wrapping_add(rhs)),
+                        "-" => Some(lhs.wrapping_sub(rhs)),
+                        "*" => Some(lhs.wrapping_mul(rhs)),
+                        "/" => lhs.checked_div(rhs),
+                        "%" => lhs.checked_rem(rhs),
+                        "@" if rhs.is_power_of_two() => Some(lhs - lhs % rhs),
+                        "@" => None,
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        }
+    }
+
+    fn cfi_tree() -> impl Strategy<Value = CfiTree> {
+        let leaf = prop_oneof![
+            // Mostly small values so derefs and alignments have a chance of working.
+            (-4i64..PROP_STACK_SIZE as i64).prop_map(CfiTree::Const),
+            any::<i64>().prop_map(CfiTree::Const),
+            prop::sample::select(&PROP_REGS[..]).prop_map(CfiTree::Reg),
+            Just(CfiTree::Cfa),
+        ];
+        leaf.prop_recursive(5, 64, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|ptr| CfiTree::Deref(Box::new(ptr))),
+                (
+                    prop::sample::select(&["+", "-", "*", "/", "%", "@"][..]),
+                    inner.clone(),
+                    inner,
+                )
+                    .prop_map(|(op, lhs, rhs)| CfiTree::Binary(
+                        op,
+                        Box::new(lhs),
+                        Box::new(rhs)
+                    )),
+            ]
+        })
+    }
+
+    fn prop_walker<Reg: Int + Copy + Arbitrary>() -> impl Strategy<Value = TestFrameWalker<Reg>> {
+        (
+            prop::collection::vec(any::<u8>(), PROP_STACK_SIZE),
+            prop::collection::vec(any::<Reg>(), PROP_REGS.len()),
+            any::<u32>(),
+        )
+            .prop_map(|(stack, vals, grand_callee_param_size)| {
+                let regs = PROP_REGS.iter().copied().zip(vals).collect();
+                let mut walker = TestFrameWalker::new(stack, regs);
+                walker.grand_callee_param_size = grand_callee_param_size;
+                walker
+            })
+    }
+
+    /// Tokens that are mostly meaningful to the evaluators, with some junk.
+    fn postfix_token() -> impl Strategy<Value = String> {
+        prop_oneof![
+            prop::sample::select(
+                &[
+                    "+",
+                    "-",
+                    "*",
+                    "/",
+                    "%",
+                    "@",
+                    "^",
+                    "=",
+                    ".cfa",
+                    ".ra",
+                    ".undef",
+                    "$rsp",
+                    "$rbp",
+                    "rax",
+                    "$esp",
+                    "$ebp",
+                    "$eip",
+                    "$T0",
+                    ".raSearch",
+                    ".cbParams",
+                    ".cfa:",
+                    ".ra:",
+                    "$rax:",
+                    "x11:",
+                    "0",
+                    "1",
+                    "-1",
+                ][..]
+            )
+            .prop_map(String::from),
+            any::<i64>().prop_map(|val| val.to_string()),
+            "[ -~]{0,4}",
+        ]
+    }
+
+    fn win_info() -> impl Strategy<Value = StackInfoWin> {
+        (any::<[u32; 3]>(), any::<bool>()).prop_map(|(sizes, allocates_base_pointer)| {
+            StackInfoWin {
+                parameter_size: sizes[0],
+                saved_register_size: sizes[1],
+                local_size: sizes[2],
+                program_string_or_base_pointer: WinStackThing::AllocatesBasePointer(
+                    allocates_base_pointer,
+                ),
+                ..whatever_win_info()
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_cfi_expr_matches_reference(
+            tree in cfi_tree(),
+            mut walker in prop_walker::<u64>(),
+            cfa in prop::option::of(any::<u64>()),
+        ) {
+            let expected = tree.eval(&walker.callee_regs, &walker.stack, cfa);
+            let expr = tree.to_postfix();
+            prop_assert_eq!(eval_cfi_expr(&expr, &mut walker, cfa), expected, "{}", expr);
+        }
+
+        #[test]
+        fn prop_cfi_never_panics(
+            tokens in prop::collection::vec(postfix_token(), 0..16),
+            mut walker in prop_walker::<u64>(),
+        ) {
+            let expr = tokens.join(" ");
+            let _ = eval_cfi_expr(&expr, &mut walker, Some(0x10));
+            let (init, additional) = build_cfi_rules(&expr, &[&expr]);
+            let _ = walk_with_stack_cfi(&init, &additional, &mut walker);
+        }
+
+        #[test]
+        fn prop_stack_win_never_panics(
+            tokens in prop::collection::vec(postfix_token(), 0..16),
+            info in win_info(),
+            esp in any::<u32>(),
+            ebp in any::<u32>(),
+            mut walker in prop_walker::<u32>(),
+        ) {
+            walker.callee_regs.insert("esp", esp);
+            walker.callee_regs.insert("ebp", ebp);
+            let _ = walk_with_stack_win_fpo(&info, &mut walker);
+            let _ = eval_win_expr(&tokens.join(" "), &info, &mut walker);
+        }
+    }
 }
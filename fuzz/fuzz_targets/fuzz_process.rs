@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minidump::Minidump;
+use minidump_processor::{simple_symbol_supplier, Symbolizer};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dump) = Minidump::read(data) {
+        let provider = Symbolizer::new(simple_symbol_supplier(vec![]));
+        if let Ok(state) = minidump_processor::process_minidump(&dump, &provider) {
+            // The formatters look at everything, so make sure they cope too.
+            let _ = state.print(&mut std::io::sink());
+            let _ = state.print_json(&mut std::io::sink(), false);
+        }
+    }
+});
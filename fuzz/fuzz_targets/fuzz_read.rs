@@ -0,0 +1,36 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minidump::*;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dump) = Minidump::read(data) {
+        // Streams are parsed lazily, so ask for every one we know how to parse.
+        let _ = dump.get_stream::<MinidumpAssertion>();
+        let _ = dump.get_stream::<MinidumpBreakpadInfo>();
+        let _ = dump.get_stream::<MinidumpCrashpadInfo>();
+        let _ = dump.get_stream::<MinidumpException>();
+        let _ = dump.get_stream::<MinidumpFunctionTableList>();
+        let _ = dump.get_stream::<MinidumpHandleDataStream>();
+        let _ = dump.get_stream::<MinidumpHandleOperationList>();
+        let _ = dump.get_stream::<MinidumpLinuxCpuInfo>();
+        let _ = dump.get_stream::<MinidumpLinuxDsoDebug>();
+        let _ = dump.get_stream::<MinidumpLinuxEnviron>();
+        let _ = dump.get_stream::<MinidumpLinuxLsbRelease>();
+        let _ = dump.get_stream::<MinidumpLinuxMaps>();
+        let _ = dump.get_stream::<MinidumpLinuxProcStatus>();
+        let _ = dump.get_stream::<MinidumpMacCrashInfo>();
+        let _ = dump.get_stream::<MinidumpMemoryList>();
+        let _ = dump.get_stream::<MinidumpMemory64List>();
+        let _ = dump.get_stream::<MinidumpMemoryInfoList>();
+        let _ = dump.get_stream::<MinidumpMiscInfo>();
+        let _ = dump.get_stream::<MinidumpModuleList>();
+        let _ = dump.get_stream::<MinidumpProcessVmCounters>();
+        let _ = dump.get_stream::<MinidumpSystemInfo>();
+        let _ = dump.get_stream::<MinidumpSystemMemoryInfo>();
+        let _ = dump.get_stream::<MinidumpThreadList>();
+        let _ = dump.get_stream::<MinidumpThreadInfoList>();
+        let _ = dump.get_stream::<MinidumpThreadNames>();
+        let _ = dump.get_stream::<MinidumpUnloadedModuleList>();
+    }
+});
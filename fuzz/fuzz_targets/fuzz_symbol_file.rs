@@ -0,0 +1,8 @@
+#![no_main]
+
+use breakpad_symbols::SymbolFile;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = SymbolFile::parse(data, |_| ());
+});
@@ -15,7 +15,7 @@ use std::fmt;
 
 use bitflags::bitflags;
 use enum_primitive_derive::Primitive;
-use scroll::{Endian, Pread, SizeWith};
+use scroll::{Endian, Pread, Pwrite, SizeWith};
 use smart_default::SmartDefault;
 
 /// An offset from the start of the minidump file.
@@ -35,7 +35,7 @@ pub const MINIDUMP_VERSION: u32 = 42899;
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_header
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_HEADER {
     /// This should be [`MINIDUMP_SIGNATURE`][signature].
     ///
@@ -63,7 +63,7 @@ pub struct MINIDUMP_HEADER {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_location_descriptor
-#[derive(Debug, Copy, Default, Clone, Pread, SizeWith)]
+#[derive(Debug, Copy, Default, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_LOCATION_DESCRIPTOR {
     /// The size of this data.
     pub data_size: u32,
@@ -83,7 +83,7 @@ impl From<u8> for MINIDUMP_LOCATION_DESCRIPTOR {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_memory_descriptor
-#[derive(Debug, Copy, Clone, Default, Pread, SizeWith)]
+#[derive(Debug, Copy, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_MEMORY_DESCRIPTOR {
     /// The base address of this memory range from the process.
     pub start_of_memory_range: u64,
@@ -91,6 +91,40 @@ pub struct MINIDUMP_MEMORY_DESCRIPTOR {
     pub memory: MINIDUMP_LOCATION_DESCRIPTOR,
 }
 
+/// A range of memory contained within a full-memory minidump.
+///
+/// Unlike [`MINIDUMP_MEMORY_DESCRIPTOR`] this doesn't say where the bytes of memory
+/// are stored: all the ranges in a [`MINIDUMP_MEMORY64_LIST`] are stored back to back,
+/// starting at its `base_rva`.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_memory_descriptor64
+#[derive(Debug, Copy, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_MEMORY_DESCRIPTOR64 {
+    /// The base address of this memory range from the process.
+    pub start_of_memory_range: u64,
+    /// The size of this memory range.
+    pub data_size: u64,
+}
+
+/// The header for the memory list of a full-memory minidump.
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::Memory64ListStream`]. The individual
+/// [`MINIDUMP_MEMORY_DESCRIPTOR64`] entries follow this header in the stream.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name (minus the
+/// trailing variable-length array of descriptors).
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_memory64_list
+#[derive(Debug, Copy, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_MEMORY64_LIST {
+    /// The number of memory ranges in the list.
+    pub number_of_memory_ranges: u64,
+    /// The offset of the first memory range's bytes in the dump.
+    pub base_rva: u64,
+}
+
 /// Information about a data stream contained in a minidump file.
 ///
 /// The minidump header contains a pointer to a list of these structs which allows locating
@@ -98,7 +132,7 @@ pub struct MINIDUMP_MEMORY_DESCRIPTOR {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_directory
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_DIRECTORY {
     /// This is usually one of the values in [`MINIDUMP_STREAM_TYPE`][ty] for known stream types,
     /// but user streams can have arbitrary values.
@@ -161,10 +195,19 @@ pub enum MINIDUMP_STREAM_TYPE {
     /// See [`MINIDUMP_SYSTEM_INFO`].
     SystemInfoStream = 7,
     ThreadExListStream = 8,
+    /// The list of memory regions from the process contained within a full-memory dump
+    ///
+    /// See [`MINIDUMP_MEMORY64_LIST`].
     Memory64ListStream = 9,
     CommentStreamA = 10,
     CommentStreamW = 11,
+    /// Information about the handles the process had open
+    ///
+    /// See [`MINIDUMP_HANDLE_DATA_STREAM`].
     HandleDataStream = 12,
+    /// Dynamically registered function tables, typically for JIT code
+    ///
+    /// See [`MINIDUMP_FUNCTION_TABLE_STREAM`].
     FunctionTable = 13,
     /// The list of executable modules from the process that were unloaded by the time of the crash
     ///
@@ -186,7 +229,13 @@ pub enum MINIDUMP_STREAM_TYPE {
     ///
     /// See ['MINIDUMP_MEMORY_INFO_LIST'].
     MemoryInfoListStream = 16,
+    /// Extra information about threads, such as start addresses and CPU times
+    ///
+    /// See ['MINIDUMP_THREAD_INFO_LIST'].
     ThreadInfoListStream = 17,
+    /// Traces of operations on handles, if handle tracing was enabled
+    ///
+    /// See [`MINIDUMP_HANDLE_OPERATION_LIST`].
     HandleOperationListStream = 18,
     TokenStream = 19,
     JavaScriptDataStream = 20,
@@ -260,7 +309,7 @@ impl From<MINIDUMP_STREAM_TYPE> for u32 {
 }
 
 /// The name of a thread, found in the ThreadNamesStream.
-#[derive(Debug, Clone, Default, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_THREAD_NAME {
     /// The id of the thread.
     pub thread_id: u32,
@@ -273,7 +322,7 @@ pub struct MINIDUMP_THREAD_NAME {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_module
-#[derive(Debug, Clone, Default, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_MODULE {
     /// The base address of the executable image in memory.
     pub base_of_image: u64,
@@ -312,7 +361,7 @@ pub struct MINIDUMP_MODULE {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_unloaded_module
-#[derive(Debug, Clone, Default, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_UNLOADED_MODULE {
     /// The base address of the executable image in memory (when it was loaded).
     pub base_of_image: u64,
@@ -331,7 +380,7 @@ pub struct MINIDUMP_UNLOADED_MODULE {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/verrsrc/ns-verrsrc-tagvs_fixedfileinfo
-#[derive(Debug, Clone, Default, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct VS_FIXEDFILEINFO {
     /// Contains the value of `VS_FFI_SIGNATURE`
     pub signature: u32,
@@ -342,8 +391,11 @@ pub struct VS_FIXEDFILEINFO {
     pub product_version_hi: u32,
     pub product_version_lo: u32,
     pub file_flags_mask: u32,
+    /// Values from [`FileFlags`], only meaningful where set in `file_flags_mask`
     pub file_flags: u32,
+    /// A value from [`FileOs`]
     pub file_os: u32,
+    /// A value from [`FileType`]
     pub file_type: u32,
     pub file_subtype: u32,
     pub file_date_hi: u32,
@@ -356,6 +408,66 @@ pub const VS_FFI_SIGNATURE: u32 = 0xfeef04bd;
 /// The expected value of `VS_FIXEDFILEINFO.struct_version`
 pub const VS_FFI_STRUCVERSION: u32 = 0x00010000;
 
+bitflags! {
+    /// Known flags for [`VS_FIXEDFILEINFO::file_flags`]
+    pub struct FileFlags: u32 {
+        /// The file contains debugging information or is a debug build
+        const VS_FF_DEBUG        = 0x01;
+        /// The file is a development version, not a released product
+        const VS_FF_PRERELEASE   = 0x02;
+        /// The file has been modified and isn't identical to the original
+        const VS_FF_PATCHED      = 0x04;
+        /// The file wasn't built using standard release procedures
+        const VS_FF_PRIVATEBUILD = 0x08;
+        /// The file's version info was created dynamically
+        const VS_FF_INFOINFERRED = 0x10;
+        /// The file is a variation of the normal file of the same version
+        const VS_FF_SPECIALBUILD = 0x20;
+    }
+}
+
+/// Known values of [`VS_FIXEDFILEINFO::file_os`]
+///
+/// The high word is the operating system and the low word is the windowing
+/// system it was designed for.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum FileOs {
+    VOS_UNKNOWN = 0,
+    VOS__WINDOWS16 = 0x00000001,
+    VOS__PM16 = 0x00000002,
+    VOS__PM32 = 0x00000003,
+    VOS__WINDOWS32 = 0x00000004,
+    VOS_DOS = 0x00010000,
+    VOS_DOS_WINDOWS16 = 0x00010001,
+    VOS_DOS_WINDOWS32 = 0x00010004,
+    VOS_OS216 = 0x00020000,
+    VOS_OS216_PM16 = 0x00020002,
+    VOS_OS232 = 0x00030000,
+    VOS_OS232_PM32 = 0x00030003,
+    VOS_NT = 0x00040000,
+    VOS_NT_WINDOWS32 = 0x00040004,
+}
+
+/// Known values of [`VS_FIXEDFILEINFO::file_type`]
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum FileType {
+    VFT_UNKNOWN = 0,
+    /// An application
+    VFT_APP = 1,
+    /// A DLL
+    VFT_DLL = 2,
+    /// A device driver, `file_subtype` says which kind
+    VFT_DRV = 3,
+    /// A font, `file_subtype` says which kind
+    VFT_FONT = 4,
+    /// A virtual device
+    VFT_VXD = 5,
+    /// A static-link library
+    VFT_STATIC_LIB = 7,
+}
+
 /// Known values for the `signature` field of CodeView records
 ///
 /// In addition to the two CodeView record formats used for linking
@@ -419,6 +531,20 @@ impl<'a> scroll::ctx::TryFromCtx<'a, Endian> for CV_INFO_PDB20 {
     }
 }
 
+impl scroll::ctx::TryIntoCtx<Endian> for CV_INFO_PDB20 {
+    type Error = scroll::Error;
+
+    fn try_into_ctx(self, dst: &mut [u8], endian: Endian) -> Result<usize, Self::Error> {
+        let offset = &mut 0;
+        dst.gwrite_with(self.cv_signature, offset, endian)?;
+        dst.gwrite_with(self.cv_offset, offset, endian)?;
+        dst.gwrite_with(self.signature, offset, endian)?;
+        dst.gwrite_with(self.age, offset, endian)?;
+        dst.gwrite(&self.pdb_file_name[..], offset)?;
+        Ok(*offset)
+    }
+}
+
 /// CodeView debug information in the current PDB 7.0 ("RSDS") format.
 ///
 /// This struct is defined as variable-length in C with a trailing PDB filename member.
@@ -454,6 +580,19 @@ impl<'a> scroll::ctx::TryFromCtx<'a, Endian> for CV_INFO_PDB70 {
     }
 }
 
+impl scroll::ctx::TryIntoCtx<Endian> for CV_INFO_PDB70 {
+    type Error = scroll::Error;
+
+    fn try_into_ctx(self, dst: &mut [u8], endian: Endian) -> Result<usize, Self::Error> {
+        let offset = &mut 0;
+        dst.gwrite_with(self.cv_signature, offset, endian)?;
+        dst.gwrite_with(self.signature, offset, endian)?;
+        dst.gwrite_with(self.age, offset, endian)?;
+        dst.gwrite(&self.pdb_file_name[..], offset)?;
+        Ok(*offset)
+    }
+}
+
 /// A GUID as specified in Rpcdce.h
 ///
 /// Matches the [Microsoft struct][msdn] of the same name.
@@ -477,7 +616,7 @@ impl<'a> scroll::ctx::TryFromCtx<'a, Endian> for CV_INFO_PDB70 {
 /// ```
 ///
 /// [msdn]: https://msdn.microsoft.com/en-us/library/windows/desktop/aa373931(v=vs.85).aspx
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Pread, SizeWith)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Pread, Pwrite, SizeWith)]
 pub struct GUID {
     pub data1: u32,
     pub data2: u16,
@@ -560,6 +699,17 @@ impl<'a> scroll::ctx::TryFromCtx<'a, Endian> for CV_INFO_ELF {
     }
 }
 
+impl scroll::ctx::TryIntoCtx<Endian> for CV_INFO_ELF {
+    type Error = scroll::Error;
+
+    fn try_into_ctx(self, dst: &mut [u8], endian: Endian) -> Result<usize, Self::Error> {
+        let offset = &mut 0;
+        dst.gwrite_with(self.cv_signature, offset, endian)?;
+        dst.gwrite(&self.build_id[..], offset)?;
+        Ok(*offset)
+    }
+}
+
 /// Obsolete debug record type defined in WinNT.h.
 #[derive(Debug, Clone, Pread, SizeWith)]
 pub struct IMAGE_DEBUG_MISC {
@@ -575,7 +725,7 @@ pub struct IMAGE_DEBUG_MISC {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_thread
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_THREAD {
     /// The identifier of this thread
     pub thread_id: u32,
@@ -604,7 +754,7 @@ pub struct MINIDUMP_THREAD {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-minidump_exception_stream
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_EXCEPTION_STREAM {
     /// The identifier of the thread that encountered the exception.
     pub thread_id: u32,
@@ -622,7 +772,7 @@ pub struct MINIDUMP_EXCEPTION_STREAM {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_exception
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_EXCEPTION {
     /// The reason the exception occurred.
     ///
@@ -6332,6 +6482,81 @@ pub enum NtStatusWindows {
     STATUS_APPEXEC_CALLER_WAIT_TIMEOUT_RESOURCES = 0xc0ec000c,
 }
 
+/// The severity of an NTSTATUS value, from its top two bits.
+///
+/// These constants are defined in ntdef.h.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum NtStatusSeverity {
+    STATUS_SEVERITY_SUCCESS = 0,
+    STATUS_SEVERITY_INFORMATIONAL = 1,
+    STATUS_SEVERITY_WARNING = 2,
+    STATUS_SEVERITY_ERROR = 3,
+}
+
+/// The facility (the part of the system that defined it) of an NTSTATUS value,
+/// from bits 16-27.
+///
+/// These constants are defined in ntstatus.h. They only mean anything if the
+/// customer bit (29) of the value is clear.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum NtStatusFacility {
+    FACILITY_DEBUGGER = 0x1,
+    FACILITY_RPC_RUNTIME = 0x2,
+    FACILITY_RPC_STUBS = 0x3,
+    FACILITY_IO_ERROR_CODE = 0x4,
+    FACILITY_CODCLASS_ERROR_CODE = 0x6,
+    FACILITY_NTWIN32 = 0x7,
+    FACILITY_NTCERT = 0x8,
+    FACILITY_NTSSPI = 0x9,
+    FACILITY_TERMINAL_SERVER = 0xa,
+    FACILITY_MUI_ERROR_CODE = 0xb,
+    FACILITY_USB_ERROR_CODE = 0x10,
+    FACILITY_HID_ERROR_CODE = 0x11,
+    FACILITY_FIREWIRE_ERROR_CODE = 0x12,
+    FACILITY_CLUSTER_ERROR_CODE = 0x13,
+    FACILITY_ACPI_ERROR_CODE = 0x14,
+    FACILITY_SXS_ERROR_CODE = 0x15,
+    FACILITY_TRANSACTION = 0x19,
+    FACILITY_COMMONLOG = 0x1a,
+    FACILITY_VIDEO = 0x1b,
+    FACILITY_FILTER_MANAGER = 0x1c,
+    FACILITY_MONITOR = 0x1d,
+    FACILITY_GRAPHICS_KERNEL = 0x1e,
+    FACILITY_DRIVER_FRAMEWORK = 0x20,
+    FACILITY_FVE_ERROR_CODE = 0x21,
+    FACILITY_FWP_ERROR_CODE = 0x22,
+    FACILITY_NDIS_ERROR_CODE = 0x23,
+    FACILITY_TPM = 0x29,
+    FACILITY_RTPM = 0x2a,
+    FACILITY_HYPERVISOR = 0x35,
+    FACILITY_IPSEC = 0x36,
+    FACILITY_VIRTUALIZATION = 0x37,
+    FACILITY_VOLMGR = 0x38,
+    FACILITY_BCD_ERROR_CODE = 0x39,
+    FACILITY_WIN32K_NTUSER = 0x3e,
+    FACILITY_WIN32K_NTGDI = 0x3f,
+    FACILITY_RESUME_KEY_FILTER = 0x40,
+    FACILITY_RDBSS = 0x41,
+    FACILITY_BTH_ATT = 0x42,
+    FACILITY_SECUREBOOT = 0x43,
+    FACILITY_AUDIO_KERNEL = 0x44,
+    FACILITY_VSM = 0x45,
+    FACILITY_VOLSNAP = 0x50,
+    FACILITY_SDBUS = 0x51,
+    FACILITY_SHARED_VHDX = 0x5c,
+    FACILITY_SMB = 0x5d,
+    FACILITY_INTERIX = 0x99,
+    FACILITY_SPACES = 0xe7,
+    FACILITY_SECURITY_CORE = 0xe8,
+    FACILITY_SYSTEM_INTEGRITY = 0xe9,
+    FACILITY_LICENSING = 0xea,
+    FACILITY_PLATFORM_MANIFEST = 0xeb,
+    FACILITY_APP_EXEC = 0xec,
+    FACILITY_MAXIMUM_VALUE = 0xed,
+}
+
 /// Values for [`MINIDUMP_EXCEPTION::exception_information`]`[0]`,
 /// when [`MINIDUMP_EXCEPTION::exception_code`] is [`NtStatusWindows::STATUS_STACK_BUFFER_OVERRUN`].
 /// This describes the underlying reason for the crash.
@@ -6517,6 +6742,37 @@ pub enum ExceptionCodeLinux {
     DUMP_REQUESTED = 0xffffffff,
 }
 
+/// Values for [`MINIDUMP_EXCEPTION::exception_flags`] (the signal's `si_code`) on Linux
+/// that any signal can have.
+///
+/// These say who sent the signal, for signals that weren't raised by a fault.
+/// The negative values are stored as their two's complement.
+// These values come from asm-generic/siginfo.h
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Debug, Primitive)]
+pub enum ExceptionCodeLinuxSicode {
+    /// Sent by kill, sigsend or raise
+    SI_USER = 0u32,
+    /// Sent by the kernel
+    SI_KERNEL = 0x80,
+    /// Sent by sigqueue
+    SI_QUEUE = 0xffffffff,
+    /// Sent by timer expiration
+    SI_TIMER = 0xfffffffe,
+    /// Sent by real time mesq state change
+    SI_MESGQ = 0xfffffffd,
+    /// Sent by AIO completion
+    SI_ASYNCIO = 0xfffffffc,
+    /// Sent by queued SIGIO
+    SI_SIGIO = 0xfffffffb,
+    /// Sent by tkill or tgkill
+    SI_TKILL = 0xfffffffa,
+    /// Sent by execve() killing subsidiary threads
+    SI_DETHREAD = 0xfffffff9,
+    /// Sent by glibc async name lookup completion
+    SI_ASYNCNL = 0xfffffff4,
+}
+
 // These values come from asm-generic/siginfo.h
 #[derive(Copy, Clone, PartialEq, Debug, Primitive)]
 pub enum ExceptionCodeLinuxSigillKind {
@@ -6529,6 +6785,7 @@ pub enum ExceptionCodeLinuxSigillKind {
     ILL_PRVREG = 6,
     ILL_COPROC = 7,
     ILL_BADSTK = 8,
+    ILL_BADIADDR = 9,
     SI_KERNEL = 0x80,
 }
 
@@ -6543,6 +6800,8 @@ pub enum ExceptionCodeLinuxSigfpeKind {
     FPE_FLTRES = 6,
     FPE_FLTINV = 7,
     FPE_FLTSUB = 8,
+    FPE_FLTUNK = 14,
+    FPE_CONDTRAP = 15,
     SI_KERNEL = 0x80,
 }
 
@@ -6553,6 +6812,11 @@ pub enum ExceptionCodeLinuxSigsegvKind {
     SEGV_ACCERR = 2,
     SEGV_BNDERR = 3,
     SEGV_PKUERR = 4,
+    SEGV_ACCADI = 5,
+    SEGV_ADIDERR = 6,
+    SEGV_ADIPERR = 7,
+    SEGV_MTEAERR = 8,
+    SEGV_MTESERR = 9,
     SI_KERNEL = 0x80,
 }
 
@@ -6567,6 +6831,26 @@ pub enum ExceptionCodeLinuxSigbusKind {
     SI_KERNEL = 0x80,
 }
 
+#[derive(Copy, Clone, PartialEq, Debug, Primitive)]
+pub enum ExceptionCodeLinuxSigtrapKind {
+    SI_USER = 0,
+    TRAP_BRKPT = 1,
+    TRAP_TRACE = 2,
+    TRAP_BRANCH = 3,
+    TRAP_HWBKPT = 4,
+    TRAP_UNK = 5,
+    TRAP_PERF = 6,
+    SI_KERNEL = 0x80,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Primitive)]
+pub enum ExceptionCodeLinuxSigsysKind {
+    SI_USER = 0,
+    SYS_SECCOMP = 1,
+    SYS_USER_DISPATCH = 2,
+    SI_KERNEL = 0x80,
+}
+
 /// Values for [`MINIDUMP_EXCEPTION::exception_code`] for crashes on macOS
 ///
 /// Based on Darwin/macOS' mach/exception_types.h. This is what macOS calls an "exception",
@@ -6588,12 +6872,54 @@ pub enum ExceptionCodeMac {
     EXC_SYSCALL = 7,
     EXC_MACH_SYSCALL = 8,
     EXC_RPC_ALERT = 9,
+    /// Abnormal process exit, code describes the exception and signal that caused it
+    EXC_CRASH = 10,
     EXC_RESOURCE = 11,
     EXC_GUARD = 12,
+    EXC_CORPSE_NOTIFY = 13,
     /// Fake exception code used by Crashpad's SimulateCrash ('CPsx')
     SIMULATED = 0x43507378,
 }
 
+/// Signal numbers on macOS/iOS, as found in the code of an `EXC_CRASH`
+///
+/// These come from sys/signal.h, and mostly don't match the Linux ones.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Debug, Primitive)]
+pub enum ExceptionCodeMacSignal {
+    SIGHUP = 1,
+    SIGINT = 2,
+    SIGQUIT = 3,
+    SIGILL = 4,
+    SIGTRAP = 5,
+    SIGABRT = 6,
+    SIGEMT = 7,
+    SIGFPE = 8,
+    SIGKILL = 9,
+    SIGBUS = 10,
+    SIGSEGV = 11,
+    SIGSYS = 12,
+    SIGPIPE = 13,
+    SIGALRM = 14,
+    SIGTERM = 15,
+    SIGURG = 16,
+    SIGSTOP = 17,
+    SIGTSTP = 18,
+    SIGCONT = 19,
+    SIGCHLD = 20,
+    SIGTTIN = 21,
+    SIGTTOU = 22,
+    SIGIO = 23,
+    SIGXCPU = 24,
+    SIGXFSZ = 25,
+    SIGVTALRM = 26,
+    SIGPROF = 27,
+    SIGWINCH = 28,
+    SIGINFO = 29,
+    SIGUSR1 = 30,
+    SIGUSR2 = 31,
+}
+
 // These error codes are based on
 // * mach/ppc/exception.h
 // * mach/i386/exception.h
@@ -6614,8 +6940,16 @@ pub enum ExceptionCodeMacBadAccessKernType {
 /// Mac/iOS Arm Userland Bad Accesses Exceptions
 #[derive(Copy, Clone, PartialEq, Debug, Primitive)]
 pub enum ExceptionCodeMacBadAccessArmType {
+    /// Misaligned data access
     EXC_ARM_DA_ALIGN = 0x0101,
+    /// Data abort from a debug event (watchpoint)
     EXC_ARM_DA_DEBUG = 0x0102,
+    /// Misaligned stack pointer
+    EXC_ARM_SP_ALIGN = 0x0103,
+    /// SWP instruction
+    EXC_ARM_SWP = 0x0104,
+    /// Pointer authentication failure
+    EXC_ARM_PAC_FAIL = 0x0105,
 }
 
 /// Mac/iOS Ppc Userland Bad Access Exceptions
@@ -6684,6 +7018,27 @@ pub enum ExceptionCodeMacBadInstructionX86Type {
     // EXC_I386_ENDPERR   = 33: should not occur
 }
 
+/// Mac/iOS Arm Arithmetic Exceptions
+///
+/// These are all floating point traps, integer divides don't fault on ARM.
+#[derive(Copy, Clone, PartialEq, Debug, Primitive)]
+pub enum ExceptionCodeMacArithmeticArmType {
+    /// Undefined floating point exception
+    EXC_ARM_FP_UNDEFINED = 0,
+    /// Invalid floating point operation
+    EXC_ARM_FP_IO = 1,
+    /// Floating point divide by zero
+    EXC_ARM_FP_DZ = 2,
+    /// Floating point overflow
+    EXC_ARM_FP_OF = 3,
+    /// Floating point underflow
+    EXC_ARM_FP_UF = 4,
+    /// Inexact floating point result
+    EXC_ARM_FP_IX = 5,
+    /// Denormal floating point input
+    EXC_ARM_FP_ID = 6,
+}
+
 /// Mac/iOS Ppc Arithmetic Exceptions
 #[derive(Copy, Clone, PartialEq, Debug, Primitive)]
 pub enum ExceptionCodeMacArithmeticPpcType {
@@ -6891,6 +7246,9 @@ pub const CONTEXT_CPU_MASK: u32 = 0xffffff00;
 /// x86 and x64 contexts have this bit set in their `context_flags` when they have
 /// extra XSTATE beyond the traditional context definition.
 pub const CONTEXT_HAS_XSTATE: u32 = 0x00000040;
+/// x86 contexts have this bit set in their `context_flags` when their
+/// `extended_registers` hold the FXSAVE state (see [`XMM_SAVE_AREA32`]).
+pub const CONTEXT_X86_EXTENDED_REGISTERS: u32 = 0x00000020;
 
 bitflags! {
     /// CPU type values in the `context_flags` member of `CONTEXT_` structs
@@ -7198,7 +7556,7 @@ pub enum MipsRegisterNumbers {
 }
 
 /// PPC floating point state
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, SizeWith)]
 pub struct FLOATING_SAVE_AREA_PPC {
     pub fpregs: [u64; 32],
     pub fpscr_pad: u32,
@@ -7206,7 +7564,7 @@ pub struct FLOATING_SAVE_AREA_PPC {
 }
 
 /// PPC vector state
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, SizeWith)]
 pub struct VECTOR_SAVE_AREA_PPC {
     pub save_vr: [u128; 32],
     pub save_vscr: u128,
@@ -7218,7 +7576,7 @@ pub struct VECTOR_SAVE_AREA_PPC {
 /// A PPC CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for PPC in WinNT.h.
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, SizeWith)]
 pub struct CONTEXT_PPC {
     pub context_flags: u32,
     pub srr0: u32,
@@ -7241,10 +7599,18 @@ pub enum PpcRegisterNumbers {
     StackPointer = 1,
 }
 
+impl PpcRegisterNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::StackPointer => "r1",
+        }
+    }
+}
+
 /// A PPC64 CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for PPC64 in WinNT.h.
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, SizeWith)]
 pub struct CONTEXT_PPC64 {
     pub context_flags: u64,
     pub srr0: u64,
@@ -7266,6 +7632,14 @@ pub enum Ppc64RegisterNumbers {
     StackPointer = 1,
 }
 
+impl Ppc64RegisterNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::StackPointer => "r1",
+        }
+    }
+}
+
 /// SPARC floating point state
 #[derive(Debug, Clone, Pread, SizeWith)]
 pub struct FLOATING_SAVE_AREA_SPARC {
@@ -7351,7 +7725,7 @@ pub struct CONTEXT_X86 {
 /// CPU information contained within the [`MINIDUMP_SYSTEM_INFO`] struct
 ///
 /// This struct matches the definition of the `CPU_INFORMATION` union from minidumpapiset.h.
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct CPU_INFORMATION {
     /// `data` is defined as a union in the Microsoft headers
     ///
@@ -7398,7 +7772,7 @@ pub struct OtherCpuInfo {
 /// This struct matches the [Microsoft struct][msdn] of the same name.
 ///
 /// [msdn]: https://docs.microsoft.com/en-us/windows/desktop/api/minidumpapiset/ns-minidumpapiset-_minidump_system_info
-#[derive(Debug, Clone, Pread, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_SYSTEM_INFO {
     /// The system's processor architecture
     ///
@@ -7640,12 +8014,18 @@ multi_structs! {
 /// * The following features are already contained inside of [`CONTEXT_AMD64`]:
 ///    * LEGACY_FLOATING_POINT
 ///    * LEGACY_SSE
-///    * GSSE_AND_AVX
 ///
 /// * If there are XSTATE entries that *actually* map outside of the context's
 /// normal memory range, then the context's [`context_flags`](`CONTEXT_AMD64::context_flags`)
 /// will have bit 0x40 set ([`CONTEXT_HAS_XSTATE`]).
 ///
+/// * Those entries (e.g. GSSE_AND_AVX) are stored after the context, in the
+///   chunk described by the [`CONTEXT_EX`] that immediately follows it. That chunk
+///   starts with the [`XSAVE_AREA_HEADER`], which is at offset 512 of the XSAVE
+///   context, so an entry's offset *into the chunk* is its `offset` minus 512.
+///   If the header's `compaction_mask` has its top bit set, entries are instead
+///   packed one after another (in order of index) right after the header.
+///
 /// * [`ContextFlagsCpu::from_flags`] will mask out the [`CONTEXT_HAS_XSTATE`] bit.
 /// If you want to check for that bit, check the raw value of
 /// [`context_flags`](`CONTEXT_AMD64::context_flags`).
@@ -7736,6 +8116,44 @@ impl XstateFeatureIndex {
     }
 }
 
+/// The location of part of an extended context, relative to the [`CONTEXT_EX`].
+///
+/// This struct matches the definition of `CONTEXT_CHUNK` in WinNT.h.
+#[derive(Clone, Copy, Debug, Default, Pread, SizeWith, PartialEq, Eq)]
+pub struct CONTEXT_CHUNK {
+    pub offset: i32,
+    pub length: u32,
+}
+
+/// A description of an extended context, which follows a context with
+/// [`CONTEXT_HAS_XSTATE`] set.
+///
+/// This struct matches the definition of `CONTEXT_EX` in WinNT.h. See
+/// [`XSTATE_CONFIG_FEATURE_MSC_INFO`] for how to read the XSTATE chunk.
+#[derive(Clone, Copy, Debug, Default, Pread, SizeWith, PartialEq, Eq)]
+pub struct CONTEXT_EX {
+    /// The whole context, including the classic context before this struct.
+    pub all: CONTEXT_CHUNK,
+    /// The classic context (e.g. the [`CONTEXT_AMD64`]).
+    pub legacy: CONTEXT_CHUNK,
+    /// The part of the XSAVE context that isn't in the classic context.
+    pub xstate: CONTEXT_CHUNK,
+}
+
+/// The header of an XSAVE context, at the start of [`CONTEXT_EX::xstate`].
+///
+/// This struct matches the definition of `XSAVE_AREA_HEADER` in WinNT.h.
+#[derive(Clone, Copy, Debug, Default, Pread, SizeWith, PartialEq, Eq)]
+pub struct XSAVE_AREA_HEADER {
+    /// The bit `mask[i]` indicates that feature `i` was saved. Features that
+    /// are enabled but weren't saved are in their initial state (all zeroes).
+    pub mask: u64,
+    /// If the top bit is set, the XSAVE context is compacted and this is the
+    /// set of enabled features.
+    pub compaction_mask: u64,
+    pub reserved: [u64; 6],
+}
+
 /// The offset and size of each XSAVE entry inside the XSAVE context.
 #[derive(Clone, Copy, Debug, Default, Pread, SizeWith, PartialEq, Eq)]
 pub struct XSTATE_FEATURE {
@@ -7771,6 +8189,224 @@ bitflags! {
     }
 }
 
+/// Basic information about the system, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_SYSTEM_BASIC_INFORMATION {
+    pub timer_resolution: u32,
+    pub page_size: u32,
+    pub number_of_physical_pages: u32,
+    pub lowest_physical_page_number: u32,
+    pub highest_physical_page_number: u32,
+    pub allocation_granularity: u32,
+    pub minimum_user_mode_address: u64,
+    pub maximum_user_mode_address: u64,
+    pub active_processors_affinity_mask: u64,
+    pub number_of_processors: u32,
+}
+
+/// Information about the system's file cache, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_SYSTEM_FILECACHE_INFORMATION {
+    pub current_size: u64,
+    pub peak_size: u64,
+    pub page_fault_count: u32,
+    pub minimum_working_set: u64,
+    pub maximum_working_set: u64,
+    pub current_size_including_transition_in_pages: u64,
+    pub peak_size_including_transition_in_pages: u64,
+    pub transition_re_purpose_count: u32,
+    pub flags: u32,
+}
+
+/// The system's commit charge, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// All values are in pages. This struct matches the struct of the same name
+/// from minidumpapiset.h.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION {
+    pub available_pages: u64,
+    pub committed_pages: u64,
+    pub commit_limit: u64,
+    pub peak_commitment: u64,
+}
+
+/// System-wide performance counters, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_SYSTEM_PERFORMANCE_INFORMATION {
+    pub idle_process_time: u64,
+    pub io_read_transfer_count: u64,
+    pub io_write_transfer_count: u64,
+    pub io_other_transfer_count: u64,
+    pub io_read_operation_count: u32,
+    pub io_write_operation_count: u32,
+    pub io_other_operation_count: u32,
+    pub available_pages: u32,
+    pub committed_pages: u32,
+    pub commit_limit: u32,
+    pub peak_commitment: u32,
+    pub page_fault_count: u32,
+    pub copy_on_write_count: u32,
+    pub transition_count: u32,
+    pub cache_transition_count: u32,
+    pub demand_zero_count: u32,
+    pub page_read_count: u32,
+    pub page_read_io_count: u32,
+    pub cache_read_count: u32,
+    pub cache_io_count: u32,
+    pub dirty_pages_write_count: u32,
+    pub dirty_write_io_count: u32,
+    pub mapped_pages_write_count: u32,
+    pub mapped_write_io_count: u32,
+    pub paged_pool_pages: u32,
+    pub non_paged_pool_pages: u32,
+    pub paged_pool_allocs: u32,
+    pub paged_pool_frees: u32,
+    pub non_paged_pool_allocs: u32,
+    pub non_paged_pool_frees: u32,
+    pub free_system_ptes: u32,
+    pub resident_system_code_page: u32,
+    pub total_system_driver_pages: u32,
+    pub total_system_code_pages: u32,
+    pub non_paged_pool_lookaside_hits: u32,
+    pub paged_pool_lookaside_hits: u32,
+    pub available_paged_pool_pages: u32,
+    pub resident_system_cache_page: u32,
+    pub resident_paged_pool_page: u32,
+    pub resident_system_driver_page: u32,
+    pub cc_fast_read_no_wait: u32,
+    pub cc_fast_read_wait: u32,
+    pub cc_fast_read_resource_miss: u32,
+    pub cc_fast_read_not_possible: u32,
+    pub cc_fast_mdl_read_no_wait: u32,
+    pub cc_fast_mdl_read_wait: u32,
+    pub cc_fast_mdl_read_resource_miss: u32,
+    pub cc_fast_mdl_read_not_possible: u32,
+    pub cc_map_data_no_wait: u32,
+    pub cc_map_data_wait: u32,
+    pub cc_map_data_no_wait_miss: u32,
+    pub cc_map_data_wait_miss: u32,
+    pub cc_pin_mapped_data_count: u32,
+    pub cc_pin_read_no_wait: u32,
+    pub cc_pin_read_wait: u32,
+    pub cc_pin_read_no_wait_miss: u32,
+    pub cc_pin_read_wait_miss: u32,
+    pub cc_copy_read_no_wait: u32,
+    pub cc_copy_read_wait: u32,
+    pub cc_copy_read_no_wait_miss: u32,
+    pub cc_copy_read_wait_miss: u32,
+    pub cc_mdl_read_no_wait: u32,
+    pub cc_mdl_read_wait: u32,
+    pub cc_mdl_read_no_wait_miss: u32,
+    pub cc_mdl_read_wait_miss: u32,
+    pub cc_read_ahead_ios: u32,
+    pub cc_lazy_write_ios: u32,
+    pub cc_lazy_write_pages: u32,
+    pub cc_data_flushes: u32,
+    pub cc_data_pages: u32,
+    pub context_switches: u32,
+    pub first_level_tb_fills: u32,
+    pub second_level_tb_fills: u32,
+    pub system_calls: u32,
+    pub cc_total_dirty_pages: u64,
+    pub cc_dirty_page_threshold: u64,
+    pub resident_available_pages: i64,
+    pub shared_committed_pages: u64,
+}
+
+/// System-wide memory information
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream`].
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_SYSTEM_MEMORY_INFO_1 {
+    pub revision: u16,
+    /// Which optional parts of the struct are valid, see [`SystemMemoryInfoFlags`]
+    pub flags: u16,
+    pub basic_info: MINIDUMP_SYSTEM_BASIC_INFORMATION,
+    pub file_cache_info: MINIDUMP_SYSTEM_FILECACHE_INFORMATION,
+    pub basic_perf_info: MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION,
+    pub perf_info: MINIDUMP_SYSTEM_PERFORMANCE_INFORMATION,
+}
+
+bitflags! {
+    /// Known flags for [`MINIDUMP_SYSTEM_MEMORY_INFO_1::flags`]
+    pub struct SystemMemoryInfoFlags: u16 {
+        const MINIDUMP_SYSMEMINFO1_FILECACHE_TRANSITIONREPURPOSECOUNT_FLAGS = 0x0001;
+        const MINIDUMP_SYSMEMINFO1_BASICPERF = 0x0002;
+        const MINIDUMP_SYSMEMINFO1_PERF_CCTOTALDIRTYPAGES_CCDIRTYPAGETHRESHOLD = 0x0004;
+        const MINIDUMP_SYSMEMINFO1_PERF_RESIDENTAVAILABLEPAGES_SHAREDCOMMITPAGES = 0x0008;
+    }
+}
+
+/// The process's virtual memory counters
+///
+/// This is the original format of the [`MINIDUMP_STREAM_TYPE::ProcessVmCountersStream`].
+/// All sizes are in bytes. This struct matches the struct of the same name from
+/// minidumpapiset.h, which is packed to 4 bytes.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_PROCESS_VM_COUNTERS_1 {
+    pub revision: u16,
+    pub __align: u16,
+    pub page_fault_count: u32,
+    pub peak_working_set_size: u64,
+    pub working_set_size: u64,
+    pub quota_peak_paged_pool_usage: u64,
+    pub quota_paged_pool_usage: u64,
+    pub quota_peak_non_paged_pool_usage: u64,
+    pub quota_non_paged_pool_usage: u64,
+    pub pagefile_usage: u64,
+    pub peak_pagefile_usage: u64,
+    pub private_usage: u64,
+}
+
+/// The process's virtual memory counters
+///
+/// This is the newer format of the [`MINIDUMP_STREAM_TYPE::ProcessVmCountersStream`].
+/// All sizes are in bytes, and `flags` says which of them are valid (see
+/// [`ProcessVmCountersFlags`]). This struct matches the struct of the same name
+/// from minidumpapiset.h.
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_PROCESS_VM_COUNTERS_2 {
+    pub revision: u16,
+    pub flags: u16,
+    pub page_fault_count: u32,
+    pub peak_working_set_size: u64,
+    pub working_set_size: u64,
+    pub quota_peak_paged_pool_usage: u64,
+    pub quota_paged_pool_usage: u64,
+    pub quota_peak_non_paged_pool_usage: u64,
+    pub quota_non_paged_pool_usage: u64,
+    pub pagefile_usage: u64,
+    pub peak_pagefile_usage: u64,
+    pub peak_virtual_size: u64,
+    pub virtual_size: u64,
+    pub private_usage: u64,
+    pub private_working_set_size: u64,
+    pub shared_commit_usage: u64,
+    pub job_shared_commit_usage: u64,
+    pub job_private_commit_usage: u64,
+    pub job_peak_private_commit_usage: u64,
+    pub job_private_commit_limit: u64,
+    pub job_total_commit_limit: u64,
+}
+
+bitflags! {
+    /// Known flags for [`MINIDUMP_PROCESS_VM_COUNTERS_2::flags`]
+    pub struct ProcessVmCountersFlags: u16 {
+        const MINIDUMP_PROCESS_VM_COUNTERS = 0x0001;
+        const MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE = 0x0002;
+        const MINIDUMP_PROCESS_VM_COUNTERS_EX = 0x0004;
+        const MINIDUMP_PROCESS_VM_COUNTERS_EX2 = 0x0008;
+        const MINIDUMP_PROCESS_VM_COUNTERS_JOB = 0x0010;
+    }
+}
+
 /// A list of memory regions in a minidump
 ///
 /// This is the format of the [`MINIDUMP_STREAM_TYPE::MemoryInfoListStream`]. The individual
@@ -7862,6 +8498,281 @@ bitflags! {
     }
 }
 
+/// A list of extra thread information in a minidump
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::ThreadInfoListStream`]. The individual
+/// [`MINIDUMP_THREAD_INFO`] entries follow this header in the stream.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info_list
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_THREAD_INFO_LIST {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each entry in the list
+    pub size_of_entry: u32,
+    /// The number of entries in the list
+    pub number_of_entries: u32,
+}
+
+/// Extra information about a thread in a minidump
+///
+/// Times are in the units of a Windows `FILETIME`: 100-nanosecond intervals, counted from
+/// January 1, 1601 (UTC) for `create_time` and `exit_time`.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info
+#[derive(Debug, Clone, PartialEq, Eq, Pread, SizeWith)]
+pub struct MINIDUMP_THREAD_INFO {
+    /// The identifier of this thread
+    pub thread_id: u32,
+    /// Flags describing how the thread was dumped
+    ///
+    /// See [`ThreadInfoDumpFlags`] for valid values.
+    pub dump_flags: u32,
+    /// An `HRESULT` describing why the thread's state couldn't be dumped, if it couldn't
+    pub dump_error: u32,
+    /// The thread's exit code, if it had exited
+    pub exit_status: u32,
+    /// When the thread was created
+    pub create_time: u64,
+    /// When the thread exited, if it had exited
+    pub exit_time: u64,
+    /// The time the thread has spent executing in kernel mode
+    pub kernel_time: u64,
+    /// The time the thread has spent executing in user mode
+    pub user_time: u64,
+    /// The address of the function the thread started executing at
+    pub start_address: u64,
+    /// The processor affinity mask of the thread
+    pub affinity: u64,
+}
+
+bitflags! {
+    /// Potential values for [`MINIDUMP_THREAD_INFO::dump_flags`]
+    pub struct ThreadInfoDumpFlags: u32 {
+        /// A placeholder thread that was not included due to an error
+        const MINIDUMP_THREAD_INFO_ERROR_THREAD    = 0x00000001;
+        /// The thread that wrote the minidump
+        const MINIDUMP_THREAD_INFO_WRITING_THREAD  = 0x00000002;
+        /// The thread had exited and was not included in the dump
+        const MINIDUMP_THREAD_INFO_EXITED_THREAD   = 0x00000004;
+        /// This information wasn't fully populated
+        const MINIDUMP_THREAD_INFO_INVALID_INFO    = 0x00000008;
+        /// The thread's context couldn't be retrieved
+        const MINIDUMP_THREAD_INFO_INVALID_CONTEXT = 0x00000010;
+        /// The thread's TEB couldn't be retrieved
+        const MINIDUMP_THREAD_INFO_INVALID_TEB     = 0x00000020;
+    }
+}
+
+/// The header of the handle data stream
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::HandleDataStream`]. The individual
+/// [`MINIDUMP_HANDLE_DESCRIPTOR`] or [`MINIDUMP_HANDLE_DESCRIPTOR_2`] entries follow this
+/// header in the stream, as indicated by `size_of_descriptor`.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_handle_data_stream
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_HANDLE_DATA_STREAM {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each descriptor in the stream
+    pub size_of_descriptor: u32,
+    /// The number of descriptors in the stream
+    pub number_of_descriptors: u32,
+    pub reserved: u32,
+}
+
+/// A handle the process had open
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_handle_descriptor
+#[derive(Debug, Clone, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_HANDLE_DESCRIPTOR {
+    /// The value of the handle
+    pub handle: u64,
+    /// An RVA to a `MINIDUMP_STRING` naming the type of the object, or 0
+    pub type_name_rva: RVA,
+    /// An RVA to a `MINIDUMP_STRING` naming the object, or 0
+    pub object_name_rva: RVA,
+    /// The attributes of the handle
+    pub attributes: u32,
+    /// The access rights the handle was granted
+    pub granted_access: u32,
+    /// The number of handles to the object
+    pub handle_count: u32,
+    /// The number of pointers to the object
+    pub pointer_count: u32,
+}
+
+/// A handle the process had open, with extra information about the object
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_handle_descriptor_2
+#[derive(Debug, Clone, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_HANDLE_DESCRIPTOR_2 {
+    /// The value of the handle
+    pub handle: u64,
+    /// An RVA to a `MINIDUMP_STRING` naming the type of the object, or 0
+    pub type_name_rva: RVA,
+    /// An RVA to a `MINIDUMP_STRING` naming the object, or 0
+    pub object_name_rva: RVA,
+    /// The attributes of the handle
+    pub attributes: u32,
+    /// The access rights the handle was granted
+    pub granted_access: u32,
+    /// The number of handles to the object
+    pub handle_count: u32,
+    /// The number of pointers to the object
+    pub pointer_count: u32,
+    /// An RVA to a list of `MINIDUMP_HANDLE_OBJECT_INFORMATION` structs, or 0
+    pub object_info_rva: RVA,
+    pub reserved0: u32,
+}
+
+/// The header of the handle operation list stream
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::HandleOperationListStream`]. The
+/// individual [`AVRF_HANDLE_OPERATION`] entries follow this header in the stream.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_handle_operation_list
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_HANDLE_OPERATION_LIST {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each entry in the list
+    pub size_of_entry: u32,
+    /// The number of entries in the list
+    pub number_of_entries: u32,
+    pub reserved: u32,
+}
+
+/// The maximum number of return addresses in an [`AVRF_BACKTRACE_INFORMATION`]
+pub const AVRF_MAX_TRACES: usize = 32;
+
+/// A backtrace captured by Application Verifier
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/avrfsdk/ns-avrfsdk-avrf_backtrace_information
+#[derive(Debug, Clone, PartialEq, Eq, Pread, SizeWith)]
+pub struct AVRF_BACKTRACE_INFORMATION {
+    /// The number of valid entries in `return_addresses`
+    pub depth: u32,
+    /// The index of this backtrace in Application Verifier's database
+    pub index: u32,
+    /// The return addresses of the backtrace, innermost first
+    pub return_addresses: [u64; 32], // AVRF_MAX_TRACES
+}
+
+/// An operation on a handle, traced by Application Verifier
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/avrfsdk/ns-avrfsdk-avrf_handle_operation
+#[derive(Debug, Clone, PartialEq, Eq, Pread, SizeWith)]
+pub struct AVRF_HANDLE_OPERATION {
+    /// The value of the handle
+    pub handle: u64,
+    /// The process that performed the operation
+    pub process_id: u32,
+    /// The thread that performed the operation
+    pub thread_id: u32,
+    /// What kind of operation this was
+    ///
+    /// See [`HandleTraceOperation`] for valid values.
+    pub operation_type: u32,
+    pub spare0: u32,
+    /// The backtrace of the operation
+    pub back_trace_information: AVRF_BACKTRACE_INFORMATION,
+}
+
+/// Potential values for [`AVRF_HANDLE_OPERATION::operation_type`]
+///
+/// This matches the Microsoft `eHANDLE_TRACE_OPERATIONS` enum.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum HandleTraceOperation {
+    OperationDbUnused = 0,
+    /// The handle was opened
+    OperationDbOPEN = 1,
+    /// The handle was closed
+    OperationDbCLOSE = 2,
+    /// An invalid handle was used
+    OperationDbBADREF = 3,
+}
+
+/// The header of the function table stream
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::FunctionTable`] stream. Each
+/// [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`] in the stream is followed by the process's native
+/// description of the table, its function entries (on x64, [`IMAGE_RUNTIME_FUNCTION_ENTRY`]),
+/// and then some padding.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_function_table_stream
+#[derive(Debug, Clone, Pread, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_STREAM {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`]
+    pub size_of_descriptor: u32,
+    /// The size of the native description of each table
+    pub size_of_native_descriptor: u32,
+    /// The size of each function entry
+    pub size_of_function_entry: u32,
+    /// The number of function tables in the stream
+    pub number_of_descriptors: u32,
+    /// The size of the padding between this header and the first descriptor
+    pub size_of_align_pad: u32,
+}
+
+/// A dynamically registered function table
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_function_table_descriptor
+#[derive(Debug, Clone, PartialEq, Eq, Pread, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_DESCRIPTOR {
+    /// The lowest address covered by the table
+    pub minimum_address: u64,
+    /// The highest address covered by the table
+    pub maximum_address: u64,
+    /// The address that the table's entries are relative to
+    pub base_address: u64,
+    /// The number of function entries in the table
+    pub entry_count: u32,
+    /// The size of the padding after the table's function entries
+    pub size_of_align_pad: u32,
+}
+
+/// An x64 function table entry, describing where to find a function's unwind info
+///
+/// All the addresses are relative to the base address of the module (or function table)
+/// containing the function.
+///
+/// This struct matches the Microsoft struct of the same name from winnt.h, which is
+/// also known as `RUNTIME_FUNCTION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pread, SizeWith)]
+pub struct IMAGE_RUNTIME_FUNCTION_ENTRY {
+    /// The start of the function
+    pub begin_address: u32,
+    /// The end of the function
+    pub end_address: u32,
+    /// The location of the function's `UNWIND_INFO`
+    pub unwind_info_address: u32,
+}
+
 /// A Breakpad extension containing some additional process information
 ///
 /// Taken from the definition in Breakpad's [minidump_format.h][fmt].
@@ -1,5 +1,5 @@
 //! This crate defines [structs for the on-disk minidump format](format/index.html) as well as
-//! [some common traits](traits/index.html) used by related crates.
+//! [some common traits](traits/index.html) and [helpers](utils/index.html) used by related crates.
 //!
 //! You probably don't want to use this crate directly, the [minidump][minidump] crate provides
 //! the actual functionality of reading minidumps using the structs defined in this crate.
@@ -10,3 +10,4 @@
 
 pub mod format;
 pub mod traits;
+pub mod utils;
@@ -0,0 +1,48 @@
+//! Helpers for strings found in minidumps.
+
+/// The file name at the end of `path`, which may be a Windows or POSIX-style path.
+///
+/// Module paths in minidumps come from whatever OS wrote them, so this splits on
+/// both `/` and `\`, regardless of the host. That also handles Windows device
+/// and namespace paths like `\Device\HarddiskVolume4\Windows\System32\ntdll.dll`,
+/// `\\?\C:\Windows\ntdll.dll` and UNC paths with either kind of slash, as well as
+/// drive-relative paths like `C:ntdll.dll`. Trailing separators are ignored.
+///
+/// ```
+/// use minidump_common::utils::basename;
+///
+/// assert_eq!(basename(r"C:\Windows\System32\ntdll.dll"), "ntdll.dll");
+/// assert_eq!(basename(r"\Device\HarddiskVolume4\Windows\System32\ntdll.dll"), "ntdll.dll");
+/// assert_eq!(basename("//server/share/lib/foo.dll"), "foo.dll");
+/// assert_eq!(basename("/usr/lib/libc.so.6"), "libc.so.6");
+/// ```
+pub fn basename(path: &str) -> &str {
+    let path = path.trim_end_matches(['/', '\\']);
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match name.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => &name[2..],
+        _ => name,
+    }
+}
+
+/// The PDB file name a Windows module at `code_file` would have, if it's a *.dll* or *.exe*.
+///
+/// This is the file name of `code_file` with its extension (compared
+/// case-insensitively) swapped for *.pdb*, and is what symbol stores expect
+/// for modules that don't record their PDB name.
+///
+/// ```
+/// use minidump_common::utils::pdb_file_name;
+///
+/// assert_eq!(pdb_file_name(r"C:\Windows\System32\KERNEL32.DLL").as_deref(), Some("KERNEL32.pdb"));
+/// assert_eq!(pdb_file_name("/usr/lib/libc.so.6"), None);
+/// ```
+pub fn pdb_file_name(code_file: &str) -> Option<String> {
+    let leaf = basename(code_file);
+    let (stem, extension) = leaf.rsplit_once('.')?;
+    if extension.eq_ignore_ascii_case("dll") || extension.eq_ignore_ascii_case("exe") {
+        Some(format!("{}.pdb", stem))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,206 @@
+//! Benchmarks for the hot paths of minidump processing.
+//!
+//! Everything is synthesized up front, so these don't need any test data
+//! (or network access). Run them with `cargo bench -p minidump-processor`.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use breakpad_symbols::{SimpleFrame, SimpleModule, StringSymbolSupplier, SymbolFile, Symbolizer};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use minidump::{Minidump, MinidumpMemoryList, MinidumpModuleList, MinidumpThreadList};
+use synth_minidump::{DumpString, Memory, Module, SynthMinidump, SystemInfo, Thread};
+use test_assembler::{Endian, Section};
+
+const MODULE_NAME: &str = "C:\\WINDOWS\\system32\\KERNEL32.DLL";
+const MODULE_BASE: u64 = 0x7c800000;
+const MODULE_SIZE: u64 = 0x400000;
+const FUNCTION_SIZE: u64 = 0x40;
+const FUNCTION_COUNT: u64 = MODULE_SIZE / FUNCTION_SIZE;
+const THREAD_COUNT: u32 = 100;
+const STACK_BASE: u64 = 0x10000000;
+const STACK_SIZE: u64 = 0x800;
+
+/// A symbol file for the whole module, with a function every `FUNCTION_SIZE`
+/// bytes and a line record every 16 bytes of each function.
+///
+/// This is about 7MB, which is on the small side of real symbol files for
+/// large binaries, but big enough for the parser to dominate.
+fn large_symbol_file() -> String {
+    let mut sym = String::new();
+    writeln!(
+        sym,
+        "MODULE windows x86 ABCD1234F00DBEEF01020304050607081 KERNEL32.pdb"
+    )
+    .unwrap();
+    for file in 0..100 {
+        writeln!(sym, "FILE {} c:\\src\\kernel32\\file{}.cpp", file, file).unwrap();
+    }
+    for function in 0..FUNCTION_COUNT {
+        let address = function * FUNCTION_SIZE;
+        writeln!(
+            sym,
+            "FUNC {:x} {:x} 8 kernel32::module{}::Function{}(int, char const*)",
+            address,
+            FUNCTION_SIZE,
+            function % 50,
+            function
+        )
+        .unwrap();
+        for line in 0..FUNCTION_SIZE / 16 {
+            writeln!(
+                sym,
+                "{:x} 10 {} {}",
+                address + line * 16,
+                function * 10 + line,
+                function % 100
+            )
+            .unwrap();
+        }
+    }
+    sym
+}
+
+/// A minidump with `THREAD_COUNT` threads in one large module.
+///
+/// Every thread's stack is seeded with return addresses into the module,
+/// so all of them have to be unwound (by stack scanning) down to the bottom.
+fn many_threads_minidump() -> Vec<u8> {
+    let name = DumpString::new(MODULE_NAME, Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little)
+        // "RSDS" signature
+        .D32(0x53445352)
+        // GUID
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08])
+        // age
+        .D32(1)
+        .append_bytes(b"KERNEL32.PDB\0");
+    let module = Module::new(
+        Endian::Little,
+        MODULE_BASE,
+        MODULE_SIZE as u32,
+        &name,
+        0xb1054a2a,
+        0x0000f8b4,
+        None,
+    )
+    .cv_record(&cv_record);
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module)
+        .add(name)
+        .add(cv_record);
+
+    for thread in 0..THREAD_COUNT {
+        let stack_address = STACK_BASE + thread as u64 * 0x10000;
+        let mut stack = Section::with_endian(Endian::Little);
+        for word in 0..STACK_SIZE / 4 {
+            if word % 4 == 1 {
+                let function = (thread as u64 * 37 + word) % FUNCTION_COUNT;
+                stack = stack.D32((MODULE_BASE + function * FUNCTION_SIZE + 8) as u32);
+            } else {
+                stack = stack.D32(0);
+            }
+        }
+        let stack = Memory::with_section(stack, stack_address);
+        let eip = MODULE_BASE + thread as u64 * FUNCTION_SIZE + 4;
+        let context = synth_minidump::x86_context(Endian::Little, eip as u32, stack_address as u32);
+        dump = dump
+            .add_thread(Thread::new(Endian::Little, thread + 1, &stack, &context))
+            .add(context)
+            .add_memory(stack);
+    }
+
+    dump.finish().unwrap()
+}
+
+fn symbolizer(symbols: &str) -> Symbolizer {
+    let mut modules = HashMap::new();
+    modules.insert(MODULE_NAME.to_string(), symbols.to_string());
+    Symbolizer::new(StringSymbolSupplier::new(modules))
+}
+
+fn bench_parse_minidump(c: &mut Criterion) {
+    let bytes = many_threads_minidump();
+    c.bench_function("parse_minidump", |b| {
+        b.iter(|| {
+            let dump = Minidump::read(black_box(&bytes[..])).unwrap();
+            let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+            let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+            (
+                threads.threads.len(),
+                modules.iter().count(),
+                memory.iter().count(),
+            )
+        })
+    });
+}
+
+fn bench_stackwalk(c: &mut Criterion) {
+    let bytes = many_threads_minidump();
+    let dump = Minidump::read(&bytes[..]).unwrap();
+    // Symbols are cached by the Symbolizer, so only the first iteration
+    // parses them. Everything after that is walking and symbolizing.
+    let provider = symbolizer(&large_symbol_file());
+    let state = minidump_processor::process_minidump(&dump, &provider).unwrap();
+    assert_eq!(state.threads.len(), THREAD_COUNT as usize);
+    assert!(state.threads.iter().all(|thread| thread.frames.len() > 1));
+
+    let mut group = c.benchmark_group("stackwalk");
+    group.sample_size(20);
+    group.bench_function("100_threads_with_symbols", |b| {
+        b.iter(|| minidump_processor::process_minidump(black_box(&dump), &provider).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_parse_symbols(c: &mut Criterion) {
+    let sym = large_symbol_file();
+    let mut group = c.benchmark_group("symbols");
+    group.sample_size(10);
+    group.bench_function("parse_large_sym", |b| {
+        b.iter(|| SymbolFile::from_bytes(black_box(sym.as_bytes())).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let provider = symbolizer(&large_symbol_file());
+    let module = SimpleModule {
+        base_address: Some(MODULE_BASE),
+        size: Some(MODULE_SIZE),
+        code_file: Some(MODULE_NAME.to_string()),
+        ..SimpleModule::new("KERNEL32.pdb", "ABCD1234F00DBEEF01020304050607081")
+    };
+    // Load the symbols before measuring.
+    let mut frame = SimpleFrame::with_instruction(MODULE_BASE);
+    provider.fill_symbol(&module, &mut frame).unwrap();
+
+    // Addresses spread over the whole module, so the lookups don't all hit
+    // the same part of the function and line tables.
+    let addresses: Vec<u64> = (0..1000)
+        .map(|i| MODULE_BASE + (i * 7919 * FUNCTION_SIZE + i % FUNCTION_SIZE) % MODULE_SIZE)
+        .collect();
+    c.bench_function("lookup_1000_addresses", |b| {
+        b.iter(|| {
+            for &address in &addresses {
+                let mut frame = SimpleFrame::with_instruction(address);
+                provider.fill_symbol(&module, &mut frame).unwrap();
+                black_box(frame);
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_parse_minidump,
+    bench_stackwalk,
+    bench_parse_symbols,
+    bench_lookup
+);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+//! Hooks for tagging stack frames.
+//!
+//! A [StackFrameAnnotator][] is run on every frame after it's been symbolized
+//! (see [`ProcessorOptions::annotators`][crate::ProcessorOptions::annotators]),
+//! and can add [`tags`][StackFrame::tags] to it, e.g. to mark frames from
+//! certain modules as noise, or frames from certain functions as being lock
+//! acquisition. [RegexAnnotator][] covers the common cases.
+
+use std::fmt;
+
+use regex::Regex;
+
+use crate::process_state::StackFrame;
+
+/// Something that annotates symbolized stack frames.
+pub trait StackFrameAnnotator: Send + Sync {
+    /// Annotate `frame`, usually by calling [`StackFrame::add_tag`].
+    fn annotate(&self, frame: &mut StackFrame);
+}
+
+impl fmt::Debug for dyn StackFrameAnnotator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StackFrameAnnotator")
+    }
+}
+
+/// A [StackFrameAnnotator][] that tags frames whose function or module
+/// matches a regex.
+///
+/// Functions are matched against their demangled names, and modules against
+/// the file name of their code file (e.g. "xul.dll"). Frames without symbols
+/// or without a module never match the respective kind of rule.
+#[derive(Clone, Debug, Default)]
+pub struct RegexAnnotator {
+    function_rules: Vec<(Regex, String)>,
+    module_rules: Vec<(Regex, String)>,
+}
+
+impl RegexAnnotator {
+    /// Create a `RegexAnnotator` with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tag frames whose function matches `pattern` with `tag`.
+    pub fn tag_functions(&mut self, pattern: &str, tag: &str) -> Result<(), regex::Error> {
+        self.function_rules
+            .push((Regex::new(pattern)?, tag.to_string()));
+        Ok(())
+    }
+
+    /// Tag frames whose module matches `pattern` with `tag`.
+    pub fn tag_modules(&mut self, pattern: &str, tag: &str) -> Result<(), regex::Error> {
+        self.module_rules
+            .push((Regex::new(pattern)?, tag.to_string()));
+        Ok(())
+    }
+}
+
+impl StackFrameAnnotator for RegexAnnotator {
+    fn annotate(&self, frame: &mut StackFrame) {
+        if let Some(function) = frame.demangled_function_name() {
+            for (regex, tag) in &self.function_rules {
+                if regex.is_match(&function) {
+                    frame.add_tag(tag);
+                }
+            }
+        }
+        let module_name = frame
+            .module
+            .as_ref()
+            .map(|module| module.name().to_string());
+        if let Some(name) = module_name {
+            for (regex, tag) in &self.module_rules {
+                if regex.is_match(&name) {
+                    frame.add_tag(tag);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2015 Ted Mielczarek. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+
+use std::path::PathBuf;
+use std::process;
+
+use minidump::Minidump;
+use minidump_processor::{simple_symbol_supplier, MinidumpDiff, ProcessState, Symbolizer};
+
+use clap::{crate_version, App, Arg};
+
+fn make_app() -> App<'static, 'static> {
+    App::new("minidump-diff")
+        .version(crate_version!())
+        .about("Compares two minidumps, e.g. from before and after a fix.")
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Emit a machine-readable JSON diff"),
+        )
+        .arg(
+            Arg::with_name("symbols-path")
+                .long("symbols-path")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .help("A directory of Breakpad .sym files to symbolize the stacks with"),
+        )
+        .arg(
+            Arg::with_name("old")
+                .required(true)
+                .help("The minidump to compare from"),
+        )
+        .arg(
+            Arg::with_name("new")
+                .required(true)
+                .help("The minidump to compare to"),
+        )
+}
+
+fn main() {
+    let matches = make_app().get_matches();
+
+    let symbols_paths = matches
+        .values_of_os("symbols-path")
+        .map(|paths| paths.map(PathBuf::from).collect())
+        .unwrap_or_default();
+    let provider = Symbolizer::new(simple_symbol_supplier(symbols_paths));
+
+    let process = |arg: &str| -> ProcessState {
+        let path = matches.value_of_os(arg).unwrap();
+        let dump = Minidump::read_path(path).unwrap_or_else(|err| {
+            eprintln!("Error reading {}: {}", path.to_string_lossy(), err);
+            process::exit(1);
+        });
+        minidump_processor::process_minidump(&dump, &provider).unwrap_or_else(|err| {
+            eprintln!("Error processing {}: {}", path.to_string_lossy(), err);
+            process::exit(1);
+        })
+    };
+    let diff = MinidumpDiff::new(&process("old"), &process("new"));
+
+    if matches.is_present("json") {
+        println!("{:#}", diff.to_json());
+    } else {
+        print!("{}", diff.to_text());
+    }
+}
@@ -0,0 +1,298 @@
+//! Comparing two minidumps, e.g. from before and after a fix.
+//!
+//! [`minidump_diff`][] does the whole thing, but a [`MinidumpDiff`][] can also
+//! be made from two [`ProcessState`][]s directly, which is useful if you want
+//! them to be symbolized.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ops::Deref;
+
+use minidump::{Minidump, Module};
+use serde_json::json;
+
+use crate::process_state::{CallStack, ProcessState, StackFrame};
+use crate::processor::{process_minidump, ProcessError};
+use crate::symbols::{simple_symbol_supplier, Symbolizer};
+
+/// A module that's in both minidumps, but with different versions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ModuleVersionChange {
+    /// The module's file name, e.g. "kernel32.dll".
+    pub name: String,
+    /// The module's version in the first minidump, if known.
+    pub old_version: Option<String>,
+    /// The module's version in the second minidump, if known.
+    pub new_version: Option<String>,
+    /// Whether the version in the second minidump is newer, if both are known.
+    pub is_upgrade: Option<bool>,
+}
+
+/// The differences between two minidumps.
+///
+/// Everything is from the perspective of going from the first minidump to
+/// the second, so "added" modules are the ones only in the second.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MinidumpDiff {
+    /// Modules that are only in the second minidump.
+    pub added_modules: Vec<String>,
+    /// Modules that are only in the first minidump.
+    pub removed_modules: Vec<String>,
+    /// Modules that are in both minidumps, but with different versions.
+    pub changed_modules: Vec<ModuleVersionChange>,
+    /// The number of threads in each minidump.
+    pub thread_counts: (usize, usize),
+    /// The crash reason of each minidump, if it crashed.
+    pub crash_reasons: (Option<String>, Option<String>),
+    /// The crash address of each minidump, if it crashed.
+    pub crash_addresses: (Option<u64>, Option<u64>),
+    /// How similar the crashing threads' stacks are, from 0.0 (nothing in common)
+    /// to 1.0 (the same functions).
+    ///
+    /// This is the Jaccard index of the sets of function names in each stack.
+    /// Frames without symbols are named by their module and offset instead.
+    pub stack_similarity: f64,
+}
+
+/// Compare two minidumps.
+///
+/// No symbols are used, so the stacks are compared by module and offset.
+/// To compare symbolized stacks, process the minidumps yourself and use
+/// [`MinidumpDiff::new`][].
+pub fn minidump_diff<'a, 'b, T, U>(
+    a: &Minidump<'a, T>,
+    b: &Minidump<'b, U>,
+) -> Result<MinidumpDiff, ProcessError>
+where
+    T: Deref<Target = [u8]> + 'a,
+    U: Deref<Target = [u8]> + 'b,
+{
+    let provider = Symbolizer::new(simple_symbol_supplier(vec![]));
+    let a = process_minidump(a, &provider)?;
+    let b = process_minidump(b, &provider)?;
+    Ok(MinidumpDiff::new(&a, &b))
+}
+
+impl MinidumpDiff {
+    /// Compare two processed minidumps.
+    pub fn new(a: &ProcessState, b: &ProcessState) -> MinidumpDiff {
+        let a_modules = module_versions(a);
+        let b_modules = module_versions(b);
+
+        let added_modules = b_modules
+            .keys()
+            .filter(|name| !a_modules.contains_key(*name))
+            .cloned()
+            .collect();
+        let removed_modules = a_modules
+            .keys()
+            .filter(|name| !b_modules.contains_key(*name))
+            .cloned()
+            .collect();
+        let changed_modules = a_modules
+            .iter()
+            .filter_map(|(name, old)| {
+                let new = b_modules.get(name)?;
+                if old == new {
+                    return None;
+                }
+                Some(ModuleVersionChange {
+                    name: name.clone(),
+                    old_version: old.string.clone(),
+                    new_version: new.string.clone(),
+                    is_upgrade: old.tuple.zip(new.tuple).map(|(old, new)| new > old),
+                })
+            })
+            .collect();
+
+        let a_functions = crashing_thread(a).map(stack_functions).unwrap_or_default();
+        let b_functions = crashing_thread(b).map(stack_functions).unwrap_or_default();
+        let union = a_functions.union(&b_functions).count();
+        let stack_similarity = if union == 0 {
+            // Two empty stacks are as similar as stacks get.
+            1.0
+        } else {
+            a_functions.intersection(&b_functions).count() as f64 / union as f64
+        };
+
+        MinidumpDiff {
+            added_modules,
+            removed_modules,
+            changed_modules,
+            thread_counts: (a.threads.len(), b.threads.len()),
+            crash_reasons: (
+                a.crash_reason.map(|reason| reason.to_string()),
+                b.crash_reason.map(|reason| reason.to_string()),
+            ),
+            crash_addresses: (a.crash_address, b.crash_address),
+            stack_similarity,
+        }
+    }
+
+    /// Whether the two minidumps have no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added_modules.is_empty()
+            && self.removed_modules.is_empty()
+            && self.changed_modules.is_empty()
+            && self.thread_counts.0 == self.thread_counts.1
+            && self.crash_reasons.0 == self.crash_reasons.1
+            && self.crash_addresses.0 == self.crash_addresses.1
+            && self.stack_similarity == 1.0
+    }
+
+    /// A human-readable description of the differences.
+    ///
+    /// This does not have a specified format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(&mut text).unwrap();
+        text
+    }
+
+    fn write_text(&self, f: &mut String) -> std::fmt::Result {
+        let version = |version: &Option<String>| version.clone().unwrap_or_else(|| "?".into());
+        let address = |address: &Option<u64>| match address {
+            Some(address) => format!("{:#x}", address),
+            None => "none".into(),
+        };
+
+        if self.added_modules.is_empty()
+            && self.removed_modules.is_empty()
+            && self.changed_modules.is_empty()
+        {
+            writeln!(f, "Modules: no changes")?;
+        } else {
+            writeln!(f, "Modules:")?;
+            for name in &self.added_modules {
+                writeln!(f, "  + {}", name)?;
+            }
+            for name in &self.removed_modules {
+                writeln!(f, "  - {}", name)?;
+            }
+            for change in &self.changed_modules {
+                write!(
+                    f,
+                    "  ~ {}: {} -> {}",
+                    change.name,
+                    version(&change.old_version),
+                    version(&change.new_version)
+                )?;
+                match change.is_upgrade {
+                    Some(true) => writeln!(f, " (upgrade)")?,
+                    Some(false) => writeln!(f, " (downgrade)")?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+        writeln!(
+            f,
+            "Threads: {} -> {}",
+            self.thread_counts.0, self.thread_counts.1
+        )?;
+        writeln!(
+            f,
+            "Crash reason: {} -> {}",
+            self.crash_reasons.0.as_deref().unwrap_or("none"),
+            self.crash_reasons.1.as_deref().unwrap_or("none")
+        )?;
+        writeln!(
+            f,
+            "Crash address: {} -> {}",
+            address(&self.crash_addresses.0),
+            address(&self.crash_addresses.1)
+        )?;
+        writeln!(
+            f,
+            "Crashing thread stack similarity: {:.0}%",
+            self.stack_similarity * 100.0
+        )
+    }
+
+    /// A machine-readable description of the differences.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "modules": {
+                "added": self.added_modules,
+                "removed": self.removed_modules,
+                "changed": self.changed_modules.iter().map(|change| json!({
+                    "name": change.name,
+                    // optional
+                    "old_version": change.old_version,
+                    // optional
+                    "new_version": change.new_version,
+                    // optional
+                    "is_upgrade": change.is_upgrade,
+                })).collect::<Vec<_>>(),
+            },
+            "thread_count": [self.thread_counts.0, self.thread_counts.1],
+            // optional
+            "crash_reason": [self.crash_reasons.0, self.crash_reasons.1],
+            // optional
+            "crash_address": [
+                self.crash_addresses.0.map(|address| format!("{:#x}", address)),
+                self.crash_addresses.1.map(|address| format!("{:#x}", address)),
+            ],
+            "stack_similarity": self.stack_similarity,
+        })
+    }
+}
+
+/// A module's version, for comparing modules between minidumps.
+struct ModuleVersion {
+    string: Option<String>,
+    tuple: Option<(u16, u16, u16, u16)>,
+}
+
+impl PartialEq for ModuleVersion {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare the actual numbers when there are any, the strings are just
+        // for modules that have a version some other way.
+        match (self.tuple, other.tuple) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.string == other.string,
+        }
+    }
+}
+
+/// The version of each module in `state`, by file name.
+fn module_versions(state: &ProcessState) -> BTreeMap<String, ModuleVersion> {
+    state
+        .modules
+        .iter()
+        .map(|module| {
+            let name = module.name().to_string();
+            let version = ModuleVersion {
+                string: module.version().map(String::from),
+                tuple: module.version_as_tuple(),
+            };
+            (name, version)
+        })
+        .collect()
+}
+
+fn crashing_thread(state: &ProcessState) -> Option<&CallStack> {
+    state
+        .requesting_thread
+        .and_then(|index| state.threads.get(index))
+        .or_else(|| state.threads.first())
+}
+
+fn stack_functions(stack: &CallStack) -> BTreeSet<String> {
+    stack.frames.iter().map(frame_function).collect()
+}
+
+fn frame_function(frame: &StackFrame) -> String {
+    if let Some(function) = &frame.function_name {
+        return function.clone();
+    }
+    match &frame.module {
+        Some(module) => format!(
+            "{}+{:#x}",
+            module.name(),
+            frame.instruction - module.base_address()
+        ),
+        None => format!("{:#x}", frame.instruction),
+    }
+}
@@ -74,15 +74,27 @@
 #[cfg(doctest)]
 doc_comment::doctest!("../README.md");
 
+mod annotator;
+mod diff;
 mod evil;
 mod process_state;
 mod processor;
+mod redact;
+mod signature;
+mod source_link;
 mod stackwalker;
 pub mod symbols;
 mod system_info;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
 
+pub use crate::annotator::*;
+pub use crate::diff::*;
 pub use crate::process_state::*;
 pub use crate::processor::*;
+pub use crate::redact::*;
+pub use crate::signature::*;
+pub use crate::source_link::*;
 pub use crate::stackwalker::*;
 pub use crate::symbols::*;
 pub use crate::system_info::*;
@@ -4,12 +4,16 @@
 //! The state of a process.
 
 use std::borrow::{Borrow, Cow};
+use std::cmp::Reverse;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io;
 use std::io::prelude::*;
+use std::time::Duration;
 
+use crate::signature::NoiseList;
+use crate::source_link::VcsPath;
 use crate::system_info::SystemInfo;
-use crate::{FrameSymbolizer, SymbolStats};
+use crate::{FrameSymbolizer, SymbolError, SymbolStats};
 use chrono::prelude::*;
 use minidump::system_info::Cpu;
 use minidump::*;
@@ -18,9 +22,13 @@ use serde_json::json;
 /// Indicates how well the instruction pointer derived during
 /// stack walking is trusted. Since the stack walker can resort to
 /// stack scanning, it can wind up with dubious frames.
-#[derive(Copy, Clone, Debug, PartialEq)]
+///
+/// The variants are ordered from least to most trusted, so e.g.
+/// `FrameTrust::Scan < FrameTrust::FramePointer`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FrameTrust {
     /// Unknown
+    #[default]
     None,
     /// Scanned the stack, found this.
     Scan,
@@ -36,8 +44,62 @@ pub enum FrameTrust {
     Context,
 }
 
+/// How an x86 function expects to be called.
+///
+/// The other architectures we support only have one calling convention each,
+/// so this is only ever inferred for x86.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// Arguments on the stack, which the caller pops.
+    Cdecl,
+    /// Arguments on the stack, which the callee pops.
+    Stdcall,
+    /// Like `Stdcall`, but with `this` in ecx.
+    Thiscall,
+    /// Like `Stdcall`, but with the first two arguments in ecx and edx.
+    Fastcall,
+}
+
+impl CallingConvention {
+    /// Infer the calling convention of an x86 function from its symbol.
+    ///
+    /// The size of a function's stack parameters is recorded regardless of
+    /// its calling convention, so on its own it can't tell them apart. But
+    /// MSVC C++ symbols spell out their calling convention, and decorated C
+    /// symbols (like `PUBLIC` records often retain) encode it along with the
+    /// parameter size: `_name@8` is stdcall and `@name@8` is fastcall. The
+    /// decoration is only trusted if its size agrees with `parameter_size`.
+    pub fn infer(function_name: &str, parameter_size: Option<u32>) -> Option<CallingConvention> {
+        let demangled = demangle(function_name);
+        for (keyword, convention) in [
+            ("__cdecl", CallingConvention::Cdecl),
+            ("__stdcall", CallingConvention::Stdcall),
+            ("__thiscall", CallingConvention::Thiscall),
+            ("__fastcall", CallingConvention::Fastcall),
+        ] {
+            if demangled.contains(keyword) {
+                return Some(convention);
+            }
+        }
+
+        let (name, size) = function_name.rsplit_once('@')?;
+        let size = size.parse::<u32>().ok()?;
+        if parameter_size.is_some_and(|parameter_size| parameter_size != size) {
+            return None;
+        }
+        if name.len() > 1 && name.starts_with('@') {
+            Some(CallingConvention::Fastcall)
+        } else if name.len() > 1 && name.starts_with('_') && !name.contains('@') {
+            Some(CallingConvention::Stdcall)
+        } else {
+            None
+        }
+    }
+}
+
 /// A single stack frame produced from unwinding a thread's stack.
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct StackFrame {
     /// The program counter location as an absolute virtual address.
     ///
@@ -88,9 +150,23 @@ pub struct StackFrame {
     /// WIN STACK unwinding needs this value to work; it's otherwise uninteresting.
     pub parameter_size: Option<u32>,
 
+    /// The calling convention of the function, if it could be inferred from
+    /// its symbol (see [`CallingConvention::infer`]). Only used on x86.
+    pub calling_convention: Option<CallingConvention>,
+
     /// The source file name, may be omitted if debug symbols are not available.
+    ///
+    /// This is exactly as it appears in the symbols, so it may include VCS
+    /// annotations (see [`VcsPath`]).
     pub source_file_name: Option<String>,
 
+    /// The path of `source_file_name` with any VCS annotations removed.
+    pub source_file_path: Option<String>,
+
+    /// A permalink to `source_line` of the source file, if it has VCS
+    /// annotations and there's a URL template for its VCS.
+    pub source_link: Option<String>,
+
     /// The (1-based) source line number, may be omitted if debug symbols are
     /// not available.
     pub source_line: Option<u32>,
@@ -99,6 +175,19 @@ pub struct StackFrame {
     /// are not available.
     pub source_line_base: Option<u64>,
 
+    /// Why the symbols for `module` couldn't be used, if they couldn't.
+    ///
+    /// This distinguishes a module that has no symbols
+    /// ([`SymbolError::NotFound`]) from one whose symbols couldn't be
+    /// acquired in time ([`SymbolError::Timeout`]), e.g. because a symbol
+    /// server was unreachable. Always `None` if the module's symbols were
+    /// loaded, or if the symbol provider can't tell.
+    pub symbol_error: Option<SymbolError>,
+
+    /// Tags added by [`ProcessorOptions::annotators`][crate::ProcessorOptions::annotators],
+    /// in the order they were added.
+    pub tags: Vec<String>,
+
     /// Amount of trust the stack walker has in the instruction pointer
     /// of this frame.
     pub trust: FrameTrust,
@@ -107,8 +196,27 @@ pub struct StackFrame {
     pub context: MinidumpContext,
 }
 
+/// The function a thread started executing at, from the `ThreadInfoListStream`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ThreadStartRoutine {
+    /// The address the thread started executing at.
+    pub address: u64,
+
+    /// The `MinidumpModule` that contains `address`, if any.
+    pub module: Option<MinidumpModule>,
+
+    /// The function name, may be omitted if debug symbols are not available.
+    pub function_name: Option<String>,
+
+    /// The start address of the function, may be omitted if debug symbols
+    /// are not available.
+    pub function_base: Option<u64>,
+}
+
 /// Information about the results of unwinding a thread's stack.
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum CallStackInfo {
     /// Everything went great.
     Ok,
@@ -120,9 +228,12 @@ pub enum CallStackInfo {
     UnsupportedCpu,
     /// This thread wrote the minidump, it was skipped.
     DumpThreadSkipped,
+    /// This thread was excluded by `ProcessorOptions::thread_filter`, it was skipped.
+    Filtered,
 }
 
 /// A stack of `StackFrame`s produced as a result of unwinding a thread.
+#[non_exhaustive]
 pub struct CallStack {
     /// The stack frames.
     /// By convention, the stack frame at index 0 is the innermost callee frame,
@@ -135,9 +246,28 @@ pub struct CallStack {
     pub thread_name: Option<String>,
     /// The GetLastError() value stored in the TEB.
     pub last_error_value: Option<CrashReason>,
+    /// The function the thread started executing at, if known.
+    pub start_routine: Option<ThreadStartRoutine>,
+    /// The time the thread spent executing in kernel mode, if known.
+    pub kernel_time: Option<Duration>,
+    /// The time the thread spent executing in user mode, if known.
+    pub user_time: Option<Duration>,
+}
+
+/// A summary of the handles the process had open, from the `HandleDataStream`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HandleSummary {
+    /// The total number of open handles.
+    pub total: usize,
+    /// The number of open handles of each object type (e.g. "Event", "File").
+    ///
+    /// Handles with no type name are counted under "Unknown".
+    pub by_type: BTreeMap<String, usize>,
 }
 
 #[derive(Debug, Default)]
+#[non_exhaustive]
 pub struct LinuxStandardBase {
     pub id: String,
     pub release: String,
@@ -145,10 +275,97 @@ pub struct LinuxStandardBase {
     pub description: String,
 }
 
+/// A shared library the dynamic linker and the module list disagree about.
+///
+/// See [`ProcessState::dso_mismatches`][].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DsoMismatch {
+    /// The library's path, according to the dynamic linker.
+    pub name: String,
+    /// Where the dynamic linker says the library is loaded.
+    pub link_map_address: u64,
+    /// Where the module list says the library is loaded, or `None` if it
+    /// isn't in the module list at all.
+    pub module_address: Option<u64>,
+}
+
+/// How much memory the process was using when the minidump was written.
+///
+/// All values are in bytes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemoryUsage {
+    /// The largest the virtual memory size of the process ever got.
+    pub peak_virtual_size: Option<u64>,
+    /// The virtual memory size of the process.
+    pub virtual_size: Option<u64>,
+    /// How much of the process's memory was resident in RAM.
+    pub resident_size: Option<u64>,
+    /// How much of the process's memory was swapped out.
+    pub swap_size: Option<u64>,
+    /// How much memory the process had committed that can't be shared with
+    /// other processes (Windows' "private bytes").
+    pub private_size: Option<u64>,
+}
+
+/// How much memory the whole system was using when the minidump was written.
+///
+/// All values are in bytes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SystemMemoryUsage {
+    /// How much physical memory the system has.
+    pub physical_size: Option<u64>,
+    /// How much of the physical memory was available.
+    pub available_physical_size: Option<u64>,
+    /// How much memory the system had committed (promised to back with RAM
+    /// or the page file).
+    pub committed_size: Option<u64>,
+    /// The most memory the system can commit. Allocations fail when this is reached.
+    pub commit_limit: Option<u64>,
+    /// The most memory the system ever had committed.
+    pub peak_committed_size: Option<u64>,
+}
+
+/// One frame of one thread's stack, flattened out of a [`ProcessState`][].
+///
+/// See [`ProcessState::symbolicated_frames`][].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SymbolicatedFrame<'a> {
+    /// The index of the frame's thread in [`ProcessState::threads`][].
+    pub thread_index: usize,
+    /// The index of the frame in its thread's stack, 0 being the innermost.
+    pub frame_index: usize,
+    /// The program counter location, as an absolute virtual address.
+    pub instruction: u64,
+    /// The file name of the module containing the instruction, if known.
+    pub module: Option<&'a str>,
+    /// The (mangled) name of the function containing the instruction, if known.
+    pub function: Option<&'a str>,
+    /// The source file the instruction came from, if known.
+    pub file: Option<&'a str>,
+    /// The source line the instruction came from, if known.
+    pub line: Option<u32>,
+}
+
 /// The state of a process as recorded by a `Minidump`.
+#[non_exhaustive]
 pub struct ProcessState {
     /// The PID of the process.
     pub process_id: Option<u32>,
+    /// The name of the process, if the minidump records it.
+    pub process_name: Option<String>,
+    /// How much memory the process was using, if the minidump records it.
+    pub memory_usage: Option<MemoryUsage>,
+    /// How much memory the system was using, if the minidump records it.
+    pub system_memory_usage: Option<SystemMemoryUsage>,
+    /// The environment variables allowed by [`ProcessorOptions::environ_allowlist`][],
+    /// if the minidump records the environment.
+    ///
+    /// [`ProcessorOptions::environ_allowlist`]: crate::ProcessorOptions::environ_allowlist
+    pub environment: Option<BTreeMap<String, String>>,
     /// When the minidump was written.
     pub time: DateTime<Utc>,
     /// When the process started, if available
@@ -165,6 +382,15 @@ pub struct ProcessState {
     /// errors, this will be the address of the instruction that caused the
     /// fault.
     pub crash_address: Option<u64>,
+    /// What the crash address pointed at, if the minidump has memory info for it.
+    ///
+    /// e.g. "write to a PAGE_READONLY committed region"
+    pub crash_address_description: Option<String>,
+    /// The decoded MXCSR of the crashing thread, if the crash was a floating
+    /// point exception on x86 or x86-64.
+    ///
+    /// e.g. "0x1d84 (raised: ZE; unmasked: ZE; rounding: nearest)"
+    pub crash_mxcsr: Option<String>,
     /// A string describing an assertion that was hit, if present.
     pub assertion: Option<String>,
     /// The index of the thread that requested a dump be written.
@@ -176,6 +402,13 @@ pub struct ProcessState {
     /// extended Breakpad information is present, this field will be
     /// `None`.
     pub requesting_thread: Option<usize>,
+    /// The id of the thread that wrote the dump, if the Breakpad info stream
+    /// says. It isn't walked, see [`CallStackInfo::DumpThreadSkipped`][].
+    pub dump_thread_id: Option<u32>,
+    /// The id of the thread that requested the dump, if the Breakpad info
+    /// stream says. Unless the process crashed on another thread, this is
+    /// the thread [`ProcessState::requesting_thread`][] points at.
+    pub requesting_thread_id: Option<u32>,
     /// Stacks for each thread (except possibly the exception handler
     /// thread) at the time of the crash.
     pub threads: Vec<CallStack>,
@@ -186,16 +419,39 @@ pub struct ProcessState {
     /// Linux Standard Base Info
     pub linux_standard_base: Option<LinuxStandardBase>,
     pub mac_crash_info: Option<Vec<RawMacCrashInfo>>,
+    /// The annotations ("crash keys") a Crashpad client recorded, if it was
+    /// Crashpad that wrote the minidump.
+    pub crashpad_info: Option<MinidumpCrashpadInfo>,
+    /// Shared libraries the dynamic linker had loaded that don't match the
+    /// module list, if the minidump has the dynamic linker's list.
+    ///
+    /// Libraries the module list doesn't have are likely to be missing from
+    /// the backtraces too.
+    pub dso_mismatches: Option<Vec<DsoMismatch>>,
     /// The modules that were loaded into the process represented by the
     /// `ProcessState`.
     pub modules: MinidumpModuleList,
     pub unloaded_modules: MinidumpUnloadedModuleList,
+    /// A summary of the handles the process had open, if the minidump has them.
+    pub handle_summary: Option<HandleSummary>,
     // modules_without_symbols
     // modules_with_corrupt_symbols
     // exploitability
     pub unknown_streams: Vec<MinidumpUnknownStream>,
     pub unimplemented_streams: Vec<MinidumpUnimplementedStream>,
+    /// Every stream in the minidump's stream directory, in directory order.
+    pub streams: Vec<StreamDescriptor>,
+    /// What was missing from the minidump, if it was truncated or corrupt.
+    /// See [`Minidump::warnings`][]. This also has a
+    /// [`MinidumpWarning::ContextCpuMismatch`][] for every thread context
+    /// that didn't match the system info, and a
+    /// [`MinidumpWarning::SystemInfoInferred`][] if there was no system info.
+    pub warnings: Vec<MinidumpWarning>,
     pub symbol_stats: HashMap<String, SymbolStats>,
+    /// The index in `threads` of each thread id, see [`ProcessState::thread_for_id`][].
+    pub(crate) thread_indices: HashMap<u32, usize>,
+    /// The thread id in the exception stream, see [`ProcessState::crashing_thread_id`][].
+    pub(crate) exception_thread_id: Option<u32>,
 }
 
 impl FrameTrust {
@@ -236,19 +492,52 @@ impl StackFrame {
             function_name: None,
             function_base: None,
             parameter_size: None,
+            calling_convention: None,
             source_file_name: None,
+            source_file_path: None,
+            source_link: None,
             source_line: None,
             source_line_base: None,
+            symbol_error: None,
+            tags: Vec::new(),
             trust,
             context,
         }
     }
 
+    /// Add `tag` to this frame's [`tags`][Self::tags], unless it already has it.
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.tags.iter().any(|existing| existing == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
     /// Return the actual return address, as saved on the stack or in a
     /// register. See the comments for `StackFrame::instruction` for details.
     pub fn return_address(&self) -> u64 {
         self.instruction
     }
+
+    /// The function name, demangled if it's a mangled Rust, C++ (Itanium),
+    /// or MSVC symbol.
+    ///
+    /// Symbol files usually contain demangled names already, so this is
+    /// just `function_name` unless it couldn't be demangled when the symbols
+    /// were generated.
+    pub fn demangled_function_name(&self) -> Option<String> {
+        self.function_name.as_deref().map(demangle)
+    }
+
+    /// Whether this frame's function is one of the runtime's in `noise`,
+    /// like `__libc_start_main`. Frames without symbols are never noise.
+    pub fn is_noise(&self, noise: &NoiseList) -> bool {
+        crate::signature::is_noise(self, noise)
+    }
+}
+
+/// Demangle `name`, or return it unchanged if it isn't a symbol we can demangle.
+fn demangle(name: &str) -> String {
+    crate::symbols::demangle(name).unwrap_or_else(|| name.to_string())
 }
 
 impl FrameSymbolizer for StackFrame {
@@ -262,18 +551,73 @@ impl FrameSymbolizer for StackFrame {
     }
     fn set_source_file(&mut self, file: &str, line: u32, base: u64) {
         self.source_file_name = Some(String::from(file));
+        self.source_file_path =
+            Some(VcsPath::parse(file).map_or_else(|| file.to_string(), |vcs| vcs.path));
         self.source_line = Some(line);
         self.source_line_base = Some(base);
     }
 }
 
-fn basename(f: &str) -> &str {
-    match f.rfind(|c| c == '/' || c == '\\') {
-        None => f,
-        Some(index) => &f[(index + 1)..],
+impl ThreadStartRoutine {
+    /// Create a `ThreadStartRoutine` for `address`, with no module or symbols.
+    pub fn new(address: u64) -> ThreadStartRoutine {
+        ThreadStartRoutine {
+            address,
+            module: None,
+            function_name: None,
+            function_base: None,
+        }
+    }
+
+    /// Write the routine as `module!function + offset`, as much as is known.
+    fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        match (&self.module, &self.function_name, self.function_base) {
+            (Some(module), Some(function), Some(base)) => write!(
+                f,
+                "{}!{} + {:#x}",
+                module.name(),
+                function,
+                self.address - base
+            ),
+            (Some(module), _, _) => write!(
+                f,
+                "{} + {:#x}",
+                module.name(),
+                self.address - module.base_address()
+            ),
+            (None, _, _) => write!(f, "{:#x}", self.address),
+        }
+    }
+}
+
+impl FrameSymbolizer for ThreadStartRoutine {
+    fn get_instruction(&self) -> u64 {
+        self.address
+    }
+    fn set_function(&mut self, name: &str, base: u64, _parameter_size: u32) {
+        self.function_name = Some(String::from(name));
+        self.function_base = Some(base);
+    }
+    fn set_source_file(&mut self, _file: &str, _line: u32, _base: u64) {
+        // The start routine is only printed by function name.
     }
 }
 
+/// Dedupe `modules`, sorted by how many times each appeared (most first).
+///
+/// Ties are broken by base address, to keep the order stable.
+fn rank_by_count<'a, M: Module>(modules: impl IntoIterator<Item = &'a M>) -> Vec<&'a M> {
+    let mut counts: Vec<(&M, usize)> = vec![];
+    for module in modules {
+        match counts.iter_mut().find(|(m, _)| std::ptr::eq(*m, module)) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((module, 1)),
+        }
+    }
+    counts.sort_by_key(|&(module, count)| (Reverse(count), module.base_address()));
+    counts.into_iter().map(|(module, _)| module).collect()
+}
+
 fn print_registers<T: Write>(f: &mut T, ctx: &MinidumpContext) -> io::Result<()> {
     let registers: Cow<HashSet<&str>> = match ctx.valid {
         MinidumpContextValidity::All => {
@@ -301,6 +645,10 @@ fn print_registers<T: Write>(f: &mut T, ctx: &MinidumpContext) -> io::Result<()>
     if !output.is_empty() {
         writeln!(f, " {}", output)?;
     }
+    // The vector registers are too long to share lines.
+    for (reg, reg_val) in ctx.nonzero_vector_registers() {
+        writeln!(f, "  {: >5} = {}", reg, reg_val)?;
+    }
     Ok(())
 }
 
@@ -324,6 +672,19 @@ fn json_registers(ctx: &MinidumpContext) -> serde_json::Value {
     json!(output)
 }
 
+impl HandleSummary {
+    /// Count up the handles in `handles`.
+    pub fn from_handles(handles: &MinidumpHandleDataStream) -> HandleSummary {
+        let mut summary = HandleSummary::default();
+        for handle in handles.iter() {
+            let ty = handle.type_name.as_deref().unwrap_or("Unknown");
+            *summary.by_type.entry(ty.to_string()).or_insert(0) += 1;
+            summary.total += 1;
+        }
+        summary
+    }
+}
+
 impl CallStack {
     /// Create a `CallStack` with `info` and no frames.
     pub fn with_info(info: CallStackInfo) -> CallStack {
@@ -332,24 +693,62 @@ impl CallStack {
             frames: vec![],
             thread_name: None,
             last_error_value: None,
+            start_routine: None,
+            kernel_time: None,
+            user_time: None,
         }
     }
 
+    /// Compute a crash signature from the top `max_frames` frames of this stack.
+    ///
+    /// Crash aggregators use signatures to group crashes with the same cause.
+    /// Like Mozilla's signature generation, this joins the names of the
+    /// frames with ` | `, where:
+    ///
+    /// * Functions are normalized by dropping their parameter lists and
+    ///   collapsing template arguments to `<T>`.
+    /// * Frames without symbols are named `module@offset`, or `@address` if
+    ///   they aren't in any module.
+    /// * Frames that are part of every crash, such as `abort` or Rust's panic
+    ///   machinery, are skipped.
+    /// * Recursion is collapsed into a single frame.
+    /// * Signature sentinels, such as `_purecall`, end the signature early.
+    /// * Program and thread entry points, such as `__libc_start_main`, and
+    ///   everything below them are dropped, see [`NoiseList`][].
+    ///
+    /// A stack with no frames has an empty signature.
+    pub fn signature(&self, max_frames: usize) -> String {
+        self.signature_with_noise(max_frames, &NoiseList::default())
+    }
+
+    /// Like [`CallStack::signature`][], but with the runtime frames in `noise`
+    /// instead of the default ones.
+    pub fn signature_with_noise(&self, max_frames: usize, noise: &NoiseList) -> String {
+        crate::signature::signature(&self.frames, max_frames, noise)
+    }
+
     /// Write a human-readable description of the call stack to `f`.
     ///
     /// This is very verbose, it implements the output format used by
     /// minidump_stackwalk.
     pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
-        if self.frames.is_empty() {
+        if let Some(ref routine) = self.start_routine {
+            write!(f, "Start routine: ")?;
+            routine.print(f)?;
+            writeln!(f)?;
+        }
+        if self.info == CallStackInfo::Filtered {
+            writeln!(f, "<not walked>")?;
+        } else if self.frames.is_empty() {
             writeln!(f, "<no frames>")?;
         }
         for (i, frame) in self.frames.iter().enumerate() {
             let addr = frame.instruction;
             write!(f, "{:2}  ", i)?;
             if let Some(ref module) = frame.module {
-                write!(f, "{}", basename(&module.code_file()))?;
-                if let (&Some(ref function), &Some(ref function_base)) =
-                    (&frame.function_name, &frame.function_base)
+                write!(f, "{}", module.name())?;
+                if let (Some(function), &Some(ref function_base)) =
+                    (frame.demangled_function_name(), &frame.function_base)
                 {
                     write!(f, "!{}", function)?;
                     if let (
@@ -357,7 +756,7 @@ impl CallStack {
                         &Some(ref source_line),
                         &Some(ref source_line_base),
                     ) = (
-                        &frame.source_file_name,
+                        &frame.source_file_path,
                         &frame.source_line,
                         &frame.source_line_base,
                     ) {
@@ -377,34 +776,85 @@ impl CallStack {
             } else {
                 write!(f, "{:#x}", addr)?;
 
-                // List off overlapping unloaded modules.
-
-                // First we need to collect them up by name so that we can print
-                // all the overlaps from one module together and dedupe them.
-
+                // List off overlapping unloaded modules. These are already
+                // collected up by name, so all the overlaps from one module
+                // are printed together and deduped.
+                if !frame.unloaded_modules.is_empty() {
+                    write!(f, " (unloaded)")?;
+                }
+                let mut first_module = true;
                 for (name, offsets) in &frame.unloaded_modules {
-                    write!(f, " (unloaded {}@", name)?;
+                    if !first_module {
+                        write!(f, ",")?;
+                    }
+                    first_module = false;
+                    write!(f, " {} + ", name)?;
                     let mut first = true;
                     for offset in offsets {
-                        if first {
-                            write!(f, "0x{:#x}", offset)?;
-                        } else {
+                        if !first {
                             // `|` is our separator for multiple entries
-                            write!(f, "|0x{:#x}", offset)?;
+                            write!(f, "|")?;
                         }
+                        write!(f, "{:#x}", offset)?;
                         first = false;
                     }
-                    write!(f, ")")?;
                 }
             }
             writeln!(f)?;
             print_registers(f, &frame.context)?;
             writeln!(f, "    Found by: {}", frame.trust.description())?;
+            if !frame.tags.is_empty() {
+                writeln!(f, "    Tags: {}", frame.tags.join(", "))?;
+            }
         }
         Ok(())
     }
 }
 
+/// Format a number of bytes like "1.9 GiB".
+const BYTE_UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, BYTE_UNITS[unit])
+}
+
+/// Format `used` out of `total` bytes, like "31.8/32.0 GiB", in the unit that suits `total`.
+fn format_bytes_of(used: u64, total: u64) -> String {
+    if total < 1024 {
+        return format!("{}/{} B", used, total);
+    }
+    let mut divisor = 1024.0;
+    let mut unit = 0;
+    while total as f64 / divisor >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+        divisor *= 1024.0;
+        unit += 1;
+    }
+    format!(
+        "{:.1}/{:.1} {}",
+        used as f64 / divisor,
+        total as f64 / divisor,
+        BYTE_UNITS[unit]
+    )
+}
+
+fn symbol_error_json_name(error: &SymbolError) -> &'static str {
+    match error {
+        SymbolError::NotFound => "not_found",
+        SymbolError::LoadError(..) => "load_error",
+        SymbolError::ParseError(..) => "parse_error",
+        SymbolError::Timeout => "timeout",
+    }
+}
+
 fn eq_some<T: PartialEq>(opt: Option<T>, val: T) -> bool {
     match opt {
         Some(v) => v == val,
@@ -417,6 +867,181 @@ impl ProcessState {
     pub fn crashed(&self) -> bool {
         self.crash_reason.is_some() && self.crash_address.is_some()
     }
+
+    /// The index in `threads` of the thread with id `tid`, if the minidump's
+    /// thread list has one.
+    ///
+    /// This is for thread ids from elsewhere, like the `ThreadNamesStream`
+    /// or the Breakpad info stream. Every thread in the list has a `CallStack`,
+    /// even ones that weren't walked.
+    pub fn thread_for_id(&self, tid: u32) -> Option<usize> {
+        self.thread_indices.get(&tid).copied()
+    }
+
+    /// The id of the thread the exception stream says crashed, if there is one.
+    ///
+    /// This is straight from the `MinidumpException`, so for a dump requested
+    /// without crashing it's whichever thread wrote the dump. Use
+    /// [`ProcessState::requesting_thread`][] for the thread that was walked
+    /// as the crashing thread.
+    pub fn crashing_thread_id(&self) -> Option<u32> {
+        self.exception_thread_id
+    }
+    /// The address the process faulted on, if it crashed on a bad memory access.
+    ///
+    /// Every OS records this differently: Windows access violations and
+    /// in-page errors put it in the exception parameters, Linux in the
+    /// signal's `si_addr`, and macOS in the `EXC_BAD_ACCESS` subcode.
+    /// `crash_address` has already been pulled out of the right place, so this
+    /// only has to check that the crash reason is actually a memory fault.
+    ///
+    /// Returns `None` for crashes that don't fault on an address, like
+    /// breakpoints, illegal instructions or aborts, where `crash_address` is
+    /// just the instruction that raised the exception.
+    pub fn crashed_address(&self) -> Option<u64> {
+        use format::{ExceptionCodeLinux, ExceptionCodeMac};
+        use CrashReason::*;
+        let is_fault = matches!(
+            self.crash_reason?,
+            MacBadAccessKern(_)
+                | MacBadAccessArm(_)
+                | MacBadAccessPpc(_)
+                | MacBadAccessX86(_)
+                | MacGeneral(ExceptionCodeMac::EXC_BAD_ACCESS, _)
+                | LinuxSigsegv(_)
+                | LinuxSigbus(_)
+                | LinuxGeneral(ExceptionCodeLinux::SIGSEGV | ExceptionCodeLinux::SIGBUS, _)
+                | LinuxSignal(ExceptionCodeLinux::SIGSEGV | ExceptionCodeLinux::SIGBUS, _)
+                | WindowsAccessViolation(_)
+                | WindowsInPageError(..)
+        );
+        if is_fault {
+            self.crash_address
+        } else {
+            None
+        }
+    }
+
+    /// All of the message strings from the macOS crash info records.
+    ///
+    /// These are what the crashing modules put in their `__crash_info`
+    /// sections, and are frequently the text of the assertion that failed.
+    /// Empty if this isn't a macOS minidump or it had no such messages.
+    pub fn mac_crash_messages(&self) -> Vec<&str> {
+        self.mac_crash_info
+            .iter()
+            .flatten()
+            .flat_map(|record| record.messages())
+            .collect()
+    }
+
+    /// Every frame of every thread, in order.
+    ///
+    /// This saves walking `threads` and their `frames` by hand when all
+    /// that's wanted is where each frame is, e.g. for a crash signature.
+    pub fn symbolicated_frames(&self) -> impl Iterator<Item = SymbolicatedFrame<'_>> {
+        self.threads
+            .iter()
+            .enumerate()
+            .flat_map(|(thread_index, thread)| {
+                thread
+                    .frames
+                    .iter()
+                    .enumerate()
+                    .map(move |(frame_index, frame)| SymbolicatedFrame {
+                        thread_index,
+                        frame_index,
+                        instruction: frame.instruction,
+                        module: frame.module.as_ref().map(|module| module.name()),
+                        function: frame.function_name.as_deref(),
+                        file: frame.source_file_name.as_deref(),
+                        line: frame.source_line,
+                    })
+            })
+    }
+
+    /// Modules that are likely to be at fault for a crash.
+    ///
+    /// This looks at the suspect frames of the requesting thread: frames found
+    /// by scanning the stack, which are often garbage, and frames whose
+    /// instruction isn't in any module, which usually come from calling a bad
+    /// function pointer. The modules containing the scanned frames and the
+    /// callers of the bad frames are returned, with the modules with the most
+    /// suspect frames first. Empty if there's no requesting thread.
+    ///
+    /// A bad frame's own instruction is often in a module that has since been
+    /// unloaded, see [`ProcessState::potential_culprit_unloaded_modules`].
+    pub fn potential_culprit_modules(&self) -> Vec<&MinidumpModule> {
+        let (suspects, _) = self.suspect_addresses();
+        rank_by_count(
+            suspects
+                .into_iter()
+                .filter_map(|address| self.modules.module_at_address(address)),
+        )
+    }
+
+    /// Unloaded modules that the requesting thread's bad frames point into.
+    ///
+    /// This is the counterpart of [`ProcessState::potential_culprit_modules`]
+    /// for code that was called after its module was unloaded. The modules
+    /// with the most bad frames come first.
+    pub fn potential_culprit_unloaded_modules(&self) -> Vec<&MinidumpUnloadedModule> {
+        let (_, bad) = self.suspect_addresses();
+        rank_by_count(
+            bad.into_iter()
+                .flat_map(|address| self.unloaded_modules.modules_at_address(address)),
+        )
+    }
+
+    /// The size of each stream in the minidump, largest first.
+    ///
+    /// This is the same as [`Minidump::memory_usage_report`][], see there for details.
+    pub fn memory_usage_report(&self) -> Vec<(StreamTypeName, usize)> {
+        let mut report = self
+            .streams
+            .iter()
+            .map(|stream| {
+                (
+                    StreamTypeName(stream.stream_type),
+                    stream.data_size as usize,
+                )
+            })
+            .collect::<Vec<_>>();
+        report.sort_by_key(|&(_, size)| Reverse(size));
+        report
+    }
+
+    /// The addresses of the requesting thread's suspect frames (see
+    /// [`ProcessState::potential_culprit_modules`]).
+    ///
+    /// Returns the addresses to look up in the loaded modules (those of scanned
+    /// frames, and of the callers of bad frames), and the addresses of the bad
+    /// frames themselves.
+    fn suspect_addresses(&self) -> (Vec<u64>, Vec<u64>) {
+        let frames = match self.requesting_thread.and_then(|i| self.threads.get(i)) {
+            Some(thread) => &thread.frames,
+            None => return (vec![], vec![]),
+        };
+        let mut suspects = vec![];
+        let mut bad = vec![];
+        for (i, frame) in frames.iter().enumerate() {
+            if frame.module.is_none() {
+                bad.push(frame.instruction);
+                if let Some(caller) = frames.get(i + 1) {
+                    suspects.push(caller.instruction);
+                }
+            } else if matches!(frame.trust, FrameTrust::Scan | FrameTrust::CfiScan) {
+                suspects.push(frame.instruction);
+            }
+        }
+        (suspects, bad)
+    }
+
+    /// The module a Crashpad module annotation entry belongs to.
+    fn crashpad_module(&self, module_info: &MinidumpModuleCrashpadInfo) -> Option<&MinidumpModule> {
+        self.modules.iter().nth(module_info.module_index)
+    }
+
     /// Write a human-readable description of the process state to `f`.
     ///
     /// This is very verbose, it implements the output format used by
@@ -432,15 +1057,85 @@ impl ProcessState {
         self.print_internal(f, true)
     }
 
+    /// A description of the operating system, e.g. "Linux (Ubuntu 22.04.1 LTS)".
+    ///
+    /// The distribution is only known on Linux, from `/etc/lsb-release`
+    /// or `/etc/os-release`. On Windows the build string of the OS is used
+    /// instead, if the minidump has one.
+    pub fn os_description(&self) -> String {
+        let os = self.system_info.os.long_name();
+        let distro = self
+            .linux_standard_base
+            .as_ref()
+            .map(|lsb| {
+                if lsb.description.is_empty() {
+                    format!("{} {}", lsb.id, lsb.release).trim().to_string()
+                } else {
+                    lsb.description.clone()
+                }
+            })
+            .or_else(|| self.system_info.os_build.clone());
+        match distro {
+            Some(distro) if !distro.is_empty() => format!("{} ({})", os, distro),
+            _ => os.to_string(),
+        }
+    }
+
+    /// Print the memory usage that the "RSS at crash" line doesn't cover.
+    fn print_memory_summary<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        if let Some(ref usage) = self.memory_usage {
+            let mut lines = Vec::new();
+            if let Some(private) = usage.private_size {
+                lines.push(format!("private: {}", format_bytes(private)));
+            }
+            if let Some(virtual_size) = usage.virtual_size {
+                lines.push(format!("virtual: {}", format_bytes(virtual_size)));
+            }
+            if !lines.is_empty() {
+                writeln!(f, "Memory usage:")?;
+                for line in lines {
+                    writeln!(f, "  {}", line)?;
+                }
+            }
+        }
+        if let Some(ref usage) = self.system_memory_usage {
+            writeln!(f, "System memory:")?;
+            if let (Some(committed), Some(limit)) = (usage.committed_size, usage.commit_limit) {
+                writeln!(f, "  commit: {}", format_bytes_of(committed, limit))?;
+            }
+            if let (Some(available), Some(physical)) =
+                (usage.available_physical_size, usage.physical_size)
+            {
+                writeln!(
+                    f,
+                    "  available physical memory: {}",
+                    format_bytes_of(available, physical)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     fn print_internal<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
-        writeln!(f, "Operating system: {}", self.system_info.os.long_name())?;
+        writeln!(f, "Operating system: {}", self.os_description())?;
         if let Some(ref ver) = self.system_info.os_version {
             writeln!(f, "                  {}", ver)?;
         }
-        writeln!(f, "CPU: {}", self.system_info.cpu)?;
+        write!(f, "CPU: {}", self.system_info.cpu)?;
+        if self.system_info.cpu_inferred {
+            write!(f, " (inferred)")?;
+        }
+        writeln!(f)?;
         if let Some(ref info) = self.system_info.cpu_info {
             writeln!(f, "     {}", info)?;
         }
+        if !self.system_info.cpu_features.is_empty() {
+            writeln!(
+                f,
+                "     features: {}",
+                self.system_info.cpu_features.join(" ")
+            )?;
+        }
         writeln!(
             f,
             "     {} CPU{}",
@@ -458,16 +1153,41 @@ impl ProcessState {
                 lsb.id, lsb.release, lsb.codename, lsb.description
             )?;
         }
+        if let Some(ref name) = self.process_name {
+            writeln!(f, "Process name: {}", name)?;
+        }
+        if let Some(rss) = self
+            .memory_usage
+            .as_ref()
+            .and_then(|usage| usage.resident_size)
+        {
+            writeln!(f, "RSS at crash: {}", format_bytes(rss))?;
+        }
+        self.print_memory_summary(f)?;
+        if let Some(ref environment) = self.environment {
+            for (key, val) in environment {
+                writeln!(f, "Environment: {}={}", key, val)?;
+            }
+        }
         writeln!(f)?;
 
-        if let (&Some(ref reason), &Some(ref address)) = (&self.crash_reason, &self.crash_address) {
+        if let (Some(reason), Some(address)) = (&self.crash_reason, &self.crash_address) {
             write!(
                 f,
                 "Crash reason:  {}
-Crash address: {:#x}
-",
+Crash address: {:#x}",
                 reason, address
             )?;
+            if let Some(ref description) = self.crash_address_description {
+                write!(f, " ({})", description)?;
+            }
+            writeln!(f)?;
+            if let Some(fault_address) = self.crashed_address() {
+                writeln!(f, "Fault address: {:#x}", fault_address)?;
+            }
+            if let Some(ref mxcsr) = self.crash_mxcsr {
+                writeln!(f, "Crash MXCSR:   {}", mxcsr)?;
+            }
         } else {
             writeln!(f, "No crash")?;
         }
@@ -479,13 +1199,13 @@ Crash address: {:#x}
             for (idx, record) in info.iter().enumerate() {
                 writeln!(f, "  Record {}", idx)?;
                 if let Some(val) = record.thread() {
-                    writeln!(f, "    thread: 0x{}", val)?;
+                    writeln!(f, "    thread: {:#x}", val)?;
                 }
                 if let Some(val) = record.dialog_mode() {
-                    writeln!(f, "    dialog mode: 0x{}", val)?;
+                    writeln!(f, "    dialog mode: {:#x}", val)?;
                 }
                 if let Some(val) = record.abort_cause() {
-                    writeln!(f, "    abort_cause: 0x{}", val)?;
+                    writeln!(f, "    abort_cause: {:#x}", val)?;
                 }
 
                 if let Some(val) = record.module_path() {
@@ -506,12 +1226,85 @@ Crash address: {:#x}
             }
             writeln!(f)?;
         }
+        if let Some(ref crashpad_info) = self.crashpad_info {
+            writeln!(f, "Crashpad Info:")?;
+            if let Some(report_id) = crashpad_info.report_id() {
+                writeln!(f, "  report id: {}", report_id)?;
+            }
+            if let Some(client_id) = crashpad_info.client_id() {
+                writeln!(f, "  client id: {}", client_id)?;
+            }
+            for (key, value) in &crashpad_info.simple_annotations {
+                writeln!(f, "  {} = {}", key, value)?;
+            }
+            for module_info in &crashpad_info.module_list {
+                if module_info.list_annotations.is_empty()
+                    && module_info.simple_annotations.is_empty()
+                    && module_info.annotation_objects.is_empty()
+                {
+                    continue;
+                }
+                let name = self
+                    .crashpad_module(module_info)
+                    .map(|module| module.name().to_string())
+                    .unwrap_or_else(|| format!("module {}", module_info.module_index));
+                writeln!(f, "  {}:", name)?;
+                for annotation in &module_info.list_annotations {
+                    writeln!(f, "    {}", annotation)?;
+                }
+                for (key, value) in &module_info.simple_annotations {
+                    writeln!(f, "    {} = {}", key, value)?;
+                }
+                for (key, value) in &module_info.annotation_objects {
+                    match value {
+                        MinidumpAnnotation::String(value) => {
+                            writeln!(f, "    {} = {}", key, value)?
+                        }
+                        MinidumpAnnotation::Invalid => writeln!(f, "    {} = <invalid>", key)?,
+                        _ => writeln!(f, "    {} = <binary>", key)?,
+                    }
+                }
+            }
+            writeln!(f)?;
+        }
+        if let Some(ref mismatches) = self.dso_mismatches {
+            if !mismatches.is_empty() {
+                writeln!(f, "Dynamic linker mismatches:")?;
+                for mismatch in mismatches {
+                    match mismatch.module_address {
+                        Some(address) => writeln!(
+                            f,
+                            "  {} is loaded at {:#x}, but the module list has it at {:#x}",
+                            mismatch.name, mismatch.link_map_address, address
+                        )?,
+                        None => writeln!(
+                            f,
+                            "  {} is loaded at {:#x}, but isn't in the module list",
+                            mismatch.name, mismatch.link_map_address
+                        )?,
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
         if let Some(ref time) = self.process_create_time {
             let uptime = self.time - *time;
             writeln!(f, "Process uptime: {} seconds", uptime.num_seconds())?;
         } else {
             writeln!(f, "Process uptime: not available")?;
         }
+        if let Some(ref handles) = self.handle_summary {
+            write!(f, "Open handles: {}", handles.total)?;
+            let mut first = true;
+            for (ty, count) in &handles.by_type {
+                write!(f, "{}{}: {}", if first { " (" } else { ", " }, ty, count)?;
+                first = false;
+            }
+            if !first {
+                write!(f, ")")?;
+            }
+            writeln!(f)?;
+        }
         writeln!(f)?;
 
         if let Some(requesting_thread) = self.requesting_thread {
@@ -561,8 +1354,7 @@ Loaded modules:
         let main_address = self.modules.main_module().map(|m| m.base_address());
         for module in self.modules.by_addr() {
             // TODO: missing symbols, corrupt symbols
-            let full_name = module.code_file();
-            let name = basename(&full_name);
+            let name = module.name();
             write!(
                 f,
                 "{:#010x} - {:#010x}  {}  {}",
@@ -593,7 +1385,7 @@ Unloaded modules:
                 "{:#010x} - {:#010x}  {}",
                 module.base_address(),
                 module.base_address() + module.size() - 1,
-                basename(&module.code_file()),
+                name,
             )?;
             if let Some(cert) = self.cert_info.get(name) {
                 write!(f, " ({})", cert)?;
@@ -633,6 +1425,17 @@ Unknown streams encountered:
                 )?;
             }
         }
+        if !self.warnings.is_empty() {
+            write!(
+                f,
+                "
+Minidump is truncated or corrupt:
+"
+            )?;
+            for warning in &self.warnings {
+                writeln!(f, "  {}", warning)?;
+            }
+        }
         Ok(())
     }
 
@@ -655,9 +1458,18 @@ Unknown streams encountered:
                 // Linux | Windows NT | Mac OS X
                 "os": sys.os.long_name(),
                 "os_ver": sys.os_version,
+                // optional, the exact build of the OS (Windows only)
+                "os_build": sys.os_build,
                 // x86 | amd64 | arm | ppc | sparc
                 "cpu_arch": sys.cpu.to_string(),
                 "cpu_info": sys.cpu_info,
+                // optional
+                "cpu_vendor": sys.cpu_vendor,
+                // optional, x86 and amd64 only
+                "cpu_family": sys.cpu_family_model_stepping.map(|(family, _, _)| family),
+                "cpu_model": sys.cpu_family_model_stepping.map(|(_, model, _)| model),
+                "cpu_stepping": sys.cpu_family_model_stepping.map(|(_, _, stepping)| stepping),
+                "cpu_features": sys.cpu_features,
                 "cpu_count": sys.cpu_count,
                 // optional
                 "cpu_microcode_version": sys.cpu_microcode_version,
@@ -665,6 +1477,12 @@ Unknown streams encountered:
             "crash_info": {
                 "type": self.crash_reason.map(|reason| reason.to_string()),
                 "address": self.crash_address.map(json_hex),
+                // optional
+                "fault_address": self.crashed_address().map(json_hex),
+                // optional
+                "address_description": self.crash_address_description,
+                // optional
+                "mxcsr": self.crash_mxcsr,
                 // thread index | null
                 "crashing_thread": self.requesting_thread,
                 "assertion": self.assertion,
@@ -692,13 +1510,43 @@ Unknown streams encountered:
                     "message2": record.message2(),
                 })).collect::<Vec<_>>()
             })),
+            // optional
+            "crashpad_info": self.crashpad_info.as_ref().map(|info| json!({
+                "report_id": info.report_id().map(|id| id.to_string()),
+                "client_id": info.client_id().map(|id| id.to_string()),
+                "simple_annotations": info.simple_annotations,
+                "module_info": info.module_list.iter().map(|module_info| json!({
+                    "module_index": module_info.module_index,
+                    "module": self.crashpad_module(module_info)
+                        .map(|module| module.name().to_string()),
+                    "list_annotations": module_info.list_annotations,
+                    "simple_annotations": module_info.simple_annotations,
+                    "annotation_objects": module_info.annotation_objects.iter()
+                        .map(|(key, value)| {
+                            let value = match value {
+                                MinidumpAnnotation::String(value) => Some(value),
+                                _ => None,
+                            };
+                            (key.clone(), json!(value))
+                        })
+                        .collect::<serde_json::Map<_, _>>(),
+                })).collect::<Vec<_>>(),
+            })),
+            // optional
+            "dso_mismatches": self.dso_mismatches.as_ref().map(|mismatches| {
+                mismatches.iter().map(|mismatch| json!({
+                    "name": mismatch.name,
+                    "link_map_address": json_hex(mismatch.link_map_address),
+                    // optional
+                    "module_address": mismatch.module_address.map(json_hex),
+                })).collect::<Vec<_>>()
+            }),
 
             // the first module is always the main one
             "main_module": 0,
             "modules_contains_cert_info": !self.cert_info.is_empty(),
             "modules": self.modules.iter().map(|module| {
-                let full_name = module.code_file();
-                let name = basename(&full_name);
+                let name = module.name();
 
                 // Gather statistics on the module's symbols
                 let stats = self.symbol_stats.get(name);
@@ -734,6 +1582,28 @@ Unknown streams encountered:
                 })
             }).collect::<Vec<_>>(),
             "pid": self.process_id,
+            // optional
+            "process_name": self.process_name,
+            // optional
+            "memory_usage": self.memory_usage.as_ref().map(|usage| json!({
+                // All of these fields are optional
+                "peak_virtual_size": usage.peak_virtual_size,
+                "virtual_size": usage.virtual_size,
+                "resident_size": usage.resident_size,
+                "swap_size": usage.swap_size,
+                "private_size": usage.private_size,
+            })),
+            // optional
+            "system_memory_usage": self.system_memory_usage.as_ref().map(|usage| json!({
+                // All of these fields are optional
+                "physical_size": usage.physical_size,
+                "available_physical_size": usage.available_physical_size,
+                "committed_size": usage.committed_size,
+                "commit_limit": usage.commit_limit,
+                "peak_committed_size": usage.peak_committed_size,
+            })),
+            // optional
+            "environment": self.environment,
             "thread_count": self.threads.len(),
             "threads": self.threads.iter().map(|thread| json!({
                 "frame_count": thread.frames.len(),
@@ -745,7 +1615,7 @@ Unknown streams encountered:
                     // temporary hack: grab the first matching unloaded module
                     // and pretend it's a real module.
                     let module_info = frame.module.as_ref().map(|module| {
-                        (basename(&module.name), frame.instruction - module.raw.base_of_image)
+                        (module.name(), frame.instruction - module.raw.base_of_image)
                     }).or_else(|| frame.unloaded_modules.iter().next().and_then(|(name, offsets)| offsets.iter().next().map(|offset| {
                         (&**name, *offset)
                     })));
@@ -754,12 +1624,19 @@ Unknown streams encountered:
                         // optional
                         "module": module_info.map(|(name, _)| name),
                         // optional
-                        "function": frame.function_name,
+                        "function": frame.demangled_function_name(),
                         // optional
                         "file": frame.source_file_name,
                         // optional
+                        "source_link": frame.source_link,
+                        // optional
                         "line": frame.source_line,
                         "offset": json_hex(frame.instruction),
+                        // Every unloaded module this frame could be in, if it isn't in a loaded one.
+                        "unloaded_modules": frame.unloaded_modules.iter().map(|(name, offsets)| json!({
+                            "module": name,
+                            "offsets": offsets.iter().map(|offset| json_hex(*offset)).collect::<Vec<_>>(),
+                        })).collect::<Vec<_>>(),
                         // optional
                         "module_offset": module_info
                             .map(|(_, offset)| offset)
@@ -770,6 +1647,10 @@ Unknown streams encountered:
                             .map(|func_base| frame.instruction - func_base)
                             .map(json_hex),
                         "missing_symbols": frame.function_name.is_none(),
+                        // optional, not_found | load_error | parse_error | timeout
+                        "symbol_error": frame.symbol_error.as_ref().map(symbol_error_json_name),
+                        // Tags from ProcessorOptions::annotators
+                        "tags": frame.tags,
                         // none | scan | cfi_scan | frame_pointer | cfi | context | prewalked
                         "trust": frame.trust.json_name(),
                     })
@@ -800,8 +1681,6 @@ Unknown streams encountered:
             // of a normal "threads" entry, while the original schema strips
             // many of the fields here. We don't to keep things more uniform.
 
-            let registers = json_registers(&self.threads[requesting_thread].frames[0].context);
-
             // Yuck, spidering through json...
             let mut thread =
                 output.get_mut("threads").unwrap().as_array().unwrap()[requesting_thread].clone();
@@ -811,9 +1690,14 @@ Unknown streams encountered:
                 .unwrap()
                 .as_array_mut()
                 .unwrap();
-            let frame = frames[0].as_object_mut().unwrap();
-
-            frame.insert(String::from("registers"), registers);
+            // A thread we couldn't get a context for has no frames at all.
+            if let Some(frame) = frames.first_mut() {
+                let registers = json_registers(&self.threads[requesting_thread].frames[0].context);
+                frame
+                    .as_object_mut()
+                    .unwrap()
+                    .insert(String::from("registers"), registers);
+            }
             thread_obj.insert(String::from("threads_index"), json!(requesting_thread));
 
             output
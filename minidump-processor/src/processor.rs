@@ -4,28 +4,114 @@
 use chrono::{TimeZone, Utc};
 use failure::Fail;
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::Deref;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
+use minidump::format::{AssertionType, ExceptionCodeWindowsAccessType};
+use minidump::system_info::{Cpu, Os};
 use minidump::{self, *};
 
+use crate::annotator::StackFrameAnnotator;
 use crate::evil;
-use crate::process_state::{CallStack, CallStackInfo, LinuxStandardBase, ProcessState};
+use crate::process_state::{
+    CallStack, CallStackInfo, CallingConvention, DsoMismatch, FrameTrust, HandleSummary,
+    LinuxStandardBase, MemoryUsage, ProcessState, SystemMemoryUsage, ThreadStartRoutine,
+};
+use crate::source_link::{SourceLinkTemplates, VcsPath};
 use crate::stackwalker;
 use crate::symbols::*;
 use crate::system_info::SystemInfo;
 
 /// Various advanced options for the processor.
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct ProcessorOptions<'a> {
     /// The evil "raw json" mozilla's legacy infrastructure relies on (to be phased out).
     pub evil_json: Option<&'a Path>,
+    /// URL templates used to generate `source_link`s for VCS-annotated source files.
+    pub source_link_templates: SourceLinkTemplates,
+    /// The bits of an ARM64 code pointer that hold its Pointer Authentication
+    /// Code, which are cleared from return addresses recovered from the stack.
+    ///
+    /// If `None`, the mask is guessed from the addresses of the loaded modules.
+    pub arm64_pac_mask: Option<u64>,
+    /// How long the symbol provider may spend acquiring a single module's
+    /// symbols, overriding e.g. the `timeout` of [`http_symbol_supplier`][].
+    ///
+    /// Modules whose symbols time out are treated as having no symbols, with a
+    /// [`SymbolError::Timeout`][] in their frames' `symbol_error`. The override
+    /// only lasts for this call. Defaults to 30 seconds.
+    pub symbol_timeout: Duration,
+    /// Which threads to walk the stacks of.
+    ///
+    /// Threads that are filtered out are still included in the ProcessState
+    /// (with their names), but with no frames and [`CallStackInfo::Filtered`].
+    pub thread_filter: ThreadFilter,
+    /// Environment variables to include in [`ProcessState::environment`][].
+    ///
+    /// The environment is often full of secrets, so nothing is included by
+    /// default. Some variables are worth having though: `LD_PRELOAD` is
+    /// frequently the actual cause of a crash. The patterns are the same as
+    /// for [`MinidumpLinuxEnviron::redacted`][], so e.g.
+    /// `["LANG", "MESA_*", "LD_PRELOAD"]`.
+    pub environ_allowlist: Vec<String>,
+    /// Annotators to run on every stack frame after it's been symbolized, in order.
+    ///
+    /// See [`RegexAnnotator`][crate::RegexAnnotator] for tagging frames by function or module name.
+    pub annotators: Vec<Arc<dyn StackFrameAnnotator>>,
+    /// The least trusted frames to include in the output.
+    ///
+    /// Frames with a lower [`FrameTrust`][] are dropped from every [`CallStack`][],
+    /// e.g. use [`FrameTrust::FramePointer`][] to leave out frames that were only
+    /// found by scanning the stack. By default every frame is included.
+    pub trust_threshold: FrameTrust,
+    /// Whether potential return addresses found by scanning the stack (or, on
+    /// PowerPC, in the link register) must be in a loaded module.
+    ///
+    /// Scanned addresses are checked against the symbols (and CFI) of the
+    /// module they're in, which an address outside of every module doesn't
+    /// have. By default such addresses are rejected without querying the
+    /// symbol provider at all. If `false`, they're accepted unchecked, which
+    /// lets scanning walk through e.g. JIT code that isn't in the module list,
+    /// at the cost of finding more bogus frames. Defaults to `true`.
+    pub cfi_only_for_known_modules: bool,
+}
+
+impl Default for ProcessorOptions<'_> {
+    fn default() -> Self {
+        ProcessorOptions {
+            evil_json: None,
+            source_link_templates: SourceLinkTemplates::default(),
+            arm64_pac_mask: None,
+            symbol_timeout: Duration::from_secs(30),
+            thread_filter: ThreadFilter::default(),
+            environ_allowlist: Vec::new(),
+            annotators: Vec::new(),
+            trust_threshold: FrameTrust::default(),
+            cfi_only_for_known_modules: true,
+        }
+    }
+}
+
+/// Which threads [`process_minidump_with_options`] should walk the stacks of.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ThreadFilter {
+    /// Walk every thread.
+    #[default]
+    All,
+    /// Only walk the threads with these thread ids.
+    Ids(Vec<u32>),
+    /// Only walk the thread that crashed (or requested the dump, if nothing crashed).
+    CrashingThread,
 }
 
 /// An error encountered during minidump processing.
 #[derive(Debug, Fail)]
+#[non_exhaustive]
 pub enum ProcessError {
     #[fail(display = "Failed to read minidump")]
     MinidumpReadError(minidump::Error),
@@ -86,6 +172,8 @@ where
     T: Deref<Target = [u8]> + 'a,
     P: SymbolProvider,
 {
+    let _symbol_timeout = SymbolTimeoutOverride::new(symbol_provider, options.symbol_timeout);
+
     // Thread list is required for processing.
     let thread_list = dump
         .get_stream::<MinidumpThreadList>()
@@ -94,11 +182,21 @@ where
     let thread_names = dump
         .get_stream::<MinidumpThreadNames>()
         .unwrap_or_else(|_| MinidumpThreadNames::default());
+    // Extra thread info (start addresses, CPU times) is also a nice-to-have.
+    let thread_infos = dump
+        .get_stream::<MinidumpThreadInfoList>()
+        .unwrap_or_default();
 
-    // System info is required for processing.
-    let dump_system_info = dump
-        .get_stream::<MinidumpSystemInfo>()
-        .or(Err(ProcessError::MissingSystemInfo))?;
+    // System info is required for processing, but if it's missing the CPU can
+    // usually be inferred from the thread contexts, which is enough to walk them.
+    let (dump_system_info, cpu_inferred) = match dump.get_stream::<MinidumpSystemInfo>() {
+        Ok(system_info) => (system_info, false),
+        Err(_) => (
+            dump.infer_system_info()
+                .ok_or(ProcessError::MissingSystemInfo)?,
+            true,
+        ),
+    };
 
     let mut os_version = format!(
         "{}.{}.{}",
@@ -111,12 +209,14 @@ where
         os_version.push_str(&csd_version);
     }
 
+    let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+
     let linux_standard_base = dump.get_stream::<MinidumpLinuxLsbRelease>().ok();
     let linux_cpu_info = dump
         .get_stream::<MinidumpLinuxCpuInfo>()
         .unwrap_or_default();
-    let _linux_environ = dump.get_stream::<MinidumpLinuxEnviron>().ok();
-    let _linux_proc_status = dump.get_stream::<MinidumpLinuxProcStatus>().ok();
+    let linux_environ = dump.get_stream::<MinidumpLinuxEnviron>().ok();
+    let linux_proc_status = dump.get_stream::<MinidumpLinuxProcStatus>().ok();
 
     // Extract everything we care about from linux streams here.
     // We don't eagerly process them in the minidump crate because there's just
@@ -136,35 +236,72 @@ where
         }
     }
 
-    let linux_standard_base = linux_standard_base.map(|linux_standard_base| {
-        let mut lsb = LinuxStandardBase::default();
-        for (key, val) in linux_standard_base.iter() {
-            match key.as_bytes() {
-                b"DISTRIB_ID" | b"ID" => lsb.id = val.to_string_lossy().into_owned(),
-                b"DISTRIB_RELEASE" | b"VERSION_ID" => {
-                    lsb.release = val.to_string_lossy().into_owned()
-                }
-                b"DISTRIB_CODENAME" | b"VERSION_CODENAME" => {
-                    lsb.codename = val.to_string_lossy().into_owned()
-                }
-                b"DISTRIB_DESCRIPTION" | b"PRETTY_NAME" => {
-                    lsb.description = val.to_string_lossy().into_owned()
-                }
-                _ => {}
-            }
+    let linux_standard_base = linux_standard_base.map(|lsb| {
+        let owned = |val: Option<Cow<str>>| val.map(Cow::into_owned).unwrap_or_default();
+        LinuxStandardBase {
+            id: owned(lsb.id()),
+            release: owned(lsb.release()),
+            codename: owned(lsb.codename()),
+            description: owned(lsb.description()),
         }
-        lsb
     });
 
+    let process_name = linux_proc_status
+        .as_ref()
+        .and_then(|status| status.name())
+        .map(String::from);
+    let vm_counters = dump.get_stream::<MinidumpProcessVmCounters>().ok();
+    let memory_usage = match (&linux_proc_status, &vm_counters) {
+        (Some(status), _) => Some(MemoryUsage {
+            peak_virtual_size: status.vm_peak(),
+            virtual_size: status.vm_size(),
+            resident_size: status.vm_rss(),
+            swap_size: status.vm_swap(),
+            private_size: None,
+        }),
+        (None, Some(counters)) => Some(MemoryUsage {
+            peak_virtual_size: counters.peak_virtual_size(),
+            virtual_size: counters.virtual_size(),
+            resident_size: counters.working_set_size(),
+            swap_size: None,
+            private_size: counters.private_usage(),
+        }),
+        (None, None) => None,
+    };
+    let system_memory_usage = dump
+        .get_stream::<MinidumpSystemMemoryInfo>()
+        .ok()
+        .map(|info| SystemMemoryUsage {
+            physical_size: Some(info.physical_memory()),
+            available_physical_size: Some(info.available_physical_memory()),
+            committed_size: Some(info.committed_memory()),
+            commit_limit: Some(info.commit_limit()),
+            peak_committed_size: Some(info.peak_committed_memory()),
+        });
+    let environment = linux_environ
+        .filter(|_| !options.environ_allowlist.is_empty())
+        .map(|environ| {
+            environ
+                .allowlisted(&options.environ_allowlist)
+                .into_iter()
+                .map(|(key, val)| (key.into_owned(), val.into_owned()))
+                .collect()
+        });
+
     let cpu_info = dump_system_info
         .cpu_info()
         .map(|string| string.into_owned());
 
     let system_info = SystemInfo {
         os: dump_system_info.os,
-        os_version: Some(os_version),
+        os_version: if cpu_inferred { None } else { Some(os_version) },
+        os_build: misc_info.as_ref().and_then(|info| info.build_string()),
         cpu: dump_system_info.cpu,
+        cpu_inferred,
         cpu_info,
+        cpu_vendor: dump_system_info.cpu_vendor(),
+        cpu_family_model_stepping: dump_system_info.cpu_family_model_stepping(),
+        cpu_features: dump_system_info.cpu_features(),
         cpu_microcode_version,
         cpu_count: dump_system_info.raw.number_of_processors as usize,
     };
@@ -174,7 +311,8 @@ where
         .ok()
         .map(|info| info.raw);
 
-    let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+    let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().ok();
+
     // Process create time is optional.
     let (process_id, process_create_time) = if let Some(misc_info) = misc_info.as_ref() {
         (
@@ -194,7 +332,8 @@ where
     // Get exception info if it exists.
     let exception_stream = dump.get_stream::<MinidumpException>().ok();
     let exception_ref = exception_stream.as_ref();
-    let (crash_reason, crash_address, crashing_thread_id) = if let Some(exception) = exception_ref {
+    let (crash_reason, crash_address, exception_thread_id) = if let Some(exception) = exception_ref
+    {
         (
             Some(exception.get_crash_reason(system_info.os, system_info.cpu)),
             Some(exception.get_crash_address(system_info.os, system_info.cpu)),
@@ -203,24 +342,63 @@ where
     } else {
         (None, None, None)
     };
+    // Dumps written on request (e.g. with Breakpad's DumpRequestingThread) can
+    // have an exception stream for the thread that wrote the dump, which we don't
+    // walk. The exception's context is the requesting thread's then.
+    let crashing_thread_id = match (exception_thread_id, requesting_thread_id) {
+        (Some(id), Some(requesting_id)) if Some(id) == dump_thread_id => Some(requesting_id),
+        (id, _) => id,
+    };
     let exception_context =
         exception_ref.and_then(|e| e.context(&dump_system_info, misc_info.as_ref()));
-    // Get assertion
-    let assertion = None;
+    let mut warnings = dump.warnings().to_vec();
+    if cpu_inferred {
+        warnings.push(MinidumpWarning::SystemInfoInferred {
+            cpu: dump_system_info.cpu,
+        });
+    }
+    if let Some(exception) = exception_ref {
+        warnings.extend(context_cpu_warning(
+            exception.check_context_cpu(&dump_system_info),
+            exception.get_crashing_thread_id(),
+            true,
+            exception_context.is_some(),
+        ));
+    }
+    let assertion = dump
+        .get_stream::<MinidumpAssertion>()
+        .ok()
+        .filter(|assertion| !assertion.is_empty())
+        .map(|assertion| describe_assertion(&assertion));
     let modules = match dump.get_stream::<MinidumpModuleList>() {
         Ok(module_list) => module_list,
         // Just give an empty list, simplifies things.
         Err(_) => MinidumpModuleList::new(),
     };
+    // Cross-check the module list against the dynamic linker's.
+    let dso_mismatches = dump
+        .get_stream::<MinidumpLinuxDsoDebug>()
+        .ok()
+        .map(|dso_debug| dso_mismatches(&dso_debug, &modules));
     let unloaded_modules = match dump.get_stream::<MinidumpUnloadedModuleList>() {
         Ok(module_list) => module_list,
         // Just give an empty list, simplifies things.
         Err(_) => MinidumpUnloadedModuleList::new(),
     };
-    let memory_list = dump.get_stream::<MinidumpMemoryList>().unwrap_or_default();
+    // Both the MemoryList and the Memory64List (of full-memory dumps).
+    let memory = dump.memory().unwrap_or_default();
+    // Unwind tables for JIT code, which needs the memory to be usable.
+    let function_table_list = dump
+        .get_stream::<MinidumpFunctionTableList>()
+        .unwrap_or_default();
+    let function_tables = stackwalker::FunctionTables {
+        tables: &function_table_list,
+        memory: &memory,
+    };
+    // Linux minidumps don't have a MemoryInfoList, but their LinuxMaps work the same.
     let memory_info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
     let linux_maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
-    let _memory_info = UnifiedMemoryInfoList::new(memory_info_list, linux_maps).unwrap_or_default();
+    let memory_info = UnifiedMemoryInfoList::new(memory_info_list, linux_maps).unwrap_or_default();
 
     // Get the evil JSON file (thread names and module certificates)
     let evil = options
@@ -228,16 +406,50 @@ where
         .and_then(evil::handle_evil)
         .unwrap_or_default();
 
+    // Module names from Windows are case-insensitive, so let lookups
+    // that miss be retried with normalized names.
+    let stack_symbol_provider =
+        CaseFoldingSymbolProvider::new(symbol_provider, system_info.os == Os::Windows);
+
     let mut threads = vec![];
+    let mut thread_indices = HashMap::new();
     let mut requesting_thread = None;
     for (i, thread) in thread_list.threads.iter().enumerate() {
+        // Every thread gets a CallStack, so `i` is its index in `threads` too.
+        thread_indices.entry(thread.raw.thread_id).or_insert(i);
+
         // If this is the thread that wrote the dump, skip processing it.
         if dump_thread_id.is_some() && dump_thread_id.unwrap() == thread.raw.thread_id {
             threads.push(CallStack::with_info(CallStackInfo::DumpThreadSkipped));
             continue;
         }
 
+        let name = thread
+            .name(&thread_names)
+            .map(|cow| cow.into_owned())
+            .or_else(|| evil.thread_names.get(&thread.raw.thread_id).cloned());
+
+        let selected = match &options.thread_filter {
+            ThreadFilter::All => true,
+            ThreadFilter::Ids(ids) => ids.contains(&thread.raw.thread_id),
+            ThreadFilter::CrashingThread => {
+                crashing_thread_id.or(requesting_thread_id) == Some(thread.raw.thread_id)
+            }
+        };
+        if !selected {
+            let mut stack = CallStack::with_info(CallStackInfo::Filtered);
+            stack.thread_name = name;
+            threads.push(stack);
+            continue;
+        }
+
         let thread_context = thread.context(&dump_system_info, misc_info.as_ref());
+        warnings.extend(context_cpu_warning(
+            thread.check_context_cpu(&dump_system_info),
+            thread.raw.thread_id,
+            false,
+            thread_context.is_some(),
+        ));
         // If this thread requested the dump then try to use the exception
         // context if it exists. (prefer the exception stream's thread id over
         // the breakpad info stream's thread id.)
@@ -254,10 +466,21 @@ where
             thread_context.as_deref()
         };
 
-        let stack = thread.stack_memory(&memory_list);
-
-        let mut stack =
-            stackwalker::walk_stack(&context, stack.as_deref(), &modules, symbol_provider);
+        let stack = thread.stack_memory(&memory);
+
+        let mut stack = stackwalker::walk_stack(
+            &context,
+            stack.as_deref(),
+            &modules,
+            Some(&function_tables),
+            &stack_symbol_provider,
+            &options,
+        );
+        // The stack walk needed the dropped frames to find the later ones,
+        // so they can only be filtered out afterwards.
+        stack
+            .frames
+            .retain(|frame| frame.trust >= options.trust_threshold);
 
         for frame in &mut stack.frames {
             // If the frame doesn't have a loaded module, try to find an unloaded module
@@ -275,43 +498,257 @@ where
 
                 frame.unloaded_modules = offsets;
             }
+
+            if let (Some(file), Some(line)) = (&frame.source_file_name, frame.source_line) {
+                frame.source_link = VcsPath::parse(file)
+                    .and_then(|file| options.source_link_templates.link(&file, line));
+            }
+
+            if system_info.cpu == Cpu::X86 {
+                if let Some(function) = &frame.function_name {
+                    frame.calling_convention =
+                        CallingConvention::infer(function, frame.parameter_size);
+                }
+            }
+
+            for annotator in &options.annotators {
+                annotator.annotate(frame);
+            }
         }
 
-        let name = thread_names
-            .get_name(thread.raw.thread_id)
-            .map(|cow| cow.into_owned())
-            .or_else(|| evil.thread_names.get(&thread.raw.thread_id).cloned());
         stack.thread_name = name;
 
-        stack.last_error_value = thread.last_error(system_info.cpu, &memory_list);
+        if let Some(info) = thread.info(&thread_infos) {
+            let mut routine = ThreadStartRoutine::new(info.start_address());
+            if let Some(module) = modules.module_at_address(routine.address) {
+                // This is best effort, so ignore any errors.
+                let _ = stack_symbol_provider.fill_symbol(module, &mut routine);
+                routine.module = Some(module.clone());
+            }
+            stack.start_routine = Some(routine);
+            stack.kernel_time = Some(info.kernel_time());
+            stack.user_time = Some(info.user_time());
+        }
+
+        stack.last_error_value = thread.last_error(system_info.cpu, &memory);
 
         threads.push(stack);
     }
 
+    // The bounds of the memory region the crashing thread's stack pointer is in.
+    let crashing_stack = requesting_thread
+        .and_then(|i| threads[i].frames.first())
+        .and_then(|frame| memory_info.memory_info_at_address(frame.context.get_stack_pointer()))
+        .and_then(|region| region.memory_range());
+    // Describe what the crash address pointed at, e.g. "write to a PAGE_READONLY committed region".
+    let crash_address_description = crash_address.and_then(|address| {
+        let region = memory_info.memory_info_at_address(address)?;
+        let access = match crash_reason {
+            Some(CrashReason::WindowsAccessViolation(access)) => match access {
+                ExceptionCodeWindowsAccessType::READ => "read from",
+                ExceptionCodeWindowsAccessType::WRITE => "write to",
+                ExceptionCodeWindowsAccessType::EXEC => "execute in",
+            },
+            _ => "in",
+        };
+        let mut description = format!("{} {}", access, region.describe());
+        if crashing_stack.is_some_and(|stack| stack.contains(address)) {
+            description.push_str(" (the crashing thread's stack)");
+        }
+        Some(description)
+    });
+
+    // The SSE unit's state, if it's what raised the crash.
+    let crash_mxcsr = crash_reason
+        .filter(is_floating_point_exception)
+        .and(requesting_thread)
+        .and_then(|i| threads[i].frames.first())
+        .and_then(|frame| frame.context.mxcsr_description());
+
+    let handle_summary = dump
+        .get_stream::<MinidumpHandleDataStream>()
+        .ok()
+        .map(|handles| HandleSummary::from_handles(&handles));
+
     // Collect up info on unimplemented/unknown modules
     let unknown_streams = dump.unknown_streams().collect();
     let unimplemented_streams = dump.unimplemented_streams().collect();
+    let streams = dump.streams().map(|(_, stream)| stream).collect();
 
     // Get symbol stats from the symbolizer
     let symbol_stats = symbol_provider.stats();
 
     Ok(ProcessState {
         process_id,
+        process_name,
+        memory_usage,
+        system_memory_usage,
+        environment,
         time: Utc.timestamp(dump.header.time_date_stamp as i64, 0),
         process_create_time,
         cert_info: evil.certs,
         crash_reason,
         crash_address,
+        crash_address_description,
+        crash_mxcsr,
         assertion,
         requesting_thread,
+        dump_thread_id,
+        requesting_thread_id,
         system_info,
         linux_standard_base,
         mac_crash_info,
+        crashpad_info,
+        dso_mismatches,
         threads,
         modules,
         unloaded_modules,
+        handle_summary,
         unknown_streams,
         unimplemented_streams,
+        streams,
+        warnings,
         symbol_stats,
+        thread_indices,
+        exception_thread_id,
     })
 }
+
+/// Overrides a provider's symbol timeout until dropped, so that
+/// [`ProcessorOptions::symbol_timeout`] doesn't leak into later uses of it.
+/// Whatever override the provider had before is put back.
+struct SymbolTimeoutOverride<'a, P: SymbolProvider> {
+    provider: &'a P,
+    previous: Option<Duration>,
+}
+
+impl<'a, P: SymbolProvider> SymbolTimeoutOverride<'a, P> {
+    fn new(provider: &'a P, timeout: Duration) -> Self {
+        let previous = provider.symbol_timeout();
+        provider.set_symbol_timeout(Some(timeout));
+        SymbolTimeoutOverride { provider, previous }
+    }
+}
+
+impl<'a, P: SymbolProvider> Drop for SymbolTimeoutOverride<'a, P> {
+    fn drop(&mut self) {
+        self.provider.set_symbol_timeout(self.previous);
+    }
+}
+
+/// Turn a failed [`MinidumpThread::check_context_cpu`][] into a warning for the
+/// [`ProcessState`][]. `used` is whether the context was read anyway.
+fn context_cpu_warning(
+    result: Result<(), ContextError>,
+    thread_id: u32,
+    exception: bool,
+    used: bool,
+) -> Option<MinidumpWarning> {
+    match result {
+        Err(ContextError::CpuMismatch {
+            context_cpu,
+            system_info_cpu,
+        }) => Some(MinidumpWarning::ContextCpuMismatch {
+            thread_id,
+            exception,
+            context_cpu,
+            system_info_cpu,
+            used,
+        }),
+        _ => None,
+    }
+}
+
+/// Describe an assertion the way Breakpad's processor does, e.g. "Invalid
+/// parameter passed to library function format != nullptr in function
+/// common_vfprintf, in file output.cpp at line 32".
+fn describe_assertion(assertion: &MinidumpAssertion) -> String {
+    let mut description = match assertion.assertion_type() {
+        Some(AssertionType::InvalidParameter) => {
+            String::from("Invalid parameter passed to library function")
+        }
+        Some(AssertionType::PureVirtualCall) => String::from("Pure virtual function called"),
+        _ => format!("Unknown assertion type {:#010x}", assertion.raw._type),
+    };
+    if let Some(expression) = assertion.expression() {
+        description.push(' ');
+        description.push_str(&expression);
+    }
+    if let Some(function) = assertion.function() {
+        description.push_str(" in function ");
+        description.push_str(&function);
+    }
+    if let Some(file) = assertion.file() {
+        description.push_str(", in file ");
+        description.push_str(&file);
+    }
+    if let Some(line) = assertion.line() {
+        description.push_str(&format!(" at line {}", line));
+    }
+    description
+}
+
+/// Find the shared libraries in the dynamic linker's list that the module list
+/// disagrees with.
+fn dso_mismatches(
+    dso_debug: &MinidumpLinuxDsoDebug,
+    modules: &MinidumpModuleList,
+) -> Vec<DsoMismatch> {
+    dso_debug
+        .link_maps
+        .iter()
+        .filter_map(|link_map| {
+            // The main executable has no name, and the vdso isn't a file at all
+            // (the dynamic linker calls it linux-vdso.so.1, Breakpad linux-gate.so).
+            let name = link_map.name.as_deref().filter(|name| !name.is_empty())?;
+            if name.starts_with("linux-vdso") || name.starts_with("linux-gate") {
+                return None;
+            }
+            let module_addresses: Vec<u64> = modules
+                .iter()
+                .filter(|module| module.name() == basename(name))
+                .map(|module| module.base_address())
+                .collect();
+            if module_addresses.contains(&link_map.addr) {
+                return None;
+            }
+            Some(DsoMismatch {
+                name: name.to_string(),
+                link_map_address: link_map.addr,
+                module_address: module_addresses.first().copied(),
+            })
+        })
+        .collect()
+}
+
+/// Whether `reason` is a floating point exception the SSE unit could have
+/// raised, so the MXCSR says something about it.
+fn is_floating_point_exception(reason: &CrashReason) -> bool {
+    use minidump::format::{
+        ExceptionCodeLinuxSigfpeKind as Sigfpe, ExceptionCodeMacArithmeticX86Type as MacArithmetic,
+        ExceptionCodeWindows as Windows, NtStatusWindows as NtStatus,
+    };
+    match *reason {
+        CrashReason::WindowsGeneral(code) => matches!(
+            code,
+            Windows::EXCEPTION_FLT_DENORMAL_OPERAND
+                | Windows::EXCEPTION_FLT_DIVIDE_BY_ZERO
+                | Windows::EXCEPTION_FLT_INEXACT_RESULT
+                | Windows::EXCEPTION_FLT_INVALID_OPERATION
+                | Windows::EXCEPTION_FLT_OVERFLOW
+                | Windows::EXCEPTION_FLT_STACK_CHECK
+                | Windows::EXCEPTION_FLT_UNDERFLOW
+        ),
+        // SSE exceptions on x86-64 Windows.
+        CrashReason::WindowsNtStatus(status) => matches!(
+            status,
+            NtStatus::STATUS_FLOAT_MULTIPLE_FAULTS | NtStatus::STATUS_FLOAT_MULTIPLE_TRAPS
+        ),
+        CrashReason::LinuxSigfpe(kind) => !matches!(kind, Sigfpe::FPE_INTDIV | Sigfpe::FPE_INTOVF),
+        CrashReason::MacArithmeticX86(kind) => matches!(
+            kind,
+            MacArithmetic::EXC_I386_EXTERR | MacArithmetic::EXC_I386_SSEEXTERR
+        ),
+        _ => false,
+    }
+}
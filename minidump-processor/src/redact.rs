@@ -0,0 +1,66 @@
+//! Removing sensitive data from minidumps without breaking their stack traces.
+//!
+//! [`redact_minidump`] uses the stack walks in a [`ProcessState`] to decide
+//! which memory a minidump still needs, and scrubs everything else with a
+//! [`MinidumpScrubber`].
+
+use std::ops::{DerefMut, Range};
+
+use minidump::{Minidump, MinidumpScrubber, ScrubPolicy};
+
+use crate::process_state::{CallStack, ProcessState};
+
+/// Options for [`redact_minidump`].
+#[derive(Debug, Default)]
+pub struct RedactionOptions {
+    /// What else to scrub. By default the environment and command line are dropped.
+    ///
+    /// `zero_non_stack_memory` is always set, and `keep_memory` is replaced
+    /// if `walked_stack_only` is set.
+    pub policy: ScrubPolicy,
+    /// Only keep the parts of the stacks that were used to unwind them,
+    /// instead of the whole stacks.
+    pub walked_stack_only: bool,
+}
+
+/// Redact `dump` in-place, keeping what's needed to unwind it the way `state` did.
+///
+/// `state` should be the result of processing `dump`. All memory that isn't part
+/// of a thread's stack is zeroed, and with [`RedactionOptions::walked_stack_only`]
+/// so is every part of a stack that its stack walk didn't cover. Processing the
+/// redacted minidump with the same symbols produces the same stack traces, but
+/// anything else that reads memory (like the thread's last error value) won't
+/// find it anymore. Stacks that can only be unwound with data from the heap, such
+/// as JIT code's function tables, may lose frames.
+///
+/// Use [`Minidump::write`] afterwards to write a minidump without the dropped streams.
+pub fn redact_minidump<'a, T>(
+    dump: &mut Minidump<'a, T>,
+    state: &ProcessState,
+    options: RedactionOptions,
+) -> Result<(), minidump::Error>
+where
+    T: DerefMut<Target = [u8]> + 'a,
+{
+    let mut policy = options.policy;
+    policy.zero_non_stack_memory = true;
+    if options.walked_stack_only {
+        policy.keep_memory = Some(state.threads.iter().filter_map(walked_stack).collect());
+    }
+    MinidumpScrubber::new(policy).apply(dump)
+}
+
+/// The stack memory that `stack`'s stack walk covered, from the innermost
+/// frame's stack pointer to the outermost frame's.
+///
+/// Unwinding a frame only reads the memory between its own stack pointer and
+/// its caller's, so this is enough to find the same frames again.
+fn walked_stack(stack: &CallStack) -> Option<Range<u64>> {
+    let stack_pointers = stack
+        .frames
+        .iter()
+        .map(|frame| frame.context.get_stack_pointer());
+    let start = stack_pointers.clone().min()?;
+    let end = stack_pointers.max()?;
+    Some(start..end.saturating_add(1))
+}
@@ -0,0 +1,373 @@
+//! Crash signatures, for grouping similar crashes.
+//!
+//! This follows the approach of Mozilla's signature generation (as used by
+//! Socorro): every frame is reduced to a short name, frames that show up in
+//! almost every crash (abort handlers, exception dispatchers, panic
+//! machinery) are skipped, and the names of the remaining top frames are
+//! joined with ` | `. See [`CallStack::signature`][crate::CallStack::signature].
+//!
+//! The runtime frames at the bottom of every stack, like `_start`, are
+//! dropped too. Which ones those are is configurable, see [`NoiseList`].
+
+use minidump::Module;
+
+use crate::process_state::StackFrame;
+
+/// Frames that are skipped because they're part of how a program crashes,
+/// rather than why.
+static IRRELEVANT_FUNCTIONS: &[&str] = &[
+    "__GI_abort",
+    "__GI_raise",
+    "__pthread_kill_implementation",
+    "__rust_start_panic",
+    "_CxxThrowException",
+    "abort",
+    "KiFastSystemCallRet",
+    "KiUserExceptionDispatcher",
+    "mozalloc_abort",
+    "MOZ_Crash",
+    "NtWaitForMultipleObjects",
+    "NtWaitForSingleObject",
+    "pthread_kill",
+    "raise",
+    "RaiseException",
+    "RtlpCallVectoredHandlers",
+    "rust_panic",
+    "WaitForSingleObject",
+    "WaitForSingleObjectEx",
+];
+
+/// Prefixes of irrelevant functions, for whole families of them.
+static IRRELEVANT_PREFIXES: &[&str] = &[
+    "core::panicking::",
+    "std::panicking::",
+    "std::sys::unix::abort_internal",
+    "std::sys_common::backtrace::",
+];
+
+/// Functions that end the signature, because nothing below them helps tell
+/// crashes apart.
+static SENTINEL_FUNCTIONS: &[&str] = &[
+    "__cxa_pure_virtual",
+    "_purecall",
+    "std::rt::lang_start_internal",
+    "std::terminate",
+];
+
+/// The program and thread entry points that [`NoiseList::default`] has.
+static DEFAULT_NOISE_FUNCTIONS: &[&str] = &[
+    // glibc
+    "__clone",
+    "__clone3",
+    "__libc_start_call_main",
+    "__libc_start_main",
+    "_start",
+    "clone",
+    "clone3",
+    "start_thread",
+    // musl
+    "_start_c",
+    "libc_start_main_stage2",
+    "start",
+    // macOS
+    "_pthread_start",
+    "thread_start",
+    // Windows CRT and loader
+    "__scrt_common_main",
+    "__scrt_common_main_seh",
+    "__tmainCRTStartup",
+    "_callthreadstartex",
+    "_RtlUserThreadStart",
+    "_threadstartex",
+    "BaseThreadInitThunk",
+    "invoke_main",
+    "mainCRTStartup",
+    "pthread_create_wrapper",
+    "RtlUserThreadStart",
+    "thread_start<T>",
+    "WinMainCRTStartup",
+    "wmainCRTStartup",
+    "wWinMainCRTStartup",
+];
+
+/// Functions that are at the bottom of many stacks because they start
+/// programs or threads, like `__libc_start_main` or `BaseThreadInitThunk`.
+///
+/// Everything from the first of these down is the same in every crash, so
+/// [`CallStack::signature`][crate::CallStack::signature] ends there. The
+/// default list has the entry points of glibc, musl, macOS, and the Windows
+/// CRT, and [`NoiseList::new`] is an empty one to build on instead.
+///
+/// Names are matched after the same normalization as the signature's, so
+/// they have no parameter lists and template arguments are `<T>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoiseList {
+    functions: Vec<String>,
+    prefixes: Vec<String>,
+}
+
+impl NoiseList {
+    /// A `NoiseList` without any functions.
+    pub fn new() -> NoiseList {
+        NoiseList {
+            functions: Vec::new(),
+            prefixes: Vec::new(),
+        }
+    }
+
+    /// Add a function, like `my_runtime::thread_main`.
+    pub fn add_function(&mut self, function: impl Into<String>) -> &mut Self {
+        self.functions.push(function.into());
+        self
+    }
+
+    /// Add every function whose name starts with `prefix`, like `my_runtime::`.
+    pub fn add_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.prefixes.push(prefix.into());
+        self
+    }
+
+    /// Whether the normalized function name `function` is noise.
+    pub fn contains(&self, function: &str) -> bool {
+        self.functions.iter().any(|noise| noise == function)
+            || self
+                .prefixes
+                .iter()
+                .any(|prefix| function.starts_with(prefix.as_str()))
+    }
+}
+
+impl Default for NoiseList {
+    fn default() -> NoiseList {
+        let mut noise = NoiseList::new();
+        for &function in DEFAULT_NOISE_FUNCTIONS {
+            noise.add_function(function);
+        }
+        noise
+    }
+}
+
+/// MSVC calling conventions, which demangled MSVC names include along with
+/// an access specifier and the return type.
+static CALLING_CONVENTIONS: &[&str] = &[
+    " __cdecl ",
+    " __clrcall ",
+    " __fastcall ",
+    " __stdcall ",
+    " __thiscall ",
+    " __vectorcall ",
+];
+
+/// Compute the signature of `frames`, see [`CallStack::signature`][crate::CallStack::signature].
+pub(crate) fn signature(frames: &[StackFrame], max_frames: usize, noise: &NoiseList) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for frame in frames {
+        if parts.len() >= max_frames {
+            break;
+        }
+        let name = frame_name(frame);
+        if is_irrelevant(&name) {
+            continue;
+        }
+        if noise.contains(&name) {
+            // If the crash was in the entry point itself, look further down.
+            if parts.is_empty() {
+                continue;
+            }
+            break;
+        }
+        // Collapse recursion, it would just push the interesting frames out.
+        if parts.last() == Some(&name) {
+            continue;
+        }
+        let is_sentinel = SENTINEL_FUNCTIONS.contains(&name.as_str());
+        parts.push(name);
+        if is_sentinel {
+            break;
+        }
+    }
+    parts.join(" | ")
+}
+
+/// Whether `frame` is one of the functions in `noise`, see [`StackFrame::is_noise`][].
+pub(crate) fn is_noise(frame: &StackFrame, noise: &NoiseList) -> bool {
+    match frame.demangled_function_name() {
+        Some(function) => noise.contains(&normalize_function(&function)),
+        None => false,
+    }
+}
+
+/// The name of a frame in a signature.
+///
+/// Symbolized frames use their normalized function name. Other frames use
+/// `module@offset`, or just `@address` if they aren't in a module.
+fn frame_name(frame: &StackFrame) -> String {
+    if let Some(function) = frame.demangled_function_name() {
+        return normalize_function(&function);
+    }
+    match frame.module {
+        Some(ref module) => format!(
+            "{}@{:#x}",
+            module.name(),
+            frame.instruction - module.base_address()
+        ),
+        None => format!("@{:#x}", frame.instruction),
+    }
+}
+
+/// Remove the parts of a function name that make the same function look
+/// different across builds: parameter lists, `const` qualifiers, template
+/// arguments (which are collapsed to `<T>`), and for MSVC names the access
+/// specifier, return type and calling convention.
+fn normalize_function(function: &str) -> String {
+    let mut function = function.trim();
+    for convention in CALLING_CONVENTIONS {
+        if let Some((_, rest)) = function.split_once(convention) {
+            function = rest;
+            break;
+        }
+    }
+    if let Some(stripped) = function.strip_suffix(" const") {
+        function = stripped;
+    }
+    if let Some(params) = parameter_list_start(function) {
+        function = function[..params].trim_end();
+    }
+    collapse_templates(function)
+}
+
+/// Find the `(` that starts the parameter list at the end of `function`, if it has one.
+fn parameter_list_start(function: &str) -> Option<usize> {
+    if !function.ends_with(')') {
+        return None;
+    }
+    let mut depth = 0usize;
+    for (i, c) in function.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' => {
+                depth -= 1;
+                if depth == 0 {
+                    // A name that's entirely in parens (e.g. "(anonymous namespace)")
+                    // has no parameter list.
+                    return if i == 0 { None } else { Some(i) };
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Replace every top-level template argument list with `<T>`.
+fn collapse_templates(function: &str) -> String {
+    let mut collapsed = String::with_capacity(function.len());
+    let mut depth = 0usize;
+    for c in function.chars() {
+        match c {
+            // operator<, operator<<, operator->, ...
+            '<' | '>' if depth == 0 && ends_with_operator(&collapsed) => collapsed.push(c),
+            '<' => {
+                if depth == 0 {
+                    collapsed.push_str("<T>");
+                }
+                depth += 1;
+            }
+            '>' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            _ => collapsed.push(c),
+        }
+    }
+    collapsed
+}
+
+fn ends_with_operator(name: &str) -> bool {
+    name.trim_end_matches(['<', '>', '=', '-'])
+        .ends_with("operator")
+}
+
+fn is_irrelevant(name: &str) -> bool {
+    IRRELEVANT_FUNCTIONS.contains(&name)
+        || IRRELEVANT_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_function() {
+        assert_eq!(normalize_function("main"), "main");
+        assert_eq!(normalize_function("  Foo::Bar(int, char*)  "), "Foo::Bar");
+        assert_eq!(normalize_function("Foo::Bar() const"), "Foo::Bar");
+        assert_eq!(
+            normalize_function("public: int __thiscall foo::Bar::baz(int) const"),
+            "foo::Bar::baz"
+        );
+        assert_eq!(
+            normalize_function("void __cdecl std::sort<int *>(int *, int *)"),
+            "std::sort<T>"
+        );
+        // Function pointer parameters have parens of their own.
+        assert_eq!(
+            normalize_function("call_with(void (*)(int), int)"),
+            "call_with"
+        );
+        // Nothing to strip from a name that's all parens.
+        assert_eq!(
+            normalize_function("(anonymous namespace)"),
+            "(anonymous namespace)"
+        );
+        assert_eq!(
+            normalize_function("(anonymous namespace)::helper(int)"),
+            "(anonymous namespace)::helper"
+        );
+    }
+
+    #[test]
+    fn test_collapse_templates() {
+        assert_eq!(collapse_templates("no_templates"), "no_templates");
+        assert_eq!(
+            collapse_templates("std::vector<int, std::allocator<int> >::push_back"),
+            "std::vector<T>::push_back"
+        );
+        assert_eq!(
+            collapse_templates("Outer<A>::Inner<B<C>>::get"),
+            "Outer<T>::Inner<T>::get"
+        );
+        // Operators aren't template arguments.
+        assert_eq!(collapse_templates("Foo::operator<"), "Foo::operator<");
+        assert_eq!(collapse_templates("Foo::operator<<"), "Foo::operator<<");
+        assert_eq!(collapse_templates("Foo::operator->"), "Foo::operator->");
+        assert_eq!(collapse_templates("Foo::operator>="), "Foo::operator>=");
+        assert_eq!(
+            collapse_templates("Foo<int>::operator< <int>"),
+            "Foo<T>::operator< <T>"
+        );
+        // An unbalanced '>' is kept.
+        assert_eq!(collapse_templates("a>b"), "a>b");
+    }
+
+    #[test]
+    fn test_noise_list() {
+        let mut noise = NoiseList::new();
+        assert!(!noise.contains("_start"));
+        noise
+            .add_function("my_runtime::thread_main")
+            .add_prefix("tokio::runtime::");
+        assert!(noise.contains("my_runtime::thread_main"));
+        assert!(!noise.contains("my_runtime::thread_main2"));
+        assert!(!noise.contains("my_runtime"));
+        assert!(noise.contains("tokio::runtime::task::harness"));
+        assert!(!noise.contains("tokio::spawn"));
+
+        let noise = NoiseList::default();
+        assert!(noise.contains("_start"));
+        assert!(noise.contains("BaseThreadInitThunk"));
+        assert!(noise.contains("thread_start<T>"));
+        assert!(!noise.contains("main"));
+        assert_ne!(noise, NoiseList::new());
+    }
+}
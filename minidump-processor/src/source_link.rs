@@ -0,0 +1,108 @@
+//! Support for VCS-annotated source file paths.
+//!
+//! Mozilla-style builds annotate the paths in their symbol files' FILE records
+//! with the repository and revision they were built from, in the form
+//! `<vcs>:<repo>:<path>:<revision>`, e.g.
+//!
+//! ```text
+//! hg:hg.mozilla.org/mozilla-central:dom/base/nsDocument.cpp:abcdef
+//! git:github.com/rust-minidump/rust-minidump:minidump/src/lib.rs:0123456
+//! ```
+//!
+//! [VcsPath][] decodes these, and [SourceLinkTemplates][] turns them into
+//! permalinks to the source.
+
+use std::collections::HashMap;
+
+/// A source file path annotated with the VCS information it was built from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VcsPath {
+    /// The version control system, e.g. "hg" or "git".
+    pub vcs: String,
+    /// The repository, e.g. "hg.mozilla.org/mozilla-central".
+    pub repo: String,
+    /// The path of the file within the repository.
+    pub path: String,
+    /// The revision the file was built from.
+    pub revision: String,
+}
+
+impl VcsPath {
+    /// Create a `VcsPath` from its parts.
+    pub fn new(vcs: &str, repo: &str, path: &str, revision: &str) -> VcsPath {
+        VcsPath {
+            vcs: vcs.to_string(),
+            repo: repo.to_string(),
+            path: path.to_string(),
+            revision: revision.to_string(),
+        }
+    }
+
+    /// Parse a VCS-annotated path, returning `None` if `file` isn't annotated.
+    pub fn parse(file: &str) -> Option<VcsPath> {
+        let (vcs, rest) = file.split_once(':')?;
+        let (rest, revision) = rest.rsplit_once(':')?;
+        let (repo, path) = rest.split_once(':')?;
+
+        // Don't mistake a Windows drive letter (c:\foo\bar.cpp) for a VCS.
+        if vcs.len() < 2 || !vcs.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        if repo.is_empty() || path.is_empty() || revision.is_empty() {
+            return None;
+        }
+
+        Some(VcsPath::new(vcs, repo, path, revision))
+    }
+}
+
+/// URL templates for generating source permalinks, keyed by VCS.
+///
+/// Templates may contain `{repo}`, `{path}`, `{revision}`, and `{line}`,
+/// which are replaced with the corresponding values. By default templates
+/// are provided for "hg" (hgweb) and "git" (GitHub-style).
+#[derive(Clone, Debug)]
+pub struct SourceLinkTemplates {
+    templates: HashMap<String, String>,
+}
+
+impl Default for SourceLinkTemplates {
+    fn default() -> Self {
+        let mut templates = SourceLinkTemplates::empty();
+        templates.set("hg", "https://{repo}/annotate/{revision}/{path}#l{line}");
+        templates.set("git", "https://{repo}/blob/{revision}/{path}#L{line}");
+        templates
+    }
+}
+
+impl SourceLinkTemplates {
+    /// Create a `SourceLinkTemplates` with no templates at all.
+    pub fn empty() -> Self {
+        SourceLinkTemplates {
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Set the template for `vcs`, replacing any existing one.
+    pub fn set(&mut self, vcs: &str, template: &str) {
+        self.templates.insert(vcs.to_string(), template.to_string());
+    }
+
+    /// Remove the template for `vcs`, so no links are generated for it.
+    pub fn remove(&mut self, vcs: &str) {
+        self.templates.remove(vcs);
+    }
+
+    /// Generate a permalink to `line` of `file`, if there's a template for its VCS.
+    pub fn link(&self, file: &VcsPath, line: u32) -> Option<String> {
+        let template = self.templates.get(&file.vcs)?;
+        Some(
+            template
+                .replace("{repo}", &file.repo)
+                .replace("{path}", &file.path)
+                .replace("{revision}", &file.revision)
+                .replace("{line}", &line.to_string()),
+        )
+    }
+}
@@ -9,11 +9,11 @@
 use crate::process_state::{FrameTrust, StackFrame};
 use crate::stackwalker::unwind::Unwind;
 use crate::stackwalker::CfiStackWalker;
-use crate::SymbolProvider;
+use crate::{FunctionTables, ProcessorOptions, SymbolProvider};
 use log::trace;
 use minidump::format::CONTEXT_AMD64;
 use minidump::{
-    MinidumpContext, MinidumpContextValidity, MinidumpMemory, MinidumpModuleList,
+    CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpMemory, MinidumpModuleList,
     MinidumpRawContext,
 };
 use std::collections::HashSet;
@@ -86,6 +86,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Amd64(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -101,6 +102,204 @@ fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static st
     }
 }
 
+/// The registers in the order Windows x64 unwind codes number them.
+const UNWIND_CODE_REGS: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+
+// Windows x64 unwind operations, see the documentation for `UNWIND_CODE`.
+const UWOP_PUSH_NONVOL: u8 = 0;
+const UWOP_ALLOC_LARGE: u8 = 1;
+const UWOP_ALLOC_SMALL: u8 = 2;
+const UWOP_SET_FPREG: u8 = 3;
+const UWOP_SAVE_NONVOL: u8 = 4;
+const UWOP_SAVE_NONVOL_FAR: u8 = 5;
+const UWOP_EPILOG: u8 = 6;
+const UWOP_SPARE_CODE: u8 = 7;
+const UWOP_SAVE_XMM128: u8 = 8;
+const UWOP_SAVE_XMM128_FAR: u8 = 9;
+const UWOP_PUSH_MACHFRAME: u8 = 10;
+
+/// The `UNWIND_INFO` is followed by the `RUNTIME_FUNCTION` it continues.
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+/// Give up on chains of `UNWIND_INFO` longer than this, they're probably garbage.
+const MAX_CHAINED_UNWIND_INFO: usize = 32;
+
+fn get_caller_by_function_table(
+    ctx: &CONTEXT_AMD64,
+    callee: &StackFrame,
+    stack_memory: &MinidumpMemory,
+    function_tables: &FunctionTables,
+) -> Option<StackFrame> {
+    trace!("unwind: trying function table");
+
+    let valid = &callee.context.valid;
+    if let MinidumpContextValidity::Some(ref which) = valid {
+        if !which.contains(STACK_POINTER_REGISTER) {
+            return None;
+        }
+    }
+
+    // Function tables describe code outside of any module (i.e. JIT code), using
+    // the same UNWIND_INFO that Windows x64 modules store in their .pdata section.
+    // This is essentially RtlVirtualUnwind, minus the epilogue detection.
+    let (table, function) = function_tables
+        .tables
+        .function_at_address(callee.instruction)?;
+    let base = table.base_address();
+    let mut function = *function;
+    // How far into the function's prologue we are. Only the unwind codes for
+    // instructions that have already executed should be applied.
+    //
+    // Everything here comes from the dump, so all the arithmetic is checked
+    // to reject garbage instead of overflowing.
+    let mut prolog_offset = callee
+        .instruction
+        .checked_sub(base.checked_add(function.begin_address as u64)?)?;
+
+    let mut caller_ctx = ctx.clone();
+    let mut caller_validity = callee_forwarded_regs(valid);
+    let mut sp = ctx.rsp;
+    let mut caller_ip = None;
+
+    let read_stack = |address: u64| stack_memory.read_u64(address);
+
+    for _ in 0..MAX_CHAINED_UNWIND_INFO {
+        let info_address = base.checked_add(function.unwind_info_address as u64)?;
+        let codes_address = info_address.checked_add(4)?;
+        let version_and_flags: u8 = function_tables.read(info_address)?;
+        let count_of_codes: u8 = function_tables.read(info_address.checked_add(2)?)?;
+        let frame_register_and_offset: u8 = function_tables.read(info_address.checked_add(3)?)?;
+
+        let version = version_and_flags & 0x7;
+        let flags = version_and_flags >> 3;
+        if version != 1 && version != 2 {
+            trace!("unwind: unknown UNWIND_INFO version {}", version);
+            return None;
+        }
+        let frame_register = UNWIND_CODE_REGS[(frame_register_and_offset & 0xf) as usize];
+        let frame_offset = (frame_register_and_offset >> 4) as u64 * 16;
+
+        let code_at = |slot: u64| -> Option<u16> {
+            function_tables.read(codes_address.checked_add(slot.checked_mul(2)?)?)
+        };
+        let mut slot = 0;
+        while slot < count_of_codes as u64 {
+            let code = code_at(slot)?;
+            let code_offset = (code & 0xff) as u64;
+            let op = ((code >> 8) & 0xf) as u8;
+            let op_info = (code >> 12) as u8;
+
+            // Operations that haven't executed yet must be skipped, but not
+            // without knowing how many slots they take up.
+            let executed = code_offset <= prolog_offset;
+            let slots = match op {
+                UWOP_PUSH_NONVOL | UWOP_ALLOC_SMALL | UWOP_SET_FPREG | UWOP_PUSH_MACHFRAME => 1,
+                UWOP_ALLOC_LARGE if op_info == 0 => 2,
+                UWOP_ALLOC_LARGE => 3,
+                UWOP_SAVE_NONVOL | UWOP_EPILOG | UWOP_SAVE_XMM128 => 2,
+                UWOP_SAVE_NONVOL_FAR | UWOP_SPARE_CODE | UWOP_SAVE_XMM128_FAR => 3,
+                _ => {
+                    trace!("unwind: unknown unwind operation {}", op);
+                    return None;
+                }
+            };
+
+            if executed {
+                let reg = UNWIND_CODE_REGS[op_info as usize];
+                match op {
+                    UWOP_PUSH_NONVOL => {
+                        caller_ctx.set_register(reg, read_stack(sp)?)?;
+                        caller_validity.insert(reg);
+                        sp = sp.checked_add(POINTER_WIDTH)?;
+                    }
+                    UWOP_ALLOC_LARGE if op_info == 0 => {
+                        sp = sp.checked_add((code_at(slot + 1)? as u64).checked_mul(8)?)?;
+                    }
+                    UWOP_ALLOC_LARGE => {
+                        let low = code_at(slot + 1)? as u64;
+                        let high = code_at(slot + 2)? as u64;
+                        sp = sp.checked_add(low | (high << 16))?;
+                    }
+                    UWOP_ALLOC_SMALL => {
+                        sp = sp.checked_add(op_info as u64 * 8 + 8)?;
+                    }
+                    UWOP_SET_FPREG => {
+                        let frame = ctx.get_register(frame_register, valid)?;
+                        sp = frame.checked_sub(frame_offset)?;
+                    }
+                    UWOP_SAVE_NONVOL | UWOP_SAVE_NONVOL_FAR => {
+                        let offset = if op == UWOP_SAVE_NONVOL {
+                            code_at(slot + 1)? as u64 * 8
+                        } else {
+                            code_at(slot + 1)? as u64 | ((code_at(slot + 2)? as u64) << 16)
+                        };
+                        caller_ctx.set_register(reg, read_stack(sp.checked_add(offset)?)?)?;
+                        caller_validity.insert(reg);
+                    }
+                    UWOP_PUSH_MACHFRAME => {
+                        // An interrupt or exception pushed a whole machine frame
+                        // (optionally preceded by an error code), so the caller's
+                        // ip and sp come straight from it.
+                        if op_info == 1 {
+                            sp = sp.checked_add(POINTER_WIDTH)?;
+                        }
+                        caller_ip = Some(read_stack(sp)?);
+                        sp = read_stack(sp.checked_add(3 * POINTER_WIDTH)?)?;
+                        break;
+                    }
+                    // Epilogue and xmm codes don't affect the registers we track.
+                    _ => {}
+                }
+            }
+            slot += slots;
+        }
+
+        if caller_ip.is_some() || flags & UNW_FLAG_CHAININFO == 0 {
+            break;
+        }
+
+        // The chained function's prologue has necessarily run to completion.
+        let chain_address =
+            codes_address.checked_add(((count_of_codes as u64 + 1) & !1).checked_mul(2)?)?;
+        function = function_tables.read(chain_address)?;
+        prolog_offset = u64::MAX;
+    }
+
+    let caller_ip = match caller_ip {
+        Some(ip) => ip,
+        None => {
+            let ip = read_stack(sp)?;
+            sp = sp.checked_add(POINTER_WIDTH)?;
+            ip
+        }
+    };
+
+    // Don't accept obviously wrong instruction pointers.
+    if is_non_canonical(caller_ip) {
+        trace!("unwind: rejecting function table result for unreasonable instruction pointer");
+        return None;
+    }
+
+    trace!(
+        "unwind: function table seems valid -- caller_ip: 0x{:016x}, caller_sp: 0x{:016x}",
+        caller_ip,
+        sp,
+    );
+
+    caller_ctx.rip = caller_ip;
+    caller_ctx.rsp = sp;
+    caller_validity.insert(INSTRUCTION_REGISTER);
+    caller_validity.insert(STACK_POINTER_REGISTER);
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Amd64(caller_ctx),
+        valid: MinidumpContextValidity::Some(caller_validity),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+}
+
 fn get_caller_by_frame_pointer<P>(
     ctx: &CONTEXT_AMD64,
     callee: &StackFrame,
@@ -152,8 +351,8 @@ where
         // drowning the rest of the code in checked_add.
         return None;
     }
-    let caller_ip = stack_memory.get_memory_at_address(last_bp as u64 + POINTER_WIDTH as u64)?;
-    let caller_bp = stack_memory.get_memory_at_address(last_bp as u64)?;
+    let caller_ip = stack_memory.read_u64(last_bp as u64 + POINTER_WIDTH as u64)?;
+    let caller_bp = stack_memory.read_u64(last_bp as u64)?;
     let caller_sp = last_bp + POINTER_WIDTH * 2;
 
     // If the recovered ip is not a canonical address it can't be
@@ -167,7 +366,7 @@ where
     }
     // Since we're assuming coherent frame pointers, check that the resulting
     // frame pointer is still inside stack memory.
-    let _unused: Pointer = stack_memory.get_memory_at_address(caller_bp as u64)?;
+    let _unused: Pointer = stack_memory.read_u64(caller_bp as u64)?;
     // Don't accept obviously wrong instruction pointers.
     if is_non_canonical(caller_ip) {
         trace!("unwind: rejecting frame pointer result for unreasonable instruction pointer");
@@ -198,6 +397,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Amd64(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -208,6 +408,7 @@ fn get_caller_by_scan<P>(
     stack_memory: &MinidumpMemory,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider,
@@ -249,8 +450,8 @@ where
 
     for i in 0..scan_range {
         let address_of_ip = last_sp.checked_add(i * POINTER_WIDTH)?;
-        let caller_ip = stack_memory.get_memory_at_address(address_of_ip as u64)?;
-        if instruction_seems_valid(caller_ip, modules, symbol_provider) {
+        let caller_ip = stack_memory.read_u64(address_of_ip as u64)?;
+        if instruction_seems_valid(caller_ip, modules, symbol_provider, options) {
             // ip is pushed by CALL, so sp is just address_of_ip + ptr
             let caller_sp = address_of_ip.checked_add(POINTER_WIDTH)?;
 
@@ -282,16 +483,13 @@ where
                 let address_of_bp = address_of_ip - POINTER_WIDTH;
                 // Can assume this resolves because we already walked over it when
                 // checking address_of_ip values.
-                let bp = stack_memory.get_memory_at_address(address_of_bp as u64)?;
+                let bp = stack_memory.read_u64(address_of_bp as u64)?;
                 if last_bp == address_of_bp
                     && bp > address_of_ip
                     && bp - address_of_bp <= MAX_REASONABLE_GAP_BETWEEN_FRAMES
                 {
                     // Final sanity check that resulting bp is still inside stack memory.
-                    if stack_memory
-                        .get_memory_at_address::<Pointer>(bp as u64)
-                        .is_some()
-                    {
+                    if stack_memory.read_u64(bp as u64).is_some() {
                         caller_bp = Some(bp);
                     }
                 } else if last_bp >= caller_sp {
@@ -321,6 +519,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Amd64(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -356,6 +555,7 @@ fn instruction_seems_valid<P>(
     instruction: Pointer,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> bool
 where
     P: SymbolProvider,
@@ -364,7 +564,7 @@ where
         return false;
     }
 
-    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider)
+    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider, options)
 }
 
 fn stack_seems_valid(
@@ -378,9 +578,7 @@ fn stack_seems_valid(
     }
 
     // The stack pointer should be in the stack
-    stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
-        .is_some()
+    stack_memory.read_u64(caller_sp as u64).is_some()
 }
 
 fn is_non_canonical(ptr: Pointer) -> bool {
@@ -406,7 +604,9 @@ impl Unwind for CONTEXT_AMD64 {
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<&MinidumpMemory>,
         modules: &MinidumpModuleList,
+        function_tables: Option<&FunctionTables>,
         syms: &P,
+        options: &ProcessorOptions,
     ) -> Option<StackFrame>
     where
         P: SymbolProvider,
@@ -415,8 +615,12 @@ impl Unwind for CONTEXT_AMD64 {
             .as_ref()
             .and_then(|stack| {
                 get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms)
+                    .or_else(|| {
+                        let function_tables = function_tables?;
+                        get_caller_by_function_table(self, callee, stack, function_tables)
+                    })
                     .or_else(|| get_caller_by_frame_pointer(self, callee, stack, modules, syms))
-                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms))
+                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms, options))
             })
             .and_then(|mut frame| {
                 // We now check the frame to see if it looks like unwinding is complete,
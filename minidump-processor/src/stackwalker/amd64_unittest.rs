@@ -3,7 +3,7 @@
 
 use crate::process_state::*;
 use crate::stackwalker::walk_stack;
-use crate::{string_symbol_supplier, Symbolizer};
+use crate::{string_symbol_supplier, ProcessorOptions, Symbolizer};
 use minidump::format::CONTEXT_AMD64;
 use minidump::*;
 use std::collections::HashMap;
@@ -33,6 +33,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Amd64(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -42,13 +43,16 @@ impl TestFixture {
             base_address: base,
             size,
             bytes: &stack,
+            endian: scroll::LE,
         };
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         walk_stack(
             &Some(&context),
             Some(&stack_memory),
             &self.modules,
+            None,
             &symbolizer,
+            &ProcessorOptions::default(),
         )
     }
 
@@ -391,11 +395,9 @@ fn check_cfi(
             let frame = &s.frames[1];
             let valid = &frame.context.valid;
             assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
-            if let MinidumpContextValidity::Some(ref which) = valid {
-                assert_eq!(which.len(), expected_regs.len());
-            } else {
-                unreachable!();
-            }
+            // Every expected register was recovered, and nothing else was.
+            assert_eq!(valid.intersect(&expected_valid), expected_valid);
+            assert_eq!(valid.union(&expected_valid), expected_valid);
 
             if let MinidumpRawContext::Amd64(ctx) = &frame.context.raw {
                 for reg in expected_regs {
@@ -7,7 +7,7 @@
 use crate::process_state::{FrameTrust, StackFrame};
 use crate::stackwalker::unwind::Unwind;
 use crate::stackwalker::CfiStackWalker;
-use crate::SymbolProvider;
+use crate::{FunctionTables, ProcessorOptions, SymbolProvider};
 use log::trace;
 use minidump::{
     CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpMemory, MinidumpModuleList,
@@ -26,6 +26,16 @@ const PROGRAM_COUNTER: &str = Registers::ProgramCounter.name();
 const LINK_REGISTER: &str = Registers::LinkRegister.name();
 const CALLEE_SAVED_REGS: &[&str] = &["r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11"];
 
+/// Return addresses into Thumb code have their lowest bit set (this is how
+/// `bx`/`blx` know to switch instruction sets). It isn't actually part of the
+/// address, so it must be stripped before looking anything up, but we leave it
+/// in the pc we recover so that the mode of the frame isn't forgotten.
+const THUMB_BIT: Pointer = 1;
+
+fn strip_thumb_bit(pc: Pointer) -> Pointer {
+    pc & !THUMB_BIT
+}
+
 fn get_caller_by_cfi<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
@@ -77,6 +87,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -133,8 +144,8 @@ where
         // drowning the rest of the code in checked_add.
         return None;
     }
-    let caller_fp = stack_memory.get_memory_at_address(last_fp as u64)?;
-    let caller_lr = stack_memory.get_memory_at_address(last_fp as u64 + POINTER_WIDTH as u64)?;
+    let caller_fp = stack_memory.read_u32(last_fp as u64)?;
+    let caller_lr = stack_memory.read_u32(last_fp as u64 + POINTER_WIDTH as u64)?;
     let caller_pc = last_lr;
 
     // TODO: why does breakpad do this? how can fp be null by here?
@@ -167,6 +178,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -177,6 +189,7 @@ fn get_caller_by_scan<P>(
     stack_memory: &MinidumpMemory,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider,
@@ -205,8 +218,13 @@ where
 
     for i in 0..scan_range {
         let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
-        let caller_pc = stack_memory.get_memory_at_address(address_of_pc as u64)?;
-        if instruction_seems_valid(caller_pc, modules, symbol_provider) {
+        let caller_pc = stack_memory.read_u32(address_of_pc as u64)?;
+        if instruction_seems_valid(
+            strip_thumb_bit(caller_pc),
+            modules,
+            symbol_provider,
+            options,
+        ) {
             // pc is pushed by CALL, so sp is just address_of_pc + ptr
             let caller_sp = address_of_pc.checked_add(POINTER_WIDTH)?;
 
@@ -230,6 +248,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Arm(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -266,11 +285,12 @@ fn instruction_seems_valid<P>(
     instruction: Pointer,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> bool
 where
     P: SymbolProvider,
 {
-    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider)
+    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider, options)
 }
 
 /*
@@ -288,7 +308,7 @@ fn stack_seems_valid(
 
     // The stack pointer should be in the stack
     stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
+        .read_u32(caller_sp as u64)
         .is_some()
 }
 */
@@ -300,7 +320,9 @@ impl Unwind for ArmContext {
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<&MinidumpMemory>,
         modules: &MinidumpModuleList,
+        _function_tables: Option<&FunctionTables>,
         syms: &P,
+        options: &ProcessorOptions,
     ) -> Option<StackFrame>
     where
         P: SymbolProvider,
@@ -310,7 +332,7 @@ impl Unwind for ArmContext {
             .and_then(|stack| {
                 get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms)
                     .or_else(|| get_caller_by_frame_pointer(self, callee, stack, modules, syms))
-                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms))
+                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms, options))
             })
             .and_then(|mut frame| {
                 // We now check the frame to see if it looks like unwinding is complete,
@@ -348,8 +370,11 @@ impl Unwind for ArmContext {
                 // the value to 2 less than that, so it points to the CALL instruction
                 // (arm instructions are all 2 bytes wide). This is important because
                 // we use this value to lookup the CFI we need to unwind the next frame.
-                let ip = frame.context.get_instruction_pointer() as u64;
-                frame.instruction = ip - 2;
+                //
+                // If the return address is into Thumb code, strip the Thumb bit first
+                // so that we don't end up pointing at an odd address.
+                let ip = frame.context.get_instruction_pointer() as Pointer;
+                frame.instruction = (strip_thumb_bit(ip) - 2) as u64;
 
                 Some(frame)
             })
@@ -7,7 +7,7 @@
 use crate::process_state::{FrameTrust, StackFrame};
 use crate::stackwalker::unwind::Unwind;
 use crate::stackwalker::CfiStackWalker;
-use crate::SymbolProvider;
+use crate::{FunctionTables, ProcessorOptions, SymbolProvider};
 use log::trace;
 use minidump::{
     CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpMemory, MinidumpModuleList,
@@ -34,6 +34,7 @@ fn get_caller_by_cfi<P>(
     grand_callee: Option<&StackFrame>,
     stack_memory: &MinidumpMemory,
     modules: &MinidumpModuleList,
+    pac_mask: Option<Pointer>,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -64,7 +65,20 @@ where
 
     symbol_provider.walk_frame(module, &mut stack_walker)?;
 
+    // The return address was restored from wherever the callee saved it, so it
+    // may be signed, just like the ones frame pointers recover.
     let caller_pc = stack_walker.caller_ctx.get_register_always(PROGRAM_COUNTER);
+    let caller_pc = ptr_auth_strip(modules, pac_mask, caller_pc);
+    stack_walker
+        .caller_ctx
+        .set_register(PROGRAM_COUNTER, caller_pc);
+    if stack_walker.caller_validity.contains(LINK_REGISTER) {
+        let caller_lr = stack_walker.caller_ctx.get_register_always(LINK_REGISTER);
+        let caller_lr = ptr_auth_strip(modules, pac_mask, caller_lr);
+        stack_walker
+            .caller_ctx
+            .set_register(LINK_REGISTER, caller_lr);
+    }
     let caller_sp = stack_walker.caller_ctx.get_register_always(STACK_POINTER);
 
     trace!(
@@ -85,6 +99,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm64(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -106,6 +121,7 @@ fn get_caller_by_frame_pointer<P>(
     grand_callee: Option<&StackFrame>,
     stack_memory: &MinidumpMemory,
     modules: &MinidumpModuleList,
+    pac_mask: Option<Pointer>,
     _symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -134,10 +150,17 @@ where
     let last_fp = ctx.get_register(FRAME_POINTER, valid)?;
     let last_sp = ctx.get_register(STACK_POINTER, valid)?;
     let last_lr = match ctx.get_register(LINK_REGISTER, valid) {
-        Some(lr) => ptr_auth_strip(modules, lr),
+        Some(lr) => ptr_auth_strip(modules, pac_mask, lr),
         None => {
             // FIXME: it would be good to write this back to the callee's ctx/validity
-            get_link_register_by_frame_pointer(ctx, valid, stack_memory, grand_callee, modules)?
+            get_link_register_by_frame_pointer(
+                ctx,
+                valid,
+                stack_memory,
+                grand_callee,
+                modules,
+                pac_mask,
+            )?
         }
     };
 
@@ -147,9 +170,9 @@ where
         // drowning the rest of the code in checked_add.
         return None;
     }
-    let caller_fp = stack_memory.get_memory_at_address(last_fp as u64)?;
-    let caller_lr = stack_memory.get_memory_at_address(last_fp + POINTER_WIDTH as u64)?;
-    let caller_lr = ptr_auth_strip(modules, caller_lr);
+    let caller_fp = stack_memory.read_u64(last_fp as u64)?;
+    let caller_lr = stack_memory.read_u64(last_fp + POINTER_WIDTH as u64)?;
+    let caller_lr = ptr_auth_strip(modules, pac_mask, caller_lr);
     let caller_pc = last_lr;
 
     // TODO: why does breakpad do this? How could we get this far with a null fp?
@@ -194,6 +217,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm64(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -205,6 +229,7 @@ fn get_link_register_by_frame_pointer(
     stack_memory: &MinidumpMemory,
     grand_callee: Option<&StackFrame>,
     modules: &MinidumpModuleList,
+    pac_mask: Option<Pointer>,
 ) -> Option<Pointer> {
     // It may happen that whatever unwinding strategy we're using managed to
     // restore %fp but didn't restore %lr. Frame-pointer-based unwinding requires
@@ -221,7 +246,7 @@ fn get_link_register_by_frame_pointer(
     } else {
         return None;
     };
-    let presumed_last_fp: Pointer = stack_memory.get_memory_at_address(last_last_fp as u64)?;
+    let presumed_last_fp: Pointer = stack_memory.read_u64(last_last_fp as u64)?;
 
     // Make sure fp and sp aren't obviously garbage (are well-ordered)
     let last_fp = ctx.get_register(FRAME_POINTER, valid)?;
@@ -237,19 +262,29 @@ fn get_link_register_by_frame_pointer(
 
     // Now that we're pretty confident that frame pointers are valid, restore
     // the callee's %lr, which should be right next to where its %fp is saved.
-    let last_lr = stack_memory.get_memory_at_address(last_last_fp + POINTER_WIDTH)?;
+    let last_lr = stack_memory.read_u64(last_last_fp + POINTER_WIDTH)?;
 
-    Some(ptr_auth_strip(modules, last_lr))
+    Some(ptr_auth_strip(modules, pac_mask, last_lr))
 }
 
-fn ptr_auth_strip(modules: &MinidumpModuleList, ptr: Pointer) -> Pointer {
+fn ptr_auth_strip(
+    modules: &MinidumpModuleList,
+    pac_mask: Option<Pointer>,
+    ptr: Pointer,
+) -> Pointer {
     // ARMv8.3 introduced a code hardening system called "Pointer Authentication"
     // which is used on Apple platforms. It adds some extra high bits to the
     // several pointers when they get pushed to memory. Interestingly
     // this doesn't seem to affect return addresses pushed by a function call,
     // but it does affect lr/fp registers that get pushed to the stack.
     //
-    // Rather than actually thinking about how to recover the key and properly
+    // If the user told us exactly which bits hold the authentication code
+    // (ProcessorOptions::arm64_pac_mask), just clear those.
+    if let Some(pac_mask) = pac_mask {
+        return ptr & !pac_mask;
+    }
+
+    // Otherwise, rather than actually thinking about how to recover the key and properly
     // decode this, let's apply a simple heuristic. We get the maximum address
     // that's contained in a module we know about, which will have some highest
     // bit that is set. We can then safely mask out any bit that's higher than
@@ -287,7 +322,9 @@ fn get_caller_by_scan<P>(
     callee: &StackFrame,
     stack_memory: &MinidumpMemory,
     modules: &MinidumpModuleList,
+    pac_mask: Option<Pointer>,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider,
@@ -316,8 +353,10 @@ where
 
     for i in 0..scan_range {
         let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
-        let caller_pc = stack_memory.get_memory_at_address(address_of_pc as u64)?;
-        if instruction_seems_valid(caller_pc, modules, symbol_provider) {
+        // Saved return addresses may be signed, which would make them look invalid.
+        let caller_pc = stack_memory.read_u64(address_of_pc as u64)?;
+        let caller_pc = ptr_auth_strip(modules, pac_mask, caller_pc);
+        if instruction_seems_valid(caller_pc, modules, symbol_provider, options) {
             // pc is pushed by CALL, so sp is just address_of_pc + ptr
             let caller_sp = address_of_pc.checked_add(POINTER_WIDTH)?;
 
@@ -341,6 +380,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Arm64(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -376,6 +416,7 @@ fn instruction_seems_valid<P>(
     instruction: Pointer,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> bool
 where
     P: SymbolProvider,
@@ -384,7 +425,7 @@ where
         return false;
     }
 
-    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider)
+    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider, options)
 }
 
 fn is_non_canonical(instruction: Pointer) -> bool {
@@ -407,7 +448,7 @@ fn stack_seems_valid(
 
     // The stack pointer should be in the stack
     stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
+        .read_u64(caller_sp as u64)
         .is_some()
 }
 */
@@ -419,15 +460,18 @@ impl Unwind for ArmContext {
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<&MinidumpMemory>,
         modules: &MinidumpModuleList,
+        _function_tables: Option<&FunctionTables>,
         syms: &P,
+        options: &ProcessorOptions,
     ) -> Option<StackFrame>
     where
         P: SymbolProvider,
     {
+        let pac_mask = options.arm64_pac_mask;
         stack_memory
             .as_ref()
             .and_then(|stack| {
-                get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms)
+                get_caller_by_cfi(self, callee, grand_callee, stack, modules, pac_mask, syms)
                     .or_else(|| {
                         get_caller_by_frame_pointer(
                             self,
@@ -435,10 +479,13 @@ impl Unwind for ArmContext {
                             grand_callee,
                             stack,
                             modules,
+                            pac_mask,
                             syms,
                         )
                     })
-                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms))
+                    .or_else(|| {
+                        get_caller_by_scan(self, callee, stack, modules, pac_mask, syms, options)
+                    })
             })
             .and_then(|mut frame| {
                 // We now check the frame to see if it looks like unwinding is complete,
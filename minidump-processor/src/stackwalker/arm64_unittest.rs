@@ -6,7 +6,7 @@
 
 use crate::process_state::*;
 use crate::stackwalker::walk_stack;
-use crate::{string_symbol_supplier, Symbolizer};
+use crate::{string_symbol_supplier, ProcessorOptions, Symbolizer};
 use minidump::*;
 use std::collections::HashMap;
 use test_assembler::*;
@@ -17,6 +17,7 @@ struct TestFixture {
     pub raw: Context,
     pub modules: MinidumpModuleList,
     pub symbols: HashMap<String, String>,
+    pub options: ProcessorOptions<'static>,
 }
 
 impl TestFixture {
@@ -30,6 +31,7 @@ impl TestFixture {
                 MinidumpModule::new(0x50000000, 0x10000, "module2"),
             ]),
             symbols: HashMap::new(),
+            options: ProcessorOptions::default(),
         }
     }
 
@@ -37,6 +39,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Arm64(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -46,13 +49,16 @@ impl TestFixture {
             base_address: base,
             size,
             bytes: &stack,
+            endian: scroll::LE,
         };
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         walk_stack(
             &Some(&context),
             Some(&stack_memory),
             &self.modules,
+            None,
             &symbolizer,
+            &self.options,
         )
     }
 
@@ -517,8 +523,150 @@ fn test_ptr_auth_strip() {
     }
 }
 
+#[test]
+fn test_ptr_auth_strip_with_mask() {
+    // Same as test_ptr_auth_strip, but this time the return addresses don't
+    // point into any known module, so the module-based heuristic can't be
+    // used. Instead we're told exactly which bits hold the 16-bit PAC.
+    let mut f = TestFixture::new();
+    f.options.arm64_pac_mask = Some(0xffff_0000_0000_0000);
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x00007fff00000100u64;
+    let return_address2 = 0x00007fff00000900u64;
+    let authenticated_return_address1 = return_address1 | 0x5a3c000000000000u64;
+    let authenticated_return_address2 = return_address2 | 0xc3a5000000000000u64;
+
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+    let frame1_fp = Label::new();
+    let frame2_fp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // space
+        .D64(0x0000000D) // junk that's not
+        .D64(0xF0000000) // a return address
+        .mark(&frame1_fp) // next fp will point to the next value
+        .D64(&frame2_fp) // save current frame pointer
+        .D64(authenticated_return_address2) // save current link register
+        .mark(&frame1_sp)
+        // frame 1
+        .append_repeated(0, 64) // space
+        .D64(0x0000000D) // junk that's not
+        .D64(0xF0000000) // a return address
+        .mark(&frame2_fp)
+        .D64(0)
+        .D64(0)
+        .mark(&frame2_sp)
+        // frame 2
+        .append_repeated(0, 64) // Whatever values on the stack.
+        .D64(0x0000000D) // junk that's not
+        .D64(0xF0000000); // a return address.
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", authenticated_return_address1);
+    f.raw.set_register("fp", frame1_fp.value().unwrap());
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 1
+        let frame = &s.frames[1];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::FramePointer);
+        assert_eq!(frame.instruction, return_address1 - 4);
+
+        if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+            assert_eq!(ctx.get_register("lr", valid).unwrap(), return_address2);
+        } else {
+            unreachable!();
+        }
+    }
+
+    {
+        // Frame 2
+        let frame = &s.frames[2];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::FramePointer);
+        assert_eq!(frame.instruction, return_address2 - 4);
+
+        if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address2);
+            assert_eq!(
+                ctx.get_register("sp", valid).unwrap(),
+                frame2_sp.value().unwrap()
+            );
+        } else {
+            unreachable!();
+        }
+    }
+
+    // Return addresses that CFI restores from the stack are stripped too.
+    let mut f = TestFixture::new();
+    f.options.arm64_pac_mask = Some(0xffff_0000_0000_0000);
+    f.add_symbols(
+        String::from("module1"),
+        String::from("STACK CFI INIT 4000 100 .cfa: sp 16 + .ra: .cfa -8 + ^\n"),
+    );
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    let frame1_sp = Label::new();
+    stack = stack
+        .D64(0) // space
+        .D64(authenticated_return_address1) // saved return address
+        .mark(&frame1_sp)
+        .append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40004010);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+    {
+        // Frame 1
+        let frame = &s.frames[1];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+        assert_eq!(frame.instruction, return_address1 - 4);
+
+        if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+            assert_eq!(
+                ctx.get_register("sp", valid).unwrap(),
+                frame1_sp.value().unwrap()
+            );
+        } else {
+            unreachable!();
+        }
+    }
+
+    // And so are the ones found by scanning, which would otherwise be rejected.
+    let mut f = TestFixture::new();
+    f.options.arm64_pac_mask = Some(0xffff_0000_0000_0000);
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    let module_return_address = 0x50000100u64;
+    stack = stack
+        .append_repeated(0, 16) // space
+        .D64(module_return_address | 0x5a3c000000000000u64) // signed return address
+        .append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+    assert_eq!(s.frames[1].trust, FrameTrust::Scan);
+    assert_eq!(s.frames[1].instruction, module_return_address - 4);
+}
+
 const CALLEE_SAVE_REGS: &[&str] = &[
-    "pc", "sp", "fp", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27", "x28",
+    "pc", "sp", "x29", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27", "x28",
 ];
 
 fn init_cfi_state() -> (TestFixture, Section, Context, MinidumpContextValidity) {
@@ -612,11 +760,9 @@ fn check_cfi(
             let frame = &s.frames[1];
             let valid = &frame.context.valid;
             assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
-            if let MinidumpContextValidity::Some(ref which) = valid {
-                assert_eq!(which.len(), expected_regs.len());
-            } else {
-                unreachable!();
-            }
+            // Every expected register was recovered, and nothing else was.
+            assert_eq!(valid.intersect(&expected_valid), expected_valid);
+            assert_eq!(valid.union(&expected_valid), expected_valid);
 
             if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
                 for reg in expected_regs {
@@ -3,7 +3,7 @@
 
 use crate::process_state::*;
 use crate::stackwalker::walk_stack;
-use crate::{string_symbol_supplier, Symbolizer};
+use crate::{string_symbol_supplier, ProcessorOptions, Symbolizer};
 use minidump::format::CONTEXT_ARM;
 use minidump::*;
 use std::collections::HashMap;
@@ -33,6 +33,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Arm(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -42,13 +43,16 @@ impl TestFixture {
             base_address: base,
             size,
             bytes: &stack,
+            endian: scroll::LE,
         };
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         walk_stack(
             &Some(&context),
             Some(&stack_memory),
             &self.modules,
+            None,
             &symbolizer,
+            &ProcessorOptions::default(),
         )
     }
 
@@ -514,11 +518,9 @@ fn check_cfi(
             let frame = &s.frames[1];
             let valid = &frame.context.valid;
             assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
-            if let MinidumpContextValidity::Some(ref which) = valid {
-                assert_eq!(which.len(), expected_regs.len());
-            } else {
-                unreachable!();
-            }
+            // Every expected register was recovered, and nothing else was.
+            assert_eq!(valid.intersect(&expected_valid), expected_valid);
+            assert_eq!(valid.union(&expected_valid), expected_valid);
 
             if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
                 for reg in expected_regs {
@@ -739,3 +741,128 @@ fn test_cfi_reject_bad_exprs() {
     let s = f.walk_stack(stack);
     assert_eq!(s.frames.len(), 1);
 }
+
+#[test]
+fn test_thumb_cfi() {
+    // A pure Thumb-2 call chain: every return address has the Thumb bit set.
+    let mut f = TestFixture::new();
+    let symbols = [
+        "FUNC 4000 1000 10 thumb_leaf\n",
+        "STACK CFI INIT 4000 1000 .cfa: sp .ra: lr\n",
+        "FUNC 5000 1000 10 thumb_middle\n",
+        "STACK CFI INIT 5000 1000 .cfa: sp 8 + .ra: .cfa 4 - ^\n",
+        "FUNC 6000 1000 10 thumb_root\n",
+        "STACK CFI INIT 6000 1000 .cfa: 0 .ra: 0\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let return_address1 = 0x40005013u32;
+    let return_address2 = 0x40006021u32;
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    stack = stack
+        // frame 1
+        .D32(0xb5d55e68) // saved r4
+        .D32(return_address2) // saved lr
+        // frame 2
+        .append_repeated(0, 16);
+
+    // The context frame is also in Thumb mode, which is only indicated by cpsr.
+    f.raw.cpsr = 0x60000030;
+    f.raw.set_register("pc", 0x40004100);
+    f.raw.set_register("lr", return_address1);
+    f.raw.set_register("sp", 0x80000000);
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.instruction, 0x40004100);
+        assert_eq!(frame.function_name.as_deref(), Some("thumb_leaf"));
+    }
+
+    {
+        // Frame 1
+        let frame = &s.frames[1];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+        // The lookup address has the Thumb bit stripped and points into the call...
+        assert_eq!(frame.instruction, 0x40005010);
+        assert_eq!(frame.function_name.as_deref(), Some("thumb_middle"));
+        assert_eq!(frame.function_base, Some(0x40005000));
+        // ...but the recovered pc still has it.
+        if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+            assert_eq!(ctx.get_register("sp", valid).unwrap(), 0x80000000);
+        } else {
+            unreachable!();
+        }
+    }
+
+    {
+        // Frame 2
+        let frame = &s.frames[2];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+        assert_eq!(frame.instruction, 0x4000601e);
+        assert_eq!(frame.function_name.as_deref(), Some("thumb_root"));
+        if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address2);
+            assert_eq!(ctx.get_register("sp", valid).unwrap(), 0x80000008);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+#[test]
+fn test_thumb_scan() {
+    // Scanning should find Thumb return addresses, and point the caller's
+    // instruction at the call *before* the (stripped) return address.
+    let mut f = TestFixture::new();
+    let symbols = [
+        "FUNC 5000 100 10 thumb_caller\n",
+        "FUNC 5100 100 10 thumb_other\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let return_address = 0x40005101u32;
+    let frame1_sp = Label::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // space
+        .D32(0x40090000) // junk that's not
+        .D32(0x60000000) // a return address
+        .D32(return_address) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 16);
+
+    f.raw.cpsr = 0x60000030;
+    f.raw.set_register("pc", 0x40004100);
+    f.raw
+        .set_register("sp", stack.start().value().unwrap() as u32);
+
+    let s = f.walk_stack(stack);
+    assert!(s.frames.len() >= 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::Scan);
+    assert_eq!(frame.instruction, 0x400050fe);
+    assert_eq!(frame.function_name.as_deref(), Some("thumb_caller"));
+    if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address);
+        assert_eq!(
+            ctx.get_register("sp", valid).unwrap(),
+            frame1_sp.value().unwrap() as u32
+        );
+    } else {
+        unreachable!();
+    }
+}
@@ -7,11 +7,13 @@ mod amd64;
 mod arm;
 mod arm64;
 mod arm64_old;
+mod ppc;
+mod ppc64;
 mod unwind;
 mod x86;
 
 use crate::process_state::*;
-use crate::{FrameWalker, SymbolProvider};
+use crate::{FrameWalker, ProcessorOptions, SymbolProvider};
 use log::trace;
 use minidump::*;
 use scroll::ctx::{SizeWith, TryFromCtx};
@@ -20,6 +22,31 @@ use self::unwind::Unwind;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 
+/// Unwind tables for code that isn't part of any module, such as JIT code.
+///
+/// Windows x64 programs register these with `RtlAddFunctionTable`, and minidumps
+/// record them in the [`MinidumpFunctionTableList`][]. The unwind info they point
+/// to can only be used if the minidump happens to include that memory.
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionTables<'a> {
+    /// The function tables from the minidump.
+    pub tables: &'a MinidumpFunctionTableList,
+    /// The minidump's memory, for reading unwind info.
+    pub memory: &'a UnifiedMemoryList<'a>,
+}
+
+impl<'a> FunctionTables<'a> {
+    /// Read a `T` from the minidump's memory at `address`.
+    fn read<T>(&self, address: u64) -> Option<T>
+    where
+        T: TryFromCtx<'a, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
+    {
+        self.memory
+            .memory_at_address(address)?
+            .get_memory_at_address(address)
+    }
+}
+
 struct CfiStackWalker<'a, C: CpuContext> {
     instruction: u64,
     grand_callee_parameter_size: u32,
@@ -85,15 +112,15 @@ fn get_caller_frame<P>(
     grand_callee_frame: Option<&StackFrame>,
     stack_memory: Option<&MinidumpMemory>,
     modules: &MinidumpModuleList,
+    function_tables: Option<&FunctionTables>,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider,
 {
     match callee_frame.context.raw {
         /*
-        MinidumpRawContext::PPC(ctx) => ctx.get_caller_frame(stack_memory),
-        MinidumpRawContext::PPC64(ctx) => ctx.get_caller_frame(stack_memory),
         MinidumpRawContext::SPARC(ctx) => ctx.get_caller_frame(stack_memory),
         MinidumpRawContext::MIPS(ctx) => ctx.get_caller_frame(stack_memory),
          */
@@ -102,35 +129,63 @@ where
             grand_callee_frame,
             stack_memory,
             modules,
+            function_tables,
             symbol_provider,
+            options,
         ),
         MinidumpRawContext::Arm64(ref ctx) => ctx.get_caller_frame(
             callee_frame,
             grand_callee_frame,
             stack_memory,
             modules,
+            function_tables,
             symbol_provider,
+            options,
         ),
         MinidumpRawContext::OldArm64(ref ctx) => ctx.get_caller_frame(
             callee_frame,
             grand_callee_frame,
             stack_memory,
             modules,
+            function_tables,
             symbol_provider,
+            options,
+        ),
+        MinidumpRawContext::Ppc(ref ctx) => ctx.get_caller_frame(
+            callee_frame,
+            grand_callee_frame,
+            stack_memory,
+            modules,
+            function_tables,
+            symbol_provider,
+            options,
+        ),
+        MinidumpRawContext::Ppc64(ref ctx) => ctx.get_caller_frame(
+            callee_frame,
+            grand_callee_frame,
+            stack_memory,
+            modules,
+            function_tables,
+            symbol_provider,
+            options,
         ),
         MinidumpRawContext::Amd64(ref ctx) => ctx.get_caller_frame(
             callee_frame,
             grand_callee_frame,
             stack_memory,
             modules,
+            function_tables,
             symbol_provider,
+            options,
         ),
         MinidumpRawContext::X86(ref ctx) => ctx.get_caller_frame(
             callee_frame,
             grand_callee_frame,
             stack_memory,
             modules,
+            function_tables,
             symbol_provider,
+            options,
         ),
         _ => None,
     }
@@ -149,8 +204,10 @@ fn fill_source_line_info<P>(
         // the same lifetime as the module list that's passed in.
         frame.module = Some(module.clone());
 
-        // This is best effort, so ignore any errors.
-        let _ = symbol_provider.fill_symbol(module, frame);
+        // This is best effort, so just note why it failed.
+        if symbol_provider.fill_symbol(module, frame).is_err() {
+            frame.symbol_error = symbol_provider.symbol_error(module);
+        }
     }
 }
 
@@ -158,7 +215,9 @@ pub fn walk_stack<P>(
     maybe_context: &Option<&MinidumpContext>,
     stack_memory: Option<&MinidumpMemory>,
     modules: &MinidumpModuleList,
+    function_tables: Option<&FunctionTables>,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> CallStack
 where
     P: SymbolProvider,
@@ -188,7 +247,9 @@ where
                 grand_callee_frame,
                 stack_memory,
                 modules,
+                function_tables,
                 symbol_provider,
+                options,
             );
         }
         trace!("unwind: finished stack unwind\n");
@@ -200,6 +261,9 @@ where
         info,
         thread_name: None,
         last_error_value: None,
+        start_routine: None,
+        kernel_time: None,
+        user_time: None,
     }
 }
 
@@ -209,6 +273,7 @@ fn instruction_seems_valid_by_symbols<P>(
     instruction: u64,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> bool
 where
     P: SymbolProvider,
@@ -251,7 +316,8 @@ where
             true
         }
     } else {
-        // We couldn't even map this address to a module. Reject the pointer
+        // We couldn't even map this address to a module, so there are no
+        // symbols or CFI to check it with. By default reject the pointer
         // so that we have *some* way to distinguish "normal" pointers
         // from instruction address.
         //
@@ -259,7 +325,7 @@ where
         // isn't part of a normal well-defined module. We can potentially use
         // MemoryInfoListStream (windows) and /proc/self/maps (linux) to refine
         // this analysis and allow scans to walk through JITed code.
-        false
+        !options.cfi_only_for_known_modules
     }
 }
 
@@ -270,4 +336,6 @@ mod arm64_unittest;
 #[cfg(test)]
 mod arm_unittest;
 #[cfg(test)]
+mod ppc_unittest;
+#[cfg(test)]
 mod x86_unittest;
@@ -0,0 +1,276 @@
+// NOTE: ppc.rs and ppc64.rs should be identical except for the names of
+// their context types, their pointer widths, and the offset of the saved
+// link register.
+
+use crate::process_state::{FrameTrust, StackFrame};
+use crate::stackwalker::unwind::Unwind;
+use crate::{FunctionTables, ProcessorOptions, SymbolProvider};
+use log::trace;
+use minidump::{
+    CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpMemory, MinidumpModuleList,
+    MinidumpRawContext,
+};
+use std::collections::HashSet;
+
+type PpcContext = minidump::format::CONTEXT_PPC;
+type Pointer = <PpcContext as CpuContext>::Register;
+type Registers = minidump::format::PpcRegisterNumbers;
+
+const POINTER_WIDTH: Pointer = std::mem::size_of::<Pointer>() as Pointer;
+const STACK_POINTER: &str = Registers::StackPointer.name();
+const PROGRAM_COUNTER: &str = "srr0";
+const LINK_REGISTER: &str = "lr";
+/// Where a function saves its return address, relative to its caller's stack pointer.
+const LINK_REGISTER_SAVE_OFFSET: Pointer = 8;
+
+fn get_caller_by_back_chain<P>(
+    ctx: &PpcContext,
+    callee: &StackFrame,
+    stack_memory: &MinidumpMemory,
+    _modules: &MinidumpModuleList,
+    _symbol_provider: &P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider,
+{
+    trace!("unwind: trying back chain");
+    // PowerPC has no dedicated frame pointer, because the stack pointer (r1)
+    // already does that job. When a function allocates its frame, it stores
+    // its caller's r1 at the bottom of it (the "back chain"), and it saves
+    // its return address (which the call left in lr) in the linkage area of
+    // its *caller's* frame.
+    //
+    // So to restore the caller's registers, we have:
+    //
+    // sp := *sp
+    // pc := *(sp + LINK_REGISTER_SAVE_OFFSET)
+    let valid = &callee.context.valid;
+    let last_sp = ctx.get_register(STACK_POINTER, valid)?;
+
+    let caller_sp = stack_memory.read_u32(last_sp as u64)?;
+    if caller_sp <= last_sp {
+        // The stack grows down, so this is either the end of the back chain
+        // (which is null) or garbage.
+        return None;
+    }
+    let caller_pc =
+        stack_memory.read_u32(caller_sp.checked_add(LINK_REGISTER_SAVE_OFFSET)? as u64)?;
+
+    trace!(
+        "unwind: back chain seems valid -- caller_pc: 0x{:08x}, caller_sp: 0x{:08x}",
+        caller_pc,
+        caller_sp,
+    );
+
+    let mut caller_ctx = PpcContext::default();
+    caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
+    caller_ctx.set_register(STACK_POINTER, caller_sp);
+
+    let mut valid = HashSet::new();
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Ppc(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::FramePointer))
+}
+
+fn get_caller_by_link_register<P>(
+    ctx: &PpcContext,
+    callee: &StackFrame,
+    _stack_memory: &MinidumpMemory,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+    options: &ProcessorOptions,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider,
+{
+    // A leaf function doesn't need to save lr or allocate a frame, so if we
+    // crashed in one, its return address is still in lr and the caller's frame
+    // is the one r1 points to. lr is only known for the context frame, and it's
+    // often stale, so only trust it if it points into some code.
+    if callee.trust != FrameTrust::Context {
+        return None;
+    }
+    trace!("unwind: trying link register");
+    let valid = &callee.context.valid;
+    let caller_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let caller_pc = ctx.get_register(LINK_REGISTER, valid)?;
+    if !instruction_seems_valid(caller_pc, modules, symbol_provider, options) {
+        return None;
+    }
+
+    trace!(
+        "unwind: link register seems valid -- caller_pc: 0x{:08x}, caller_sp: 0x{:08x}",
+        caller_pc,
+        caller_sp,
+    );
+
+    let mut caller_ctx = PpcContext::default();
+    caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
+    caller_ctx.set_register(STACK_POINTER, caller_sp);
+
+    let mut valid = HashSet::new();
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Ppc(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::FramePointer))
+}
+
+fn get_caller_by_scan<P>(
+    ctx: &PpcContext,
+    callee: &StackFrame,
+    stack_memory: &MinidumpMemory,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+    options: &ProcessorOptions,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider,
+{
+    trace!("unwind: trying scan");
+    // Stack scanning is just walking from the end of the frame until we encounter
+    // a value on the stack that looks like a pointer into some code (it's an address
+    // in a range covered by one of our modules). If we find such an instruction,
+    // we assume it's a saved return address, and that the caller's frame starts
+    // just after it.
+    //
+    // The start of the frame is its linkage area, where its *callees* saved their
+    // return addresses, which point back into this frame's own function. Skip it
+    // so we don't find those.
+    let valid = &callee.context.valid;
+    let last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let scan_start = last_sp.checked_add(LINK_REGISTER_SAVE_OFFSET + POINTER_WIDTH)?;
+
+    // Number of pointer-sized values to scan through in our search.
+    let default_scan_range = 40;
+    let extended_scan_range = default_scan_range * 4;
+
+    // Breakpad devs found that the first frame of an unwind can be really messed up,
+    // and therefore benefits from a longer scan. Let's do it too.
+    let scan_range = if let FrameTrust::Context = callee.trust {
+        extended_scan_range
+    } else {
+        default_scan_range
+    };
+
+    for i in 0..scan_range {
+        let address_of_pc = scan_start.checked_add(i * POINTER_WIDTH)?;
+        let caller_pc = stack_memory.read_u32(address_of_pc as u64)?;
+        if instruction_seems_valid(caller_pc, modules, symbol_provider, options) {
+            let caller_sp = address_of_pc.checked_add(POINTER_WIDTH)?;
+
+            trace!(
+                "unwind: scan seems valid -- caller_pc: 0x{:08x}, caller_sp: 0x{:08x}",
+                caller_pc,
+                caller_sp,
+            );
+
+            let mut caller_ctx = PpcContext::default();
+            caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
+            caller_ctx.set_register(STACK_POINTER, caller_sp);
+
+            let mut valid = HashSet::new();
+            valid.insert(PROGRAM_COUNTER);
+            valid.insert(STACK_POINTER);
+
+            let context = MinidumpContext {
+                raw: MinidumpRawContext::Ppc(caller_ctx),
+                valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
+            };
+            return Some(StackFrame::from_context(context, FrameTrust::Scan));
+        }
+    }
+
+    None
+}
+
+/// The most strict validation we have for instruction pointers.
+///
+/// This is only used for stack-scanning and the link register, because
+/// they're explicitly trying to distinguish between total garbage and
+/// correct values. See the version in arm.rs for why the back chain
+/// doesn't use it.
+fn instruction_seems_valid<P>(
+    instruction: Pointer,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+    options: &ProcessorOptions,
+) -> bool
+where
+    P: SymbolProvider,
+{
+    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider, options)
+}
+
+impl Unwind for PpcContext {
+    fn get_caller_frame<P>(
+        &self,
+        callee: &StackFrame,
+        _grand_callee: Option<&StackFrame>,
+        stack_memory: Option<&MinidumpMemory>,
+        modules: &MinidumpModuleList,
+        _function_tables: Option<&FunctionTables>,
+        syms: &P,
+        options: &ProcessorOptions,
+    ) -> Option<StackFrame>
+    where
+        P: SymbolProvider,
+    {
+        stack_memory
+            .as_ref()
+            .and_then(|stack| {
+                get_caller_by_back_chain(self, callee, stack, modules, syms)
+                    .or_else(|| {
+                        get_caller_by_link_register(self, callee, stack, modules, syms, options)
+                    })
+                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms, options))
+            })
+            .and_then(|mut frame| {
+                // We now check the frame to see if it looks like unwinding is complete,
+                // based on the frame we computed having a nonsense value. Returning
+                // None signals to the unwinder to stop unwinding.
+
+                // if the instruction is within the first ~page of memory, it's basically
+                // null, and we can assume unwinding is complete.
+                if frame.context.get_instruction_pointer() < 4096 {
+                    trace!("unwind: instruction pointer was nullish, assuming unwind complete");
+                    return None;
+                }
+                // If the new stack pointer is at a lower address than the old,
+                // then that's clearly incorrect. Treat this as end-of-stack to
+                // enforce progress and avoid infinite loops. The only exception
+                // is a leaf function that was unwound with the link register,
+                // which doesn't move the stack pointer at all.
+                let sp = frame.context.get_stack_pointer();
+                let last_sp = self.get_register_always(STACK_POINTER) as u64;
+                if sp <= last_sp {
+                    let is_leaf = callee.trust == FrameTrust::Context && sp == last_sp;
+                    if !is_leaf {
+                        trace!("unwind: stack pointer went backwards, assuming unwind complete");
+                        return None;
+                    }
+                }
+
+                // Ok, the frame now seems well and truly valid, do final cleanup.
+
+                // A caller's ip is the return address, which is the instruction
+                // *after* the branch that caused us to arrive at the callee. Set
+                // the value to 4 less than that, so it points to the branch
+                // (ppc instructions are all 4 bytes wide).
+                frame.instruction = frame.context.get_instruction_pointer() - 4;
+
+                Some(frame)
+            })
+    }
+}
@@ -0,0 +1,281 @@
+use crate::process_state::*;
+use crate::stackwalker::walk_stack;
+use crate::{string_symbol_supplier, ProcessorOptions, Symbolizer};
+use minidump::format::{CONTEXT_PPC, CONTEXT_PPC64};
+use minidump::*;
+use std::collections::HashMap;
+use test_assembler::{Endian, *};
+
+struct TestFixture {
+    pub raw: MinidumpRawContext,
+    pub endian: Endian,
+    pub modules: MinidumpModuleList,
+}
+
+impl TestFixture {
+    /// A 32-bit PowerPC, which is big-endian like the Macs that used them.
+    pub fn new() -> TestFixture {
+        TestFixture::with_context(MinidumpRawContext::Ppc(CONTEXT_PPC::default()), Endian::Big)
+    }
+
+    /// A little-endian 64-bit PowerPC, like most Linux ones.
+    pub fn new_ppc64() -> TestFixture {
+        TestFixture::with_context(
+            MinidumpRawContext::Ppc64(CONTEXT_PPC64::default()),
+            Endian::Little,
+        )
+    }
+
+    fn with_context(raw: MinidumpRawContext, endian: Endian) -> TestFixture {
+        TestFixture {
+            raw,
+            endian,
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+        }
+    }
+
+    pub fn set_register(&mut self, reg: &str, val: u64) {
+        match self.raw {
+            MinidumpRawContext::Ppc(ref mut ctx) => ctx.set_register(reg, val as u32),
+            MinidumpRawContext::Ppc64(ref mut ctx) => ctx.set_register(reg, val),
+            _ => unreachable!(),
+        }
+        .unwrap();
+    }
+
+    pub fn walk_stack(&self, stack: Section) -> CallStack {
+        let context = MinidumpContext {
+            raw: self.raw.clone(),
+            valid: MinidumpContextValidity::All,
+            xstate: None,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: match self.endian {
+                Endian::Big => scroll::BE,
+                Endian::Little => scroll::LE,
+            },
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(HashMap::new()));
+        walk_stack(
+            &Some(&context),
+            Some(&stack_memory),
+            &self.modules,
+            None,
+            &symbolizer,
+            &ProcessorOptions::default(),
+        )
+    }
+}
+
+fn frame_registers(frame: &StackFrame) -> (u64, u64) {
+    (
+        frame.context.get_instruction_pointer(),
+        frame.context.get_stack_pointer(),
+    )
+}
+
+#[test]
+fn test_simple() {
+    let mut f = TestFixture::new();
+    let stack = Section::with_endian(f.endian);
+    stack.start().set_const(0x80000000);
+    f.set_register("srr0", 0x4000c020);
+    f.set_register("r1", 0x80000000);
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    assert_eq!(f.trust, FrameTrust::Context);
+    assert_eq!(f.instruction, 0x4000c020);
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+#[test]
+fn test_back_chain() {
+    let mut f = TestFixture::new();
+    let mut stack = Section::with_endian(f.endian);
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x40001000u32;
+    let return_address2 = 0x50002000u32;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D32(&frame1_sp) // back chain
+        .append_repeated(0, 12) // locals
+        // frame 1
+        .mark(&frame1_sp)
+        .D32(&frame2_sp) // back chain
+        .D32(0) // saved cr
+        .D32(return_address1) // saved lr (frame 0's return address)
+        .append_repeated(0, 20) // locals
+        // frame 2
+        .mark(&frame2_sp)
+        .D32(0) // end of the back chain
+        .D32(0) // saved cr
+        .D32(return_address2) // saved lr (frame 1's return address)
+        .append_repeated(0, 20);
+
+    f.set_register("srr0", 0x40000200);
+    f.set_register("r1", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 3);
+    assert_eq!(s.frames[0].trust, FrameTrust::Context);
+
+    let frame = &s.frames[1];
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    assert_eq!(
+        frame_registers(frame),
+        (return_address1 as u64, frame1_sp.value().unwrap())
+    );
+    assert_eq!(frame.instruction, return_address1 as u64 - 4);
+
+    let frame = &s.frames[2];
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    assert_eq!(
+        frame_registers(frame),
+        (return_address2 as u64, frame2_sp.value().unwrap())
+    );
+    assert_eq!(frame.module.as_ref().unwrap().code_file(), "module2");
+}
+
+#[test]
+fn test_link_register() {
+    // A leaf function that crashed before allocating a frame: its caller's
+    // frame ends the back chain, and the return address is still in lr.
+    let mut f = TestFixture::new();
+    let mut stack = Section::with_endian(f.endian);
+    stack.start().set_const(0x80000000);
+    stack = stack.D32(0).append_repeated(0, 28);
+
+    let return_address = 0x40002000;
+    f.set_register("srr0", 0x40000200);
+    f.set_register("lr", return_address);
+    f.set_register("r1", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+    let frame = &s.frames[1];
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    assert_eq!(frame_registers(frame), (return_address, 0x80000000));
+}
+
+#[test]
+fn test_scan_without_symbols() {
+    // Scanning should work without any symbols
+    let mut f = TestFixture::new();
+    let mut stack = Section::with_endian(f.endian);
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u32;
+    let return_address2 = 0x50000900u32;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D32(0) // no back chain
+        .D32(0) // saved cr
+        .D32(0x40001000) // saved lr, which points into frame 0's own function
+        .D32(0x40090000) // junk that's not
+        .D32(0x60000000) // a return address
+        .D32(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .D32(0) // no back chain
+        .D32(0) // saved cr
+        .D32(0) // saved lr
+        .D32(0xF0000000) // more junk
+        .D32(0x0000000D)
+        .D32(return_address2) // actual return address
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 32); // end of stack
+
+    f.set_register("srr0", 0x40005510);
+    f.set_register("r1", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    {
+        // Frame 1
+        let frame = &s.frames[1];
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        if let MinidumpContextValidity::Some(ref which) = frame.context.valid {
+            assert_eq!(which.len(), 2);
+        } else {
+            unreachable!();
+        }
+        assert_eq!(
+            frame_registers(frame),
+            (return_address1 as u64, frame1_sp.value().unwrap())
+        );
+    }
+
+    {
+        // Frame 2
+        let frame = &s.frames[2];
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        assert_eq!(
+            frame_registers(frame),
+            (return_address2 as u64, frame2_sp.value().unwrap())
+        );
+    }
+}
+
+#[test]
+fn test_ppc64_back_chain() {
+    let mut f = TestFixture::new_ppc64();
+    let mut stack = Section::with_endian(f.endian);
+    stack.start().set_const(0x7fff_0000_0000);
+
+    let return_address = 0x40001000u64;
+    let frame1_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(&frame1_sp) // back chain
+        .append_repeated(0, 24) // locals
+        // frame 1
+        .mark(&frame1_sp)
+        .D64(0) // end of the back chain
+        .D64(0) // saved cr
+        .D64(return_address) // saved lr
+        .append_repeated(0, 40);
+
+    f.set_register("srr0", 0x40000200);
+    f.set_register("r1", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+    let frame = &s.frames[1];
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    assert_eq!(
+        frame_registers(frame),
+        (return_address, frame1_sp.value().unwrap())
+    );
+}
@@ -2,19 +2,22 @@
 // file at the top-level directory of this distribution.
 
 use crate::process_state::StackFrame;
-use crate::SymbolProvider;
+use crate::{FunctionTables, ProcessorOptions, SymbolProvider};
 use minidump::{MinidumpMemory, MinidumpModuleList};
 
 /// A trait for things that can unwind to a caller.
 pub trait Unwind {
     /// Get the caller frame of this frame.
+    #[allow(clippy::too_many_arguments)]
     fn get_caller_frame<P>(
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<&MinidumpMemory>,
         modules: &MinidumpModuleList,
+        function_tables: Option<&FunctionTables>,
         symbol_provider: &P,
+        options: &ProcessorOptions,
     ) -> Option<StackFrame>
     where
         P: SymbolProvider;
@@ -9,7 +9,7 @@
 use crate::process_state::{FrameTrust, StackFrame};
 use crate::stackwalker::unwind::Unwind;
 use crate::stackwalker::CfiStackWalker;
-use crate::SymbolProvider;
+use crate::{FunctionTables, ProcessorOptions, SymbolProvider};
 use log::trace;
 use minidump::format::CONTEXT_X86;
 use minidump::{
@@ -98,6 +98,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::X86(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -160,8 +161,8 @@ where
         // drowning the rest of the code in checked_add.
         return None;
     }
-    let caller_ip = stack_memory.get_memory_at_address(last_bp as u64 + POINTER_WIDTH as u64)?;
-    let caller_bp = stack_memory.get_memory_at_address(last_bp as u64)?;
+    let caller_ip = stack_memory.read_u32(last_bp as u64 + POINTER_WIDTH as u64)?;
+    let caller_bp = stack_memory.read_u32(last_bp as u64)?;
     let caller_sp = last_bp + POINTER_WIDTH * 2;
 
     // NOTE: minor divergence from x64 impl here: doing extra validation on the
@@ -189,6 +190,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::X86(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -199,6 +201,7 @@ fn get_caller_by_scan<P>(
     stack_memory: &MinidumpMemory,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider,
@@ -240,8 +243,8 @@ where
 
     for i in 0..scan_range {
         let address_of_ip = last_sp.checked_add(i * POINTER_WIDTH)?;
-        let caller_ip = stack_memory.get_memory_at_address(address_of_ip as u64)?;
-        if instruction_seems_valid(caller_ip, modules, symbol_provider) {
+        let caller_ip = stack_memory.read_u32(address_of_ip as u64)?;
+        if instruction_seems_valid(caller_ip, modules, symbol_provider, options) {
             // ip is pushed by CALL, so sp is just address_of_ip + ptr
             let caller_sp = address_of_ip.checked_add(POINTER_WIDTH)?;
 
@@ -267,22 +270,16 @@ where
             const MAX_REASONABLE_GAP_BETWEEN_FRAMES: Pointer = 128 * 1024;
 
             let address_of_bp = address_of_ip - POINTER_WIDTH;
-            let bp = stack_memory.get_memory_at_address(address_of_bp as u64)?;
+            let bp = stack_memory.read_u32(address_of_bp as u64)?;
             if bp > address_of_ip && bp - address_of_bp <= MAX_REASONABLE_GAP_BETWEEN_FRAMES {
                 // Sanity check that resulting bp is still inside stack memory.
-                if stack_memory
-                    .get_memory_at_address::<Pointer>(bp as u64)
-                    .is_some()
-                {
+                if stack_memory.read_u32(bp as u64).is_some() {
                     caller_bp = Some(bp);
                 }
             } else if let Some(last_bp) = last_bp {
                 if last_bp >= caller_sp {
                     // Sanity check that resulting bp is still inside stack memory.
-                    if stack_memory
-                        .get_memory_at_address::<Pointer>(last_bp as u64)
-                        .is_some()
-                    {
+                    if stack_memory.read_u32(last_bp as u64).is_some() {
                         caller_bp = Some(last_bp);
                     }
                 }
@@ -309,6 +306,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::X86(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -344,6 +342,7 @@ fn instruction_seems_valid<P>(
     instruction: Pointer,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
+    options: &ProcessorOptions,
 ) -> bool
 where
     P: SymbolProvider,
@@ -352,7 +351,7 @@ where
         return false;
     }
 
-    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider)
+    super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider, options)
 }
 
 /*
@@ -370,7 +369,7 @@ fn stack_seems_valid(
 
     // The stack pointer should be in the stack
     stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
+        .read_u32(caller_sp as u64)
         .is_some()
 }
 */
@@ -382,7 +381,9 @@ impl Unwind for CONTEXT_X86 {
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<&MinidumpMemory>,
         modules: &MinidumpModuleList,
+        _function_tables: Option<&FunctionTables>,
         syms: &P,
+        options: &ProcessorOptions,
     ) -> Option<StackFrame>
     where
         P: SymbolProvider,
@@ -392,7 +393,7 @@ impl Unwind for CONTEXT_X86 {
             .and_then(|stack| {
                 get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms)
                     .or_else(|| get_caller_by_frame_pointer(self, callee, stack, modules, syms))
-                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms))
+                    .or_else(|| get_caller_by_scan(self, callee, stack, modules, syms, options))
             })
             .and_then(|mut frame| {
                 // We now check the frame to see if it looks like unwinding is complete,
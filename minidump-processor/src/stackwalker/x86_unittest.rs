@@ -3,7 +3,7 @@
 
 use crate::process_state::*;
 use crate::stackwalker::walk_stack;
-use crate::{string_symbol_supplier, Symbolizer};
+use crate::{string_symbol_supplier, ProcessorOptions, Symbolizer};
 use minidump::format::CONTEXT_X86;
 use minidump::*;
 use std::collections::HashMap;
@@ -33,6 +33,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::X86(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -42,13 +43,16 @@ impl TestFixture {
             base_address: base,
             size,
             bytes: &stack,
+            endian: scroll::LE,
         };
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         walk_stack(
             &Some(&context),
             Some(&stack_memory),
             &self.modules,
+            None,
             &symbolizer,
+            &ProcessorOptions::default(),
         )
     }
 
@@ -186,6 +190,104 @@ fn test_traditional_scan() {
     }
 }
 
+// Addresses that aren't in any module are never looked up in symbol files,
+// even when the stack is full of them. By default they're not used as return
+// addresses either.
+#[test]
+fn test_scan_only_queries_known_modules() {
+    use crate::{FillSymbolError, FrameSymbolizer, FrameWalker, SymbolProvider, SymbolStats};
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct RecordingProvider {
+        queries: RefCell<Vec<u64>>,
+    }
+    impl SymbolProvider for RecordingProvider {
+        fn fill_symbol(
+            &self,
+            _module: &dyn Module,
+            frame: &mut dyn FrameSymbolizer,
+        ) -> Result<(), FillSymbolError> {
+            self.queries.borrow_mut().push(frame.get_instruction());
+            Err(FillSymbolError {})
+        }
+        fn walk_frame(&self, _module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()> {
+            self.queries.borrow_mut().push(walker.get_instruction());
+            None
+        }
+        fn stats(&self) -> HashMap<String, SymbolStats> {
+            HashMap::new()
+        }
+    }
+
+    let f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    stack.start().set_const(stack_start);
+    stack = stack
+        .D32(0) // below frame 0, so a bp can be recovered for any scanned return address
+        // frame 0
+        .D32(0x00001234) // garbage that doesn't point into
+        .D32(0x60000000) // any module
+        .D32(0xbab023ec)
+        .D32(0x4000129d) // return address
+        // frame 1
+        .append_repeated(8, 0);
+
+    let mut raw = f.raw.clone();
+    raw.eip = 0x4000f49d;
+    raw.esp = stack_start as u32 + 4;
+    raw.ebp = 0xd43eed6e;
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::X86(raw),
+        valid: MinidumpContextValidity::All,
+        xstate: None,
+    };
+    let contents = stack.get_contents().unwrap();
+    let stack_memory = MinidumpMemory {
+        desc: Default::default(),
+        base_address: stack_start,
+        size: contents.len() as u64,
+        bytes: &contents,
+        endian: scroll::LE,
+    };
+    let walk = |options: &ProcessorOptions| {
+        let provider = RecordingProvider::default();
+        let s = walk_stack(
+            &Some(&context),
+            Some(&stack_memory),
+            &f.modules,
+            None,
+            &provider,
+            options,
+        );
+        let queries = provider.queries.into_inner();
+        assert!(!queries.is_empty());
+        for &address in queries.iter() {
+            assert!(
+                f.modules.module_at_address(address).is_some(),
+                "looked up {:#x}, which isn't in a module",
+                address
+            );
+        }
+        s
+    };
+
+    let s = walk(&ProcessorOptions::default());
+    assert_eq!(s.frames.len(), 2);
+    assert_eq!(s.frames[1].trust, FrameTrust::Scan);
+    assert_eq!(s.frames[1].context.get_instruction_pointer(), 0x4000129d);
+
+    // Otherwise garbage is taken as the return address.
+    let s = walk(&ProcessorOptions {
+        cfi_only_for_known_modules: false,
+        ..ProcessorOptions::default()
+    });
+    assert_eq!(s.frames[1].trust, FrameTrust::Scan);
+    assert_eq!(s.frames[1].context.get_instruction_pointer(), 0x00001234);
+    assert!(s.frames[1].module.is_none());
+}
+
 // Force scanning for a return address a long way down the stack
 #[test]
 fn test_traditional_scan_long_way() {
@@ -328,11 +430,9 @@ fn check_cfi(
             let frame = &s.frames[1];
             let valid = &frame.context.valid;
             assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
-            if let MinidumpContextValidity::Some(ref which) = valid {
-                assert_eq!(which.len(), expected_regs.len());
-            } else {
-                unreachable!();
-            }
+            // Every expected register was recovered, and nothing else was.
+            assert_eq!(valid.intersect(&expected_valid), expected_valid);
+            assert_eq!(valid.union(&expected_valid), expected_valid);
 
             if let MinidumpRawContext::X86(ctx) = &frame.context.raw {
                 for reg in expected_regs {
@@ -818,3 +918,255 @@ fn test_stack_win_frame_data_parameter_size() {
         }
     }
 }
+
+// Basic STACK WIN fpo, for a function that doesn't touch %ebp.
+#[test]
+fn test_stack_win_fpo_basic() {
+    let mut f = TestFixture::new();
+    // 4 bytes of saved registers, 8 bytes of locals, no base pointer.
+    let symbols = ["STACK WIN 0 aa85 176 0 0 4 4 8 0 0 0\n"];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let frame1_esp = Label::new();
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    stack.start().set_const(stack_start);
+
+    stack = stack
+        // frame 0
+        .D32(0x9068a878) // saved regs: unused
+        .D32(0xa08ea45f) // locals: unused
+        .D32(0xa7120d1a) //         unused
+        .D32(0x40001350) // return address
+        // frame 1
+        .mark(&frame1_esp)
+        .append_repeated(0, 16); // empty space
+
+    f.raw.set_register("eip", 0x4000aa85);
+    f.raw
+        .set_register("esp", stack.start().value().unwrap() as u32);
+    f.raw.set_register("ebp", 0xf052c1de);
+    f.raw.set_register("ebx", 0x6ee7bd35);
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+
+    {
+        let f1 = &s.frames[1];
+        assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+        if let MinidumpContextValidity::Some(ref which) = f1.context.valid {
+            assert!(which.contains("eip"));
+            assert!(which.contains("esp"));
+            assert!(which.contains("ebp"));
+            // %ebx is forwarded when %ebp wasn't used
+            assert!(which.contains("ebx"));
+            assert!(!which.contains("esi"));
+            assert!(!which.contains("edi"));
+        } else {
+            unreachable!();
+        }
+        assert_eq!(f1.instruction + 1, 0x40001350);
+
+        if let MinidumpRawContext::X86(ctx) = &f1.context.raw {
+            assert_eq!(ctx.eip, 0x40001350);
+            assert_eq!(ctx.esp, frame1_esp.value().unwrap() as u32);
+            assert_eq!(ctx.ebp, 0xf052c1de);
+            assert_eq!(ctx.ebx, 0x6ee7bd35);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+// STACK WIN fpo for a function that uses %ebp as a general purpose register,
+// so its caller's value must be restored from the saved registers.
+#[test]
+fn test_stack_win_fpo_allocates_base_pointer() {
+    let mut f = TestFixture::new();
+    // 8 bytes of saved registers, 4 bytes of locals, allocates base pointer.
+    let symbols = ["STACK WIN 0 aa85 176 0 0 4 8 4 0 0 1\n"];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let frame1_esp = Label::new();
+    let frame1_ebp = Label::new();
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    stack.start().set_const(stack_start);
+
+    stack = stack
+        // frame 0
+        .D32(&frame1_ebp) // saved regs: %ebp
+        .D32(0xa7120d1a) //             unused
+        .D32(0xa08ea45f) // locals: unused
+        .D32(0x40001350) // return address
+        // frame 1
+        .mark(&frame1_esp)
+        .append_repeated(0, 12) // empty space
+        .mark(&frame1_ebp)
+        .D32(0) // saved %ebp (stack end)
+        .D32(0); // saved %eip (stack end)
+
+    f.raw.set_register("eip", 0x4000aa85);
+    f.raw
+        .set_register("esp", stack.start().value().unwrap() as u32);
+    f.raw.set_register("ebp", 0xf052c1de);
+    f.raw.set_register("ebx", 0x6ee7bd35);
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+
+    {
+        let f1 = &s.frames[1];
+        assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+        if let MinidumpContextValidity::Some(ref which) = f1.context.valid {
+            assert!(which.contains("eip"));
+            assert!(which.contains("esp"));
+            assert!(which.contains("ebp"));
+            assert!(!which.contains("ebx"));
+        } else {
+            unreachable!();
+        }
+        assert_eq!(f1.instruction + 1, 0x40001350);
+
+        if let MinidumpRawContext::X86(ctx) = &f1.context.raw {
+            assert_eq!(ctx.eip, 0x40001350);
+            assert_eq!(ctx.esp, frame1_esp.value().unwrap() as u32);
+            assert_eq!(ctx.ebp, frame1_ebp.value().unwrap() as u32);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+// STACK WIN fpo for a caller that pushed arguments for its callee, which are
+// part of its frame, so grand_callee_parameter_size must be included.
+#[test]
+fn test_stack_win_fpo_parameter_size() {
+    let mut f = TestFixture::new();
+
+    let module1_symbols = ["FUNC 1000 100 8 module1::wheedle\n"];
+    let module2_symbols = [
+        "FUNC aa85 176 0 module2::whine\n",
+        // 4 bytes of saved registers, 4 bytes of locals, no base pointer.
+        "STACK WIN 0 aa85 176 0 0 0 4 4 0 0 0\n",
+    ];
+    f.add_symbols(String::from("module1"), module1_symbols.concat());
+    f.add_symbols(String::from("module2"), module2_symbols.concat());
+
+    let frame0_ebp = Label::new();
+    let frame1_esp = Label::new();
+    let frame2_esp = Label::new();
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    stack.start().set_const(stack_start);
+
+    stack = stack
+        // frame 0, in module1::wheedle.  Traditional frame.
+        .append_repeated(0, 16) // frame space
+        .mark(&frame0_ebp)
+        .D32(0x6fa902e0) // saved %ebp.  Not a frame pointer.
+        .D32(0x5000aa95) // return address, in module2::whine
+        // frame 1, in module2::whine.  FPO frame.
+        .mark(&frame1_esp)
+        .D32(0xbdc92f9f) // argument 2 passed to module1::wheedle
+        .D32(0x0b1d8442) // argument 1
+        .D32(0x2558c7f3) // saved regs: unused
+        .D32(0x0365e25e) // locals: unused
+        .D32(0x2a179e38) // return address
+        // frame 2, in no module
+        .mark(&frame2_esp)
+        .append_repeated(0, 16); // empty space
+
+    f.raw.set_register("eip", 0x40001004);
+    f.raw
+        .set_register("esp", stack.start().value().unwrap() as u32);
+    f.raw
+        .set_register("ebp", frame0_ebp.value().unwrap() as u32);
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        let f1 = &s.frames[1];
+        assert_eq!(f1.trust, FrameTrust::FramePointer);
+        assert_eq!(f1.instruction + 1, 0x5000aa95);
+        if let MinidumpRawContext::X86(ctx) = &f1.context.raw {
+            assert_eq!(ctx.esp, frame1_esp.value().unwrap() as u32);
+            assert_eq!(ctx.ebp, 0x6fa902e0);
+        } else {
+            unreachable!();
+        }
+    }
+
+    {
+        let f2 = &s.frames[2];
+        assert_eq!(f2.trust, FrameTrust::CallFrameInfo);
+        if let MinidumpContextValidity::Some(ref which) = f2.context.valid {
+            assert!(which.contains("eip"));
+            assert!(which.contains("esp"));
+            assert!(which.contains("ebp"));
+        } else {
+            unreachable!();
+        }
+        assert_eq!(f2.instruction + 1, 0x2a179e38);
+
+        if let MinidumpRawContext::X86(ctx) = &f2.context.raw {
+            assert_eq!(ctx.eip, 0x2a179e38);
+            assert_eq!(ctx.esp, frame2_esp.value().unwrap() as u32);
+            assert_eq!(ctx.ebp, 0x6fa902e0);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+// When both framedata and fpo cover an address, framedata is preferred.
+#[test]
+fn test_stack_win_frame_data_preferred_over_fpo() {
+    let mut f = TestFixture::new();
+    let symbols = [
+        // Would restore the wrong return address if it were used.
+        "STACK WIN 0 aa80 200 0 0 4 0 8 0 0 0\n",
+        "STACK WIN 4 aa85 176 0 0 4 0 4 0 1",
+        " $T0 .raSearchStart =",
+        " $eip $T0 ^ =",
+        " $esp $T0 4 + =",
+        " $ebp $ebp =\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let frame1_esp = Label::new();
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    stack.start().set_const(stack_start);
+
+    stack = stack
+        // frame 0
+        .D32(0xa08ea45f) // locals: unused
+        .D32(0x40001350) // return address
+        .D32(0x40002350) // not a return address
+        // frame 1
+        .mark(&frame1_esp)
+        .append_repeated(0, 16); // empty space
+
+    f.raw.set_register("eip", 0x4000aa85);
+    f.raw
+        .set_register("esp", stack.start().value().unwrap() as u32);
+    f.raw.set_register("ebp", 0xf052c1de);
+
+    let s = f.walk_stack(stack);
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+    assert_eq!(f1.instruction + 1, 0x40001350);
+    if let MinidumpRawContext::X86(ctx) = &f1.context.raw {
+        assert_eq!(ctx.esp, frame1_esp.value().unwrap() as u32 - 4);
+    } else {
+        unreachable!();
+    }
+}
@@ -94,7 +94,9 @@
 //! ```
 //!
 use minidump::Module;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
 pub use symbols_shim::*;
 
 pub trait SymbolProvider {
@@ -105,6 +107,20 @@ pub trait SymbolProvider {
     ) -> Result<(), FillSymbolError>;
     fn walk_frame(&self, module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()>;
     fn stats(&self) -> HashMap<String, SymbolStats>;
+    /// Override how long may be spent acquiring a single module's symbols.
+    ///
+    /// `None` removes the override. Providers that don't have a deadline ignore this.
+    fn set_symbol_timeout(&self, _timeout: Option<Duration>) {}
+    /// The override set by `set_symbol_timeout`, if any.
+    fn symbol_timeout(&self) -> Option<Duration> {
+        None
+    }
+    /// Get the error from acquiring `module`'s symbols, if it failed.
+    ///
+    /// Providers that can't tell why they have no symbols return `None`.
+    fn symbol_error(&self, _module: &dyn Module) -> Option<SymbolError> {
+        None
+    }
 }
 
 #[derive(Default)]
@@ -158,14 +174,147 @@ impl SymbolProvider for MultiSymbolProvider {
         }
         result
     }
+
+    fn set_symbol_timeout(&self, timeout: Option<Duration>) {
+        for p in self.providers.iter() {
+            p.set_symbol_timeout(timeout);
+        }
+    }
+
+    fn symbol_timeout(&self) -> Option<Duration> {
+        self.providers.iter().find_map(|p| p.symbol_timeout())
+    }
+
+    fn symbol_error(&self, module: &dyn Module) -> Option<SymbolError> {
+        self.providers.iter().find_map(|p| p.symbol_error(module))
+    }
+}
+
+/// A view of a `Module` with its debug file name lowercased.
+///
+/// File names on Windows are case-insensitive, so the same module may be
+/// reported as `KERNEL32.DLL` by one minidump and `kernel32.dll` by another,
+/// while symbol stores generally key Windows symbols by the lowercase PDB
+/// name. If the module has no debug file, one is derived from the code file
+/// by swapping its `.dll`/`.exe` extension for `.pdb`.
+///
+/// The code file is left as-is, so that symbol statistics are still reported
+/// under the module's real name.
+pub struct CaseFoldedModule<'a>(pub &'a dyn Module);
+
+impl<'a> Module for CaseFoldedModule<'a> {
+    fn base_address(&self) -> u64 {
+        self.0.base_address()
+    }
+    fn size(&self) -> u64 {
+        self.0.size()
+    }
+    fn code_file(&self) -> Cow<'_, str> {
+        self.0.code_file()
+    }
+    fn code_identifier(&self) -> Cow<'_, str> {
+        self.0.code_identifier()
+    }
+    fn debug_file(&self) -> Option<Cow<'_, str>> {
+        match self.0.debug_file() {
+            Some(debug_file) if !debug_file.is_empty() => {
+                Some(Cow::Owned(debug_file.to_lowercase()))
+            }
+            _ => minidump::pdb_file_name(&self.0.code_file().to_lowercase()).map(Cow::Owned),
+        }
+    }
+    fn debug_identifier(&self) -> Option<Cow<'_, str>> {
+        self.0.debug_identifier()
+    }
+    fn version(&self) -> Option<Cow<'_, str>> {
+        self.0.version()
+    }
+}
+
+/// A `SymbolProvider` that retries failed lookups with a [`CaseFoldedModule`].
+///
+/// This is enabled for minidumps from Windows, where module names are
+/// case-insensitive. Lookups are only retried if the module's symbol file
+/// wasn't found, not if it just has nothing at the address (or couldn't be
+/// fetched in time), so that modules aren't looked up twice for nothing.
+pub(crate) struct CaseFoldingSymbolProvider<'a, P> {
+    provider: &'a P,
+    enabled: bool,
+}
+
+impl<'a, P: SymbolProvider> CaseFoldingSymbolProvider<'a, P> {
+    pub(crate) fn new(provider: &'a P, enabled: bool) -> Self {
+        Self { provider, enabled }
+    }
+
+    /// Get the case-folded view of `module` to retry a failed lookup with, if
+    /// it would be looked up differently and `module`'s symbol file wasn't found.
+    fn folded<'m>(&self, module: &'m dyn Module) -> Option<CaseFoldedModule<'m>> {
+        let folded = CaseFoldedModule(module);
+        if self.enabled
+            && folded.debug_file() != module.debug_file()
+            && matches!(
+                self.provider.symbol_error(module),
+                Some(SymbolError::NotFound)
+            )
+        {
+            Some(folded)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, P: SymbolProvider> SymbolProvider for CaseFoldingSymbolProvider<'a, P> {
+    fn fill_symbol(
+        &self,
+        module: &dyn Module,
+        frame: &mut dyn FrameSymbolizer,
+    ) -> Result<(), FillSymbolError> {
+        self.provider
+            .fill_symbol(module, frame)
+            .or_else(|e| match self.folded(module) {
+                Some(folded) => self.provider.fill_symbol(&folded, frame),
+                None => Err(e),
+            })
+    }
+
+    fn walk_frame(&self, module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()> {
+        self.provider.walk_frame(module, walker).or_else(|| {
+            self.folded(module)
+                .and_then(|folded| self.provider.walk_frame(&folded, walker))
+        })
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        self.provider.stats()
+    }
+
+    fn set_symbol_timeout(&self, timeout: Option<Duration>) {
+        self.provider.set_symbol_timeout(timeout)
+    }
+
+    fn symbol_timeout(&self) -> Option<Duration> {
+        self.provider.symbol_timeout()
+    }
+
+    fn symbol_error(&self, module: &dyn Module) -> Option<SymbolError> {
+        // A lookup that didn't find the symbol file is retried with the folded
+        // module, so that's the one that decides whether there are symbols.
+        match self.folded(module) {
+            Some(folded) => self.provider.symbol_error(&folded),
+            None => self.provider.symbol_error(module),
+        }
+    }
 }
 
 #[cfg(feature = "breakpad-syms")]
 mod symbols_shim {
     use super::SymbolProvider;
+    pub(crate) use breakpad_symbols::demangle;
     pub use breakpad_symbols::{
-        FillSymbolError, FrameSymbolizer, FrameWalker, SymbolError, SymbolFile, SymbolStats,
-        SymbolSupplier, Symbolizer,
+        FillSymbolError, FrameSymbolizer, FrameWalker, SymbolError, SymbolFile, SymbolOrigin,
+        SymbolStats, SymbolSupplier, Symbolizer,
     };
     use minidump::Module;
     use std::collections::HashMap;
@@ -185,6 +334,15 @@ mod symbols_shim {
         fn stats(&self) -> HashMap<String, SymbolStats> {
             self.stats()
         }
+        fn set_symbol_timeout(&self, timeout: Option<Duration>) {
+            self.set_symbol_timeout(timeout)
+        }
+        fn symbol_timeout(&self) -> Option<Duration> {
+            self.symbol_timeout()
+        }
+        fn symbol_error(&self, module: &dyn Module) -> Option<SymbolError> {
+            self.symbol_error(module)
+        }
     }
 
     /// Gets a SymbolSupplier that looks up symbols by path or with urls.
@@ -206,11 +364,12 @@ mod symbols_shim {
     ///   before atomically swapping them into the cache. Has the same "temp"
     ///   assumptions as symbols_cache.
     ///
-    /// * `timeout` a maximum time limit for a symbol file download. This
+    /// * `timeout` a maximum time limit for acquiring a module's symbol file,
+    ///   covering connecting to and downloading from all of `symbol_urls`. This
     ///   is primarily defined to avoid getting stuck on buggy infinite downloads.
     ///   As of this writing, minidump-stackwalk defaults this to 1000 seconds. In
-    ///   the event of a timeout, the supplier may still try to parse the truncated
-    ///   download.
+    ///   the event of a timeout, the module is treated as having no symbols.
+    ///   While processing, this is overridden by [`ProcessorOptions::symbol_timeout`][crate::ProcessorOptions::symbol_timeout].
     pub fn http_symbol_supplier(
         symbol_paths: Vec<PathBuf>,
         symbol_urls: Vec<String>,
@@ -362,11 +521,12 @@ mod symbols_shim {
     ///   before atomically swapping them into the cache. Has the same "temp"
     ///   assumptions as symbols_cache.
     ///
-    /// * `timeout` a maximum time limit (in seconds) for a symbol file download. This
+    /// * `timeout` a maximum time limit for acquiring a module's symbol file,
+    ///   covering connecting to and downloading from all of `symbol_urls`. This
     ///   is primarily defined to avoid getting stuck on buggy infinite downloads.
     ///   As of this writing, minidump-stackwalk defaults this to 1000 seconds. In
-    ///   the event of a timeout, the supplier may still try to parse the truncated
-    ///   download.
+    ///   the event of a timeout, the module is treated as having no symbols.
+    ///   While processing, this is overridden by [`ProcessorOptions::symbol_timeout`][crate::ProcessorOptions::symbol_timeout].
     pub fn http_symbol_supplier(
         _symbol_paths: Vec<PathBuf>,
         _symbol_urls: Vec<String>,
@@ -426,23 +586,45 @@ mod symbols_shim {
         /// else). But sometimes we can't make any sense of the symbol file, and
         /// you find yourself here.
         ParseError,
+        /// Acquiring the symbol file took too long.
+        Timeout,
     }
 
     #[derive(Debug)]
     pub struct FillSymbolError {}
 
+    /// Demangle `name` if it's a mangled Rust, Itanium (C++) or MSVC symbol.
+    pub(crate) fn demangle(_name: &str) -> Option<String> {
+        unimplemented!()
+    }
+
     // Whatever representation you want, rust-minidump won't look at it.
     pub struct SymbolFile {}
 
+    /// Where a module's symbols were loaded from.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum SymbolOrigin {
+        /// Downloaded from this URL.
+        Url(String),
+        /// Read from this file on disk (which may be a cached download).
+        Path(PathBuf),
+        /// Parsed from an in-memory buffer.
+        Memory,
+    }
+
     /// Statistics on the symbols of a module.
     #[derive(Default, Debug)]
     pub struct SymbolStats {
         /// If the module's symbols were downloaded, this is the url used.
         pub symbol_url: Option<String>,
+        /// Where the supplier ended up loading the module's symbols from.
+        pub symbol_origin: Option<SymbolOrigin>,
         /// If the symbols were found and loaded into memory.
         pub loaded_symbols: bool,
         /// If we tried to parse the symbols, but failed.
         pub corrupt_symbols: bool,
+        /// If we gave up on acquiring the symbols because it took too long.
+        pub timed_out: bool,
     }
 
     // These suppliers are entriely private to the implementation, so do whatever you
@@ -1,6 +1,7 @@
 use minidump::system_info::{Cpu, Os};
 
 /// Information about the system that produced a `Minidump`.
+#[non_exhaustive]
 pub struct SystemInfo {
     /// The operating system that produced the minidump
     pub os: Os,
@@ -8,12 +9,34 @@ pub struct SystemInfo {
     ///
     /// This may look like "5.1.2600 Service Pack 2" or "10.4.8 8L2127", if present
     pub os_version: Option<String>,
+    /// A string identifying the exact build of the operating system
+    ///
+    /// This is the build string from the misc info stream, which only newer
+    /// Windows minidumps have. It looks like
+    /// "19041.1.amd64fre.vb_release.191206-1406", if present.
+    pub os_build: Option<String>,
     /// The CPU on which the dump was produced
     pub cpu: Cpu,
+    /// Whether `cpu` was inferred from the thread contexts, because the
+    /// minidump had no system info
+    ///
+    /// Everything else is unknown then, see [`Minidump::infer_system_info`][].
+    ///
+    /// [`Minidump::infer_system_info`]: minidump::Minidump::infer_system_info
+    pub cpu_inferred: bool,
     /// A string further identifying the specific CPU
     ///
     /// For example,  "GenuineIntel level 6 model 13 stepping 8", if present.
     pub cpu_info: Option<String>,
+    /// The CPU's vendor, e.g. "GenuineIntel" or "Qualcomm", if known
+    pub cpu_vendor: Option<String>,
+    /// The family, model, and stepping of an x86 or x86-64 CPU
+    pub cpu_family_model_stepping: Option<(u32, u32, u32)>,
+    /// The features the CPU has, as the Linux kernel names them, e.g. "sse2"
+    /// or "neon"
+    ///
+    /// This is empty if the minidump doesn't record them.
+    pub cpu_features: Vec<&'static str>,
     /// The microcode version of the cpu
     pub cpu_microcode_version: Option<u64>,
     /// The number of processors in the system
@@ -0,0 +1,92 @@
+//! Assertions for tests that process minidumps.
+//!
+//! These are only built for this crate's own tests, or with the `testing`
+//! feature. They panic with a list of the offending frames, which is more
+//! helpful than a bare `assert!` over all the threads.
+
+use crate::process_state::{FrameTrust, ProcessState, StackFrame};
+
+impl ProcessState {
+    /// Panic if any thread has a frame that was found by scanning the stack.
+    ///
+    /// This includes [`FrameTrust::CfiScan`] frames.
+    pub fn assert_no_scan_frames(&self) {
+        let scanned = self
+            .describe_frames(|frame| matches!(frame.trust, FrameTrust::Scan | FrameTrust::CfiScan));
+        assert!(
+            scanned.is_empty(),
+            "found {} scan frames:\n{}",
+            scanned.len(),
+            scanned.join("\n")
+        );
+    }
+
+    /// Panic if any thread has a frame without a function name.
+    pub fn assert_all_frames_have_symbols(&self) {
+        let unsymbolized = self.describe_frames(|frame| frame.function_name.is_none());
+        assert!(
+            unsymbolized.is_empty(),
+            "found {} frames without symbols:\n{}",
+            unsymbolized.len(),
+            unsymbolized.join("\n")
+        );
+    }
+
+    /// Panic unless there is a crashing (or dump-requesting) thread and it
+    /// has exactly `n` frames.
+    pub fn assert_crashing_thread_has_n_frames(&self, n: usize) {
+        let index = self
+            .requesting_thread
+            .expect("there is no crashing or requesting thread");
+        let frames = &self.threads[index].frames;
+        assert!(
+            frames.len() == n,
+            "expected the crashing thread (thread {}) to have {} frames, found {}:\n{}",
+            index,
+            n,
+            frames.len(),
+            frames
+                .iter()
+                .enumerate()
+                .map(|(frame_index, frame)| describe_frame(index, frame_index, frame))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    /// Describe every frame of every thread that matches `predicate`.
+    fn describe_frames(&self, predicate: impl Fn(&StackFrame) -> bool) -> Vec<String> {
+        self.threads
+            .iter()
+            .enumerate()
+            .flat_map(|(thread_index, thread)| {
+                thread
+                    .frames
+                    .iter()
+                    .enumerate()
+                    .map(move |(frame_index, frame)| (thread_index, frame_index, frame))
+            })
+            .filter(|(_, _, frame)| predicate(frame))
+            .map(|(thread_index, frame_index, frame)| {
+                describe_frame(thread_index, frame_index, frame)
+            })
+            .collect()
+    }
+}
+
+/// e.g. "  thread 0 frame 2: 0x4053eb in test_app.exe (stack scanning)"
+fn describe_frame(thread_index: usize, frame_index: usize, frame: &StackFrame) -> String {
+    let location = match (&frame.function_name, &frame.module) {
+        (Some(function), _) => function.clone(),
+        (None, Some(module)) => module.name().to_owned(),
+        (None, None) => String::from("no module"),
+    };
+    format!(
+        "  thread {} frame {}: {:#x} in {} ({})",
+        thread_index,
+        frame_index,
+        frame.instruction,
+        location,
+        frame.trust.description()
+    )
+}
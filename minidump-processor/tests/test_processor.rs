@@ -1,14 +1,23 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
+use minidump::format::{ContextFlagsCpu, CONTEXT_X86};
 use minidump::system_info::{Cpu, Os};
 use minidump::{
-    Error, Minidump, MinidumpContext, MinidumpContextValidity, MinidumpRawContext, Module,
+    Error, Minidump, MinidumpContext, MinidumpContextValidity, MinidumpMemoryList, MinidumpModule,
+    MinidumpRawContext, MinidumpThreadList, MinidumpUnloadedModule, MinidumpUnloadedModuleList,
+    MinidumpWarning, Module,
 };
 use minidump_processor::{
-    simple_symbol_supplier, CallStackInfo, FrameTrust, LinuxStandardBase, ProcessState, Symbolizer,
+    http_symbol_supplier, simple_symbol_supplier, CallStack, CallStackInfo, CallingConvention,
+    FrameTrust, LinuxStandardBase, NoiseList, ProcessState, ProcessorOptions, RedactionOptions,
+    RegexAnnotator, StackFrame, StackFrameAnnotator, SymbolError, Symbolizer, ThreadFilter,
 };
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempdir::TempDir;
 
 use synth_minidump::*;
 use test_assembler::*;
@@ -77,6 +86,7 @@ fn test_processor() {
     if let MinidumpContext {
         raw: MinidumpRawContext::X86(ref raw),
         ref valid,
+        ..
     } = f0.context
     {
         assert_eq!(raw.eip, 0x0040429e);
@@ -95,6 +105,7 @@ fn test_processor() {
     if let MinidumpContext {
         raw: MinidumpRawContext::X86(ref raw),
         ref valid,
+        ..
     } = f3.context
     {
         assert_eq!(raw.eip, 0x7c816fd7);
@@ -115,6 +126,345 @@ fn test_processor() {
     assert_eq!(state.threads[1].frames.len(), 0);
 }
 
+#[test]
+fn test_symbol_error_not_found() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    let f0 = &state.threads[0].frames[0];
+    assert!(f0.function_name.is_none());
+    assert_eq!(f0.symbol_error, Some(SymbolError::NotFound));
+}
+
+#[test]
+fn test_memory_usage_report() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    let report = state.memory_usage_report();
+    assert_eq!(report, dump.memory_usage_report());
+    // test.dmp has two UnusedStreams, and each is reported.
+    assert_eq!(report.len(), 9);
+    assert_eq!(report[0].0.to_string(), "ModuleListStream");
+    assert_eq!(report[0].1, 1408);
+    assert!(report.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[test]
+fn test_thread_filter() {
+    let dump = read_test_minidump().unwrap();
+    let process = |thread_filter: ThreadFilter| {
+        let mut options = ProcessorOptions::default();
+        options.thread_filter = thread_filter;
+        minidump_processor::process_minidump_with_options(
+            &dump,
+            &Symbolizer::new(simple_symbol_supplier(vec![])),
+            options,
+        )
+        .unwrap()
+    };
+
+    // Thread 0 (0xbf4) requested the dump, thread 1 (0x11c0) wrote it.
+    for filter in [
+        ThreadFilter::All,
+        ThreadFilter::Ids(vec![0xbf4]),
+        ThreadFilter::CrashingThread,
+    ] {
+        let state = process(filter);
+        assert_eq!(state.threads.len(), 2);
+        assert_eq!(state.threads[0].info, CallStackInfo::Ok);
+        assert_eq!(state.threads[0].frames.len(), 4);
+        assert_eq!(state.requesting_thread, Some(0));
+        assert_eq!(state.threads[1].info, CallStackInfo::DumpThreadSkipped);
+    }
+
+    for filter in [
+        ThreadFilter::Ids(vec![]),
+        ThreadFilter::Ids(vec![0x11c0, 0x1234]),
+    ] {
+        let state = process(filter);
+        assert_eq!(state.threads.len(), 2);
+        assert_eq!(state.threads[0].info, CallStackInfo::Filtered);
+        assert_eq!(state.threads[0].frames.len(), 0);
+        assert_eq!(state.requesting_thread, None);
+        assert_eq!(state.threads[1].info, CallStackInfo::DumpThreadSkipped);
+    }
+}
+
+#[test]
+fn test_breakpad_info_threads() {
+    // Thread 0x1234 requested the dump, and thread 0x2 wrote it.
+    let dump = |breakpad_info: bool, exception_thread: Option<u32>| {
+        let context = synth_minidump::x86_context(Endian::Little, 0x402000, 0x10000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x10),
+            0x10000,
+        );
+        let thread = Thread::new(Endian::Little, 0x2, &stack, &context);
+        let mut dump = minimal_minidump()
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack);
+        if breakpad_info {
+            let validity = minidump::format::BreakpadInfoValid::DumpThreadId.bits()
+                | minidump::format::BreakpadInfoValid::RequestingThreadId.bits();
+            dump = dump.add_stream(SimpleStream {
+                stream_type: minidump::format::MINIDUMP_STREAM_TYPE::BreakpadInfoStream as u32,
+                section: Section::with_endian(Endian::Little)
+                    .D32(validity)
+                    .D32(0x2)
+                    .D32(0x1234),
+            });
+        }
+        if let Some(thread_id) = exception_thread {
+            let mut exception = Exception::new(Endian::Little);
+            exception.thread_id = thread_id;
+            exception.exception_record.exception_code = 0xc0000005; // EXCEPTION_ACCESS_VIOLATION
+            exception.exception_record.exception_address = 0x401000;
+            dump = dump.add_exception(exception);
+        }
+        read_synth_dump(dump)
+    };
+
+    // Without the Breakpad info stream, the exception says which thread crashed.
+    let state = dump(false, Some(0x2));
+    assert_eq!(state.requesting_thread, Some(1));
+    assert_eq!(state.crashing_thread_id(), Some(0x2));
+    assert_eq!(state.thread_for_id(0x1234), Some(0));
+    assert_eq!(state.thread_for_id(0x2), Some(1));
+    assert_eq!(state.thread_for_id(0x3), None);
+    assert_eq!(state.dump_thread_id, None);
+    assert_eq!(state.requesting_thread_id, None);
+    assert_eq!(state.threads[0].info, CallStackInfo::Ok);
+    assert!(state.crashed());
+
+    // Without either, no thread is singled out.
+    let state = dump(false, None);
+    assert_eq!(state.requesting_thread, None);
+    assert_eq!(state.crashing_thread_id(), None);
+    assert_eq!(state.threads[1].info, CallStackInfo::Ok);
+
+    // A dump requested without crashing. The thread that wrote it isn't walked.
+    let state = dump(true, None);
+    assert_eq!(state.requesting_thread, Some(0));
+    assert_eq!(state.dump_thread_id, Some(0x2));
+    assert_eq!(state.requesting_thread_id, Some(0x1234));
+    assert_eq!(state.threads[0].frames[0].instruction, 0xabcd1234);
+    assert_eq!(state.threads[1].info, CallStackInfo::DumpThreadSkipped);
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Thread 0  (requested dump, did not crash)"));
+
+    // An exception on the thread that wrote the dump belongs to the requesting thread.
+    let state = dump(true, Some(0x2));
+    assert_eq!(state.requesting_thread, Some(0));
+    assert_eq!(state.crashing_thread_id(), Some(0x2));
+    assert_eq!(state.threads[1].info, CallStackInfo::DumpThreadSkipped);
+    assert_eq!(state.thread_for_id(0x2), Some(1));
+    assert!(state.crashed());
+
+    // The requesting thread can also have crashed.
+    let state = dump(true, Some(0x1234));
+    assert_eq!(state.requesting_thread, Some(0));
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Thread 0  (crashed)"));
+}
+
+#[test]
+fn test_windows_memory_usage() {
+    const GIB: u64 = 1024 * 1024 * 1024;
+    let system_memory_info = Section::with_endian(Endian::Little)
+        .D16(1) // revision
+        .D16(minidump::format::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF.bits())
+        .D32(0) // timer_resolution
+        .D32(0x1000) // page_size
+        .D32(0x40_0000) // number_of_physical_pages (16 GiB)
+        .append_repeated(0, 3 * 4 + 3 * 8 + 4 + 60)
+        .D64(0x8_0000) // available_pages (2 GiB)
+        .D64(0x7F_3333) // committed_pages (31.8 GiB)
+        .D64(0x80_0000) // commit_limit (32 GiB)
+        .D64(0x7F_8000) // peak_commitment
+        .append_repeated(0, 344);
+    let vm_counters = Section::with_endian(Endian::Little)
+        .D16(2) // revision
+        .D16(minidump::format::ProcessVmCountersFlags::all().bits())
+        .D32(0) // page_fault_count
+        .D64(3 * GIB) // peak_working_set_size
+        .D64(2 * GIB) // working_set_size
+        .append_repeated(0, 6 * 8)
+        .D64(5 * GIB) // peak_virtual_size
+        .D64(4 * GIB) // virtual_size
+        .D64(GIB) // private_usage
+        .append_repeated(0, 7 * 8);
+    let dump = minimal_minidump()
+        .add_stream(SimpleStream {
+            stream_type: minidump::format::MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream as u32,
+            section: system_memory_info,
+        })
+        .add_stream(SimpleStream {
+            stream_type: minidump::format::MINIDUMP_STREAM_TYPE::ProcessVmCountersStream as u32,
+            section: vm_counters,
+        });
+    let state = read_synth_dump(dump);
+
+    let usage = state.memory_usage.as_ref().unwrap();
+    assert_eq!(usage.peak_virtual_size, Some(5 * GIB));
+    assert_eq!(usage.virtual_size, Some(4 * GIB));
+    assert_eq!(usage.resident_size, Some(2 * GIB));
+    assert_eq!(usage.swap_size, None);
+    assert_eq!(usage.private_size, Some(GIB));
+    let system = state.system_memory_usage.as_ref().unwrap();
+    assert_eq!(system.physical_size, Some(16 * GIB));
+    assert_eq!(system.available_physical_size, Some(2 * GIB));
+    assert_eq!(system.committed_size, Some(0x7F_3333 * 0x1000));
+    assert_eq!(system.commit_limit, Some(32 * GIB));
+    assert_eq!(system.peak_committed_size, Some(0x7F_8000 * 0x1000));
+
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("RSS at crash: 2.0 GiB\n"));
+    assert!(output.contains("Memory usage:\n  private: 1.0 GiB\n  virtual: 4.0 GiB\n"));
+    assert!(output.contains(
+        "System memory:\n  commit: 31.8/32.0 GiB\n  available physical memory: 2.0/16.0 GiB\n"
+    ));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["memory_usage"]["private_size"], GIB);
+    assert_eq!(json["system_memory_usage"]["commit_limit"], 32 * GIB);
+}
+
+#[test]
+fn test_assertion() {
+    let path = locate_testdata().join("invalid-parameter.dmp");
+    let dump = Minidump::read_path(&path).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    assert_eq!(
+        state.assertion.as_deref(),
+        Some(
+            r"Invalid parameter passed to library function format != nullptr in function common_vfprintf, in file minkernel\crts\ucrt\src\appcrt\stdio\output.cpp at line 32"
+        )
+    );
+}
+
+#[test]
+fn test_synth_assertion() {
+    let dump = |expression: &str| {
+        let mut assertion = Section::with_endian(Endian::Little);
+        for string in [expression, "", ""] {
+            let units = string.encode_utf16().collect::<Vec<_>>();
+            for unit in &units {
+                assertion = assertion.D16(*unit);
+            }
+            assertion = assertion.append_repeated(0, (128 - units.len()) * 2);
+        }
+        let assertion = if expression.is_empty() {
+            assertion.D32(0).D32(0)
+        } else {
+            assertion
+                .D32(0)
+                .D32(minidump::format::AssertionType::PureVirtualCall as u32)
+        };
+        let dump = minimal_minidump().add_stream(SimpleStream {
+            stream_type: minidump::format::MINIDUMP_STREAM_TYPE::AssertionInfoStream as u32,
+            section: assertion,
+        });
+        read_synth_dump(dump)
+    };
+
+    let state = dump("ça != ∅");
+    let description = "Pure virtual function called ça != ∅";
+    assert_eq!(state.assertion.as_deref(), Some(description));
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let assertion = output.find(&format!("Assertion: {}", description)).unwrap();
+    assert!(assertion < output.find("Thread 0").unwrap());
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["crash_info"]["assertion"], description);
+
+    // An all-zero stream means there was no assertion.
+    let state = dump("");
+    assert_eq!(state.assertion, None);
+}
+
+#[test]
+fn test_trust_threshold() {
+    assert!(FrameTrust::Context > FrameTrust::CallFrameInfo);
+    assert!(FrameTrust::CallFrameInfo > FrameTrust::FramePointer);
+    assert!(FrameTrust::FramePointer > FrameTrust::Scan);
+    assert!(FrameTrust::Scan > FrameTrust::None);
+
+    // Without symbols, the caller's frame can only be found by scanning the stack.
+    let dump = arm64_scan_minidump(Endian::Little);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let frames = |trust_threshold: FrameTrust| {
+        let mut options = ProcessorOptions::default();
+        options.trust_threshold = trust_threshold;
+        let state = minidump_processor::process_minidump_with_options(
+            &dump,
+            &Symbolizer::new(simple_symbol_supplier(vec![])),
+            options,
+        )
+        .unwrap();
+        state.threads[0]
+            .frames
+            .iter()
+            .map(|frame| (frame.instruction, frame.trust))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        frames(FrameTrust::default()),
+        [(0x10100, FrameTrust::Context), (0x101fc, FrameTrust::Scan)]
+    );
+    assert_eq!(
+        frames(FrameTrust::Scan),
+        [(0x10100, FrameTrust::Context), (0x101fc, FrameTrust::Scan)]
+    );
+    assert_eq!(
+        frames(FrameTrust::FramePointer),
+        [(0x10100, FrameTrust::Context)]
+    );
+}
+
+#[test]
+fn test_print_crashing_thread_without_frames() {
+    let dump = read_test_minidump().unwrap();
+    let mut state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    // A malformed dump can leave the crashing thread without any frames.
+    let requesting_thread = state.requesting_thread.unwrap();
+    state.threads[requesting_thread].frames.clear();
+
+    let mut text = Vec::new();
+    state.print(&mut text).unwrap();
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["crashing_thread"]["frames"], serde_json::json!([]));
+}
+
 #[test]
 fn test_processor_symbols() {
     let dump = read_test_minidump().unwrap();
@@ -132,6 +482,234 @@ fn test_processor_symbols() {
     );
 }
 
+#[test]
+fn test_frame_assertions() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()])),
+    )
+    .unwrap();
+    state.assert_no_scan_frames();
+    state.assert_crashing_thread_has_n_frames(state.threads[0].frames.len());
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        state.assert_crashing_thread_has_n_frames(100)
+    }));
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.starts_with("expected the crashing thread (thread 0) to have 100 frames"));
+    assert!(message.contains("  thread 0 frame 0: 0x40429e in `anonymous namespace'::CrashFunction (given as instruction pointer in context)"));
+
+    // test.dmp has no symbols for kernel32.dll.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| state.assert_all_frames_have_symbols()));
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains(" in kernel32.dll ("), "{}", message);
+}
+
+#[test]
+#[should_panic(
+    expected = "found 1 scan frames:\n  thread 0 frame 1: 0x101fc in libfoo.so (stack scanning)"
+)]
+fn test_assert_no_scan_frames() {
+    read_synth_dump(arm64_scan_minidump(Endian::Little)).assert_no_scan_frames();
+}
+
+#[test]
+fn test_redact_minidump() {
+    let bytes = fs::read(locate_testdata().join("test.dmp")).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()]));
+    let frames = |state: &ProcessState| {
+        state
+            .threads
+            .iter()
+            .map(|thread| {
+                thread
+                    .frames
+                    .iter()
+                    .map(|frame| (frame.instruction, frame.trust, frame.function_name.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let dump = Minidump::read(bytes.clone()).unwrap();
+    let state = minidump_processor::process_minidump(&dump, &symbolizer).unwrap();
+
+    for walked_stack_only in [false, true] {
+        let mut redacted = Minidump::read(bytes.clone()).unwrap();
+        let options = RedactionOptions {
+            walked_stack_only,
+            ..RedactionOptions::default()
+        };
+        minidump_processor::redact_minidump(&mut redacted, &state, options).unwrap();
+        let mut written = Vec::new();
+        redacted.write(&mut written).unwrap();
+
+        let redacted = Minidump::read(written).unwrap();
+        let redacted_state = minidump_processor::process_minidump(&redacted, &symbolizer).unwrap();
+        assert_eq!(frames(&redacted_state), frames(&state));
+
+        // Only stack memory survives.
+        let threads = redacted.get_stream::<MinidumpThreadList>().unwrap();
+        let memory = redacted.get_stream::<MinidumpMemoryList>().unwrap();
+        let mut nonzero = 0;
+        for region in memory.iter() {
+            let range = region.memory_range().unwrap();
+            let stack = threads
+                .threads
+                .iter()
+                .position(|thread| thread.raw.stack.start_of_memory_range == region.base_address);
+            let kept = match stack {
+                Some(i) if walked_stack_only => {
+                    let stack_pointers = state.threads[i]
+                        .frames
+                        .iter()
+                        .map(|frame| frame.context.get_stack_pointer());
+                    // Threads that weren't walked don't keep any of their stack.
+                    let start = stack_pointers.clone().min();
+                    start
+                        .zip(stack_pointers.max())
+                        .map(|(start, end)| start..=end)
+                }
+                Some(_) => Some(range.start..=range.end),
+                None => None,
+            };
+            for (address, &byte) in (range.start..=range.end).zip(region.bytes) {
+                if !matches!(&kept, Some(kept) if kept.contains(&address)) {
+                    assert_eq!(byte, 0, "{:#x} wasn't redacted", address);
+                }
+                nonzero += (byte != 0) as usize;
+            }
+        }
+        assert!(nonzero > 0);
+    }
+}
+
+#[test]
+fn test_process_truncated_minidump() {
+    let bytes = fs::read(locate_testdata().join("test.dmp")).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()]));
+    let dump = Minidump::read(&bytes[..]).unwrap();
+    let full = minidump_processor::process_minidump(&dump, &symbolizer).unwrap();
+    assert!(full.warnings.is_empty());
+
+    // Cut in the second thread's stack, in the memory list, before the memory list,
+    // and in the module list. The thread list and system info are still there.
+    for cut in [10000, 5400, 5000, 1000] {
+        let dump = Minidump::read(&bytes[..cut]).unwrap();
+        let state = minidump_processor::process_minidump(&dump, &symbolizer).unwrap();
+        assert_eq!(state.warnings, dump.warnings());
+        assert!(!state.warnings.is_empty());
+        assert_eq!(state.threads.len(), 2);
+        assert_eq!(state.system_info.cpu, Cpu::X86);
+    }
+
+    // Only the second thread's stack is cut short, so the first is unaffected.
+    let dump = Minidump::read(&bytes[..10000]).unwrap();
+    let state = minidump_processor::process_minidump(&dump, &symbolizer).unwrap();
+    let frames = |thread: &CallStack| {
+        thread
+            .frames
+            .iter()
+            .map(|frame| (frame.instruction, frame.function_name.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(frames(&state.threads[0]), frames(&full.threads[0]));
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("memory region 2 at 0x97f6e8 truncated from 2328 to 1011 bytes"));
+
+    // Without a thread list there's nothing to process.
+    let dump = Minidump::read(&bytes[..300]).unwrap();
+    assert!(minidump_processor::process_minidump(&dump, &symbolizer).is_err());
+}
+
+#[test]
+fn test_symbolicated_frames() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()])),
+    )
+    .unwrap();
+    let frames: Vec<_> = state.symbolicated_frames().collect();
+    assert_eq!(
+        frames.len(),
+        state.threads.iter().map(|t| t.frames.len()).sum::<usize>()
+    );
+
+    let f0 = frames[0];
+    assert_eq!((f0.thread_index, f0.frame_index), (0, 0));
+    assert_eq!(f0.instruction, 0x0040429e);
+    assert_eq!(f0.module, Some("test_app.exe"));
+    assert_eq!(f0.function, Some("`anonymous namespace'::CrashFunction"));
+    assert_eq!(f0.file, Some("c:\\test_app.cc"));
+    assert_eq!(f0.line, Some(58));
+
+    // The dump thread isn't walked, so everything is from the crashing thread.
+    let indices: Vec<_> = frames
+        .iter()
+        .map(|f| (f.thread_index, f.frame_index))
+        .collect();
+    assert_eq!(indices, [(0, 0), (0, 1), (0, 2), (0, 3)]);
+}
+
+#[test]
+fn test_frame_annotators() {
+    struct TagEverything;
+    impl StackFrameAnnotator for TagEverything {
+        fn annotate(&self, frame: &mut StackFrame) {
+            // Annotators run after symbolization.
+            assert!(frame.module.is_some());
+            frame.add_tag("seen");
+        }
+    }
+
+    let mut regexes = RegexAnnotator::new();
+    regexes.tag_functions("CrashFunction$", "crash").unwrap();
+    regexes
+        .tag_functions("^main$|CrashFunction", "app")
+        .unwrap();
+    regexes
+        .tag_modules("(?i)^kernel32\\.dll$", "noise")
+        .unwrap();
+    regexes.tag_modules("^test_app\\.exe$", "app").unwrap();
+    assert!(regexes.tag_functions("(", "bad").is_err());
+
+    let mut options = ProcessorOptions::default();
+    options.annotators = vec![Arc::new(TagEverything), Arc::new(regexes)];
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()])),
+        options,
+    )
+    .unwrap();
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[0].tags, ["seen", "crash", "app"]);
+    assert_eq!(frames[1].tags, ["seen", "app"]);
+    assert_eq!(frames[3].tags, ["seen", "noise"]);
+
+    let mut text = Vec::new();
+    state.print(&mut text).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(text.contains("    Tags: seen, crash, app\n"));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(
+        json["threads"][0]["frames"][0]["tags"],
+        serde_json::json!(["seen", "crash", "app"])
+    );
+    assert_eq!(
+        json["crashing_thread"]["frames"][3]["tags"],
+        serde_json::json!(["seen", "noise"])
+    );
+}
+
 fn minimal_minidump() -> SynthMinidump {
     let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
     let stack = Memory::with_section(
@@ -147,12 +725,174 @@ fn minimal_minidump() -> SynthMinidump {
         .add_memory(stack)
 }
 
+/// An ARM64 dump with libfoo.so at 0x10000, crashing at 0x10100 with a
+/// return address into libfoo.so on the stack that can only be found by
+/// scanning.
+fn arm64_scan_minidump(endian: Endian) -> SynthMinidump {
+    let name = DumpString::new("libfoo.so", endian);
+    let module = synth_minidump::Module::new(endian, 0x10000, 0x10000, &name, 0, 0, None);
+    let context = synth_minidump::arm64_context(endian, 0x10100, 0x1000);
+    let stack = Memory::with_section(
+        Section::with_endian(endian)
+            .D64(0)
+            .D64(0x10200) // a return address into libfoo.so
+            .append_repeated(0, 0x100),
+        0x1000,
+    );
+    let thread = Thread::new(endian, 0x1234, &stack, &context);
+    let arch = minidump::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16;
+    let system_info = SystemInfo::new(endian).set_processor_architecture(arch);
+    SynthMinidump::with_endian(endian)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add_memory(stack)
+}
+
 fn read_synth_dump(dump: SynthMinidump) -> ProcessState {
     let dump = Minidump::read(dump.finish().unwrap()).unwrap();
     minidump_processor::process_minidump(&dump, &Symbolizer::new(simple_symbol_supplier(vec![])))
         .unwrap()
 }
 
+#[test]
+fn test_bigendian_stackwalk() {
+    // Everything in a big-endian minidump is big-endian, including the stack.
+    let state = read_synth_dump(arm64_scan_minidump(Endian::Big));
+
+    assert_eq!(
+        state.modules.iter().next().unwrap().code_file(),
+        "libfoo.so"
+    );
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].instruction, 0x10100);
+    assert_eq!(frames[1].trust, FrameTrust::Scan);
+    assert_eq!(frames[1].instruction, 0x10200 - 4);
+    assert_eq!(frames[1].context.get_stack_pointer(), 0x1010);
+}
+
+#[test]
+fn test_streaming_full_memory_dump() {
+    // In a full-memory dump the thread's stack is only in the Memory64List.
+    let endian = Endian::Little;
+    let name = DumpString::new("libfoo.so", endian);
+    let module = synth_minidump::Module::new(endian, 0x10000, 0x10000, &name, 0, 0, None);
+    let context = synth_minidump::x86_context(endian, 0x10100, 0x1000);
+    let stack_desc = Memory::with_section(Section::with_endian(endian), 0x1000);
+    let thread = Thread::new(endian, 0x1234, &stack_desc, &context);
+    let stack = Memory::with_section(
+        Section::with_endian(endian)
+            .D32(0)
+            .D32(0x10200) // a return address into libfoo.so
+            .append_repeated(0, 0x100),
+        0x1000,
+    );
+    let heap = Memory::with_section(
+        Section::with_endian(endian).append_repeated(0, 0x10000),
+        0x100000,
+    );
+    let dump = SynthMinidump::with_endian(endian)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(endian))
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add(stack_desc)
+        .add_memory64(stack)
+        .add_memory64(heap);
+    let bytes = dump.finish().unwrap();
+
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+    let streaming = minidump::StreamingMinidump::read(std::io::Cursor::new(bytes.clone())).unwrap();
+    let state =
+        minidump_processor::process_minidump(&streaming.to_minidump().unwrap(), &symbolizer)
+            .unwrap();
+    let expected =
+        minidump_processor::process_minidump(&Minidump::read(bytes).unwrap(), &symbolizer).unwrap();
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].instruction, 0x10100);
+    assert_eq!(frames[1].trust, FrameTrust::Scan);
+    let instructions = |state: &ProcessState| {
+        state.threads[0]
+            .frames
+            .iter()
+            .map(|frame| frame.instruction)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(instructions(&state), instructions(&expected));
+}
+
+#[test]
+fn test_context_cpu_mismatch() {
+    // An x86 context in an amd64 minidump is too small to read as amd64.
+    let endian = Endian::Little;
+    let context = synth_minidump::x86_context(endian, 0x10100, 0x1000);
+    let stack = Memory::with_section(
+        Section::with_endian(endian).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let thread = Thread::new(endian, 0x1234, &stack, &context);
+    let arch = minidump::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+    let system_info = SystemInfo::new(endian).set_processor_architecture(arch);
+    let dump = SynthMinidump::with_endian(endian)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump);
+
+    assert!(state.threads[0].frames.is_empty());
+    assert_eq!(
+        state.warnings,
+        vec![MinidumpWarning::ContextCpuMismatch {
+            thread_id: 0x1234,
+            exception: false,
+            context_cpu: ContextFlagsCpu::CONTEXT_X86,
+            system_info_cpu: ContextFlagsCpu::CONTEXT_AMD64,
+            used: false,
+        }]
+    );
+    assert_eq!(
+        state.warnings[0].to_string(),
+        "thread context of thread 0x1234 is for x86 but the system info says amd64, ignoring it"
+    );
+}
+
+#[test]
+fn test_missing_system_info() {
+    // The CPU is inferred from the thread's context, which is enough to walk it.
+    let endian = Endian::Little;
+    let context = synth_minidump::amd64_context(endian, 0x10100, 0x1000);
+    let stack = Memory::with_section(
+        Section::with_endian(endian).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let thread = Thread::new(endian, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(endian)
+        .add_thread(thread)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump);
+
+    assert_eq!(state.system_info.cpu, Cpu::X86_64);
+    assert!(state.system_info.cpu_inferred);
+    assert_eq!(state.system_info.os_version, None);
+    assert_eq!(state.threads[0].frames[0].instruction, 0x10100);
+    assert_eq!(
+        state.warnings,
+        vec![MinidumpWarning::SystemInfoInferred { cpu: Cpu::X86_64 }]
+    );
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("CPU: amd64 (inferred)\n"));
+}
+
 #[test]
 fn test_linux_cpu_info() {
     // Whitespace intentionally wonky to test robustness
@@ -185,6 +925,7 @@ DISTRIB_DESCRIPTION= wow long string!!!
             release,
             codename,
             description,
+            ..
         } = state.linux_standard_base.unwrap();
 
         assert_eq!(id, "hello");
@@ -193,6 +934,14 @@ DISTRIB_DESCRIPTION= wow long string!!!
         assert_eq!(description, "wow long string!!!");
     }
 
+    {
+        let input = b"DISTRIB_ID=Ubuntu\nDISTRIB_RELEASE=22.04\n";
+        let dump = minimal_minidump().set_linux_lsb_release(input);
+        let state = read_synth_dump(dump);
+        // No description, so it's made from the id and release.
+        assert!(state.os_description().ends_with(" (Ubuntu 22.04)"));
+    }
+
     {
         let input = br#"
 ID="hello"
@@ -208,6 +957,7 @@ PRETTY_NAME= wow long string!!!
             release,
             codename,
             description,
+            ..
         } = state.linux_standard_base.unwrap();
 
         assert_eq!(id, "hello");
@@ -217,24 +967,1627 @@ PRETTY_NAME= wow long string!!!
     }
 }
 
+#[test]
+fn test_os_build() {
+    const BUILD_STRING: &str = "19041.1.amd64fre.vb_release.191206-1406";
+    let mut build_string = [0; 260];
+    for (dst, src) in build_string.iter_mut().zip(BUILD_STRING.encode_utf16()) {
+        *dst = src;
+    }
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.build_strings = Some(MiscFieldsBuildString {
+        build_string,
+        dbg_bld_str: [0; 40],
+    });
+    let dump = minimal_minidump().add_stream(misc);
+    let state = read_synth_dump(dump);
+
+    assert_eq!(state.system_info.os_build.as_deref(), Some(BUILD_STRING));
+    assert!(state
+        .os_description()
+        .ends_with(" (19041.1.amd64fre.vb_release.191206-1406)"));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["system_info"]["os_build"], BUILD_STRING);
+
+    // Older misc info streams don't have a build string.
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.process_id = Some(1);
+    let state = read_synth_dump(minimal_minidump().add_stream(misc));
+    assert_eq!(state.system_info.os_build, None);
+    assert_eq!(state.os_description(), state.system_info.os.long_name());
+}
+
+#[test]
+fn test_cpu_identification() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    let system_info = &state.system_info;
+    assert_eq!(system_info.cpu_vendor.as_deref(), Some("GenuineIntel"));
+    assert_eq!(system_info.cpu_family_model_stepping, Some((6, 13, 8)));
+    assert_eq!(system_info.cpu_features[..3], ["fpu", "vme", "de"]);
+    assert!(system_info.cpu_features.contains(&"sse2"));
+
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("\n     features: fpu vme de "));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["system_info"]["cpu_vendor"], "GenuineIntel");
+    assert_eq!(json["system_info"]["cpu_family"], 6);
+    assert_eq!(json["system_info"]["cpu_model"], 13);
+    assert_eq!(json["system_info"]["cpu_stepping"], 8);
+    assert_eq!(json["system_info"]["cpu_features"][0], "fpu");
+}
+
+#[test]
+fn test_dso_mismatches() {
+    let module = |name: &DumpString, base: u64| {
+        synth_minidump::Module::new(Endian::Little, base, 0x1000, name, 0, 0, None)
+    };
+    let link_map = |section: Section, addr: u64, name: Option<&DumpString>| {
+        let section = section.D64(addr);
+        let section = match name {
+            Some(name) => section.D32(name.file_offset()),
+            None => section.D32(0),
+        };
+        section.D64(0)
+    };
+
+    let libc = DumpString::new("/lib/x86_64-linux-gnu/libc.so.6", Endian::Little);
+    let libfoo = DumpString::new("/opt/app/libfoo.so", Endian::Little);
+    let libbar = DumpString::new("/opt/app/libbar.so", Endian::Little);
+    let vdso = DumpString::new("linux-vdso.so.1", Endian::Little);
+    let map = Section::with_endian(Endian::Little);
+    let map = link_map(map, 0, None);
+    let map = link_map(map, 0x7f0000000000, Some(&libc));
+    let map = link_map(map, 0x7f1000000000, Some(&libfoo));
+    let map = link_map(map, 0x7f2000000000, Some(&libbar));
+    let map = link_map(map, 0x7ffd00000000, Some(&vdso));
+    let stream = SimpleStream {
+        stream_type: minidump::format::MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+        section: Section::with_endian(Endian::Little)
+            .D32(1)
+            .D32(map.file_offset())
+            .D32(5)
+            .D64(0)
+            .D64(0)
+            .D64(0),
+    };
+
+    let dump = minimal_minidump()
+        .add_module(module(&libc, 0x7f0000000000))
+        .add_module(module(&libbar, 0x7f3000000000))
+        .add(map)
+        .add(libc)
+        .add(libfoo)
+        .add(libbar)
+        .add(vdso)
+        .add_stream(stream);
+    let state = read_synth_dump(dump);
+
+    // libc matches, and the main executable and vdso aren't checked.
+    let mismatches = state.dso_mismatches.as_ref().unwrap();
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(mismatches[0].name, "/opt/app/libfoo.so");
+    assert_eq!(mismatches[0].link_map_address, 0x7f1000000000);
+    assert_eq!(mismatches[0].module_address, None);
+    assert_eq!(mismatches[1].name, "/opt/app/libbar.so");
+    assert_eq!(mismatches[1].link_map_address, 0x7f2000000000);
+    assert_eq!(mismatches[1].module_address, Some(0x7f3000000000));
+
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "  /opt/app/libfoo.so is loaded at 0x7f1000000000, but isn't in the module list\n"
+    ));
+
+    // Nothing to check without the stream.
+    assert_eq!(read_synth_dump(minimal_minidump()).dso_mismatches, None);
+}
+
 #[test]
 fn test_linux_environ() {
-    // Whitespace intentionally wonky to test robustness
+    let input = b"LANG=en_US.UTF-8\0\
+MESA_GL_VERSION_OVERRIDE=4.5\0\
+LD_PRELOAD=/usr/lib/libhook.so\0\
+HOME=/home/me\0\
+MESA_EMPTY=\0";
+
+    // Nothing is included by default.
+    let state = read_synth_dump(minimal_minidump().set_linux_environ(input));
+    assert_eq!(state.environment, None);
+
+    let dump = Minidump::read(
+        minimal_minidump()
+            .set_linux_environ(input)
+            .finish()
+            .unwrap(),
+    )
+    .unwrap();
+    let mut options = ProcessorOptions::default();
+    options.environ_allowlist = vec!["LANG".into(), "MESA_*".into(), "LD_PRELOAD".into()];
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+        options,
+    )
+    .unwrap();
 
-    // TODO: add tests for values we care about
-    let input = b"";
+    let environment = state.environment.as_ref().unwrap();
+    assert_eq!(
+        environment.keys().collect::<Vec<_>>(),
+        [
+            "LANG",
+            "LD_PRELOAD",
+            "MESA_EMPTY",
+            "MESA_GL_VERSION_OVERRIDE"
+        ]
+    );
+    assert_eq!(environment["LD_PRELOAD"], "/usr/lib/libhook.so");
+    assert_eq!(environment["MESA_EMPTY"], "");
 
-    let dump = minimal_minidump().set_linux_environ(input);
-    let _state = read_synth_dump(dump);
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Environment: LD_PRELOAD=/usr/lib/libhook.so\n"));
+    assert!(!output.contains("HOME"));
 }
 
 #[test]
-fn test_linux_proc_status() {
-    // Whitespace intentionally wonky to test robustness
+fn test_thread_names() {
+    let name = DumpString::new("RenderThread", Endian::Little);
+    let dump = minimal_minidump()
+        .add_thread_name(ThreadName::new(Endian::Little, 0x1234, Some(&name)))
+        .add(name);
+    let state = read_synth_dump(dump);
+    assert_eq!(
+        state.threads[0].thread_name.as_deref(),
+        Some("RenderThread")
+    );
 
-    // TODO: add tests for values we care about
-    let input = b"";
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Thread 0 RenderThread\n"));
 
-    let dump = minimal_minidump().set_linux_proc_status(input);
-    let _state = read_synth_dump(dump);
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["threads"][0]["thread_name"], "RenderThread");
+
+    // Threads without a name just don't get one.
+    let state = read_synth_dump(minimal_minidump());
+    assert_eq!(state.threads[0].thread_name, None);
+}
+
+#[test]
+fn test_crashpad_info() {
+    let module = ModuleCrashpadInfo::new(3, Endian::Little)
+        .add_list_annotation("abort() called")
+        .add_simple_annotation("ProductVersion", "1.2.3")
+        .add_annotation_object(
+            "abort_message",
+            AnnotationValue::String("assertion failed: x > 0".to_owned()),
+        )
+        .add_annotation_object("invalid", AnnotationValue::Invalid)
+        .add_annotation_object("blob", AnnotationValue::Custom(0x8001, vec![1, 2, 3]));
+    let crashpad_info = CrashpadInfo::new(Endian::Little)
+        .add_module(module)
+        .add_module(ModuleCrashpadInfo::new(4, Endian::Little))
+        .add_simple_annotation("channel", "beta");
+    let state = read_synth_dump(minimal_minidump().add_crashpad_info(crashpad_info));
+
+    let info = state.crashpad_info.as_ref().unwrap();
+    assert_eq!(info.report_id(), None);
+    assert_eq!(info.simple_annotations["channel"], "beta");
+    assert_eq!(info.module_list.len(), 2);
+
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "Crashpad Info:
+  channel = beta
+  module 3:
+    abort() called
+    ProductVersion = 1.2.3
+    abort_message = assertion failed: x > 0
+    blob = <binary>
+    invalid = <invalid>
+
+"
+    ));
+    // Modules without annotations are left out.
+    assert!(!output.contains("module 4"));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let info = &json["crashpad_info"];
+    assert_eq!(info["report_id"], serde_json::Value::Null);
+    assert_eq!(info["simple_annotations"]["channel"], "beta");
+    let module_info = info["module_info"].as_array().unwrap();
+    assert_eq!(module_info.len(), 2);
+    assert_eq!(module_info[0]["module_index"], 3);
+    assert_eq!(module_info[0]["module"], serde_json::Value::Null);
+    assert_eq!(
+        module_info[0]["annotation_objects"],
+        serde_json::json!({
+            "abort_message": "assertion failed: x > 0",
+            "blob": null,
+            "invalid": null,
+        })
+    );
+    assert_eq!(module_info[1]["list_annotations"], serde_json::json!([]));
+
+    // The real thing, with modules that are in the module list.
+    let path = locate_testdata().join("simple-crashpad.dmp");
+    let dump = Minidump::read_path(path).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("  report id: 42f9de72-518a-43dd-97d7-8ddc328d3662\n"));
+    assert!(output.contains("  libsystem_c.dylib:\n    abort() called\n"));
+}
+
+#[test]
+fn test_mac_crash_info() {
+    let stream_type = minidump::format::MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32;
+    let record = Section::with_endian(Endian::Little)
+        .D64(stream_type as u64)
+        .D64(5) // version
+        .D64(0x103) // thread
+        .D64(0) // dialog_mode
+        .D64(0x6) // abort_cause
+        .append_bytes(b"/usr/lib/libc++abi.dylib\0terminating with uncaught exception\0\0\0");
+    let section = Section::with_endian(Endian::Little)
+        .D32(stream_type)
+        .D32(1) // record_count
+        .D32(40) // record_start_size
+        .cite_location(&record)
+        .append_repeated(0, 19 * 8);
+    let dump = minimal_minidump()
+        .add_stream(SimpleStream {
+            stream_type,
+            section,
+        })
+        .add(record);
+    let state = read_synth_dump(dump);
+
+    assert_eq!(state.mac_crash_info.as_ref().unwrap().len(), 1);
+    // The record was cut off before message2, which is fine.
+    assert_eq!(
+        state.mac_crash_messages(),
+        vec!["terminating with uncaught exception"]
+    );
+
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Mac Crash Info:\n  Record 0\n    thread: 0x103\n"));
+    assert!(output.contains("    message: terminating with uncaught exception\n"));
+
+    // Other dumps don't have any.
+    let state = read_synth_dump(minimal_minidump());
+    assert!(state.mac_crash_messages().is_empty());
+}
+
+#[test]
+fn test_linux_proc_status() {
+    let input = b"Name:\tfirefox
+Pid:\t4183
+VmPeak:\t 21689408 kB
+VmSize:\t 21153684 kB
+VmRSS:\t  1992296 kB
+VmSwap:\t       0 kB
+";
+
+    let dump = minimal_minidump().set_linux_proc_status(input);
+    let state = read_synth_dump(dump);
+
+    assert_eq!(state.process_name.as_deref(), Some("firefox"));
+    let usage = state.memory_usage.as_ref().unwrap();
+    assert_eq!(usage.peak_virtual_size, Some(21689408 * 1024));
+    assert_eq!(usage.virtual_size, Some(21153684 * 1024));
+    assert_eq!(usage.resident_size, Some(1992296 * 1024));
+    assert_eq!(usage.swap_size, Some(0));
+
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Process name: firefox\nRSS at crash: 1.9 GiB\n"));
+
+    let mut json = vec![];
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["process_name"], "firefox");
+    assert_eq!(json["memory_usage"]["resident_size"], 1992296u64 * 1024);
+
+    // Nothing to report without the stream.
+    let state = read_synth_dump(minimal_minidump());
+    assert_eq!(state.process_name, None);
+    assert_eq!(state.memory_usage, None);
+}
+
+#[test]
+fn test_handle_summary() {
+    let event = DumpString::new("Event", Endian::Little);
+    let file = DumpString::new("File", Endian::Little);
+    let mut section = Section::with_endian(Endian::Little)
+        .D32(16) // size_of_header
+        .D32(32) // size_of_descriptor
+        .D32(4) // number_of_descriptors
+        .D32(0); // reserved
+    for (handle, type_name) in [
+        (0x4, Some(&event)),
+        (0x8, Some(&event)),
+        (0xc, Some(&file)),
+        (0x10, None),
+    ] {
+        section = section.D64(handle);
+        section = match type_name {
+            Some(name) => section.D32(name.file_offset()),
+            None => section.D32(0),
+        };
+        section = section.D32(0).D32(0).D32(0).D32(1).D32(1);
+    }
+    let dump = minimal_minidump()
+        .add_stream(SimpleStream {
+            stream_type: minidump::format::MINIDUMP_STREAM_TYPE::HandleDataStream as u32,
+            section,
+        })
+        .add(event)
+        .add(file);
+    let state = read_synth_dump(dump);
+
+    let summary = state.handle_summary.as_ref().unwrap();
+    assert_eq!(summary.total, 4);
+    assert_eq!(summary.by_type["Event"], 2);
+    assert_eq!(summary.by_type["File"], 1);
+    assert_eq!(summary.by_type["Unknown"], 1);
+
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Open handles: 4 (Event: 2, File: 1, Unknown: 1)\n"));
+
+    // No stream, no summary.
+    let state = read_synth_dump(minimal_minidump());
+    assert_eq!(state.handle_summary, None);
+}
+
+fn windows_module_minidump(platform_id: u32) -> SynthMinidump {
+    let name = DumpString::new("C:\\WINDOWS\\system32\\KERNEL32.DLL", Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little)
+        // "RSDS" signature
+        .D32(0x53445352)
+        // GUID
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08])
+        // age
+        .D32(1)
+        .append_bytes(b"KERNEL32.PDB\0");
+    let module = synth_minidump::Module::new(
+        Endian::Little,
+        0x7c800000,
+        0x10000,
+        &name,
+        0xb1054a2a,
+        0x0000f8b4,
+        None,
+    )
+    .cv_record(&cv_record);
+
+    let context = synth_minidump::x86_context(Endian::Little, 0x7c801010, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let mut system_info = SystemInfo::new(Endian::Little);
+    system_info.platform_id = platform_id;
+    SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add(name)
+        .add(cv_record)
+        .add(context)
+        .add_memory(stack)
+}
+
+#[test]
+fn test_calling_convention() {
+    let t = TempDir::new("symtest").unwrap();
+    let sym_path = t
+        .path()
+        .join("kernel32.pdb/ABCD1234F00DBEEF01020304050607081/kernel32.sym");
+    fs::create_dir_all(sym_path.parent().unwrap()).unwrap();
+
+    let calling_convention = |record: &str| {
+        fs::write(
+            &sym_path,
+            format!(
+                "MODULE windows x86 ABCD1234F00DBEEF01020304050607081 kernel32.pdb\n{}\n",
+                record
+            ),
+        )
+        .unwrap();
+        let dump = Minidump::read(windows_module_minidump(3).finish().unwrap()).unwrap();
+        let state = minidump_processor::process_minidump(
+            &dump,
+            &Symbolizer::new(simple_symbol_supplier(vec![t.path().to_path_buf()])),
+        )
+        .unwrap();
+        state.threads[0].frames[0].calling_convention
+    };
+
+    assert_eq!(
+        calling_convention("PUBLIC 1000 8 _Sleep@8"),
+        Some(CallingConvention::Stdcall)
+    );
+    assert_eq!(
+        calling_convention("PUBLIC 1000 8 @Sleep@8"),
+        Some(CallingConvention::Fastcall)
+    );
+    // The decoration disagrees with the parameter size.
+    assert_eq!(calling_convention("PUBLIC 1000 4 _Sleep@8"), None);
+    assert_eq!(
+        calling_convention("FUNC 1000 20 4 ?bar@foo@@QAEHH@Z"),
+        Some(CallingConvention::Thiscall)
+    );
+    assert_eq!(
+        calling_convention("FUNC 1000 20 4 ?bar@@YAHH@Z"),
+        Some(CallingConvention::Cdecl)
+    );
+    // Undecorated names could be anything.
+    assert_eq!(
+        calling_convention("FUNC 1000 20 4 BaseThreadInitThunk"),
+        None
+    );
+
+    assert_eq!(
+        CallingConvention::infer("_WinMain@16", None),
+        Some(CallingConvention::Stdcall)
+    );
+    assert_eq!(CallingConvention::infer("user@example.com", None), None);
+    assert_eq!(CallingConvention::infer("_@4", None), None);
+}
+
+#[test]
+fn test_windows_case_insensitive_symbols() {
+    let t = TempDir::new("symtest").unwrap();
+    let sym_path = t
+        .path()
+        .join("kernel32.pdb/ABCD1234F00DBEEF01020304050607081/kernel32.sym");
+    fs::create_dir_all(sym_path.parent().unwrap()).unwrap();
+    fs::write(
+        &sym_path,
+        b"MODULE windows x86 ABCD1234F00DBEEF01020304050607081 kernel32.pdb
+FUNC 1000 20 0 BaseThreadInitThunk
+",
+    )
+    .unwrap();
+
+    let process = |platform_id: u32| {
+        let dump = Minidump::read(windows_module_minidump(platform_id).finish().unwrap()).unwrap();
+        minidump_processor::process_minidump(
+            &dump,
+            &Symbolizer::new(simple_symbol_supplier(vec![t.path().to_path_buf()])),
+        )
+        .unwrap()
+    };
+
+    // VER_PLATFORM_WIN32_NT: the uppercase module name has lowercase symbols.
+    let state = process(3);
+    let f0 = &state.threads[0].frames[0];
+    assert_eq!(
+        f0.module.as_ref().unwrap().debug_file().unwrap(),
+        "KERNEL32.PDB"
+    );
+    assert_eq!(f0.function_name.as_deref(), Some("BaseThreadInitThunk"));
+    let stats = &state.symbol_stats["KERNEL32.DLL"];
+    assert!(stats.loaded_symbols);
+
+    // Linux file names are case-sensitive, so no symbols should be found.
+    let state = process(0x8201);
+    let f0 = &state.threads[0].frames[0];
+    assert_eq!(f0.function_name, None);
+}
+
+#[test]
+fn test_windows_case_folding_only_retries_missing_symbols() {
+    use minidump_processor::{
+        FillSymbolError, FrameSymbolizer, FrameWalker, SymbolProvider, SymbolStats,
+    };
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    // Records the debug file of every module symbols are requested for. Without
+    // a symbolizer, every lookup times out.
+    struct LookupRecorder {
+        symbolizer: Option<Symbolizer>,
+        lookups: RefCell<Vec<String>>,
+    }
+    impl SymbolProvider for LookupRecorder {
+        fn fill_symbol(
+            &self,
+            module: &dyn Module,
+            frame: &mut dyn FrameSymbolizer,
+        ) -> Result<(), FillSymbolError> {
+            let debug_file = module.debug_file().unwrap_or_default().into_owned();
+            self.lookups.borrow_mut().push(debug_file);
+            match &self.symbolizer {
+                Some(symbolizer) => symbolizer.fill_symbol(module, frame),
+                None => Err(FillSymbolError {}),
+            }
+        }
+        fn walk_frame(&self, module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()> {
+            self.symbolizer.as_ref()?.walk_frame(module, walker)
+        }
+        fn stats(&self) -> HashMap<String, SymbolStats> {
+            HashMap::new()
+        }
+        fn symbol_error(&self, module: &dyn Module) -> Option<SymbolError> {
+            match &self.symbolizer {
+                Some(symbolizer) => symbolizer.symbol_error(module),
+                None => Some(SymbolError::Timeout),
+            }
+        }
+    }
+
+    let lookups = |sym_path: &str, timed_out: bool| {
+        let t = TempDir::new("symtest").unwrap();
+        let sym_path = t.path().join(sym_path);
+        fs::create_dir_all(sym_path.parent().unwrap()).unwrap();
+        // Nothing covers the crashing address.
+        fs::write(
+            &sym_path,
+            b"MODULE windows x86 ABCD1234F00DBEEF01020304050607081 KERNEL32.PDB
+FUNC 4000 20 0 BaseThreadInitThunk
+",
+        )
+        .unwrap();
+
+        let dump = Minidump::read(windows_module_minidump(3).finish().unwrap()).unwrap();
+        let provider = LookupRecorder {
+            symbolizer: (!timed_out)
+                .then(|| Symbolizer::new(simple_symbol_supplier(vec![t.path().to_path_buf()]))),
+            lookups: RefCell::default(),
+        };
+        let state = minidump_processor::process_minidump(&dump, &provider).unwrap();
+        assert_eq!(state.threads[0].frames[0].function_name, None);
+        provider.lookups.into_inner()
+    };
+
+    // The symbol file was found, it just doesn't have the address.
+    let path = "KERNEL32.PDB/ABCD1234F00DBEEF01020304050607081/KERNEL32.sym";
+    let found = lookups(path, false);
+    assert!(!found.is_empty());
+    assert!(found.iter().all(|debug_file| debug_file == "KERNEL32.PDB"));
+
+    // Retrying after a timeout would just wait all over again.
+    let timed_out = lookups(path, true);
+    assert!(!timed_out.is_empty());
+    assert!(timed_out
+        .iter()
+        .all(|debug_file| debug_file == "KERNEL32.PDB"));
+
+    // The symbol file isn't there under either name, so it's retried.
+    let missing = lookups(
+        "other.pdb/ABCD1234F00DBEEF01020304050607081/other.sym",
+        false,
+    );
+    assert!(missing
+        .iter()
+        .any(|debug_file| debug_file == "kernel32.pdb"));
+}
+
+#[test]
+fn test_thread_start_routine() {
+    let t = TempDir::new("symtest").unwrap();
+    let sym_path = t
+        .path()
+        .join("KERNEL32.PDB/ABCD1234F00DBEEF01020304050607081/KERNEL32.sym");
+    fs::create_dir_all(sym_path.parent().unwrap()).unwrap();
+    fs::write(
+        &sym_path,
+        b"MODULE windows x86 ABCD1234F00DBEEF01020304050607081 KERNEL32.PDB
+FUNC 1000 20 0 BaseThreadInitThunk
+",
+    )
+    .unwrap();
+
+    let info =
+        synth_minidump::ThreadInfo::new(Endian::Little, 0x1234, 0, 15_000_000, 0, 0x7c801004);
+    let dump = windows_module_minidump(3).add_thread_info(info);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![t.path().to_path_buf()])),
+    )
+    .unwrap();
+
+    let stack = &state.threads[0];
+    let routine = stack.start_routine.as_ref().unwrap();
+    assert_eq!(routine.address, 0x7c801004);
+    assert_eq!(
+        routine.function_name.as_deref(),
+        Some("BaseThreadInitThunk")
+    );
+    assert_eq!(routine.function_base, Some(0x7c801000));
+    assert_eq!(
+        stack.kernel_time,
+        Some(std::time::Duration::from_millis(1500))
+    );
+    assert_eq!(stack.user_time, Some(std::time::Duration::from_secs(0)));
+
+    let mut output = vec![];
+    stack.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Start routine: KERNEL32.DLL!BaseThreadInitThunk + 0x4\n"));
+
+    // Without the stream, there's nothing to report.
+    let dump = Minidump::read(windows_module_minidump(3).finish().unwrap()).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![t.path().to_path_buf()])),
+    )
+    .unwrap();
+    assert!(state.threads[0].start_routine.is_none());
+    assert_eq!(state.threads[0].kernel_time, None);
+}
+
+#[test]
+fn test_vcs_source_links() {
+    let process = |file: &str, options: ProcessorOptions| {
+        let t = TempDir::new("symtest").unwrap();
+        let sym_path = t
+            .path()
+            .join("KERNEL32.PDB/ABCD1234F00DBEEF01020304050607081/KERNEL32.sym");
+        fs::create_dir_all(sym_path.parent().unwrap()).unwrap();
+        fs::write(
+            &sym_path,
+            format!(
+                "MODULE windows x86 ABCD1234F00DBEEF01020304050607081 KERNEL32.PDB
+FILE 7 {}
+FUNC 1000 20 0 BaseThreadInitThunk
+1000 20 42 7
+",
+                file
+            ),
+        )
+        .unwrap();
+
+        let dump = Minidump::read(windows_module_minidump(3).finish().unwrap()).unwrap();
+        minidump_processor::process_minidump_with_options(
+            &dump,
+            &Symbolizer::new(simple_symbol_supplier(vec![t.path().to_path_buf()])),
+            options,
+        )
+        .unwrap()
+    };
+
+    let hg_file = "hg:hg.mozilla.org/mozilla-central:dom/base/nsDocument.cpp:abcdef0123";
+    let git_file = "git:github.com/rust-minidump/rust-minidump:minidump/src/lib.rs:9876fedcba";
+    let plain_file = "/home/john/my_project/src/main.cpp";
+    let windows_file = r"c:\src\my_project\main.cpp";
+    let cases = [
+        (
+            hg_file,
+            "dom/base/nsDocument.cpp",
+            Some("https://hg.mozilla.org/mozilla-central/annotate/abcdef0123/dom/base/nsDocument.cpp#l42"),
+        ),
+        (
+            git_file,
+            "minidump/src/lib.rs",
+            Some("https://github.com/rust-minidump/rust-minidump/blob/9876fedcba/minidump/src/lib.rs#L42"),
+        ),
+        (plain_file, plain_file, None),
+        (windows_file, windows_file, None),
+    ];
+
+    for &(file, path, link) in &cases {
+        let state = process(file, ProcessorOptions::default());
+        let f0 = &state.threads[0].frames[0];
+        assert_eq!(f0.source_file_name.as_deref(), Some(file));
+        assert_eq!(f0.source_file_path.as_deref(), Some(path));
+        assert_eq!(f0.source_link.as_deref(), link);
+
+        // The text output only shows the plain path, the json has the link.
+        let mut text = Vec::new();
+        state.print(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        let basename = path.rsplit(['/', '\\']).next().unwrap();
+        assert!(text.contains(&format!("[{} : 42 + 0x10]", basename)));
+
+        let mut json = Vec::new();
+        state.print_json(&mut json, false).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let frame = &json["threads"][0]["frames"][0];
+        assert_eq!(frame["file"], file);
+        assert_eq!(frame["source_link"].as_str(), link);
+    }
+
+    // Templates can be replaced or removed per-VCS.
+    let mut options = ProcessorOptions::default();
+    options.source_link_templates.set(
+        "hg",
+        "https://example.com/{revision}/{path}?line={line}&repo={repo}",
+    );
+    options.source_link_templates.remove("git");
+    let state = process(hg_file, options.clone());
+    assert_eq!(
+        state.threads[0].frames[0].source_link.as_deref(),
+        Some("https://example.com/abcdef0123/dom/base/nsDocument.cpp?line=42&repo=hg.mozilla.org/mozilla-central")
+    );
+    let state = process(git_file, options);
+    assert_eq!(
+        state.threads[0].frames[0].source_file_path.as_deref(),
+        Some("minidump/src/lib.rs")
+    );
+    assert_eq!(state.threads[0].frames[0].source_link, None);
+}
+
+#[test]
+fn test_symbol_timeout() {
+    // A symbol server that accepts connections but never responds.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let _stalled = listener.incoming().collect::<Vec<_>>();
+    });
+
+    let tmp = TempDir::new("symbol-timeout").unwrap();
+    let cache = tmp.path().join("cache");
+    let download = tmp.path().join("tmp");
+    fs::create_dir_all(&download).unwrap();
+    // The supplier's own deadline is far too long for this test to finish
+    // in time, so this also checks that ProcessorOptions overrides it.
+    let provider = Symbolizer::new(http_symbol_supplier(
+        vec![],
+        vec![url],
+        cache,
+        download,
+        std::time::Duration::from_secs(1000),
+    ));
+    let mut options = ProcessorOptions::default();
+    options.symbol_timeout = std::time::Duration::from_millis(100);
+
+    let dump = read_test_minidump().unwrap();
+    let start = std::time::Instant::now();
+    let state =
+        minidump_processor::process_minidump_with_options(&dump, &provider, options).unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_secs(60));
+
+    // The walk still proceeds, just without the help of symbols.
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 4);
+    assert!(frames.iter().all(|f| f.function_name.is_none()));
+    assert!(frames.iter().all(|f| f.trust != FrameTrust::CallFrameInfo));
+    assert_eq!(frames[0].symbol_error, Some(SymbolError::Timeout));
+
+    let stats = &state.symbol_stats["test_app.exe"];
+    assert!(stats.timed_out);
+    assert!(!stats.loaded_symbols);
+}
+
+#[test]
+fn test_symbol_timeout_is_restored() {
+    use minidump_processor::{
+        FillSymbolError, FrameSymbolizer, FrameWalker, SymbolProvider, SymbolStats,
+    };
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct TimeoutRecorder {
+        timeouts: RefCell<Vec<Option<Duration>>>,
+    }
+    impl SymbolProvider for TimeoutRecorder {
+        fn fill_symbol(
+            &self,
+            _module: &dyn Module,
+            _frame: &mut dyn FrameSymbolizer,
+        ) -> Result<(), FillSymbolError> {
+            Err(FillSymbolError {})
+        }
+        fn walk_frame(&self, _module: &dyn Module, _walker: &mut dyn FrameWalker) -> Option<()> {
+            None
+        }
+        fn stats(&self) -> HashMap<String, SymbolStats> {
+            HashMap::new()
+        }
+        fn set_symbol_timeout(&self, timeout: Option<Duration>) {
+            self.timeouts.borrow_mut().push(timeout);
+        }
+        fn symbol_timeout(&self) -> Option<Duration> {
+            self.timeouts.borrow().last().copied().flatten()
+        }
+    }
+
+    let dump = read_test_minidump().unwrap();
+    let provider = TimeoutRecorder::default();
+    minidump_processor::process_minidump(&dump, &provider).unwrap();
+    assert_eq!(
+        *provider.timeouts.borrow(),
+        vec![Some(Duration::from_secs(30)), None]
+    );
+
+    // The override is removed again once processing is done.
+    let provider = TimeoutRecorder::default();
+    let mut options = ProcessorOptions::default();
+    options.symbol_timeout = Duration::from_secs(5);
+    minidump_processor::process_minidump_with_options(&dump, &provider, options.clone()).unwrap();
+    assert_eq!(
+        *provider.timeouts.borrow(),
+        vec![Some(Duration::from_secs(5)), None]
+    );
+
+    // An override the caller had already set is put back, not removed.
+    let provider = TimeoutRecorder::default();
+    provider.set_symbol_timeout(Some(Duration::from_secs(60)));
+    minidump_processor::process_minidump_with_options(&dump, &provider, options).unwrap();
+    assert_eq!(
+        *provider.timeouts.borrow(),
+        vec![
+            Some(Duration::from_secs(60)),
+            Some(Duration::from_secs(5)),
+            Some(Duration::from_secs(60))
+        ]
+    );
+}
+
+#[test]
+fn test_function_table_unwind() {
+    let dump = |with_table: bool, rsp: u64| {
+        // Some JIT code at 0x10000000 with two functions, crashing at 0x10000180.
+        let code_base = 0x1000_0000;
+        let table = FunctionTable::new(
+            Endian::Little,
+            code_base,
+            code_base + 0x200,
+            code_base,
+            &[(0x0, 0x100, 0x1000), (0x100, 0x200, 0x1010)],
+        );
+        // UNWIND_INFO for the second function: push rbp; sub rsp, 0x20
+        let unwind_info = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .append_bytes(&[0x01, 0x05, 0x02, 0x00])
+                .D16(0x3205) // UWOP_ALLOC_SMALL 0x20 at offset 5
+                .D16(0x5001), // UWOP_PUSH_NONVOL rbp at offset 1
+            code_base + 0x1010,
+        );
+
+        let name = DumpString::new("C:\\WINDOWS\\system32\\KERNEL32.DLL", Endian::Little);
+        let module = synth_minidump::Module::new(
+            Endian::Little,
+            0x7c800000,
+            0x10000,
+            &name,
+            0xb1054a2a,
+            0x0000f8b4,
+            None,
+        );
+
+        let context = synth_minidump::amd64_context(Endian::Little, code_base + 0x180, rsp);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .append_repeated(0, 0x20) // locals
+                .D64(0x10100) // saved rbp
+                .D64(0x7c801005) // return address
+                .append_repeated(0, 0x1000),
+            0x10000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+            minidump::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add_system_info(system_info)
+            .add_module(module)
+            .add(name)
+            .add(context)
+            .add_memory(stack)
+            .add_memory(unwind_info);
+        if with_table {
+            dump.add_function_table(table)
+        } else {
+            dump
+        }
+    };
+
+    let state = read_synth_dump(dump(true, 0x10000));
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[1].trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frames[1].instruction, 0x7c801004);
+    let context = match &frames[1].context.raw {
+        MinidumpRawContext::Amd64(context) => context,
+        _ => panic!("expected an amd64 context"),
+    };
+    assert_eq!(context.rsp, 0x10030);
+    assert_eq!(context.rbp, 0x10100);
+
+    // Without the table we have to fall back to scanning.
+    let state = read_synth_dump(dump(false, 0x10000));
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[1].trust, FrameTrust::Scan);
+
+    // Applying the unwind codes to a garbage rsp would overflow, which must
+    // just make the function table unusable, not panic.
+    let state = read_synth_dump(dump(true, u64::MAX - 0x10));
+    let frames = &state.threads[0].frames;
+    assert!(frames
+        .iter()
+        .skip(1)
+        .all(|frame| frame.trust != FrameTrust::CallFrameInfo));
+}
+
+#[test]
+fn test_unloaded_module_frames() {
+    let many = DumpString::new("many.dll", Endian::Little);
+    let solo = DumpString::new("solo.dll", Endian::Little);
+    let context = synth_minidump::x86_context(Endian::Little, 0x10001234, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack)
+        // Two overlapping loads of many.dll, and an unrelated solo.dll.
+        .add_unloaded_module(UnloadedModule::new(
+            Endian::Little,
+            0x10000000,
+            0x2000,
+            &many,
+            1,
+            0,
+        ))
+        .add_unloaded_module(UnloadedModule::new(
+            Endian::Little,
+            0x10001000,
+            0x2000,
+            &many,
+            1,
+            0,
+        ))
+        .add_unloaded_module(UnloadedModule::new(
+            Endian::Little,
+            0x20000000,
+            0x1000,
+            &solo,
+            2,
+            0,
+        ))
+        .add(many)
+        .add(solo);
+    let state = read_synth_dump(dump);
+
+    let frame = &state.threads[0].frames[0];
+    assert!(frame.module.is_none());
+    assert_eq!(frame.unloaded_modules.len(), 1);
+    assert_eq!(
+        frame.unloaded_modules["many.dll"]
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![&0x234, &0x1234]
+    );
+
+    let mut output = vec![];
+    state.threads[0].print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(" 0  0x10001234 (unloaded) many.dll + 0x234|0x1234\n"));
+
+    let mut json = vec![];
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let frame = &json["threads"][0]["frames"][0];
+    assert_eq!(frame["module"], "many.dll");
+    assert_eq!(frame["unloaded_modules"][0]["module"], "many.dll");
+    assert_eq!(
+        frame["unloaded_modules"][0]["offsets"],
+        serde_json::json!(["0x00000234", "0x00001234"])
+    );
+}
+
+#[test]
+fn test_minidump_diff() {
+    // Comparing a minidump to itself finds nothing.
+    let dump = read_test_minidump().unwrap();
+    let diff = minidump_processor::minidump_diff(&dump, &dump).unwrap();
+    assert!(diff.is_empty());
+    assert_eq!(diff.thread_counts, (2, 2));
+    assert_eq!(diff.stack_similarity, 1.0);
+
+    let old = Minidump::read(minimal_minidump().finish().unwrap()).unwrap();
+    let new = Minidump::read(windows_module_minidump(3).finish().unwrap()).unwrap();
+    let diff = minidump_processor::minidump_diff(&old, &new).unwrap();
+    assert!(!diff.is_empty());
+    assert_eq!(diff.added_modules, vec!["KERNEL32.DLL"]);
+    assert!(diff.removed_modules.is_empty());
+    assert!(diff.changed_modules.is_empty());
+    assert_eq!(diff.thread_counts, (1, 1));
+    // The only frames are in completely different places.
+    assert_eq!(diff.stack_similarity, 0.0);
+
+    let text = diff.to_text();
+    assert!(text.starts_with("Modules:\n  + KERNEL32.DLL\nThreads: 1 -> 1\n"));
+    assert!(text.ends_with("Crashing thread stack similarity: 0%\n"));
+
+    let json = diff.to_json();
+    assert_eq!(
+        json["modules"]["added"],
+        serde_json::json!(["KERNEL32.DLL"])
+    );
+    assert_eq!(json["thread_count"], serde_json::json!([1, 1]));
+
+    // The same modules, with different versions.
+    let versioned = |file_version_hi: u32| {
+        let name = DumpString::new("C:\\WINDOWS\\system32\\KERNEL32.DLL", Endian::Little);
+        let version_info = minidump::format::VS_FIXEDFILEINFO {
+            file_version_hi,
+            file_version_lo: 0x0001_0002,
+            ..STOCK_VERSION_INFO
+        };
+        let module = synth_minidump::Module::new(
+            Endian::Little,
+            0x7c800000,
+            0x10000,
+            &name,
+            0xb1054a2a,
+            0x0000f8b4,
+            Some(&version_info),
+        );
+        let dump = minimal_minidump().add_module(module).add(name);
+        Minidump::read(dump.finish().unwrap()).unwrap()
+    };
+    let diff = minidump_processor::minidump_diff(&versioned(0x000a_0000), &versioned(0x0009_0005))
+        .unwrap();
+    assert_eq!(diff.changed_modules.len(), 1);
+    let change = &diff.changed_modules[0];
+    assert_eq!(change.name, "KERNEL32.DLL");
+    assert_eq!(change.old_version.as_deref(), Some("10.0.1.2"));
+    assert_eq!(change.new_version.as_deref(), Some("9.5.1.2"));
+    // Numerically, not alphabetically.
+    assert_eq!(change.is_upgrade, Some(false));
+    assert!(diff
+        .to_text()
+        .contains("  ~ KERNEL32.DLL: 10.0.1.2 -> 9.5.1.2 (downgrade)\n"));
+    assert_eq!(diff.to_json()["modules"]["changed"][0]["is_upgrade"], false);
+
+    let diff = minidump_processor::minidump_diff(&versioned(0x000a_0000), &versioned(0x000a_0000))
+        .unwrap();
+    assert!(diff.changed_modules.is_empty());
+}
+
+#[test]
+fn test_crash_address_description() {
+    let dump = |access: u64| {
+        let mut exception = Exception::new(Endian::Little);
+        exception.thread_id = 0x1234;
+        exception.exception_record.exception_code = 0xc0000005; // EXCEPTION_ACCESS_VIOLATION
+        exception.exception_record.number_parameters = 2;
+        exception.exception_record.exception_information[0] = access;
+        exception.exception_record.exception_information[1] = 0x50000010;
+        let info = synth_minidump::MemoryInfo::new(
+            Endian::Little,
+            0x50000000,
+            0x50000000,
+            minidump::format::MemoryProtection::PAGE_READWRITE.bits(),
+            0x1000,
+            minidump::format::MemoryState::MEM_COMMIT.bits(),
+            minidump::format::MemoryProtection::PAGE_READONLY.bits(),
+            minidump::format::MemoryType::MEM_PRIVATE.bits(),
+        );
+        windows_module_minidump(3)
+            .add_exception(exception)
+            .add_memory_info(info)
+    };
+
+    let state = read_synth_dump(dump(1));
+    assert_eq!(state.crash_address, Some(0x50000010));
+    assert_eq!(
+        state.crash_address_description.as_deref(),
+        Some("write to a PAGE_READONLY committed region")
+    );
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("Crash address: 0x50000010 (write to a PAGE_READONLY committed region)\n")
+    );
+
+    let state = read_synth_dump(dump(0));
+    assert_eq!(
+        state.crash_address_description.as_deref(),
+        Some("read from a PAGE_READONLY committed region")
+    );
+
+    // Nothing to say without memory info.
+    let state = read_synth_dump(windows_module_minidump(3));
+    assert_eq!(state.crash_address_description, None);
+}
+
+#[test]
+fn test_crash_address_description_linux_maps() {
+    let dump = |address: u64| {
+        let mut exception = Exception::new(Endian::Little);
+        exception.thread_id = 0x1234;
+        exception.exception_record.exception_code = 11; // SIGSEGV
+        exception.exception_record.exception_address = address;
+        // The thread's stack pointer is 0x1010.
+        let maps = b"\
+00000000-00001000 ---p 00000000 00:00 0
+00001000-00003000 rw-p 00000000 00:00 0                [stack]
+7c800000-7c810000 r-xp 00000000 fd:01 1234             /lib/libc.so.6
+";
+        windows_module_minidump(0x8201) // Linux
+            .add_exception(exception)
+            .set_linux_maps(maps)
+    };
+
+    let state = read_synth_dump(dump(0x2ff8));
+    assert_eq!(
+        state.crash_address_description.as_deref(),
+        Some("in the main thread's rw-p stack (the crashing thread's stack)")
+    );
+
+    // Just below the stack.
+    let state = read_synth_dump(dump(0xff8));
+    assert_eq!(
+        state.crash_address_description.as_deref(),
+        Some("in an anonymous ---p mapping")
+    );
+
+    let state = read_synth_dump(dump(0x7c801000));
+    assert_eq!(
+        state.crash_address_description.as_deref(),
+        Some("in a r-xp mapping of /lib/libc.so.6")
+    );
+}
+
+#[test]
+fn test_crashed_address() {
+    let dump = |platform_id: u32, code: u32, address: u64| {
+        let mut exception = Exception::new(Endian::Little);
+        exception.thread_id = 0x1234;
+        exception.exception_record.exception_code = code;
+        exception.exception_record.exception_flags = 1;
+        exception.exception_record.exception_address = address;
+        exception.exception_record.number_parameters = 2;
+        exception.exception_record.exception_information[1] = 0x50000010;
+        windows_module_minidump(platform_id).add_exception(exception)
+    };
+
+    // Windows access violations fault on the address in the parameters.
+    let state = read_synth_dump(dump(3, 0xc0000005, 0x7c801000));
+    assert_eq!(state.crashed_address(), Some(0x50000010));
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Crash address: 0x50000010\nFault address: 0x50000010\n"));
+
+    // SIGSEGV, and EXC_BAD_ACCESS / KERN_INVALID_ADDRESS.
+    let state = read_synth_dump(dump(0x8201, 11, 0x1000));
+    assert_eq!(state.crashed_address(), Some(0x1000));
+    let state = read_synth_dump(dump(0x8101, 1, 0x1000));
+    assert_eq!(state.crashed_address(), Some(0x1000));
+
+    // A breakpoint and an abort aren't faults, even though they have an address.
+    let state = read_synth_dump(dump(3, 0x80000003, 0x7c801000));
+    assert_eq!(state.crash_address, Some(0x7c801000));
+    assert_eq!(state.crashed_address(), None);
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Crash reason:  EXCEPTION_BREAKPOINT\nCrash address: 0x7c801000\n"));
+    assert!(!output.contains("Fault address:"));
+
+    let state = read_synth_dump(dump(0x8201, 6, 0x7c801000));
+    assert_eq!(state.crashed_address(), None);
+}
+
+#[test]
+fn test_crash_mxcsr() {
+    let dump = |platform_id: u32, code: u32, flags: u32| {
+        let mut exception = Exception::new(Endian::Little);
+        exception.thread_id = 0x1234;
+        exception.exception_record.exception_code = code;
+        exception.exception_record.exception_flags = flags;
+        windows_module_minidump(platform_id).add_exception(exception)
+    };
+    // The thread's context has its extended registers, but they're all zero.
+    const MXCSR: &str = "0x0 (raised: none; unmasked: IE DE ZE OE UE PE; rounding: nearest)";
+
+    // EXCEPTION_FLT_DIVIDE_BY_ZERO
+    let state = read_synth_dump(dump(3, 0xc000008e, 0));
+    assert_eq!(state.crash_mxcsr.as_deref(), Some(MXCSR));
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(&format!("Crash MXCSR:   {}\n", MXCSR)));
+
+    // SIGFPE / FPE_FLTDIV
+    let state = read_synth_dump(dump(0x8201, 8, 3));
+    assert_eq!(state.crash_mxcsr.as_deref(), Some(MXCSR));
+
+    // SIGFPE / FPE_INTDIV and EXCEPTION_ACCESS_VIOLATION have nothing to do with SSE.
+    let state = read_synth_dump(dump(0x8201, 8, 1));
+    assert_eq!(state.crash_mxcsr, None);
+    let state = read_synth_dump(dump(3, 0xc0000005, 0));
+    assert_eq!(state.crash_mxcsr, None);
+}
+
+#[test]
+fn test_demangled_function_name() {
+    let dump = read_test_minidump().unwrap();
+    let mut state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    let frame = &mut state.threads[0].frames[0];
+    assert_eq!(frame.demangled_function_name(), None);
+
+    let mut demangle = |name: &str| {
+        frame.function_name = Some(name.to_string());
+        frame.demangled_function_name().unwrap()
+    };
+    // Itanium
+    assert_eq!(demangle("_ZN3foo3barEv"), "foo::bar()");
+    // MSVC
+    assert_eq!(
+        demangle("?bar@foo@@QAEHH@Z"),
+        "public: int __thiscall foo::bar(int)"
+    );
+    // Rust, both legacy (which is also valid Itanium) and v0
+    assert_eq!(
+        demangle("_ZN4core9panicking5panic17h0123456789abcdefE"),
+        "core::panicking::panic"
+    );
+    assert_eq!(demangle("_RNvCs1234_7mycrate4main"), "mycrate::main");
+    // Already demangled, or not mangled at all
+    assert_eq!(demangle("foo::bar(int)"), "foo::bar(int)");
+    assert_eq!(demangle("_Znotasymbol"), "_Znotasymbol");
+    assert_eq!(demangle("vswprintf"), "vswprintf");
+
+    // The formatters use the demangled name.
+    state.threads[0].frames[0].function_name = Some("_ZN3foo3barEv".to_string());
+    state.threads[0].frames[0].function_base = Some(0x00404200);
+    let mut output = vec![];
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("test_app.exe!foo::bar() + 0x9e"));
+    let mut json = vec![];
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["threads"][0]["frames"][0]["function"], "foo::bar()");
+}
+
+#[test]
+fn test_signature() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()])),
+    )
+    .unwrap();
+    let stack = &state.threads[0];
+    // The CRT's entry point and the kernel32.dll frame below it are noise.
+    assert_eq!(
+        stack.signature(10),
+        "`anonymous namespace'::CrashFunction | main"
+    );
+    assert_eq!(
+        stack.signature_with_noise(10, &NoiseList::new()),
+        "`anonymous namespace'::CrashFunction | main | __tmainCRTStartup | kernel32.dll@0x16fd6"
+    );
+    assert_eq!(
+        stack.signature(2),
+        "`anonymous namespace'::CrashFunction | main"
+    );
+    assert_eq!(stack.signature(0), "");
+    assert_eq!(CallStack::with_info(CallStackInfo::Ok).signature(10), "");
+
+    // Without symbols, frames are named by their module and offset.
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    assert!(state.threads[0]
+        .signature(10)
+        .starts_with("test_app.exe@0x"));
+}
+
+/// A `CallStack` whose frames have the given function names (or are at the
+/// given addresses in app.exe, if they have none).
+fn signature_stack(frames: &[(Option<&str>, u64)]) -> CallStack {
+    let mut stack = CallStack::with_info(CallStackInfo::Ok);
+    for &(function, instruction) in frames {
+        let context = MinidumpContext::from_raw(MinidumpRawContext::X86(CONTEXT_X86::default()));
+        let mut frame = StackFrame::from_context(context, FrameTrust::Scan);
+        frame.instruction = instruction;
+        if instruction >= 0x400000 {
+            frame.module = Some(MinidumpModule::new(0x400000, 0x10000, "c:\\app\\app.exe"));
+        }
+        frame.function_name = function.map(String::from);
+        stack.frames.push(frame);
+    }
+    stack
+}
+
+#[test]
+fn test_signature_noise() {
+    // Noise is skipped, and names are normalized.
+    let stack = signature_stack(&[
+        (Some("raise"), 0x401000),
+        (Some("abort"), 0x401010),
+        (
+            Some("_ZN4core9panicking5panic17h0123456789abcdefE"),
+            0x401020,
+        ),
+        (
+            Some("public: int __thiscall foo::Bar::baz(int) const"),
+            0x401030,
+        ),
+        (
+            Some("std::vector<int, std::allocator<int> >::push_back(int const&)"),
+            0x401040,
+        ),
+        (Some("operator<<(std::ostream&, Foo const&)"), 0x401050),
+        (Some("(anonymous namespace)::helper"), 0x401060),
+    ]);
+    assert_eq!(
+        stack.signature(10),
+        "foo::Bar::baz | std::vector<T>::push_back | operator<< | (anonymous namespace)::helper"
+    );
+    // Skipped frames don't count against max_frames.
+    assert_eq!(
+        stack.signature(2),
+        "foo::Bar::baz | std::vector<T>::push_back"
+    );
+}
+
+#[test]
+fn test_signature_runtime_noise() {
+    let stack = signature_stack(&[
+        (Some("foo::bar(int)"), 0x401000),
+        (Some("main"), 0x401010),
+        (Some("__libc_start_main"), 0x401020),
+        (Some("_start"), 0x401030),
+    ]);
+    assert_eq!(stack.signature(10), "foo::bar | main");
+    assert!(!stack.frames[1].is_noise(&NoiseList::default()));
+    assert!(stack.frames[2].is_noise(&NoiseList::default()));
+    assert!(!stack.frames[2].is_noise(&NoiseList::new()));
+
+    // Callers can add their own runtime's frames.
+    let mut noise = NoiseList::default();
+    noise.add_function("main");
+    assert_eq!(stack.signature_with_noise(10, &noise), "foo::bar");
+    let mut noise = NoiseList::new();
+    noise.add_prefix("foo::");
+    assert!(stack.frames[0].is_noise(&noise));
+    assert_eq!(
+        stack.signature_with_noise(10, &noise),
+        "main | __libc_start_main | _start"
+    );
+
+    // A crash in an entry point still gets a signature, from what's below it.
+    let stack = signature_stack(&[
+        (
+            Some("thread_start<unsigned int (__stdcall*)(void*)>(void*)"),
+            0x401000,
+        ),
+        (Some("BaseThreadInitThunk"), 0x401010),
+        (None, 0x401020),
+    ]);
+    assert!(stack.frames[0].is_noise(&NoiseList::default()));
+    assert_eq!(stack.signature(10), "app.exe@0x1020");
+}
+
+#[test]
+fn test_signature_recursion() {
+    let stack = signature_stack(&[
+        (Some("recurse(int)"), 0x401000),
+        (Some("recurse(int)"), 0x401000),
+        (Some("recurse(int)"), 0x401000),
+        (Some("recurse(int)"), 0x401000),
+        (Some("helper"), 0x401100),
+        (Some("recurse(int)"), 0x401000),
+    ]);
+    assert_eq!(stack.signature(3), "recurse | helper | recurse");
+}
+
+#[test]
+fn test_signature_uses_outer_function() {
+    // Inline frames aren't supported (breakpad-symbols doesn't parse INLINE
+    // records), so code inlined into a function is signed with that function.
+    let stack = signature_stack(&[
+        (Some("outer<int>(int)"), 0x401000),
+        (Some("main"), 0x401100),
+    ]);
+    assert_eq!(stack.signature(10), "outer<T> | main");
+}
+
+#[test]
+fn test_signature_missing_symbols() {
+    let stack = signature_stack(&[(None, 0x1234), (None, 0x402000), (Some("main"), 0x401100)]);
+    assert_eq!(stack.signature(10), "@0x1234 | app.exe@0x2000 | main");
+}
+
+#[test]
+fn test_signature_sentinel() {
+    let stack = signature_stack(&[
+        (Some("abort"), 0x401000),
+        (Some("_purecall"), 0x401010),
+        (Some("Foo::Bar()"), 0x401020),
+    ]);
+    assert_eq!(stack.signature(10), "_purecall");
+    let stack = signature_stack(&[
+        (Some("Foo::Bar()"), 0x401020),
+        (Some("std::terminate()"), 0x401010),
+        (Some("main"), 0x401100),
+    ]);
+    assert_eq!(stack.signature(10), "Foo::Bar | std::terminate");
+}
+
+#[test]
+fn test_potential_culprit_modules() {
+    let dump = read_test_minidump().unwrap();
+    let mut state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()])),
+    )
+    .unwrap();
+    let names = |modules: Vec<&MinidumpModule>| {
+        modules
+            .into_iter()
+            .map(|module| module.code_file().into_owned())
+            .collect::<Vec<_>>()
+    };
+    // Every frame was found reliably.
+    assert_eq!(state.requesting_thread, Some(0));
+    assert!(state.potential_culprit_modules().is_empty());
+
+    let frames = &mut state.threads[0].frames;
+    frames[3].trust = FrameTrust::Scan;
+    assert_eq!(
+        names(state.potential_culprit_modules()),
+        ["C:\\WINDOWS\\system32\\kernel32.dll"]
+    );
+
+    // A bad instruction pointer implicates its caller, which now has more
+    // suspect frames than kernel32.
+    let frames = &mut state.threads[0].frames;
+    frames[1].trust = FrameTrust::CfiScan;
+    frames[0].instruction = 0x1234_5678;
+    frames[0].module = None;
+    assert_eq!(
+        names(state.potential_culprit_modules()),
+        ["c:\\test_app.exe", "C:\\WINDOWS\\system32\\kernel32.dll"]
+    );
+    assert!(state.potential_culprit_unloaded_modules().is_empty());
+
+    // The bad instruction pointer may be in an unloaded module.
+    state.unloaded_modules = MinidumpUnloadedModuleList::from_modules(vec![
+        MinidumpUnloadedModule::new(0x1234_0000, 0x10000, "c:\\gone.dll"),
+        MinidumpUnloadedModule::new(0x2000_0000, 0x10000, "c:\\other.dll"),
+    ]);
+    let unloaded = state
+        .potential_culprit_unloaded_modules()
+        .into_iter()
+        .map(|module| module.code_file().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(unloaded, ["c:\\gone.dll"]);
+
+    // Only the requesting thread is considered.
+    state.requesting_thread = None;
+    assert!(state.potential_culprit_modules().is_empty());
+    assert!(state.potential_culprit_unloaded_modules().is_empty());
+}
+
+#[test]
+fn test_synth_minidump_round_trip() {
+    let name = DumpString::new("c:\\test_app.exe", Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little)
+        .D32(minidump::format::CvSignature::Pdb70 as u32)
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+        .D32(1) // age
+        .append_bytes(b"c:\\test_app.pdb\0");
+    let module = synth_minidump::Module::new(
+        Endian::Little,
+        0x400000,
+        0x10000,
+        &name,
+        0xb1054d2a,
+        0x34571371,
+        Some(&STOCK_VERSION_INFO),
+    )
+    .cv_record(&cv_record);
+
+    let context = synth_minidump::x86_context(Endian::Little, 0x401000, 0x10000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x10000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+
+    let mut exception = Exception::new(Endian::Little);
+    exception.thread_id = 0x1234;
+    exception.exception_record.exception_code = 0xc0000005; // EXCEPTION_ACCESS_VIOLATION
+    exception.exception_record.exception_address = 0x401000;
+
+    let custom = SimpleStream {
+        stream_type: 0x4d7a_0001,
+        section: Section::with_endian(Endian::Little).append_bytes(b"custom"),
+    };
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module)
+        .add(name)
+        .add(cv_record)
+        .add_thread(thread)
+        .add(context)
+        .add_memory(stack)
+        .add_exception(exception)
+        .add_stream(custom)
+        .finish_validated()
+        .unwrap();
+    let dump = Minidump::read(dump).unwrap();
+    assert_eq!(dump.get_raw_stream(0x4d7a_0001u32).unwrap(), b"custom");
+
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .unwrap();
+    assert_eq!(state.system_info.cpu, Cpu::X86);
+    assert_eq!(state.crash_address, Some(0x401000));
+    assert_eq!(state.requesting_thread, Some(0));
+
+    let module = state.modules.module_at_address(0x401000).unwrap();
+    assert_eq!(module.code_file(), "c:\\test_app.exe");
+    assert_eq!(module.debug_file().unwrap(), "c:\\test_app.pdb");
+    assert_eq!(
+        module.debug_identifier().unwrap(),
+        "ABCD1234F00DBEEF01020304050607081"
+    );
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[0].trust, FrameTrust::Context);
+    assert_eq!(frames[0].instruction, 0x401000);
+    assert_eq!(
+        frames[0].module.as_ref().unwrap().code_file(),
+        "c:\\test_app.exe"
+    );
 }
@@ -4,7 +4,7 @@
 use std::boxed::Box;
 use std::env;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::ops::Deref;
 use std::panic;
 use std::path::Path;
@@ -13,7 +13,8 @@ use std::time::Duration;
 
 use minidump::*;
 use minidump_processor::{
-    http_symbol_supplier, simple_symbol_supplier, MultiSymbolProvider, ProcessorOptions, Symbolizer,
+    http_symbol_supplier, simple_symbol_supplier, MultiSymbolProvider, ProcessorOptions,
+    Symbolizer, ThreadFilter,
 };
 
 use clap::{crate_version, App, AppSettings, Arg, ArgGroup};
@@ -111,6 +112,24 @@ Only provides the top-level summary and a backtrace of the crashing thread.")
 This is a gross hack for some legacy side-channel information that mozilla uses. It will \
 hopefully be phased out and deprecated in favour of just using custom streams in the \
 minidump itself.\n\n\n")
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .takes_value(true)
+                .conflicts_with("crashing-thread-only")
+                .long_help("Only walk the stacks of these threads (a comma-separated list of thread ids).
+
+Thread ids can be decimal or hex (with a 0x prefix). All threads are still listed in the \
+output, but the ones that weren't selected will have no frames. This can save a lot of \
+time on minidumps with hundreds of threads.\n\n\n")
+        )
+        .arg(
+            Arg::with_name("crashing-thread-only")
+                .long("crashing-thread-only")
+                .help("Only walk the stack of the crashing thread (or the thread that requested the dump).
+
+Shorthand for --threads with the crashing thread's id.")
         )
         .arg(
             Arg::with_name("symbols-url")
@@ -305,6 +324,26 @@ fn main() {
 
     options.evil_json = matches.value_of_os("raw-json").map(Path::new);
 
+    if matches.is_present("crashing-thread-only") {
+        options.thread_filter = ThreadFilter::CrashingThread;
+    } else if let Some(threads) = matches.value_of("threads") {
+        let ids = threads
+            .split(',')
+            .map(|id| {
+                let id = id.trim();
+                let parsed = match id.strip_prefix("0x") {
+                    Some(hex) => u32::from_str_radix(hex, 16),
+                    None => u32::from_str(id),
+                };
+                parsed.unwrap_or_else(|_| {
+                    error!("Invalid thread id for --threads: {:?}", id);
+                    std::process::exit(1);
+                })
+            })
+            .collect();
+        options.thread_filter = ThreadFilter::Ids(ids);
+    }
+
     let temp_dir = std::env::temp_dir();
 
     let symbols_paths = matches
@@ -337,6 +376,7 @@ fn main() {
         .and_then(|x| u64::from_str(x).ok())
         .map(Duration::from_secs)
         .unwrap();
+    options.symbol_timeout = timeout;
 
     let minidump_path = matches.value_of_os("minidump").map(Path::new).unwrap();
 
@@ -390,34 +430,43 @@ fn main() {
 
             match minidump_processor::process_minidump_with_options(&dump, &provider, options) {
                 Ok(state) => {
-                    let mut stdout;
-                    let mut output_f;
-                    let cyborg_output_f = cyborg.map(|path| File::create(path).unwrap());
-
-                    let mut output: &mut dyn Write = if let Some(output_path) = output_file {
-                        output_f = File::create(output_path).unwrap();
-                        &mut output_f
+                    // Only open the outputs now, so that fatal errors don't
+                    // clobber them.
+                    let mut output: BufWriter<Box<dyn Write>> = if let Some(output_path) =
+                        output_file
+                    {
+                        BufWriter::new(Box::new(create_output_file(&output_path, "--output-file")))
                     } else {
-                        stdout = std::io::stdout();
-                        &mut stdout
+                        BufWriter::new(Box::new(std::io::stdout()))
                     };
+                    let mut cyborg_output =
+                        cyborg.map(|path| BufWriter::new(create_output_file(path, "--cyborg")));
 
                     // Print the human output if requested (always uses the "real" output).
                     if human {
-                        if brief {
-                            state.print_brief(&mut output).unwrap();
+                        let res = if brief {
+                            state.print_brief(&mut output)
                         } else {
-                            state.print(&mut output).unwrap();
-                        }
+                            state.print(&mut output)
+                        };
+                        res.and_then(|_| output.flush())
+                            .unwrap_or_else(|e| exit_write_error(e));
                     }
 
                     // Print the json output if requested (using "cyborg" output if available).
                     if json {
-                        if let Some(mut cyborg_output_f) = cyborg_output_f {
-                            state.print_json(&mut cyborg_output_f, pretty).unwrap();
+                        let res = if let Some(cyborg_output) = cyborg_output.as_mut() {
+                            state
+                                .print_json(&mut *cyborg_output, pretty)
+                                .map_err(std::io::Error::from)
+                                .and_then(|_| cyborg_output.flush())
                         } else {
-                            state.print_json(&mut output, pretty).unwrap();
-                        }
+                            state
+                                .print_json(&mut output, pretty)
+                                .map_err(std::io::Error::from)
+                                .and_then(|_| output.flush())
+                        };
+                        res.unwrap_or_else(|e| exit_write_error(e));
                     }
                 }
                 Err(err) => {
@@ -433,6 +482,25 @@ fn main() {
     }
 }
 
+/// Create (or truncate) a file to write output to, exiting with an error
+/// mentioning `flag` if that isn't possible.
+fn create_output_file(path: &Path, flag: &str) -> File {
+    File::create(path).unwrap_or_else(|e| {
+        error!(
+            "Couldn't open {} path {} for writing: {}",
+            flag,
+            path.display(),
+            e
+        );
+        std::process::exit(1);
+    })
+}
+
+fn exit_write_error(err: std::io::Error) -> ! {
+    error!("{:?} - Error writing output: {}", err, err);
+    std::process::exit(1);
+}
+
 fn print_help_markdown() {
     let mut help_buf = Vec::new();
 
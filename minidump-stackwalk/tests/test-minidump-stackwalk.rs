@@ -318,6 +318,66 @@ fn test_output_files() {
     assert_eq!(stderr, "");
 }
 
+#[test]
+fn test_unwritable_output_file() {
+    let out_path = test_output("mdsw-no-such-dir/out.txt");
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--output-file")
+        .arg(&out_path)
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("--output-file"), "{}", stderr);
+}
+
+#[test]
+fn test_threads_filter() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let run = |args: &[&str]| {
+        Command::new(bin)
+            .args(args)
+            .arg("../testdata/test.dmp")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .unwrap()
+    };
+
+    // The only walkable thread in test.dmp crashed, so these are all the same.
+    let all = run(&["--human"]);
+    assert!(all.status.success());
+    for args in [
+        &["--crashing-thread-only"][..],
+        &["--threads", "0xbf4"],
+        &["--threads", "3060,4544"],
+    ] {
+        let output = run(args);
+        assert!(output.status.success());
+        assert_eq!(output.stdout, all.stdout);
+    }
+
+    let output = run(&["--threads", "4544"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("Thread 0 \n<not walked>\n"), "{}", stdout);
+
+    let output = run(&["--threads", "bf4"]);
+    assert!(!output.status.success());
+    assert_eq!(output.stdout, b"");
+
+    let output = run(&["--threads", "1", "--crashing-thread-only"]);
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_version() {
     let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
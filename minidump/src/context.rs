@@ -5,8 +5,10 @@
 
 use log::warn;
 use num_traits::FromPrimitive;
-use scroll::{self, Pread};
+use scroll::ctx::SizeWith;
+use scroll::{self, Pread, LE};
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
@@ -508,6 +510,98 @@ impl CpuContext for md::CONTEXT_ARM64 {
     }
 }
 
+/// The index into `gpr` of a PowerPC general-purpose register name (`r0` – `r31`).
+fn ppc_gpr_index(reg: &str) -> Option<usize> {
+    let index = reg.strip_prefix('r')?.parse::<usize>().ok()?;
+    if index < 32 && PPC_REGS[index] == reg {
+        Some(index)
+    } else {
+        None
+    }
+}
+
+impl CpuContext for md::CONTEXT_PPC {
+    type Register = u32;
+
+    fn get_register_always(&self, reg: &str) -> u32 {
+        match reg {
+            "srr0" => self.srr0,
+            "lr" => self.lr,
+            "ctr" => self.ctr,
+            "cr" => self.cr,
+            _ => match ppc_gpr_index(reg) {
+                Some(index) => self.gpr[index],
+                None => unreachable!("Invalid ppc register! {}", reg),
+            },
+        }
+    }
+
+    fn set_register(&mut self, reg: &str, val: Self::Register) -> Option<()> {
+        match reg {
+            "srr0" => self.srr0 = val,
+            "lr" => self.lr = val,
+            "ctr" => self.ctr = val,
+            "cr" => self.cr = val,
+            _ => self.gpr[ppc_gpr_index(reg)?] = val,
+        }
+        Some(())
+    }
+
+    fn memoize_register(&self, reg: &str) -> Option<&'static str> {
+        let idx = PPC_REGS.iter().position(|val| *val == reg)?;
+        Some(PPC_REGS[idx])
+    }
+
+    fn stack_pointer_register_name(&self) -> &'static str {
+        md::PpcRegisterNumbers::StackPointer.name()
+    }
+
+    fn instruction_pointer_register_name(&self) -> &'static str {
+        "srr0"
+    }
+}
+
+impl CpuContext for md::CONTEXT_PPC64 {
+    type Register = u64;
+
+    fn get_register_always(&self, reg: &str) -> u64 {
+        match reg {
+            "srr0" => self.srr0,
+            "lr" => self.lr,
+            "ctr" => self.ctr,
+            "cr" => self.cr,
+            _ => match ppc_gpr_index(reg) {
+                Some(index) => self.gpr[index],
+                None => unreachable!("Invalid ppc64 register! {}", reg),
+            },
+        }
+    }
+
+    fn set_register(&mut self, reg: &str, val: Self::Register) -> Option<()> {
+        match reg {
+            "srr0" => self.srr0 = val,
+            "lr" => self.lr = val,
+            "ctr" => self.ctr = val,
+            "cr" => self.cr = val,
+            _ => self.gpr[ppc_gpr_index(reg)?] = val,
+        }
+        Some(())
+    }
+
+    fn memoize_register(&self, reg: &str) -> Option<&'static str> {
+        let idx = PPC_REGS.iter().position(|val| *val == reg)?;
+        Some(PPC_REGS[idx])
+    }
+
+    fn stack_pointer_register_name(&self) -> &'static str {
+        md::Ppc64RegisterNumbers::StackPointer.name()
+    }
+
+    fn instruction_pointer_register_name(&self) -> &'static str {
+        "srr0"
+    }
+}
+
 /// Information about which registers are valid in a `MinidumpContext`.
 #[derive(Clone, Debug, PartialEq)]
 pub enum MinidumpContextValidity {
@@ -517,6 +611,35 @@ pub enum MinidumpContextValidity {
     Some(HashSet<&'static str>),
 }
 
+impl MinidumpContextValidity {
+    /// The registers that are valid in both `self` and `other`.
+    ///
+    /// Registers are compared by name, so aliases (like `fp` and `x29` on ARM64)
+    /// aren't the same register here.
+    pub fn intersect(&self, other: &Self) -> Self {
+        match (self, other) {
+            (MinidumpContextValidity::All, valid) | (valid, MinidumpContextValidity::All) => {
+                valid.clone()
+            }
+            (MinidumpContextValidity::Some(which), MinidumpContextValidity::Some(other)) => {
+                MinidumpContextValidity::Some(which.intersection(other).copied().collect())
+            }
+        }
+    }
+
+    /// The registers that are valid in either `self` or `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (MinidumpContextValidity::All, _) | (_, MinidumpContextValidity::All) => {
+                MinidumpContextValidity::All
+            }
+            (MinidumpContextValidity::Some(which), MinidumpContextValidity::Some(other)) => {
+                MinidumpContextValidity::Some(which.union(other).copied().collect())
+            }
+        }
+    }
+}
+
 /// CPU context such as register states.
 ///
 /// MinidumpContext carries a CPU-specific MDRawContext structure, which
@@ -535,15 +658,172 @@ pub struct MinidumpContext {
     pub raw: MinidumpRawContext,
     /// Which registers are valid in `raw`.
     pub valid: MinidumpContextValidity,
+    /// Registers from the extended (XSAVE) part of the context, if it had one.
+    ///
+    /// This is only ever present for x86-64 contexts read from a minidump, and
+    /// never for contexts recovered by unwinding.
+    pub xstate: Option<MinidumpXState>,
+}
+
+/// The registers in the extended (XSAVE) part of an x86-64 context.
+///
+/// These are the parts of the vector registers that don't fit in
+/// `CONTEXT_AMD64`. Each group is only present if the CPU that wrote the
+/// minidump supported it. See [`MinidumpContext::ymm`][] and
+/// [`MinidumpContext::zmm`][] to get the full registers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinidumpXState {
+    /// The upper 128 bits of ymm0-ymm15 (AVX).
+    pub ymm_high: Option<[u128; 16]>,
+    /// The opmask registers k0-k7 (AVX-512).
+    pub opmask: Option<[u64; 8]>,
+    /// The upper 256 bits of zmm0-zmm15, lowest 128 bits first (AVX-512).
+    pub zmm_high: Option<[[u128; 2]; 16]>,
+    /// All 512 bits of zmm16-zmm31, lowest 128 bits first (AVX-512).
+    pub zmm_hi16: Option<[[u128; 4]; 16]>,
 }
 
 /// Errors encountered while reading a `MinidumpContext`.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ContextError {
     /// Failed to read data.
     ReadFailure,
     /// Encountered an unknown CPU context.
     UnknownCpuContext,
+    /// The context's flags say it's for `context_cpu`, but the system info
+    /// says the minidump is for `system_info_cpu`.
+    CpuMismatch {
+        context_cpu: ContextFlagsCpu,
+        system_info_cpu: ContextFlagsCpu,
+    },
+}
+
+/// Every kind of context that [`MinidumpContext::read`][] can parse, by the CPU
+/// its `context_flags` name.
+///
+/// `CONTEXT_AMD64` is last because its flags aren't at the start, so random data
+/// is more likely to look like them than like another CPU's.
+const CONTEXT_CPUS: [ContextFlagsCpu; 9] = [
+    ContextFlagsCpu::CONTEXT_X86,
+    ContextFlagsCpu::CONTEXT_PPC,
+    ContextFlagsCpu::CONTEXT_PPC64,
+    ContextFlagsCpu::CONTEXT_SPARC,
+    ContextFlagsCpu::CONTEXT_ARM,
+    ContextFlagsCpu::CONTEXT_ARM64,
+    ContextFlagsCpu::CONTEXT_ARM64_OLD,
+    ContextFlagsCpu::CONTEXT_MIPS,
+    ContextFlagsCpu::CONTEXT_AMD64,
+];
+
+/// `CONTEXT_AMD64` has six 64-bit "home" registers before its `context_flags`.
+const AMD64_CONTEXT_FLAGS_OFFSET: usize = 6 * 8;
+
+/// A short name for the CPU of a kind of context, e.g. "amd64".
+pub(crate) fn context_cpu_name(cpu: ContextFlagsCpu) -> &'static str {
+    match cpu {
+        ContextFlagsCpu::CONTEXT_X86 => "x86",
+        ContextFlagsCpu::CONTEXT_AMD64 => "amd64",
+        ContextFlagsCpu::CONTEXT_PPC => "ppc",
+        ContextFlagsCpu::CONTEXT_PPC64 => "ppc64",
+        ContextFlagsCpu::CONTEXT_SPARC => "sparc",
+        ContextFlagsCpu::CONTEXT_ARM => "arm",
+        ContextFlagsCpu::CONTEXT_ARM64 | ContextFlagsCpu::CONTEXT_ARM64_OLD => "arm64",
+        ContextFlagsCpu::CONTEXT_MIPS => "mips",
+        _ => "unknown",
+    }
+}
+
+/// The kind of context that `system_info` says the minidump's threads have.
+fn system_info_context_cpu(system_info: &MinidumpSystemInfo) -> Option<ContextFlagsCpu> {
+    use md::ProcessorArchitecture::*;
+
+    match md::ProcessorArchitecture::from_u16(system_info.raw.processor_architecture)? {
+        PROCESSOR_ARCHITECTURE_INTEL | PROCESSOR_ARCHITECTURE_IA32_ON_WIN64 => {
+            Some(ContextFlagsCpu::CONTEXT_X86)
+        }
+        PROCESSOR_ARCHITECTURE_AMD64 => Some(ContextFlagsCpu::CONTEXT_AMD64),
+        PROCESSOR_ARCHITECTURE_PPC => Some(ContextFlagsCpu::CONTEXT_PPC),
+        PROCESSOR_ARCHITECTURE_PPC64 => Some(ContextFlagsCpu::CONTEXT_PPC64),
+        PROCESSOR_ARCHITECTURE_SPARC => Some(ContextFlagsCpu::CONTEXT_SPARC),
+        PROCESSOR_ARCHITECTURE_ARM => Some(ContextFlagsCpu::CONTEXT_ARM),
+        PROCESSOR_ARCHITECTURE_ARM64 => Some(ContextFlagsCpu::CONTEXT_ARM64),
+        PROCESSOR_ARCHITECTURE_ARM64_OLD => Some(ContextFlagsCpu::CONTEXT_ARM64_OLD),
+        PROCESSOR_ARCHITECTURE_MIPS => Some(ContextFlagsCpu::CONTEXT_MIPS),
+        _ => None,
+    }
+}
+
+/// The `processor_architecture` a SystemInfo would have for a kind of context.
+fn context_cpu_architecture(cpu: ContextFlagsCpu) -> Option<md::ProcessorArchitecture> {
+    use md::ProcessorArchitecture::*;
+
+    match cpu {
+        ContextFlagsCpu::CONTEXT_X86 => Some(PROCESSOR_ARCHITECTURE_INTEL),
+        ContextFlagsCpu::CONTEXT_AMD64 => Some(PROCESSOR_ARCHITECTURE_AMD64),
+        ContextFlagsCpu::CONTEXT_PPC => Some(PROCESSOR_ARCHITECTURE_PPC),
+        ContextFlagsCpu::CONTEXT_PPC64 => Some(PROCESSOR_ARCHITECTURE_PPC64),
+        ContextFlagsCpu::CONTEXT_SPARC => Some(PROCESSOR_ARCHITECTURE_SPARC),
+        ContextFlagsCpu::CONTEXT_ARM => Some(PROCESSOR_ARCHITECTURE_ARM),
+        ContextFlagsCpu::CONTEXT_ARM64 => Some(PROCESSOR_ARCHITECTURE_ARM64),
+        ContextFlagsCpu::CONTEXT_ARM64_OLD => Some(PROCESSOR_ARCHITECTURE_ARM64_OLD),
+        ContextFlagsCpu::CONTEXT_MIPS => Some(PROCESSOR_ARCHITECTURE_MIPS),
+        _ => None,
+    }
+}
+
+/// The size of a kind of context.
+fn context_size(cpu: ContextFlagsCpu) -> Option<usize> {
+    match cpu {
+        ContextFlagsCpu::CONTEXT_X86 => Some(md::CONTEXT_X86::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_AMD64 => Some(md::CONTEXT_AMD64::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_PPC => Some(md::CONTEXT_PPC::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_PPC64 => Some(md::CONTEXT_PPC64::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_SPARC => Some(md::CONTEXT_SPARC::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_ARM => Some(md::CONTEXT_ARM::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_ARM64 => Some(md::CONTEXT_ARM64::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_ARM64_OLD => Some(md::CONTEXT_ARM64_OLD::size_with(&LE)),
+        ContextFlagsCpu::CONTEXT_MIPS => Some(md::CONTEXT_MIPS::size_with(&LE)),
+        _ => None,
+    }
+}
+
+/// Guess the `processor_architecture` of the context in `bytes` from its flags
+/// and size, for minidumps without a SystemInfo to say.
+///
+/// A kind of context that's exactly as big as `bytes` wins over one that only
+/// fits in it, because some contexts (like x86 with XSTATE) have more after them.
+pub(crate) fn infer_context_architecture(
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Option<md::ProcessorArchitecture> {
+    let candidates = || {
+        CONTEXT_CPUS
+            .iter()
+            .copied()
+            .filter(move |&cpu| context_flags_are(bytes, endian, cpu))
+    };
+    let cpu = candidates()
+        .find(|&cpu| context_size(cpu) == Some(bytes.len()))
+        .or_else(|| {
+            candidates().find(|&cpu| matches!(context_size(cpu), Some(size) if size <= bytes.len()))
+        })?;
+    context_cpu_architecture(cpu)
+}
+
+/// Whether the context in `bytes` has `context_flags` for `cpu`, where that kind of
+/// context keeps them.
+fn context_flags_are(bytes: &[u8], endian: scroll::Endian, cpu: ContextFlagsCpu) -> bool {
+    let flags = match cpu {
+        ContextFlagsCpu::CONTEXT_AMD64 => bytes
+            .pread_with::<u32>(AMD64_CONTEXT_FLAGS_OFFSET, endian)
+            .ok(),
+        ContextFlagsCpu::CONTEXT_PPC64 | ContextFlagsCpu::CONTEXT_ARM64_OLD => bytes
+            .pread_with::<u64>(0, endian)
+            .ok()
+            .map(|flags| flags as u32),
+        _ => bytes.pread_with::<u32>(0, endian).ok(),
+    };
+    flags.map(ContextFlagsCpu::from_flags) == Some(cpu)
 }
 
 /// General-purpose registers for x86.
@@ -569,15 +849,237 @@ static ARM64_REGS: [&str; 35] = [
     "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
     "x28", "x29", "x30", "pc", "lr", "fp", "sp",
 ];
+
+/// General-purpose registers for PowerPC (both 32-bit and 64-bit).
+///
+/// `srr0` holds the address of the interrupted instruction and `r1` is the stack pointer.
+static PPC_REGS: [&str; 36] = [
+    "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "r13", "r14",
+    "r15", "r16", "r17", "r18", "r19", "r20", "r21", "r22", "r23", "r24", "r25", "r26", "r27",
+    "r28", "r29", "r30", "r31", "srr0", "lr", "ctr", "cr",
+];
 //======================================================
 // Implementations
 
+/// Print the floating point and vector state shared by `CONTEXT_PPC` and `CONTEXT_PPC64`.
+fn write_ppc_float_and_vector_save<T: Write>(
+    f: &mut T,
+    float_save: &md::FLOATING_SAVE_AREA_PPC,
+    vector_save: &md::VECTOR_SAVE_AREA_PPC,
+) -> io::Result<()> {
+    for (i, reg) in float_save.fpregs.iter().enumerate() {
+        writeln!(f, "  float_save.fpregs[{:2}]    = {:#x}", i, reg)?;
+    }
+    writeln!(f, "  float_save.fpscr         = {:#x}", float_save.fpscr)?;
+    for (i, reg) in vector_save.save_vr.iter().enumerate() {
+        writeln!(f, "  vector_save.save_vr[{:2}]  = {:#x}", i, reg)?;
+    }
+    write!(
+        f,
+        r#"  vector_save.save_vscr    = {:#x}
+  vector_save.save_vrvalid = {:#x}
+"#,
+        vector_save.save_vscr, vector_save.save_vrvalid
+    )
+}
+
+/// Format a vector register, given lowest 128 bits first, as one big hex number.
+fn format_vector(lanes: &[u128]) -> String {
+    let mut output = String::from("0x");
+    for lane in lanes.iter().rev() {
+        output.push_str(&format!("{:032x}", lane));
+    }
+    output
+}
+
+/// The SSE floating point exceptions, in the order of their flags in the MXCSR.
+///
+/// Their masks are in the same order, 7 bits higher.
+const MXCSR_EXCEPTIONS: [&str; 6] = ["IE", "DE", "ZE", "OE", "UE", "PE"];
+
+/// Describe an MXCSR value: which floating point exceptions have been raised,
+/// which of them aren't masked (and so trap), and the rounding mode.
+///
+/// e.g. "0x1d84 (raised: ZE; unmasked: ZE; rounding: nearest)"
+fn describe_mxcsr(mxcsr: u32) -> String {
+    let exceptions = |shift: usize, set: bool| {
+        let names = MXCSR_EXCEPTIONS
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| (mxcsr >> (i + shift) & 1 != 0) == set)
+            .map(|(_, &name)| name)
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            String::from("none")
+        } else {
+            names.join(" ")
+        }
+    };
+    let rounding = match mxcsr >> 13 & 3 {
+        0 => "nearest",
+        1 => "down",
+        2 => "up",
+        _ => "toward zero",
+    };
+    let mut description = format!(
+        "{:#x} (raised: {}; unmasked: {}; rounding: {}",
+        mxcsr,
+        exceptions(0, true),
+        exceptions(7, false),
+        rounding
+    );
+    if mxcsr & 1 << 6 != 0 {
+        description.push_str("; DAZ");
+    }
+    if mxcsr & 1 << 15 != 0 {
+        description.push_str("; FZ");
+    }
+    description.push(')');
+    description
+}
+
+/// The usual location of the XSAVE features in an uncompacted XSAVE context.
+///
+/// These are what Intel CPUs report, and are only used if the minidump doesn't
+/// have a `MINIDUMP_MISC_INFO_5` to tell us.
+fn standard_xstate_feature(index: usize) -> Option<md::XSTATE_FEATURE> {
+    let (offset, size) = match md::XstateFeatureIndex::from_index(index)? {
+        md::XstateFeatureIndex::GSSE_AND_AVX => (576, 256),
+        md::XstateFeatureIndex::MPX_BNDREGS => (960, 64),
+        md::XstateFeatureIndex::MPX_BNDCSR => (1024, 64),
+        md::XstateFeatureIndex::AVX512_KMASK => (1088, 64),
+        md::XstateFeatureIndex::AVX512_ZMM_H => (1152, 512),
+        md::XstateFeatureIndex::ACK512_ZMM => (1664, 1024),
+        _ => return None,
+    };
+    Some(md::XSTATE_FEATURE { offset, size })
+}
+
+impl MinidumpXState {
+    /// Read the extended part of a context, whose `CONTEXT_EX` is at `offset` in `bytes`.
+    ///
+    /// See [`md::XSTATE_CONFIG_FEATURE_MSC_INFO`] for the details of the format.
+    fn read(
+        bytes: &[u8],
+        offset: usize,
+        endian: scroll::Endian,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<MinidumpXState> {
+        const XSAVE_HEADER_OFFSET: u32 = 512;
+        const XSAVE_COMPACTED: u64 = 1 << 63;
+
+        let context_ex: md::CONTEXT_EX = bytes.pread_with(offset, endian).ok()?;
+        let start = offset.checked_add(usize::try_from(context_ex.xstate.offset).ok()?)?;
+        let end = start.checked_add(context_ex.xstate.length as usize)?;
+        let area = bytes.get(start..end)?;
+        let header: md::XSAVE_AREA_HEADER = area.pread_with(0, endian).ok()?;
+
+        let xstate_data = misc.and_then(|misc| misc.raw.xstate_data());
+        let feature = |index: usize| match xstate_data {
+            Some(xstate_data) => xstate_data
+                .iter()
+                .find(|&(i, _)| i == index)
+                .map(|(_, feature)| feature),
+            None => standard_xstate_feature(index),
+        };
+        let compacted = header.compaction_mask & XSAVE_COMPACTED != 0;
+        let enabled = if compacted {
+            header.compaction_mask
+        } else {
+            xstate_data.map_or(header.mask, |xstate_data| xstate_data.enabled_features)
+        };
+
+        // Get the contents of a feature, or None if the CPU doesn't have it.
+        // Features that the CPU has but didn't save are in their initial state.
+        let read_feature = |index: md::XstateFeatureIndex| -> Option<Vec<u8>> {
+            let index = index as usize;
+            if enabled & (1 << index) == 0 {
+                return None;
+            }
+            let size = feature(index)?.size as usize;
+            if header.mask & (1 << index) == 0 {
+                return Some(vec![0; size]);
+            }
+            let location = if compacted {
+                // Features are packed in order right after the header.
+                let mut location = md::XSAVE_AREA_HEADER::size_with(&endian);
+                for i in 0..index {
+                    if i >= 2 && header.compaction_mask & (1 << i) != 0 {
+                        location = location.checked_add(feature(i)?.size as usize)?;
+                    }
+                }
+                location
+            } else {
+                feature(index)?.offset.checked_sub(XSAVE_HEADER_OFFSET)? as usize
+            };
+            area.get(location..location.checked_add(size)?)
+                .map(<[u8]>::to_vec)
+        };
+        let lanes = |bytes: Vec<u8>| -> Option<Vec<u128>> {
+            (0..bytes.len() / 16)
+                .map(|i| bytes.pread_with::<u128>(i * 16, endian).ok())
+                .collect()
+        };
+
+        let ymm_high = read_feature(md::XstateFeatureIndex::GSSE_AND_AVX)
+            .and_then(lanes)
+            .and_then(|lanes| <[u128; 16]>::try_from(lanes.get(..16)?).ok());
+        let opmask = read_feature(md::XstateFeatureIndex::AVX512_KMASK).and_then(|bytes| {
+            let mut opmask = [0; 8];
+            for (i, k) in opmask.iter_mut().enumerate() {
+                *k = bytes.pread_with(i * 8, endian).ok()?;
+            }
+            Some(opmask)
+        });
+        let zmm_high = read_feature(md::XstateFeatureIndex::AVX512_ZMM_H)
+            .and_then(lanes)
+            .and_then(|lanes| {
+                let mut zmm_high = [[0; 2]; 16];
+                for (i, zmm) in zmm_high.iter_mut().enumerate() {
+                    *zmm = <[u128; 2]>::try_from(lanes.get(i * 2..i * 2 + 2)?).ok()?;
+                }
+                Some(zmm_high)
+            });
+        let zmm_hi16 = read_feature(md::XstateFeatureIndex::ACK512_ZMM)
+            .and_then(lanes)
+            .and_then(|lanes| {
+                let mut zmm_hi16 = [[0; 4]; 16];
+                for (i, zmm) in zmm_hi16.iter_mut().enumerate() {
+                    *zmm = <[u128; 4]>::try_from(lanes.get(i * 4..i * 4 + 4)?).ok()?;
+                }
+                Some(zmm_hi16)
+            });
+
+        Some(MinidumpXState {
+            ymm_high,
+            opmask,
+            zmm_high,
+            zmm_hi16,
+        })
+    }
+}
+
+/// Report that the XSTATE sections of a context aren't being parsed.
+///
+/// The layout of those sections (including the AVX registers) comes from
+/// `MINIDUMP_MISC_INFO_5`, so mention whether we would have been able to.
+fn warn_ignored_xstate(misc: Option<&MinidumpMiscInfo>) {
+    match misc.and_then(|misc| misc.xstate_feature(md::XstateFeatureIndex::GSSE_AND_AVX)) {
+        Some(avx) => warn!(
+            "Cpu context has extra XSTATE that is being ignored (AVX at offset {}, size {})",
+            avx.offset, avx.size
+        ),
+        None => warn!("Cpu context has extra XSTATE that is being ignored"),
+    }
+}
+
 impl MinidumpContext {
     /// Return a MinidumpContext given a `MinidumpRawContext`.
     pub fn from_raw(raw: MinidumpRawContext) -> MinidumpContext {
         MinidumpContext {
             raw,
             valid: MinidumpContextValidity::All,
+            xstate: None,
         }
     }
 
@@ -586,137 +1088,192 @@ impl MinidumpContext {
         bytes: &[u8],
         endian: scroll::Endian,
         system_info: &MinidumpSystemInfo,
-        _misc: Option<&MinidumpMiscInfo>,
+        misc: Option<&MinidumpMiscInfo>,
     ) -> Result<MinidumpContext, ContextError> {
         use md::ProcessorArchitecture::*;
 
         let mut offset = 0;
 
+        // If the context's flags say it's for another CPU, it's still read the way
+        // SystemInfo says as long as it's big enough, because a corrupt context is
+        // more likely than a corrupt SystemInfo. Otherwise that's why it's rejected.
+        let mismatch = Self::check_cpu(bytes, endian, system_info).err();
+        let read_failure = || mismatch.clone().unwrap_or(ContextError::ReadFailure);
+
         // Although every context contains `context_flags` which tell us what kind
         // ok context we're handling, they aren't all in the same location, so we
         // need to use SystemInfo to choose what kind of context to parse this as.
         // We can then use the `context_flags` to validate our parse.
         // We need to use the raw processor_architecture because system_info.cpu
         // flattens away some key distinctions for this code.
-        match md::ProcessorArchitecture::from_u16(system_info.raw.processor_architecture) {
-            Some(PROCESSOR_ARCHITECTURE_INTEL) | Some(PROCESSOR_ARCHITECTURE_IA32_ON_WIN64) => {
-                // Not 100% sure IA32_ON_WIN64 is this format, but let's assume so?
-                let ctx: md::CONTEXT_X86 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_X86 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
+        let context =
+            match md::ProcessorArchitecture::from_u16(system_info.raw.processor_architecture) {
+                Some(PROCESSOR_ARCHITECTURE_INTEL) | Some(PROCESSOR_ARCHITECTURE_IA32_ON_WIN64) => {
+                    // Not 100% sure IA32_ON_WIN64 is this format, but let's assume so?
+                    let ctx: md::CONTEXT_X86 = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_X86 || mismatch.is_some() {
+                        if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
+                            // FIXME: uses MISC_INFO_5 to parse out extra sections here
+                            warn_ignored_xstate(misc);
+                        }
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::X86(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
                     }
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::X86(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_AMD64) => {
-                let ctx: md::CONTEXT_AMD64 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_AMD64 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
+                Some(PROCESSOR_ARCHITECTURE_AMD64) => {
+                    let ctx: md::CONTEXT_AMD64 = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_AMD64 || mismatch.is_some() {
+                        let xstate = if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
+                            let xstate = MinidumpXState::read(bytes, offset, endian, misc);
+                            if xstate.is_none() {
+                                warn!("Cpu context has extra XSTATE that couldn't be read");
+                            }
+                            xstate
+                        } else {
+                            None
+                        };
+                        let mut context = MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx));
+                        context.xstate = xstate;
+                        Ok(context)
+                    } else {
+                        Err(ContextError::ReadFailure)
                     }
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_PPC) => {
-                let ctx: md::CONTEXT_PPC = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_PPC {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Ppc(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_PPC) => {
+                    let ctx: md::CONTEXT_PPC = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_PPC || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::Ppc(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_PPC64) => {
-                let ctx: md::CONTEXT_PPC64 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_PPC64 {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Ppc64(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_PPC64) => {
+                    let ctx: md::CONTEXT_PPC64 = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_PPC64 || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::Ppc64(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_SPARC) => {
-                let ctx: md::CONTEXT_SPARC = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_SPARC {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Sparc(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_SPARC) => {
+                    let ctx: md::CONTEXT_SPARC = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_SPARC || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::Sparc(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_ARM) => {
-                let ctx: md::CONTEXT_ARM = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_ARM {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_ARM) => {
+                    let ctx: md::CONTEXT_ARM = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_ARM || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_ARM64) => {
-                let ctx: md::CONTEXT_ARM64 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_ARM64 {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm64(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_ARM64) => {
+                    let ctx: md::CONTEXT_ARM64 = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_ARM64 || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm64(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_ARM64_OLD) => {
-                let ctx: md::CONTEXT_ARM64_OLD = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_ARM64_OLD {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::OldArm64(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_ARM64_OLD) => {
+                    let ctx: md::CONTEXT_ARM64_OLD = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_ARM64_OLD || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::OldArm64(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            Some(PROCESSOR_ARCHITECTURE_MIPS) => {
-                let ctx: md::CONTEXT_MIPS = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
-                if flags == ContextFlagsCpu::CONTEXT_MIPS {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Mips(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                Some(PROCESSOR_ARCHITECTURE_MIPS) => {
+                    let ctx: md::CONTEXT_MIPS = bytes
+                        .gread_with(&mut offset, endian)
+                        .map_err(|_| read_failure())?;
+
+                    let flags = ContextFlagsCpu::from_flags(ctx.context_flags as u32);
+                    if flags == ContextFlagsCpu::CONTEXT_MIPS || mismatch.is_some() {
+                        Ok(MinidumpContext::from_raw(MinidumpRawContext::Mips(ctx)))
+                    } else {
+                        Err(ContextError::ReadFailure)
+                    }
                 }
-            }
-            _ => Err(ContextError::UnknownCpuContext),
+                _ => Err(ContextError::UnknownCpuContext),
+            };
+        if let (Ok(_), Some(ContextError::CpuMismatch { context_cpu, .. })) = (&context, &mismatch)
+        {
+            warn!(
+                "Cpu context says it's for {} instead of {}, reading it as {} anyway",
+                context_cpu_name(*context_cpu),
+                system_info.cpu,
+                system_info.cpu
+            );
+        }
+        context
+    }
+
+    /// Check that the context in `bytes` is for the CPU that `system_info` says.
+    ///
+    /// Returns [`ContextError::CpuMismatch`][] if the context's flags say it's for a
+    /// different CPU. [`MinidumpContext::read`][] still reads such a context as the kind
+    /// that `system_info` says, if there are enough bytes for one. Contexts whose flags
+    /// don't name any CPU are left for `read` to reject.
+    pub fn check_cpu(
+        bytes: &[u8],
+        endian: scroll::Endian,
+        system_info: &MinidumpSystemInfo,
+    ) -> Result<(), ContextError> {
+        let system_info_cpu = match system_info_context_cpu(system_info) {
+            Some(cpu) => cpu,
+            None => return Ok(()),
+        };
+        if context_flags_are(bytes, endian, system_info_cpu) {
+            return Ok(());
+        }
+        match CONTEXT_CPUS
+            .iter()
+            .copied()
+            .find(|&cpu| context_flags_are(bytes, endian, cpu))
+        {
+            Some(context_cpu) => Err(ContextError::CpuMismatch {
+                context_cpu,
+                system_info_cpu,
+            }),
+            None => Ok(()),
         }
     }
 
@@ -770,8 +1327,8 @@ impl MinidumpContext {
             MinidumpRawContext::Arm(ref ctx) => ctx.format_register(reg),
             MinidumpRawContext::Arm64(ref ctx) => ctx.format_register(reg),
             MinidumpRawContext::OldArm64(ref ctx) => ctx.format_register(reg),
-            MinidumpRawContext::Ppc(_) => unimplemented!(),
-            MinidumpRawContext::Ppc64(_) => unimplemented!(),
+            MinidumpRawContext::Ppc(ref ctx) => ctx.format_register(reg),
+            MinidumpRawContext::Ppc64(ref ctx) => ctx.format_register(reg),
             MinidumpRawContext::Sparc(_) => unimplemented!(),
             MinidumpRawContext::X86(ref ctx) => ctx.format_register(reg),
             MinidumpRawContext::Mips(_) => unimplemented!(),
@@ -784,14 +1341,237 @@ impl MinidumpContext {
             MinidumpRawContext::Arm(_) => &ARM_REGS[..],
             MinidumpRawContext::Arm64(_) => &ARM64_REGS[..],
             MinidumpRawContext::OldArm64(_) => &ARM64_REGS[..],
-            MinidumpRawContext::Ppc(_) => unimplemented!(),
-            MinidumpRawContext::Ppc64(_) => unimplemented!(),
+            MinidumpRawContext::Ppc(_) => &PPC_REGS[..],
+            MinidumpRawContext::Ppc64(_) => &PPC_REGS[..],
             MinidumpRawContext::Sparc(_) => unimplemented!(),
             MinidumpRawContext::X86(_) => &X86_REGS[..],
             MinidumpRawContext::Mips(_) => unimplemented!(),
         }
     }
 
+    /// The FXSAVE state of an x86 or x86-64 context, if all of it is valid.
+    ///
+    /// x86 contexts only have it if they have `CONTEXT_X86_EXTENDED_REGISTERS`.
+    fn fxsave(&self) -> Option<md::XMM_SAVE_AREA32> {
+        if self.valid != MinidumpContextValidity::All {
+            return None;
+        }
+        // The FXSAVE state is always little-endian, because x86 is.
+        match self.raw {
+            MinidumpRawContext::Amd64(ref ctx) => ctx.float_save.pread_with(0, LE).ok(),
+            MinidumpRawContext::X86(ref ctx)
+                if ctx.context_flags & md::CONTEXT_X86_EXTENDED_REGISTERS != 0 =>
+            {
+                ctx.extended_registers.pread_with(0, LE).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the value of `xmm<index>` (0-15 for x86-64, 0-7 for x86), if this
+    /// context has the SSE registers.
+    pub fn xmm(&self, index: usize) -> Option<u128> {
+        let count = match self.raw {
+            MinidumpRawContext::X86(_) => 8,
+            _ => 16,
+        };
+        if index >= count {
+            return None;
+        }
+        Some(self.fxsave()?.xmm_registers[index])
+    }
+
+    /// Get the value of the MXCSR, if this context has the SSE registers.
+    pub fn mxcsr(&self) -> Option<u32> {
+        Some(self.fxsave()?.mx_csr)
+    }
+
+    /// Describe the MXCSR, if this context has the SSE registers: which floating
+    /// point exceptions have been raised, which of them trap, and the rounding mode.
+    ///
+    /// e.g. "0x1d84 (raised: ZE; unmasked: ZE; rounding: nearest)"
+    pub fn mxcsr_description(&self) -> Option<String> {
+        self.mxcsr().map(describe_mxcsr)
+    }
+
+    /// Get the value of the x87 register `st<index>` (0-7), if this is an x86
+    /// or x86-64 context.
+    ///
+    /// The 80-bit extended precision value is in the low bits. These are the
+    /// registers in stack order, so `st0` is the top of the stack, and they
+    /// double as the MMX registers.
+    pub fn x87_register(&self, index: usize) -> Option<u128> {
+        if index >= 8 || self.valid != MinidumpContextValidity::All {
+            return None;
+        }
+        match self.raw {
+            MinidumpRawContext::X86(ref ctx) => {
+                let mut bytes = [0; 16];
+                bytes[..10].copy_from_slice(&ctx.float_save.register_area[index * 10..][..10]);
+                Some(u128::from_le_bytes(bytes))
+            }
+            MinidumpRawContext::Amd64(_) => {
+                Some(self.fxsave()?.float_registers[index] & ((1 << 80) - 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// The x87 control, status, and tag words.
+    fn fpu_words(&self) -> Option<[u16; 3]> {
+        if self.valid != MinidumpContextValidity::All {
+            return None;
+        }
+        match self.raw {
+            MinidumpRawContext::X86(ref ctx) => {
+                let float_save = &ctx.float_save;
+                Some([
+                    float_save.control_word as u16,
+                    float_save.status_word as u16,
+                    float_save.tag_word as u16,
+                ])
+            }
+            MinidumpRawContext::Amd64(_) => {
+                let fxsave = self.fxsave()?;
+                Some([
+                    fxsave.control_word,
+                    fxsave.status_word,
+                    fxsave.tag_word as u16,
+                ])
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the x87 FPU control word, if this is an x86 or x86-64 context.
+    pub fn fpu_control_word(&self) -> Option<u16> {
+        Some(self.fpu_words()?[0])
+    }
+
+    /// Get the x87 FPU status word, if this is an x86 or x86-64 context.
+    pub fn fpu_status_word(&self) -> Option<u16> {
+        Some(self.fpu_words()?[1])
+    }
+
+    /// Get the x87 FPU tag word, if this is an x86 or x86-64 context.
+    ///
+    /// x86-64 contexts only have the abridged tag word from FXSAVE, which has
+    /// one bit per register (set if it's in use) instead of two.
+    pub fn fpu_tag_word(&self) -> Option<u16> {
+        Some(self.fpu_words()?[2])
+    }
+
+    /// Get the value of `ymm<index>` (0-15), lowest 128 bits first, if this
+    /// context has the AVX registers.
+    pub fn ymm(&self, index: usize) -> Option<[u128; 2]> {
+        let high = self.xstate.as_ref()?.ymm_high?.get(index).copied()?;
+        Some([self.xmm(index)?, high])
+    }
+
+    /// Get the value of `zmm<index>` (0-31), lowest 128 bits first, if this
+    /// context has the AVX-512 registers.
+    pub fn zmm(&self, index: usize) -> Option<[u128; 4]> {
+        let xstate = self.xstate.as_ref()?;
+        if index < 16 {
+            let [low, mid] = self.ymm(index)?;
+            let [high_low, high_high] = xstate.zmm_high?[index];
+            Some([low, mid, high_low, high_high])
+        } else {
+            xstate.zmm_hi16?.get(index - 16).copied()
+        }
+    }
+
+    /// Get the value of the opmask register `k<index>` (0-7), if this context
+    /// has the AVX-512 registers.
+    pub fn opmask(&self, index: usize) -> Option<u64> {
+        self.xstate.as_ref()?.opmask?.get(index).copied()
+    }
+
+    /// The floating point state of an ARM64 context, if all of it is valid.
+    ///
+    /// Unwinding doesn't recover any of these registers, so they're only
+    /// valid for contexts that were read from a minidump.
+    fn arm64_float_save(&self) -> Option<md::FLOATING_SAVE_AREA_ARM64> {
+        if self.valid != MinidumpContextValidity::All {
+            return None;
+        }
+        match self.raw {
+            MinidumpRawContext::Arm64(ref ctx) => Some(ctx.float_save.clone()),
+            MinidumpRawContext::OldArm64(ref ctx) => {
+                let float_save = ctx.float_save;
+                Some(md::FLOATING_SAVE_AREA_ARM64 {
+                    regs: float_save.regs,
+                    fpsr: float_save.fpsr,
+                    fpcr: float_save.fpcr,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the value of the NEON/FP register `v<index>` (0-31), if this is an
+    /// ARM64 context whose registers are all valid.
+    pub fn arm64_vector(&self, index: usize) -> Option<u128> {
+        self.arm64_float_save()?.regs.get(index).copied()
+    }
+
+    /// Get the value of `fpsr`, if this is an ARM64 context whose registers
+    /// are all valid.
+    pub fn fpsr(&self) -> Option<u32> {
+        Some(self.arm64_float_save()?.fpsr)
+    }
+
+    /// Get the value of `fpcr`, if this is an ARM64 context whose registers
+    /// are all valid.
+    pub fn fpcr(&self) -> Option<u32> {
+        Some(self.arm64_float_save()?.fpcr)
+    }
+
+    /// The vector registers from the extended part of an x86-64 context, or
+    /// the NEON/FP registers of an ARM64 context, with their names, in a known
+    /// order.
+    ///
+    /// Registers that are entirely zero aren't included, because most of them
+    /// usually are.
+    pub fn nonzero_vector_registers(&self) -> Vec<(String, String)> {
+        let mut registers = vec![];
+        if let Some(float_save) = self.arm64_float_save() {
+            for (i, &v) in float_save.regs.iter().enumerate() {
+                if v != 0 {
+                    registers.push((format!("v{}", i), format_vector(&[v])));
+                }
+            }
+            for (name, value) in [("fpsr", float_save.fpsr), ("fpcr", float_save.fpcr)] {
+                if value != 0 {
+                    registers.push((name.to_string(), format!("{:#010x}", value)));
+                }
+            }
+            return registers;
+        }
+        if self.xstate.is_none() {
+            return registers;
+        }
+        for i in 0..32 {
+            let (name, value) = if let Some(zmm) = self.zmm(i) {
+                (format!("zmm{}", i), zmm.to_vec())
+            } else if let Some(ymm) = self.ymm(i) {
+                (format!("ymm{}", i), ymm.to_vec())
+            } else {
+                continue;
+            };
+            if value.iter().any(|&lane| lane != 0) {
+                registers.push((name, format_vector(&value)));
+            }
+        }
+        for i in 0..8 {
+            match self.opmask(i) {
+                Some(k) if k != 0 => registers.push((format!("k{}", i), format!("{:#018x}", k))),
+                _ => {}
+            }
+        }
+        registers
+    }
+
     /// Write a human-readable description of this `MinidumpContext` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -874,13 +1654,71 @@ impl MinidumpContext {
                     raw.extended_registers.len(),
                 )?;
                 write_bytes(f, &raw.extended_registers)?;
-                write!(f, "\n\n")?;
+                writeln!(f)?;
+                if let Some(mxcsr) = self.mxcsr() {
+                    writeln!(
+                        f,
+                        "  mxcsr                        = {}",
+                        describe_mxcsr(mxcsr)
+                    )?;
+                    for i in 0..8 {
+                        if let Some(xmm) = self.xmm(i) {
+                            writeln!(f, "  xmm{}                         = {:#x}", i, xmm)?;
+                        }
+                    }
+                }
+                writeln!(f)?;
             }
-            MinidumpRawContext::Ppc(_) => {
-                unimplemented!();
+            MinidumpRawContext::Ppc(ref raw) => {
+                write!(
+                    f,
+                    r#"CONTEXT_PPC
+  context_flags            = {:#x}
+  srr0                     = {:#x}
+  srr1                     = {:#x}
+"#,
+                    raw.context_flags, raw.srr0, raw.srr1
+                )?;
+                for (i, reg) in raw.gpr.iter().enumerate() {
+                    writeln!(f, "  gpr[{:2}]                  = {:#x}", i, reg)?;
+                }
+                write!(
+                    f,
+                    r#"  cr                       = {:#x}
+  xer                      = {:#x}
+  lr                       = {:#x}
+  ctr                      = {:#x}
+  mq                       = {:#x}
+  vrsave                   = {:#x}
+"#,
+                    raw.cr, raw.xer, raw.lr, raw.ctr, raw.mq, raw.vrsave
+                )?;
+                write_ppc_float_and_vector_save(f, &raw.float_save, &raw.vector_save)?;
             }
-            MinidumpRawContext::Ppc64(_) => {
-                unimplemented!();
+            MinidumpRawContext::Ppc64(ref raw) => {
+                write!(
+                    f,
+                    r#"CONTEXT_PPC64
+  context_flags            = {:#x}
+  srr0                     = {:#x}
+  srr1                     = {:#x}
+"#,
+                    raw.context_flags, raw.srr0, raw.srr1
+                )?;
+                for (i, reg) in raw.gpr.iter().enumerate() {
+                    writeln!(f, "  gpr[{:2}]                  = {:#x}", i, reg)?;
+                }
+                write!(
+                    f,
+                    r#"  cr                       = {:#x}
+  xer                      = {:#x}
+  lr                       = {:#x}
+  ctr                      = {:#x}
+  vrsave                   = {:#x}
+"#,
+                    raw.cr, raw.xer, raw.lr, raw.ctr, raw.vrsave
+                )?;
+                write_ppc_float_and_vector_save(f, &raw.float_save, &raw.vector_save)?;
             }
             MinidumpRawContext::Amd64(ref raw) => {
                 write!(
@@ -965,6 +1803,21 @@ impl MinidumpContext {
                     raw.r15,
                     raw.rip,
                 )?;
+                if self.xstate.is_some() {
+                    for i in 0..32 {
+                        if let Some(zmm) = self.zmm(i) {
+                            writeln!(f, "  zmm{:<10} = {}", i, format_vector(&zmm))?;
+                        } else if let Some(ymm) = self.ymm(i) {
+                            writeln!(f, "  ymm{:<10} = {}", i, format_vector(&ymm))?;
+                        }
+                    }
+                    for i in 0..8 {
+                        if let Some(k) = self.opmask(i) {
+                            writeln!(f, "  k{:<12} = {:#018x}", i, k)?;
+                        }
+                    }
+                    writeln!(f)?;
+                }
             }
             MinidumpRawContext::Sparc(_) => {
                 unimplemented!();
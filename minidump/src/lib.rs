@@ -19,8 +19,19 @@
 //! header and stream directory. Individual streams are only parsed when they're
 //! requested.
 //!
+//! Full-memory dumps may be too large to map into memory, in which case
+//! [`StreamingMinidump::open`][] only reads the parts of the file it needs.
+//! [`StreamingMinidump::to_minidump`][] turns it into a (much smaller)
+//! `Minidump` with just the thread stacks out of the dump's bulk memory.
+//!
+//! A `Minidump` can also be written back out with [`Minidump::write`][] (for
+//! instance after scrubbing some streams with [`Minidump::write_filtered`][]),
+//! and new minidumps can be built up with [`MinidumpWriter`][]. To remove
+//! personal information like environment variables and heap memory first,
+//! see [`MinidumpScrubber`][].
+//!
 //! Although you may enumerate the streams in a minidump with methods like
-//! [`Minidump::all_streams`][], this is only really useful for debugging. Instead
+//! [`Minidump::streams`][], this is only really useful for debugging. Instead
 //! you should statically request streams with [`Minidump::get_stream`][].
 //! Depending on what analysis you're trying to perform, you may:
 //!
@@ -46,9 +57,9 @@
 //!     // Conditionally analyze a stream
 //!     if let Ok(threads) = dump.get_stream::<MinidumpThreadList>() {
 //!         // Use `Default` to try to make progress when a stream is missing.
-//!         // This is especially natural for MinidumpMemoryList because
+//!         // This is especially natural for the memory because
 //!         // everything needs to handle memory lookups failing anyway.
-//!         let mem = dump.get_stream::<MinidumpMemoryList>().unwrap_or_default();
+//!         let mem = dump.memory().unwrap_or_default();
 //!
 //!         for thread in &threads.threads {
 //!             let stack = thread.stack_memory(&mem);
@@ -71,6 +82,9 @@
 //! * [`MinidumpBreakpadInfo`][]
 //! * [`MinidumpCrashpadInfo`][]
 //! * [`MinidumpException`][]
+//! * [`MinidumpFunctionTableList`][]
+//! * [`MinidumpHandleDataStream`][]
+//! * [`MinidumpHandleOperationList`][]
 //! * [`MinidumpLinuxCpuInfo`][]
 //! * [`MinidumpLinuxEnviron`][]
 //! * [`MinidumpLinuxLsbRelease`][]
@@ -78,11 +92,15 @@
 //! * [`MinidumpLinuxProcStatus`][]
 //! * [`MinidumpMacCrashInfo`][]
 //! * [`MinidumpMemoryList`][]
+//! * [`MinidumpMemory64List`][]
 //! * [`MinidumpMemoryInfoList`][]
 //! * [`MinidumpMiscInfo`][]
 //! * [`MinidumpModuleList`][]
+//! * [`MinidumpProcessVmCounters`][]
 //! * [`MinidumpSystemInfo`][]
+//! * [`MinidumpSystemMemoryInfo`][]
 //! * [`MinidumpThreadList`][]
+//! * [`MinidumpThreadInfoList`][]
 //! * [`MinidumpThreadNames`][]
 //! * [`MinidumpUnloadedModuleList`][]
 //!
@@ -107,7 +125,8 @@
 //! every thread.
 //!
 //! [`MinidumpMemoryList`][] maps the crashing program's runtime addresses (such as
-//! `$rsp`) to ranges of memory in the Minidump.
+//! `$rsp`) to ranges of memory in the Minidump. Full-memory dumps use
+//! [`MinidumpMemory64List`][] instead, and [`Minidump::memory`][] combines the two.
 //!
 //! [`MinidumpModuleList`][] includes info on all the modules (libraries) that were
 //! linked into the crashing program. This enables symbolication, as you can map
@@ -262,7 +281,8 @@
 //! [`MINIDUMP_STREAM_TYPE`][format::MINIDUMP_STREAM_TYPE], but users
 //! are allowed to define their own stream types, so it's normal to see unknown
 //! types (this is the primary mechanism breakpad uses to extend the format without
-//! causing upstream problems).
+//! causing upstream problems). [`Minidump::get_raw_stream`][] returns the contents
+//! of a stream of any type, so you can parse your own streams on top of this crate.
 //!
 //! And that's it! Everything else in a minidump is just all the different types of
 //! stream. As of this writing, rust-minidump is aware of 51 different types of
@@ -295,7 +315,9 @@
 //! * [`MinidumpBreakpadInfo`][] (contains [`MINIDUMP_BREAKPAD_INFO`][format::MINIDUMP_BREAKPAD_INFO])
 //! * [`MinidumpCrashpadInfo`][] (contains [`MINIDUMP_CRASHPAD_INFO`][format::MINIDUMP_CRASHPAD_INFO])
 //! * [`MinidumpException`][] (contains [`MINIDUMP_EXCEPTION_STREAM`][format::MINIDUMP_EXCEPTION_STREAM])
+//! * [`MinidumpProcessVmCounters`][] (contains [`MINIDUMP_PROCESS_VM_COUNTERS_2`][format::MINIDUMP_PROCESS_VM_COUNTERS_2])
 //! * [`MinidumpSystemInfo`][] (contains [`MINIDUMP_SYSTEM_INFO`][format::MINIDUMP_SYSTEM_INFO])
+//! * [`MinidumpSystemMemoryInfo`][] (contains [`MINIDUMP_SYSTEM_MEMORY_INFO_1`][format::MINIDUMP_SYSTEM_MEMORY_INFO_1])
 //!
 //!
 //!
@@ -350,6 +372,7 @@
 //! Known members of this family:
 //!
 //! * [`MinidumpMemoryInfoList`][] (entries are [`MINIDUMP_MEMORY_INFO`][format::MINIDUMP_MEMORY_INFO])
+//! * [`MinidumpThreadInfoList`][] (entries are [`MINIDUMP_THREAD_INFO`][format::MINIDUMP_THREAD_INFO])
 //! * [`MinidumpUnloadedModuleList`][] (entries are [`MINIDUMP_UNLOADED_MODULE`][format::MINIDUMP_UNLOADED_MODULE])
 //!
 //!
@@ -391,6 +414,7 @@
 doc_comment::doctest!("../README.md");
 
 pub use scroll::Endian;
+pub use uuid::Uuid;
 
 mod context;
 mod iostuff;
@@ -398,6 +422,7 @@ mod minidump;
 
 pub use minidump_common::format;
 pub use minidump_common::traits::Module;
+pub use minidump_common::utils::{basename, pdb_file_name};
 
 pub use crate::iostuff::Readable;
 pub use crate::minidump::*;
@@ -2,27 +2,34 @@
 // file at the top-level directory of this distribution.
 
 use chrono::prelude::*;
-use encoding::all::UTF_16LE;
-use encoding::{DecoderTrap, Encoding};
+use encoding::all::{UTF_16BE, UTF_16LE};
+use encoding::{DecoderTrap, EncoderTrap, EncodingRef};
 use failure::Fail;
 use log::warn;
 use memmap::Mmap;
 use num_traits::FromPrimitive;
-use scroll::ctx::{SizeWith, TryFromCtx};
-use scroll::{self, Pread, BE, LE};
+use scroll::ctx::{SizeWith, TryFromCtx, TryIntoCtx};
+use scroll::{self, Pread, Pwrite, BE, LE};
+use std::any::{Any, TypeId};
 use std::borrow::Cow;
+use std::cell::{OnceCell, RefCell};
 use std::collections::BTreeMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
+use std::io::{BufReader, SeekFrom};
 use std::iter;
 use std::marker::PhantomData;
 use std::mem;
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
 use std::str;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
 
 pub use crate::context::*;
 use crate::strings::*;
@@ -30,6 +37,7 @@ use crate::system_info::{Cpu, Os};
 use minidump_common::format as md;
 use minidump_common::format::{CvSignature, MINIDUMP_STREAM_TYPE};
 use minidump_common::traits::{IntoRangeMapSafe, Module};
+use minidump_common::utils::basename;
 use range_map::{Range, RangeMap};
 
 /// An index into the contents of a minidump.
@@ -61,8 +69,15 @@ where
     /// The raw minidump header from the file.
     pub header: md::MINIDUMP_HEADER,
     streams: HashMap<u32, (u32, md::MINIDUMP_DIRECTORY)>,
+    /// Streams with the same type as a later stream in the directory, which
+    /// [`Minidump::get_stream`][] ignores. In directory order.
+    shadowed_streams: Vec<(u32, md::MINIDUMP_DIRECTORY)>,
     /// The endianness of this minidump file.
     pub endian: scroll::Endian,
+    /// What was missing from the file when it was read.
+    warnings: Vec<MinidumpWarning>,
+    /// Streams that have already been parsed, by type. See [`Minidump::get_stream_cached`].
+    stream_cache: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
     _phantom: PhantomData<&'a [u8]>,
 }
 
@@ -98,6 +113,153 @@ pub enum Error {
     DataError,
     #[fail(display = "Error reading CodeView data")]
     CodeViewReadFailure,
+    #[fail(display = "Error writing minidump")]
+    WriteFailure,
+    #[fail(
+        display = "Stream type {:#x} is reserved, user streams must be above LastReservedStream",
+        stream_type
+    )]
+    ReservedStreamType { stream_type: u32 },
+    #[fail(
+        display = "The minidump already has a stream of type {:#x}",
+        stream_type
+    )]
+    DuplicateStreamType { stream_type: u32 },
+}
+
+/// Problems with a `Minidump` that didn't stop it from being read.
+///
+/// Minidumps are often written by a process that's in the middle of crashing,
+/// or cut short when they're uploaded, so their ends can be missing. Instead of
+/// refusing such dumps, [`Minidump::read`][] keeps everything that's still there
+/// and records what's missing in [`Minidump::warnings`][].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MinidumpWarning {
+    /// The stream directory was cut short, so only the first `read` of its
+    /// `expected` streams are known.
+    TruncatedDirectory { expected: u32, read: u32 },
+    /// A stream starts past the end of the file.
+    MissingStream { stream_type: u32 },
+    /// There are `count` streams of the same type. [`Minidump::get_stream`][]
+    /// uses the last one, see [`Minidump::get_all_streams`][] for all of them.
+    DuplicateStream { stream_type: u32, count: usize },
+    /// A stream was cut short, to `actual` of its `expected` bytes.
+    TruncatedStream {
+        stream_type: u32,
+        expected: u32,
+        actual: u32,
+    },
+    /// The contents of a memory region start past the end of the file.
+    ///
+    /// `index` is the region's index in its memory list stream.
+    MissingMemory {
+        index: usize,
+        base_address: u64,
+        size: u64,
+    },
+    /// The contents of a memory region were cut short, to `actual` of its `expected` bytes.
+    TruncatedMemory {
+        index: usize,
+        base_address: u64,
+        expected: u64,
+        actual: u64,
+    },
+    /// A CPU context's flags say it's for `context_cpu`, but the system info says
+    /// the minidump is for `system_info_cpu`.
+    ///
+    /// [`Minidump::read`][] doesn't look at contexts, so this comes from whoever read
+    /// them, see [`MinidumpContext::check_cpu`][]. `exception` is whether it's the
+    /// exception stream's context for `thread_id` rather than the thread's own,
+    /// and `used` is whether it was still read as a `system_info_cpu` context.
+    ContextCpuMismatch {
+        thread_id: u32,
+        exception: bool,
+        context_cpu: md::ContextFlagsCpu,
+        system_info_cpu: md::ContextFlagsCpu,
+        used: bool,
+    },
+    /// There was no system info, so `cpu` was inferred from a CPU context.
+    ///
+    /// This also comes from whoever used [`Minidump::infer_system_info`][].
+    SystemInfoInferred { cpu: Cpu },
+}
+
+impl fmt::Display for MinidumpWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MinidumpWarning::TruncatedDirectory { expected, read } => write!(
+                f,
+                "stream directory truncated, only {} of {} streams could be read",
+                read, expected
+            ),
+            MinidumpWarning::MissingStream { stream_type } => write!(
+                f,
+                "stream {} is past the end of the file",
+                StreamTypeName(stream_type)
+            ),
+            MinidumpWarning::DuplicateStream { stream_type, count } => write!(
+                f,
+                "{} streams of type {}, using the last one",
+                count,
+                StreamTypeName(stream_type)
+            ),
+            MinidumpWarning::TruncatedStream {
+                stream_type,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "stream {} truncated from {} to {} bytes",
+                StreamTypeName(stream_type),
+                expected,
+                actual
+            ),
+            MinidumpWarning::MissingMemory {
+                index,
+                base_address,
+                size,
+            } => write!(
+                f,
+                "memory region {} at {:#x} ({} bytes) is past the end of the file",
+                index, base_address, size
+            ),
+            MinidumpWarning::TruncatedMemory {
+                index,
+                base_address,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "memory region {} at {:#x} truncated from {} to {} bytes",
+                index, base_address, expected, actual
+            ),
+            MinidumpWarning::ContextCpuMismatch {
+                thread_id,
+                exception,
+                context_cpu,
+                system_info_cpu,
+                used,
+            } => write!(
+                f,
+                "{} context of thread {:#x} is for {} but the system info says {}, {}",
+                if exception { "exception" } else { "thread" },
+                thread_id,
+                context_cpu_name(context_cpu),
+                context_cpu_name(system_info_cpu),
+                if used {
+                    "reading it that way anyway"
+                } else {
+                    "ignoring it"
+                }
+            ),
+            MinidumpWarning::SystemInfoInferred { cpu } => write!(
+                f,
+                "no system info, so the cpu ({}) was inferred from a thread context",
+                cpu
+            ),
+        }
+    }
 }
 
 /// The fundamental unit of data in a `Minidump`.
@@ -110,6 +272,30 @@ pub trait MinidumpStream<'a>: Sized {
     /// `all` refers to the full contents of the minidump, for reading auxilliary data
     /// referred to with `MINIDUMP_LOCATION_DESCRIPTOR`s.
     fn read(bytes: &'a [u8], all: &'a [u8], endian: scroll::Endian) -> Result<Self, Error>;
+    /// Read this `MinidumpStream` type from `bytes`, knowing the minidump's system info.
+    ///
+    /// This is what [`Minidump::get_stream`][] uses. Streams that can't be fully
+    /// interpreted without knowing the OS or CPU (like [`MinidumpModuleList`][])
+    /// override it, for everything else it's the same as [`MinidumpStream::read`][].
+    fn read_with_system_info(
+        bytes: &'a [u8],
+        all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<Self, Error> {
+        Self::read(bytes, all, endian)
+    }
+}
+
+/// A [`MinidumpStream`][] that can be serialized into a new minidump.
+///
+/// Anything the stream refers to outside of itself (strings, CPU contexts, memory...)
+/// is written to the [`MinidumpWriter`][] along with it, and the stream is written
+/// with its `MINIDUMP_LOCATION_DESCRIPTOR`s updated to point at the new copies.
+pub trait MinidumpStreamWrite<'a>: MinidumpStream<'a> {
+    /// Write this stream to `writer`, returning the location of the stream itself.
+    fn write(&self, writer: &mut MinidumpWriter)
+        -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error>;
 }
 
 /// Provides a unified interface for getting metadata about the process's mapped memory regions
@@ -158,29 +344,58 @@ pub struct MinidumpLinuxMaps<'a> {
 pub struct MinidumpLinuxMapInfo<'a> {
     /// The first address this metadata applies to
     pub base_address: u64,
-    /// The last address this metadata applies to
+    /// The end of the mapping (exclusive, like in `/proc/self/maps`)
     pub final_address: u64,
 
     /// The kind of mapping
     pub kind: MinidumpLinuxMapKind<'a>,
 
-    // FIXME: These could be bitflags but I'm not worried about it right now
-    /// Whether the memory region is readable.
-    pub is_read: bool,
-    /// Whether the memory region is writeable.
-    pub is_write: bool,
-    /// Whether the memory region is executable.
-    pub is_exec: bool,
-    /// Whether the memory region is shared.
-    pub is_shared: bool,
-    /// Whether the memory region is private (copy-on-write).
-    pub is_private: bool,
-
-    // Fields in the format we ignore (not yet useful)
-    // * offset
-    // * dev
-    // * inode
-    _phantom: PhantomData<&'a u8>,
+    /// What the process was allowed to do with the memory region.
+    pub permissions: MinidumpLinuxMapPermissions,
+    /// The offset of the mapping into the mapped file/device.
+    pub offset: u64,
+    /// The (major, minor) numbers of the mapped file's device.
+    pub dev: (u32, u32),
+    /// The inode of the mapped file on `dev`, or 0 if there is none.
+    pub inode: u64,
+}
+
+bitflags::bitflags! {
+    /// The `perms` of a [`MinidumpLinuxMapInfo`].
+    pub struct MinidumpLinuxMapPermissions: u8 {
+        /// The memory region is readable (`r`).
+        const READ = 1 << 0;
+        /// The memory region is writeable (`w`).
+        const WRITE = 1 << 1;
+        /// The memory region is executable (`x`).
+        const EXEC = 1 << 2;
+        /// The memory region is shared (`s`).
+        const SHARED = 1 << 3;
+        /// The memory region is private, i.e. copy-on-write (`p`).
+        const PRIVATE = 1 << 4;
+    }
+}
+
+impl fmt::Display for MinidumpLinuxMapPermissions {
+    /// Formats the permissions like in `/proc/self/maps`, e.g. `r-xp`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |flag, c| if self.contains(flag) { c } else { '-' };
+        let sharing = if self.contains(Self::PRIVATE) {
+            'p'
+        } else if self.contains(Self::SHARED) {
+            's'
+        } else {
+            '-'
+        };
+        write!(
+            f,
+            "{}{}{}{}",
+            flag(Self::READ, 'r'),
+            flag(Self::WRITE, 'w'),
+            flag(Self::EXEC, 'x'),
+            sharing
+        )
+    }
 }
 
 /// A broad classification of the mapped memory described by a [`MinidumpLinuxMapInfo`].
@@ -253,6 +468,106 @@ pub struct MinidumpModule {
     pub codeview_info: Option<CodeView>,
     /// A misc debug record, if one is present.
     pub misc_info: Option<md::IMAGE_DEBUG_MISC>,
+    /// The OS of the minidump this module came from, if known.
+    ///
+    /// The same CodeView record means different things on different OSes.
+    os: Option<Os>,
+}
+
+/// Patterns for recognizing the operating system's own modules, see
+/// [`MinidumpModule::is_system_module_with`][].
+///
+/// Patterns are matched case-insensitively, and `\` and `/` are treated the same.
+/// The [`Default`][] patterns cover Windows, macOS and Linux.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemModulePatterns {
+    /// Directories that only contain system modules, e.g. `C:\Windows\System32\`.
+    pub path_prefixes: Vec<String>,
+    /// Names of system modules, or the start of them (e.g. `libc-` for `libc-2.31.so`).
+    /// These match modules in any directory.
+    pub name_prefixes: Vec<String>,
+}
+
+static SYSTEM_PATH_PREFIXES: &[&str] = &[
+    // Windows
+    "c:/windows/system32/",
+    "c:/windows/syswow64/",
+    "c:/windows/winsxs/",
+    // macOS
+    "/system/library/",
+    "/usr/lib/system/",
+    // Linux (and /usr/lib/ on macOS)
+    "/lib/",
+    "/lib32/",
+    "/lib64/",
+    "/usr/lib/",
+    "/usr/lib32/",
+    "/usr/lib64/",
+];
+
+static SYSTEM_NAME_PREFIXES: &[&str] = &[
+    // Windows
+    "kernel32.dll",
+    "kernelbase.dll",
+    "msvcrt.dll",
+    "ntdll.dll",
+    "ucrtbase.dll",
+    "user32.dll",
+    // macOS
+    "libdyld.dylib",
+    "libsystem_",
+    // Linux
+    "ld-linux",
+    "libc-",
+    "libc.so",
+    "libdl-",
+    "libdl.so",
+    "libm-",
+    "libm.so",
+    "libpthread-",
+    "libpthread.so",
+    "linux-gate.so",
+    "linux-vdso.so",
+];
+
+impl Default for SystemModulePatterns {
+    fn default() -> Self {
+        let owned = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        SystemModulePatterns {
+            path_prefixes: owned(SYSTEM_PATH_PREFIXES),
+            name_prefixes: owned(SYSTEM_NAME_PREFIXES),
+        }
+    }
+}
+
+/// Percent-encode `segment` so it can be used as one segment of a URL's path.
+fn url_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for &byte in segment.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Whether `path` matches any of the [`SystemModulePatterns`].
+fn is_system_path<'p>(
+    path: &str,
+    path_prefixes: impl IntoIterator<Item = &'p str>,
+    name_prefixes: impl IntoIterator<Item = &'p str>,
+) -> bool {
+    let normalize = |s: &str| s.replace('\\', "/").to_lowercase();
+    let path = normalize(path);
+    let name = basename(&path);
+    path_prefixes
+        .into_iter()
+        .any(|prefix| path.starts_with(&normalize(prefix)))
+        || name_prefixes
+            .into_iter()
+            .any(|prefix| name.starts_with(&normalize(prefix)))
 }
 
 /// A list of `MinidumpModule`s contained in a `Minidump`.
@@ -270,6 +585,80 @@ pub struct MinidumpThreadNames {
     names: HashMap<u32, String>,
 }
 
+/// Extra information about a thread, such as its start address and CPU times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpThreadInfo {
+    /// The `MINIDUMP_THREAD_INFO` direct from the minidump file.
+    pub raw: md::MINIDUMP_THREAD_INFO,
+    /// Flags describing how the thread was dumped.
+    pub dump_flags: md::ThreadInfoDumpFlags,
+}
+
+/// A list of `MinidumpThreadInfo`s contained in a `Minidump`.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpThreadInfoList {
+    /// The thread infos, in the order they were stored in the minidump.
+    thread_infos: Vec<MinidumpThreadInfo>,
+    /// Map from thread id to index in thread_infos.
+    thread_ids: HashMap<u32, usize>,
+}
+
+/// The raw descriptor of a handle, which has gone through several revisions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawHandleDescriptor {
+    HandleDescriptor(md::MINIDUMP_HANDLE_DESCRIPTOR),
+    HandleDescriptor2(md::MINIDUMP_HANDLE_DESCRIPTOR_2),
+}
+
+/// A handle the process had open when the `Minidump` was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpHandleDescriptor {
+    /// The `MINIDUMP_HANDLE_DESCRIPTOR` direct from the minidump file.
+    pub raw: RawHandleDescriptor,
+    /// The name of the type of the object, e.g. "Event" or "File". This is stored separately
+    /// in the minidump.
+    pub type_name: Option<String>,
+    /// The name of the object, if it has one. This is stored separately in the minidump.
+    pub object_name: Option<String>,
+}
+
+/// The handles the process had open when the `Minidump` was written.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpHandleDataStream {
+    /// The handles, in the order they were stored in the minidump.
+    handles: Vec<MinidumpHandleDescriptor>,
+}
+
+/// An operation on a handle, traced by Application Verifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpHandleOperation {
+    /// The `AVRF_HANDLE_OPERATION` direct from the minidump file.
+    pub raw: md::AVRF_HANDLE_OPERATION,
+}
+
+/// The traced operations on handles, if the process had handle tracing enabled.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpHandleOperationList {
+    /// The operations, in the order they were stored in the minidump.
+    operations: Vec<MinidumpHandleOperation>,
+}
+
+/// A function table registered at runtime, typically to describe JIT code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpFunctionTable {
+    /// The `MINIDUMP_FUNCTION_TABLE_DESCRIPTOR` direct from the minidump file.
+    pub raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR,
+    /// The functions in the table, with addresses relative to `raw.base_address`.
+    pub entries: Vec<md::IMAGE_RUNTIME_FUNCTION_ENTRY>,
+}
+
+/// The function tables registered at runtime with APIs like `RtlAddFunctionTable`.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpFunctionTableList {
+    /// The function tables, in the order they were stored in the minidump.
+    tables: Vec<MinidumpFunctionTable>,
+}
+
 /// An executable or shared library that was once loaded into the process, but was unloaded
 /// by the time the `Minidump` was written.
 #[derive(Debug, Clone)]
@@ -297,8 +686,10 @@ pub struct MinidumpThread<'a> {
     pub raw: md::MINIDUMP_THREAD,
     /// The CPU context for the thread, if present.
     context: Option<&'a [u8]>,
-    /// The stack memory for the thread, if present.
-    stack: Option<MinidumpMemory<'a>>,
+    /// The minidump's data, for lazily finding the stack memory.
+    data: &'a [u8],
+    /// The stack memory for the thread, resolved by `stack_memory` on first use.
+    stack: OnceLock<MinidumpMemory<'a>>,
     /// Saved endianess for lazy parsing.
     endian: scroll::Endian,
 }
@@ -328,19 +719,85 @@ pub struct MinidumpSystemInfo {
     /// An x86 (not x64!) CPU vendor name that is stored in `raw` but in a way
     /// that's
     cpu_info: Option<String>,
+    /// Saved endianess for lazy parsing of `raw.cpu`.
+    endian: scroll::Endian,
 }
 
 /// A region of memory from the process that wrote the minidump.
+///
+/// This is generic over the kind of descriptor the region was read from,
+/// see [`MinidumpMemory`] and [`MinidumpMemory64`].
 #[derive(Clone, Debug)]
-pub struct MinidumpMemory<'a> {
-    /// The raw `MINIDUMP_MEMORY_DESCRIPTOR` from the minidump.
-    pub desc: md::MINIDUMP_MEMORY_DESCRIPTOR,
+pub struct MinidumpMemoryBase<'a, Descriptor> {
+    /// The raw descriptor from the minidump.
+    pub desc: Descriptor,
     /// The starting address of this range of memory.
     pub base_address: u64,
     /// The length of this range of memory.
     pub size: u64,
     /// The contents of the memory.
     pub bytes: &'a [u8],
+    /// The byte order of the process the memory is from, which values are read in.
+    pub endian: scroll::Endian,
+}
+
+/// A region of memory from a [`MinidumpMemoryList`].
+pub type MinidumpMemory<'a> = MinidumpMemoryBase<'a, md::MINIDUMP_MEMORY_DESCRIPTOR>;
+
+/// A region of memory from a [`MinidumpMemory64List`].
+pub type MinidumpMemory64<'a> = MinidumpMemoryBase<'a, md::MINIDUMP_MEMORY_DESCRIPTOR64>;
+
+/// A kind of memory descriptor that can appear in a memory list.
+///
+/// This is implemented for `MINIDUMP_MEMORY_DESCRIPTOR` (from a [`MinidumpMemoryList`])
+/// and `MINIDUMP_MEMORY_DESCRIPTOR64` (from a [`MinidumpMemory64List`]).
+pub trait MemoryDescriptor: Clone {
+    /// Get the memory of the stack described by `stack` from `region`, which contains it.
+    ///
+    /// This is how [`MinidumpThread::stack_memory`] finds a stack whose own
+    /// descriptor doesn't point at any memory.
+    fn stack_memory<'a>(
+        region: &MinidumpMemoryBase<'a, Self>,
+        stack: &md::MINIDUMP_MEMORY_DESCRIPTOR,
+    ) -> MinidumpMemory<'a>;
+}
+
+impl MemoryDescriptor for md::MINIDUMP_MEMORY_DESCRIPTOR {
+    fn stack_memory<'a>(
+        region: &MinidumpMemory<'a>,
+        _stack: &md::MINIDUMP_MEMORY_DESCRIPTOR,
+    ) -> MinidumpMemory<'a> {
+        region.clone()
+    }
+}
+
+impl MemoryDescriptor for md::MINIDUMP_MEMORY_DESCRIPTOR64 {
+    fn stack_memory<'a>(
+        region: &MinidumpMemory64<'a>,
+        stack: &md::MINIDUMP_MEMORY_DESCRIPTOR,
+    ) -> MinidumpMemory<'a> {
+        // Full-memory dumps can have huge regions, so only take the part holding the
+        // stack. If the stack's size is missing, assume it goes to the end of the region.
+        let start = stack.start_of_memory_range - region.base_address;
+        let available = region.size - start;
+        let size = match stack.memory.data_size as u64 {
+            0 => available.min(u32::MAX as u64),
+            size => size.min(available),
+        };
+        MinidumpMemory {
+            desc: md::MINIDUMP_MEMORY_DESCRIPTOR {
+                start_of_memory_range: stack.start_of_memory_range,
+                memory: md::MINIDUMP_LOCATION_DESCRIPTOR {
+                    data_size: size as u32,
+                    rva: stack.memory.rva,
+                },
+            },
+            base_address: stack.start_of_memory_range,
+            size,
+            bytes: &region.bytes[start as usize..(start + size) as usize],
+            endian: region.endian,
+        }
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -383,6 +840,30 @@ pub struct MinidumpMiscInfo {
     pub raw: RawMiscInfo,
 }
 
+/// System-wide memory information, like how much memory is committed.
+///
+/// MinidumpSystemMemoryInfo wraps MINIDUMP_SYSTEM_MEMORY_INFO_1, which newer
+/// versions of Windows write.
+#[derive(Debug)]
+pub struct MinidumpSystemMemoryInfo {
+    /// The `MINIDUMP_SYSTEM_MEMORY_INFO_1` struct direct from the minidump.
+    pub raw: md::MINIDUMP_SYSTEM_MEMORY_INFO_1,
+}
+
+#[derive(Debug)]
+pub enum RawProcessVmCounters {
+    VmCounters1(md::MINIDUMP_PROCESS_VM_COUNTERS_1),
+    VmCounters2(md::MINIDUMP_PROCESS_VM_COUNTERS_2),
+}
+
+/// The virtual memory counters of the process that wrote the minidump,
+/// like its working set and private bytes.
+#[derive(Debug)]
+pub struct MinidumpProcessVmCounters {
+    /// The `MINIDUMP_PROCESS_VM_COUNTERS*` struct direct from the minidump.
+    pub raw: RawProcessVmCounters,
+}
+
 /// Additional information about process state.
 ///
 /// MinidumpBreakpadInfo wraps MINIDUMP_BREAKPAD_INFO, which is an optional stream
@@ -421,6 +902,44 @@ pub struct MinidumpLinuxProcStatus<'a> {
     data: &'a [u8],
 }
 
+/// The dynamic linker's list of loaded shared libraries, from a Linux minidump.
+///
+/// This is a snapshot of the process's `r_debug` and the `link_map` list it
+/// points to, so it's a record of what was loaded that's independent of the
+/// [`MinidumpModuleList`][].
+#[derive(Debug, Clone)]
+pub struct MinidumpLinuxDsoDebug {
+    /// The version number of the `r_debug` protocol.
+    pub version: u32,
+    /// The address of the function the dynamic linker calls when the list
+    /// changes, for debuggers to set a breakpoint on.
+    pub brk: u64,
+    /// The address the dynamic linker is loaded at.
+    pub ldbase: u64,
+    /// The address of the main executable's dynamic section.
+    pub dynamic: u64,
+    /// The shared libraries, in the order the dynamic linker loaded them.
+    pub link_maps: Vec<MinidumpLinkMap>,
+}
+
+/// One shared library in a [`MinidumpLinuxDsoDebug`][].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpLinkMap {
+    /// The difference between the addresses in the library's ELF file and
+    /// where it's loaded (`l_addr`).
+    ///
+    /// For shared libraries this is their base address.
+    pub addr: u64,
+    /// The library's path (`l_name`), if the minidump has one.
+    ///
+    /// The main executable usually has an empty name.
+    pub name: Option<String>,
+    /// The offset of the name in the minidump, or 0 if there is none.
+    pub name_rva: md::RVA,
+    /// The address of the library's dynamic section (`l_ld`).
+    pub ld: u64,
+}
+
 /// The reason for a process crash.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum CrashReason {
@@ -433,6 +952,7 @@ pub enum CrashReason {
     MacBadInstructionArm(md::ExceptionCodeMacBadInstructionArmType),
     MacBadInstructionPpc(md::ExceptionCodeMacBadInstructionPpcType),
     MacBadInstructionX86(md::ExceptionCodeMacBadInstructionX86Type),
+    MacArithmeticArm(md::ExceptionCodeMacArithmeticArmType),
     MacArithmeticPpc(md::ExceptionCodeMacArithmeticPpcType),
     MacArithmeticX86(md::ExceptionCodeMacArithmeticX86Type),
     MacSoftware(md::ExceptionCodeMacSoftwareType),
@@ -441,6 +961,12 @@ pub enum CrashReason {
     MacBreakpointX86(md::ExceptionCodeMacBreakpointX86Type),
     MacResource(md::ExceptionCodeMacResourceType, u64, u64),
     MacGuard(md::ExceptionCodeMacGuardType, u64, u64),
+    /// An `EXC_CRASH`, with the exception type and the (BSD) signal that
+    /// killed the process, in that order.
+    ///
+    /// The exception type is 0 if the process was killed by a signal that
+    /// didn't come from a Mach exception (e.g. `abort()`).
+    MacCrash(u32, u32),
 
     /// A Linux/Android error code with no other interesting metadata.
     LinuxGeneral(md::ExceptionCodeLinux, u32),
@@ -448,6 +974,11 @@ pub enum CrashReason {
     LinuxSigbus(md::ExceptionCodeLinuxSigbusKind),
     LinuxSigfpe(md::ExceptionCodeLinuxSigfpeKind),
     LinuxSigsegv(md::ExceptionCodeLinuxSigsegvKind),
+    LinuxSigtrap(md::ExceptionCodeLinuxSigtrapKind),
+    LinuxSigsys(md::ExceptionCodeLinuxSigsysKind),
+    /// A Linux/Android signal with a `si_code` that any signal can have,
+    /// saying who sent it.
+    LinuxSignal(md::ExceptionCodeLinux, md::ExceptionCodeLinuxSicode),
 
     /// A Windows error code with no other interesting metadata.
     WindowsGeneral(md::ExceptionCodeWindows),
@@ -495,14 +1026,60 @@ pub struct MinidumpException<'a> {
 }
 
 /// A list of memory regions included in a minidump.
+///
+/// This is generic over the kind of descriptor the regions were read from,
+/// see [`MinidumpMemoryList`] and [`MinidumpMemory64List`].
 #[derive(Debug)]
-pub struct MinidumpMemoryList<'a> {
+pub struct MinidumpMemoryListBase<'a, Descriptor> {
     /// The memory regions, in the order they were stored in the minidump.
-    regions: Vec<MinidumpMemory<'a>>,
+    regions: Vec<MinidumpMemoryBase<'a, Descriptor>>,
     /// Map from address range to index in regions. Use `MinidumpMemoryList::memory_at_address`.
     regions_by_addr: RangeMap<u64, usize>,
 }
 
+/// A list of memory regions included in a minidump (the `MemoryListStream`).
+pub type MinidumpMemoryList<'a> = MinidumpMemoryListBase<'a, md::MINIDUMP_MEMORY_DESCRIPTOR>;
+
+/// A list of memory regions included in a full-memory minidump (the `Memory64ListStream`).
+///
+/// Dumps written with `MiniDumpWithFullMemory` store all their memory here, and
+/// have no [`MinidumpMemoryList`] at all.
+pub type MinidumpMemory64List<'a> = MinidumpMemoryListBase<'a, md::MINIDUMP_MEMORY_DESCRIPTOR64>;
+
+/// The memory of a minidump, from its [`MinidumpMemoryList`], its
+/// [`MinidumpMemory64List`], or both.
+///
+/// This allows you to read the process's memory without worrying about
+/// whether the minidump is a full-memory dump. Use [`Minidump::memory`] to
+/// get one.
+///
+/// The two lists normally don't overlap, but if any regions do, only the
+/// larger one (or the later one in the minidump, if they're the same size)
+/// is used. Regions from the `MinidumpMemory64List` count as coming after
+/// those from the `MinidumpMemoryList`.
+#[derive(Debug)]
+pub struct UnifiedMemoryList<'a> {
+    memory: MinidumpMemoryList<'a>,
+    memory64: MinidumpMemory64List<'a>,
+    /// Map from address range to the region in one of the two lists.
+    regions_by_addr: RangeMap<u64, UnifiedMemoryIndex>,
+}
+
+/// An index into one of the lists of a [`UnifiedMemoryList`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum UnifiedMemoryIndex {
+    Memory(usize),
+    Memory64(usize),
+}
+
+/// A [`UnifiedMemoryList`] entry, a region of memory from the process that
+/// wrote the minidump.
+#[derive(Debug, Copy, Clone)]
+pub enum UnifiedMemory<'slf, 'a> {
+    Memory(&'slf MinidumpMemory<'a>),
+    Memory64(&'slf MinidumpMemory64<'a>),
+}
+
 /// Information about an assertion that caused a crash.
 #[derive(Debug)]
 pub struct MinidumpAssertion {
@@ -546,6 +1123,10 @@ pub struct MinidumpModuleCrashpadInfo {
 }
 
 /// Additional Crashpad-specific information carried within a minidump file.
+///
+/// This is where Crashpad clients put their "crash keys": the process-wide
+/// `simple_annotations`, and per-module annotations in `module_list` (for
+/// example Android's `abort_message`).
 #[derive(Debug)]
 pub struct MinidumpCrashpadInfo {
     pub raw: md::MINIDUMP_CRASHPAD_INFO,
@@ -564,6 +1145,18 @@ fn format_time_t(t: u32) -> String {
     }
 }
 
+/// Split a `VS_FIXEDFILEINFO` version into `(major, minor, patch, build)`.
+fn split_version(hi: u32, lo: u32) -> Option<(u16, u16, u16, u16)> {
+    if hi == 0 && lo == 0 {
+        return None;
+    }
+    Some(((hi >> 16) as u16, hi as u16, (lo >> 16) as u16, lo as u16))
+}
+
+fn format_version((major, minor, patch, build): (u16, u16, u16, u16)) -> String {
+    format!("{}.{}.{}.{}", major, minor, patch, build)
+}
+
 fn format_system_time(time: &md::SYSTEMTIME) -> String {
     // Note this drops the day_of_week field on the ground -- is that fine?
     if let Some(date) =
@@ -595,6 +1188,28 @@ fn location_slice<'a>(
         .ok_or(Error::StreamReadFailure)
 }
 
+/// Produce the part of the `size` bytes at `rva` in `bytes` that's actually there,
+/// or an `Error` if none of it is.
+fn available_slice(bytes: &[u8], rva: u64, size: u64) -> Result<&[u8], Error> {
+    let start = usize::try_from(rva).or(Err(Error::StreamReadFailure))?;
+    let available = bytes
+        .get(start..)
+        .filter(|available| !available.is_empty())
+        .ok_or(Error::StreamReadFailure)?;
+    let len = usize::try_from(size)
+        .unwrap_or(usize::MAX)
+        .min(available.len());
+    Ok(&available[..len])
+}
+
+/// The UTF-16 encoding of strings in a minidump with the given byte order.
+fn utf16_encoding(endian: scroll::Endian) -> EncodingRef {
+    match endian {
+        scroll::Endian::Little => UTF_16LE,
+        scroll::Endian::Big => UTF_16BE,
+    }
+}
+
 /// Read a u32 length-prefixed UTF-16 string from `bytes` at `offset`.
 fn read_string_utf16(
     offset: &mut usize,
@@ -606,7 +1221,7 @@ fn read_string_utf16(
     if size % 2 != 0 || (*offset + size) > bytes.len() {
         return Err(());
     }
-    match UTF_16LE.decode(&bytes[*offset..*offset + size], DecoderTrap::Strict) {
+    match utf16_encoding(endian).decode(&bytes[*offset..*offset + size], DecoderTrap::Strict) {
         Ok(s) => {
             *offset += size;
             Ok(s)
@@ -638,17 +1253,21 @@ fn read_string_utf8<'a>(
     }
 }
 
-fn read_cstring_utf8(offset: &mut usize, bytes: &[u8]) -> Result<String, ()> {
-    let initial_offset = *offset;
-    loop {
-        let byte: u8 = bytes.gread(offset).or(Err(()))?;
-        if byte == 0 {
-            break;
+/// Read a NUL-terminated string from `bytes` at `offset`, advancing `offset` past it.
+///
+/// If `bytes` ends before the NUL, the string is cut off there, and `truncated`
+/// is set. Reading past the end just produces empty strings.
+fn read_cstring_lossy(offset: &mut usize, bytes: &[u8], truncated: &mut bool) -> String {
+    let rest = bytes.get(*offset..).unwrap_or(&[]);
+    let len = match rest.iter().position(|&b| b == 0) {
+        Some(len) => len,
+        None => {
+            *truncated = true;
+            rest.len()
         }
-    }
-    std::str::from_utf8(&bytes[initial_offset..*offset - 1])
-        .map(String::from)
-        .or(Err(()))
+    };
+    *offset += len + 1;
+    String::from_utf8_lossy(&rest[..len]).into_owned()
 }
 
 /// Convert `bytes` with trailing NUL characters to a string
@@ -720,9 +1339,17 @@ impl MinidumpModule {
             name: String::from(name),
             codeview_info: None,
             misc_info: None,
+            os: None,
         }
     }
 
+    /// The file name of the module, without the directories of [`MinidumpModule::name`][].
+    ///
+    /// This works for Windows and POSIX-style paths on any host, see [`basename`].
+    pub fn name(&self) -> &str {
+        basename(&self.name)
+    }
+
     /// Read additional data to construct a `MinidumpModule` from `bytes` using the information
     /// from the module list in `raw`.
     pub fn read(
@@ -733,19 +1360,142 @@ impl MinidumpModule {
         let mut offset = raw.module_name_rva as usize;
         let name =
             read_string_utf16(&mut offset, bytes, endian).or(Err(Error::CodeViewReadFailure))?;
+        // A CodeView record that's out of bounds or too short to be what its
+        // signature says is treated like a missing one, the rest of the module
+        // is still useful.
         let codeview_info = if raw.cv_record.data_size == 0 {
             None
         } else {
-            Some(read_codeview(&raw.cv_record, bytes, endian).or(Err(Error::CodeViewReadFailure))?)
+            read_codeview(&raw.cv_record, bytes, endian).ok()
         };
         Ok(MinidumpModule {
             raw,
             name,
             codeview_info,
             misc_info: None,
+            os: None,
         })
     }
 
+    /// The module's `VS_FIXEDFILEINFO`, if it has a valid one.
+    fn fixed_file_info(&self) -> Option<&md::VS_FIXEDFILEINFO> {
+        let info = &self.raw.version_info;
+        if info.signature == md::VS_FFI_SIGNATURE && info.struct_version == md::VS_FFI_STRUCVERSION
+        {
+            Some(info)
+        } else {
+            None
+        }
+    }
+
+    /// The module's file version as `(major, minor, patch, build)`.
+    ///
+    /// This comes from the module's `VS_FIXEDFILEINFO`. On Windows that's
+    /// the PE file's version resource, and Crashpad fills it in on macOS from
+    /// the Mach-O `LC_SOURCE_VERSION` (or `LC_ID_DYLIB`) load command. ELF
+    /// files don't have a version, so Linux modules generally don't either.
+    /// A version of all zeroes is treated as no version, since that's what
+    /// writers put there when they don't know it.
+    ///
+    /// Tuples compare in version order, and the first three fields can be
+    /// used as a semver `MAJOR.MINOR.PATCH`.
+    pub fn file_version(&self) -> Option<(u16, u16, u16, u16)> {
+        let info = self.fixed_file_info()?;
+        split_version(info.file_version_hi, info.file_version_lo)
+    }
+
+    /// The version of the product this module ships with, as
+    /// `(major, minor, patch, build)`.
+    ///
+    /// This is often the same as [`file_version`][Self::file_version], but
+    /// modules that are distributed as part of a larger product may have
+    /// their own file version.
+    pub fn product_version(&self) -> Option<(u16, u16, u16, u16)> {
+        let info = self.fixed_file_info()?;
+        split_version(info.product_version_hi, info.product_version_lo)
+    }
+
+    /// The same as [`file_version`][Self::file_version].
+    pub fn version_as_tuple(&self) -> Option<(u16, u16, u16, u16)> {
+        self.file_version()
+    }
+
+    /// The module's file flags (e.g. whether it's a debug or prerelease
+    /// build), limited to the ones its `file_flags_mask` says are valid.
+    pub fn file_flags(&self) -> Option<md::FileFlags> {
+        let info = self.fixed_file_info()?;
+        Some(md::FileFlags::from_bits_truncate(
+            info.file_flags & info.file_flags_mask,
+        ))
+    }
+
+    /// The operating system the module was designed for.
+    pub fn file_os(&self) -> Option<md::FileOs> {
+        md::FileOs::from_u32(self.fixed_file_info()?.file_os)
+    }
+
+    /// The kind of file this module is (application, DLL, driver...).
+    pub fn file_type(&self) -> Option<md::FileType> {
+        md::FileType::from_u32(self.fixed_file_info()?.file_type)
+    }
+
+    /// Whether this module is probably part of the operating system, like
+    /// `ntdll.dll`, `libc-2.31.so` or `CoreFoundation.framework`.
+    ///
+    /// This is a guess based on the module's path, using the
+    /// [default `SystemModulePatterns`][SystemModulePatterns::default].
+    /// Use [`is_system_module_with`][Self::is_system_module_with] to customize them.
+    pub fn is_system_module(&self) -> bool {
+        is_system_path(
+            &self.name,
+            SYSTEM_PATH_PREFIXES.iter().copied(),
+            SYSTEM_NAME_PREFIXES.iter().copied(),
+        )
+    }
+
+    /// Like [`is_system_module`][Self::is_system_module], with custom `patterns`.
+    pub fn is_system_module_with(&self, patterns: &SystemModulePatterns) -> bool {
+        is_system_path(
+            &self.name,
+            patterns.path_prefixes.iter().map(String::as_str),
+            patterns.name_prefixes.iter().map(String::as_str),
+        )
+    }
+
+    /// The URL of this module's Breakpad symbol file on the symbol server at `base_url`.
+    ///
+    /// Symbol servers lay symbol files out as `<debug file>/<debug id>/<debug file>.sym`,
+    /// with a *.pdb* extension replaced by *.sym*. What the debug file and id are depends
+    /// on the kind of module (see [`Module::debug_file`] and [`Module::debug_identifier`]):
+    ///
+    /// * PE: the PDB's name and GUID, e.g. `xul.pdb/<id>/xul.sym`
+    /// * ELF: the module's file name and build id, e.g. `libxul.so/<id>/libxul.so.sym`
+    /// * Mach-O: the module's file name and UUID, e.g. `XUL/<id>/XUL.sym`
+    ///
+    /// Return `None` if the module doesn't have a debug file and id.
+    pub fn debug_file_url(&self, base_url: &str) -> Option<String> {
+        let debug_file = self.debug_file()?;
+        let debug_id = self.debug_identifier()?;
+        let leaf = basename(&debug_file);
+        if leaf.is_empty() || debug_id.is_empty() {
+            return None;
+        }
+        let has_pdb_extension =
+            leaf.len() >= 4 && leaf.as_bytes()[leaf.len() - 4..].eq_ignore_ascii_case(b".pdb");
+        let stem = if has_pdb_extension {
+            &leaf[..leaf.len() - 4]
+        } else {
+            leaf
+        };
+        Some(format!(
+            "{}/{}/{}/{}.sym",
+            base_url.trim_end_matches('/'),
+            url_path_segment(leaf),
+            url_path_segment(&debug_id),
+            url_path_segment(stem),
+        ))
+    }
+
     /// Write a human-readable description of this `MinidumpModule` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -774,6 +1524,11 @@ impl MinidumpModule {
   misc_record.rva                 = {:#x}
   (code_file)                     = \"{}\"
   (code_identifier)               = \"{}\"
+  (file_version)                  = \"{}\"
+  (product_version)               = \"{}\"
+  (file_flags)                    = {:?}
+  (file_os)                       = {}
+  (file_type)                     = {}
 ",
             self.raw.base_of_image,
             self.raw.size_of_image,
@@ -800,6 +1555,17 @@ impl MinidumpModule {
             self.raw.misc_record.rva,
             self.code_file(),
             self.code_identifier(),
+            self.file_version().map(format_version).unwrap_or_default(),
+            self.product_version()
+                .map(format_version)
+                .unwrap_or_default(),
+            self.file_flags().unwrap_or_else(md::FileFlags::empty),
+            self.file_os()
+                .map(|os| format!("{:?}", os))
+                .unwrap_or_else(|| "(unknown)".to_owned()),
+            self.file_type()
+                .map(|ty| format!("{:?}", ty))
+                .unwrap_or_else(|| "(unknown)".to_owned()),
         )?;
         // Print CodeView data.
         match self.codeview_info {
@@ -913,16 +1679,24 @@ impl Module for MinidumpModule {
     fn code_file(&self) -> Cow<'_, str> {
         Cow::Borrowed(&self.name)
     }
+    /// The module's code identifier.
+    ///
+    /// This is the ELF build id for ELF modules, and the UUID for Mach-O
+    /// modules (which get PDB 7.0 CodeView records with the UUID as their
+    /// signature). Otherwise, on Windows or if the OS isn't known, it's the
+    /// PE timestamp and image size hex-concatenated. It's empty if the module
+    /// has no identifier that makes sense for its OS.
     fn code_identifier(&self) -> Cow<'_, str> {
-        match self.codeview_info {
-            Some(CodeView::Elf(ref raw)) => Cow::Owned(bytes_to_hex(&raw.build_id)),
-            _ => {
-                // TODO: Breakpad stubs this out on non-Windows.
-                Cow::Owned(format!(
-                    "{0:08X}{1:x}",
-                    self.raw.time_date_stamp, self.raw.size_of_image
-                ))
+        match (&self.codeview_info, self.os) {
+            (Some(CodeView::Elf(raw)), _) => Cow::Owned(bytes_to_hex(&raw.build_id)),
+            (Some(CodeView::Pdb70(raw)), Some(Os::MacOs | Os::Ios)) => {
+                Cow::Owned(format!("{:#}", raw.signature))
             }
+            (_, Some(Os::Windows) | None) => Cow::Owned(format!(
+                "{0:08X}{1:x}",
+                self.raw.time_date_stamp, self.raw.size_of_image
+            )),
+            _ => Cow::Borrowed(""),
         }
     }
     fn debug_file(&self) -> Option<Cow<'_, str>> {
@@ -968,20 +1742,7 @@ impl Module for MinidumpModule {
         }
     }
     fn version(&self) -> Option<Cow<'_, str>> {
-        if self.raw.version_info.signature == md::VS_FFI_SIGNATURE
-            && self.raw.version_info.struct_version == md::VS_FFI_STRUCVERSION
-        {
-            let ver = format!(
-                "{}.{}.{}.{}",
-                self.raw.version_info.file_version_hi >> 16,
-                self.raw.version_info.file_version_hi & 0xffff,
-                self.raw.version_info.file_version_lo >> 16,
-                self.raw.version_info.file_version_lo & 0xffff
-            );
-            Some(Cow::Owned(ver))
-        } else {
-            None
-        }
+        self.file_version().map(format_version).map(Cow::Owned)
     }
 }
 
@@ -1223,9 +1984,16 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadNames {
         // read out the actual names
         let mut names = HashMap::with_capacity(raw_names.len());
         for raw_name in raw_names {
-            let mut offset = raw_name.thread_name_rva as usize;
+            // A null RVA means the thread just doesn't have a name.
+            if raw_name.thread_name_rva == 0 {
+                continue;
+            }
+            // If a thread is listed more than once, the last readable name wins.
             // Better to just drop unreadable names individually than the whole stream.
-            if let Ok(name) = read_string_utf16(&mut offset, all, endian) {
+            let name = usize::try_from(raw_name.thread_name_rva)
+                .ok()
+                .and_then(|mut offset| read_string_utf16(&mut offset, all, endian).ok());
+            if let Some(name) = name {
                 names.insert(raw_name.thread_id, name);
             } else {
                 warn!(
@@ -1271,46 +2039,614 @@ MINIDUMP_THREAD_NAME
     }
 }
 
-impl MinidumpModuleList {
-    /// Return an empty `MinidumpModuleList`.
-    pub fn new() -> MinidumpModuleList {
-        MinidumpModuleList {
-            modules: vec![],
-            modules_by_addr: RangeMap::new(),
-        }
+impl<'a> MinidumpStream<'a> for MinidumpThreadInfoList {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::ThreadInfoListStream;
+
+    fn read(bytes: &'a [u8], _all: &'a [u8], endian: scroll::Endian) -> Result<Self, Error> {
+        let mut offset = 0;
+        let raw_infos: Vec<md::MINIDUMP_THREAD_INFO> =
+            read_ex_stream_list(&mut offset, bytes, endian)?;
+        let thread_infos = raw_infos
+            .into_iter()
+            .map(|raw| MinidumpThreadInfo {
+                dump_flags: md::ThreadInfoDumpFlags::from_bits_truncate(raw.dump_flags),
+                raw,
+            })
+            .collect();
+        Ok(MinidumpThreadInfoList::from_thread_infos(thread_infos))
     }
-    /// Create a `MinidumpModuleList` from a list of `MinidumpModule`s.
-    pub fn from_modules(modules: Vec<MinidumpModule>) -> MinidumpModuleList {
-        let modules_by_addr = modules
+}
+
+impl MinidumpThreadInfoList {
+    /// Create a `MinidumpThreadInfoList` from a list of `MinidumpThreadInfo`s.
+    pub fn from_thread_infos(thread_infos: Vec<MinidumpThreadInfo>) -> MinidumpThreadInfoList {
+        let thread_ids = thread_infos
             .iter()
             .enumerate()
-            .map(|(i, module)| (module.memory_range(), i))
-            .into_rangemap_safe();
-        MinidumpModuleList {
-            modules,
-            modules_by_addr,
+            .map(|(i, info)| (info.raw.thread_id, i))
+            .collect();
+        MinidumpThreadInfoList {
+            thread_infos,
+            thread_ids,
         }
     }
 
-    /// Returns the module corresponding to the main executable.
-    pub fn main_module(&self) -> Option<&MinidumpModule> {
-        // The main code module is the first one present in a minidump file's
-        // MINIDUMP_MODULEList.
-        if !self.modules.is_empty() {
-            Some(&self.modules[0])
-        } else {
-            None
-        }
+    /// Get the `MinidumpThreadInfo` for the thread with id `thread_id`, if there is one.
+    pub fn get_thread_info(&self, thread_id: u32) -> Option<&MinidumpThreadInfo> {
+        self.thread_ids
+            .get(&thread_id)
+            .map(|&index| &self.thread_infos[index])
     }
 
-    /// Return a `MinidumpModule` whose address range covers `address`.
-    pub fn module_at_address(&self, address: u64) -> Option<&MinidumpModule> {
-        self.modules_by_addr
-            .get(address)
-            .map(|&index| &self.modules[index])
+    /// Iterate over the thread infos in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpThreadInfo> {
+        self.thread_infos.iter()
     }
 
-    /// Iterate over the modules in arbitrary order.
+    /// Write a human-readable description of this `MinidumpThreadInfoList` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpThreadInfoList
+  thread_info_count = {}
+
+",
+            self.thread_infos.len()
+        )?;
+        for (i, info) in self.thread_infos.iter().enumerate() {
+            writeln!(f, "thread_info[{}]", i)?;
+            info.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl MinidumpThreadInfo {
+    /// The identifier of the thread this information is about.
+    pub fn thread_id(&self) -> u32 {
+        self.raw.thread_id
+    }
+
+    /// The address of the function the thread started executing at.
+    pub fn start_address(&self) -> u64 {
+        self.raw.start_address
+    }
+
+    /// The time the thread has spent executing in kernel mode.
+    pub fn kernel_time(&self) -> Duration {
+        filetime_duration(self.raw.kernel_time)
+    }
+
+    /// The time the thread has spent executing in user mode.
+    pub fn user_time(&self) -> Duration {
+        filetime_duration(self.raw.user_time)
+    }
+
+    /// Whether this thread is a placeholder for one that couldn't be dumped.
+    pub fn is_error_thread(&self) -> bool {
+        self.dump_flags
+            .contains(md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_ERROR_THREAD)
+    }
+
+    /// Whether this thread is the one that wrote the minidump.
+    pub fn is_writing_thread(&self) -> bool {
+        self.dump_flags
+            .contains(md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_WRITING_THREAD)
+    }
+
+    /// Write a human-readable description of this `MinidumpThreadInfo` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_THREAD_INFO
+  thread_id     = {:#x}
+  dump_flags    = {:#x}
+  dump_error    = {:#x}
+  exit_status   = {:#x}
+  create_time   = {:#x}
+  exit_time     = {:#x}
+  kernel_time   = {:#x}
+  user_time     = {:#x}
+  start_address = {:#x}
+  affinity      = {:#x}
+
+",
+            self.raw.thread_id,
+            self.raw.dump_flags,
+            self.raw.dump_error,
+            self.raw.exit_status,
+            self.raw.create_time,
+            self.raw.exit_time,
+            self.raw.kernel_time,
+            self.raw.user_time,
+            self.raw.start_address,
+            self.raw.affinity,
+        )
+    }
+}
+
+/// Convert a span of time in `FILETIME` units (100 nanoseconds) to a `Duration`.
+fn filetime_duration(ticks: u64) -> Duration {
+    Duration::from_secs(ticks / 10_000_000) + Duration::from_nanos((ticks % 10_000_000) * 100)
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpHandleDataStream {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::HandleDataStream;
+
+    fn read(bytes: &'a [u8], all: &'a [u8], endian: scroll::Endian) -> Result<Self, Error> {
+        let header: md::MINIDUMP_HANDLE_DATA_STREAM = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let size_of_descriptor = header.size_of_descriptor as usize;
+        // There are two versions of the descriptor, distinguished only by their size.
+        // Anything else (in particular 0) would make the count below meaningless.
+        if size_of_descriptor != md::MINIDUMP_HANDLE_DESCRIPTOR_2::size_with(&endian)
+            && size_of_descriptor != md::MINIDUMP_HANDLE_DESCRIPTOR::size_with(&endian)
+        {
+            return Err(Error::StreamReadFailure);
+        }
+        let (number_of_descriptors, _) = ensure_count_in_bound(
+            bytes,
+            header.number_of_descriptors as usize,
+            size_of_descriptor,
+            header.size_of_header as usize,
+        )?;
+
+        // There are two versions of the descriptor, distinguished only by their size.
+        let read_raw = |offset: usize| -> Result<RawHandleDescriptor, Error> {
+            if size_of_descriptor == md::MINIDUMP_HANDLE_DESCRIPTOR_2::size_with(&endian) {
+                Ok(RawHandleDescriptor::HandleDescriptor2(
+                    bytes
+                        .pread_with(offset, endian)
+                        .or(Err(Error::StreamReadFailure))?,
+                ))
+            } else if size_of_descriptor == md::MINIDUMP_HANDLE_DESCRIPTOR::size_with(&endian) {
+                Ok(RawHandleDescriptor::HandleDescriptor(
+                    bytes
+                        .pread_with(offset, endian)
+                        .or(Err(Error::StreamReadFailure))?,
+                ))
+            } else {
+                Err(Error::StreamReadFailure)
+            }
+        };
+
+        let mut handles = Vec::with_capacity(number_of_descriptors);
+        for i in 0..number_of_descriptors {
+            let raw = read_raw(header.size_of_header as usize + i * size_of_descriptor)?;
+            let mut handle = MinidumpHandleDescriptor {
+                raw,
+                type_name: None,
+                object_name: None,
+            };
+            handle.type_name = read_handle_string(handle.type_name_rva(), all, endian);
+            handle.object_name = read_handle_string(handle.object_name_rva(), all, endian);
+            handles.push(handle);
+        }
+        Ok(MinidumpHandleDataStream { handles })
+    }
+}
+
+/// Read one of the strings a handle descriptor refers to, if it has one.
+fn read_handle_string(rva: md::RVA, all: &[u8], endian: scroll::Endian) -> Option<String> {
+    if rva == 0 {
+        return None;
+    }
+    let mut offset = rva as usize;
+    // Better to just drop unreadable names individually than the whole stream.
+    match read_string_utf16(&mut offset, all, endian) {
+        Ok(name) => Some(name),
+        Err(()) => {
+            warn!("Couldn't read handle string at rva {:#x}", rva);
+            None
+        }
+    }
+}
+
+impl MinidumpHandleDataStream {
+    /// Iterate over the handles in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpHandleDescriptor> {
+        self.handles.iter()
+    }
+
+    /// The number of handles in the stream.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Whether there are no handles in the stream.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Write a human-readable description of this `MinidumpHandleDataStream` to `f`.
+    ///
+    /// The handles are printed as a table, one per line.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpHandleDataStream
+  handle_count = {}
+
+",
+            self.handles.len()
+        )?;
+        writeln!(
+            f,
+            "  {:<18}  {:<20}  {:>7}  {:>8}  {:>10}  {:>10}  object_name",
+            "handle", "type", "handles", "pointers", "access", "attributes"
+        )?;
+        for handle in &self.handles {
+            writeln!(
+                f,
+                "  {:<#18x}  {:<20}  {:>7}  {:>8}  {:>#10x}  {:>#10x}  {}",
+                handle.handle(),
+                handle.type_name.as_deref().unwrap_or("(null)"),
+                handle.handle_count(),
+                handle.pointer_count(),
+                handle.granted_access(),
+                handle.attributes(),
+                handle.object_name.as_deref().unwrap_or("(null)"),
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+// Generates accessors for fields that all versions of MINIDUMP_HANDLE_DESCRIPTOR share.
+macro_rules! handle_descriptor_accessors {
+    ($($(#[$attr:meta])* $name:ident -> $t:ty,)*) => {
+        $(
+            $(#[$attr])*
+            pub fn $name(&self) -> $t {
+                match &self.raw {
+                    RawHandleDescriptor::HandleDescriptor(raw) => raw.$name,
+                    RawHandleDescriptor::HandleDescriptor2(raw) => raw.$name,
+                }
+            }
+        )*
+    };
+}
+
+impl MinidumpHandleDescriptor {
+    handle_descriptor_accessors!(
+        /// The value of the handle.
+        handle -> u64,
+        /// The rva of the name of the type of the object, or 0.
+        type_name_rva -> md::RVA,
+        /// The rva of the name of the object, or 0.
+        object_name_rva -> md::RVA,
+        /// The attributes of the handle.
+        attributes -> u32,
+        /// The access rights the handle was granted.
+        granted_access -> u32,
+        /// The number of handles to the object.
+        handle_count -> u32,
+        /// The number of pointers to the object.
+        pointer_count -> u32,
+    );
+
+    /// The rva of the object's extra information, if the descriptor includes it.
+    pub fn object_info_rva(&self) -> Option<md::RVA> {
+        match &self.raw {
+            RawHandleDescriptor::HandleDescriptor(_) => None,
+            RawHandleDescriptor::HandleDescriptor2(raw) => Some(raw.object_info_rva),
+        }
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpHandleOperationList {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::HandleOperationListStream;
+
+    fn read(bytes: &'a [u8], _all: &'a [u8], endian: scroll::Endian) -> Result<Self, Error> {
+        let header: md::MINIDUMP_HANDLE_OPERATION_LIST = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let size_of_entry = header.size_of_entry as usize;
+        if size_of_entry != md::AVRF_HANDLE_OPERATION::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+        let (number_of_entries, _) = ensure_count_in_bound(
+            bytes,
+            header.number_of_entries as usize,
+            size_of_entry,
+            header.size_of_header as usize,
+        )?;
+
+        let mut operations = Vec::with_capacity(number_of_entries);
+        for i in 0..number_of_entries {
+            let raw = bytes
+                .pread_with(header.size_of_header as usize + i * size_of_entry, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            operations.push(MinidumpHandleOperation { raw });
+        }
+        Ok(MinidumpHandleOperationList { operations })
+    }
+}
+
+impl MinidumpHandleOperationList {
+    /// Iterate over the operations in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.operations.iter()
+    }
+
+    /// Iterate over the operations on the handle with value `handle`, in the order
+    /// contained in the minidump.
+    pub fn operations_on_handle(
+        &self,
+        handle: u64,
+    ) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.operations
+            .iter()
+            .filter(move |operation| operation.handle() == handle)
+    }
+
+    /// Write a human-readable description of this `MinidumpHandleOperationList` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpHandleOperationList
+  operation_count = {}
+
+",
+            self.operations.len()
+        )?;
+        for (i, operation) in self.operations.iter().enumerate() {
+            writeln!(f, "operation[{}]", i)?;
+            operation.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl MinidumpHandleOperation {
+    /// The value of the handle that was operated on.
+    pub fn handle(&self) -> u64 {
+        self.raw.handle
+    }
+
+    /// The process that performed the operation.
+    pub fn process_id(&self) -> u32 {
+        self.raw.process_id
+    }
+
+    /// The thread that performed the operation.
+    pub fn thread_id(&self) -> u32 {
+        self.raw.thread_id
+    }
+
+    /// What kind of operation this was, if it's a known kind.
+    pub fn operation_type(&self) -> Option<md::HandleTraceOperation> {
+        md::HandleTraceOperation::from_u32(self.raw.operation_type)
+    }
+
+    /// The return addresses of the operation's backtrace, innermost first.
+    ///
+    /// These are absolute addresses, so they can be looked up in the
+    /// `MinidumpModuleList` and symbolicated like any stack frame.
+    pub fn backtrace(&self) -> &[u64] {
+        let addresses = &self.raw.back_trace_information.return_addresses;
+        let depth = self.raw.back_trace_information.depth as usize;
+        &addresses[..depth.min(addresses.len())]
+    }
+
+    /// Write a human-readable description of this `MinidumpHandleOperation` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "AVRF_HANDLE_OPERATION
+  handle         = {:#x}
+  process_id     = {:#x}
+  thread_id      = {:#x}
+  operation_type = {:#x}",
+            self.raw.handle, self.raw.process_id, self.raw.thread_id, self.raw.operation_type,
+        )?;
+        if let Some(operation_type) = self.operation_type() {
+            write!(f, " ({:?})", operation_type)?;
+        }
+        writeln!(
+            f,
+            "
+  back_trace_information.depth = {}
+  back_trace_information.index = {:#x}",
+            self.raw.back_trace_information.depth, self.raw.back_trace_information.index,
+        )?;
+        for (i, address) in self.backtrace().iter().enumerate() {
+            writeln!(
+                f,
+                "  back_trace_information.return_addresses[{}] = {:#x}",
+                i, address
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpFunctionTableList {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::FunctionTable;
+
+    fn read(bytes: &'a [u8], _all: &'a [u8], endian: scroll::Endian) -> Result<Self, Error> {
+        let header: md::MINIDUMP_FUNCTION_TABLE_STREAM = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        // The function entries are in the native format of the process, and for now
+        // we only understand x64's.
+        let size_of_entry = md::IMAGE_RUNTIME_FUNCTION_ENTRY::size_with(&endian);
+        if header.size_of_function_entry as usize != size_of_entry {
+            return Err(Error::StreamReadFailure);
+        }
+
+        // Everything is padded out to suit the native alignment of the process,
+        // so we have to skip around a bit.
+        let mut offset = (header.size_of_header as usize)
+            .checked_add(header.size_of_align_pad as usize)
+            .ok_or(Error::StreamReadFailure)?;
+        let mut tables = Vec::new();
+        for _ in 0..header.number_of_descriptors {
+            let raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let entries_offset = offset
+                .checked_add(header.size_of_descriptor as usize)
+                .and_then(|o| o.checked_add(header.size_of_native_descriptor as usize))
+                .ok_or(Error::StreamReadFailure)?;
+            let (entry_count, table_end) = ensure_count_in_bound(
+                bytes,
+                raw.entry_count as usize,
+                size_of_entry,
+                entries_offset,
+            )?;
+
+            let mut entries = Vec::with_capacity(entry_count);
+            for i in 0..entry_count {
+                let entry = bytes
+                    .pread_with(entries_offset + i * size_of_entry, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                entries.push(entry);
+            }
+            offset = table_end
+                .checked_add(raw.size_of_align_pad as usize)
+                .ok_or(Error::StreamReadFailure)?;
+            tables.push(MinidumpFunctionTable { raw, entries });
+        }
+        Ok(MinidumpFunctionTableList { tables })
+    }
+}
+
+impl MinidumpFunctionTableList {
+    /// Iterate over the function tables in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpFunctionTable> {
+        self.tables.iter()
+    }
+
+    /// Find the table and function entry covering `address`, if there is one.
+    pub fn function_at_address(
+        &self,
+        address: u64,
+    ) -> Option<(&MinidumpFunctionTable, &md::IMAGE_RUNTIME_FUNCTION_ENTRY)> {
+        self.tables
+            .iter()
+            .filter(|table| table.contains(address))
+            .find_map(|table| Some((table, table.function_at_address(address)?)))
+    }
+
+    /// Write a human-readable description of this `MinidumpFunctionTableList` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpFunctionTableList
+  table_count = {}
+
+",
+            self.tables.len()
+        )?;
+        for (i, table) in self.tables.iter().enumerate() {
+            writeln!(f, "function_table[{}]", i)?;
+            table.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl MinidumpFunctionTable {
+    /// The address that the function entries are relative to.
+    pub fn base_address(&self) -> u64 {
+        self.raw.base_address
+    }
+
+    /// Whether `address` is in the range of addresses this table covers.
+    pub fn contains(&self, address: u64) -> bool {
+        self.raw.minimum_address <= address && address < self.raw.maximum_address
+    }
+
+    /// Find the function entry covering `address`, if there is one.
+    pub fn function_at_address(&self, address: u64) -> Option<&md::IMAGE_RUNTIME_FUNCTION_ENTRY> {
+        let offset = address.checked_sub(self.raw.base_address)?;
+        self.entries
+            .iter()
+            .find(|entry| entry.begin_address as u64 <= offset && offset < entry.end_address as u64)
+    }
+
+    /// Write a human-readable description of this `MinidumpFunctionTable` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        writeln!(
+            f,
+            "MINIDUMP_FUNCTION_TABLE_DESCRIPTOR
+  minimum_address = {:#x}
+  maximum_address = {:#x}
+  base_address    = {:#x}
+  entry_count     = {}",
+            self.raw.minimum_address,
+            self.raw.maximum_address,
+            self.raw.base_address,
+            self.raw.entry_count,
+        )?;
+        for (i, entry) in self.entries.iter().enumerate() {
+            writeln!(
+                f,
+                "  entries[{}] = begin {:#x}, end {:#x}, unwind info {:#x}",
+                i, entry.begin_address, entry.end_address, entry.unwind_info_address
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl MinidumpModuleList {
+    /// Return an empty `MinidumpModuleList`.
+    pub fn new() -> MinidumpModuleList {
+        MinidumpModuleList {
+            modules: vec![],
+            modules_by_addr: RangeMap::new(),
+        }
+    }
+    /// Create a `MinidumpModuleList` from a list of `MinidumpModule`s.
+    pub fn from_modules(modules: Vec<MinidumpModule>) -> MinidumpModuleList {
+        let modules_by_addr = modules
+            .iter()
+            .enumerate()
+            .map(|(i, module)| (module.memory_range(), i))
+            .into_rangemap_safe();
+        MinidumpModuleList {
+            modules,
+            modules_by_addr,
+        }
+    }
+
+    /// Returns the module corresponding to the main executable.
+    pub fn main_module(&self) -> Option<&MinidumpModule> {
+        // The main code module is the first one present in a minidump file's
+        // MINIDUMP_MODULEList.
+        if !self.modules.is_empty() {
+            Some(&self.modules[0])
+        } else {
+            None
+        }
+    }
+
+    /// Return a `MinidumpModule` whose address range covers `address`.
+    pub fn module_at_address(&self, address: u64) -> Option<&MinidumpModule> {
+        self.modules_by_addr
+            .get(address)
+            .map(|&index| &self.modules[index])
+    }
+
+    /// Return the first module whose file name is `name`, ignoring case.
+    ///
+    /// Only the file names are compared (see [`MinidumpModule::name`]), so
+    /// `name` can also be a path to the module.
+    pub fn module_by_name(&self, name: &str) -> Option<&MinidumpModule> {
+        let name = basename(name);
+        self.modules.iter().find(|module| {
+            module
+                .name()
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(name.chars().flat_map(char::to_lowercase))
+        })
+    }
+
+    /// Iterate over the modules in arbitrary order.
     pub fn iter(&self) -> impl Iterator<Item = &MinidumpModule> {
         self.modules.iter()
     }
@@ -1335,7 +2671,11 @@ impl MinidumpModuleList {
             self.modules.len()
         )?;
         for (i, module) in self.modules.iter().enumerate() {
-            writeln!(f, "module[{}]", i)?;
+            let name = basename(&module.name);
+            match module.version() {
+                Some(version) => writeln!(f, "module[{}] {} {}", i, name, version)?,
+                None => writeln!(f, "module[{}] {}", i, name)?,
+            }
             module.print(f)?;
         }
         Ok(())
@@ -1355,6 +2695,15 @@ impl<'a> MinidumpStream<'a> for MinidumpModuleList {
         bytes: &'a [u8],
         all: &'a [u8],
         endian: scroll::Endian,
+    ) -> Result<MinidumpModuleList, Error> {
+        Self::read_with_system_info(bytes, all, endian, None)
+    }
+
+    fn read_with_system_info(
+        bytes: &'a [u8],
+        all: &'a [u8],
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
     ) -> Result<MinidumpModuleList, Error> {
         let mut offset = 0;
         let raw_modules: Vec<md::MINIDUMP_MODULE> = read_stream_list(&mut offset, bytes, endian)?;
@@ -1368,7 +2717,9 @@ impl<'a> MinidumpStream<'a> for MinidumpModuleList {
                 // TODO: just drop this module, keep the rest?
                 return Err(Error::ModuleReadFailure);
             }
-            modules.push(MinidumpModule::read(raw, all, endian)?);
+            let mut module = MinidumpModule::read(raw, all, endian)?;
+            module.os = system_info.map(|info| info.os);
+            modules.push(module);
         }
         Ok(MinidumpModuleList::from_modules(modules))
     }
@@ -1437,7 +2788,11 @@ impl MinidumpUnloadedModuleList {
             self.modules.len()
         )?;
         for (i, module) in self.modules.iter().enumerate() {
-            writeln!(f, "module[{}]", i)?;
+            let name = basename(&module.name);
+            match module.version() {
+                Some(version) => writeln!(f, "module[{}] {} {}", i, name, version)?,
+                None => writeln!(f, "module[{}] {}", i, name)?,
+            }
             module.print(f)?;
         }
         Ok(())
@@ -1481,6 +2836,7 @@ impl<'a> MinidumpMemory<'a> {
     pub fn read(
         desc: &md::MINIDUMP_MEMORY_DESCRIPTOR,
         data: &'a [u8],
+        endian: scroll::Endian,
     ) -> Result<MinidumpMemory<'a>, Error> {
         if desc.memory.rva == 0 || desc.memory.data_size == 0 {
             // Windows will sometimes emit null stack RVAs, indicating that
@@ -1489,33 +2845,17 @@ impl<'a> MinidumpMemory<'a> {
             // catch it.
             return Err(Error::MemoryReadFailure);
         }
-        let bytes = location_slice(data, &desc.memory).or(Err(Error::StreamReadFailure))?;
+        // Keep whatever is left of regions that were cut short by a truncated dump.
+        let bytes = available_slice(data, desc.memory.rva as u64, desc.memory.data_size as u64)?;
         Ok(MinidumpMemory {
             desc: *desc,
             base_address: desc.start_of_memory_range,
-            size: desc.memory.data_size as u64,
+            size: bytes.len() as u64,
             bytes,
+            endian,
         })
     }
 
-    /// Get `mem::size_of::<T>()` bytes of memory at `addr` from this region.
-    ///
-    /// Return `None` if the requested address range falls out of the bounds
-    /// of this memory region.
-    pub fn get_memory_at_address<T>(&self, addr: u64) -> Option<T>
-    where
-        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
-        T: SizeWith<scroll::Endian>,
-    {
-        let in_range = |a: u64| a >= self.base_address && a < (self.base_address + self.size);
-        let size = <T>::size_with(&LE);
-        if !in_range(addr) || !in_range(addr + size as u64 - 1) {
-            return None;
-        }
-        let start = (addr - self.base_address) as usize;
-        self.bytes.pread_with::<T>(start, LE).ok()
-    }
-
     /// Write a human-readable description of this `MinidumpMemory` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -1533,6 +2873,146 @@ Memory
         self.print_contents(f)?;
         writeln!(f)
     }
+}
+
+impl<'a> MinidumpMemory64<'a> {
+    /// Read the region described by `desc`, whose bytes are stored at `rva`.
+    ///
+    /// Memory64 descriptors don't say where their bytes are themselves, see
+    /// [`MINIDUMP_MEMORY64_LIST`][md::MINIDUMP_MEMORY64_LIST].
+    pub fn read(
+        desc: &md::MINIDUMP_MEMORY_DESCRIPTOR64,
+        rva: u64,
+        data: &'a [u8],
+        endian: scroll::Endian,
+    ) -> Result<MinidumpMemory64<'a>, Error> {
+        if desc.data_size == 0 {
+            return Err(Error::MemoryReadFailure);
+        }
+        let bytes = available_slice(data, rva, desc.data_size)?;
+        Ok(MinidumpMemory64 {
+            desc: *desc,
+            base_address: desc.start_of_memory_range,
+            size: bytes.len() as u64,
+            bytes,
+            endian,
+        })
+    }
+
+    /// Write a human-readable description of this `MinidumpMemory64` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_MEMORY_DESCRIPTOR64
+  start_of_memory_range = {:#x}
+  data_size             = {:#x}
+Memory
+",
+            self.desc.start_of_memory_range, self.desc.data_size,
+        )?;
+        self.print_contents(f)?;
+        writeln!(f)
+    }
+}
+
+impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
+    /// Get `mem::size_of::<T>()` bytes of memory at `addr` from this region.
+    ///
+    /// Return `None` if the requested address range falls out of the bounds
+    /// of this memory region.
+    pub fn get_memory_at_address<T>(&self, addr: u64) -> Option<T>
+    where
+        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
+        T: SizeWith<scroll::Endian>,
+    {
+        let bytes = self.read_bytes(addr, <T>::size_with(&self.endian))?;
+        bytes.pread_with::<T>(0, self.endian).ok()
+    }
+
+    /// Get the `len` bytes of memory at `addr` from this region.
+    ///
+    /// Return `None` if any of them fall out of the bounds of this memory region.
+    pub fn read_bytes(&self, addr: u64, len: usize) -> Option<&'a [u8]> {
+        let start = addr.checked_sub(self.base_address)?;
+        let end = start.checked_add(len as u64)?;
+        if end > self.size {
+            return None;
+        }
+        self.bytes.get(start as usize..end as usize)
+    }
+
+    /// Read a `u8` at `addr` from this region.
+    pub fn read_u8(&self, addr: u64) -> Option<u8> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Read a `u16` at `addr` from this region, in its byte order.
+    pub fn read_u16(&self, addr: u64) -> Option<u16> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Read a `u32` at `addr` from this region, in its byte order.
+    pub fn read_u32(&self, addr: u64) -> Option<u32> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Read a `u64` at `addr` from this region, in its byte order.
+    pub fn read_u64(&self, addr: u64) -> Option<u64> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Read a pointer at `addr` from this region.
+    ///
+    /// `pointer_width` is in bytes, as returned by [`Cpu::pointer_width`][].
+    /// Return `None` if it's neither 4 nor 8.
+    pub fn read_ptr(&self, addr: u64, pointer_width: u64) -> Option<u64> {
+        match pointer_width {
+            4 => self.read_u32(addr).map(u64::from),
+            8 => self.read_u64(addr),
+            _ => None,
+        }
+    }
+
+    /// Read a NUL-terminated string at `addr` from this region.
+    ///
+    /// The string ends at the first NUL, after `max_len` bytes, or at the end
+    /// of the region, whichever comes first. Invalid UTF-8 is replaced with
+    /// U+FFFD. Return `None` if `addr` isn't in this region.
+    pub fn read_cstring(&self, addr: u64, max_len: usize) -> Option<Cow<'a, str>> {
+        let bytes = self.read_available_bytes(addr, max_len)?;
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..len]))
+    }
+
+    /// Read a NUL-terminated UTF-16 string at `addr` from this region, in its
+    /// byte order.
+    ///
+    /// The string ends at the first NUL, after `max_len` code units, or at
+    /// the end of the region, whichever comes first. Invalid UTF-16 is
+    /// replaced with U+FFFD. Return `None` if `addr` isn't in this region.
+    pub fn read_utf16_string(&self, addr: u64, max_len: usize) -> Option<String> {
+        let bytes = self.read_available_bytes(addr, max_len.saturating_mul(2))?;
+        let len = bytes
+            .chunks_exact(2)
+            .position(|unit| unit == [0, 0])
+            .unwrap_or(bytes.len() / 2);
+        utf16_encoding(self.endian)
+            .decode(&bytes[..len * 2], DecoderTrap::Replace)
+            .ok()
+    }
+
+    /// Get up to `max_len` bytes of memory at `addr`, stopping early at the
+    /// end of this region.
+    fn read_available_bytes(&self, addr: u64, max_len: usize) -> Option<&'a [u8]> {
+        let start = addr.checked_sub(self.base_address)?;
+        if start >= self.size {
+            return None;
+        }
+        let available = self.bytes.get(start as usize..)?;
+        Some(&available[..available.len().min(max_len)])
+    }
 
     /// Write the contents of this `MinidumpMemory` to `f` as a hex string.
     pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
@@ -1555,30 +3035,35 @@ Memory
     }
 }
 
-impl<'mdmp> MinidumpMemoryList<'mdmp> {
+impl<'mdmp, Descriptor> MinidumpMemoryListBase<'mdmp, Descriptor> {
     /// Return an empty `MinidumpMemoryList`.
-    pub fn new() -> MinidumpMemoryList<'mdmp> {
-        MinidumpMemoryList {
+    pub fn new() -> MinidumpMemoryListBase<'mdmp, Descriptor> {
+        MinidumpMemoryListBase {
             regions: vec![],
             regions_by_addr: RangeMap::new(),
         }
     }
 
     /// Create a `MinidumpMemoryList` from a list of `MinidumpMemory`s.
-    pub fn from_regions(regions: Vec<MinidumpMemory<'mdmp>>) -> MinidumpMemoryList<'mdmp> {
+    pub fn from_regions(
+        regions: Vec<MinidumpMemoryBase<'mdmp, Descriptor>>,
+    ) -> MinidumpMemoryListBase<'mdmp, Descriptor> {
         let regions_by_addr = regions
             .iter()
             .enumerate()
             .map(|(i, region)| (region.memory_range(), i))
             .into_rangemap_safe();
-        MinidumpMemoryList {
+        MinidumpMemoryListBase {
             regions,
             regions_by_addr,
         }
     }
 
     /// Return a `MinidumpMemory` containing memory at `address`, if one exists.
-    pub fn memory_at_address(&self, address: u64) -> Option<&MinidumpMemory<'mdmp>> {
+    pub fn memory_at_address(
+        &self,
+        address: u64,
+    ) -> Option<&MinidumpMemoryBase<'mdmp, Descriptor>> {
         self.regions_by_addr
             .get(address)
             .map(|&index| &self.regions[index])
@@ -1590,17 +3075,233 @@ impl<'mdmp> MinidumpMemoryList<'mdmp> {
     /// That is the lifetime of the item is bound to the lifetime of the iterator itself
     /// (`'slf`), while the slice inside [MinidumpMemory] pointing at the memory itself has
     /// the lifetime of the [Minidump] struct ('mdmp).
-    pub fn iter<'slf>(&'slf self) -> impl Iterator<Item = &'slf MinidumpMemory<'mdmp>> {
+    pub fn iter<'slf>(
+        &'slf self,
+    ) -> impl Iterator<Item = &'slf MinidumpMemoryBase<'mdmp, Descriptor>> {
         self.regions.iter()
     }
 
     /// Iterate over the memory regions in order by memory address.
-    pub fn by_addr<'slf>(&'slf self) -> impl Iterator<Item = &'slf MinidumpMemory<'mdmp>> {
+    pub fn by_addr<'slf>(
+        &'slf self,
+    ) -> impl Iterator<Item = &'slf MinidumpMemoryBase<'mdmp, Descriptor>> {
         self.regions_by_addr
             .ranges_values()
             .map(move |&(_, index)| &self.regions[index])
     }
+}
+
+impl<'a> Default for UnifiedMemoryList<'a> {
+    fn default() -> Self {
+        UnifiedMemoryList {
+            memory: MinidumpMemoryList::new(),
+            memory64: MinidumpMemory64List::new(),
+            regions_by_addr: RangeMap::new(),
+        }
+    }
+}
+
+impl<'a> UnifiedMemoryList<'a> {
+    /// Combine the regions of a minidump's two kinds of memory list.
+    pub fn new(memory: MinidumpMemoryList<'a>, memory64: MinidumpMemory64List<'a>) -> Self {
+        // Sort every region by its start, and then sweep over them keeping the
+        // regions which win any overlaps. The position of each region in the
+        // minidump breaks ties.
+        let mut regions = memory
+            .regions
+            .iter()
+            .enumerate()
+            .filter_map(|(i, region)| Some((region.memory_range()?, UnifiedMemoryIndex::Memory(i))))
+            .chain(
+                memory64
+                    .regions
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, region)| {
+                        Some((region.memory_range()?, UnifiedMemoryIndex::Memory64(i)))
+                    }),
+            )
+            .enumerate()
+            .map(|(order, (range, index))| (range, index, order))
+            .collect::<Vec<_>>();
+        regions.sort_by_key(|(range, _, _)| range.start);
+
+        let priority = |range: &Range<u64>, order: usize| (range.end - range.start, order);
+        let mut kept: Vec<(Range<u64>, UnifiedMemoryIndex, usize)> =
+            Vec::with_capacity(regions.len());
+        for (range, index, order) in regions {
+            if let Some((last_range, last_index, last_order)) = kept.last_mut() {
+                if range.start <= last_range.end {
+                    let (winner, loser) =
+                        if priority(&range, order) > priority(last_range, *last_order) {
+                            (range, *last_range)
+                        } else {
+                            (*last_range, range)
+                        };
+                    warn!(
+                        "memory region {:#x}-{:#x} overlaps {:#x}-{:#x}, dropping it",
+                        loser.start, loser.end, winner.start, winner.end
+                    );
+                    if winner == range {
+                        *last_range = range;
+                        *last_index = index;
+                        *last_order = order;
+                    }
+                    continue;
+                }
+            }
+            kept.push((range, index, order));
+        }
+
+        let regions_by_addr = kept
+            .into_iter()
+            .map(|(range, index, _)| (range, index))
+            .collect();
+        UnifiedMemoryList {
+            memory,
+            memory64,
+            regions_by_addr: RangeMap::from_sorted_vec(regions_by_addr),
+        }
+    }
+
+    /// Return the region containing memory at `address`, if one exists.
+    pub fn memory_at_address(&self, address: u64) -> Option<UnifiedMemory<'_, 'a>> {
+        self.regions_by_addr
+            .get(address)
+            .map(|&index| self.region(index))
+    }
+
+    /// Iterate over the memory regions in order by memory address.
+    ///
+    /// Regions which lost an overlap with another region are skipped.
+    pub fn by_addr(&self) -> impl Iterator<Item = UnifiedMemory<'_, 'a>> {
+        self.regions_by_addr
+            .ranges_values()
+            .map(move |&(_, index)| self.region(index))
+    }
+
+    /// The total number of bytes of memory in [`by_addr`][Self::by_addr]'s regions.
+    pub fn total_size(&self) -> u64 {
+        self.by_addr().map(|region| region.size()).sum()
+    }
+
+    /// Get the [`MinidumpMemoryList`] contained inside.
+    pub fn memory_list(&self) -> &MinidumpMemoryList<'a> {
+        &self.memory
+    }
+
+    /// Get the [`MinidumpMemory64List`] contained inside.
+    pub fn memory64_list(&self) -> &MinidumpMemory64List<'a> {
+        &self.memory64
+    }
+
+    fn region(&self, index: UnifiedMemoryIndex) -> UnifiedMemory<'_, 'a> {
+        match index {
+            UnifiedMemoryIndex::Memory(i) => UnifiedMemory::Memory(&self.memory.regions[i]),
+            UnifiedMemoryIndex::Memory64(i) => UnifiedMemory::Memory64(&self.memory64.regions[i]),
+        }
+    }
+}
+
+impl<'slf, 'a> UnifiedMemory<'slf, 'a> {
+    /// The starting address of this range of memory.
+    pub fn base_address(&self) -> u64 {
+        match self {
+            Self::Memory(memory) => memory.base_address,
+            Self::Memory64(memory) => memory.base_address,
+        }
+    }
+
+    /// The length of this range of memory.
+    pub fn size(&self) -> u64 {
+        match self {
+            Self::Memory(memory) => memory.size,
+            Self::Memory64(memory) => memory.size,
+        }
+    }
+
+    /// The contents of the memory.
+    pub fn bytes(&self) -> &'a [u8] {
+        match self {
+            Self::Memory(memory) => memory.bytes,
+            Self::Memory64(memory) => memory.bytes,
+        }
+    }
+
+    /// The range of memory this region covers.
+    pub fn memory_range(&self) -> Option<Range<u64>> {
+        match self {
+            Self::Memory(memory) => memory.memory_range(),
+            Self::Memory64(memory) => memory.memory_range(),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::get_memory_at_address`].
+    pub fn get_memory_at_address<T>(&self, addr: u64) -> Option<T>
+    where
+        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
+        T: SizeWith<scroll::Endian>,
+    {
+        match self {
+            Self::Memory(memory) => memory.get_memory_at_address(addr),
+            Self::Memory64(memory) => memory.get_memory_at_address(addr),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::read_bytes`].
+    pub fn read_bytes(&self, addr: u64, len: usize) -> Option<&'a [u8]> {
+        match self {
+            Self::Memory(memory) => memory.read_bytes(addr, len),
+            Self::Memory64(memory) => memory.read_bytes(addr, len),
+        }
+    }
 
+    /// See [`MinidumpMemoryBase::read_u8`].
+    pub fn read_u8(&self, addr: u64) -> Option<u8> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::read_u16`].
+    pub fn read_u16(&self, addr: u64) -> Option<u16> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::read_u32`].
+    pub fn read_u32(&self, addr: u64) -> Option<u32> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::read_u64`].
+    pub fn read_u64(&self, addr: u64) -> Option<u64> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::read_ptr`].
+    pub fn read_ptr(&self, addr: u64, pointer_width: u64) -> Option<u64> {
+        match self {
+            Self::Memory(memory) => memory.read_ptr(addr, pointer_width),
+            Self::Memory64(memory) => memory.read_ptr(addr, pointer_width),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::read_cstring`].
+    pub fn read_cstring(&self, addr: u64, max_len: usize) -> Option<Cow<'a, str>> {
+        match self {
+            Self::Memory(memory) => memory.read_cstring(addr, max_len),
+            Self::Memory64(memory) => memory.read_cstring(addr, max_len),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::read_utf16_string`].
+    pub fn read_utf16_string(&self, addr: u64, max_len: usize) -> Option<String> {
+        match self {
+            Self::Memory(memory) => memory.read_utf16_string(addr, max_len),
+            Self::Memory64(memory) => memory.read_utf16_string(addr, max_len),
+        }
+    }
+}
+
+impl<'mdmp> MinidumpMemoryList<'mdmp> {
     /// Write a human-readable description of this `MinidumpMemoryList` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -1621,7 +3322,28 @@ impl<'mdmp> MinidumpMemoryList<'mdmp> {
     }
 }
 
-impl<'a> Default for MinidumpMemoryList<'a> {
+impl<'mdmp> MinidumpMemory64List<'mdmp> {
+    /// Write a human-readable description of this `MinidumpMemory64List` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpMemory64List
+  region_count = {}
+
+",
+            self.regions.len()
+        )?;
+        for (i, region) in self.regions.iter().enumerate() {
+            writeln!(f, "region[{}]", i)?;
+            region.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Descriptor> Default for MinidumpMemoryListBase<'a, Descriptor> {
     fn default() -> Self {
         Self::new()
     }
@@ -1641,7 +3363,7 @@ impl<'a> MinidumpStream<'a> for MinidumpMemoryList<'a> {
         // read memory contents for each region
         let mut regions = Vec::with_capacity(descriptors.len());
         for raw in descriptors.into_iter() {
-            if let Ok(memory) = MinidumpMemory::read(&raw, all) {
+            if let Ok(memory) = MinidumpMemory::read(&raw, all, endian) {
                 regions.push(memory);
             } else {
                 // Just skip over corrupt entries and try to limp along.
@@ -1652,6 +3374,60 @@ impl<'a> MinidumpStream<'a> for MinidumpMemoryList<'a> {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpMemory64List<'a> {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::Memory64ListStream;
+
+    fn read(
+        bytes: &'a [u8],
+        all: &'a [u8],
+        endian: scroll::Endian,
+    ) -> Result<MinidumpMemory64List<'a>, Error> {
+        let regions = read_memory64_descriptors(bytes, endian)?
+            .into_iter()
+            .filter_map(|(raw, rva)| MinidumpMemory64::read(&raw, rva, all, endian).ok())
+            .collect();
+        Ok(MinidumpMemory64List::from_regions(regions))
+    }
+}
+
+/// Read the descriptors of a `Memory64ListStream`, along with the rva of each region's contents.
+fn read_memory64_descriptors(
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<Vec<(md::MINIDUMP_MEMORY_DESCRIPTOR64, u64)>, Error> {
+    let mut offset = 0;
+    let header: md::MINIDUMP_MEMORY64_LIST = bytes
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
+    let count =
+        usize::try_from(header.number_of_memory_ranges).or(Err(Error::StreamReadFailure))?;
+    let (count, _) = ensure_count_in_bound(
+        bytes,
+        count,
+        md::MINIDUMP_MEMORY_DESCRIPTOR64::size_with(&endian),
+        offset,
+    )?;
+
+    // The contents of the regions are stored back to back, in the same order
+    // as the descriptors, starting at base_rva.
+    let mut rva = Some(header.base_rva);
+    let mut descriptors = Vec::with_capacity(count);
+    for _ in 0..count {
+        let desc: md::MINIDUMP_MEMORY_DESCRIPTOR64 = bytes
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        // Even if an entry is corrupt, later ones may be fine, but
+        // only if we still know where they are.
+        let desc_rva = match rva {
+            Some(rva) => rva,
+            None => break,
+        };
+        rva = desc_rva.checked_add(desc.data_size);
+        descriptors.push((desc, desc_rva));
+    }
+    Ok(descriptors)
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpMemoryInfoList<'a> {
     const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::MemoryInfoListStream;
 
@@ -1733,7 +3509,19 @@ impl<'mdmp> MinidumpMemoryInfoList<'mdmp> {
     }
 
     /// Write a human-readable description.
+    ///
+    /// This is modeled after the output of windbg's `!address`: a table of the
+    /// regions by address, followed by a summary of their states, types, and
+    /// protections.
     pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        fn flags<F: fmt::Debug>(flags: F, is_empty: bool) -> String {
+            if is_empty {
+                String::new()
+            } else {
+                format!("{:?}", flags)
+            }
+        }
+
         write!(
             f,
             "MinidumpMemoryInfoList
@@ -1742,11 +3530,57 @@ impl<'mdmp> MinidumpMemoryInfoList<'mdmp> {
 ",
             self.regions.len()
         )?;
-        for (i, region) in self.regions.iter().enumerate() {
-            writeln!(f, "region[{}]", i)?;
-            region.print(f)?;
+        writeln!(
+            f,
+            "  {:>18} {:>18} {:>18} {:<12} {:<12} Protect",
+            "BaseAddr", "EndAddr+1", "RgnSize", "Type", "State"
+        )?;
+        for region in self.by_addr() {
+            writeln!(
+                f,
+                "  {:#018x} {:#018x} {:#018x} {:<12} {:<12} {}",
+                region.raw.base_address,
+                region
+                    .raw
+                    .base_address
+                    .saturating_add(region.raw.region_size),
+                region.raw.region_size,
+                flags(region.ty, region.ty.is_empty()),
+                flags(region.state, region.state.is_empty()),
+                flags(region.protection, region.protection.is_empty()),
+            )?;
         }
-        Ok(())
+
+        // (region count, total size) of each kind of region.
+        let mut states = BTreeMap::<String, (usize, u64)>::new();
+        let mut types = BTreeMap::<String, (usize, u64)>::new();
+        let mut protections = BTreeMap::<String, (usize, u64)>::new();
+        for region in self.by_addr() {
+            let add = |summary: &mut BTreeMap<String, (usize, u64)>, name: String| {
+                let entry = summary.entry(name).or_default();
+                entry.0 += 1;
+                entry.1 = entry.1.saturating_add(region.raw.region_size);
+            };
+            add(&mut states, flags(region.state, region.state.is_empty()));
+            if !region.state.contains(md::MemoryState::MEM_FREE) {
+                add(&mut types, flags(region.ty, region.ty.is_empty()));
+            }
+            if region.state.contains(md::MemoryState::MEM_COMMIT) {
+                add(&mut protections, flags(region.protection, false));
+            }
+        }
+        for (title, summary) in [
+            ("State Summary", states),
+            ("Type Summary (for busy)", types),
+            ("Protect Summary (for commit)", protections),
+        ] {
+            writeln!(f)?;
+            writeln!(f, "  --- {:<28} {:>8} {:>18}", title, "RgnCount", "TotSize")?;
+            for (name, (count, size)) in summary {
+                writeln!(f, "  {:<32} {:>8} {:#18x}", name, count, size)?;
+            }
+        }
+        writeln!(f)
     }
 }
 
@@ -1785,6 +3619,17 @@ impl<'a> MinidumpMemoryInfo<'a> {
         ))
     }
 
+    /// A short description of this region, like "a PAGE_READONLY committed region".
+    pub fn describe(&self) -> String {
+        if self.state.contains(md::MemoryState::MEM_COMMIT) {
+            format!("a {:?} committed region", self.protection)
+        } else if self.state.contains(md::MemoryState::MEM_RESERVE) {
+            String::from("a reserved region")
+        } else {
+            String::from("free memory")
+        }
+    }
+
     /// Whether this memory range was executable.
     pub fn is_executable(&self) -> bool {
         self.protection.intersects(
@@ -1905,14 +3750,15 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
         //   * s = shared
         //   * p = private (copy on write)
         //   * - = <ignore> (just for formatting)
-        // * offset: the offset this mapping has into the mapped file/device. (ignored)
-        // * dev: the "device" (major:minor). (ignored)
-        // * inode: the inode on the device, 0 means no inode (uninitialized memory?). (ignored)
+        // * offset: the offset this mapping has into the mapped file/device (hex).
+        // * dev: the "device" (major:minor, both hex).
+        // * inode: the inode on the device, 0 means no inode (uninitialized memory?).
         // * path/kind: either a path to the mapped file/device or a special `[kind]`:
         //   * `[stack]`       - the main thread's stack
         //   * `[stack:<tid>]` - the stack of the thread with this tid (e.g. `[stack:123]`)
         //   * `[heap]`        - the process's heap
         //   * `[vdso]`        - the Virtual Dynamically-linked Shared Object
+        //   * `[anon:<name>]` - an anonymous mmap that was given a name (Android)
         //   * `<blank>`       - an anonymous mmap
         //
         // A path has a few extra caveats:
@@ -1945,21 +3791,17 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
         let empty = LinuxOsStr::new();
         let perms = tokens.next().unwrap_or(empty);
 
-        let mut is_read = false;
-        let mut is_write = false;
-        let mut is_exec = false;
-        let mut is_shared = false;
-        let mut is_private = false;
+        let mut permissions = MinidumpLinuxMapPermissions::empty();
 
         // Although some of these are mutually exclusive and they come in a specific
         // order, I see no reason to mandate this in this parser.
         for c in perms.iter() {
             match &c {
-                b'r' => is_read = true,
-                b'w' => is_write = true,
-                b'x' => is_exec = true,
-                b's' => is_shared = true,
-                b'p' => is_private = true,
+                b'r' => permissions |= MinidumpLinuxMapPermissions::READ,
+                b'w' => permissions |= MinidumpLinuxMapPermissions::WRITE,
+                b'x' => permissions |= MinidumpLinuxMapPermissions::EXEC,
+                b's' => permissions |= MinidumpLinuxMapPermissions::SHARED,
+                b'p' => permissions |= MinidumpLinuxMapPermissions::PRIVATE,
                 b'-' => {}
                 _ => {
                     // This shouldn't happen. That said, there's no obvious reason
@@ -1969,10 +3811,19 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
             }
         }
 
-        // We don't care about these values
-        let _offset = tokens.next();
-        let _dev = tokens.next();
-        let _inode = tokens.next();
+        // Like with perms, a garbled value here isn't worth failing the parse over.
+        let hex = |x: &LinuxOsStr, radix| {
+            x.to_str()
+                .ok()
+                .and_then(|x| u64::from_str_radix(x, radix).ok())
+        };
+        let offset = tokens.next().and_then(|x| hex(x, 16)).unwrap_or(0);
+        let dev = tokens
+            .next()
+            .and_then(|dev| dev.split_once(b':'))
+            .and_then(|(major, minor)| Some((hex(major, 16)? as u32, hex(minor, 16)? as u32)))
+            .unwrap_or((0, 0));
+        let inode = tokens.next().and_then(|x| hex(x, 10)).unwrap_or(0);
 
         let kind = tokens.next();
         let kind = match kind.map(|x| x.as_bytes()) {
@@ -1995,6 +3846,8 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
                         // As far as I know, this part *is* base 10!
                         let tid = str::parse(tid).map_err(|_| Error::DataError)?;
                         MinidumpLinuxMapKind::Stack(tid)
+                    } else if special.starts_with("anon:") {
+                        MinidumpLinuxMapKind::AnonymousMap
                     } else {
                         MinidumpLinuxMapKind::UnknownSpecial(Cow::Borrowed(kind))
                     }
@@ -2029,12 +3882,10 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
             base_address,
             final_address,
             kind,
-            is_read,
-            is_write,
-            is_exec,
-            is_private,
-            is_shared,
-            _phantom: PhantomData,
+            permissions,
+            offset,
+            dev,
+            inode,
         })
     }
     /// Write a human-readable description of this.
@@ -2045,47 +3896,58 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
   base_address          = {:#x}
   final_address         = {:#x}
   kind                  = {:#?}
-  permissions           = 
+  permissions           = {}
+  offset                = {:#x}
+  dev                   = {:02x}:{:02x}
+  inode                 = {}
 ",
-            self.base_address, self.final_address, self.kind,
-        )?;
-
-        if self.is_read {
-            write!(f, "r")?;
-        } else {
-            write!(f, "-")?;
-        }
-        if self.is_write {
-            write!(f, "w")?;
-        } else {
-            write!(f, "-")?;
-        }
-        if self.is_exec {
-            write!(f, "r")?;
-        } else {
-            write!(f, "-")?;
-        }
-        if self.is_private {
-            write!(f, "p")?;
-        } else if self.is_shared {
-            write!(f, "s")?;
-        } else {
-            write!(f, "-")?;
-        }
-        writeln!(f)
+            self.base_address,
+            self.final_address,
+            self.kind,
+            self.permissions,
+            self.offset,
+            self.dev.0,
+            self.dev.1,
+            self.inode,
+        )
     }
 
     pub fn memory_range(&self) -> Option<Range<u64>> {
-        // final address is inclusive afaik
-        if self.base_address < self.final_address {
+        // final_address is exclusive, but Range is inclusive
+        if self.base_address >= self.final_address {
             return None;
         }
-        Some(Range::new(self.base_address, self.final_address))
+        Some(Range::new(self.base_address, self.final_address - 1))
     }
 
     /// Whether this memory range was executable.
     pub fn is_executable(&self) -> bool {
-        self.is_exec
+        self.permissions.contains(MinidumpLinuxMapPermissions::EXEC)
+    }
+
+    /// A short description of what this memory is, e.g. "a r-xp mapping of /lib/libc.so.6".
+    pub fn describe(&self) -> String {
+        let perms = self.permissions;
+        match &self.kind {
+            MinidumpLinuxMapKind::MainThreadStack => format!("the main thread's {} stack", perms),
+            MinidumpLinuxMapKind::Stack(tid) => format!("the {} stack of thread {}", perms, tid),
+            MinidumpLinuxMapKind::Heap => format!("the {} heap", perms),
+            MinidumpLinuxMapKind::Vdso => format!("the {} vdso", perms),
+            MinidumpLinuxMapKind::AnonymousMap => format!("an anonymous {} mapping", perms),
+            MinidumpLinuxMapKind::UnknownSpecial(name) => {
+                format!("the {} {} mapping", perms, name.to_string_lossy())
+            }
+            MinidumpLinuxMapKind::File(path) => {
+                format!("a {} mapping of {}", perms, path.to_string_lossy())
+            }
+            MinidumpLinuxMapKind::DeletedFile(path) => {
+                format!(
+                    "a {} mapping of deleted file {}",
+                    perms,
+                    path.to_string_lossy()
+                )
+            }
+        }
     }
 }
 
@@ -2215,9 +4077,27 @@ impl<'a> UnifiedMemoryInfo<'a> {
             Self::Map(map) => map.is_executable(),
         }
     }
+
+    /// A short description of what this memory is, e.g. "a PAGE_READWRITE committed region".
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Info(info) => info.describe(),
+            Self::Map(map) => map.describe(),
+        }
+    }
 }
 
 impl<'a> MinidumpThread<'a> {
+    /// Check that this thread's context is for the CPU that `system_info` says.
+    ///
+    /// See [`MinidumpContext::check_cpu`][].
+    pub fn check_context_cpu(&self, system_info: &MinidumpSystemInfo) -> Result<(), ContextError> {
+        match self.context {
+            Some(bytes) => MinidumpContext::check_cpu(bytes, self.endian, system_info),
+            None => Ok(()),
+        }
+    }
+
     pub fn context(
         &self,
         system_info: &MinidumpSystemInfo,
@@ -2228,19 +4108,55 @@ impl<'a> MinidumpThread<'a> {
             .map(Cow::Owned)
     }
 
-    pub fn stack_memory(
-        &self,
-        memory_list: &MinidumpMemoryList<'a>,
-    ) -> Option<Cow<MinidumpMemory<'a>>> {
-        self.stack.as_ref().map(Cow::Borrowed).or_else(|| {
+    /// Get this thread's stack memory.
+    ///
+    /// `memory` is usually from [`Minidump::memory`], so that the stack is
+    /// also found in full-memory dumps.
+    ///
+    /// The stack isn't looked up until this is first called, and is then
+    /// remembered for later calls. Nothing is copied: the returned memory
+    /// borrows from the minidump's data, so with [`Minidump::read_path`][] the
+    /// stack is only paged in from the file when its bytes are actually used.
+    /// That matters for full-memory dumps, where every thread can have
+    /// megabytes of stack.
+    pub fn stack_memory(&self, memory: &UnifiedMemoryList<'a>) -> Option<Cow<MinidumpMemory<'a>>> {
+        if let Some(stack) = self.stack.get() {
+            return Some(Cow::Borrowed(stack));
+        }
+        let stack = self.direct_stack().or_else(|| {
             // Sometimes the raw.stack RVA is null/busted, but the start_of_memory_range
             // value is correct. So if the `read` fails, try resolving start_of_memory_range
-            // with the MinidumpMemoryList. (This seems to specifically be a problem with
-            // Windows minidumps.)
+            // with the memory lists. (This seems to specifically be a problem with
+            // Windows minidumps, and is always the case for full-memory dumps.)
             let stack_addr = self.raw.stack.start_of_memory_range;
-            let memory = memory_list.memory_at_address(stack_addr)?;
-            Some(Cow::Owned(memory.clone()))
-        })
+            let stack = match memory.memory_at_address(stack_addr)? {
+                UnifiedMemory::Memory(region) => {
+                    md::MINIDUMP_MEMORY_DESCRIPTOR::stack_memory(region, &self.raw.stack)
+                }
+                UnifiedMemory::Memory64(region) => {
+                    md::MINIDUMP_MEMORY_DESCRIPTOR64::stack_memory(region, &self.raw.stack)
+                }
+            };
+            Some(stack)
+        })?;
+        // A stack that wasn't found isn't remembered, since it may still be in
+        // a different `memory` list.
+        Some(Cow::Borrowed(self.stack.get_or_init(|| stack)))
+    }
+
+    /// The stack memory `raw.stack` points at, if it's valid.
+    fn direct_stack(&self) -> Option<MinidumpMemory<'a>> {
+        MinidumpMemory::read(&self.raw.stack, self.data, self.endian).ok()
+    }
+
+    /// Get this thread's name from the `MinidumpThreadNames` stream, if it has one.
+    pub fn name<'n>(&self, names: &'n MinidumpThreadNames) -> Option<Cow<'n, str>> {
+        names.get_name(self.raw.thread_id)
+    }
+
+    /// Get this thread's entry in the `MinidumpThreadInfoList` stream, if it has one.
+    pub fn info<'i>(&self, infos: &'i MinidumpThreadInfoList) -> Option<&'i MinidumpThreadInfo> {
+        infos.get_thread_info(self.raw.thread_id)
     }
 
     /// Write a human-readable description of this `MinidumpThread` to `f`.
@@ -2249,7 +4165,7 @@ impl<'a> MinidumpThread<'a> {
     pub fn print<T: Write>(
         &self,
         f: &mut T,
-        memory: Option<&MinidumpMemoryList<'a>>,
+        memory: Option<&UnifiedMemoryList<'a>>,
         system: Option<&MinidumpSystemInfo>,
         misc: Option<&MinidumpMiscInfo>,
     ) -> io::Result<()> {
@@ -2290,8 +4206,8 @@ impl<'a> MinidumpThread<'a> {
         }
 
         // We might not need any memory, so try to limp forward with an empty
-        // MemoryList if we don't have one.
-        let dummy_memory = MinidumpMemoryList::default();
+        // list if we don't have one.
+        let dummy_memory = UnifiedMemoryList::default();
         let memory = memory.unwrap_or(&dummy_memory);
         if let Some(ref stack) = self.stack_memory(memory) {
             writeln!(f, "Stack")?;
@@ -2308,7 +4224,7 @@ impl<'a> MinidumpThread<'a> {
     /// The value is heuristically converted into a CrashReason because that's our
     /// general error code handling machinery, even though this may not actually be
     /// the reason for the crash!
-    pub fn last_error(&self, cpu: Cpu, memory: &MinidumpMemoryList) -> Option<CrashReason> {
+    pub fn last_error(&self, cpu: Cpu, memory: &UnifiedMemoryList) -> Option<CrashReason> {
         // Early hacky implementation: rather than implementing all the TEB layouts,
         // just use the fact that we know the value we want is a 13-pointers offset
         // from the start of the TEB.
@@ -2316,9 +4232,7 @@ impl<'a> MinidumpThread<'a> {
         let pointer_width = cpu.pointer_width()?;
         let offset = pointer_width.checked_mul(13)?;
         let addr = teb.checked_add(offset)?;
-        let val: u32 = memory
-            .memory_at_address(addr)?
-            .get_memory_at_address(addr)?;
+        let val = memory.memory_at_address(addr)?.read_u32(addr)?;
 
         Some(CrashReason::from_windows_error(val))
     }
@@ -2343,13 +4257,13 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadList<'a> {
             // to other streams that are required to parse a context properly.
             let context = location_slice(all, &raw.thread_context).ok();
 
-            // Try to get the stack memory here, but the `stack_memory` method will
-            // attempt a fallback method with access to other streams.
-            let stack = MinidumpMemory::read(&raw.stack, all).ok();
+            // The stack memory is found by the `stack_memory` method when it's
+            // first needed, since that can need other streams.
             threads.push(MinidumpThread {
                 raw,
                 context,
-                stack,
+                data: all,
+                stack: OnceLock::new(),
                 endian,
             });
         }
@@ -2372,7 +4286,7 @@ impl<'a> MinidumpThreadList<'a> {
     pub fn print<T: Write>(
         &self,
         f: &mut T,
-        memory: Option<&MinidumpMemoryList<'a>>,
+        memory: Option<&UnifiedMemoryList<'a>>,
         system: Option<&MinidumpSystemInfo>,
         misc: Option<&MinidumpMiscInfo>,
     ) -> io::Result<()> {
@@ -2393,6 +4307,121 @@ impl<'a> MinidumpThreadList<'a> {
     }
 }
 
+/// Known ARM implementers, by the implementer field of their cpuid.
+///
+/// There is no good list of implementer id values, but the following
+/// pages provide some help:
+///   http://comments.gmane.org/gmane.linux.linaro.devel/6903
+///   http://forum.xda-developers.com/archive/index.php/t-480226.html
+static ARM_VENDORS: [(u32, &str); 4] = [
+    (0x41, "ARM"),
+    (0x51, "Qualcomm"),
+    (0x56, "Marvell"),
+    (0x69, "Intel/Marvell"),
+];
+
+/// Known ARM parts, by the implementer and part number fields of their cpuid.
+static ARM_PARTS: [(u32, &str); 15] = [
+    (0x4100c050, "Cortex-A5"),
+    (0x4100c080, "Cortex-A8"),
+    (0x4100c090, "Cortex-A9"),
+    (0x4100c0f0, "Cortex-A15"),
+    (0x4100c140, "Cortex-R4"),
+    (0x4100c150, "Cortex-R5"),
+    (0x4100b360, "ARM1136"),
+    (0x4100b560, "ARM1156"),
+    (0x4100b760, "ARM1176"),
+    (0x4100b020, "ARM11-MPCore"),
+    (0x41009260, "ARM926"),
+    (0x41009460, "ARM946"),
+    (0x41009660, "ARM966"),
+    (0x510006f0, "Krait"),
+    (0x510000f0, "Scorpion"),
+];
+
+/// The names of the ARM hardware capabilities, as the Linux kernel calls them.
+static ARM_FEATURES: [(md::ArmElfHwCaps, &str); 19] = [
+    (md::ArmElfHwCaps::HWCAP_SWP, "swp"),
+    (md::ArmElfHwCaps::HWCAP_HALF, "half"),
+    (md::ArmElfHwCaps::HWCAP_THUMB, "thumb"),
+    (md::ArmElfHwCaps::HWCAP_26BIT, "26bit"),
+    (md::ArmElfHwCaps::HWCAP_FAST_MULT, "fastmult"),
+    (md::ArmElfHwCaps::HWCAP_FPA, "fpa"),
+    (md::ArmElfHwCaps::HWCAP_VFP, "vfpv2"),
+    (md::ArmElfHwCaps::HWCAP_EDSP, "edsp"),
+    (md::ArmElfHwCaps::HWCAP_JAVA, "java"),
+    (md::ArmElfHwCaps::HWCAP_IWMMXT, "iwmmxt"),
+    (md::ArmElfHwCaps::HWCAP_CRUNCH, "crunch"),
+    (md::ArmElfHwCaps::HWCAP_THUMBEE, "thumbee"),
+    (md::ArmElfHwCaps::HWCAP_NEON, "neon"),
+    (md::ArmElfHwCaps::HWCAP_VFPv3, "vfpv3"),
+    (md::ArmElfHwCaps::HWCAP_VFPv3D16, "vfpv3d16"),
+    (md::ArmElfHwCaps::HWCAP_TLS, "tls"),
+    (md::ArmElfHwCaps::HWCAP_VFPv4, "vfpv4"),
+    (md::ArmElfHwCaps::HWCAP_IDIVA, "idiva"),
+    (md::ArmElfHwCaps::HWCAP_IDIVT, "idivt"),
+];
+
+/// The names of the x86 features in [`md::X86CpuInfo::feature_information`][]
+/// (`edx` from `cpuid` leaf 1) by bit, as the Linux kernel calls them.
+static X86_FEATURES: [(u32, &str); 30] = [
+    (0, "fpu"),
+    (1, "vme"),
+    (2, "de"),
+    (3, "pse"),
+    (4, "tsc"),
+    (5, "msr"),
+    (6, "pae"),
+    (7, "mce"),
+    (8, "cx8"),
+    (9, "apic"),
+    (11, "sep"),
+    (12, "mtrr"),
+    (13, "pge"),
+    (14, "mca"),
+    (15, "cmov"),
+    (16, "pat"),
+    (17, "pse36"),
+    (18, "pn"),
+    (19, "clflush"),
+    (21, "dts"),
+    (22, "acpi"),
+    (23, "mmx"),
+    (24, "fxsr"),
+    (25, "sse"),
+    (26, "sse2"),
+    (27, "ss"),
+    (28, "ht"),
+    (29, "tm"),
+    (30, "ia64"),
+    (31, "pbe"),
+];
+
+/// The names of the AMD features in [`md::X86CpuInfo::amd_extended_cpu_features`][]
+/// (`edx` from `cpuid` leaf 0x80000001) by bit, as the Linux kernel calls them.
+///
+/// Only the ones that aren't the same as in [`X86_FEATURES`] are here.
+static X86_AMD_FEATURES: [(u32, &str); 10] = [
+    (11, "syscall"),
+    (19, "mp"),
+    (20, "nx"),
+    (22, "mmxext"),
+    (25, "fxsr_opt"),
+    (26, "pdpe1gb"),
+    (27, "rdtscp"),
+    (29, "lm"),
+    (30, "3dnowext"),
+    (31, "3dnow"),
+];
+
+/// The vendor id from `cpuid`, which is an ascii string stored in three `u32`s.
+fn x86_vendor_bytes(info: &md::X86CpuInfo) -> Vec<u8> {
+    info.vendor_id
+        .iter()
+        .flat_map(|i| IntoIterator::into_iter(i.to_le_bytes()))
+        .collect()
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
     const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::SystemInfoStream;
 
@@ -2449,55 +4478,6 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
                     .pread_with(0, endian)
                     .or(Err(Error::StreamReadFailure))?;
 
-                // There is no good list of implementer id values, but the following
-                // pages provide some help:
-                //   http://comments.gmane.org/gmane.linux.linaro.devel/6903
-                //   http://forum.xda-developers.com/archive/index.php/t-480226.html
-                let vendors = [
-                    (0x41, "ARM"),
-                    (0x51, "Qualcomm"),
-                    (0x56, "Marvell"),
-                    (0x69, "Intel/Marvell"),
-                ];
-                let parts = [
-                    (0x4100c050, "Cortex-A5"),
-                    (0x4100c080, "Cortex-A8"),
-                    (0x4100c090, "Cortex-A9"),
-                    (0x4100c0f0, "Cortex-A15"),
-                    (0x4100c140, "Cortex-R4"),
-                    (0x4100c150, "Cortex-R5"),
-                    (0x4100b360, "ARM1136"),
-                    (0x4100b560, "ARM1156"),
-                    (0x4100b760, "ARM1176"),
-                    (0x4100b020, "ARM11-MPCore"),
-                    (0x41009260, "ARM926"),
-                    (0x41009460, "ARM946"),
-                    (0x41009660, "ARM966"),
-                    (0x510006f0, "Krait"),
-                    (0x510000f0, "Scorpion"),
-                ];
-                let features = [
-                    (md::ArmElfHwCaps::HWCAP_SWP, "swp"),
-                    (md::ArmElfHwCaps::HWCAP_HALF, "half"),
-                    (md::ArmElfHwCaps::HWCAP_THUMB, "thumb"),
-                    (md::ArmElfHwCaps::HWCAP_26BIT, "26bit"),
-                    (md::ArmElfHwCaps::HWCAP_FAST_MULT, "fastmult"),
-                    (md::ArmElfHwCaps::HWCAP_FPA, "fpa"),
-                    (md::ArmElfHwCaps::HWCAP_VFP, "vfpv2"),
-                    (md::ArmElfHwCaps::HWCAP_EDSP, "edsp"),
-                    (md::ArmElfHwCaps::HWCAP_JAVA, "java"),
-                    (md::ArmElfHwCaps::HWCAP_IWMMXT, "iwmmxt"),
-                    (md::ArmElfHwCaps::HWCAP_CRUNCH, "crunch"),
-                    (md::ArmElfHwCaps::HWCAP_THUMBEE, "thumbee"),
-                    (md::ArmElfHwCaps::HWCAP_NEON, "neon"),
-                    (md::ArmElfHwCaps::HWCAP_VFPv3, "vfpv3"),
-                    (md::ArmElfHwCaps::HWCAP_VFPv3D16, "vfpv3d16"),
-                    (md::ArmElfHwCaps::HWCAP_TLS, "tls"),
-                    (md::ArmElfHwCaps::HWCAP_VFPv4, "vfpv4"),
-                    (md::ArmElfHwCaps::HWCAP_IDIVA, "idiva"),
-                    (md::ArmElfHwCaps::HWCAP_IDIVT, "idivt"),
-                ];
-
                 let mut cpu_info = format!("ARMv{}", raw.processor_level);
 
                 // Try to extract out known vendor/part names from the cpuid,
@@ -2507,13 +4487,14 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
                     let vendor_id = (cpuid >> 24) & 0xff;
                     let part_id = cpuid & 0xff00fff0;
 
-                    if let Some(&(_, vendor)) = vendors.iter().find(|&&(id, _)| id == vendor_id) {
+                    if let Some(&(_, vendor)) = ARM_VENDORS.iter().find(|&&(id, _)| id == vendor_id)
+                    {
                         write!(&mut cpu_info, " {}", vendor).unwrap();
                     } else {
                         write!(&mut cpu_info, " vendor(0x{:x})", vendor_id).unwrap();
                     }
 
-                    if let Some(&(_, part)) = parts.iter().find(|&&(id, _)| id == part_id) {
+                    if let Some(&(_, part)) = ARM_PARTS.iter().find(|&&(id, _)| id == part_id) {
                         write!(&mut cpu_info, " {}", part).unwrap();
                     } else {
                         write!(&mut cpu_info, " part(0x{:x})", part_id).unwrap();
@@ -2527,7 +4508,7 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
 
                     // Iterator::intersperse is still unstable, so do it manually
                     let mut comma = "";
-                    for &(_, feature) in features
+                    for &(_, feature) in ARM_FEATURES
                         .iter()
                         .filter(|&&(feature, _)| elf_hwcaps.contains(feature))
                     {
@@ -2548,11 +4529,29 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
             cpu,
             csd_version,
             cpu_info,
+            endian,
         })
     }
 }
 
 impl MinidumpSystemInfo {
+    /// A `MinidumpSystemInfo` with just the CPU that the context in `bytes` is for,
+    /// see [`Minidump::infer_system_info`][].
+    fn from_context(bytes: &[u8], endian: scroll::Endian) -> Option<MinidumpSystemInfo> {
+        let raw = md::MINIDUMP_SYSTEM_INFO {
+            processor_architecture: infer_context_architecture(bytes, endian)? as u16,
+            ..Default::default()
+        };
+        Some(MinidumpSystemInfo {
+            os: Os::from_platform_id(raw.platform_id),
+            cpu: Cpu::from_processor_architecture(raw.processor_architecture),
+            raw,
+            csd_version: None,
+            cpu_info: None,
+            endian,
+        })
+    }
+
     /// Write a human-readable description of this `MinidumpSystemInfo` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -2573,6 +4572,8 @@ impl MinidumpSystemInfo {
   suite_mask                                 = {:#x}
   (version)                                  = {}{}{} {}
   (cpu_info)                                 = {}
+  (cpu_vendor)                               = {}
+  (cpu_features)                             = {}
 
 ",
             self.raw.processor_architecture,
@@ -2591,8 +4592,9 @@ impl MinidumpSystemInfo {
             self.raw.build_number,
             self.csd_version().as_deref().unwrap_or(""),
             self.cpu_info().as_deref().unwrap_or(""),
+            self.cpu_vendor().as_deref().unwrap_or(""),
+            self.cpu_features().join(" "),
         )?;
-        // TODO: cpu info etc
         Ok(())
     }
 
@@ -2604,6 +4606,126 @@ impl MinidumpSystemInfo {
     pub fn cpu_info(&self) -> Option<Cow<str>> {
         self.cpu_info.as_deref().map(Cow::Borrowed)
     }
+
+    /// The `cpuid` information in `raw.cpu`, if it has any.
+    ///
+    /// This is always there for x86, and only sometimes for x86-64 (Windows
+    /// puts a [`md::OtherCpuInfo`][] there instead), so it's only trusted if
+    /// the vendor id looks like one.
+    fn x86_cpu_info(&self) -> Option<md::X86CpuInfo> {
+        if !matches!(self.cpu, Cpu::X86 | Cpu::X86_64) {
+            return None;
+        }
+        let info: md::X86CpuInfo = self.raw.cpu.data.pread_with(0, self.endian).ok()?;
+        let vendor = x86_vendor_bytes(&info);
+        if vendor.iter().all(|byte| byte.is_ascii_graphic()) {
+            Some(info)
+        } else {
+            None
+        }
+    }
+
+    /// The cpuid and hardware capabilities in `raw.cpu`, if this is an ARM CPU.
+    fn arm_cpu_info(&self) -> Option<md::ARMCpuInfo> {
+        if self.cpu != Cpu::Arm {
+            return None;
+        }
+        self.raw.cpu.data.pread_with(0, self.endian).ok()
+    }
+
+    /// The CPU's vendor, if it's known.
+    ///
+    /// For x86 this is the vendor id from `cpuid`, e.g. "GenuineIntel" or
+    /// "AuthenticAMD". For ARM this is the name of the cpuid's implementer,
+    /// e.g. "Qualcomm".
+    pub fn cpu_vendor(&self) -> Option<String> {
+        if let Some(info) = self.x86_cpu_info() {
+            return Some(
+                x86_vendor_bytes(&info)
+                    .into_iter()
+                    .map(char::from)
+                    .collect(),
+            );
+        }
+        let cpuid = self.arm_cpu_info()?.cpuid;
+        if cpuid == 0 {
+            return None;
+        }
+        let vendor_id = (cpuid >> 24) & 0xff;
+        ARM_VENDORS
+            .iter()
+            .find(|&&(id, _)| id == vendor_id)
+            .map(|&(_, vendor)| String::from(vendor))
+    }
+
+    /// The family, model, and stepping of an x86 or x86-64 CPU.
+    ///
+    /// These are decoded from the version information from `cpuid` if the
+    /// minidump has it, including the extended family and model, and otherwise
+    /// come from `raw.processor_level` and `raw.processor_revision`.
+    pub fn cpu_family_model_stepping(&self) -> Option<(u32, u32, u32)> {
+        if !matches!(self.cpu, Cpu::X86 | Cpu::X86_64) {
+            return None;
+        }
+        let version = self
+            .x86_cpu_info()
+            .map(|info| info.version_information)
+            .unwrap_or(0);
+        if version == 0 {
+            let revision = self.raw.processor_revision as u32;
+            return Some((
+                self.raw.processor_level as u32,
+                (revision >> 8) & 0xff,
+                revision & 0xff,
+            ));
+        }
+        let stepping = version & 0xf;
+        let mut model = (version >> 4) & 0xf;
+        let mut family = (version >> 8) & 0xf;
+        if family == 0xf {
+            family += (version >> 20) & 0xff;
+        }
+        if family == 0x6 || family >= 0xf {
+            model += ((version >> 16) & 0xf) << 4;
+        }
+        Some((family, model, stepping))
+    }
+
+    /// The features the CPU has, as the Linux kernel names them, if the
+    /// minidump records them.
+    ///
+    /// For x86 these are the basic features from `cpuid` (and AMD's extended
+    /// ones on AMD CPUs), e.g. "sse2". For ARM these are the hardware
+    /// capabilities, e.g. "neon".
+    pub fn cpu_features(&self) -> Vec<&'static str> {
+        if let Some(info) = self.x86_cpu_info() {
+            let mut features = X86_FEATURES
+                .iter()
+                .filter(|&&(bit, _)| info.feature_information & (1 << bit) != 0)
+                .map(|&(_, name)| name)
+                .collect::<Vec<_>>();
+            if x86_vendor_bytes(&info) == b"AuthenticAMD" {
+                features.extend(
+                    X86_AMD_FEATURES
+                        .iter()
+                        .filter(|&&(bit, _)| info.amd_extended_cpu_features & (1 << bit) != 0)
+                        .map(|&(_, name)| name),
+                );
+            }
+            return features;
+        }
+        match self.arm_cpu_info() {
+            Some(info) => {
+                let elf_hwcaps = md::ArmElfHwCaps::from_bits_truncate(info.elf_hwcaps);
+                ARM_FEATURES
+                    .iter()
+                    .filter(|&&(feature, _)| elf_hwcaps.contains(feature))
+                    .map(|&(_, name)| name)
+                    .collect()
+            }
+            None => vec![],
+        }
+    }
 }
 
 // Generates an accessor for a MISC_INFO field with two possible syntaxes:
@@ -2846,6 +4968,63 @@ impl RawMacCrashInfo {
 
         5: abort_cause -> u64,
     );
+
+    /// The non-empty message strings of this record, in the order they appear.
+    ///
+    /// These are the free-form strings a module (or the OS on its behalf) set
+    /// before crashing, and often contain the text of a failed assertion.
+    pub fn messages(&self) -> Vec<&str> {
+        [
+            self.message(),
+            self.signature_string(),
+            self.backtrace(),
+            self.message2(),
+        ]
+        .iter()
+        .flatten()
+        .copied()
+        .collect()
+    }
+}
+
+impl MinidumpMacCrashInfo {
+    /// Write a human-readable description of this.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpMacCrashInfo
+  num_records = {}
+",
+            self.raw.len()
+        )?;
+        for (i, record) in self.raw.iter().enumerate() {
+            write!(
+                f,
+                "  record[{}]
+    version          = {}
+    thread           = {:#x}
+    dialog_mode      = {:#x}
+    abort_cause      = {:#x}
+    module_path      = {}
+    message          = {}
+    signature_string = {}
+    backtrace        = {}
+    message2         = {}
+",
+                i,
+                record.version().copied().unwrap_or(0),
+                record.thread().copied().unwrap_or(0),
+                record.dialog_mode().copied().unwrap_or(0),
+                record.abort_cause().copied().unwrap_or(0),
+                record.module_path().unwrap_or(""),
+                record.message().unwrap_or(""),
+                record.signature_string().unwrap_or(""),
+                record.backtrace().unwrap_or(""),
+                record.message2().unwrap_or(""),
+            )?;
+        }
+        writeln!(f)
+    }
 }
 
 impl<'a> MinidumpStream<'a> for MinidumpMacCrashInfo {
@@ -2864,7 +5043,11 @@ impl<'a> MinidumpStream<'a> for MinidumpMacCrashInfo {
         let strings_offset = header.record_start_size as usize;
         let mut prev_version = None;
         let mut infos = Vec::new();
-        for record_location in &header.records[..header.record_count as usize] {
+        let records = header
+            .records
+            .get(..header.record_count as usize)
+            .ok_or(Error::StreamReadFailure)?;
+        for record_location in records {
             // Peek the V1 version to get the `version` field
             let record_slice = location_slice(all, record_location)?;
             let base: md::MINIDUMP_MAC_CRASH_INFO_RECORD = record_slice
@@ -2917,12 +5100,18 @@ impl<'a> MinidumpStream<'a> for MinidumpMacCrashInfo {
                         let num_strings = <$strings>::num_strings();
                         let mut strings = <$strings>::default();
 
-                        // Read out all the strings we know about
+                        // Read out all the strings we know about. The strings are copied
+                        // out of the crashing process with a size limit, so the record
+                        // can end in the middle of (or before) some of them. The part we
+                        // have is still useful, so keep it rather than failing.
+                        let mut truncated = false;
                         for i in 0..num_strings {
-                            let string = read_cstring_utf8(offset, record_slice)
-                                .or(Err(Error::StreamReadFailure))?;
+                            let string = read_cstring_lossy(offset, record_slice, &mut truncated);
                             strings.set_string(i, string);
                         }
+                        if truncated {
+                            warn!("MozMacosCrashInfoStream record has truncated strings");
+                        }
                         // If this is a newer version, there may be some extra variable length
                         // data in this record, but we don't know what it is, so don't try to parse it.
 
@@ -2985,6 +5174,90 @@ impl<'a> MinidumpStream<'a> for MinidumpLinuxEnviron<'a> {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpLinuxDsoDebug {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::LinuxDsoDebug;
+
+    fn read(
+        bytes: &'a [u8],
+        all: &'a [u8],
+        endian: scroll::Endian,
+    ) -> Result<MinidumpLinuxDsoDebug, Error> {
+        // There's nothing in the stream that says which format it's in, but
+        // the 64-bit header is bigger.
+        let is_64_bit = bytes.len() >= md::DSO_DEBUG_64::size_with(&endian);
+        let (version, map, dso_count, brk, ldbase, dynamic) = if is_64_bit {
+            let raw: md::DSO_DEBUG_64 = bytes
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            (
+                raw.version,
+                raw.map,
+                raw.dso_count,
+                raw.brk,
+                raw.ldbase,
+                raw.dynamic,
+            )
+        } else {
+            let raw: md::DSO_DEBUG_32 = bytes
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            (
+                raw.version,
+                raw.map,
+                raw.dso_count,
+                raw.brk as u64,
+                raw.ldbase as u64,
+                raw.dynamic as u64,
+            )
+        };
+
+        let entry_size = if is_64_bit {
+            md::LINK_MAP_64::size_with(&endian)
+        } else {
+            md::LINK_MAP_32::size_with(&endian)
+        };
+        let entries = all.get(map as usize..).ok_or(Error::StreamReadFailure)?;
+        let (count, _) = ensure_count_in_bound(entries, dso_count as usize, entry_size, 0)?;
+
+        let mut offset = 0;
+        let mut link_maps = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (addr, name_rva, ld) = if is_64_bit {
+                let raw: md::LINK_MAP_64 = entries
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                (raw.addr, raw.name, raw.ld)
+            } else {
+                let raw: md::LINK_MAP_32 = entries
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                (raw.addr as u64, raw.name, raw.ld as u64)
+            };
+            // A null name pointer is normal (it's usually the main executable),
+            // and a bad one shouldn't lose the rest of the list.
+            let name = if name_rva == 0 {
+                None
+            } else {
+                read_string_utf16(&mut (name_rva as usize), all, endian).ok()
+            };
+            link_maps.push(MinidumpLinkMap {
+                addr,
+                name,
+                name_rva,
+                ld,
+            });
+        }
+
+        Ok(MinidumpLinuxDsoDebug {
+            version,
+            brk,
+            ldbase,
+            dynamic,
+            link_maps,
+        })
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpLinuxProcStatus<'a> {
     const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::LinuxProcStatus;
 
@@ -3024,16 +5297,162 @@ impl<'a> MinidumpLinuxCpuInfo<'a> {
     pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
         Cow::Borrowed(self.data)
     }
+
+    /// Get the first value for `key`, if it's valid utf8.
+    ///
+    /// Every processor gets its own block of values, so this is the value
+    /// for the first processor.
+    fn first_value(&self, key: &str) -> Option<&'a str> {
+        self.iter()
+            .find(|(k, _)| k.as_bytes() == key.as_bytes())
+            .and_then(|(_, v)| v.to_str().ok())
+    }
+
+    /// The number of processors listed.
+    pub fn cpu_count(&self) -> usize {
+        self.iter()
+            .filter(|(k, _)| k.as_bytes() == b"processor")
+            .count()
+    }
+
+    /// The `model name` of the CPU, e.g. "Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz".
+    pub fn model_name(&self) -> Option<&'a str> {
+        self.first_value("model name")
+    }
+
+    /// The `vendor_id` of the CPU, e.g. "GenuineIntel" (x86 only).
+    pub fn vendor(&self) -> Option<&'a str> {
+        self.first_value("vendor_id")
+    }
+
+    /// The features the CPU supports, e.g. "avx2" or "sse4_2".
+    ///
+    /// These are the `flags` on x86 and the `Features` on ARM.
+    pub fn flags(&self) -> HashSet<&'a str> {
+        self.first_value("flags")
+            .or_else(|| self.first_value("Features"))
+            .map(|flags| flags.split_ascii_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the CPU supports the given feature (see [`flags`][Self::flags]).
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags().contains(flag)
+    }
+
+    /// The (`CPU implementer`, `CPU part`) of each processor (ARM only).
+    ///
+    /// For instance (0x41, 0xd03) is an ARM Cortex-A53. Systems that mix
+    /// different kinds of cores (e.g. big.LITTLE) will have several
+    /// different pairs.
+    pub fn arm_cpu_parts(&self) -> Vec<(u32, u32)> {
+        let hex = |val: &LinuxOsStr| {
+            val.to_str()
+                .ok()
+                .and_then(|val| val.strip_prefix("0x"))
+                .and_then(|val| u32::from_str_radix(val, 16).ok())
+        };
+        let mut implementer = None;
+        let mut parts = vec![];
+        for (key, val) in self.iter() {
+            match key.as_bytes() {
+                b"CPU implementer" => implementer = hex(val),
+                b"CPU part" => {
+                    if let (Some(implementer), Some(part)) = (implementer, hex(val)) {
+                        parts.push((implementer, part));
+                    }
+                }
+                _ => {}
+            }
+        }
+        parts
+    }
+
+    /// Write a human-readable description of this.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let mut flags = self.flags().into_iter().collect::<Vec<_>>();
+        flags.sort_unstable();
+        let arm_cpu_parts = self
+            .arm_cpu_parts()
+            .iter()
+            .map(|(implementer, part)| format!("{:#x}:{:#x}", implementer, part))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "MinidumpLinuxCpuInfo
+  cpu_count     = {}
+  vendor        = {}
+  model_name    = {}
+  flags         = {}
+  arm_cpu_parts = {}
+
+",
+            self.cpu_count(),
+            self.vendor().unwrap_or(""),
+            self.model_name().unwrap_or(""),
+            flags.join(" "),
+            arm_cpu_parts.join(" "),
+        )
+    }
 }
 
 impl<'a> MinidumpLinuxEnviron<'a> {
-    /// Get an iterator over the key-value pairs stored in the `/proc/self/environ` dump.
+    /// Get an iterator over the `KEY=VALUE` pairs stored in the `/proc/self/environ` dump,
+    /// in the order they appear.
     ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
+    /// The entries are NUL-separated, and the key is everything before the
+    /// first `=`, so values may contain `=` (and newlines) themselves. Entries
+    /// without an `=` are skipped.
     pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b'=')
+        self.data
+            .split(|&b| b == 0)
+            .filter_map(|entry| LinuxOsStr::from_bytes(entry).split_once(b'='))
+    }
+
+    /// Get the value of the environment variable `key`, if it's set.
+    ///
+    /// Invalid UTF-8 is replaced with U+FFFD. If `key` is set more than once,
+    /// the first value wins, like `getenv`.
+    pub fn get(&self, key: &str) -> Option<Cow<'a, str>> {
+        self.iter()
+            .find(|(k, _)| k.as_bytes() == key.as_bytes())
+            .map(|(_, val)| val.to_string_lossy())
+    }
+
+    /// Get all the environment variables, sorted by name.
+    ///
+    /// Invalid UTF-8 is replaced with U+FFFD. If a variable is set more than
+    /// once, the first value wins, like `getenv`.
+    pub fn vars(&self) -> BTreeMap<Cow<'a, str>, Cow<'a, str>> {
+        let mut vars = BTreeMap::new();
+        for (key, val) in self.iter() {
+            vars.entry(key.to_string_lossy())
+                .or_insert_with(|| val.to_string_lossy());
+        }
+        vars
+    }
+
+    /// Get all the environment variables except the ones matching `deny_list`.
+    ///
+    /// This is for removing secrets before passing the environment along.
+    /// Each pattern is either a variable name (`AWS_SECRET_ACCESS_KEY`), a
+    /// prefix ending in `*` (`SSH_*`), or a suffix starting with `*` (`*_TOKEN`).
+    pub fn redacted<S: AsRef<str>>(&self, deny_list: &[S]) -> BTreeMap<Cow<'a, str>, Cow<'a, str>> {
+        let mut vars = self.vars();
+        vars.retain(|key, _| !env_var_matches(deny_list, key));
+        vars
+    }
+
+    /// Get only the environment variables matching `allow_list`.
+    ///
+    /// The patterns are the same as for [`MinidumpLinuxEnviron::redacted`][].
+    pub fn allowlisted<S: AsRef<str>>(
+        &self,
+        allow_list: &[S],
+    ) -> BTreeMap<Cow<'a, str>, Cow<'a, str>> {
+        let mut vars = self.vars();
+        vars.retain(|key, _| env_var_matches(allow_list, key));
+        vars
     }
 
     /// Get the raw bytes of the `/proc/self/environ` dump.
@@ -3042,6 +5461,60 @@ impl<'a> MinidumpLinuxEnviron<'a> {
     }
 }
 
+/// Whether the environment variable `name` matches any of `patterns`.
+///
+/// See [`MinidumpLinuxEnviron::redacted`][] for the pattern syntax.
+fn env_var_matches<S: AsRef<str>>(patterns: &[S], name: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.as_ref();
+        if let Some(prefix) = pattern.strip_suffix('*') {
+            name.starts_with(prefix)
+        } else if let Some(suffix) = pattern.strip_prefix('*') {
+            name.ends_with(suffix)
+        } else {
+            name == pattern
+        }
+    })
+}
+
+impl MinidumpLinuxDsoDebug {
+    /// Write a human-readable description of this.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpLinuxDsoDebug
+  version   = {}
+  brk       = {:#x}
+  ldbase    = {:#x}
+  dynamic   = {:#x}
+  dso_count = {}
+",
+            self.version,
+            self.brk,
+            self.ldbase,
+            self.dynamic,
+            self.link_maps.len(),
+        )?;
+        for (i, link_map) in self.link_maps.iter().enumerate() {
+            write!(
+                f,
+                "  link_map[{}]
+    addr     = {:#x}
+    name_rva = {:#x}
+    name     = {}
+    ld       = {:#x}
+",
+                i,
+                link_map.addr,
+                link_map.name_rva,
+                link_map.name.as_deref().unwrap_or("(null)"),
+                link_map.ld,
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
 impl<'a> MinidumpLinuxProcStatus<'a> {
     /// Get an iterator over the key-value pairs stored in the `/proc/self/status` dump.
     ///
@@ -3056,56 +5529,270 @@ impl<'a> MinidumpLinuxProcStatus<'a> {
     pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
         Cow::Borrowed(self.data)
     }
-}
 
-impl<'a> MinidumpLinuxLsbRelease<'a> {
-    /// Get an iterator over the key-value pairs stored in the `/etc/lsb-release` dump.
-    ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
-    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b'=')
+    /// Get the value for `key`, if it's valid utf8.
+    fn value(&self, key: &str) -> Option<&'a str> {
+        self.iter()
+            .find(|(k, _)| k.as_bytes() == key.as_bytes())
+            .and_then(|(_, v)| v.to_str().ok())
     }
 
-    /// Get the raw bytes of the `/etc/lsb-release` dump.
-    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
-        Cow::Borrowed(self.data)
+    fn number(&self, key: &str) -> Option<u32> {
+        self.value(key)?.parse().ok()
     }
-}
 
-impl MinidumpMiscInfo {
-    pub fn process_create_time(&self) -> Option<DateTime<Utc>> {
-        self.raw
-            .process_create_time()
-            .map(|t| Utc.timestamp(*t as i64, 0))
+    /// Get a `kB` value in bytes.
+    fn bytes(&self, key: &str) -> Option<u64> {
+        let kb = self.value(key)?.strip_suffix("kB")?.trim_end();
+        kb.parse::<u64>().ok()?.checked_mul(1024)
     }
 
-    /// Write a human-readable description of this `MinidumpMiscInfo` to `f`.
-    ///
-    /// This is very verbose, it is the format used by `minidump_dump`.
-    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
-        macro_rules! write_simple_field {
-            ($stream:ident, $field:ident, $format:literal) => {
-                write!(f, "  {:29}= ", stringify!($field))?;
-                match self.raw.$field() {
-                    Some($field) => {
-                        writeln!(f, $format, $field)?;
-                    }
-                    None => writeln!(f, "(invalid)")?,
-                }
-            };
-            ($stream:ident, $field:ident) => {
-                write_simple_field!($stream, $field, "{}");
-            };
-        }
-        writeln!(f, "MINIDUMP_MISC_INFO")?;
+    /// Get a signal mask (a bit for each signal, with signal 1 as the lowest bit).
+    fn mask(&self, key: &str) -> Option<u64> {
+        u64::from_str_radix(self.value(key)?, 16).ok()
+    }
 
-        write_simple_field!(f, size_of_info);
-        write_simple_field!(f, flags1, "{:x}");
-        write_simple_field!(f, process_id);
-        write!(f, "  process_create_time          = ")?;
-        match self.raw.process_create_time() {
+    /// Get the (real, effective, saved set, filesystem) ids.
+    fn ids(&self, key: &str) -> Option<[u32; 4]> {
+        let mut ids = self.value(key)?.split_ascii_whitespace().map(str::parse);
+        let mut next = || ids.next()?.ok();
+        Some([next()?, next()?, next()?, next()?])
+    }
+
+    /// The name of the process's executable (truncated to 15 bytes by the kernel).
+    pub fn name(&self) -> Option<&'a str> {
+        self.value("Name")
+    }
+
+    /// The process's id.
+    pub fn pid(&self) -> Option<u32> {
+        self.number("Pid")
+    }
+
+    /// The id of the process's parent.
+    pub fn ppid(&self) -> Option<u32> {
+        self.number("PPid")
+    }
+
+    /// The process's (real, effective, saved set, filesystem) user ids.
+    pub fn uids(&self) -> Option<[u32; 4]> {
+        self.ids("Uid")
+    }
+
+    /// The process's (real, effective, saved set, filesystem) group ids.
+    pub fn gids(&self) -> Option<[u32; 4]> {
+        self.ids("Gid")
+    }
+
+    /// The peak virtual memory size of the process, in bytes.
+    pub fn vm_peak(&self) -> Option<u64> {
+        self.bytes("VmPeak")
+    }
+
+    /// The virtual memory size of the process, in bytes.
+    pub fn vm_size(&self) -> Option<u64> {
+        self.bytes("VmSize")
+    }
+
+    /// The resident set size of the process (how much of it is in RAM), in bytes.
+    pub fn vm_rss(&self) -> Option<u64> {
+        self.bytes("VmRSS")
+    }
+
+    /// How much of the process's memory is swapped out, in bytes.
+    pub fn vm_swap(&self) -> Option<u64> {
+        self.bytes("VmSwap")
+    }
+
+    /// The number of threads in the process.
+    pub fn threads(&self) -> Option<u32> {
+        self.number("Threads")
+    }
+
+    /// The signals the process has handlers for.
+    pub fn sig_cgt(&self) -> Option<u64> {
+        self.mask("SigCgt")
+    }
+
+    /// The signals the process ignores.
+    pub fn sig_ign(&self) -> Option<u64> {
+        self.mask("SigIgn")
+    }
+
+    /// The signals the process has blocked.
+    pub fn sig_blk(&self) -> Option<u64> {
+        self.mask("SigBlk")
+    }
+
+    /// The process's seccomp mode: 0 is disabled, 1 is strict, and 2 is filtered.
+    pub fn seccomp(&self) -> Option<u32> {
+        self.number("Seccomp")
+    }
+
+    /// Write a human-readable description of this.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        fn opt<V: fmt::Display>(val: Option<V>) -> String {
+            val.map_or_else(|| String::from("(none)"), |val| val.to_string())
+        }
+        let hex = |val: Option<u64>| opt(val.map(|val| format!("{:#018x}", val)));
+        let ids = |val: Option<[u32; 4]>| {
+            opt(val.map(|[real, effective, saved, fs]| {
+                format!("{} {} {} {}", real, effective, saved, fs)
+            }))
+        };
+        write!(
+            f,
+            "MinidumpLinuxProcStatus
+  name    = {}
+  pid     = {}
+  ppid    = {}
+  uids    = {}
+  gids    = {}
+  vm_peak = {}
+  vm_size = {}
+  vm_rss  = {}
+  vm_swap = {}
+  threads = {}
+  sig_cgt = {}
+  sig_ign = {}
+  sig_blk = {}
+  seccomp = {}
+
+",
+            opt(self.name()),
+            opt(self.pid()),
+            opt(self.ppid()),
+            ids(self.uids()),
+            ids(self.gids()),
+            opt(self.vm_peak()),
+            opt(self.vm_size()),
+            opt(self.vm_rss()),
+            opt(self.vm_swap()),
+            opt(self.threads()),
+            hex(self.sig_cgt()),
+            hex(self.sig_ign()),
+            hex(self.sig_blk()),
+            opt(self.seccomp()),
+        )
+    }
+}
+
+impl<'a> MinidumpLinuxLsbRelease<'a> {
+    /// Get an iterator over the key-value pairs stored in the `/etc/lsb-release` dump.
+    ///
+    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
+    /// or value was surrounded by quotes ("like this"), the quotes will be
+    /// stripped.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
+        linux_list_iter(self.data, b'=')
+    }
+
+    /// Get the value for the first of `keys` that's present.
+    ///
+    /// Breakpad falls back to `/etc/os-release` if there's no `/etc/lsb-release`,
+    /// and they have different names for the same things.
+    fn value(&self, keys: &[&str]) -> Option<Cow<'a, str>> {
+        keys.iter().find_map(|key| {
+            self.iter()
+                .find(|(k, _)| k.as_bytes() == key.as_bytes())
+                .map(|(_, v)| v.to_string_lossy())
+        })
+    }
+
+    /// The distribution's id, e.g. "Ubuntu" (`DISTRIB_ID`, or `ID` in os-release).
+    pub fn id(&self) -> Option<Cow<'a, str>> {
+        self.value(&["DISTRIB_ID", "ID"])
+    }
+
+    /// The distribution's version, e.g. "22.04" (`DISTRIB_RELEASE`, or `VERSION_ID` in os-release).
+    pub fn release(&self) -> Option<Cow<'a, str>> {
+        self.value(&["DISTRIB_RELEASE", "VERSION_ID"])
+    }
+
+    /// The release's codename, e.g. "jammy" (`DISTRIB_CODENAME`, or `VERSION_CODENAME` in os-release).
+    pub fn codename(&self) -> Option<Cow<'a, str>> {
+        self.value(&["DISTRIB_CODENAME", "VERSION_CODENAME"])
+    }
+
+    /// A human-readable description of the release, e.g. "Ubuntu 22.04.1 LTS"
+    /// (`DISTRIB_DESCRIPTION`, or `PRETTY_NAME` in os-release).
+    pub fn description(&self) -> Option<Cow<'a, str>> {
+        self.value(&["DISTRIB_DESCRIPTION", "PRETTY_NAME"])
+    }
+
+    /// Get the raw bytes of the `/etc/lsb-release` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
+}
+
+impl MinidumpMiscInfo {
+    pub fn process_create_time(&self) -> Option<DateTime<Utc>> {
+        self.raw
+            .process_create_time()
+            .map(|t| Utc.timestamp(*t as i64, 0))
+    }
+
+    /// The build string of the OS that wrote the minidump.
+    ///
+    /// This is only present in `MINIDUMP_MISC_INFO_4` and later, and looks
+    /// something like "19041.1.amd64fre.vb_release.191206-1406".
+    pub fn build_string(&self) -> Option<String> {
+        self.raw
+            .build_string()
+            .and_then(|string| utf16_to_string(&string[..]))
+            .filter(|string| !string.is_empty())
+    }
+
+    /// The build string of the debugging library that wrote the minidump.
+    ///
+    /// This is only present in `MINIDUMP_MISC_INFO_4` and later, and looks
+    /// something like "dbghelp.dll:10.0.19041.1(WinBuild.160101.0800)".
+    pub fn dbg_bld_str(&self) -> Option<String> {
+        self.raw
+            .dbg_bld_str()
+            .and_then(|string| utf16_to_string(&string[..]))
+            .filter(|string| !string.is_empty())
+    }
+
+    /// The location of an XSAVE feature in the thread contexts, if it's enabled.
+    ///
+    /// This is only present in `MINIDUMP_MISC_INFO_5` and later. See
+    /// [`XSTATE_CONFIG_FEATURE_MSC_INFO`][md::XSTATE_CONFIG_FEATURE_MSC_INFO]
+    /// for how to interpret the result.
+    pub fn xstate_feature(&self, feature: md::XstateFeatureIndex) -> Option<&md::XSTATE_FEATURE> {
+        let xstate_data = self.raw.xstate_data()?;
+        xstate_data
+            .iter()
+            .find(|&(i, _)| i == feature as usize)
+            .map(|(i, _)| &xstate_data.features[i])
+    }
+
+    /// Write a human-readable description of this `MinidumpMiscInfo` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        macro_rules! write_simple_field {
+            ($stream:ident, $field:ident, $format:literal) => {
+                write!(f, "  {:29}= ", stringify!($field))?;
+                match self.raw.$field() {
+                    Some($field) => {
+                        writeln!(f, $format, $field)?;
+                    }
+                    None => writeln!(f, "(invalid)")?,
+                }
+            };
+            ($stream:ident, $field:ident) => {
+                write_simple_field!($stream, $field, "{}");
+            };
+        }
+        writeln!(f, "MINIDUMP_MISC_INFO")?;
+
+        write_simple_field!(f, size_of_info);
+        write_simple_field!(f, flags1, "{:x}");
+        write_simple_field!(f, process_id);
+        write!(f, "  process_create_time          = ")?;
+        match self.raw.process_create_time() {
             Some(&process_create_time) => {
                 writeln!(
                     f,
@@ -3164,20 +5851,12 @@ impl MinidumpMiscInfo {
         }
 
         write!(f, "  build_string                 = ")?;
-        match self
-            .raw
-            .build_string()
-            .and_then(|string| utf16_to_string(&string[..]))
-        {
+        match self.build_string() {
             Some(build_string) => writeln!(f, "{}", build_string)?,
             None => writeln!(f, "(invalid)")?,
         }
         write!(f, "  dbg_bld_str                  = ")?;
-        match self
-            .raw
-            .dbg_bld_str()
-            .and_then(|string| utf16_to_string(&string[..]))
-        {
+        match self.dbg_bld_str() {
             Some(dbg_bld_str) => writeln!(f, "{}", dbg_bld_str)?,
             None => writeln!(f, "(invalid)")?,
         }
@@ -3204,6 +5883,348 @@ impl MinidumpMiscInfo {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpSystemMemoryInfo {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream;
+
+    fn read(
+        bytes: &[u8],
+        _all: &[u8],
+        endian: scroll::Endian,
+    ) -> Result<MinidumpSystemMemoryInfo, Error> {
+        let raw = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        Ok(MinidumpSystemMemoryInfo { raw })
+    }
+}
+
+impl MinidumpSystemMemoryInfo {
+    fn basic_perf_info_valid(&self) -> bool {
+        md::SystemMemoryInfoFlags::from_bits_truncate(self.raw.flags)
+            .contains(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF)
+    }
+
+    /// The size of a page of memory, in bytes.
+    pub fn page_size(&self) -> u64 {
+        self.raw.basic_info.page_size as u64
+    }
+
+    /// The amount of physical memory in the system, in bytes.
+    pub fn physical_memory(&self) -> u64 {
+        self.raw.basic_info.number_of_physical_pages as u64 * self.page_size()
+    }
+
+    /// The amount of physical memory that was available, in bytes.
+    pub fn available_physical_memory(&self) -> u64 {
+        let pages = if self.basic_perf_info_valid() {
+            self.raw.basic_perf_info.available_pages
+        } else {
+            self.raw.perf_info.available_pages as u64
+        };
+        pages * self.page_size()
+    }
+
+    /// The system's commit charge (memory that the system has promised to
+    /// back with RAM or the page file), in bytes.
+    pub fn committed_memory(&self) -> u64 {
+        let pages = if self.basic_perf_info_valid() {
+            self.raw.basic_perf_info.committed_pages
+        } else {
+            self.raw.perf_info.committed_pages as u64
+        };
+        pages * self.page_size()
+    }
+
+    /// The most memory that the system can commit, in bytes. Allocations fail
+    /// once the commit charge reaches this.
+    pub fn commit_limit(&self) -> u64 {
+        let pages = if self.basic_perf_info_valid() {
+            self.raw.basic_perf_info.commit_limit
+        } else {
+            self.raw.perf_info.commit_limit as u64
+        };
+        pages * self.page_size()
+    }
+
+    /// The highest the system's commit charge has been, in bytes.
+    pub fn peak_committed_memory(&self) -> u64 {
+        let pages = if self.basic_perf_info_valid() {
+            self.raw.basic_perf_info.peak_commitment
+        } else {
+            self.raw.perf_info.peak_commitment as u64
+        };
+        pages * self.page_size()
+    }
+
+    /// Write a human-readable description of this `MinidumpSystemMemoryInfo` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let raw = &self.raw;
+        let basic = &raw.basic_info;
+        let cache = &raw.file_cache_info;
+        let basic_perf = &raw.basic_perf_info;
+        let perf = &raw.perf_info;
+        write!(
+            f,
+            "MINIDUMP_SYSTEM_MEMORY_INFO_1
+  revision                                   = {}
+  flags                                      = {:#x}
+  basic_info.timer_resolution                = {}
+  basic_info.page_size                       = {:#x}
+  basic_info.number_of_physical_pages        = {:#x}
+  basic_info.lowest_physical_page_number     = {:#x}
+  basic_info.highest_physical_page_number    = {:#x}
+  basic_info.allocation_granularity          = {:#x}
+  basic_info.minimum_user_mode_address       = {:#x}
+  basic_info.maximum_user_mode_address       = {:#x}
+  basic_info.active_processors_affinity_mask = {:#x}
+  basic_info.number_of_processors            = {}
+  file_cache_info.current_size               = {:#x}
+  file_cache_info.peak_size                  = {:#x}
+  file_cache_info.page_fault_count           = {}
+  file_cache_info.minimum_working_set        = {:#x}
+  file_cache_info.maximum_working_set        = {:#x}
+  basic_perf_info.available_pages            = {:#x}
+  basic_perf_info.committed_pages            = {:#x}
+  basic_perf_info.commit_limit               = {:#x}
+  basic_perf_info.peak_commitment            = {:#x}
+  perf_info.available_pages                  = {:#x}
+  perf_info.committed_pages                  = {:#x}
+  perf_info.commit_limit                     = {:#x}
+  perf_info.peak_commitment                  = {:#x}
+  perf_info.page_fault_count                 = {}
+  perf_info.paged_pool_pages                 = {:#x}
+  perf_info.non_paged_pool_pages             = {:#x}
+  perf_info.resident_available_pages         = {:#x}
+  perf_info.shared_committed_pages           = {:#x}
+
+",
+            raw.revision,
+            raw.flags,
+            basic.timer_resolution,
+            basic.page_size,
+            basic.number_of_physical_pages,
+            basic.lowest_physical_page_number,
+            basic.highest_physical_page_number,
+            basic.allocation_granularity,
+            basic.minimum_user_mode_address,
+            basic.maximum_user_mode_address,
+            basic.active_processors_affinity_mask,
+            basic.number_of_processors,
+            cache.current_size,
+            cache.peak_size,
+            cache.page_fault_count,
+            cache.minimum_working_set,
+            cache.maximum_working_set,
+            basic_perf.available_pages,
+            basic_perf.committed_pages,
+            basic_perf.commit_limit,
+            basic_perf.peak_commitment,
+            perf.available_pages,
+            perf.committed_pages,
+            perf.commit_limit,
+            perf.peak_commitment,
+            perf.page_fault_count,
+            perf.paged_pool_pages,
+            perf.non_paged_pool_pages,
+            perf.resident_available_pages,
+            perf.shared_committed_pages,
+        )?;
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpProcessVmCounters {
+    const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::ProcessVmCountersStream;
+
+    fn read(
+        bytes: &[u8],
+        _all: &[u8],
+        endian: scroll::Endian,
+    ) -> Result<MinidumpProcessVmCounters, Error> {
+        // Like the misc info, read the largest known struct that fits.
+        let raw = if bytes.len() >= md::MINIDUMP_PROCESS_VM_COUNTERS_2::size_with(&endian) {
+            RawProcessVmCounters::VmCounters2(
+                bytes
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?,
+            )
+        } else {
+            RawProcessVmCounters::VmCounters1(
+                bytes
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?,
+            )
+        };
+        Ok(MinidumpProcessVmCounters { raw })
+    }
+}
+
+// Generates accessors for MINIDUMP_PROCESS_VM_COUNTERS* fields:
+//
+// * 1 FLAG: FIELD_NAME -> FIELD_TYPE
+// * 2 FLAG: FIELD_NAME -> FIELD_TYPE
+//
+// Fields marked 1 are present in both versions, fields marked 2 only in
+// MINIDUMP_PROCESS_VM_COUNTERS_2. In MINIDUMP_PROCESS_VM_COUNTERS_2 a field is
+// only valid if its group's ProcessVmCountersFlags FLAG is set, otherwise None
+// is yielded.
+macro_rules! vm_counters_accessors {
+    () => {};
+    ($(#[$attr:meta])* 1 $flag:ident: $name:ident -> $t:ty, $($rest:tt)*) => {
+        $(#[$attr])*
+        pub fn $name(&self) -> Option<$t> {
+            match self.raw {
+                RawProcessVmCounters::VmCounters1(ref raw) => Some(raw.$name),
+                RawProcessVmCounters::VmCounters2(ref raw) => {
+                    vm_counters_accessors!(@flagged raw $flag $name)
+                }
+            }
+        }
+        vm_counters_accessors!($($rest)*);
+    };
+    ($(#[$attr:meta])* 2 $flag:ident: $name:ident -> $t:ty, $($rest:tt)*) => {
+        $(#[$attr])*
+        pub fn $name(&self) -> Option<$t> {
+            match self.raw {
+                RawProcessVmCounters::VmCounters1(_) => None,
+                RawProcessVmCounters::VmCounters2(ref raw) => {
+                    vm_counters_accessors!(@flagged raw $flag $name)
+                }
+            }
+        }
+        vm_counters_accessors!($($rest)*);
+    };
+    (@flagged $raw:ident $flag:ident $name:ident) => {
+        if md::ProcessVmCountersFlags::from_bits_truncate($raw.flags)
+            .contains(md::ProcessVmCountersFlags::$flag)
+        {
+            Some($raw.$name)
+        } else {
+            None
+        }
+    };
+}
+
+impl MinidumpProcessVmCounters {
+    vm_counters_accessors! {
+        /// The number of page faults the process has had.
+        1 MINIDUMP_PROCESS_VM_COUNTERS: page_fault_count -> u32,
+        /// The largest the process's working set (its memory that was in RAM)
+        /// ever got, in bytes.
+        1 MINIDUMP_PROCESS_VM_COUNTERS: peak_working_set_size -> u64,
+        /// The size of the process's working set (its memory that was in RAM), in bytes.
+        1 MINIDUMP_PROCESS_VM_COUNTERS: working_set_size -> u64,
+        /// The process's commit charge, in bytes.
+        1 MINIDUMP_PROCESS_VM_COUNTERS: pagefile_usage -> u64,
+        /// The highest the process's commit charge ever was, in bytes.
+        1 MINIDUMP_PROCESS_VM_COUNTERS: peak_pagefile_usage -> u64,
+        /// The largest the virtual memory size of the process ever got, in bytes.
+        2 MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE: peak_virtual_size -> u64,
+        /// The virtual memory size of the process, in bytes.
+        2 MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE: virtual_size -> u64,
+        /// The memory the process has committed that can't be shared with other
+        /// processes ("private bytes"), in bytes.
+        1 MINIDUMP_PROCESS_VM_COUNTERS_EX: private_usage -> u64,
+        /// The part of the process's working set that can't be shared with other
+        /// processes, in bytes.
+        2 MINIDUMP_PROCESS_VM_COUNTERS_EX2: private_working_set_size -> u64,
+        /// The memory the process has committed that is shared with other
+        /// processes, in bytes.
+        2 MINIDUMP_PROCESS_VM_COUNTERS_EX2: shared_commit_usage -> u64,
+        /// The commit charge of the process's job, in bytes.
+        2 MINIDUMP_PROCESS_VM_COUNTERS_JOB: job_private_commit_usage -> u64,
+        /// The most memory the process's job can commit, in bytes.
+        2 MINIDUMP_PROCESS_VM_COUNTERS_JOB: job_private_commit_limit -> u64,
+    }
+
+    /// Write a human-readable description of this `MinidumpProcessVmCounters` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        match self.raw {
+            RawProcessVmCounters::VmCounters1(ref raw) => write!(
+                f,
+                "MINIDUMP_PROCESS_VM_COUNTERS_1
+  revision                        = {}
+  page_fault_count                = {}
+  peak_working_set_size           = {:#x}
+  working_set_size                = {:#x}
+  quota_peak_paged_pool_usage     = {:#x}
+  quota_paged_pool_usage          = {:#x}
+  quota_peak_non_paged_pool_usage = {:#x}
+  quota_non_paged_pool_usage      = {:#x}
+  pagefile_usage                  = {:#x}
+  peak_pagefile_usage             = {:#x}
+  private_usage                   = {:#x}
+
+",
+                raw.revision,
+                raw.page_fault_count,
+                raw.peak_working_set_size,
+                raw.working_set_size,
+                raw.quota_peak_paged_pool_usage,
+                raw.quota_paged_pool_usage,
+                raw.quota_peak_non_paged_pool_usage,
+                raw.quota_non_paged_pool_usage,
+                raw.pagefile_usage,
+                raw.peak_pagefile_usage,
+                raw.private_usage,
+            )?,
+            RawProcessVmCounters::VmCounters2(ref raw) => write!(
+                f,
+                "MINIDUMP_PROCESS_VM_COUNTERS_2
+  revision                        = {}
+  flags                           = {:#x}
+  page_fault_count                = {}
+  peak_working_set_size           = {:#x}
+  working_set_size                = {:#x}
+  quota_peak_paged_pool_usage     = {:#x}
+  quota_paged_pool_usage          = {:#x}
+  quota_peak_non_paged_pool_usage = {:#x}
+  quota_non_paged_pool_usage      = {:#x}
+  pagefile_usage                  = {:#x}
+  peak_pagefile_usage             = {:#x}
+  peak_virtual_size               = {:#x}
+  virtual_size                    = {:#x}
+  private_usage                   = {:#x}
+  private_working_set_size        = {:#x}
+  shared_commit_usage             = {:#x}
+  job_shared_commit_usage         = {:#x}
+  job_private_commit_usage        = {:#x}
+  job_peak_private_commit_usage   = {:#x}
+  job_private_commit_limit        = {:#x}
+  job_total_commit_limit          = {:#x}
+
+",
+                raw.revision,
+                raw.flags,
+                raw.page_fault_count,
+                raw.peak_working_set_size,
+                raw.working_set_size,
+                raw.quota_peak_paged_pool_usage,
+                raw.quota_paged_pool_usage,
+                raw.quota_peak_non_paged_pool_usage,
+                raw.quota_non_paged_pool_usage,
+                raw.pagefile_usage,
+                raw.peak_pagefile_usage,
+                raw.peak_virtual_size,
+                raw.virtual_size,
+                raw.private_usage,
+                raw.private_working_set_size,
+                raw.shared_commit_usage,
+                raw.job_shared_commit_usage,
+                raw.job_private_commit_usage,
+                raw.job_peak_private_commit_usage,
+                raw.job_private_commit_limit,
+                raw.job_total_commit_limit,
+            )?,
+        }
+        Ok(())
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpBreakpadInfo {
     const STREAM_TYPE: MINIDUMP_STREAM_TYPE = MINIDUMP_STREAM_TYPE::BreakpadInfoStream;
 
@@ -3442,6 +6463,13 @@ impl CrashReason {
                 }
             },
             ExceptionCodeMac::EXC_ARITHMETIC => match cpu {
+                Cpu::Arm64 => {
+                    if let Some(ty) =
+                        md::ExceptionCodeMacArithmeticArmType::from_u32(exception_flags)
+                    {
+                        reason = CrashReason::MacArithmeticArm(ty);
+                    }
+                }
                 Cpu::Ppc => {
                     if let Some(ty) =
                         md::ExceptionCodeMacArithmeticPpcType::from_u32(exception_flags)
@@ -3505,6 +6533,13 @@ impl CrashReason {
                     reason = CrashReason::MacGuard(ty, info[1], info[2]);
                 }
             }
+            ExceptionCodeMac::EXC_CRASH => {
+                // The code packs the signal into bits 24-31 and the
+                // original exception type into bits 20-23.
+                let exception = (exception_flags >> 20) & 0xf;
+                let signal = (exception_flags >> 24) & 0xff;
+                reason = CrashReason::MacCrash(exception, signal);
+            }
             _ => {
                 // Do nothing
             }
@@ -3546,10 +6581,29 @@ impl CrashReason {
                     reason = CrashReason::LinuxSigbus(ty);
                 }
             }
+            ExceptionCodeLinux::SIGTRAP => {
+                if let Some(ty) = md::ExceptionCodeLinuxSigtrapKind::from_u32(exception_flags) {
+                    reason = CrashReason::LinuxSigtrap(ty);
+                }
+            }
+            ExceptionCodeLinux::SIGSYS => {
+                if let Some(ty) = md::ExceptionCodeLinuxSigsysKind::from_u32(exception_flags) {
+                    reason = CrashReason::LinuxSigsys(ty);
+                }
+            }
             _ => {
                 // No refinements
             }
         }
+        // Otherwise see if it's a code any signal can have, like SI_TKILL
+        // for a raise() or SI_QUEUE for a sigqueue().
+        if let CrashReason::LinuxGeneral(signal, flags) = reason {
+            if signal != ExceptionCodeLinux::DUMP_REQUESTED {
+                if let Some(code) = md::ExceptionCodeLinuxSicode::from_u32(flags) {
+                    reason = CrashReason::LinuxSignal(signal, code);
+                }
+            }
+        }
         Some(reason)
     }
 }
@@ -3558,9 +6612,9 @@ impl fmt::Display for CrashReason {
     /// A string describing the crash reason.
     ///
     /// This is OS- and possibly CPU-specific.
-    /// For example, "EXCEPTION_ACCESS_VIOLATION" (Windows),
-    /// "EXC_BAD_ACCESS / KERN_INVALID_ADDRESS" (Mac OS X), "SIGSEGV"
-    /// (other Unix).
+    /// For example, "EXCEPTION_ACCESS_VIOLATION_WRITE" (Windows),
+    /// "EXC_BAD_ACCESS / KERN_INVALID_ADDRESS" (Mac OS X), "SIGSEGV / SEGV_MAPERR"
+    /// (Linux).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use CrashReason::*;
 
@@ -3569,7 +6623,7 @@ impl fmt::Display for CrashReason {
             if let Some(nt_status) = nt_status {
                 write!(f, "{:?}", nt_status)
             } else {
-                write!(f, "0x{:08}", raw_nt_status)
+                write!(f, "0x{:08x}", raw_nt_status)
             }
         }
 
@@ -3578,7 +6632,47 @@ impl fmt::Display for CrashReason {
             if let Some(fast_fail) = fast_fail {
                 write!(f, "{:?}", fast_fail)
             } else {
-                write!(f, "0x{:08}", raw_fast_fail)
+                write!(f, "0x{:08x}", raw_fast_fail)
+            }
+        }
+
+        // Break an NTSTATUS-style code we don't know down into its parts.
+        fn write_unknown_windows_code(f: &mut fmt::Formatter<'_>, code: u32) -> fmt::Result {
+            write!(f, "unknown 0x{:08x}", code)?;
+            let severity = md::NtStatusSeverity::from_u32(code >> 30).unwrap();
+            let customer = code & 0x20000000 != 0;
+            let facility = (code >> 16) & 0xfff;
+            if severity == md::NtStatusSeverity::STATUS_SEVERITY_SUCCESS
+                && !customer
+                && facility == 0
+            {
+                // Probably not an NTSTATUS at all, e.g. a plain winerror.h code.
+                return Ok(());
+            }
+            write!(f, " / {:?}", severity)?;
+            if customer {
+                write!(f, " / customer facility 0x{:03x}", facility)?;
+            } else if let Some(facility) = md::NtStatusFacility::from_u32(facility) {
+                write!(f, " / {:?}", facility)?;
+            } else {
+                write!(f, " / facility 0x{:03x}", facility)?;
+            }
+            write!(f, " / code 0x{:04x}", code & 0xffff)
+        }
+
+        fn write_exc_crash(f: &mut fmt::Formatter<'_>, exception: u32, signal: u32) -> fmt::Result {
+            write!(f, "EXC_CRASH")?;
+            if exception != 0 {
+                if let Some(exception) = md::ExceptionCodeMac::from_u32(exception) {
+                    write!(f, " / {:?}", exception)?;
+                } else {
+                    write!(f, " / 0x{:x}", exception)?;
+                }
+            }
+            if let Some(signal) = md::ExceptionCodeMacSignal::from_u32(signal) {
+                write!(f, " / {:?}", signal)
+            } else {
+                write!(f, " / signal {}", signal)
             }
         }
 
@@ -3604,7 +6698,7 @@ impl fmt::Display for CrashReason {
                             cpu_flavor, interval, cpu_limit, cpu_consumed
                         )
                     } else {
-                        write!(f, "0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, "0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacResourceType::RESOURCE_TYPE_WAKEUPS => {
@@ -3620,7 +6714,7 @@ impl fmt::Display for CrashReason {
                             wakeups_flavor, interval, wakeups_permitted, wakeups_observed
                         )
                     } else {
-                        write!(f, "0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, "0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacResourceType::RESOURCE_TYPE_MEMORY => {
@@ -3634,7 +6728,7 @@ impl fmt::Display for CrashReason {
                             memory_flavor, hwm_limit
                         )
                     } else {
-                        write!(f, "0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, "0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacResourceType::RESOURCE_TYPE_IO => {
@@ -3649,7 +6743,7 @@ impl fmt::Display for CrashReason {
                             io_flavor, interval, io_limit, io_observed
                         )
                     } else {
-                        write!(f, "0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, "0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacResourceType::RESOURCE_TYPE_THREADS => {
@@ -3663,7 +6757,7 @@ impl fmt::Display for CrashReason {
                             threads_flavor, hwm_limit
                         )
                     } else {
-                        write!(f, "0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, "0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
             }
@@ -3692,7 +6786,7 @@ impl fmt::Display for CrashReason {
                             mach_port_flavor, port_name, subcode,
                         )
                     } else {
-                        write!(f, " / 0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, " / 0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacGuardType::GUARD_TYPE_FD => {
@@ -3704,14 +6798,14 @@ impl fmt::Display for CrashReason {
                             fd_flavor, fd, subcode,
                         )
                     } else {
-                        write!(f, " / 0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, " / 0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacGuardType::GUARD_TYPE_USER => {
                     let namespace = code & 0xffffffff;
                     write!(
                         f,
-                        "/ namespace: {} guard identifier: {}",
+                        " / namespace: {} guard identifier: {}",
                         namespace, subcode,
                     )
                 }
@@ -3724,7 +6818,7 @@ impl fmt::Display for CrashReason {
                             vn_flavor, pid, subcode,
                         )
                     } else {
-                        write!(f, " / 0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, " / 0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
                 md::ExceptionCodeMacGuardType::GUARD_TYPE_VIRT_MEMORY => {
@@ -3733,7 +6827,7 @@ impl fmt::Display for CrashReason {
                     {
                         write!(f, " / {:?} offset: {}", virt_memory_flavor, subcode)
                     } else {
-                        write!(f, " / 0x{:016} / 0x{:016}", code, subcode)
+                        write!(f, " / 0x{:016x} / 0x{:016x}", code, subcode)
                     }
                 }
             }
@@ -3757,6 +6851,7 @@ impl fmt::Display for CrashReason {
             MacBadInstructionArm(ex) => write!(f, "EXC_BAD_INSTRUCTION / {:?}", ex),
             MacBadInstructionPpc(ex) => write!(f, "EXC_BAD_INSTRUCTION / {:?}", ex),
             MacBadInstructionX86(ex) => write!(f, "EXC_BAD_INSTRUCTION / {:?}", ex),
+            MacArithmeticArm(ex) => write!(f, "EXC_ARITHMETIC / {:?}", ex),
             MacArithmeticPpc(ex) => write!(f, "EXC_ARITHMETIC / {:?}", ex),
             MacArithmeticX86(ex) => write!(f, "EXC_ARITHMETIC / {:?}", ex),
             MacSoftware(ex) => write!(f, "EXC_SOFTWARE / {:?}", ex),
@@ -3765,6 +6860,7 @@ impl fmt::Display for CrashReason {
             MacBreakpointX86(ex) => write!(f, "EXC_BREAKPOINT / {:?}", ex),
             MacResource(ex, code, subcode) => write_exc_resource(f, ex, code, subcode),
             MacGuard(ex, code, subcode) => write_exc_guard(f, ex, code, subcode),
+            MacCrash(exception, signal) => write_exc_crash(f, exception, signal),
 
             // ===================== Linux/Android =========================
 
@@ -3774,6 +6870,9 @@ impl fmt::Display for CrashReason {
             LinuxSigbus(ex) => write!(f, "SIGBUS / {:?}", ex),
             LinuxSigfpe(ex) => write!(f, "SIGFPE / {:?}", ex),
             LinuxSigsegv(ex) => write!(f, "SIGSEGV / {:?}", ex),
+            LinuxSigtrap(ex) => write!(f, "SIGTRAP / {:?}", ex),
+            LinuxSigsys(ex) => write!(f, "SIGSYS / {:?}", ex),
+            LinuxSignal(signal, code) => write!(f, "{:?} / {:?}", signal, code),
 
             // ======================== Windows =============================
 
@@ -3796,9 +6895,9 @@ impl fmt::Display for CrashReason {
                 write!(f, "EXCEPTION_STACK_BUFFER_OVERRUN / ")?;
                 write_fast_fail(f, fast_fail)
             }
-            WindowsUnknown(code) => write!(f, "unknown 0x{:08}", code),
+            WindowsUnknown(code) => write_unknown_windows_code(f, code),
 
-            Unknown(code, flags) => write!(f, "unknown 0x{:08} / 0x{:08}", code, flags),
+            Unknown(code, flags) => write!(f, "unknown 0x{:08x} / 0x{:08x}", code, flags),
         }
     }
 }
@@ -3842,6 +6941,16 @@ impl<'a> MinidumpException<'a> {
             .map(Cow::Owned)
     }
 
+    /// Check that the crashing thread's context is for the CPU that `system_info` says.
+    ///
+    /// See [`MinidumpContext::check_cpu`][].
+    pub fn check_context_cpu(&self, system_info: &MinidumpSystemInfo) -> Result<(), ContextError> {
+        match self.context {
+            Some(bytes) => MinidumpContext::check_cpu(bytes, self.endian, system_info),
+            None => Ok(()),
+        }
+    }
+
     /// Get the address that "caused" the crash.
     ///
     /// The meaning of this value depends on the kind of crash this was.
@@ -3853,17 +6962,26 @@ impl<'a> MinidumpException<'a> {
     /// So for instance, if you crashed from dereferencing a null pointer,
     /// the crash_address will be 0 (or close to it, due to offsets).
     pub fn get_crash_address(&self, os: Os, cpu: Cpu) -> u64 {
-        let addr = match (
-            os,
-            md::ExceptionCodeWindows::from_u32(self.raw.exception_record.exception_code),
-        ) {
-            (Os::Windows, Some(md::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION))
-            | (Os::Windows, Some(md::ExceptionCodeWindows::EXCEPTION_IN_PAGE_ERROR))
-                if self.raw.exception_record.number_parameters >= 2 =>
+        let record = &self.raw.exception_record;
+        let addr = match (os, record.exception_code) {
+            (Os::Windows, code)
+                if (code == md::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32
+                    || code == md::ExceptionCodeWindows::EXCEPTION_IN_PAGE_ERROR as u32)
+                    && record.number_parameters >= 2 =>
             {
-                self.raw.exception_record.exception_information[1]
+                record.exception_information[1]
             }
-            _ => self.raw.exception_record.exception_address,
+            // The address is the subcode (code[1]) of a Mach exception. That's
+            // supposed to be in exception_address, but not every writer puts it
+            // there, so fall back to the raw exception type, code and subcode.
+            (Os::MacOs | Os::Ios, code)
+                if code == md::ExceptionCodeMac::EXC_BAD_ACCESS as u32
+                    && record.exception_address == 0
+                    && record.number_parameters >= 3 =>
+            {
+                record.exception_information[2]
+            }
+            _ => record.exception_address,
         };
 
         // Sometimes on 32-bit these values can be incorrectly sign-extended,
@@ -3895,17 +7013,62 @@ impl<'a> MinidumpException<'a> {
         self.thread_id
     }
 
-    /// Write a human-readable description of this `MinidumpException` to `f`.
+    /// Get the message that was raised along with this exception, if it has one.
     ///
-    /// This is very verbose, it is the format used by `minidump_dump`.
-    pub fn print<T: Write>(
-        &self,
-        f: &mut T,
-        system: Option<&MinidumpSystemInfo>,
-        misc: Option<&MinidumpMiscInfo>,
-    ) -> io::Result<()> {
-        write!(
-            f,
+    /// On Windows, `OutputDebugString` raises a `DBG_PRINTEXCEPTION_C` (or
+    /// `DBG_PRINTEXCEPTION_WIDE_C`) exception that points at its message, and
+    /// some programs call it right before they crash to say why. The message
+    /// is read from `memory`, so this returns `None` if the minidump doesn't
+    /// include the memory it's in.
+    pub fn get_message(&self, os: Os, memory: &UnifiedMemoryList<'a>) -> Option<String> {
+        // Messages are supposed to be short, don't trust bogus lengths.
+        const MAX_MESSAGE_LEN: u64 = 0x1000;
+
+        if os != Os::Windows {
+            return None;
+        }
+        let record = &self.raw.exception_record;
+        let count = (record.number_parameters as usize).min(record.exception_information.len());
+        let params = &record.exception_information[..count];
+        // The lengths include the trailing NUL.
+        let read_string = |len: u64, addr: u64| {
+            memory
+                .memory_at_address(addr)?
+                .read_cstring(addr, len.min(MAX_MESSAGE_LEN) as usize)
+                .map(Cow::into_owned)
+        };
+        let read_utf16_string = |len: u64, addr: u64| {
+            memory
+                .memory_at_address(addr)?
+                .read_utf16_string(addr, len.min(MAX_MESSAGE_LEN) as usize)
+        };
+        match (md::NtStatusWindows::from_u32(record.exception_code), params) {
+            (Some(md::NtStatusWindows::DBG_PRINTEXCEPTION_C), &[len, addr, ..]) => {
+                read_string(len, addr)
+            }
+            // This also has the message converted to the current code page,
+            // which is all that's left if the original isn't in the minidump.
+            (Some(md::NtStatusWindows::DBG_PRINTEXCEPTION_WIDE_C), &[len, addr, ..]) => {
+                read_utf16_string(len, addr).or_else(|| match *params {
+                    [_, _, ansi_len, ansi_addr, ..] => read_string(ansi_len, ansi_addr),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Write a human-readable description of this `MinidumpException` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(
+        &self,
+        f: &mut T,
+        system: Option<&MinidumpSystemInfo>,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> io::Result<()> {
+        write!(
+            f,
             "MINIDUMP_EXCEPTION
   thread_id                                  = {:#x}
   exception_record.exception_code            = {:#x}
@@ -3975,26 +7138,45 @@ impl<'a> MinidumpStream<'a> for MinidumpAssertion {
 }
 
 fn utf16_to_string(data: &[u16]) -> Option<String> {
-    use std::slice;
-
+    // The code units were already read in the minidump's byte order.
     let len = data.iter().take_while(|c| **c != 0).count();
-    let s16 = &data[..len];
-    let bytes = unsafe { slice::from_raw_parts(s16.as_ptr() as *const u8, s16.len() * 2) };
-    UTF_16LE.decode(bytes, DecoderTrap::Strict).ok()
+    String::from_utf16(&data[..len]).ok()
+}
+
+/// Like `utf16_to_string`, but an empty (all-zero) buffer is `None`.
+fn non_empty_utf16_to_string(data: &[u16]) -> Option<String> {
+    utf16_to_string(data).filter(|string| !string.is_empty())
 }
 
 impl MinidumpAssertion {
     /// Get the assertion expression as a `String` if one exists.
     pub fn expression(&self) -> Option<String> {
-        utf16_to_string(&self.raw.expression)
+        non_empty_utf16_to_string(&self.raw.expression)
     }
     /// Get the function name where the assertion happened as a `String` if it exists.
     pub fn function(&self) -> Option<String> {
-        utf16_to_string(&self.raw.function)
+        non_empty_utf16_to_string(&self.raw.function)
     }
     /// Get the source file name where the assertion happened as a `String` if it exists.
     pub fn file(&self) -> Option<String> {
-        utf16_to_string(&self.raw.file)
+        non_empty_utf16_to_string(&self.raw.file)
+    }
+    /// Get the line number where the assertion happened if it's known.
+    pub fn line(&self) -> Option<u32> {
+        Some(self.raw.line).filter(|line| *line != 0)
+    }
+    /// Get the kind of assertion, if it's one we know.
+    pub fn assertion_type(&self) -> Option<md::AssertionType> {
+        md::AssertionType::from_u32(self.raw._type)
+    }
+    /// Whether the stream is all zeros, which is how a minidump says there was
+    /// no assertion.
+    pub fn is_empty(&self) -> bool {
+        self.expression().is_none()
+            && self.function().is_none()
+            && self.file().is_none()
+            && self.raw.line == 0
+            && self.raw._type == 0
     }
 
     /// Write a human-readable description of this `MinidumpAssertion` to `f`.
@@ -4223,7 +7405,29 @@ impl<'a> MinidumpStream<'a> for MinidumpCrashpadInfo {
     }
 }
 
+/// Convert a Crashpad `UUID`, where all zeroes means there isn't one.
+fn crashpad_uuid(guid: &md::GUID) -> Option<Uuid> {
+    let uuid = Uuid::from_fields(guid.data1, guid.data2, guid.data3, &guid.data4);
+    if uuid.is_nil() {
+        None
+    } else {
+        Some(uuid)
+    }
+}
+
 impl MinidumpCrashpadInfo {
+    /// The ID Crashpad assigned to this crash report, if it assigned one.
+    pub fn report_id(&self) -> Option<Uuid> {
+        crashpad_uuid(&self.raw.report_id)
+    }
+
+    /// The ID of the Crashpad client that crashed, if it has one.
+    ///
+    /// This stays the same across all the crashes of one installation.
+    pub fn client_id(&self) -> Option<Uuid> {
+        crashpad_uuid(&self.raw.client_id)
+    }
+
     /// Write a human-readable description of this `MinidumpCrashpadInfo` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -4306,6 +7510,346 @@ impl<'a> Minidump<'a, Mmap> {
     }
 }
 
+/// A minidump that is read from on demand, instead of all being held in memory.
+///
+/// Full-memory dumps can be tens of gigabytes, almost all of which is the
+/// contents of the [`MinidumpMemory64List`][]. A `StreamingMinidump` only
+/// reads the header and stream directory up front. Everything *but* the
+/// bulk memory of a full-memory dump is loaded the first time a stream is
+/// requested, and the memory itself is only read in pieces by
+/// [`StreamingMinidump::read_memory64`][].
+///
+/// For dumps without a [`MinidumpMemory64List`][] this ends up loading the
+/// whole file, so [`Minidump::read_path`][] is the better choice for them.
+///
+/// To process a `StreamingMinidump` (or do anything else that needs a
+/// [`Minidump`][]), use [`StreamingMinidump::to_minidump`][], which only
+/// loads the threads' stacks out of the bulk memory.
+///
+/// # Examples
+///
+/// ```
+/// use minidump::*;
+///
+/// # fn foo() -> Result<(), Error> {
+/// let dump = StreamingMinidump::open("../testdata/test.dmp")?;
+/// let system_info = dump.get_stream::<MinidumpSystemInfo>()?;
+///
+/// let dump = dump.to_minidump()?;
+/// let memory = dump.memory()?;
+/// let threads = dump.get_stream::<MinidumpThreadList>()?;
+/// let stack = threads.threads[0].stack_memory(&memory);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamingMinidump<R> {
+    reader: RefCell<R>,
+    /// The raw minidump header from the file.
+    pub header: md::MINIDUMP_HEADER,
+    streams: HashMap<u32, (u32, md::MINIDUMP_DIRECTORY)>,
+    /// The endianness of this minidump file.
+    pub endian: scroll::Endian,
+    /// The size of the whole file.
+    file_size: u64,
+    /// Everything in the file before the bulk memory of a full-memory dump.
+    metadata: OnceCell<Vec<u8>>,
+}
+
+impl StreamingMinidump<BufReader<File>> {
+    /// Open a minidump on disk for streaming, rather than mapping it into memory.
+    pub fn open<P>(path: P) -> Result<StreamingMinidump<BufReader<File>>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let f = File::open(path).or(Err(Error::FileNotFound))?;
+        StreamingMinidump::read(BufReader::new(f))
+    }
+}
+
+impl<R> StreamingMinidump<R>
+where
+    R: Read + Seek,
+{
+    /// Read the header and stream directory of a minidump from `reader`.
+    pub fn read(mut reader: R) -> Result<StreamingMinidump<R>, Error> {
+        let file_size = reader.seek(SeekFrom::End(0)).or(Err(Error::IoError))?;
+
+        let header_size = <md::MINIDUMP_HEADER>::size_with(&LE);
+        let header_bytes =
+            read_at(&mut reader, 0, header_size, file_size).or(Err(Error::MissingHeader))?;
+        let (header, endian) = read_header(&header_bytes)?;
+
+        let directory_size = (header.stream_count as usize)
+            .checked_mul(<md::MINIDUMP_DIRECTORY>::size_with(&endian))
+            .ok_or(Error::MissingDirectory)?;
+        let directory_bytes = read_at(
+            &mut reader,
+            header.stream_directory_rva as u64,
+            directory_size,
+            file_size,
+        )
+        .or(Err(Error::MissingDirectory))?;
+        let (streams, _) = index_directory(read_directory(
+            &directory_bytes,
+            0,
+            header.stream_count,
+            endian,
+        )?);
+
+        Ok(StreamingMinidump {
+            reader: RefCell::new(reader),
+            header,
+            streams,
+            endian,
+            file_size,
+            metadata: OnceCell::new(),
+        })
+    }
+
+    /// Read and parse the specified [`MinidumpStream`][] `S` from the minidump, if it exists.
+    ///
+    /// This works like [`Minidump::get_stream`][], except that the
+    /// [`MinidumpMemory64List`][] it returns is missing the regions whose
+    /// contents weren't loaded (normally all of them). Use
+    /// [`StreamingMinidump::read_memory64`][] to read those instead, or
+    /// [`StreamingMinidump::to_minidump`][] to get the stacks.
+    pub fn get_stream<'a, S>(&'a self) -> Result<S, Error>
+    where
+        S: MinidumpStream<'a>,
+    {
+        let dir = self.directory(S::STREAM_TYPE.into())?;
+        let all_bytes = self.metadata()?;
+        let bytes = location_slice(all_bytes, &dir.location)?;
+        let system_info = self
+            .directory(MINIDUMP_STREAM_TYPE::SystemInfoStream.into())
+            .and_then(|dir| location_slice(all_bytes, &dir.location))
+            .and_then(|bytes| MinidumpSystemInfo::read(bytes, all_bytes, self.endian))
+            .ok();
+        S::read_with_system_info(bytes, all_bytes, self.endian, system_info.as_ref())
+    }
+
+    /// Read a stream of raw data from the minidump.
+    ///
+    /// This can be used to get the contents of arbitrary minidump streams.
+    /// Unlike [`StreamingMinidump::get_stream`][], this only reads the stream itself.
+    pub fn get_raw_stream<S>(&self, stream_type: S) -> Result<Vec<u8>, Error>
+    where
+        S: Into<u32>,
+    {
+        let dir = self.directory(stream_type.into())?;
+        read_at(
+            &mut *self.reader.borrow_mut(),
+            dir.location.rva as u64,
+            dir.location.data_size as usize,
+            self.file_size,
+        )
+    }
+
+    /// Read the `size` bytes of process memory starting at `address` from the
+    /// [`MinidumpMemory64List`][], if they were all captured in one region.
+    pub fn read_memory64(&self, address: u64, size: usize) -> Result<Vec<u8>, Error> {
+        let bytes = self.get_raw_stream(MINIDUMP_STREAM_TYPE::Memory64ListStream)?;
+        let end = address
+            .checked_add(size as u64)
+            .ok_or(Error::StreamReadFailure)?;
+        for (desc, rva) in read_memory64_descriptors(&bytes, self.endian)? {
+            let region_end = match desc.start_of_memory_range.checked_add(desc.data_size) {
+                Some(region_end) => region_end,
+                None => continue,
+            };
+            if desc.start_of_memory_range <= address && end <= region_end {
+                let offset = rva
+                    .checked_add(address - desc.start_of_memory_range)
+                    .ok_or(Error::StreamReadFailure)?;
+                return read_at(&mut *self.reader.borrow_mut(), offset, size, self.file_size);
+            }
+        }
+        Err(Error::StreamReadFailure)
+    }
+
+    /// Load everything needed to process this dump into an in-memory [`Minidump`][].
+    ///
+    /// That's every stream, but of the bulk memory of a full-memory dump only
+    /// the threads' stacks. The result can be used with anything that takes a
+    /// `Minidump` (like `minidump_processor::process_minidump`), and is usually
+    /// a tiny fraction of the size of the file.
+    ///
+    /// The stacks make up the result's [`MinidumpMemory64List`][], and the
+    /// threads' stack descriptors are pointed at them.
+    pub fn to_minidump(&self) -> Result<Minidump<'static, Vec<u8>>, Error> {
+        let mut data = self.metadata()?.to_vec();
+        let endian = self.endian;
+        let memory64 = match self.get_raw_stream(MINIDUMP_STREAM_TYPE::Memory64ListStream) {
+            Ok(bytes) => read_memory64_descriptors(&bytes, endian)?,
+            // Without bulk memory the metadata is the whole file.
+            Err(_) => return Minidump::read(data),
+        };
+
+        // Load the stacks that weren't loaded with the metadata, remembering
+        // where the threads they belong to are.
+        let mut stacks = Vec::new();
+        let thread_list = self.directory(MINIDUMP_STREAM_TYPE::ThreadListStream.into());
+        let threads = thread_list.and_then(|dir| {
+            let bytes = location_slice(&data, &dir.location)?;
+            let threads: Vec<md::MINIDUMP_THREAD> = read_stream_list(&mut 0, bytes, endian)?;
+            // Any padding comes before the entries.
+            let entry_size = <md::MINIDUMP_THREAD>::size_with(&endian);
+            let first_entry = dir.location.rva as usize + bytes.len() - threads.len() * entry_size;
+            Ok((threads, first_entry, entry_size))
+        });
+        if let Ok((threads, first_entry, entry_size)) = threads {
+            for (i, thread) in threads.into_iter().enumerate() {
+                if MinidumpMemory::read(&thread.stack, &data, endian).is_ok() {
+                    continue;
+                }
+                let start = thread.stack.start_of_memory_range;
+                let region = memory64.iter().find(|(desc, _)| {
+                    desc.start_of_memory_range <= start
+                        && start - desc.start_of_memory_range < desc.data_size
+                });
+                let (desc, rva) = match region {
+                    Some(region) => region,
+                    None => continue,
+                };
+                // Take the same part of the region as `MinidumpThread::stack_memory` would.
+                let offset = start - desc.start_of_memory_range;
+                let available = desc.data_size - offset;
+                let size = match thread.stack.memory.data_size as u64 {
+                    0 => available.min(u32::MAX as u64),
+                    size => size.min(available),
+                };
+                let bytes = match rva.checked_add(offset) {
+                    Some(rva) => read_at(
+                        &mut *self.reader.borrow_mut(),
+                        rva,
+                        size as usize,
+                        self.file_size,
+                    ),
+                    None => continue,
+                };
+                if let Ok(bytes) = bytes {
+                    stacks.push((first_entry + i * entry_size, thread, bytes));
+                }
+            }
+        }
+
+        // Append the new Memory64List, followed by the contents of its regions.
+        let padding = (8 - data.len() % 8) % 8;
+        data.resize(data.len() + padding, 0);
+        let to_rva = |offset: usize| u32::try_from(offset).or(Err(Error::WriteFailure));
+        let list = md::MINIDUMP_LOCATION_DESCRIPTOR {
+            data_size: to_rva(
+                <md::MINIDUMP_MEMORY64_LIST>::size_with(&endian)
+                    + stacks.len() * <md::MINIDUMP_MEMORY_DESCRIPTOR64>::size_with(&endian),
+            )?,
+            rva: to_rva(data.len())?,
+        };
+        let header = md::MINIDUMP_MEMORY64_LIST {
+            number_of_memory_ranges: stacks.len() as u64,
+            base_rva: list.rva as u64 + list.data_size as u64,
+        };
+        push_struct(&mut data, header, endian)?;
+        for (_, thread, bytes) in &stacks {
+            let desc = md::MINIDUMP_MEMORY_DESCRIPTOR64 {
+                start_of_memory_range: thread.stack.start_of_memory_range,
+                data_size: bytes.len() as u64,
+            };
+            push_struct(&mut data, desc, endian)?;
+        }
+        for (entry, mut thread, bytes) in stacks {
+            thread.stack.memory = md::MINIDUMP_LOCATION_DESCRIPTOR {
+                data_size: to_rva(bytes.len())?,
+                rva: to_rva(data.len())?,
+            };
+            data.extend_from_slice(&bytes);
+            data.pwrite_with(thread, entry, endian)
+                .or(Err(Error::WriteFailure))?;
+        }
+
+        // And finally a directory pointing at it.
+        let mut streams = self.streams.values().collect::<Vec<_>>();
+        streams.sort_by_key(|&&(i, _)| i);
+        let directory_rva = to_rva(data.len())?;
+        for (_, dir) in &streams {
+            let mut dir = dir.clone();
+            if dir.stream_type == MINIDUMP_STREAM_TYPE::Memory64ListStream as u32 {
+                dir.location = list;
+            }
+            push_struct(&mut data, dir, endian)?;
+        }
+        let header = md::MINIDUMP_HEADER {
+            stream_count: streams.len() as u32,
+            stream_directory_rva: directory_rva,
+            ..self.header.clone()
+        };
+        data.pwrite_with(header, 0, endian)
+            .or(Err(Error::WriteFailure))?;
+
+        Minidump::read(data)
+    }
+
+    fn directory(&self, stream_type: u32) -> Result<&md::MINIDUMP_DIRECTORY, Error> {
+        self.streams
+            .get(&stream_type)
+            .map(|(_, dir)| dir)
+            .ok_or(Error::StreamNotFound)
+    }
+
+    /// Load everything but the bulk memory of a full-memory dump, if it isn't already.
+    fn metadata(&self) -> Result<&[u8], Error> {
+        if let Some(metadata) = self.metadata.get() {
+            return Ok(metadata);
+        }
+
+        // The memory of a full-memory dump is stored at the end of the file,
+        // after everything else, so only read up to it. If any other stream
+        // is after the memory (which shouldn't happen) read up to its end instead.
+        let mut end = self.file_size;
+        if let Ok(bytes) = self.get_raw_stream(MINIDUMP_STREAM_TYPE::Memory64ListStream) {
+            let header: md::MINIDUMP_MEMORY64_LIST = bytes
+                .pread_with(0, self.endian)
+                .or(Err(Error::StreamReadFailure))?;
+            end = self
+                .streams
+                .values()
+                .filter(|(_, dir)| {
+                    dir.stream_type != MINIDUMP_STREAM_TYPE::Memory64ListStream as u32
+                })
+                .map(|(_, dir)| dir.location.rva as u64 + dir.location.data_size as u64)
+                .fold(header.base_rva, u64::max)
+                .min(self.file_size);
+        }
+
+        let size = usize::try_from(end).or(Err(Error::IoError))?;
+        let metadata = read_at(&mut *self.reader.borrow_mut(), 0, size, self.file_size)?;
+        Ok(self.metadata.get_or_init(|| metadata))
+    }
+}
+
+/// Read exactly `size` bytes at `offset` of `reader`, which is `file_size` bytes long.
+fn read_at<R>(reader: &mut R, offset: u64, size: usize, file_size: u64) -> Result<Vec<u8>, Error>
+where
+    R: Read + Seek,
+{
+    // Check against the file size first so corrupt sizes can't make us allocate too much.
+    let end = offset
+        .checked_add(size as u64)
+        .ok_or(Error::StreamReadFailure)?;
+    if end > file_size {
+        return Err(Error::StreamSizeMismatch {
+            expected: end as usize,
+            actual: file_size as usize,
+        });
+    }
+    reader
+        .seek(SeekFrom::Start(offset))
+        .or(Err(Error::IoError))?;
+    let mut bytes = vec![0; size];
+    reader.read_exact(&mut bytes).or(Err(Error::IoError))?;
+    Ok(bytes)
+}
+
 /// A stream in the minidump that this implementation can interpret,
 #[derive(Debug)]
 pub struct MinidumpImplementedStream {
@@ -4314,6 +7858,22 @@ pub struct MinidumpImplementedStream {
     pub vendor: &'static str,
 }
 
+/// The type of a stream, which displays as its name.
+///
+/// Known streams use their [`MINIDUMP_STREAM_TYPE`][] name, and others say who
+/// they belong to (e.g. "unknown, Mozilla Extension").
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StreamTypeName(pub u32);
+
+impl fmt::Display for StreamTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match MINIDUMP_STREAM_TYPE::from_u32(self.0) {
+            Some(stream) => write!(f, "{:?}", stream),
+            None => write!(f, "unknown, {}", stream_vendor(self.0)),
+        }
+    }
+}
+
 /// A stream in the minidump that this implementation has no knowledge of.
 #[derive(Debug)]
 pub struct MinidumpUnknownStream {
@@ -4331,6 +7891,210 @@ pub struct MinidumpUnimplementedStream {
     pub vendor: &'static str,
 }
 
+/// Where a stream is in the minidump, whether or not we know what it is.
+///
+/// See [`Minidump::streams`][].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamDescriptor {
+    /// The raw stream type, which may be any of [`MINIDUMP_STREAM_TYPE`][] or
+    /// something vendor-specific.
+    pub stream_type: u32,
+    /// The size of the stream's contents, in bytes.
+    pub data_size: u32,
+    /// The offset of the stream's contents from the start of the minidump.
+    pub file_offset: u32,
+}
+
+/// A stream that the application that wrote the minidump added for its own use.
+///
+/// See [`Minidump::user_streams`][].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinidumpUserStream<'a> {
+    /// The raw stream type, which isn't any of [`MINIDUMP_STREAM_TYPE`][].
+    pub stream_type: u32,
+    /// The stream's contents.
+    pub data: &'a [u8],
+}
+
+/// Read the `MINIDUMP_HEADER` at the start of `data`, and work out the minidump's endianness.
+fn read_header(data: &[u8]) -> Result<(md::MINIDUMP_HEADER, scroll::Endian), Error> {
+    let mut endian = LE;
+    let mut header: md::MINIDUMP_HEADER =
+        data.pread_with(0, endian).or(Err(Error::MissingHeader))?;
+    if header.signature != md::MINIDUMP_SIGNATURE {
+        if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+        // Try again with big-endian.
+        endian = BE;
+        header = data.pread_with(0, endian).or(Err(Error::MissingHeader))?;
+        if header.signature != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+    }
+    if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
+        return Err(Error::VersionMismatch);
+    }
+    Ok((header, endian))
+}
+
+/// An entry of the stream directory, with its index.
+type DirectoryEntry = (u32, md::MINIDUMP_DIRECTORY);
+
+/// Read the `stream_count` entries of the stream directory at `offset` in `data`,
+/// along with their indices.
+fn read_directory(
+    data: &[u8],
+    mut offset: usize,
+    stream_count: u32,
+    endian: scroll::Endian,
+) -> Result<Vec<DirectoryEntry>, Error> {
+    let (count, _) = ensure_count_in_bound(
+        data,
+        stream_count as usize,
+        <md::MINIDUMP_DIRECTORY>::size_with(&endian),
+        offset,
+    )?;
+
+    let mut entries = Vec::with_capacity(count);
+    for i in 0..stream_count {
+        let dir: md::MINIDUMP_DIRECTORY = data
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::MissingDirectory))?;
+        entries.push((i, dir));
+    }
+    Ok(entries)
+}
+
+/// Index the entries of the stream directory by stream type.
+///
+/// If there are several streams of the same type the last one is used, like
+/// Breakpad does. The others are returned separately, in directory order.
+fn index_directory(
+    entries: Vec<DirectoryEntry>,
+) -> (HashMap<u32, DirectoryEntry>, Vec<DirectoryEntry>) {
+    let mut streams = HashMap::with_capacity(entries.len());
+    let mut shadowed = Vec::new();
+    for (i, dir) in entries {
+        if let Some((old_idx, old_dir)) = streams.insert(dir.stream_type, (i, dir.clone())) {
+            if let Some(known_stream_type) = MINIDUMP_STREAM_TYPE::from_u32(dir.stream_type) {
+                warn!("Minidump contains multiple streams of type {} ({:?}) at indices {} ({} bytes) and {} ({} bytes) (using {})",
+                    dir.stream_type,
+                    known_stream_type,
+                    old_idx,
+                    old_dir.location.data_size,
+                    i,
+                    dir.location.data_size,
+                    i,
+                );
+            } else {
+                warn!("Minidump contains multiple streams of unknown type {} at indices {} ({} bytes) and {} ({} bytes) (using {})",
+                    dir.stream_type,
+                    old_idx,
+                    old_dir.location.data_size,
+                    i,
+                    dir.location.data_size,
+                    i,
+                );
+            }
+            shadowed.push((old_idx, old_dir));
+        }
+    }
+    (streams, shadowed)
+}
+
+/// Shrink the streams in `entries` to the `file_size` bytes of the file, and drop
+/// those that start past its end.
+fn clamp_streams(
+    entries: &mut Vec<(u32, md::MINIDUMP_DIRECTORY)>,
+    file_size: usize,
+    warnings: &mut Vec<MinidumpWarning>,
+) {
+    entries.retain_mut(|(_, dir)| {
+        let location = &mut dir.location;
+        let start = location.rva as usize;
+        let end = start.saturating_add(location.data_size as usize);
+        if end <= file_size {
+            return true;
+        }
+        if start >= file_size {
+            warnings.push(MinidumpWarning::MissingStream {
+                stream_type: dir.stream_type,
+            });
+            return false;
+        }
+        let actual = (file_size - start) as u32;
+        warnings.push(MinidumpWarning::TruncatedStream {
+            stream_type: dir.stream_type,
+            expected: location.data_size,
+            actual,
+        });
+        location.data_size = actual;
+        true
+    });
+}
+
+/// Check which of the memory regions in `streams` are missing or cut short.
+///
+/// The regions themselves are still read, see [`MinidumpMemory::read`].
+fn memory_warnings(
+    data: &[u8],
+    streams: &HashMap<u32, (u32, md::MINIDUMP_DIRECTORY)>,
+    endian: scroll::Endian,
+) -> Vec<MinidumpWarning> {
+    let stream = |stream_type: MINIDUMP_STREAM_TYPE| {
+        streams
+            .get(&(stream_type as u32))
+            .and_then(|(_, dir)| location_slice(data, &dir.location).ok())
+    };
+
+    let mut regions = Vec::new();
+    if let Some(bytes) = stream(MINIDUMP_STREAM_TYPE::MemoryListStream) {
+        let descs: Vec<md::MINIDUMP_MEMORY_DESCRIPTOR> =
+            read_stream_list(&mut 0, bytes, endian).unwrap_or_default();
+        regions.extend(descs.iter().enumerate().map(|(index, desc)| {
+            (
+                index,
+                desc.start_of_memory_range,
+                desc.memory.data_size as u64,
+                desc.memory.rva as u64,
+            )
+        }));
+    }
+    if let Some(bytes) = stream(MINIDUMP_STREAM_TYPE::Memory64ListStream) {
+        let descs = read_memory64_descriptors(bytes, endian).unwrap_or_default();
+        regions.extend(
+            descs.iter().enumerate().map(|(index, (desc, rva))| {
+                (index, desc.start_of_memory_range, desc.data_size, *rva)
+            }),
+        );
+    }
+
+    let file_size = data.len() as u64;
+    let mut warnings = Vec::new();
+    for (index, base_address, size, rva) in regions {
+        // Null RVAs are how Windows says to look for a stack elsewhere, they aren't lost.
+        if rva == 0 || size == 0 || rva.saturating_add(size) <= file_size {
+            continue;
+        }
+        if rva >= file_size {
+            warnings.push(MinidumpWarning::MissingMemory {
+                index,
+                base_address,
+                size,
+            });
+        } else {
+            warnings.push(MinidumpWarning::TruncatedMemory {
+                index,
+                base_address,
+                expected: size,
+                actual: file_size - rva,
+            });
+        }
+    }
+    warnings
+}
+
 impl<'a, T> Minidump<'a, T>
 where
     T: Deref<Target = [u8]> + 'a,
@@ -4339,72 +8103,56 @@ where
     ///
     /// Typically this will be a `Vec<u8>` or `&[u8]` with the full contents of the minidump,
     /// but you can also use something like `memmap::Mmap`.
+    ///
+    /// Truncated minidumps are read as far as they go: streams and memory regions
+    /// that are cut short keep what's left of them, and anything past the end of
+    /// the file is treated as missing. See [`Minidump::warnings`][] for what was lost.
+    /// Only a missing header or stream directory is an error.
     pub fn read(data: T) -> Result<Minidump<'a, T>, Error> {
-        let mut offset = 0;
-        let mut endian = LE;
-        let mut header: md::MINIDUMP_HEADER = data
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::MissingHeader))?;
-        if header.signature != md::MINIDUMP_SIGNATURE {
-            if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-            // Try again with big-endian.
-            endian = BE;
-            offset = 0;
-            header = data
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::MissingHeader))?;
-            if header.signature != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
+        let (header, endian) = read_header(&data)?;
+        let mut warnings = Vec::new();
+
+        let offset = header.stream_directory_rva as usize;
+        if header.stream_count > 0 && offset >= data.len() {
+            return Err(Error::MissingDirectory);
         }
-        if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
-            return Err(Error::VersionMismatch);
+        let entry_size = <md::MINIDUMP_DIRECTORY>::size_with(&endian);
+        let available = (data.len().saturating_sub(offset) / entry_size).min(u32::MAX as usize);
+        let stream_count = header.stream_count.min(available as u32);
+        if stream_count < header.stream_count {
+            warnings.push(MinidumpWarning::TruncatedDirectory {
+                expected: header.stream_count,
+                read: stream_count,
+            });
         }
-
-        offset = header.stream_directory_rva as usize;
-
-        let (count, _) = ensure_count_in_bound(
-            &data,
-            header.stream_count as usize,
-            <md::MINIDUMP_DIRECTORY>::size_with(&endian),
-            offset,
-        )?;
-
-        let mut streams = HashMap::with_capacity(count);
-        for i in 0..header.stream_count {
-            let dir: md::MINIDUMP_DIRECTORY = data
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::MissingDirectory))?;
-            if let Some((old_idx, old_dir)) = streams.insert(dir.stream_type, (i, dir.clone())) {
-                if let Some(known_stream_type) = MINIDUMP_STREAM_TYPE::from_u32(dir.stream_type) {
-                    warn!("Minidump contains multiple streams of type {} ({:?}) at indices {} ({} bytes) and {} ({} bytes) (using {})",
-                        dir.stream_type,
-                        known_stream_type,
-                        old_idx,
-                        old_dir.location.data_size,
-                        i,
-                        dir.location.data_size,
-                        i,
-                    );
-                } else {
-                    warn!("Minidump contains multiple streams of unknown type {} at indices {} ({} bytes) and {} ({} bytes) (using {})",
-                        dir.stream_type,
-                        old_idx,
-                        old_dir.location.data_size,
-                        i,
-                        dir.location.data_size,
-                        i,
-                    );
-                }
+        let mut entries = read_directory(&data, offset, stream_count, endian)?;
+        clamp_streams(&mut entries, data.len(), &mut warnings);
+        let (streams, shadowed_streams) = index_directory(entries);
+        let mut duplicates = BTreeMap::new();
+        for (_, dir) in &shadowed_streams {
+            // Unused entries are just padding, there can be any number of them.
+            if dir.stream_type != MINIDUMP_STREAM_TYPE::UnusedStream as u32 {
+                *duplicates.entry(dir.stream_type).or_insert(1) += 1;
             }
         }
+        warnings.extend(
+            duplicates
+                .into_iter()
+                .map(|(stream_type, count)| MinidumpWarning::DuplicateStream {
+                    stream_type,
+                    count,
+                }),
+        );
+        warnings.extend(memory_warnings(&data, &streams, endian));
+
         Ok(Minidump {
             data,
             header,
             streams,
+            shadowed_streams,
             endian,
+            warnings,
+            stream_cache: Mutex::new(HashMap::new()),
             _phantom: PhantomData,
         })
     }
@@ -4439,9 +8187,9 @@ where
     ///     // Conditionally analyze a stream
     ///     if let Ok(threads) = dump.get_stream::<MinidumpThreadList>() {
     ///         // Use `Default` to try to make some progress when a stream is missing.
-    ///         // This is especially natural for MinidumpMemoryList because
+    ///         // This is especially natural for the memory because
     ///         // everything needs to handle memory lookups failing anyway.
-    ///         let mem = dump.get_stream::<MinidumpMemoryList>().unwrap_or_default();
+    ///         let mem = dump.memory().unwrap_or_default();
     ///
     ///         for thread in &threads.threads {
     ///            let stack = thread.stack_memory(&mem);
@@ -4463,24 +8211,37 @@ where
     /// `Minidump` struct itself and not to the lifetime of the data backing this minidump.
     /// This is a consequence of how this struct relies on [`Deref`][] to access the data.
     ///
+    /// If the minidump has several streams of type `S` (which should not happen for
+    /// well-formed Minidumps), the last one in the stream directory is used, like
+    /// Breakpad does. [`Minidump::warnings`][] says when that happens, and
+    /// [`Minidump::get_all_streams`][] gets all of them.
+    ///
     /// ## Currently Supported Streams
     ///
     /// * [`MinidumpAssertion`][]
     /// * [`MinidumpBreakpadInfo`][]
     /// * [`MinidumpCrashpadInfo`][]
     /// * [`MinidumpException`][]
+    /// * [`MinidumpFunctionTableList`][]
+    /// * [`MinidumpHandleDataStream`][]
+    /// * [`MinidumpHandleOperationList`][]
     /// * [`MinidumpLinuxCpuInfo`][]
+    /// * [`MinidumpLinuxDsoDebug`][]
     /// * [`MinidumpLinuxEnviron`][]
     /// * [`MinidumpLinuxLsbRelease`][]
     /// * [`MinidumpLinuxMaps`][]
     /// * [`MinidumpLinuxProcStatus`][]
     /// * [`MinidumpMacCrashInfo`][]
     /// * [`MinidumpMemoryList`][]
+    /// * [`MinidumpMemory64List`][]
     /// * [`MinidumpMemoryInfoList`][]
     /// * [`MinidumpMiscInfo`][]
     /// * [`MinidumpModuleList`][]
+    /// * [`MinidumpProcessVmCounters`][]
     /// * [`MinidumpSystemInfo`][]
+    /// * [`MinidumpSystemMemoryInfo`][]
     /// * [`MinidumpThreadList`][]
+    /// * [`MinidumpThreadInfoList`][]
     /// * [`MinidumpThreadNames`][]
     /// * [`MinidumpUnloadedModuleList`][]
     ///
@@ -4488,62 +8249,254 @@ where
     where
         S: MinidumpStream<'a>,
     {
-        match self.get_raw_stream(S::STREAM_TYPE) {
-            Err(e) => Err(e),
-            Ok(bytes) => {
-                let all_bytes = self.data.deref();
-                S::read(bytes, all_bytes, self.endian)
-            }
-        }
+        let bytes = self.raw_stream(S::STREAM_TYPE.into())?;
+        let system_info = self.system_info();
+        S::read_with_system_info(
+            bytes,
+            self.data.deref(),
+            self.endian,
+            system_info.as_deref(),
+        )
     }
 
-    /// Get a stream of raw data from the minidump.
+    /// Read and parse every [`MinidumpStream`][] `S` in the Minidump, in directory order.
     ///
-    /// This can be used to get the contents of arbitrary minidump streams.
-    /// For streams of known types you almost certainly want to use
-    /// [`Minidump::get_stream`][] instead.
+    /// Some writers emit more than one stream of the same type, like two thread lists,
+    /// or several user streams (e.g. [`MinidumpCrashpadInfo`][]) that share a type.
+    /// [`Minidump::get_stream`][] only gets the last of them, this gets each of them,
+    /// parsed separately so that one being corrupt doesn't hide the others.
+    /// It's empty if there are none.
+    pub fn get_all_streams<S>(&'a self) -> Vec<Result<S, Error>>
+    where
+        S: MinidumpStream<'a>,
+    {
+        let system_info = self.system_info();
+        self.get_all_raw_streams(S::STREAM_TYPE)
+            .into_iter()
+            .map(|bytes| {
+                S::read_with_system_info(
+                    bytes,
+                    self.data.deref(),
+                    self.endian,
+                    system_info.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    /// Guess a [`MinidumpSystemInfo`][] from the CPU contexts, for minidumps
+    /// without a `SystemInfoStream`.
     ///
-    /// Note that the lifetime of the returned stream is bound to the lifetime of the this
-    /// `Minidump` struct itself and not to the lifetime of the data backing this minidump.
-    /// This is a consequence of how this struct relies on [Deref] to access the data.
-    pub fn get_raw_stream<S>(&'a self, stream_type: S) -> Result<&'a [u8], Error>
+    /// Some minimal minidump writers leave it out, but the contexts' flags and
+    /// sizes, see [`MinidumpContext`][], still say what CPU wrote the minidump.
+    /// The exception's context is used if there is one, otherwise the first
+    /// thread's. Only the CPU is inferred, the OS and everything else is unknown.
+    pub fn infer_system_info(&'a self) -> Option<MinidumpSystemInfo> {
+        let exception = self.get_stream::<MinidumpException>().ok();
+        let thread_list = self.get_stream::<MinidumpThreadList>().ok();
+        let context = exception
+            .and_then(|exception| exception.context)
+            .or_else(|| {
+                thread_list
+                    .as_ref()
+                    .and_then(|list| list.threads.first())
+                    .and_then(|thread| thread.context)
+            })?;
+        MinidumpSystemInfo::from_context(context, self.endian)
+    }
+
+    /// Like [`Minidump::get_stream`][], but only parse each stream once.
+    ///
+    /// The first call for a given `S` parses it and keeps it, and later calls return
+    /// the same `Arc`, so this is the cheap way to get at a stream from several places.
+    /// Failures aren't cached. Only streams that don't borrow from the minidump's data
+    /// (like [`MinidumpModuleList`][] or [`MinidumpSystemInfo`][]) can be cached.
+    ///
+    /// See [`Minidump::reparse`][] to throw the cached streams away.
+    pub fn get_stream_cached<S>(&'a self) -> Result<Arc<S>, Error>
     where
-        S: Into<u32>,
+        S: MinidumpStream<'a> + Send + Sync + 'static,
     {
-        match self.streams.get(&stream_type.into()) {
-            None => Err(Error::StreamNotFound),
-            Some(&(_, ref dir)) => {
-                let bytes = self.data.deref();
-                location_slice(bytes, &dir.location)
-            }
-        }
+        self.cached(|| self.get_stream::<S>())
     }
 
-    /// A listing of all the streams in the Minidump that this library is *aware* of,
-    /// but has no further analysis for.
+    /// Forget every stream cached by [`Minidump::get_stream_cached`][], so they're
+    /// parsed again the next time they're asked for.
     ///
-    /// If there are multiple copies of the same stream type (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
-    pub fn unimplemented_streams(&self) -> impl Iterator<Item = MinidumpUnimplementedStream> + '_ {
-        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 33] = [
-            // Presumably will never have an implementation:
-            MINIDUMP_STREAM_TYPE::UnusedStream,
-            MINIDUMP_STREAM_TYPE::ReservedStream0,
+    /// `Arc`s that were already handed out keep their streams.
+    pub fn reparse(&self) {
+        self.stream_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    /// The `SystemInfoStream`, which many other streams need to be interpreted.
+    fn system_info(&'a self) -> Option<Arc<MinidumpSystemInfo>> {
+        self.cached(|| {
+            let bytes = self.raw_stream(MINIDUMP_STREAM_TYPE::SystemInfoStream.into())?;
+            MinidumpSystemInfo::read(bytes, self.data.deref(), self.endian)
+        })
+        .ok()
+    }
+
+    /// Get the cached `S`, or cache the result of `parse` if there isn't one yet.
+    fn cached<S>(&self, parse: impl FnOnce() -> Result<S, Error>) -> Result<Arc<S>, Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        let key = TypeId::of::<S>();
+        let cached = self
+            .stream_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(&key)
+            .cloned();
+        if let Some(Ok(stream)) = cached.map(|stream| stream.downcast::<S>()) {
+            return Ok(stream);
+        }
+        // Don't hold the lock while parsing, since that can need other cached streams.
+        // If another thread got there first, use its copy so everyone shares one.
+        let stream: Arc<dyn Any + Send + Sync> = Arc::new(parse()?);
+        let stream = self
+            .stream_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(key)
+            .or_insert(stream)
+            .clone();
+        Ok(stream
+            .downcast::<S>()
+            .expect("stream cache entries are keyed by their type"))
+    }
+
+    /// Get a stream of raw data from the minidump.
+    ///
+    /// This can be used to get the contents of arbitrary minidump streams.
+    /// For streams of known types you almost certainly want to use
+    /// [`Minidump::get_stream`][] instead.
+    ///
+    /// `stream_type` can be a [`MINIDUMP_STREAM_TYPE`][] or any `u32`, so this is also how
+    /// to read custom streams that rust-minidump doesn't know about. The returned bytes
+    /// are exactly the `data_size` bytes at the `rva` of the stream's directory entry:
+    ///
+    /// * Nothing is byte-swapped, so multi-byte values are in [`Minidump::endian`][]
+    ///   order. Use it when reading them (e.g. with [`scroll::Pread::pread_with`][]).
+    /// * Any RVAs inside the stream are offsets from the start of the whole minidump,
+    ///   not of the stream, and can't be followed from the returned slice.
+    /// * If there are several streams of `stream_type`, this is the one that
+    ///   [`Minidump::get_stream`][] would use. [`Minidump::get_all_raw_streams`][]
+    ///   returns all of them.
+    ///
+    /// Returns `None` if there is no such stream, or if its directory entry points
+    /// outside the minidump.
+    ///
+    /// ```
+    /// use minidump::*;
+    /// use scroll::Pread;
+    ///
+    /// /// A stream written by our own crash reporter: a u32 count of u32 values.
+    /// const CUSTOM_STREAM_TYPE: u32 = 0x4d7a_0001;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let dump = Minidump::read_path("../testdata/test.dmp")?;
+    ///     match dump.get_raw_stream(CUSTOM_STREAM_TYPE) {
+    ///         Some(bytes) => {
+    ///             let count: u32 = bytes
+    ///                 .pread_with(0, dump.endian)
+    ///                 .map_err(|_| Error::StreamReadFailure)?;
+    ///             println!("{} custom values", count);
+    ///         }
+    ///         None => println!("no custom stream"),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Note that the lifetime of the returned stream is bound to the lifetime of the this
+    /// `Minidump` struct itself and not to the lifetime of the data backing this minidump.
+    /// This is a consequence of how this struct relies on [Deref] to access the data.
+    pub fn get_raw_stream<S>(&'a self, stream_type: S) -> Option<&'a [u8]>
+    where
+        S: Into<u32>,
+    {
+        self.raw_stream(stream_type.into()).ok()
+    }
+
+    /// [`Minidump::get_raw_stream`][], but saying why the stream couldn't be read.
+    fn raw_stream(&'a self, stream_type: u32) -> Result<&'a [u8], Error> {
+        match self.streams.get(&stream_type) {
+            None => Err(Error::StreamNotFound),
+            Some(&(_, ref dir)) => {
+                let bytes = self.data.deref();
+                location_slice(bytes, &dir.location)
+            }
+        }
+    }
+
+    /// Get every stream of raw data of the given type from the minidump, in directory order.
+    ///
+    /// See [`Minidump::get_all_streams`][].
+    pub fn get_all_raw_streams<S>(&'a self, stream_type: S) -> Vec<&'a [u8]>
+    where
+        S: Into<u32>,
+    {
+        let bytes = self.data.deref();
+        self.stream_locations(stream_type.into())
+            .iter()
+            .filter_map(|location| location_slice(bytes, location).ok())
+            .collect()
+    }
+
+    /// The locations of every stream of `stream_type`, in directory order.
+    fn stream_locations(&self, stream_type: u32) -> Vec<md::MINIDUMP_LOCATION_DESCRIPTOR> {
+        let mut streams = self
+            .shadowed_streams
+            .iter()
+            .filter(|(_, dir)| dir.stream_type == stream_type)
+            .chain(self.streams.get(&stream_type))
+            .collect::<Vec<_>>();
+        streams.sort_by_key(|(i, _)| *i);
+        streams.into_iter().map(|(_, dir)| dir.location).collect()
+    }
+
+    /// Get the memory of the process that wrote the minidump.
+    ///
+    /// This combines the [`MinidumpMemoryList`][] and the
+    /// [`MinidumpMemory64List`][] (used by full-memory dumps), whichever of
+    /// them the minidump has. If one of them is corrupt the other is still used;
+    /// an error is only returned if neither could be read.
+    pub fn memory(&'a self) -> Result<UnifiedMemoryList<'a>, Error> {
+        match (
+            self.get_stream::<MinidumpMemoryList<'a>>(),
+            self.get_stream::<MinidumpMemory64List<'a>>(),
+        ) {
+            (Err(Error::StreamNotFound), Err(err)) | (Err(err), Err(_)) => Err(err),
+            (memory, memory64) => Ok(UnifiedMemoryList::new(
+                memory.unwrap_or_default(),
+                memory64.unwrap_or_default(),
+            )),
+        }
+    }
+
+    /// A listing of all the streams in the Minidump that this library is *aware* of,
+    /// but has no further analysis for.
+    ///
+    /// If there are multiple copies of the same stream type (which should not happen for
+    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
+    pub fn unimplemented_streams(&self) -> impl Iterator<Item = MinidumpUnimplementedStream> + '_ {
+        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 25] = [
+            // Presumably will never have an implementation:
+            MINIDUMP_STREAM_TYPE::UnusedStream,
+            MINIDUMP_STREAM_TYPE::ReservedStream0,
             MINIDUMP_STREAM_TYPE::ReservedStream1,
             MINIDUMP_STREAM_TYPE::LastReservedStream,
             // Presumably should be implemented:
             MINIDUMP_STREAM_TYPE::ThreadExListStream,
-            MINIDUMP_STREAM_TYPE::Memory64ListStream,
             MINIDUMP_STREAM_TYPE::CommentStreamA,
             MINIDUMP_STREAM_TYPE::CommentStreamW,
-            MINIDUMP_STREAM_TYPE::HandleDataStream,
-            MINIDUMP_STREAM_TYPE::FunctionTable,
-            MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
-            MINIDUMP_STREAM_TYPE::HandleOperationListStream,
             MINIDUMP_STREAM_TYPE::TokenStream,
             MINIDUMP_STREAM_TYPE::JavaScriptDataStream,
-            MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream,
-            MINIDUMP_STREAM_TYPE::ProcessVmCountersStream,
             MINIDUMP_STREAM_TYPE::IptTraceStream,
             // What on earth is all this "ce" stuff?
             MINIDUMP_STREAM_TYPE::ceStreamNull,
@@ -4562,7 +8515,6 @@ where
             // non-standard streams (should also be implemented):
             MINIDUMP_STREAM_TYPE::LinuxCmdLine,
             MINIDUMP_STREAM_TYPE::LinuxAuxv,
-            MINIDUMP_STREAM_TYPE::LinuxDsoDebug,
         ];
         self.streams.iter().filter_map(|(_, (_, stream))| {
             MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).and_then(|stream_type| {
@@ -4595,6 +8547,34 @@ where
         })
     }
 
+    /// The contents of all the streams in the Minidump that this library has no
+    /// knowledge of, in directory order.
+    ///
+    /// Applications can add their own streams to a minidump when they write it (for
+    /// instance to record what they were doing at the time), and this is how to get
+    /// them back out. Streams whose contents aren't in the minidump are skipped.
+    ///
+    /// If there are multiple streams of the same type, each of them is yielded.
+    pub fn user_streams(&'a self) -> impl Iterator<Item = MinidumpUserStream<'a>> + 'a {
+        self.streams().filter_map(move |(_, stream)| {
+            if MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).is_some() {
+                return None;
+            }
+            let data = self.stream_contents(&stream).ok()?;
+            Some(MinidumpUserStream {
+                stream_type: stream.stream_type,
+                data,
+            })
+        })
+    }
+
+    /// What was missing from the file when it was read, if it was truncated or corrupt.
+    ///
+    /// Empty for well-formed minidumps.
+    pub fn warnings(&self) -> &[MinidumpWarning] {
+        &self.warnings
+    }
+
     /// A listing of all the streams in the Minidump.
     ///
     /// If there are multiple copies of the same stream (which should not happen for
@@ -4603,16 +8583,222 @@ where
         self.streams.iter().map(|(_, (_, stream))| stream)
     }
 
-    /// Write a verbose description of the `Minidump` to `f`.
-    pub fn print<W: Write>(&self, f: &mut W) -> io::Result<()> {
-        fn get_stream_name(stream_type: u32) -> Cow<'static, str> {
-            if let Some(stream) = MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
-                Cow::Owned(format!("{:?}", stream))
-            } else {
-                Cow::Borrowed("unknown")
+    /// A listing of all the streams in the Minidump, with their index in the stream
+    /// directory, in directory order.
+    ///
+    /// Unlike [`Minidump::get_raw_stream`][], this doesn't require knowing the stream
+    /// types up front, so it's useful for tools that want to show everything in a
+    /// minidump.
+    ///
+    /// If there are multiple streams of the same type, each of them is yielded, including
+    /// the ones [`Minidump::get_stream`][] ignores.
+    ///
+    /// ```
+    /// use minidump::*;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let dump = Minidump::read_path("../testdata/test.dmp")?;
+    ///     for (index, stream) in dump.streams() {
+    ///         println!(
+    ///             "{}: type {:#x}, {} bytes at {:#x}",
+    ///             index, stream.stream_type, stream.data_size, stream.file_offset
+    ///         );
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn streams(&self) -> impl Iterator<Item = (u32, StreamDescriptor)> {
+        let mut streams = self
+            .streams
+            .values()
+            .chain(&self.shadowed_streams)
+            .map(|&(i, ref stream)| {
+                (
+                    i,
+                    StreamDescriptor {
+                        stream_type: stream.stream_type,
+                        data_size: stream.location.data_size,
+                        file_offset: stream.location.rva,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        streams.sort_by_key(|&(i, _)| i);
+        streams.into_iter()
+    }
+
+    /// The size of each stream in the Minidump, largest first.
+    ///
+    /// This only looks at the stream directory, so it's cheap even for huge
+    /// minidumps, and includes streams we don't know how to parse. Streams of
+    /// the same size are in directory order.
+    ///
+    /// Note that the sizes of the [`MinidumpMemoryList`][] and
+    /// [`MinidumpMemory64List`][] streams are just their lists of regions; the
+    /// memory itself is stored separately.
+    pub fn memory_usage_report(&self) -> Vec<(StreamTypeName, usize)> {
+        let mut report = self
+            .streams()
+            .map(|(_, stream)| {
+                (
+                    StreamTypeName(stream.stream_type),
+                    stream.data_size as usize,
+                )
+            })
+            .collect::<Vec<_>>();
+        report.sort_by_key(|&(_, size)| std::cmp::Reverse(size));
+        report
+    }
+
+    /// Write [`Minidump::memory_usage_report`][] to `f`.
+    pub fn print_memory_usage_report<W: Write>(&self, f: &mut W) -> io::Result<()> {
+        writeln!(f, "Stream sizes:")?;
+        for (name, size) in self.memory_usage_report() {
+            writeln!(f, "  {:>12} bytes  {}", size, name)?;
+        }
+        writeln!(f)
+    }
+
+    /// A listing of all the streams in the Minidump with their contents, in directory
+    /// order.
+    ///
+    /// This is [`Minidump::streams`][] combined with [`Minidump::get_raw_stream`][], and
+    /// includes streams of every type, known or not, and every stream of each type.
+    /// Streams whose contents aren't in the minidump are skipped.
+    pub fn raw_streams(&'a self) -> impl Iterator<Item = (StreamDescriptor, &'a [u8])> + 'a {
+        self.streams().filter_map(move |(_, stream)| {
+            let data = self.stream_contents(&stream).ok()?;
+            Some((stream, data))
+        })
+    }
+
+    /// The contents of `stream`, which is from [`Minidump::streams`][].
+    ///
+    /// Unlike [`Minidump::get_raw_stream`][] this works for every stream of a type.
+    fn stream_contents(&'a self, stream: &StreamDescriptor) -> Result<&'a [u8], Error> {
+        let location = md::MINIDUMP_LOCATION_DESCRIPTOR {
+            data_size: stream.data_size,
+            rva: stream.file_offset,
+        };
+        location_slice(self.data.deref(), &location)
+    }
+
+    /// Write this `Minidump` back out to `writer`.
+    ///
+    /// Streams that refer to other parts of the minidump are parsed and re-serialized
+    /// with [`MinidumpStreamWrite`][], and self-contained streams (including any we don't
+    /// recognize) are copied verbatim. Streams that refer to other parts of the minidump
+    /// but that we can't re-serialize are dropped with a warning, because copying them
+    /// would leave their references dangling.
+    ///
+    /// The result won't be byte-for-byte identical to the original, but it should
+    /// parse the same.
+    pub fn write<W: Write>(&'a self, writer: W) -> Result<(), Error> {
+        self.write_filtered(writer, |_| true)
+    }
+
+    /// Like [`Minidump::write`][], but only write the streams for which `keep`
+    /// returns true.
+    ///
+    /// This is useful for scrubbing streams you don't want to share.
+    ///
+    /// ```
+    /// use minidump::*;
+    /// use minidump::format::MINIDUMP_STREAM_TYPE;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let dump = Minidump::read_path("../testdata/test.dmp")?;
+    ///
+    ///     // Strip out the stack and heap memory.
+    ///     let mut scrubbed = Vec::new();
+    ///     dump.write_filtered(&mut scrubbed, |stream_type| {
+    ///         stream_type != MINIDUMP_STREAM_TYPE::MemoryListStream as u32
+    ///     })?;
+    ///
+    ///     let scrubbed = Minidump::read(scrubbed)?;
+    ///     assert!(scrubbed.get_stream::<MinidumpMemoryList>().is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_filtered<W, F>(&'a self, writer: W, mut keep: F) -> Result<(), Error>
+    where
+        W: Write,
+        F: FnMut(u32) -> bool,
+    {
+        use MINIDUMP_STREAM_TYPE::*;
+
+        // Streams which don't refer to anything outside of themselves.
+        static SELF_CONTAINED_STREAMS: [MINIDUMP_STREAM_TYPE; 17] = [
+            CommentStreamA,
+            CommentStreamW,
+            FunctionTable,
+            MemoryInfoListStream,
+            MiscInfoStream,
+            HandleOperationListStream,
+            ThreadInfoListStream,
+            SystemMemoryInfoStream,
+            ProcessVmCountersStream,
+            BreakpadInfoStream,
+            AssertionInfoStream,
+            LinuxCpuInfo,
+            LinuxProcStatus,
+            LinuxLsbRelease,
+            LinuxCmdLine,
+            LinuxEnviron,
+            LinuxMaps,
+        ];
+
+        let mut out = MinidumpWriter::new(self.endian);
+        let mut streams = self.streams.values().collect::<Vec<_>>();
+        streams.sort_by_key(|&&(i, _)| i);
+        for (_, stream) in streams {
+            if !keep(stream.stream_type) {
+                continue;
+            }
+            match MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type) {
+                Some(ThreadListStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpThreadList>()?)?
+                }
+                Some(ModuleListStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpModuleList>()?)?
+                }
+                Some(MemoryListStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpMemoryList>()?)?
+                }
+                Some(Memory64ListStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpMemory64List>()?)?
+                }
+                Some(ExceptionStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpException>()?)?
+                }
+                Some(SystemInfoStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpSystemInfo>()?)?
+                }
+                Some(UnloadedModuleListStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpUnloadedModuleList>()?)?
+                }
+                Some(HandleDataStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpHandleDataStream>()?)?
+                }
+                Some(ThreadNamesStream) => {
+                    out.add_stream(&self.get_stream::<MinidumpThreadNames>()?)?
+                }
+                // These are just padding in the stream directory.
+                Some(UnusedStream) => {}
+                Some(stream_type) if !SELF_CONTAINED_STREAMS.contains(&stream_type) => {
+                    warn!("Can't write {:?}, dropping it", stream_type);
+                }
+                _ => {
+                    let bytes = location_slice(&self.data, &stream.location)?;
+                    out.add_raw_stream(stream.stream_type, bytes)?;
+                }
             }
         }
+        out.finish(self.header.time_date_stamp, self.header.flags, writer)
+    }
 
+    /// Write a verbose description of the `Minidump` to `f`.
+    pub fn print<W: Write>(&self, f: &mut W) -> io::Result<()> {
         write!(
             f,
             r#"MDRawHeader
@@ -4634,9 +8820,8 @@ where
             format_time_t(self.header.time_date_stamp),
             self.header.flags,
         )?;
-        let mut streams = self.streams.iter().collect::<Vec<_>>();
-        streams.sort_by(|&(&_, &(a, _)), &(&_, &(b, _))| a.cmp(&b));
-        for &(_, &(i, ref stream)) in streams.iter() {
+        let mut streams = self.streams().collect::<Vec<_>>();
+        for &(i, ref stream) in streams.iter() {
             write!(
                 f,
                 r#"mDirectory[{}]
@@ -4648,578 +8833,4124 @@ MDRawDirectory
 "#,
                 i,
                 stream.stream_type,
-                get_stream_name(stream.stream_type),
-                stream.location.data_size,
-                stream.location.rva
+                StreamTypeName(stream.stream_type),
+                stream.data_size,
+                stream.file_offset
             )?;
         }
         writeln!(f, "Streams:")?;
-        streams.sort_by(|&(&a, &(_, _)), &(&b, &(_, _))| a.cmp(&b));
-        for (_, &(i, ref stream)) in streams {
+        streams.sort_by_key(|(_, stream)| stream.stream_type);
+        for (i, stream) in streams {
             writeln!(
                 f,
                 "  stream type {:#x} ({}) at index {}",
                 stream.stream_type,
-                get_stream_name(stream.stream_type),
+                StreamTypeName(stream.stream_type),
                 i
             )?;
         }
         writeln!(f)?;
         Ok(())
     }
-}
 
-fn stream_vendor(stream_type: u32) -> &'static str {
-    if stream_type <= MINIDUMP_STREAM_TYPE::LastReservedStream as u32 {
-        "Official"
-    } else {
-        match stream_type & 0xFFFF0000 {
-            0x4767_0000 => "Google Extension",
-            0x4d7a_0000 => "Mozilla Extension",
-            _ => "Unknown Extension",
+    /// Write a verbose description of the `Minidump` and every stream in it to `f`.
+    ///
+    /// After the header and directory (see [`Minidump::print`][]), each stream is
+    /// printed in directory order. Streams we know are parsed and printed in full,
+    /// text streams are also shown as-is, and anything else is hex-dumped. This is
+    /// the format used by `minidump_dump`, which roughly matches Breakpad's tool
+    /// of the same name.
+    pub fn print_all<W: Write>(&'a self, f: &mut W) -> io::Result<()> {
+        self.print(f)?;
+
+        // Other streams depend on these, so load them upfront.
+        let system_info = self.get_stream::<MinidumpSystemInfo>().ok();
+        let memory = self.memory().ok();
+        let misc_info = self.get_stream::<MinidumpMiscInfo>().ok();
+
+        for (_, stream) in self.streams() {
+            self.print_stream(
+                f,
+                stream,
+                system_info.as_ref(),
+                memory.as_ref(),
+                misc_info.as_ref(),
+            )?;
         }
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use md::GUID;
-    use minidump_common::format::ProcessorArchitecture;
-    use std::mem;
-    use synth_minidump::{
-        self, AnnotationValue, CrashpadInfo, DumpString, Exception, Memory,
-        MemoryInfo as SynthMemoryInfo, MiscFieldsBuildString, MiscFieldsPowerInfo,
-        MiscFieldsProcessTimes, MiscFieldsTimeZone, MiscInfo5Fields, MiscStream,
-        Module as SynthModule, ModuleCrashpadInfo, SimpleStream, SynthMinidump, SystemInfo, Thread,
-        ThreadName, UnloadedModule as SynthUnloadedModule, STOCK_VERSION_INFO,
-    };
-    use test_assembler::*;
+    /// Print one stream from the directory for [`Minidump::print_all`][], however we can.
+    fn print_stream<W: Write>(
+        &'a self,
+        f: &mut W,
+        stream: StreamDescriptor,
+        system_info: Option<&MinidumpSystemInfo>,
+        memory: Option<&UnifiedMemoryList<'a>>,
+        misc_info: Option<&MinidumpMiscInfo>,
+    ) -> io::Result<()> {
+        use MINIDUMP_STREAM_TYPE::*;
+
+        let contents = self.stream_contents(&stream).unwrap_or(&[]);
+
+        // Print a stream by parsing it, or say why it couldn't be parsed. This
+        // parses `contents` rather than using `get_stream`, so that every stream
+        // of a type gets printed, not just the last.
+        macro_rules! print_parsed {
+            ($stream:ty, |$parsed:ident| $print:expr) => {
+                match <$stream>::read_with_system_info(
+                    contents,
+                    self.data.deref(),
+                    self.endian,
+                    system_info,
+                ) {
+                    Ok($parsed) => $print,
+                    Err(err) => writeln!(f, "{} cannot be read: {}\n", stringify!($stream), err),
+                }
+            };
+            ($stream:ty) => {
+                print_parsed!($stream, |parsed| parsed.print(f))
+            };
+        }
 
-    fn read_synth_dump<'a>(dump: SynthMinidump) -> Result<Minidump<'a, Vec<u8>>, Error> {
-        Minidump::read(dump.finish().unwrap())
+        let stream_type = match MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type) {
+            Some(stream_type) => stream_type,
+            None => {
+                let name = format!("{:#x}", stream.stream_type);
+                return print_hex_stream(f, &name, contents);
+            }
+        };
+        match stream_type {
+            ThreadListStream => print_parsed!(MinidumpThreadList<'a>, |thread_list| {
+                thread_list.print(f, memory, system_info, misc_info)
+            }),
+            ModuleListStream => print_parsed!(MinidumpModuleList),
+            UnloadedModuleListStream => print_parsed!(MinidumpUnloadedModuleList),
+            MemoryListStream => print_parsed!(MinidumpMemoryList<'a>),
+            Memory64ListStream => print_parsed!(MinidumpMemory64List<'a>),
+            MemoryInfoListStream => print_parsed!(MinidumpMemoryInfoList<'a>),
+            ExceptionStream => print_parsed!(MinidumpException<'a>, |exception| {
+                exception.print(f, system_info, misc_info)
+            }),
+            AssertionInfoStream => print_parsed!(MinidumpAssertion),
+            SystemInfoStream => match system_info {
+                Some(system_info) => system_info.print(f),
+                None => writeln!(f, "MinidumpSystemInfo cannot be read\n"),
+            },
+            MiscInfoStream => match misc_info {
+                Some(misc_info) => misc_info.print(f),
+                None => writeln!(f, "MinidumpMiscInfo cannot be read\n"),
+            },
+            SystemMemoryInfoStream => print_parsed!(MinidumpSystemMemoryInfo),
+            ProcessVmCountersStream => print_parsed!(MinidumpProcessVmCounters),
+            BreakpadInfoStream => print_parsed!(MinidumpBreakpadInfo),
+            ThreadNamesStream => print_parsed!(MinidumpThreadNames),
+            ThreadInfoListStream => print_parsed!(MinidumpThreadInfoList),
+            HandleDataStream => print_parsed!(MinidumpHandleDataStream),
+            HandleOperationListStream => print_parsed!(MinidumpHandleOperationList),
+            FunctionTable => print_parsed!(MinidumpFunctionTableList),
+            CrashpadInfoStream => print_parsed!(MinidumpCrashpadInfo),
+            LinuxDsoDebug => print_parsed!(MinidumpLinuxDsoDebug),
+            MozMacosCrashInfoStream => print_parsed!(MinidumpMacCrashInfo),
+            LinuxCpuInfo | LinuxProcStatus | LinuxMaps | LinuxCmdLine | LinuxEnviron
+            | LinuxLsbRelease => {
+                match stream_type {
+                    LinuxCpuInfo => print_parsed!(MinidumpLinuxCpuInfo)?,
+                    LinuxProcStatus => print_parsed!(MinidumpLinuxProcStatus)?,
+                    LinuxMaps => print_parsed!(MinidumpLinuxMaps<'a>)?,
+                    _ => {}
+                }
+                // These are all text, so just show them as-is too.
+                print_raw_stream(f, &format!("{:?}", stream_type), contents)
+            }
+            _ => print_hex_stream(f, &format!("{:?}", stream_type), contents),
+        }
     }
+}
 
-    #[test]
-    fn test_simple_synth_dump() {
-        const STREAM_TYPE: u32 = 0x11223344;
-        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
-            stream_type: STREAM_TYPE,
-            section: Section::with_endian(Endian::Little).D32(0x55667788),
-        });
-        let dump = read_synth_dump(dump).unwrap();
-        assert_eq!(dump.endian, LE);
-        assert_eq!(
-            dump.get_raw_stream(STREAM_TYPE).unwrap(),
-            &[0x88, 0x77, 0x66, 0x55]
-        );
+/// How much of a stream [`Minidump::print_all`][] hex-dumps when it doesn't know
+/// how to print it.
+const HEX_DUMP_LIMIT: usize = 256;
+
+/// Print a text stream, with any nul separators shown as `\0` line breaks.
+fn print_raw_stream<W: Write>(f: &mut W, name: &str, contents: &[u8]) -> io::Result<()> {
+    writeln!(f, "Stream {}:", name)?;
+    let s = contents
+        .split(|&v| v == 0)
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join("\\0\n");
+    write!(f, "{}\n\n", s)
+}
 
-        assert_eq!(
-            dump.get_raw_stream(0xaabbccddu32),
-            Err(Error::StreamNotFound)
-        );
+/// Hex-dump the start of a stream we don't know how to print.
+fn print_hex_stream<W: Write>(f: &mut W, name: &str, contents: &[u8]) -> io::Result<()> {
+    writeln!(f, "Stream {} ({} bytes):", name, contents.len())?;
+    let shown = &contents[..contents.len().min(HEX_DUMP_LIMIT)];
+    for (i, line) in shown.chunks(16).enumerate() {
+        write!(f, "  {:08x}: ", i * 16)?;
+        for byte in line {
+            write!(f, "{:02x} ", byte)?;
+        }
+        for _ in line.len()..16 {
+            write!(f, "   ")?;
+        }
+        let ascii = line
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        writeln!(f, " {}", ascii)?;
     }
+    if shown.len() < contents.len() {
+        writeln!(f, "  ...")?;
+    }
+    writeln!(f)
+}
 
-    #[test]
-    fn test_simple_synth_dump_bigendian() {
-        const STREAM_TYPE: u32 = 0x11223344;
-        let dump = SynthMinidump::with_endian(Endian::Big).add_stream(SimpleStream {
-            stream_type: STREAM_TYPE,
-            section: Section::with_endian(Endian::Big).D32(0x55667788),
-        });
-        let dump = read_synth_dump(dump).unwrap();
-        assert_eq!(dump.endian, BE);
-        assert_eq!(
-            dump.get_raw_stream(STREAM_TYPE).unwrap(),
-            &[0x55, 0x66, 0x77, 0x88]
-        );
+/// Builds a new minidump out of [`MinidumpStreamWrite`][] streams and raw bytes.
+///
+/// See [`Minidump::write`][] for re-serializing an existing `Minidump`.
+///
+/// # Examples
+///
+/// ```
+/// use minidump::*;
+///
+/// fn main() -> Result<(), Error> {
+///     let mut writer = MinidumpWriter::new(Endian::Little);
+///     writer.add_stream(&MinidumpModuleList::from_modules(vec![MinidumpModule::new(
+///         0x1000, 0x2000, "foo.dll",
+///     )]))?;
+///
+///     let mut bytes = Vec::new();
+///     writer.finish(0, 0, &mut bytes)?;
+///
+///     let dump = Minidump::read(bytes)?;
+///     let modules = dump.get_stream::<MinidumpModuleList>()?;
+///     assert_eq!(modules.iter().next().unwrap().name, "foo.dll");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MinidumpWriter {
+    endian: scroll::Endian,
+    /// The contents of the minidump, starting with space for the header. The stream
+    /// directory isn't written until `finish`, when we know how many streams there are.
+    data: Vec<u8>,
+    streams: Vec<md::MINIDUMP_DIRECTORY>,
+    /// Data that has already been copied out of another minidump, by its original location.
+    ///
+    /// Thread stacks are usually also in the memory list, so this avoids writing them twice.
+    copied: HashMap<(u32, md::RVA), md::MINIDUMP_LOCATION_DESCRIPTOR>,
+}
 
-        assert_eq!(
-            dump.get_raw_stream(0xaabbccddu32),
-            Err(Error::StreamNotFound)
-        );
+impl MinidumpWriter {
+    /// Create a `MinidumpWriter` for a minidump with the given endianness.
+    pub fn new(endian: scroll::Endian) -> MinidumpWriter {
+        MinidumpWriter {
+            endian,
+            data: vec![0; md::MINIDUMP_HEADER::size_with(&endian)],
+            streams: vec![],
+            copied: HashMap::new(),
+        }
     }
 
-    #[test]
-    fn test_thread_names() {
-        let good_thread_id = 17;
-        let corrupt_thread_id = 123;
+    /// The endianness of the minidump being written.
+    pub fn endian(&self) -> scroll::Endian {
+        self.endian
+    }
 
-        let good_name = DumpString::new("MyCoolThread", Endian::Little);
-        // No corrupt name, will dangle
-
-        let good_thread_name_entry =
-            ThreadName::new(Endian::Little, good_thread_id, Some(&good_name));
-        let corrupt_thread_name_entry = ThreadName::new(Endian::Little, corrupt_thread_id, None);
-
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_thread_name(good_thread_name_entry)
-            .add_thread_name(corrupt_thread_name_entry)
-            .add(good_name);
+    /// Append `bytes` to the minidump, returning where they were written.
+    pub fn append_bytes(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        // Keep everything 4-byte aligned, like Windows does.
+        let padding = (4 - self.data.len() % 4) % 4;
+        self.data.resize(self.data.len() + padding, 0);
+        let location = md::MINIDUMP_LOCATION_DESCRIPTOR {
+            data_size: u32::try_from(bytes.len()).or(Err(Error::WriteFailure))?,
+            rva: u32::try_from(self.data.len()).or(Err(Error::WriteFailure))?,
+        };
+        self.data
+            .len()
+            .checked_add(bytes.len())
+            .filter(|&end| u32::try_from(end).is_ok())
+            .ok_or(Error::WriteFailure)?;
+        self.data.extend_from_slice(bytes);
+        Ok(location)
+    }
 
-        let dump = read_synth_dump(dump).unwrap();
-        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
-        assert_eq!(thread_names.names.len(), 1);
-        assert_eq!(
-            &*thread_names.get_name(good_thread_id).unwrap(),
-            "MyCoolThread"
-        );
-        assert_eq!(thread_names.get_name(corrupt_thread_id), None);
+    /// Append the raw minidump struct `value`, returning where it was written.
+    pub fn append_struct<T>(&mut self, value: T) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error>
+    where
+        T: SizeWith<scroll::Endian> + TryIntoCtx<scroll::Endian, Error = scroll::Error>,
+    {
+        let mut bytes = Vec::new();
+        push_struct(&mut bytes, value, self.endian)?;
+        self.append_bytes(&bytes)
+    }
+
+    /// Append `string` as a u32 length-prefixed UTF-16 string, returning its RVA.
+    pub fn append_string_utf16(&mut self, string: &str) -> Result<md::RVA, Error> {
+        let encoded = utf16_encoding(self.endian)
+            .encode(string, EncoderTrap::Strict)
+            .or(Err(Error::WriteFailure))?;
+        let mut bytes = Vec::with_capacity(encoded.len() + 6);
+        let size = u32::try_from(encoded.len()).or(Err(Error::WriteFailure))?;
+        push_struct(&mut bytes, size, self.endian)?;
+        bytes.extend_from_slice(&encoded);
+        // The length doesn't include it, but these strings are nul-terminated.
+        bytes.extend_from_slice(&[0, 0]);
+        Ok(self.append_bytes(&bytes)?.rva)
+    }
+
+    /// Append `bytes`, which were found at `original` in another minidump, unless
+    /// they've already been copied.
+    fn copy_bytes(
+        &mut self,
+        original: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+        bytes: &[u8],
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let key = (original.data_size, original.rva);
+        if let Some(location) = self.copied.get(&key) {
+            return Ok(*location);
+        }
+        let location = self.append_bytes(bytes)?;
+        self.copied.insert(key, location);
+        Ok(location)
     }
 
-    #[test]
-    fn test_module_list() {
-        let name = DumpString::new("single module", Endian::Little);
-        let cv_record = Section::with_endian(Endian::Little)
-            .D32(md::CvSignature::Pdb70 as u32) // signature
-            // signature, a GUID
-            .D32(0xabcd1234)
-            .D16(0xf00d)
-            .D16(0xbeef)
-            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
-            .D32(1) // age
-            .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
-        let module = SynthModule::new(
-            Endian::Little,
-            0xa90206ca83eb2852,
-            0xada542bd,
-            &name,
-            0xb1054d2a,
-            0x34571371,
-            Some(&STOCK_VERSION_INFO),
-        )
-        .cv_record(&cv_record);
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_module(module)
-            .add(name)
-            .add(cv_record);
-        let dump = read_synth_dump(dump).unwrap();
-        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
-        let modules = module_list.iter().collect::<Vec<_>>();
-        assert_eq!(modules.len(), 1);
-        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
-        assert_eq!(modules[0].size(), 0xada542bd);
-        assert_eq!(modules[0].code_file(), "single module");
-        // time_date_stamp and size_of_image concatenated
-        assert_eq!(modules[0].code_identifier(), "B1054D2Aada542bd");
-        assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
-        assert_eq!(
-            modules[0].debug_identifier().unwrap(),
-            "ABCD1234F00DBEEF01020304050607081"
-        );
+    /// Write `stream` to the minidump and add it to the stream directory.
+    pub fn add_stream<'a, S>(&mut self, stream: &S) -> Result<(), Error>
+    where
+        S: MinidumpStreamWrite<'a>,
+    {
+        let location = stream.write(self)?;
+        self.streams.push(md::MINIDUMP_DIRECTORY {
+            stream_type: S::STREAM_TYPE.into(),
+            location,
+        });
+        Ok(())
     }
 
-    #[test]
-    fn test_unloaded_module_list() {
-        let name = DumpString::new("single module", Endian::Little);
-        let module = SynthUnloadedModule::new(
-            Endian::Little,
-            0xa90206ca83eb2852,
-            0xada542bd,
-            &name,
-            0xb1054d2a,
-            0x34571371,
-        );
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_unloaded_module(module)
-            .add(name);
-        let dump = read_synth_dump(dump).unwrap();
-        let module_list = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
-        let modules = module_list.iter().collect::<Vec<_>>();
-        assert_eq!(modules.len(), 1);
-        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
-        assert_eq!(modules[0].size(), 0xada542bd);
-        assert_eq!(modules[0].code_file(), "single module");
-        // time_date_stamp and size_of_image concatenated
-        assert_eq!(modules[0].code_identifier(), "B1054D2Aada542bd");
+    /// Add a stream with the given type and contents to the minidump.
+    ///
+    /// `bytes` are written as-is, so they shouldn't refer to anything else in the minidump.
+    pub fn add_raw_stream<S>(&mut self, stream_type: S, bytes: &[u8]) -> Result<(), Error>
+    where
+        S: Into<u32>,
+    {
+        let location = self.append_bytes(bytes)?;
+        self.streams.push(md::MINIDUMP_DIRECTORY {
+            stream_type: stream_type.into(),
+            location,
+        });
+        Ok(())
     }
 
-    #[test]
-    fn test_memory_info() {
-        let info1_alloc_protection = md::MemoryProtection::PAGE_GUARD;
-        let info1_protection = md::MemoryProtection::PAGE_EXECUTE_READ;
-        let info1_state = md::MemoryState::MEM_FREE;
-        let info1_ty = md::MemoryType::MEM_MAPPED;
-        let info1 = SynthMemoryInfo::new(
-            Endian::Little,
-            0xa90206ca83eb2852,
-            0xa802064a83eb2752,
-            info1_alloc_protection.bits(),
-            0xf80e064a93eb2356,
-            info1_state.bits(),
-            info1_protection.bits(),
-            info1_ty.bits(),
-        );
+    /// Add an application-defined stream with the given type and contents to the minidump.
+    ///
+    /// This is [`MinidumpWriter::add_raw_stream`][] for streams that aren't part of the
+    /// minidump format. `stream_type` must be in the user-defined range, above
+    /// [`MINIDUMP_STREAM_TYPE::LastReservedStream`][], and there can only be one stream
+    /// of each type. Readers can get `bytes` back with [`Minidump::get_raw_stream`][].
+    pub fn add_user_stream(&mut self, stream_type: u32, bytes: &[u8]) -> Result<(), Error> {
+        if stream_type <= MINIDUMP_STREAM_TYPE::LastReservedStream as u32 {
+            return Err(Error::ReservedStreamType { stream_type });
+        }
+        if self
+            .streams
+            .iter()
+            .any(|stream| stream.stream_type == stream_type)
+        {
+            return Err(Error::DuplicateStreamType { stream_type });
+        }
+        self.add_raw_stream(stream_type, bytes)
+    }
+
+    /// Write the stream directory and header, and then the whole minidump to `writer`.
+    pub fn finish<W: Write>(
+        mut self,
+        time_date_stamp: u32,
+        flags: u64,
+        mut writer: W,
+    ) -> Result<(), Error> {
+        let mut directory = Vec::new();
+        for stream in &self.streams {
+            push_struct(&mut directory, stream.clone(), self.endian)?;
+        }
+        let directory = self.append_bytes(&directory)?;
+        let header = md::MINIDUMP_HEADER {
+            signature: md::MINIDUMP_SIGNATURE,
+            version: md::MINIDUMP_VERSION,
+            stream_count: self.streams.len() as u32,
+            stream_directory_rva: directory.rva,
+            checksum: 0,
+            time_date_stamp,
+            flags,
+        };
+        self.data
+            .pwrite_with(header, 0, self.endian)
+            .or(Err(Error::WriteFailure))?;
+        writer.write_all(&self.data).or(Err(Error::IoError))
+    }
+}
 
-        let info2_alloc_protection = md::MemoryProtection::PAGE_EXECUTE_READ;
-        let info2_protection = md::MemoryProtection::PAGE_READONLY;
-        let info2_state = md::MemoryState::MEM_COMMIT;
-        let info2_ty = md::MemoryType::MEM_PRIVATE;
-        let info2 = SynthMemoryInfo::new(
-            Endian::Little,
-            0xd70206ca83eb2852,
-            0xb802064383eb2752,
-            info2_alloc_protection.bits(),
-            0xe80e064a93eb2356,
-            info2_state.bits(),
-            info2_protection.bits(),
-            info2_ty.bits(),
-        );
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_memory_info(info1)
-            .add_memory_info(info2);
+/// Serialize the raw minidump struct `value` onto the end of `bytes`.
+fn push_struct<T>(bytes: &mut Vec<u8>, value: T, endian: scroll::Endian) -> Result<(), Error>
+where
+    T: SizeWith<scroll::Endian> + TryIntoCtx<scroll::Endian, Error = scroll::Error>,
+{
+    let offset = bytes.len();
+    bytes.resize(offset + T::size_with(&endian), 0);
+    bytes
+        .pwrite_with(value, offset, endian)
+        .or(Err(Error::WriteFailure))?;
+    Ok(())
+}
 
-        let dump = read_synth_dump(dump).unwrap();
+/// Serialize the header of a stream in `read_ex_stream_list`'s format onto `bytes`.
+fn push_ex_stream_list_header<T>(
+    bytes: &mut Vec<u8>,
+    number_of_entries: usize,
+    endian: scroll::Endian,
+) -> Result<(), Error>
+where
+    T: SizeWith<scroll::Endian>,
+{
+    let size_of_header = 3 * mem::size_of::<u32>() as u32;
+    let size_of_entry = T::size_with(&endian) as u32;
+    let number_of_entries = u32::try_from(number_of_entries).or(Err(Error::WriteFailure))?;
+    push_struct(bytes, size_of_header, endian)?;
+    push_struct(bytes, size_of_entry, endian)?;
+    push_struct(bytes, number_of_entries, endian)
+}
 
-        // Read both kinds of info to test this path on UnifiedMemoryInfo
-        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
-        let maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
-        assert!(info_list.is_some());
-        assert!(maps.is_none());
+/// Serialize the count that prefixes a stream in `read_stream_list`'s format onto `bytes`.
+fn push_stream_list_count(
+    bytes: &mut Vec<u8>,
+    count: usize,
+    endian: scroll::Endian,
+) -> Result<(), Error> {
+    let count = u32::try_from(count).or(Err(Error::WriteFailure))?;
+    push_struct(bytes, count, endian)
+}
 
-        let unified_info = UnifiedMemoryInfoList::new(info_list, maps).unwrap();
-        let info_list = unified_info.info().unwrap();
-        assert!(unified_info.maps().is_none());
+/// Serialize a `CodeView` record back into its raw form.
+fn write_codeview(codeview: &CodeView, endian: scroll::Endian) -> Result<Vec<u8>, Error> {
+    let (bytes, result) = match codeview {
+        CodeView::Pdb20(raw) => {
+            let mut bytes = vec![0; 16 + raw.pdb_file_name.len()];
+            let result = bytes.pwrite_with(raw.clone(), 0, endian);
+            (bytes, result)
+        }
+        CodeView::Pdb70(raw) => {
+            let mut bytes = vec![0; 24 + raw.pdb_file_name.len()];
+            let result = bytes.pwrite_with(raw.clone(), 0, endian);
+            (bytes, result)
+        }
+        CodeView::Elf(raw) => {
+            let mut bytes = vec![0; 4 + raw.build_id.len()];
+            let result = bytes.pwrite_with(raw.clone(), 0, endian);
+            (bytes, result)
+        }
+        CodeView::Unknown(bytes) => (bytes.clone(), Ok(bytes.len())),
+    };
+    result.or(Err(Error::WriteFailure))?;
+    Ok(bytes)
+}
 
-        // Assert that unified and the info_list agree
-        for (info, unified) in info_list.iter().zip(unified_info.iter()) {
-            if let UnifiedMemoryInfo::Info(info2) = unified {
-                assert_eq!(info, info2);
-            } else {
-                unreachable!();
+impl<'a> MinidumpStreamWrite<'a> for MinidumpThreadList<'a> {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut bytes = Vec::new();
+        push_stream_list_count(&mut bytes, self.threads.len(), writer.endian)?;
+        for thread in &self.threads {
+            let mut raw = thread.raw.clone();
+            raw.thread_context = match thread.context {
+                Some(context) => writer.copy_bytes(&thread.raw.thread_context, context)?,
+                None => md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+            };
+            // A stack that couldn't be read directly is left for the memory list to provide.
+            raw.stack.memory.rva = 0;
+            if let Some(stack) = thread.direct_stack() {
+                raw.stack.memory = writer.copy_bytes(&thread.raw.stack.memory, stack.bytes)?;
             }
+            push_struct(&mut bytes, raw, writer.endian)?;
         }
+        writer.append_bytes(&bytes)
+    }
+}
 
-        let infos = info_list.iter().collect::<Vec<_>>();
+impl<'a> MinidumpStreamWrite<'a> for MinidumpModuleList {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut bytes = Vec::new();
+        push_stream_list_count(&mut bytes, self.modules.len(), writer.endian)?;
+        for module in &self.modules {
+            let mut raw = module.raw.clone();
+            raw.module_name_rva = writer.append_string_utf16(&module.name)?;
+            raw.cv_record = match &module.codeview_info {
+                Some(codeview) => writer.append_bytes(&write_codeview(codeview, writer.endian)?)?,
+                None => md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+            };
+            // We don't read misc records, so there's nothing to write.
+            raw.misc_record = md::MINIDUMP_LOCATION_DESCRIPTOR::default();
+            push_struct(&mut bytes, raw, writer.endian)?;
+        }
+        writer.append_bytes(&bytes)
+    }
+}
 
-        assert_eq!(infos.len(), 2);
+impl<'a> MinidumpStreamWrite<'a> for MinidumpUnloadedModuleList {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut bytes = Vec::new();
+        push_ex_stream_list_header::<md::MINIDUMP_UNLOADED_MODULE>(
+            &mut bytes,
+            self.modules.len(),
+            writer.endian,
+        )?;
+        for module in &self.modules {
+            let mut raw = module.raw.clone();
+            raw.module_name_rva = writer.append_string_utf16(&module.name)?;
+            push_struct(&mut bytes, raw, writer.endian)?;
+        }
+        writer.append_bytes(&bytes)
+    }
+}
 
-        assert_eq!(infos[0].raw.base_address, 0xa90206ca83eb2852);
-        assert_eq!(infos[0].raw.allocation_base, 0xa802064a83eb2752);
-        assert_eq!(
-            infos[0].raw.allocation_protection,
-            info1_alloc_protection.bits()
-        );
-        assert_eq!(infos[0].raw.region_size, 0xf80e064a93eb2356);
-        assert_eq!(infos[0].raw.state, info1_state.bits());
-        assert_eq!(infos[0].raw.protection, info1_protection.bits());
-        assert_eq!(infos[0].raw._type, info1_ty.bits());
+impl<'a> MinidumpStreamWrite<'a> for MinidumpMemoryList<'a> {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut bytes = Vec::new();
+        push_stream_list_count(&mut bytes, self.regions.len(), writer.endian)?;
+        for region in &self.regions {
+            let mut desc = region.desc;
+            desc.memory = writer.copy_bytes(&region.desc.memory, region.bytes)?;
+            push_struct(&mut bytes, desc, writer.endian)?;
+        }
+        writer.append_bytes(&bytes)
+    }
+}
 
-        assert_eq!(infos[0].allocation_protection, info1_alloc_protection);
-        assert_eq!(infos[0].protection, info1_protection);
-        assert_eq!(infos[0].state, info1_state);
-        assert_eq!(infos[0].ty, info1_ty);
-        assert!(infos[0].is_executable());
+impl<'a> MinidumpStreamWrite<'a> for MinidumpMemory64List<'a> {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        // The memory of every region is stored back to back.
+        let memory = self
+            .regions
+            .iter()
+            .flat_map(|region| region.bytes.iter().copied())
+            .collect::<Vec<u8>>();
+        let base_rva = writer.append_bytes(&memory)?.rva;
+
+        let mut bytes = Vec::new();
+        let header = md::MINIDUMP_MEMORY64_LIST {
+            number_of_memory_ranges: self.regions.len() as u64,
+            base_rva: base_rva as u64,
+        };
+        push_struct(&mut bytes, header, writer.endian)?;
+        for region in &self.regions {
+            let mut desc = region.desc;
+            desc.data_size = region.bytes.len() as u64;
+            push_struct(&mut bytes, desc, writer.endian)?;
+        }
+        writer.append_bytes(&bytes)
+    }
+}
 
-        assert_eq!(infos[1].raw.base_address, 0xd70206ca83eb2852);
-        assert_eq!(infos[1].raw.allocation_base, 0xb802064383eb2752);
-        assert_eq!(
-            infos[1].raw.allocation_protection,
-            info2_alloc_protection.bits()
-        );
-        assert_eq!(infos[1].raw.region_size, 0xe80e064a93eb2356);
-        assert_eq!(infos[1].raw.state, info2_state.bits());
-        assert_eq!(infos[1].raw.protection, info2_protection.bits());
-        assert_eq!(infos[1].raw._type, info2_ty.bits());
+impl<'a> MinidumpStreamWrite<'a> for MinidumpException<'a> {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut raw = self.raw.clone();
+        raw.thread_context = match self.context {
+            Some(context) => writer.copy_bytes(&self.raw.thread_context, context)?,
+            None => md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+        };
+        writer.append_struct(raw)
+    }
+}
 
-        assert_eq!(infos[1].allocation_protection, info2_alloc_protection);
-        assert_eq!(infos[1].protection, info2_protection);
-        assert_eq!(infos[1].state, info2_state);
-        assert_eq!(infos[1].ty, info2_ty);
-        assert!(!infos[1].is_executable());
+impl<'a> MinidumpStreamWrite<'a> for MinidumpSystemInfo {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut raw = self.raw.clone();
+        raw.csd_version_rva = match &self.csd_version {
+            Some(csd_version) => writer.append_string_utf16(csd_version)?,
+            None => 0,
+        };
+        writer.append_struct(raw)
     }
+}
 
-    #[test]
-    fn test_linux_maps() {
-        // Whitespace intentionally wonky to test robustness
-        let input = b"
+impl<'a> MinidumpStreamWrite<'a> for MinidumpHandleDataStream {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut descriptors = Vec::new();
+        let mut size_of_descriptor = md::MINIDUMP_HANDLE_DESCRIPTOR::size_with(&writer.endian);
+        for handle in &self.handles {
+            let type_name_rva = match &handle.type_name {
+                Some(type_name) => writer.append_string_utf16(type_name)?,
+                None => 0,
+            };
+            let object_name_rva = match &handle.object_name {
+                Some(object_name) => writer.append_string_utf16(object_name)?,
+                None => 0,
+            };
+            match &handle.raw {
+                RawHandleDescriptor::HandleDescriptor(raw) => {
+                    let mut raw = raw.clone();
+                    raw.type_name_rva = type_name_rva;
+                    raw.object_name_rva = object_name_rva;
+                    push_struct(&mut descriptors, raw, writer.endian)?;
+                }
+                RawHandleDescriptor::HandleDescriptor2(raw) => {
+                    size_of_descriptor =
+                        md::MINIDUMP_HANDLE_DESCRIPTOR_2::size_with(&writer.endian);
+                    let mut raw = raw.clone();
+                    raw.type_name_rva = type_name_rva;
+                    raw.object_name_rva = object_name_rva;
+                    // We don't read the object info, so there's nothing to point at.
+                    raw.object_info_rva = 0;
+                    push_struct(&mut descriptors, raw, writer.endian)?;
+                }
+            }
+        }
 
- a90206ca83eb2852-b90206ca83eb3852 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  
-c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libtdb2.so  (deleted)
+        let size_of_header = md::MINIDUMP_HANDLE_DATA_STREAM::size_with(&writer.endian);
+        let header = md::MINIDUMP_HANDLE_DATA_STREAM {
+            size_of_header: size_of_header as u32,
+            size_of_descriptor: size_of_descriptor as u32,
+            number_of_descriptors: u32::try_from(self.handles.len())
+                .or(Err(Error::WriteFailure))?,
+            reserved: 0,
+        };
+        let mut bytes = Vec::new();
+        push_struct(&mut bytes, header, writer.endian)?;
+        bytes.extend_from_slice(&descriptors);
+        writer.append_bytes(&bytes)
+    }
+}
 
+impl<'a> MinidumpStreamWrite<'a> for MinidumpThreadNames {
+    fn write(
+        &self,
+        writer: &mut MinidumpWriter,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        let mut names = self.names.iter().collect::<Vec<_>>();
+        names.sort();
+
+        let mut bytes = Vec::new();
+        push_stream_list_count(&mut bytes, names.len(), writer.endian)?;
+        for (&thread_id, name) in names {
+            let raw = md::MINIDUMP_THREAD_NAME {
+                thread_id,
+                thread_name_rva: writer.append_string_utf16(name)? as md::RVA64,
+            };
+            push_struct(&mut bytes, raw, writer.endian)?;
+        }
+        writer.append_bytes(&bytes)
+    }
+}
 
-";
+/// A function that finds the parts of a command line argument to redact.
+///
+/// See [`ScrubPolicy::cmdline_patterns`][].
+pub type ScrubPattern = Box<dyn Fn(&str) -> Vec<std::ops::Range<usize>>>;
 
-        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_maps(input);
-        let dump = read_synth_dump(dump).unwrap();
+/// What a [`MinidumpScrubber`][] should remove from a minidump.
+///
+/// By default the environment and command line are dropped, and memory is left alone.
+pub struct ScrubPolicy {
+    /// Drop the `LinuxEnviron` stream, which has the process's environment variables.
+    pub drop_environ: bool,
+    /// Drop the `LinuxCmdLine` stream, which has the process's command line.
+    pub drop_cmdline: bool,
+    /// Environment variables to remove from the `LinuxEnviron` stream, if it isn't dropped.
+    ///
+    /// The patterns are the same as for [`MinidumpLinuxEnviron::redacted`][].
+    pub environ_deny_list: Vec<String>,
+    /// Zero all memory that isn't part of a thread's stack.
+    ///
+    /// Stacks are needed to unwind, but the rest of memory is mostly heap,
+    /// which is exactly where passwords and keys tend to be.
+    pub zero_non_stack_memory: bool,
+    /// Zero all memory outside of these address ranges, including thread stacks.
+    ///
+    /// This is for keeping only the memory that's actually needed, e.g. the
+    /// parts of the stacks that were used to unwind them.
+    pub keep_memory: Option<Vec<std::ops::Range<u64>>>,
+    /// Patterns to redact from the command line, if it isn't dropped.
+    ///
+    /// Each pattern is given the arguments one at a time, and returns the byte
+    /// ranges of it to redact. These are overwritten with `*`, because the
+    /// minidump is modified in-place. This makes it easy to use regexes:
+    ///
+    /// ```ignore
+    /// let token = regex::Regex::new("--token=.*").unwrap();
+    /// policy.cmdline_patterns.push(Box::new(move |arg| {
+    ///     token.find_iter(arg).map(|m| m.range()).collect()
+    /// }));
+    /// ```
+    ///
+    /// Arguments that aren't valid UTF-8 can't be matched, so they're redacted entirely.
+    pub cmdline_patterns: Vec<ScrubPattern>,
+}
 
-        // Read both kinds of info to test this path on UnifiedMemoryInfo
-        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
-        let maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
-        assert!(info_list.is_none());
-        assert!(maps.is_some());
+impl Default for ScrubPolicy {
+    fn default() -> Self {
+        ScrubPolicy {
+            drop_environ: true,
+            drop_cmdline: true,
+            environ_deny_list: Vec::new(),
+            zero_non_stack_memory: false,
+            keep_memory: None,
+            cmdline_patterns: Vec::new(),
+        }
+    }
+}
 
-        let unified_info = UnifiedMemoryInfoList::new(info_list, maps).unwrap();
-        let maps = unified_info.maps().unwrap();
-        assert!(unified_info.info().is_none());
+impl fmt::Debug for ScrubPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScrubPolicy")
+            .field("drop_environ", &self.drop_environ)
+            .field("drop_cmdline", &self.drop_cmdline)
+            .field("environ_deny_list", &self.environ_deny_list)
+            .field("zero_non_stack_memory", &self.zero_non_stack_memory)
+            .field("keep_memory", &self.keep_memory)
+            .field("cmdline_patterns", &self.cmdline_patterns.len())
+            .finish()
+    }
+}
 
-        // Assert that unified and the maps agree
-        for (info, unified) in maps.iter().zip(unified_info.iter()) {
-            if let UnifiedMemoryInfo::Map(info2) = unified {
-                assert_eq!(info, info2);
-            } else {
-                unreachable!();
+/// Removes personally identifiable information from a [`Minidump`][].
+///
+/// The minidump is modified in-place: dropped streams are zeroed and removed
+/// from the stream directory, so combine this with [`Minidump::write`][] to
+/// produce a scrubbed file without them.
+///
+/// ```
+/// use minidump::*;
+///
+/// fn main() -> Result<(), Error> {
+///     let bytes = std::fs::read("../testdata/test.dmp").or(Err(Error::IoError))?;
+///     let mut dump = Minidump::read(bytes)?;
+///
+///     let policy = ScrubPolicy {
+///         zero_non_stack_memory: true,
+///         ..ScrubPolicy::default()
+///     };
+///     MinidumpScrubber::new(policy).apply(&mut dump)?;
+///
+///     let mut scrubbed = Vec::new();
+///     dump.write(&mut scrubbed)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MinidumpScrubber {
+    policy: ScrubPolicy,
+}
+
+impl MinidumpScrubber {
+    /// Create a `MinidumpScrubber` that scrubs according to `policy`.
+    pub fn new(policy: ScrubPolicy) -> MinidumpScrubber {
+        MinidumpScrubber { policy }
+    }
+
+    /// Scrub `dump` in-place.
+    pub fn apply<'a, T>(&self, dump: &mut Minidump<'a, T>) -> Result<(), Error>
+    where
+        T: DerefMut<Target = [u8]> + 'a,
+    {
+        let mut dropped = Vec::new();
+        if self.policy.drop_environ {
+            dropped.push(MINIDUMP_STREAM_TYPE::LinuxEnviron);
+        }
+        if self.policy.drop_cmdline {
+            dropped.push(MINIDUMP_STREAM_TYPE::LinuxCmdLine);
+        }
+        // Scrub duplicate streams too, or get_all_streams would still find them.
+        for stream_type in dropped {
+            let stream_type = stream_type as u32;
+            for location in dump.stream_locations(stream_type) {
+                location_slice_mut(&mut dump.data, &location)?.fill(0);
             }
+            dump.streams.remove(&stream_type);
+            dump.shadowed_streams
+                .retain(|(_, dir)| dir.stream_type != stream_type);
         }
 
-        let maps = maps.iter().collect::<Vec<_>>();
-        assert_eq!(maps.len(), 2);
+        if !self.policy.environ_deny_list.is_empty() {
+            let environ = MINIDUMP_STREAM_TYPE::LinuxEnviron as u32;
+            for location in dump.stream_locations(environ) {
+                let bytes = location_slice_mut(&mut dump.data, &location)?;
+                self.scrub_environ(bytes);
+            }
+        }
 
-        assert_eq!(maps[0].base_address, 0xa90206ca83eb2852);
-        assert_eq!(maps[0].final_address, 0xb90206ca83eb3852);
-        assert_eq!(
-            maps[0].kind,
-            MinidumpLinuxMapKind::File(Cow::Borrowed(LinuxOsStr::from_bytes(
-                b"/usr/lib64/libtdb1.so"
-            )))
-        );
-        assert!(maps[0].is_read);
-        assert!(!maps[0].is_write);
-        assert!(maps[0].is_exec);
-        assert!(maps[0].is_private);
-        assert!(!maps[0].is_shared);
-        assert!(maps[0].is_executable());
+        if !self.policy.cmdline_patterns.is_empty() {
+            let cmdline = MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32;
+            for location in dump.stream_locations(cmdline) {
+                let bytes = location_slice_mut(&mut dump.data, &location)?;
+                for arg in bytes.split_mut(|&b| b == 0) {
+                    self.scrub_arg(arg);
+                }
+            }
+        }
 
-        assert_eq!(maps[1].base_address, 0xc70206ca83eb2852);
-        assert_eq!(maps[1].final_address, 0xde0206ca83eb2852);
-        assert_eq!(
-            maps[1].kind,
-            MinidumpLinuxMapKind::DeletedFile(Cow::Borrowed(LinuxOsStr::from_bytes(
-                b"/usr/lib64/libtdb2.so"
-            )))
-        );
-        assert!(!maps[1].is_read);
-        assert!(maps[1].is_write);
-        assert!(!maps[1].is_exec);
-        assert!(!maps[1].is_private);
-        assert!(maps[1].is_shared);
-        assert!(!maps[1].is_executable());
-    }
+        if self.policy.zero_non_stack_memory || self.policy.keep_memory.is_some() {
+            let (regions, stacks) = memory_locations(dump)?;
+            let mut zeroed = Vec::new();
+            if self.policy.zero_non_stack_memory {
+                let stacks = stacks
+                    .iter()
+                    .map(|&(address, size, _)| address..address.saturating_add(size))
+                    .collect::<Vec<_>>();
+                zeroed.extend(memory_outside(&regions, &stacks));
+            }
+            if let Some(keep_memory) = &self.policy.keep_memory {
+                zeroed.extend(memory_outside(&regions, keep_memory));
+                zeroed.extend(memory_outside(&stacks, keep_memory));
+            }
+            for (rva, len) in zeroed {
+                let start = rva.min(dump.data.len());
+                let end = rva.saturating_add(len).min(dump.data.len());
+                dump.data[start..end].fill(0);
+            }
+        }
 
-    #[test]
-    fn test_linux_map_parse() {
-        use MinidumpLinuxMapKind::*;
+        Ok(())
+    }
 
-        let parse = |input| {
-            let string = LinuxOsStr::from_bytes(input);
-            MinidumpLinuxMapInfo::from_line(string)
+    /// Remove the denied variables from a `LinuxEnviron` stream, moving the
+    /// rest down and zeroing the space left over at the end.
+    fn scrub_environ(&self, bytes: &mut [u8]) {
+        let mut kept = Vec::with_capacity(bytes.len());
+        for entry in bytes.split(|&b| b == 0).filter(|entry| !entry.is_empty()) {
+            let name = entry.split(|&b| b == b'=').next().unwrap_or(entry);
+            let name = String::from_utf8_lossy(name);
+            if !env_var_matches(&self.policy.environ_deny_list, &name) {
+                kept.extend_from_slice(entry);
+                kept.push(0);
+            }
+        }
+        // This only drops the final NUL if nothing was removed and the stream
+        // didn't end with one.
+        kept.resize(bytes.len(), 0);
+        bytes.copy_from_slice(&kept);
+    }
+
+    fn scrub_arg(&self, arg: &mut [u8]) {
+        let ranges: Vec<_> = match str::from_utf8(arg) {
+            Ok(arg) => self
+                .policy
+                .cmdline_patterns
+                .iter()
+                .flat_map(|pattern| pattern(arg))
+                .collect(),
+            Err(_) => iter::once(0..arg.len()).collect(),
         };
+        for range in ranges {
+            let end = range.end.min(arg.len());
+            if range.start < end {
+                arg[range.start..end].fill(b'*');
+            }
+        }
+    }
+}
 
-        // Whitespace intentionally wonky to test parser robustness
+/// The address, size, and rva of a region of memory in a minidump.
+type MemoryLocation = (u64, u64, u64);
 
-        {
-            // Normal file
-            let map = parse(b"  10a00-10b00 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
-            let map = map.unwrap();
+/// Find the location of every region of `dump`'s memory, and of every thread's stack.
+///
+/// The stacks are usually also in the memory list, but aren't required to be.
+fn memory_locations<'a, T>(
+    dump: &Minidump<'a, T>,
+) -> Result<(Vec<MemoryLocation>, Vec<MemoryLocation>), Error>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let raw_stream = |stream_type: MINIDUMP_STREAM_TYPE| {
+        dump.streams
+            .get(&(stream_type as u32))
+            .map(|(_, stream)| location_slice(&dump.data, &stream.location))
+            .transpose()
+    };
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(
-                map.kind,
-                File(Cow::Borrowed(LinuxOsStr::from_bytes(
-                    b"/usr/lib64/libtdb1.so"
-                )))
-            );
+    let mut stacks = Vec::new();
+    if let Some(bytes) = raw_stream(MINIDUMP_STREAM_TYPE::ThreadListStream)? {
+        let threads: Vec<md::MINIDUMP_THREAD> = read_stream_list(&mut 0, bytes, dump.endian)?;
+        stacks.extend(threads.iter().map(|thread| {
+            (
+                thread.stack.start_of_memory_range,
+                thread.stack.memory.data_size as u64,
+                thread.stack.memory.rva as u64,
+            )
+        }));
+    }
+
+    let mut regions = Vec::new();
+    if let Some(bytes) = raw_stream(MINIDUMP_STREAM_TYPE::MemoryListStream)? {
+        let descs: Vec<md::MINIDUMP_MEMORY_DESCRIPTOR> =
+            read_stream_list(&mut 0, bytes, dump.endian)?;
+        regions.extend(descs.iter().map(|desc| {
+            (
+                desc.start_of_memory_range,
+                desc.memory.data_size as u64,
+                desc.memory.rva as u64,
+            )
+        }));
+    }
+    if let Some(bytes) = raw_stream(MINIDUMP_STREAM_TYPE::Memory64ListStream)? {
+        let descs = read_memory64_descriptors(bytes, dump.endian)?;
+        regions.extend(
+            descs
+                .iter()
+                .map(|(desc, rva)| (desc.start_of_memory_range, desc.data_size, *rva)),
+        );
+    }
+    Ok((regions, stacks))
+}
 
-            assert!(map.is_read);
-            assert!(!map.is_write);
-            assert!(map.is_exec);
-            assert!(map.is_executable());
-            assert!(map.is_private);
-            assert!(!map.is_shared);
+/// Find the (rva, length) of every part of `regions` that isn't in one of the
+/// `kept` address ranges.
+fn memory_outside(
+    regions: &[MemoryLocation],
+    kept: &[std::ops::Range<u64>],
+) -> Vec<(usize, usize)> {
+    // Full-memory dumps have the stacks inside larger regions, so carve them out.
+    let mut result = Vec::new();
+    for &(address, size, rva) in regions {
+        let end = address.saturating_add(size);
+        let mut overlaps = kept
+            .iter()
+            .map(|range| (range.start.max(address), range.end.min(end)))
+            .filter(|(start, kept_end)| start < kept_end)
+            .collect::<Vec<_>>();
+        overlaps.sort_unstable();
+        overlaps.push((end, end));
+
+        let mut cursor = address;
+        for (start, kept_end) in overlaps {
+            if start > cursor {
+                let offset = rva.saturating_add(cursor - address);
+                result.push((offset as usize, (start - cursor) as usize));
+            }
+            cursor = cursor.max(kept_end);
         }
+    }
+    result
+}
 
-        {
-            // Deleted file (also some whitespace in the file name)
-            let map = parse(b"ffffffffff600000-ffffffffff601000 -wxs  10bac9000 fd:05 1196511  /usr/lib64/ libtdb1.so   (deleted) ");
-            let map = map.unwrap();
-
-            assert_eq!(map.base_address, 0xffffffffff600000);
-            assert_eq!(map.final_address, 0xffffffffff601000);
-            assert_eq!(
-                map.kind,
-                DeletedFile(Cow::Borrowed(LinuxOsStr::from_bytes(
-                    b"/usr/lib64/ libtdb1.so"
-                )))
-            );
+fn location_slice_mut<'a>(
+    bytes: &'a mut [u8],
+    loc: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+) -> Result<&'a mut [u8], Error> {
+    let start = loc.rva as usize;
+    start
+        .checked_add(loc.data_size as usize)
+        .and_then(move |end| bytes.get_mut(start..end))
+        .ok_or(Error::StreamReadFailure)
+}
 
-            assert!(!map.is_read);
-            assert!(map.is_write);
-            assert!(map.is_exec);
-            assert!(map.is_executable());
-            assert!(!map.is_private);
-            assert!(map.is_shared);
+fn stream_vendor(stream_type: u32) -> &'static str {
+    if stream_type <= MINIDUMP_STREAM_TYPE::LastReservedStream as u32 {
+        "Official"
+    } else {
+        match stream_type & 0xFFFF0000 {
+            0x4767_0000 => "Google Extension",
+            0x4d7a_0000 => "Mozilla Extension",
+            _ => "Unknown Extension",
         }
+    }
+}
 
-        {
-            // Stack
-            let map = parse(b"10a00-10b00 -------  10bac9000 fd:05 1196511  [stack] ");
-            let map = map.unwrap();
+#[cfg(test)]
+mod test {
+    use super::*;
+    use md::GUID;
+    use minidump_common::format::ProcessorArchitecture;
+    use std::mem;
+    use synth_minidump::{
+        self, AnnotationValue, CpuInfo, CrashpadInfo, DumpSection, DumpString, Exception,
+        FunctionTable as SynthFunctionTable, ListStream, Memory, MemoryInfo as SynthMemoryInfo,
+        MiscFieldsBuildString, MiscFieldsPowerInfo, MiscFieldsProcessTimes, MiscFieldsTimeZone,
+        MiscInfo5Fields, MiscStream, Module as SynthModule, ModuleCrashpadInfo, SectionExtra,
+        SimpleStream, SynthMinidump, SystemInfo, Thread, ThreadInfo as SynthThreadInfo, ThreadName,
+        UnloadedModule as SynthUnloadedModule, STOCK_VERSION_INFO,
+    };
+    use test_assembler::*;
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(map.kind, MainThreadStack);
+    fn read_synth_dump<'a>(dump: SynthMinidump) -> Result<Minidump<'a, Vec<u8>>, Error> {
+        Minidump::read(dump.finish().unwrap())
+    }
 
-            assert!(!map.is_read);
-            assert!(!map.is_write);
-            assert!(!map.is_exec);
-            assert!(!map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
-        }
+    #[test]
+    fn test_simple_synth_dump() {
+        const STREAM_TYPE: u32 = 0x11223344;
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: STREAM_TYPE,
+            section: Section::with_endian(Endian::Little).D32(0x55667788),
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.endian, LE);
+        assert_eq!(
+            dump.get_raw_stream(STREAM_TYPE).unwrap(),
+            &[0x88, 0x77, 0x66, 0x55]
+        );
 
-        {
-            // Stack with tid
-            let map = parse(b"10a00-10b00 -------  10bac9000 fd:05 1196511  [stack:1234567] ");
-            let map = map.unwrap();
+        assert_eq!(dump.get_raw_stream(0xaabbccddu32), None);
+    }
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(map.kind, Stack(1234567));
+    #[test]
+    fn test_simple_synth_dump_bigendian() {
+        const STREAM_TYPE: u32 = 0x11223344;
+        let dump = SynthMinidump::with_endian(Endian::Big).add_stream(SimpleStream {
+            stream_type: STREAM_TYPE,
+            section: Section::with_endian(Endian::Big).D32(0x55667788),
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.endian, BE);
+        assert_eq!(
+            dump.get_raw_stream(STREAM_TYPE).unwrap(),
+            &[0x55, 0x66, 0x77, 0x88]
+        );
 
-            assert!(!map.is_read);
-            assert!(!map.is_write);
-            assert!(!map.is_exec);
-            assert!(!map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
-        }
+        assert_eq!(dump.get_raw_stream(0xaabbccddu32), None);
+    }
 
-        {
-            // Heap
-            let map = parse(b"10a00-10b00 --  10bac9000 fd:05 1196511  [heap]");
-            let map = map.unwrap();
+    #[test]
+    fn test_streams() {
+        let stream = |stream_type: u32, len: usize| SimpleStream {
+            stream_type,
+            section: Section::with_endian(Endian::Little).append_repeated(0, len),
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(stream(0x11223344, 4))
+            .add_stream(stream(MINIDUMP_STREAM_TYPE::MiscInfoStream as u32, 24))
+            .add_stream(stream(0x11223344, 8))
+            .add_stream(stream(0x55667788, 0));
+        let dump = read_synth_dump(dump).unwrap();
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(map.kind, Heap);
+        let streams = dump.streams().collect::<Vec<_>>();
+        // Both copies of the duplicated stream are included.
+        let indices = streams.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        assert_eq!(indices, [0, 1, 2, 3]);
+        let types = streams
+            .iter()
+            .map(|(_, stream)| stream.stream_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                0x11223344,
+                MINIDUMP_STREAM_TYPE::MiscInfoStream as u32,
+                0x11223344,
+                0x55667788
+            ]
+        );
+        let sizes = streams
+            .iter()
+            .map(|(_, stream)| stream.data_size)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [4, 24, 8, 0]);
 
-            assert!(!map.is_read);
-            assert!(!map.is_write);
-            assert!(!map.is_exec);
-            assert!(!map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
+        for (_, stream) in streams {
+            let contents = dump.stream_contents(&stream).unwrap();
+            assert_eq!(contents.len(), stream.data_size as usize);
+            let offset = contents.as_ptr() as usize - dump.data.as_ptr() as usize;
+            assert_eq!(offset, stream.file_offset as usize);
         }
+    }
 
-        {
-            // Vdso
-            let map = parse(b"10a00-10b00 r-wx-  10bac9000 fd:05 1196511  [vdso]");
-            let map = map.unwrap();
+    #[test]
+    fn test_user_streams() {
+        let stream = |stream_type: u32, contents: &[u8]| SimpleStream {
+            stream_type,
+            section: Section::with_endian(Endian::Little).append_bytes(contents),
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(stream(0x8000_0001, b"request-id=1234"))
+            .add_stream(stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32, b"a.out"))
+            .add_stream(stream(0x0001_0000, b""))
+            .add_stream(stream(0xabcd_0000, b"\x01\x02\x03"));
+        let dump = read_synth_dump(dump).unwrap();
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(map.kind, Vdso);
+        let expected = [
+            MinidumpUserStream {
+                stream_type: 0x8000_0001,
+                data: b"request-id=1234",
+            },
+            MinidumpUserStream {
+                stream_type: 0x0001_0000,
+                data: b"",
+            },
+            MinidumpUserStream {
+                stream_type: 0xabcd_0000,
+                data: b"\x01\x02\x03",
+            },
+        ];
+        assert_eq!(dump.user_streams().collect::<Vec<_>>(), expected);
 
-            assert!(map.is_read);
-            assert!(map.is_write);
-            assert!(map.is_exec);
-            assert!(map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
-        }
+        // They survive being written back out verbatim.
+        let mut written = Vec::new();
+        dump.write(&mut written).unwrap();
+        let dump = Minidump::read(&written[..]).unwrap();
+        assert_eq!(dump.user_streams().collect::<Vec<_>>(), expected);
+    }
 
-        {
-            // Unknown Special
-            let map = parse(b"10a00-10b00 r-wx-  10bac9000 fd:05 1196511  [asdfasd]");
-            let map = map.unwrap();
+    #[test]
+    fn test_duplicate_user_streams() {
+        let stream = |stream_type: u32, contents: &[u8]| SimpleStream {
+            stream_type,
+            section: Section::with_endian(Endian::Little).append_bytes(contents),
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(stream(0x8000_0001, b"first"))
+            .add_stream(stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32, b"a.out"))
+            .add_stream(stream(0x8000_0001, b"second"));
+        let dump = read_synth_dump(dump).unwrap();
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(
-                map.kind,
-                UnknownSpecial(Cow::Borrowed(LinuxOsStr::from_bytes(b"[asdfasd]")))
+        // Both are yielded, in directory order, not just the one `get_raw_stream` sees.
+        let expected = [
+            MinidumpUserStream {
+                stream_type: 0x8000_0001,
+                data: b"first",
+            },
+            MinidumpUserStream {
+                stream_type: 0x8000_0001,
+                data: b"second",
+            },
+        ];
+        assert_eq!(dump.user_streams().collect::<Vec<_>>(), expected);
+        assert_eq!(dump.get_raw_stream(0x8000_0001u32).unwrap(), b"second");
+
+        let raw = dump
+            .raw_streams()
+            .map(|(stream, data)| (stream.stream_type, data))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            raw,
+            [
+                (0x8000_0001, &b"first"[..]),
+                (MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32, &b"a.out"[..]),
+                (0x8000_0001, &b"second"[..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_streams() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: 0x4d7a_0123,
+                section: Section::with_endian(Endian::Little).append_bytes(b"custom"),
+            })
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+                section: Section::with_endian(Endian::Little).append_bytes(b"a.out"),
+            });
+        let dump = read_synth_dump(dump).unwrap();
+
+        let streams = dump.raw_streams().collect::<Vec<_>>();
+        assert_eq!(streams.len(), 2);
+        let (custom, custom_data) = streams[0];
+        assert_eq!(custom.stream_type, 0x4d7a_0123);
+        assert_eq!(custom.data_size, 6);
+        assert_eq!(custom_data, b"custom");
+        assert_eq!(
+            custom_data.as_ptr() as usize - dump.data.as_ptr() as usize,
+            custom.file_offset as usize
+        );
+        let (cmdline, cmdline_data) = streams[1];
+        assert_eq!(
+            cmdline.stream_type,
+            MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32
+        );
+        assert_eq!(cmdline_data, b"a.out");
+
+        let mut output = Vec::new();
+        dump.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "  stream_type        = 0x4d7a0123 (unknown, Mozilla Extension)\n  location.data_size = 6\n"
+        ));
+        assert!(
+            output.contains("  stream type 0x4d7a0123 (unknown, Mozilla Extension) at index 0\n")
+        );
+    }
+
+    #[test]
+    fn test_is_system_module() {
+        let is_system = |name: &str| MinidumpModule::new(0x1000, 0x1000, name).is_system_module();
+
+        // Windows
+        assert!(is_system("C:\\Windows\\System32\\ntdll.dll"));
+        assert!(is_system("c:\\windows\\SysWOW64\\foo.dll"));
+        assert!(is_system("D:\\elsewhere\\KERNELBASE.dll"));
+        assert!(!is_system("C:\\Program Files\\Mozilla Firefox\\xul.dll"));
+        assert!(!is_system("C:\\Windows\\Temp\\injected.dll"));
+
+        // macOS
+        assert!(is_system(
+            "/System/Library/Frameworks/CoreFoundation.framework/Versions/A/CoreFoundation"
+        ));
+        assert!(is_system("/usr/lib/system/libsystem_kernel.dylib"));
+        assert!(is_system("/usr/lib/libobjc.A.dylib"));
+        assert!(!is_system("/Applications/Firefox.app/Contents/MacOS/XUL"));
+
+        // Linux
+        assert!(is_system("/lib/x86_64-linux-gnu/libc-2.31.so"));
+        assert!(is_system("/usr/lib64/libstdc++.so.6"));
+        assert!(is_system("/opt/glibc/libpthread.so.0"));
+        assert!(is_system("linux-gate.so"));
+        assert!(!is_system("/home/user/firefox/libxul.so"));
+        assert!(!is_system("/home/user/firefox/libcrashreporter.so"));
+        assert!(!is_system(""));
+    }
+
+    #[test]
+    fn test_is_system_module_with() {
+        let module = MinidumpModule::new(0x1000, 0x1000, "/opt/vendor/lib/libvendor.so");
+        assert!(!module.is_system_module());
+
+        let mut patterns = SystemModulePatterns::default();
+        assert!(!module.is_system_module_with(&patterns));
+        patterns.path_prefixes.push(String::from("/opt/vendor/"));
+        assert!(module.is_system_module_with(&patterns));
+
+        let patterns = SystemModulePatterns {
+            path_prefixes: vec![],
+            name_prefixes: vec![String::from("LIBVENDOR")],
+        };
+        assert!(module.is_system_module_with(&patterns));
+        let ntdll = MinidumpModule::new(0x1000, 0x1000, "C:\\Windows\\System32\\ntdll.dll");
+        assert!(!ntdll.is_system_module_with(&patterns));
+    }
+
+    #[test]
+    fn test_debug_file_url() {
+        let signature = md::GUID {
+            data1: 0xabcd1234,
+            data2: 0xf00d,
+            data3: 0xbeef,
+            data4: [1, 2, 3, 4, 5, 6, 7, 8],
+        };
+        let pdb70 = |pdb_file_name: &[u8]| {
+            Some(CodeView::Pdb70(md::CV_INFO_PDB70 {
+                cv_signature: md::CvSignature::Pdb70 as u32,
+                signature,
+                age: 1,
+                pdb_file_name: pdb_file_name.to_vec(),
+            }))
+        };
+
+        let mut pe = MinidumpModule::new(0x1000, 0x1000, "c:\\foo\\file.dll");
+        pe.codeview_info = pdb70(b"c:\\foo\\File.PDB\0");
+        assert_eq!(
+            pe.debug_file_url("https://symbols.example.com/").unwrap(),
+            "https://symbols.example.com/File.PDB/ABCD1234F00DBEEF01020304050607081/File.sym"
+        );
+
+        let mut elf = MinidumpModule::new(0x1000, 0x1000, "/usr/lib/libfoo.so");
+        elf.codeview_info = Some(CodeView::Elf(md::CV_INFO_ELF {
+            cv_signature: md::CvSignature::Elf as u32,
+            build_id: (0..20).collect(),
+        }));
+        assert_eq!(
+            elf.debug_file_url("https://symbols.example.com").unwrap(),
+            "https://symbols.example.com/libfoo.so/030201000504070608090A0B0C0D0E0F0/libfoo.so.sym"
+        );
+
+        let mut macho = MinidumpModule::new(0x1000, 0x1000, "/Frameworks/Electron Framework");
+        macho.codeview_info = pdb70(b"Electron Framework\0");
+        assert_eq!(
+            macho.debug_file_url("https://symbols.example.com").unwrap(),
+            "https://symbols.example.com/Electron%20Framework/ABCD1234F00DBEEF01020304050607081/Electron%20Framework.sym"
+        );
+
+        // Without CodeView info there's nothing to look symbols up by.
+        let unknown = MinidumpModule::new(0x1000, 0x1000, "a.out");
+        assert_eq!(unknown.debug_file_url("https://symbols.example.com"), None);
+    }
+
+    #[test]
+    fn test_memory_usage_report() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+                section: Section::with_endian(Endian::Little).append_bytes(b"a.out"),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x4d7a_0123,
+                section: Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x1234_5678,
+                section: Section::with_endian(Endian::Little).append_bytes(b"other"),
+            });
+        let dump = read_synth_dump(dump).unwrap();
+
+        assert_eq!(
+            dump.memory_usage_report(),
+            vec![
+                (StreamTypeName(0x4d7a_0123), 0x100),
+                (StreamTypeName(MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32), 5),
+                (StreamTypeName(0x1234_5678), 5),
+            ]
+        );
+
+        let mut output = Vec::new();
+        dump.print_memory_usage_report(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Stream sizes:
+           256 bytes  unknown, Mozilla Extension
+             5 bytes  LinuxCmdLine
+             5 bytes  unknown, Unknown Extension
+
+"
+        );
+    }
+
+    fn read_cache_test_dump() -> Minidump<'static, Vec<u8>> {
+        let name = DumpString::new("cached module", Endian::Little);
+        let module = SynthModule::new(
+            Endian::Little,
+            0x10000,
+            0x1000,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        );
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_module(module)
+            .add(name);
+        read_synth_dump(dump).unwrap()
+    }
+
+    #[test]
+    fn test_get_stream_cached() {
+        let dump = read_cache_test_dump();
+
+        let modules = dump.get_stream_cached::<MinidumpModuleList>().unwrap();
+        assert_eq!(modules.iter().count(), 1);
+        assert!(Arc::ptr_eq(
+            &modules,
+            &dump.get_stream_cached::<MinidumpModuleList>().unwrap()
+        ));
+        let system_info = dump.get_stream_cached::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(system_info.cpu, Cpu::X86_64);
+        assert!(Arc::ptr_eq(
+            &system_info,
+            &dump.get_stream_cached::<MinidumpSystemInfo>().unwrap()
+        ));
+
+        // Missing streams are reported every time, and never cached.
+        for _ in 0..2 {
+            assert_eq!(
+                dump.get_stream_cached::<MinidumpMiscInfo>().unwrap_err(),
+                Error::StreamNotFound
             );
+        }
 
-            assert!(map.is_read);
-            assert!(map.is_write);
-            assert!(map.is_exec);
-            assert!(map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
+        dump.reparse();
+        let reparsed = dump.get_stream_cached::<MinidumpModuleList>().unwrap();
+        assert!(!Arc::ptr_eq(&modules, &reparsed));
+        assert_eq!(reparsed.iter().count(), 1);
+        // The old Arc is still good.
+        assert_eq!(modules.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_get_stream_cached_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Minidump<'static, Vec<u8>>>();
+
+        let dump = Arc::new(read_cache_test_dump());
+        let handles = (0..8)
+            .map(|_| {
+                let dump = dump.clone();
+                std::thread::spawn(move || dump.get_stream_cached::<MinidumpModuleList>().unwrap())
+            })
+            .collect::<Vec<_>>();
+        let modules = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        for module_list in &modules {
+            assert!(Arc::ptr_eq(module_list, &modules[0]));
         }
+        assert!(Arc::ptr_eq(
+            &modules[0],
+            &dump.get_stream_cached::<MinidumpModuleList>().unwrap()
+        ));
+    }
 
-        {
-            // Anonymous
-            let map = parse(b"10a00-10b00 -r-  10bac9000 fd:05 1196511  ");
-            let map = map.unwrap();
+    #[test]
+    fn test_thread_names() {
+        let good_thread_id = 17;
+        let corrupt_thread_id = 123;
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(map.kind, AnonymousMap);
+        let good_name = DumpString::new("MyCoolThread", Endian::Little);
+        // No corrupt name, will dangle
 
-            assert!(map.is_read);
-            assert!(!map.is_write);
-            assert!(!map.is_exec);
-            assert!(!map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
+        let good_thread_name_entry =
+            ThreadName::new(Endian::Little, good_thread_id, Some(&good_name));
+        let corrupt_thread_name_entry = ThreadName::new(Endian::Little, corrupt_thread_id, None);
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_name(good_thread_name_entry)
+            .add_thread_name(corrupt_thread_name_entry)
+            .add(good_name);
+
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(thread_names.names.len(), 1);
+        assert_eq!(
+            &*thread_names.get_name(good_thread_id).unwrap(),
+            "MyCoolThread"
+        );
+        assert_eq!(thread_names.get_name(corrupt_thread_id), None);
+    }
+
+    #[test]
+    fn test_thread_names_duplicates_and_null() {
+        let old_name = DumpString::new("OldName", Endian::Little);
+        let new_name = DumpString::new("NewName", Endian::Little);
+        // Entries are a u32 thread id and a 64-bit RVA.
+        let section = Section::with_endian(Endian::Little)
+            .D32(4) // number_of_thread_names
+            .D32(1)
+            .D64(old_name.file_offset())
+            .D32(1)
+            .D64(new_name.file_offset())
+            // A later entry that can't be read doesn't replace a good one.
+            .D32(1)
+            .D64(0x1_0000_0000)
+            // No name at all.
+            .D32(2)
+            .D64(0);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::ThreadNamesStream as u32,
+                section,
+            })
+            .add(old_name)
+            .add(new_name);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(thread_names.names.len(), 1);
+        assert_eq!(thread_names.get_name(1).as_deref(), Some("NewName"));
+        assert_eq!(thread_names.get_name(2), None);
+    }
+
+    #[test]
+    fn test_thread_info_list() {
+        let error_flags = md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_ERROR_THREAD
+            | md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_INVALID_CONTEXT;
+        let main_info = SynthThreadInfo::new(Endian::Little, 0x1, 0, 15_000_000, 2_500, 0xabcd0000);
+        let error_info =
+            SynthThreadInfo::new(Endian::Little, 0x2, error_flags.bits(), 0, 0, 0xabcd1000);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_info(main_info)
+            .add_thread_info(error_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_infos = dump.get_stream::<MinidumpThreadInfoList>().unwrap();
+        let infos = thread_infos.iter().collect::<Vec<_>>();
+        assert_eq!(infos.len(), 2);
+
+        let main_info = thread_infos.get_thread_info(0x1).unwrap();
+        assert_eq!(main_info.thread_id(), 0x1);
+        assert_eq!(main_info.start_address(), 0xabcd0000);
+        assert_eq!(main_info.kernel_time(), Duration::from_millis(1500));
+        assert_eq!(main_info.user_time(), Duration::from_micros(250));
+        assert!(main_info.dump_flags.is_empty());
+        assert!(!main_info.is_error_thread());
+
+        let error_info = thread_infos.get_thread_info(0x2).unwrap();
+        assert_eq!(error_info, infos[1]);
+        assert_eq!(error_info.start_address(), 0xabcd1000);
+        assert_eq!(error_info.dump_flags, error_flags);
+        assert!(error_info.is_error_thread());
+        assert!(!error_info.is_writing_thread());
+
+        assert_eq!(thread_infos.get_thread_info(0x3), None);
+    }
+
+    #[test]
+    fn test_thread_info_lookup() {
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1, &stack, &context);
+        let other_thread = Thread::new(Endian::Little, 0x2, &stack, &context);
+        let info = SynthThreadInfo::new(Endian::Little, 0x1, 0, 0, 0, 0xabcd0000);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add_thread(other_thread)
+            .add_thread_info(info)
+            .add(context)
+            .add_memory(stack);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList>().unwrap();
+        let thread_infos = dump.get_stream::<MinidumpThreadInfoList>().unwrap();
+        assert_eq!(
+            thread_list.threads[0]
+                .info(&thread_infos)
+                .map(|info| info.start_address()),
+            Some(0xabcd0000)
+        );
+        assert_eq!(thread_list.threads[1].info(&thread_infos), None);
+    }
+
+    #[test]
+    fn test_thread_name_lookup() {
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let main_name = DumpString::new("MainThread", Endian::Little);
+        let worker_name = DumpString::new("Worker #1", Endian::Little);
+        let main_thread = Thread::new(Endian::Little, 0x1, &stack, &context);
+        let worker_thread = Thread::new(Endian::Little, 0x2, &stack, &context);
+        let unnamed_thread = Thread::new(Endian::Little, 0x3, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(main_thread)
+            .add_thread(worker_thread)
+            .add_thread(unnamed_thread)
+            .add_thread_name(ThreadName::new(Endian::Little, 0x1, Some(&main_name)))
+            .add_thread_name(ThreadName::new(Endian::Little, 0x2, Some(&worker_name)))
+            .add(main_name)
+            .add(worker_name)
+            .add(context)
+            .add_memory(stack);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        let names = thread_list
+            .threads
+            .iter()
+            .map(|thread| thread.name(&thread_names).map(Cow::into_owned))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                Some(String::from("MainThread")),
+                Some(String::from("Worker #1")),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_list() {
+        for endian in [Endian::Little, Endian::Big] {
+            let name = DumpString::new("single module", endian);
+            let cv_record = Section::with_endian(endian)
+                .D32(md::CvSignature::Pdb70 as u32) // signature
+                // signature, a GUID
+                .D32(0xabcd1234)
+                .D16(0xf00d)
+                .D16(0xbeef)
+                .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+                .D32(1) // age
+                .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
+            let module = SynthModule::new(
+                endian,
+                0xa90206ca83eb2852,
+                0xada542bd,
+                &name,
+                0xb1054d2a,
+                0x34571371,
+                Some(&STOCK_VERSION_INFO),
+            )
+            .cv_record(&cv_record);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_module(module)
+                .add(name)
+                .add(cv_record);
+            let dump = read_synth_dump(dump).unwrap();
+            let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let modules = module_list.iter().collect::<Vec<_>>();
+            assert_eq!(modules.len(), 1);
+            assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
+            assert_eq!(modules[0].size(), 0xada542bd);
+            assert_eq!(modules[0].code_file(), "single module");
+            // time_date_stamp and size_of_image concatenated
+            assert_eq!(modules[0].code_identifier(), "B1054D2Aada542bd");
+            assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
+            assert_eq!(
+                modules[0].debug_identifier().unwrap(),
+                "ABCD1234F00DBEEF01020304050607081"
+            );
+            assert_eq!(
+                modules[0].version_as_tuple(),
+                Some((0x1111, 0x1111, 0x2222, 0x2222))
+            );
+            assert_eq!(modules[0].version().unwrap(), "4369.4369.8738.8738");
+        }
+    }
+
+    #[test]
+    fn test_duplicate_streams() {
+        let first_name = DumpString::new("first", Endian::Little);
+        let second_name = DumpString::new("second", Endian::Little);
+        let module_list = |base: u64, name: &DumpString| {
+            let module = SynthModule::new(Endian::Little, base, 0x1000, name, 0, 0, None);
+            ListStream::new(MINIDUMP_STREAM_TYPE::ModuleListStream, Endian::Little).add(module)
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(module_list(0x1000, &first_name))
+            .add_stream(module_list(0x2000, &second_name))
+            .add(first_name)
+            .add(second_name);
+        let dump = read_synth_dump(dump).unwrap();
+
+        // get_stream uses the last one.
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        assert_eq!(module_list.iter().next().unwrap().code_file(), "second");
+
+        let module_lists = dump.get_all_streams::<MinidumpModuleList>();
+        let names = module_lists
+            .iter()
+            .map(|list| {
+                let module = list.as_ref().unwrap().iter().next().unwrap();
+                (module.base_address(), module.code_file().into_owned())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (0x1000, String::from("first")),
+                (0x2000, String::from("second"))
+            ]
+        );
+        assert_eq!(
+            dump.get_all_raw_streams(MINIDUMP_STREAM_TYPE::ModuleListStream)
+                .len(),
+            2
+        );
+        assert!(dump.get_all_streams::<MinidumpThreadList>().is_empty());
+
+        assert_eq!(
+            dump.warnings(),
+            &[MinidumpWarning::DuplicateStream {
+                stream_type: MINIDUMP_STREAM_TYPE::ModuleListStream as u32,
+                count: 2,
+            }]
+        );
+        assert_eq!(
+            dump.warnings()[0].to_string(),
+            "2 streams of type ModuleListStream, using the last one"
+        );
+    }
+
+    /// An assertion stream with the given strings, which are zero-padded to
+    /// their 128 code units.
+    fn assertion_section(
+        endian: Endian,
+        strings: [&str; 3],
+        line: u32,
+        assertion_type: u32,
+    ) -> Section {
+        let mut section = Section::with_endian(endian);
+        for string in strings {
+            let units = string.encode_utf16().collect::<Vec<_>>();
+            for unit in &units {
+                section = section.D16(*unit);
+            }
+            section = section.append_repeated(0, (128 - units.len()) * 2);
+        }
+        section.D32(line).D32(assertion_type)
+    }
+
+    #[test]
+    fn test_assertion() {
+        for endian in [Endian::Little, Endian::Big] {
+            let dump = SynthMinidump::with_endian(endian).add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::AssertionInfoStream as u32,
+                section: assertion_section(
+                    endian,
+                    ["größe < 10 ✓", "überprüfen", r"c:\src\naïve.cpp"],
+                    17,
+                    md::AssertionType::PureVirtualCall as u32,
+                ),
+            });
+            let dump = read_synth_dump(dump).unwrap();
+            let assertion = dump.get_stream::<MinidumpAssertion>().unwrap();
+            assert!(!assertion.is_empty());
+            assert_eq!(assertion.expression().unwrap(), "größe < 10 ✓");
+            assert_eq!(assertion.function().unwrap(), "überprüfen");
+            assert_eq!(assertion.file().unwrap(), r"c:\src\naïve.cpp");
+            assert_eq!(assertion.line(), Some(17));
+            assert_eq!(
+                assertion.assertion_type(),
+                Some(md::AssertionType::PureVirtualCall)
+            );
         }
+    }
+
+    #[test]
+    fn test_empty_assertion() {
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::AssertionInfoStream as u32,
+            section: assertion_section(Endian::Little, ["", "", ""], 0, 0),
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        let assertion = dump.get_stream::<MinidumpAssertion>().unwrap();
+        assert!(assertion.is_empty());
+        assert_eq!(assertion.expression(), None);
+        assert_eq!(assertion.function(), None);
+        assert_eq!(assertion.file(), None);
+        assert_eq!(assertion.line(), None);
+        assert_eq!(assertion.assertion_type(), Some(md::AssertionType::Unknown));
+    }
+
+    #[test]
+    fn test_system_memory_info() {
+        let page_size = 0x1000;
+        let stream = |flags: u16| {
+            let section = Section::with_endian(Endian::Little)
+                .D16(1) // revision
+                .D16(flags)
+                // basic_info
+                .D32(15625) // timer_resolution
+                .D32(page_size)
+                .D32(0x40_0000) // number_of_physical_pages (16 GiB)
+                .append_repeated(0, 3 * 4 + 3 * 8)
+                .D32(8) // number_of_processors
+                // file_cache_info
+                .append_repeated(0, 60)
+                // basic_perf_info
+                .D64(0x8_0000) // available_pages (2 GiB)
+                .D64(0x7F_3333) // committed_pages
+                .D64(0x80_0000) // commit_limit (32 GiB)
+                .D64(0x7F_8000) // peak_commitment
+                // perf_info
+                .append_repeated(0, 4 * 8 + 3 * 4)
+                .D32(0x1000) // available_pages
+                .D32(0x2000) // committed_pages
+                .D32(0x3000) // commit_limit
+                .D32(0x4000) // peak_commitment
+                .append_repeated(0, 63 * 4 + 4 * 8);
+            assert_eq!(section.size(), 492);
+            SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream as u32,
+                section,
+            }
+        };
+        const GIB: u64 = 1024 * 1024 * 1024;
+
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(stream(
+            md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF.bits(),
+        ));
+        let dump = read_synth_dump(dump).unwrap();
+        let info = dump.get_stream::<MinidumpSystemMemoryInfo>().unwrap();
+        assert_eq!(info.raw.basic_info.number_of_processors, 8);
+        assert_eq!(info.page_size(), 0x1000);
+        assert_eq!(info.physical_memory(), 16 * GIB);
+        assert_eq!(info.available_physical_memory(), 2 * GIB);
+        assert_eq!(info.committed_memory(), 0x7F_3333 * 0x1000);
+        assert_eq!(info.commit_limit(), 32 * GIB);
+        assert_eq!(info.peak_committed_memory(), 0x7F_8000 * 0x1000);
+        assert!(dump.unimplemented_streams().next().is_none());
+
+        // Without the basic performance info, the 32-bit performance info is used.
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(stream(0));
+        let dump = read_synth_dump(dump).unwrap();
+        let info = dump.get_stream::<MinidumpSystemMemoryInfo>().unwrap();
+        assert_eq!(info.available_physical_memory(), 0x1000 * 0x1000);
+        assert_eq!(info.committed_memory(), 0x2000 * 0x1000);
+        assert_eq!(info.commit_limit(), 0x3000 * 0x1000);
+        assert_eq!(info.peak_committed_memory(), 0x4000 * 0x1000);
+
+        // A truncated stream can't be read.
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream as u32,
+            section: Section::with_endian(Endian::Little).append_repeated(0, 491),
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(matches!(
+            dump.get_stream::<MinidumpSystemMemoryInfo>(),
+            Err(Error::StreamReadFailure)
+        ));
+    }
+
+    #[test]
+    fn test_process_vm_counters() {
+        let counters = |section: Section| {
+            let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::ProcessVmCountersStream as u32,
+                section,
+            });
+            read_synth_dump(dump)
+                .unwrap()
+                .get_stream::<MinidumpProcessVmCounters>()
+        };
+
+        // MINIDUMP_PROCESS_VM_COUNTERS_1 has no flags, everything is valid.
+        let section = Section::with_endian(Endian::Little)
+            .D16(1) // revision
+            .D16(0) // alignment
+            .D32(1234) // page_fault_count
+            .D64(0x3000) // peak_working_set_size
+            .D64(0x2000) // working_set_size
+            .append_repeated(0, 4 * 8)
+            .D64(0x5000) // pagefile_usage
+            .D64(0x6000) // peak_pagefile_usage
+            .D64(0x4000); // private_usage
+        assert_eq!(section.size(), 80);
+        let v1 = counters(section).unwrap();
+        assert!(matches!(v1.raw, RawProcessVmCounters::VmCounters1(_)));
+        assert_eq!(v1.page_fault_count(), Some(1234));
+        assert_eq!(v1.peak_working_set_size(), Some(0x3000));
+        assert_eq!(v1.working_set_size(), Some(0x2000));
+        assert_eq!(v1.pagefile_usage(), Some(0x5000));
+        assert_eq!(v1.peak_pagefile_usage(), Some(0x6000));
+        assert_eq!(v1.private_usage(), Some(0x4000));
+        assert_eq!(v1.virtual_size(), None);
+        assert_eq!(v1.private_working_set_size(), None);
+
+        // MINIDUMP_PROCESS_VM_COUNTERS_2 only has the fields its flags say.
+        let v2 = |flags: md::ProcessVmCountersFlags| {
+            let section = Section::with_endian(Endian::Little)
+                .D16(2) // revision
+                .D16(flags.bits())
+                .D32(1234) // page_fault_count
+                .D64(0x3000) // peak_working_set_size
+                .D64(0x2000) // working_set_size
+                .append_repeated(0, 4 * 8)
+                .D64(0x5000) // pagefile_usage
+                .D64(0x6000) // peak_pagefile_usage
+                .D64(0x9000) // peak_virtual_size
+                .D64(0x8000) // virtual_size
+                .D64(0x4000) // private_usage
+                .D64(0x1000) // private_working_set_size
+                .D64(0x500) // shared_commit_usage
+                .D64(0) // job_shared_commit_usage
+                .D64(0xA000) // job_private_commit_usage
+                .D64(0) // job_peak_private_commit_usage
+                .D64(0xB000) // job_private_commit_limit
+                .D64(0); // job_total_commit_limit
+            assert_eq!(section.size(), 152);
+            counters(section).unwrap()
+        };
+        let all = v2(md::ProcessVmCountersFlags::all());
+        assert!(matches!(all.raw, RawProcessVmCounters::VmCounters2(_)));
+        assert_eq!(all.working_set_size(), Some(0x2000));
+        assert_eq!(all.peak_virtual_size(), Some(0x9000));
+        assert_eq!(all.virtual_size(), Some(0x8000));
+        assert_eq!(all.private_usage(), Some(0x4000));
+        assert_eq!(all.private_working_set_size(), Some(0x1000));
+        assert_eq!(all.shared_commit_usage(), Some(0x500));
+        assert_eq!(all.job_private_commit_usage(), Some(0xA000));
+        assert_eq!(all.job_private_commit_limit(), Some(0xB000));
+
+        let basic = v2(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS);
+        assert_eq!(basic.working_set_size(), Some(0x2000));
+        assert_eq!(basic.pagefile_usage(), Some(0x5000));
+        assert_eq!(basic.virtual_size(), None);
+        assert_eq!(basic.private_usage(), None);
+        assert_eq!(basic.job_private_commit_limit(), None);
+
+        // Too small for either version.
+        assert!(matches!(
+            counters(Section::with_endian(Endian::Little).append_repeated(0, 79)),
+            Err(Error::StreamReadFailure)
+        ));
+    }
+
+    #[test]
+    fn test_module_version() {
+        let module = |version_info: md::VS_FIXEDFILEINFO| {
+            let mut module = MinidumpModule::new(0x1000, 0x1000, "libfoo.dylib");
+            module.raw.version_info = version_info;
+            module
+        };
+
+        let v10 = module(md::VS_FIXEDFILEINFO {
+            file_version_hi: 0x000a_0000,
+            file_version_lo: 0x0002_0003,
+            ..STOCK_VERSION_INFO
+        });
+        let v9 = module(md::VS_FIXEDFILEINFO {
+            file_version_hi: 0x0009_ffff,
+            file_version_lo: 0xffff_ffff,
+            ..STOCK_VERSION_INFO
+        });
+        assert_eq!(v10.version_as_tuple(), Some((10, 0, 2, 3)));
+        assert_eq!(v10.version().unwrap(), "10.0.2.3");
+        assert_eq!(v9.version_as_tuple(), Some((9, 0xffff, 0xffff, 0xffff)));
+        assert!(v9.version_as_tuple() < v10.version_as_tuple());
+
+        // Modules without a VS_FIXEDFILEINFO (like most ELF modules) have no version.
+        let unversioned = module(md::VS_FIXEDFILEINFO::default());
+        assert_eq!(unversioned.version_as_tuple(), None);
+        assert_eq!(unversioned.version(), None);
+    }
+
+    #[test]
+    fn test_module_fixed_file_info() {
+        let read_module = |name: &str, version_info: &md::VS_FIXEDFILEINFO| {
+            let name = DumpString::new(name, Endian::Little);
+            let module = SynthModule::new(
+                Endian::Little,
+                0x10000000,
+                0x1000,
+                &name,
+                0xb1054d2a,
+                0x34571371,
+                Some(version_info),
+            );
+            let dump = SynthMinidump::with_endian(Endian::Little)
+                .add_module(module)
+                .add(name);
+            let dump = read_synth_dump(dump).unwrap();
+            dump.get_stream::<MinidumpModuleList>().unwrap()
+        };
+
+        let module_list = read_module(
+            "C:\\Program Files\\Mozilla Firefox\\xul.dll",
+            &md::VS_FIXEDFILEINFO {
+                file_version_hi: 0x006d_0000,
+                file_version_lo: 0x0001_20b6,
+                product_version_hi: 0x006d_0000,
+                product_version_lo: 0x0001_0000,
+                file_flags_mask: 0x3f,
+                file_flags: 0x03,
+                file_os: 0x40004,
+                file_type: 2,
+                ..STOCK_VERSION_INFO
+            },
+        );
+        let module = module_list.iter().next().unwrap();
+        assert_eq!(module.file_version(), Some((109, 0, 1, 8374)));
+        assert_eq!(module.product_version(), Some((109, 0, 1, 0)));
+        assert_eq!(
+            module.file_flags(),
+            Some(md::FileFlags::VS_FF_DEBUG | md::FileFlags::VS_FF_PRERELEASE)
+        );
+        assert_eq!(module.file_os(), Some(md::FileOs::VOS_NT_WINDOWS32));
+        assert_eq!(module.file_type(), Some(md::FileType::VFT_DLL));
+
+        let mut output = Vec::new();
+        module_list.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("module[0] xul.dll 109.0.1.8374\n"));
+        assert!(output.contains("(file_version)                  = \"109.0.1.8374\"\n"));
+        assert!(output.contains("(product_version)               = \"109.0.1.0\"\n"));
+        assert!(
+            output.contains("(file_flags)                    = VS_FF_DEBUG | VS_FF_PRERELEASE\n")
+        );
+        assert!(output.contains("(file_os)                       = VOS_NT_WINDOWS32\n"));
+        assert!(output.contains("(file_type)                     = VFT_DLL\n"));
+
+        // Flags outside of the mask aren't valid.
+        let module_list = read_module(
+            "xul.dll",
+            &md::VS_FIXEDFILEINFO {
+                file_flags_mask: 0x02,
+                file_flags: 0x03,
+                ..STOCK_VERSION_INFO
+            },
+        );
+        let module = module_list.iter().next().unwrap();
+        assert_eq!(module.file_flags(), Some(md::FileFlags::VS_FF_PRERELEASE));
+
+        // ELF and Mach-O modules may have a valid signature but no version.
+        let module_list = read_module(
+            "/usr/lib/libc.so.6",
+            &md::VS_FIXEDFILEINFO {
+                signature: md::VS_FFI_SIGNATURE,
+                struct_version: md::VS_FFI_STRUCVERSION,
+                ..Default::default()
+            },
+        );
+        let module = module_list.iter().next().unwrap();
+        assert_eq!(module.file_version(), None);
+        assert_eq!(module.product_version(), None);
+        assert_eq!(module.file_flags(), Some(md::FileFlags::empty()));
+        assert_eq!(module.file_os(), Some(md::FileOs::VOS_UNKNOWN));
+        assert_eq!(module.file_type(), Some(md::FileType::VFT_UNKNOWN));
+
+        let mut output = Vec::new();
+        module_list.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("module[0] libc.so.6\n"));
+        assert!(output.contains("(file_version)                  = \"\"\n"));
+
+        // Or no VS_FIXEDFILEINFO at all.
+        let module_list = read_module("/usr/lib/libc.so.6", &Default::default());
+        let module = module_list.iter().next().unwrap();
+        assert_eq!(module.file_version(), None);
+        assert_eq!(module.product_version(), None);
+        assert_eq!(module.file_flags(), None);
+        assert_eq!(module.file_os(), None);
+        assert_eq!(module.file_type(), None);
+    }
+
+    #[test]
+    fn test_module_list_no_codeview() {
+        let name = DumpString::new("c:\\windows\\system32\\kernel32.dll", Endian::Little);
+        let module = SynthModule::new(
+            Endian::Little,
+            0x7c800000,
+            0xf6000,
+            &name,
+            0x49c4f482,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert!(modules[0].codeview_info.is_none());
+        assert_eq!(modules[0].debug_file(), None);
+        assert_eq!(modules[0].debug_identifier(), None);
+        // Still identifiable by time_date_stamp and size_of_image
+        assert_eq!(modules[0].code_identifier(), "49C4F482f6000");
+    }
+
+    #[test]
+    fn test_module_names() {
+        let paths = [
+            "C:\\Windows\\System32\\NTDLL.dll",
+            "\\Device\\HarddiskVolume4\\Program Files\\app\\xul.dll",
+            "//server/share/lib/mozglue.dll",
+            "\\\\?\\C:\\bin\\app.exe",
+            "C:app.exe.local",
+            "/usr/lib/libc.so.6",
+            "plain.dll",
+        ];
+        let names = paths
+            .iter()
+            .map(|path| DumpString::new(path, Endian::Little))
+            .collect::<Vec<_>>();
+        let mut dump = SynthMinidump::with_endian(Endian::Little);
+        for (i, name) in names.iter().enumerate() {
+            let base = 0x10000 * (i as u64 + 1);
+            dump = dump.add_module(SynthModule::new(
+                Endian::Little,
+                base,
+                0x1000,
+                name,
+                0,
+                0,
+                None,
+            ));
+        }
+        for name in names {
+            dump = dump.add(name);
+        }
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let names = module_list.iter().map(|m| m.name()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "NTDLL.dll",
+                "xul.dll",
+                "mozglue.dll",
+                "app.exe",
+                "app.exe.local",
+                "libc.so.6",
+                "plain.dll",
+            ]
+        );
+
+        let base_of = |name: &str| module_list.module_by_name(name).map(|m| m.base_address());
+        assert_eq!(base_of("ntdll.dll"), Some(0x10000));
+        assert_eq!(base_of("XUL.DLL"), Some(0x20000));
+        assert_eq!(base_of("c:\\other\\dir\\Mozglue.dll"), Some(0x30000));
+        assert_eq!(base_of("app.exe"), Some(0x40000));
+        assert_eq!(base_of("libc.so.6"), Some(0x60000));
+        assert_eq!(base_of("libc.so"), None);
+        assert_eq!(base_of("kernel32.dll"), None);
+    }
+
+    #[test]
+    fn test_unloaded_module_list() {
+        let name = DumpString::new("single module", Endian::Little);
+        let module = SynthUnloadedModule::new(
+            Endian::Little,
+            0xa90206ca83eb2852,
+            0xada542bd,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_unloaded_module(module)
+            .add(name);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
+        assert_eq!(modules[0].size(), 0xada542bd);
+        assert_eq!(modules[0].code_file(), "single module");
+        // time_date_stamp and size_of_image concatenated
+        assert_eq!(modules[0].code_identifier(), "B1054D2Aada542bd");
+    }
+
+    #[test]
+    fn test_unloaded_module_list_overlapping() {
+        let name1 = DumpString::new("many.dll", Endian::Little);
+        let name2 = DumpString::new("solo.dll", Endian::Little);
+        // many.dll was loaded twice at different addresses, and solo.dll
+        // was loaded over the top of both of them.
+        let module1_1 = SynthUnloadedModule::new(Endian::Little, 0x1000, 0x1000, &name1, 1, 0);
+        let module1_2 = SynthUnloadedModule::new(Endian::Little, 0x1800, 0x1000, &name1, 1, 0);
+        let module2 = SynthUnloadedModule::new(Endian::Little, 0x1400, 0x2000, &name2, 2, 0);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_unloaded_module(module1_1)
+            .add_unloaded_module(module2)
+            .add_unloaded_module(module1_2)
+            .add(name1)
+            .add(name2);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
+        assert_eq!(module_list.iter().count(), 3);
+
+        let bases = |address| {
+            module_list
+                .modules_at_address(address)
+                .map(|module| (module.name.as_str(), module.base_address()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bases(0x1200), vec![("many.dll", 0x1000)]);
+        assert_eq!(
+            bases(0x1900),
+            vec![
+                ("many.dll", 0x1000),
+                ("solo.dll", 0x1400),
+                ("many.dll", 0x1800)
+            ]
+        );
+        assert_eq!(bases(0x3000), vec![("solo.dll", 0x1400)]);
+        assert!(bases(0x3400).is_empty());
+    }
+
+    #[test]
+    fn test_memory_info_boundaries() {
+        let commit = md::MemoryState::MEM_COMMIT.bits();
+        let private = md::MemoryType::MEM_PRIVATE.bits();
+        let readonly = md::MemoryProtection::PAGE_READONLY.bits();
+        let readwrite = md::MemoryProtection::PAGE_READWRITE.bits();
+        // Two adjacent committed regions, then a gap, then a reserved region.
+        let info1 = SynthMemoryInfo::new(
+            Endian::Little,
+            0x1000,
+            0x1000,
+            readwrite,
+            0x1000,
+            commit,
+            readonly,
+            private,
+        );
+        let info2 = SynthMemoryInfo::new(
+            Endian::Little,
+            0x2000,
+            0x1000,
+            readwrite,
+            0x2000,
+            commit,
+            readwrite,
+            private,
+        );
+        let info3 = SynthMemoryInfo::new(
+            Endian::Little,
+            0x8000,
+            0x8000,
+            0,
+            0x1000,
+            md::MemoryState::MEM_RESERVE.bits(),
+            0,
+            private,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory_info(info3)
+            .add_memory_info(info1)
+            .add_memory_info(info2);
+        let dump = read_synth_dump(dump).unwrap();
+        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().unwrap();
+
+        let base_at = |address| {
+            info_list
+                .memory_info_at_address(address)
+                .map(|info| info.raw.base_address)
+        };
+        assert_eq!(base_at(0xfff), None);
+        assert_eq!(base_at(0x1000), Some(0x1000));
+        assert_eq!(base_at(0x1fff), Some(0x1000));
+        assert_eq!(base_at(0x2000), Some(0x2000));
+        assert_eq!(base_at(0x3fff), Some(0x2000));
+        assert_eq!(base_at(0x4000), None);
+        assert_eq!(base_at(0x8fff), Some(0x8000));
+        assert_eq!(base_at(0x9000), None);
+
+        let info = info_list.memory_info_at_address(0x1800).unwrap();
+        assert_eq!(info.describe(), "a PAGE_READONLY committed region");
+        let info = info_list.memory_info_at_address(0x8000).unwrap();
+        assert_eq!(info.describe(), "a reserved region");
+
+        let bases = info_list
+            .by_addr()
+            .map(|info| info.raw.base_address)
+            .collect::<Vec<_>>();
+        assert_eq!(bases, vec![0x1000, 0x2000, 0x8000]);
+
+        let mut output = Vec::new();
+        info_list.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "  0x0000000000001000 0x0000000000002000 0x0000000000001000 \
+             MEM_PRIVATE  MEM_COMMIT   PAGE_READONLY\n"
+        ));
+        assert!(output.contains("  MEM_COMMIT                              2             0x3000\n"));
+        assert!(output.contains("  MEM_RESERVE                             1             0x1000\n"));
+        assert!(output.contains("  MEM_PRIVATE                             3             0x4000\n"));
+        assert!(output.contains("  PAGE_READWRITE                          1             0x2000\n"));
+    }
+
+    #[test]
+    fn test_memory_info() {
+        let info1_alloc_protection = md::MemoryProtection::PAGE_GUARD;
+        let info1_protection = md::MemoryProtection::PAGE_EXECUTE_READ;
+        let info1_state = md::MemoryState::MEM_FREE;
+        let info1_ty = md::MemoryType::MEM_MAPPED;
+        let info1 = SynthMemoryInfo::new(
+            Endian::Little,
+            0xa90206ca83eb2852,
+            0xa802064a83eb2752,
+            info1_alloc_protection.bits(),
+            0xf80e064a93eb2356,
+            info1_state.bits(),
+            info1_protection.bits(),
+            info1_ty.bits(),
+        );
+
+        let info2_alloc_protection = md::MemoryProtection::PAGE_EXECUTE_READ;
+        let info2_protection = md::MemoryProtection::PAGE_READONLY;
+        let info2_state = md::MemoryState::MEM_COMMIT;
+        let info2_ty = md::MemoryType::MEM_PRIVATE;
+        let info2 = SynthMemoryInfo::new(
+            Endian::Little,
+            0xd70206ca83eb2852,
+            0xb802064383eb2752,
+            info2_alloc_protection.bits(),
+            0xe80e064a93eb2356,
+            info2_state.bits(),
+            info2_protection.bits(),
+            info2_ty.bits(),
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory_info(info1)
+            .add_memory_info(info2);
+
+        let dump = read_synth_dump(dump).unwrap();
+
+        // Read both kinds of info to test this path on UnifiedMemoryInfo
+        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
+        let maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
+        assert!(info_list.is_some());
+        assert!(maps.is_none());
+
+        let unified_info = UnifiedMemoryInfoList::new(info_list, maps).unwrap();
+        let info_list = unified_info.info().unwrap();
+        assert!(unified_info.maps().is_none());
+
+        // Assert that unified and the info_list agree
+        for (info, unified) in info_list.iter().zip(unified_info.iter()) {
+            if let UnifiedMemoryInfo::Info(info2) = unified {
+                assert_eq!(info, info2);
+            } else {
+                unreachable!();
+            }
+        }
+
+        let infos = info_list.iter().collect::<Vec<_>>();
+
+        assert_eq!(infos.len(), 2);
+
+        assert_eq!(infos[0].raw.base_address, 0xa90206ca83eb2852);
+        assert_eq!(infos[0].raw.allocation_base, 0xa802064a83eb2752);
+        assert_eq!(
+            infos[0].raw.allocation_protection,
+            info1_alloc_protection.bits()
+        );
+        assert_eq!(infos[0].raw.region_size, 0xf80e064a93eb2356);
+        assert_eq!(infos[0].raw.state, info1_state.bits());
+        assert_eq!(infos[0].raw.protection, info1_protection.bits());
+        assert_eq!(infos[0].raw._type, info1_ty.bits());
+
+        assert_eq!(infos[0].allocation_protection, info1_alloc_protection);
+        assert_eq!(infos[0].protection, info1_protection);
+        assert_eq!(infos[0].state, info1_state);
+        assert_eq!(infos[0].ty, info1_ty);
+        assert!(infos[0].is_executable());
+
+        assert_eq!(infos[1].raw.base_address, 0xd70206ca83eb2852);
+        assert_eq!(infos[1].raw.allocation_base, 0xb802064383eb2752);
+        assert_eq!(
+            infos[1].raw.allocation_protection,
+            info2_alloc_protection.bits()
+        );
+        assert_eq!(infos[1].raw.region_size, 0xe80e064a93eb2356);
+        assert_eq!(infos[1].raw.state, info2_state.bits());
+        assert_eq!(infos[1].raw.protection, info2_protection.bits());
+        assert_eq!(infos[1].raw._type, info2_ty.bits());
+
+        assert_eq!(infos[1].allocation_protection, info2_alloc_protection);
+        assert_eq!(infos[1].protection, info2_protection);
+        assert_eq!(infos[1].state, info2_state);
+        assert_eq!(infos[1].ty, info2_ty);
+        assert!(!infos[1].is_executable());
+    }
+
+    #[test]
+    fn test_linux_lsb_release() {
+        let lsb_release = b"\
+DISTRIB_ID=Ubuntu
+DISTRIB_RELEASE=22.04
+DISTRIB_CODENAME=jammy
+DISTRIB_DESCRIPTION=\"Ubuntu 22.04.1 LTS\"
+";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_lsb_release(lsb_release);
+        let dump = read_synth_dump(dump).unwrap();
+        let lsb = dump.get_stream::<MinidumpLinuxLsbRelease>().unwrap();
+        assert_eq!(lsb.id().as_deref(), Some("Ubuntu"));
+        assert_eq!(lsb.release().as_deref(), Some("22.04"));
+        assert_eq!(lsb.codename().as_deref(), Some("jammy"));
+        assert_eq!(lsb.description().as_deref(), Some("Ubuntu 22.04.1 LTS"));
+
+        let os_release = b"\
+NAME=\"Fedora Linux\"
+VERSION=\"38 (Workstation Edition)\"
+ID=fedora
+VERSION_ID=38
+PRETTY_NAME=\"Fedora Linux 38 (Workstation Edition)\"
+";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_lsb_release(os_release);
+        let dump = read_synth_dump(dump).unwrap();
+        let lsb = dump.get_stream::<MinidumpLinuxLsbRelease>().unwrap();
+        assert_eq!(lsb.id().as_deref(), Some("fedora"));
+        assert_eq!(lsb.release().as_deref(), Some("38"));
+        assert_eq!(lsb.codename(), None);
+        assert_eq!(
+            lsb.description().as_deref(),
+            Some("Fedora Linux 38 (Workstation Edition)")
+        );
+    }
+
+    #[test]
+    fn test_linux_dso_debug_64() {
+        let libc = DumpString::new("/lib/x86_64-linux-gnu/libc.so.6", Endian::Little);
+        let vdso = DumpString::new("linux-vdso.so.1", Endian::Little);
+        let map = Section::with_endian(Endian::Little)
+            // The main executable, with a null name.
+            .D64(0)
+            .D32(0)
+            .D64(0x601e10)
+            // libc
+            .D64(0x7f3a12000000)
+            .D32(libc.file_offset())
+            .D64(0x7f3a121d6bc0)
+            // the vdso
+            .D64(0x7ffd5c5f1000)
+            .D32(vdso.file_offset())
+            .D64(0x7ffd5c5f13e0);
+        let stream = SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+            section: Section::with_endian(Endian::Little)
+                .D32(1) // version
+                .D32(map.file_offset())
+                .D32(3) // dso_count
+                .D64(0x7f3a12400c10) // brk
+                .D64(0x7f3a12200000) // ldbase
+                .D64(0x601e10), // dynamic
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add(map)
+            .add(libc)
+            .add(vdso)
+            .add_stream(stream);
+        let dump = read_synth_dump(dump).unwrap();
+        let dso_debug = dump.get_stream::<MinidumpLinuxDsoDebug>().unwrap();
+
+        assert_eq!(dso_debug.version, 1);
+        assert_eq!(dso_debug.brk, 0x7f3a12400c10);
+        assert_eq!(dso_debug.ldbase, 0x7f3a12200000);
+        assert_eq!(dso_debug.dynamic, 0x601e10);
+        assert_eq!(dso_debug.link_maps.len(), 3);
+        assert_eq!(
+            dso_debug.link_maps[0],
+            MinidumpLinkMap {
+                addr: 0,
+                name: None,
+                name_rva: 0,
+                ld: 0x601e10,
+            }
+        );
+        assert_eq!(dso_debug.link_maps[1].addr, 0x7f3a12000000);
+        assert_eq!(
+            dso_debug.link_maps[1].name.as_deref(),
+            Some("/lib/x86_64-linux-gnu/libc.so.6")
+        );
+        assert_eq!(dso_debug.link_maps[1].ld, 0x7f3a121d6bc0);
+        assert_eq!(
+            dso_debug.link_maps[2].name.as_deref(),
+            Some("linux-vdso.so.1")
+        );
+        // It's not an unimplemented stream anymore.
+        assert_eq!(dump.unimplemented_streams().count(), 0);
+    }
+
+    #[test]
+    fn test_linux_dso_debug_32() {
+        let libc = DumpString::new("/lib/i386-linux-gnu/libc.so.6", Endian::Little);
+        let map = Section::with_endian(Endian::Little)
+            .D32(0xf7d00000)
+            .D32(libc.file_offset())
+            .D32(0xf7ee5d8c);
+        let stream = SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+            section: Section::with_endian(Endian::Little)
+                .D32(1) // version
+                .D32(map.file_offset())
+                .D32(1) // dso_count
+                .D32(0xf7f1e9a0) // brk
+                .D32(0xf7f0c000) // ldbase
+                .D32(0x8049f0c), // dynamic
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add(map)
+            .add(libc)
+            .add_stream(stream);
+        let dump = read_synth_dump(dump).unwrap();
+        let dso_debug = dump.get_stream::<MinidumpLinuxDsoDebug>().unwrap();
+
+        assert_eq!(dso_debug.brk, 0xf7f1e9a0);
+        assert_eq!(dso_debug.dynamic, 0x8049f0c);
+        assert_eq!(dso_debug.link_maps.len(), 1);
+        assert_eq!(dso_debug.link_maps[0].addr, 0xf7d00000);
+        assert_eq!(
+            dso_debug.link_maps[0].name.as_deref(),
+            Some("/lib/i386-linux-gnu/libc.so.6")
+        );
+        assert_eq!(dso_debug.link_maps[0].ld, 0xf7ee5d8c);
+    }
+
+    #[test]
+    fn test_linux_environ() {
+        let input = b"LANG=en_US.UTF-8\0\
+QUERY=a=b&c=d\0\
+EMPTY=\0\
+NOT_A_VAR\0\
+BAD=caf\xe9\0\
+LANG=fr_FR.UTF-8\0\
+GITHUB_TOKEN=secret\0";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_environ(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+
+        assert_eq!(environ.iter().count(), 6);
+        assert_eq!(environ.get("LANG").as_deref(), Some("en_US.UTF-8"));
+        assert_eq!(environ.get("QUERY").as_deref(), Some("a=b&c=d"));
+        assert_eq!(environ.get("EMPTY").as_deref(), Some(""));
+        assert_eq!(environ.get("BAD").as_deref(), Some("caf\u{fffd}"));
+        assert_eq!(environ.get("NOT_A_VAR"), None);
+
+        let vars = environ.vars();
+        assert_eq!(
+            vars.keys().collect::<Vec<_>>(),
+            ["BAD", "EMPTY", "GITHUB_TOKEN", "LANG", "QUERY"]
+        );
+        assert_eq!(vars["LANG"], "en_US.UTF-8");
+
+        let redacted = environ.redacted(&["*_TOKEN", "QUERY"]);
+        assert_eq!(
+            redacted.keys().collect::<Vec<_>>(),
+            ["BAD", "EMPTY", "LANG"]
+        );
+        let allowed = environ.allowlisted(&["LANG", "E*"]);
+        assert_eq!(allowed.keys().collect::<Vec<_>>(), ["EMPTY", "LANG"]);
+    }
+
+    #[test]
+    fn test_linux_proc_status() {
+        // Captured from a real process (with some lines removed).
+        let input = b"\
+Name:\tfirefox
+Umask:\t0022
+State:\tS (sleeping)
+Tgid:\t4183
+Ngid:\t0
+Pid:\t4183
+PPid:\t4072
+TracerPid:\t0
+Uid:\t1000\t1000\t1000\t1000
+Gid:\t100\t100\t100\t1001
+FDSize:\t512
+VmPeak:\t 21689408 kB
+VmSize:\t 21153684 kB
+VmLck:\t       0 kB
+VmRSS:\t  1992296 kB
+VmSwap:\t       12 kB
+Threads:\t104
+SigQ:\t1/126511
+SigPnd:\t0000000000000000
+ShdPnd:\t0000000000000000
+SigBlk:\t0000000000000000
+SigIgn:\t0000000000001001
+SigCgt:\t0000000f400144fc
+Seccomp:\t2
+Seccomp_filters:\t1
+";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_proc_status(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let status = dump.get_stream::<MinidumpLinuxProcStatus>().unwrap();
+
+        assert_eq!(status.name(), Some("firefox"));
+        assert_eq!(status.pid(), Some(4183));
+        assert_eq!(status.ppid(), Some(4072));
+        assert_eq!(status.uids(), Some([1000, 1000, 1000, 1000]));
+        assert_eq!(status.gids(), Some([100, 100, 100, 1001]));
+        assert_eq!(status.vm_peak(), Some(21689408 * 1024));
+        assert_eq!(status.vm_size(), Some(21153684 * 1024));
+        assert_eq!(status.vm_rss(), Some(1992296 * 1024));
+        assert_eq!(status.vm_swap(), Some(12 * 1024));
+        assert_eq!(status.threads(), Some(104));
+        assert_eq!(status.sig_blk(), Some(0));
+        // SIGHUP and SIGPIPE
+        assert_eq!(status.sig_ign(), Some((1 << (1 - 1)) | (1 << (13 - 1))));
+        assert_eq!(status.sig_cgt(), Some(0xf400144fc));
+        assert_eq!(status.seccomp(), Some(2));
+
+        let mut output = vec![];
+        status.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  gids    = 100 100 100 1001\n"));
+        assert!(output.contains("  sig_ign = 0x0000000000001001\n"));
+
+        // Missing and malformed fields
+        let input = b"Name:\tsh\nUid:\t1000\t1000\nVmRSS:\t12 MB\nThreads:\tmany\n";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_proc_status(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let status = dump.get_stream::<MinidumpLinuxProcStatus>().unwrap();
+        assert_eq!(status.name(), Some("sh"));
+        assert_eq!(status.pid(), None);
+        assert_eq!(status.uids(), None);
+        assert_eq!(status.vm_rss(), None);
+        assert_eq!(status.threads(), None);
+        assert_eq!(status.sig_cgt(), None);
+
+        let mut output = vec![];
+        status.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  pid     = (none)\n"));
+    }
+
+    fn read_system_info(arch: ProcessorArchitecture, cpu: CpuInfo) -> MinidumpSystemInfo {
+        let mut system_info =
+            SystemInfo::new(Endian::Little).set_processor_architecture(arch as u16);
+        system_info.processor_level = 6;
+        system_info.processor_revision = 0x0d08;
+        system_info.cpu = cpu;
+        let dump = SynthMinidump::with_endian(Endian::Little).add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        dump.get_stream::<MinidumpSystemInfo>().unwrap()
+    }
+
+    #[test]
+    fn test_system_info_genuine_intel() {
+        let system_info = read_system_info(
+            ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL,
+            CpuInfo::X86CpuInfo {
+                // "GenuineIntel"
+                vendor_id: [0x756e6547, 0x49656e69, 0x6c65746e],
+                version_information: 0x000906e9,
+                feature_information: 0x06008011,
+                // AMD's extended features don't mean anything on Intel CPUs.
+                amd_extended_cpu_features: 0x20000000,
+            },
+        );
+        assert_eq!(system_info.cpu_vendor().as_deref(), Some("GenuineIntel"));
+        assert_eq!(system_info.cpu_family_model_stepping(), Some((6, 158, 9)));
+        assert_eq!(
+            system_info.cpu_features(),
+            ["fpu", "tsc", "cmov", "sse", "sse2"]
+        );
+
+        let mut output = Vec::new();
+        system_info.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  (cpu_vendor)                               = GenuineIntel\n"));
+        assert!(output
+            .contains("  (cpu_features)                             = fpu tsc cmov sse sse2\n"));
+    }
+
+    #[test]
+    fn test_system_info_authentic_amd() {
+        let system_info = read_system_info(
+            ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64,
+            CpuInfo::X86CpuInfo {
+                // "AuthenticAMD"
+                vendor_id: [0x68747541, 0x69746e65, 0x444d4163],
+                version_information: 0x00870f10,
+                feature_information: 0x04000001,
+                amd_extended_cpu_features: 0x20100000,
+            },
+        );
+        assert_eq!(system_info.cpu_vendor().as_deref(), Some("AuthenticAMD"));
+        // The extended family and model are included.
+        assert_eq!(system_info.cpu_family_model_stepping(), Some((23, 113, 0)));
+        assert_eq!(system_info.cpu_features(), ["fpu", "sse2", "nx", "lm"]);
+    }
+
+    #[test]
+    fn test_system_info_without_cpuid() {
+        // x86-64 Windows minidumps don't have the cpuid information.
+        let system_info = read_system_info(
+            ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64,
+            CpuInfo::X86CpuInfo {
+                vendor_id: [0x00000003, 0, 0],
+                version_information: 0,
+                feature_information: 0,
+                amd_extended_cpu_features: 0,
+            },
+        );
+        assert_eq!(system_info.cpu_vendor(), None);
+        assert_eq!(system_info.cpu_family_model_stepping(), Some((6, 13, 8)));
+        assert!(system_info.cpu_features().is_empty());
+    }
+
+    #[test]
+    fn test_system_info_arm() {
+        let system_info = read_system_info(
+            ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM,
+            CpuInfo::ARMCpuInfo {
+                cpuid: 0x510f06f0,
+                elf_hwcaps: 1 << 1 | 1 << 2 | 1 << 12 | 1 << 16,
+            },
+        );
+        assert_eq!(system_info.cpu_vendor().as_deref(), Some("Qualcomm"));
+        assert_eq!(system_info.cpu_family_model_stepping(), None);
+        assert_eq!(
+            system_info.cpu_features(),
+            ["half", "thumb", "neon", "vfpv4"]
+        );
+        assert_eq!(
+            system_info.cpu_info().as_deref(),
+            Some("ARMv6 Qualcomm Krait features: half,thumb,neon,vfpv4")
+        );
+    }
+
+    #[test]
+    fn test_linux_cpu_info_x86() {
+        let input = b"\
+processor	: 0
+vendor_id	: GenuineIntel
+cpu family	: 6
+model		: 158
+model name	: Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz
+microcode	: 0xde
+flags		: fpu vme de pse sse sse2 ssse3 sse4_1 sse4_2 avx avx2 bmi2
+
+processor	: 1
+vendor_id	: GenuineIntel
+cpu family	: 6
+model		: 158
+model name	: Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz
+microcode	: 0xde
+flags		: fpu vme de pse sse sse2 ssse3 sse4_1 sse4_2 avx avx2 bmi2
+
+";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cpu_info(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let cpu_info = dump.get_stream::<MinidumpLinuxCpuInfo>().unwrap();
+
+        assert_eq!(cpu_info.cpu_count(), 2);
+        assert_eq!(cpu_info.vendor(), Some("GenuineIntel"));
+        assert_eq!(
+            cpu_info.model_name(),
+            Some("Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz")
+        );
+        let flags = cpu_info.flags();
+        assert_eq!(flags.len(), 12);
+        assert!(flags.contains("sse4_2"));
+        assert!(cpu_info.has_flag("avx2"));
+        assert!(!cpu_info.has_flag("avx512f"));
+        assert!(cpu_info.arm_cpu_parts().is_empty());
+
+        let mut output = vec![];
+        cpu_info.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  cpu_count     = 2\n"));
+        assert!(output.contains(
+            "  flags         = avx avx2 bmi2 de fpu pse sse sse2 sse4_1 sse4_2 ssse3 vme\n"
+        ));
+    }
+
+    #[test]
+    fn test_linux_cpu_info_aarch64() {
+        // A big.LITTLE system: two Cortex-A53s and two Cortex-A73s.
+        let input = b"\
+processor	: 0
+BogoMIPS	: 3.84
+Features	: fp asimd evtstrm aes pmull sha1 sha2 crc32 cpuid
+CPU implementer	: 0x41
+CPU architecture: 8
+CPU variant	: 0x0
+CPU part	: 0xd03
+CPU revision	: 4
+
+processor	: 1
+BogoMIPS	: 3.84
+Features	: fp asimd evtstrm aes pmull sha1 sha2 crc32 cpuid
+CPU implementer	: 0x41
+CPU architecture: 8
+CPU variant	: 0x0
+CPU part	: 0xd03
+CPU revision	: 4
+
+processor	: 2
+BogoMIPS	: 3.84
+Features	: fp asimd evtstrm aes pmull sha1 sha2 crc32 cpuid
+CPU implementer	: 0x41
+CPU architecture: 8
+CPU variant	: 0x0
+CPU part	: 0xd09
+CPU revision	: 2
+
+processor	: 3
+BogoMIPS	: 3.84
+Features	: fp asimd evtstrm aes pmull sha1 sha2 crc32 cpuid
+CPU implementer	: 0x41
+CPU architecture: 8
+CPU variant	: 0x0
+CPU part	: 0xd09
+CPU revision	: 2
+
+Hardware	: Qualcomm Technologies, Inc SDM636
+";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cpu_info(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let cpu_info = dump.get_stream::<MinidumpLinuxCpuInfo>().unwrap();
+
+        assert_eq!(cpu_info.cpu_count(), 4);
+        assert_eq!(cpu_info.vendor(), None);
+        assert_eq!(cpu_info.model_name(), None);
+        assert!(cpu_info.has_flag("asimd"));
+        assert!(cpu_info.has_flag("crc32"));
+        assert!(!cpu_info.has_flag("sve"));
+        assert_eq!(
+            cpu_info.arm_cpu_parts(),
+            vec![(0x41, 0xd03), (0x41, 0xd03), (0x41, 0xd09), (0x41, 0xd09)]
+        );
+
+        let mut output = vec![];
+        cpu_info.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  arm_cpu_parts = 0x41:0xd03 0x41:0xd03 0x41:0xd09 0x41:0xd09\n"));
+    }
+
+    #[test]
+    fn test_linux_maps() {
+        // Whitespace intentionally wonky to test robustness
+        let input = b"
+
+ a90206ca83eb2852-b90206ca83eb3852 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  
+c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libtdb2.so  (deleted)
+
+
+";
+
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_maps(input);
+        let dump = read_synth_dump(dump).unwrap();
+
+        // Read both kinds of info to test this path on UnifiedMemoryInfo
+        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
+        let maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
+        assert!(info_list.is_none());
+        assert!(maps.is_some());
+
+        let unified_info = UnifiedMemoryInfoList::new(info_list, maps).unwrap();
+        let maps = unified_info.maps().unwrap();
+        assert!(unified_info.info().is_none());
+
+        // Assert that unified and the maps agree
+        for (info, unified) in maps.iter().zip(unified_info.iter()) {
+            if let UnifiedMemoryInfo::Map(info2) = unified {
+                assert_eq!(info, info2);
+            } else {
+                unreachable!();
+            }
+        }
+
+        let maps = maps.iter().collect::<Vec<_>>();
+        assert_eq!(maps.len(), 2);
+
+        assert_eq!(maps[0].base_address, 0xa90206ca83eb2852);
+        assert_eq!(maps[0].final_address, 0xb90206ca83eb3852);
+        assert_eq!(
+            maps[0].kind,
+            MinidumpLinuxMapKind::File(Cow::Borrowed(LinuxOsStr::from_bytes(
+                b"/usr/lib64/libtdb1.so"
+            )))
+        );
+        assert_eq!(
+            maps[0].permissions,
+            MinidumpLinuxMapPermissions::READ
+                | MinidumpLinuxMapPermissions::EXEC
+                | MinidumpLinuxMapPermissions::PRIVATE
+        );
+        assert!(maps[0].is_executable());
+
+        assert_eq!(maps[1].base_address, 0xc70206ca83eb2852);
+        assert_eq!(maps[1].final_address, 0xde0206ca83eb2852);
+        assert_eq!(
+            maps[1].kind,
+            MinidumpLinuxMapKind::DeletedFile(Cow::Borrowed(LinuxOsStr::from_bytes(
+                b"/usr/lib64/libtdb2.so"
+            )))
+        );
+        assert_eq!(
+            maps[1].permissions,
+            MinidumpLinuxMapPermissions::WRITE | MinidumpLinuxMapPermissions::SHARED
+        );
+        assert!(!maps[1].is_executable());
+    }
+
+    #[test]
+    fn test_linux_map_parse() {
+        use MinidumpLinuxMapKind::*;
+
+        let parse = |input| {
+            let string = LinuxOsStr::from_bytes(input);
+            MinidumpLinuxMapInfo::from_line(string)
+        };
+
+        // Whitespace intentionally wonky to test parser robustness
+
+        {
+            // Normal file
+            let map = parse(b"  10a00-10b00 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(
+                map.kind,
+                File(Cow::Borrowed(LinuxOsStr::from_bytes(
+                    b"/usr/lib64/libtdb1.so"
+                )))
+            );
+
+            assert_eq!(
+                map.permissions,
+                MinidumpLinuxMapPermissions::READ
+                    | MinidumpLinuxMapPermissions::EXEC
+                    | MinidumpLinuxMapPermissions::PRIVATE
+            );
+            assert!(map.is_executable());
+        }
+
+        {
+            // Deleted file (also some whitespace in the file name)
+            let map = parse(b"ffffffffff600000-ffffffffff601000 -wxs  10bac9000 fd:05 1196511  /usr/lib64/ libtdb1.so   (deleted) ");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0xffffffffff600000);
+            assert_eq!(map.final_address, 0xffffffffff601000);
+            assert_eq!(
+                map.kind,
+                DeletedFile(Cow::Borrowed(LinuxOsStr::from_bytes(
+                    b"/usr/lib64/ libtdb1.so"
+                )))
+            );
+
+            assert_eq!(
+                map.permissions,
+                MinidumpLinuxMapPermissions::WRITE
+                    | MinidumpLinuxMapPermissions::EXEC
+                    | MinidumpLinuxMapPermissions::SHARED
+            );
+            assert!(map.is_executable());
+        }
+
+        {
+            // Stack
+            let map = parse(b"10a00-10b00 -------  10bac9000 fd:05 1196511  [stack] ");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(map.kind, MainThreadStack);
+
+            assert_eq!(map.permissions, MinidumpLinuxMapPermissions::empty());
+            assert!(!map.is_executable());
+        }
+
+        {
+            // Stack with tid
+            let map = parse(b"10a00-10b00 -------  10bac9000 fd:05 1196511  [stack:1234567] ");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(map.kind, Stack(1234567));
+
+            assert_eq!(map.permissions, MinidumpLinuxMapPermissions::empty());
+            assert!(!map.is_executable());
+        }
+
+        {
+            // Heap
+            let map = parse(b"10a00-10b00 --  10bac9000 fd:05 1196511  [heap]");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(map.kind, Heap);
+
+            assert_eq!(map.permissions, MinidumpLinuxMapPermissions::empty());
+            assert!(!map.is_executable());
+        }
+
+        {
+            // Vdso
+            let map = parse(b"10a00-10b00 r-wx-  10bac9000 fd:05 1196511  [vdso]");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(map.kind, Vdso);
+
+            assert_eq!(
+                map.permissions,
+                MinidumpLinuxMapPermissions::READ
+                    | MinidumpLinuxMapPermissions::WRITE
+                    | MinidumpLinuxMapPermissions::EXEC
+            );
+            assert!(map.is_executable());
+        }
+
+        {
+            // Unknown Special
+            let map = parse(b"10a00-10b00 r-wx-  10bac9000 fd:05 1196511  [asdfasd]");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(
+                map.kind,
+                UnknownSpecial(Cow::Borrowed(LinuxOsStr::from_bytes(b"[asdfasd]")))
+            );
+
+            assert_eq!(
+                map.permissions,
+                MinidumpLinuxMapPermissions::READ
+                    | MinidumpLinuxMapPermissions::WRITE
+                    | MinidumpLinuxMapPermissions::EXEC
+            );
+            assert!(map.is_executable());
+        }
+
+        {
+            // Anonymous
+            let map = parse(b"10a00-10b00 -r-  10bac9000 fd:05 1196511  ");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(map.kind, AnonymousMap);
+
+            assert_eq!(map.permissions, MinidumpLinuxMapPermissions::READ);
+            assert!(!map.is_executable());
+        }
+
+        {
+            // Truncated defaults to anonymous
+            let map = parse(b"10a00-10b00");
+            let map = map.unwrap();
+
+            assert_eq!(map.base_address, 0x10a00);
+            assert_eq!(map.final_address, 0x10b00);
+            assert_eq!(map.kind, AnonymousMap);
+
+            assert_eq!(map.permissions, MinidumpLinuxMapPermissions::empty());
+            assert!(!map.is_executable());
+        }
+
+        {
+            // blank line
+            let map = parse(b"");
+            assert!(map.is_err());
+
+            let map = parse(b"   ");
+            assert!(map.is_err());
+        }
+
+        {
+            // bad addresses
+            let map = parse(b"  -10b00 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
+            assert!(map.is_err());
+
+            let map = parse(b"  10b00- r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
+            assert!(map.is_err());
+
+            let map = parse(b"  10b00 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
+            assert!(map.is_err());
+        }
+
+        {
+            // bad [stack:<tid>]
+            let map = parse(b"  -10b00 r-xp  10bac9000 fd:05 1196511 [stack:] ");
+            assert!(map.is_err());
+
+            let map = parse(b"  -10b00 r-xp  10bac9000 fd:05 1196511 [stack:a10] ");
+            assert!(map.is_err());
+        }
+
+        {
+            // Garbled offset/dev/inode are tolerated
+            let map = parse(b"10a00-10b00 r-xp zzz fd 12a /usr/lib64/libtdb1.so");
+            let map = map.unwrap();
+
+            assert_eq!(map.offset, 0);
+            assert_eq!(map.dev, (0, 0));
+            assert_eq!(map.inode, 0);
+            assert_eq!(
+                map.kind,
+                File(Cow::Borrowed(LinuxOsStr::from_bytes(
+                    b"/usr/lib64/libtdb1.so"
+                )))
+            );
+        }
+    }
+
+    #[test]
+    fn test_linux_maps_real_world() {
+        use MinidumpLinuxMapKind::*;
+
+        // Excerpts of real maps, including a 32-bit process.
+        let input = b"\
+55d3c1a00000-55d3c1a2c000 r-xp 00002000 fd:01 2621535                    /usr/bin/crashy
+55d3c1a2c000-55d3c1a2d000 rw-p 00000000 00:00 0 
+7f1e2c000000-7f1e2c021000 rw-p 00000000 00:00 0                          [anon:libc_malloc]
+7f1e2d5f0000-7f1e2d600000 r-xp 00000000 00:05 7342                       /memfd:jit (deleted)
+7ffc8e1c3000-7ffc8e1e4000 rw-p 00000000 00:00 0                          [stack]
+08048000-08056000 r-xp 00000000 03:0c 64593      /usr/sbin/gpm
+f7f3b000-f7f3c000 r-xp 00000000 00:00 0          [vdso]
+";
+
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_maps(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let maps = dump.get_stream::<MinidumpLinuxMaps>().unwrap();
+        assert_eq!(maps.iter().count(), 7);
+
+        let exe = maps.memory_info_at_address(0x55d3c1a00000).unwrap();
+        assert_eq!(
+            exe.kind,
+            File(Cow::Borrowed(LinuxOsStr::from_bytes(b"/usr/bin/crashy")))
+        );
+        assert_eq!(exe.offset, 0x2000);
+        assert_eq!(exe.dev, (0xfd, 0x01));
+        assert_eq!(exe.inode, 2621535);
+        assert!(exe.is_executable());
+        assert_eq!(exe.describe(), "a r-xp mapping of /usr/bin/crashy");
+
+        // The end address is exclusive
+        let bss = maps.memory_info_at_address(0x55d3c1a2c000).unwrap();
+        assert_eq!(bss.kind, AnonymousMap);
+        assert!(!bss.is_executable());
+        assert_eq!(bss.describe(), "an anonymous rw-p mapping");
+        assert!(maps.memory_info_at_address(0x55d3c1a2d000).is_none());
+
+        let malloc = maps.memory_info_at_address(0x7f1e2c000100).unwrap();
+        assert_eq!(malloc.kind, AnonymousMap);
+
+        let jit = maps.memory_info_at_address(0x7f1e2d5fffff).unwrap();
+        assert_eq!(
+            jit.kind,
+            DeletedFile(Cow::Borrowed(LinuxOsStr::from_bytes(b"/memfd:jit")))
+        );
+        assert!(jit.is_executable());
+        assert_eq!(jit.describe(), "a r-xp mapping of deleted file /memfd:jit");
+
+        let stack = maps.memory_info_at_address(0x7ffc8e1e3ff0).unwrap();
+        assert_eq!(stack.kind, MainThreadStack);
+        assert_eq!(stack.describe(), "the main thread's rw-p stack");
+
+        let gpm = maps.memory_info_at_address(0x08050000).unwrap();
+        assert_eq!(gpm.base_address, 0x08048000);
+        assert_eq!(gpm.final_address, 0x08056000);
+        assert_eq!(gpm.dev, (0x03, 0x0c));
+
+        let vdso = maps.memory_info_at_address(0xf7f3b000).unwrap();
+        assert_eq!(vdso.kind, Vdso);
+        assert!(vdso.is_executable());
+
+        let by_addr = maps
+            .by_addr()
+            .map(|map| map.base_address)
+            .collect::<Vec<_>>();
+        assert_eq!(by_addr[0], 0x08048000);
+        assert_eq!(by_addr[6], 0x7ffc8e1c3000);
+    }
+
+    #[test]
+    fn test_module_list_overlap() {
+        let name1 = DumpString::new("module 1", Endian::Little);
+        let name2 = DumpString::new("module 2", Endian::Little);
+        let name3 = DumpString::new("module 3", Endian::Little);
+        let name4 = DumpString::new("module 4", Endian::Little);
+        let name5 = DumpString::new("module 5", Endian::Little);
+        let cv_record = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32) // signature
+            // signature, a GUID
+            .D32(0xabcd1234)
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1) // age
+            .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
+        let module1 = SynthModule::new(
+            Endian::Little,
+            0x100000000,
+            0x4000,
+            &name1,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        // module2 overlaps module1 exactly
+        let module2 = SynthModule::new(
+            Endian::Little,
+            0x100000000,
+            0x4000,
+            &name2,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        // module3 overlaps module1 partially
+        let module3 = SynthModule::new(
+            Endian::Little,
+            0x100000001,
+            0x4000,
+            &name3,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        // module4 is fully contained within module1
+        let module4 = SynthModule::new(
+            Endian::Little,
+            0x100000001,
+            0x3000,
+            &name4,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        // module5 is cool, though.
+        let module5 = SynthModule::new(
+            Endian::Little,
+            0x100004000,
+            0x4000,
+            &name5,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module1)
+            .add_module(module2)
+            .add_module(module3)
+            .add_module(module4)
+            .add_module(module5)
+            .add(name1)
+            .add(name2)
+            .add(name3)
+            .add(name4)
+            .add(name5)
+            .add(cv_record);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 5);
+        assert_eq!(modules[0].base_address(), 0x100000000);
+        assert_eq!(modules[0].size(), 0x4000);
+        assert_eq!(modules[0].code_file(), "module 1");
+        assert_eq!(modules[1].base_address(), 0x100000000);
+        assert_eq!(modules[1].size(), 0x4000);
+        assert_eq!(modules[1].code_file(), "module 2");
+        assert_eq!(modules[2].base_address(), 0x100000001);
+        assert_eq!(modules[2].size(), 0x4000);
+        assert_eq!(modules[2].code_file(), "module 3");
+        assert_eq!(modules[3].base_address(), 0x100000001);
+        assert_eq!(modules[3].size(), 0x3000);
+        assert_eq!(modules[3].code_file(), "module 4");
+        assert_eq!(modules[4].base_address(), 0x100004000);
+        assert_eq!(modules[4].size(), 0x4000);
+        assert_eq!(modules[4].code_file(), "module 5");
+
+        // module_at_address should discard overlapping modules.
+        assert_eq!(module_list.by_addr().count(), 2);
+        assert_eq!(
+            module_list
+                .module_at_address(0x100001000)
+                .unwrap()
+                .code_file(),
+            "module 1"
+        );
+        assert_eq!(
+            module_list
+                .module_at_address(0x100005000)
+                .unwrap()
+                .code_file(),
+            "module 5"
+        );
+    }
+
+    #[test]
+    fn test_memory_list() {
+        const CONTENTS: &[u8] = b"memory_contents";
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS),
+            0x309d68010bd21b2c,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].base_address, 0x309d68010bd21b2c);
+        assert_eq!(regions[0].size, CONTENTS.len() as u64);
+        assert_eq!(&regions[0].bytes, &CONTENTS);
+    }
+
+    #[test]
+    fn test_memory_reads() {
+        let bytes = b"\x01\x02\x03\x04\x05\x06\x07\x08hi\0\xffh\0i\0\0\0j\0";
+        let memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: 0x1000,
+            size: bytes.len() as u64,
+            bytes,
+            endian: LE,
+        };
+        let end = 0x1000 + bytes.len() as u64;
+
+        assert_eq!(memory.read_u8(0x1000), Some(0x01));
+        assert_eq!(memory.read_u16(0x1000), Some(0x0201));
+        assert_eq!(memory.read_u32(0x1001), Some(0x05040302));
+        assert_eq!(memory.read_u64(0x1000), Some(0x0807060504030201));
+        assert_eq!(memory.read_ptr(0x1000, 4), Some(0x04030201));
+        assert_eq!(memory.read_ptr(0x1000, 8), Some(0x0807060504030201));
+        assert_eq!(memory.read_ptr(0x1000, 2), None);
+        assert_eq!(memory.read_bytes(0x1002, 3), Some(&b"\x03\x04\x05"[..]));
+        assert_eq!(memory.read_bytes(end, 0), Some(&b""[..]));
+
+        // Reads straddling the end of the region.
+        assert_eq!(memory.read_u8(end - 1), Some(0));
+        assert_eq!(memory.read_u8(end), None);
+        assert_eq!(memory.read_u16(end - 1), None);
+        assert_eq!(memory.read_u32(end - 3), None);
+        assert_eq!(memory.read_u64(end - 7), None);
+        assert_eq!(memory.read_bytes(end - 2, 3), None);
+        assert_eq!(memory.read_bytes(0x1000, usize::MAX), None);
+        assert_eq!(memory.read_u64(u64::MAX), None);
+
+        // Addresses below the region.
+        assert_eq!(memory.read_u8(0xfff), None);
+        assert_eq!(memory.read_u32(0xffe), None);
+        assert_eq!(memory.read_bytes(0, 0x1001), None);
+        assert_eq!(memory.read_cstring(0xfff, 10), None);
+        assert_eq!(memory.read_utf16_string(0xfff, 10), None);
+
+        assert_eq!(memory.read_cstring(0x1008, 10).unwrap(), "hi");
+        assert_eq!(memory.read_cstring(0x1008, 1).unwrap(), "h");
+        assert_eq!(memory.read_cstring(0x100a, 10).unwrap(), "");
+        assert_eq!(memory.read_cstring(0x100b, 1).unwrap(), "\u{fffd}");
+        // Strings that run off the end of the region are cut short.
+        assert_eq!(memory.read_cstring(end - 2, 10).unwrap(), "j");
+        assert_eq!(memory.read_cstring(end, 10), None);
+
+        assert_eq!(memory.read_utf16_string(0x100c, 10).unwrap(), "hi");
+        assert_eq!(memory.read_utf16_string(0x100c, 1).unwrap(), "h");
+        assert_eq!(memory.read_utf16_string(0x1010, 10).unwrap(), "");
+        assert_eq!(memory.read_utf16_string(end - 2, 10).unwrap(), "j");
+        assert_eq!(memory.read_utf16_string(end - 1, 10).unwrap(), "");
+    }
+
+    #[test]
+    fn test_memory64_list() {
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(1, 0x100),
+            0x1000,
+        );
+        let memory2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"memory_contents"),
+            0x7fff_0000_2000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory64(memory1)
+            .add_memory64(memory2);
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpMemoryList<'_>>().is_err());
+        let memory_list = dump.get_stream::<MinidumpMemory64List<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].base_address, 0x1000);
+        assert_eq!(regions[0].size, 0x100);
+        assert_eq!(regions[0].bytes, &[1; 0x100][..]);
+        assert_eq!(regions[1].base_address, 0x7fff_0000_2000);
+        assert_eq!(regions[1].size, 15);
+        assert_eq!(regions[1].bytes, b"memory_contents");
+
+        let region = memory_list.memory_at_address(0x7fff_0000_2007).unwrap();
+        assert_eq!(region.base_address, 0x7fff_0000_2000);
+        assert_eq!(
+            region.get_memory_at_address::<u32>(0x7fff_0000_2007),
+            Some(u32::from_le_bytes(*b"cont"))
+        );
+        assert_eq!(
+            memory_list.memory_at_address(0x10ff).unwrap().base_address,
+            0x1000
+        );
+        assert!(memory_list.memory_at_address(0x1100).is_none());
+    }
+
+    #[test]
+    fn test_streaming_memory64_list() {
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(1, 0x100),
+            0x1000,
+        );
+        let memory2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"memory_contents"),
+            0x7fff_0000_2000,
+        );
+        let name = DumpString::new("MainThread", Endian::Little);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_name(ThreadName::new(Endian::Little, 0x1, Some(&name)))
+            .add(name)
+            .add_memory64(memory1)
+            .add_memory64(memory2);
+        let bytes = dump.finish().unwrap();
+        let file_size = bytes.len();
+        let dump = StreamingMinidump::read(io::Cursor::new(bytes)).unwrap();
+
+        // Only the directory has been read so far.
+        assert!(dump.metadata.get().is_none());
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(&*thread_names.get_name(0x1).unwrap(), "MainThread");
+        // The memory comes last (followed only by the stream directory, which
+        // is aligned to 4 bytes), and isn't loaded with everything else.
+        let directory_size = 2 * mem::size_of::<md::MINIDUMP_DIRECTORY>();
+        let padding = 1;
+        assert_eq!(
+            dump.metadata.get().unwrap().len(),
+            file_size - 0x100 - 15 - padding - directory_size
+        );
+        assert_eq!(
+            dump.get_stream::<MinidumpMemory64List>()
+                .unwrap()
+                .iter()
+                .count(),
+            0
+        );
+
+        assert_eq!(dump.read_memory64(0x1000, 0x100).unwrap(), vec![1; 0x100]);
+        assert_eq!(
+            dump.read_memory64(0x7fff_0000_2007, 4).unwrap(),
+            b"cont".to_vec()
+        );
+        // Past the end of a region
+        assert!(dump.read_memory64(0x7fff_0000_2007, 9).is_err());
+        assert_eq!(
+            dump.get_stream::<MinidumpMemoryList>().unwrap_err(),
+            Error::StreamNotFound
+        );
+    }
+
+    #[test]
+    fn test_streaming_to_minidump() {
+        // A full-memory dump, where the thread's stack is only in the Memory64List.
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack_desc = Memory::with_section(Section::with_endian(Endian::Little), 0x1010);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack_desc, &context);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .append_repeated(0, 0x10)
+                .D32(0xabcdef01)
+                .append_repeated(0, 0xfec),
+            0x1000,
+        );
+        let heap = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(2, 0x10000),
+            0x100000,
+        );
+        let name = DumpString::new("MainThread", Endian::Little);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add(stack_desc)
+            .add_thread_name(ThreadName::new(Endian::Little, 0x1234, Some(&name)))
+            .add(name)
+            .add_memory64(stack)
+            .add_memory64(heap);
+        let bytes = dump.finish().unwrap();
+        let file_size = bytes.len();
+        let streaming = StreamingMinidump::read(io::Cursor::new(bytes)).unwrap();
+        let dump = streaming.to_minidump().unwrap();
+
+        // Only the (4KiB) stack was loaded, not the 64KiB of heap.
+        assert!(file_size > 0x11000);
+        assert!(dump.data.len() < 0x2000);
+        let memory = dump.memory().unwrap();
+        let regions = memory.memory64_list().iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].base_address, 0x1010);
+        assert_eq!(regions[0].size, 0xff0);
+        assert!(memory.memory_at_address(0x100000).is_none());
+
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let thread = &thread_list.threads[0];
+        assert!(thread.direct_stack().is_some());
+        let stack = thread.stack_memory(&memory).unwrap();
+        assert_eq!(stack.base_address, 0x1010);
+        assert_eq!(stack.get_memory_at_address::<u32>(0x1010), Some(0xabcdef01));
+
+        // Everything else is still there.
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(&*thread_names.get_name(0x1234).unwrap(), "MainThread");
+        assert_eq!(dump.header.stream_count, 3);
+        assert!(dump.warnings().is_empty());
+
+        // Without a Memory64List there's nothing to leave out.
+        let bytes = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_name(ThreadName::new(Endian::Little, 0x1, None))
+            .finish()
+            .unwrap();
+        let streaming = StreamingMinidump::read(io::Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(streaming.to_minidump().unwrap().data, bytes);
+    }
+
+    #[test]
+    fn test_streaming_bad_header() {
+        let bytes = b"MDMP but not really".to_vec();
+        assert!(StreamingMinidump::read(io::Cursor::new(bytes)).is_err());
+
+        // A directory claiming more streams than fit in the file.
+        let mut bytes = SynthMinidump::with_endian(Endian::Little).finish().unwrap();
+        bytes[8] = 0xff;
+        assert!(StreamingMinidump::read(io::Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_memory64_list_bad_descriptors() {
+        // The second region claims more memory than there is, so it's cut
+        // short at the end of the file, but the first is still fine.
+        let base_rva = Label::new();
+        let stream = Section::with_endian(Endian::Little)
+            .D64(2)
+            .D64(&base_rva)
+            .D64(0x1000)
+            .D64(4)
+            .D64(0x2000)
+            .D64(0x1000)
+            .mark(&base_rva)
+            .append_bytes(b"abcd");
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::Memory64ListStream as u32,
+            section: stream,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemory64List<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].bytes, b"abcd");
+        assert_eq!(regions[1].base_address, 0x2000);
+        assert!(regions[1].size < 0x1000);
+        assert_eq!(regions[1].size, regions[1].bytes.len() as u64);
+        assert_eq!(
+            dump.warnings(),
+            &[MinidumpWarning::TruncatedMemory {
+                index: 1,
+                base_address: 0x2000,
+                expected: 0x1000,
+                actual: regions[1].size,
+            }]
+        );
+
+        // More descriptors than fit in the stream.
+        let stream = Section::with_endian(Endian::Little).D64(2).D64(0);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::Memory64ListStream as u32,
+            section: stream,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpMemory64List<'_>>().is_err());
+    }
+
+    #[test]
+    fn test_thread_stack_in_memory64_list() {
+        // Full-memory dumps don't point the threads' stacks at any memory.
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack_desc = Memory::with_section(Section::with_endian(Endian::Little), 0x1010);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack_desc, &context);
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .append_repeated(0, 0x10)
+                .D32(0xabcdef01)
+                .append_repeated(0, 0xfec),
+            0x1000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add(stack_desc)
+            .add_memory64(memory);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let thread = &thread_list.threads[0];
+        assert!(thread.direct_stack().is_none());
+        // Nothing is looked up until the stack is asked for.
+        assert!(thread.stack.get().is_none());
+
+        let memory_list = dump
+            .get_stream::<MinidumpMemoryList<'_>>()
+            .unwrap_or_default();
+        let memory = UnifiedMemoryList::new(memory_list, MinidumpMemory64List::new());
+        assert!(thread.stack_memory(&memory).is_none());
+        assert!(thread.stack.get().is_none());
+
+        let memory = dump.memory().unwrap();
+        let stack = thread.stack_memory(&memory).unwrap();
+        assert_eq!(stack.base_address, 0x1010);
+        assert_eq!(stack.size, 0xff0);
+        assert_eq!(stack.get_memory_at_address::<u32>(0x1010), Some(0xabcdef01));
+        // The stack is borrowed from the dump, not copied out of it.
+        assert!(dump.data.as_ptr_range().contains(&stack.bytes.as_ptr()));
+
+        // And it's remembered, even if the memory list isn't passed again.
+        let cached = thread.stack.get().unwrap();
+        assert_eq!(cached.bytes.as_ptr(), stack.bytes.as_ptr());
+        let empty = UnifiedMemoryList::default();
+        let stack = thread.stack_memory(&empty).unwrap();
+        assert_eq!(stack.base_address, 0x1010);
+    }
+
+    #[test]
+    fn test_memory_list_lifetimes() {
+        // A memory list should not own any of the minidump data.
+        const CONTENTS: &[u8] = b"memory_contents";
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS),
+            0x309d68010bd21b2c,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
+        let dump = read_synth_dump(dump).unwrap();
+        let mem_slices: Vec<&[u8]> = {
+            let mem_list: MinidumpMemoryList<'_> = dump.get_stream().unwrap();
+            mem_list.iter().map(|mem| mem.bytes).collect()
+        };
+        assert_eq!(mem_slices[0], CONTENTS);
+    }
+
+    #[test]
+    fn test_memory_list_overlap() {
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        // memory2 overlaps memory1 exactly
+        let memory2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(1, 0x1000),
+            0x1000,
+        );
+        // memory3 overlaps memory1 partially
+        let memory3 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(2, 0x1000),
+            0x1001,
+        );
+        // memory4 is fully contained within memory1
+        let memory4 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(3, 0x100),
+            0x1001,
+        );
+        // memory5 is cool, though.
+        let memory5 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(4, 0x1000),
+            0x2000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(memory1)
+            .add_memory(memory2)
+            .add_memory(memory3)
+            .add_memory(memory4)
+            .add_memory(memory5);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 5);
+        assert_eq!(regions[0].base_address, 0x1000);
+        assert_eq!(regions[0].size, 0x1000);
+        assert_eq!(regions[1].base_address, 0x1000);
+        assert_eq!(regions[1].size, 0x1000);
+        assert_eq!(regions[2].base_address, 0x1001);
+        assert_eq!(regions[2].size, 0x1000);
+        assert_eq!(regions[3].base_address, 0x1001);
+        assert_eq!(regions[3].size, 0x100);
+        assert_eq!(regions[4].base_address, 0x2000);
+        assert_eq!(regions[4].size, 0x1000);
+
+        // memory_at_address should discard overlapping regions.
+        assert_eq!(memory_list.by_addr().count(), 2);
+        let m1 = memory_list.memory_at_address(0x1a00).unwrap();
+        assert_eq!(m1.base_address, 0x1000);
+        assert_eq!(m1.size, 0x1000);
+        assert_eq!(m1.bytes, &[0u8; 0x1000][..]);
+        let m2 = memory_list.memory_at_address(0x2a00).unwrap();
+        assert_eq!(m2.base_address, 0x2000);
+        assert_eq!(m2.size, 0x1000);
+        assert_eq!(m2.bytes, &[4u8; 0x1000][..]);
+    }
+
+    #[test]
+    fn test_unified_memory_list_memory_only() {
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"memory_contents"),
+            0x2000,
+        );
+        let memory2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(1, 0x100),
+            0x1000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(memory1)
+            .add_memory(memory2);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory = dump.memory().unwrap();
+        assert_eq!(memory.memory_list().iter().count(), 2);
+        assert_eq!(memory.memory64_list().iter().count(), 0);
+
+        let regions = memory.by_addr().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 2);
+        assert!(matches!(regions[0], UnifiedMemory::Memory(_)));
+        assert_eq!(regions[0].base_address(), 0x1000);
+        assert_eq!(regions[1].base_address(), 0x2000);
+        assert_eq!(memory.total_size(), 0x100 + 15);
+
+        let region = memory.memory_at_address(0x2007).unwrap();
+        assert_eq!(region.base_address(), 0x2000);
+        assert_eq!(region.bytes(), b"memory_contents");
+        assert_eq!(region.read_u32(0x2007), Some(u32::from_le_bytes(*b"cont")));
+        assert_eq!(
+            memory.memory_at_address(0x10ff).unwrap().read_u8(0x10ff),
+            Some(1)
+        );
+        assert!(memory.memory_at_address(0x1100).is_none());
+    }
+
+    #[test]
+    fn test_unified_memory_list_memory64_only() {
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(1, 0x100),
+            0x1000,
+        );
+        let memory2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"memory_contents"),
+            0x7fff_0000_2000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory64(memory1)
+            .add_memory64(memory2);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory = dump.memory().unwrap();
+        assert_eq!(memory.memory_list().iter().count(), 0);
+        assert_eq!(memory.memory64_list().iter().count(), 2);
+
+        let regions = memory.by_addr().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 2);
+        assert!(matches!(regions[0], UnifiedMemory::Memory64(_)));
+        assert_eq!(regions[0].base_address(), 0x1000);
+        assert_eq!(regions[1].base_address(), 0x7fff_0000_2000);
+        assert_eq!(memory.total_size(), 0x100 + 15);
+
+        let region = memory.memory_at_address(0x7fff_0000_2007).unwrap();
+        assert_eq!(region.base_address(), 0x7fff_0000_2000);
+        assert_eq!(
+            region.read_u32(0x7fff_0000_2007),
+            Some(u32::from_le_bytes(*b"cont"))
+        );
+        assert!(memory.memory_at_address(0x1100).is_none());
+    }
+
+    #[test]
+    fn test_unified_memory_list_both() {
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(1, 0x100),
+            0x1000,
+        );
+        // memory2 is partially overlapped by the larger memory64_1.
+        let memory2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(2, 0x100),
+            0x2080,
+        );
+        let memory64_1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(3, 0x1000),
+            0x2000,
+        );
+        // memory64_2 exactly overlaps memory1, and is later in the dump.
+        let memory64_2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(4, 0x100),
+            0x1000,
+        );
+        let memory64_3 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(5, 0x10),
+            0x8000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(memory1)
+            .add_memory(memory2)
+            .add_memory64(memory64_1)
+            .add_memory64(memory64_2)
+            .add_memory64(memory64_3);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory = dump.memory().unwrap();
+        assert_eq!(memory.memory_list().iter().count(), 2);
+        assert_eq!(memory.memory64_list().iter().count(), 3);
+
+        let regions = memory.by_addr().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 3);
+        assert!(matches!(regions[0], UnifiedMemory::Memory64(_)));
+        assert_eq!(regions[0].base_address(), 0x1000);
+        assert_eq!(regions[0].bytes(), &[4u8; 0x100][..]);
+        assert!(matches!(regions[1], UnifiedMemory::Memory64(_)));
+        assert_eq!(regions[1].base_address(), 0x2000);
+        assert_eq!(regions[1].bytes(), &[3u8; 0x1000][..]);
+        assert_eq!(regions[2].base_address(), 0x8000);
+        assert_eq!(memory.total_size(), 0x100 + 0x1000 + 0x10);
+
+        assert_eq!(
+            memory.memory_at_address(0x1050).unwrap().read_u8(0x1050),
+            Some(4)
+        );
+        assert_eq!(
+            memory.memory_at_address(0x2100).unwrap().read_u8(0x2100),
+            Some(3)
+        );
+        assert_eq!(
+            memory.memory_at_address(0x800f).unwrap().read_u8(0x800f),
+            Some(5)
+        );
+        assert!(memory.memory_at_address(0x3000).is_none());
+    }
+
+    #[test]
+    fn test_unified_memory_list_none() {
+        let dump = read_synth_dump(SynthMinidump::with_endian(Endian::Little)).unwrap();
+        assert_eq!(dump.memory().unwrap_err(), Error::StreamNotFound);
+        let memory = UnifiedMemoryList::default();
+        assert_eq!(memory.by_addr().count(), 0);
+        assert_eq!(memory.total_size(), 0);
+        assert!(memory.memory_at_address(0x1000).is_none());
+    }
+
+    #[test]
+    fn test_misc_info() {
+        const PID: u32 = 0x1234abcd;
+        const PROCESS_TIMES: MiscFieldsProcessTimes = MiscFieldsProcessTimes {
+            process_create_time: 0xf0f0b0b0,
+            process_user_time: 0xf030a020,
+            process_kernel_time: 0xa010b420,
+        };
+
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.process_id = Some(PID);
+        misc.process_times = Some(PROCESS_TIMES);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(misc);
+        let dump = read_synth_dump(dump).unwrap();
+        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
+        assert_eq!(misc.raw.process_id(), Some(&PID));
+        assert_eq!(
+            misc.process_create_time().unwrap(),
+            Utc.timestamp(PROCESS_TIMES.process_create_time as i64, 0)
+        );
+        assert_eq!(
+            *misc.raw.process_user_time().unwrap(),
+            PROCESS_TIMES.process_user_time
+        );
+        assert_eq!(
+            *misc.raw.process_kernel_time().unwrap(),
+            PROCESS_TIMES.process_kernel_time
+        );
+    }
+
+    #[test]
+    fn test_misc_info_large() {
+        const PID: u32 = 0x1234abcd;
+        const PROCESS_TIMES: MiscFieldsProcessTimes = MiscFieldsProcessTimes {
+            process_create_time: 0xf0f0b0b0,
+            process_user_time: 0xf030a020,
+            process_kernel_time: 0xa010b420,
+        };
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.process_id = Some(PID);
+        misc.process_times = Some(PROCESS_TIMES);
+        // Make it larger.
+        misc.pad_to_size = Some(mem::size_of::<md::MINIDUMP_MISC_INFO>() + 32);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(misc);
+        let dump = read_synth_dump(dump).unwrap();
+        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
+        assert_eq!(misc.raw.process_id(), Some(&PID));
+        assert_eq!(
+            misc.process_create_time().unwrap(),
+            Utc.timestamp(PROCESS_TIMES.process_create_time as i64, 0)
+        );
+        assert_eq!(
+            *misc.raw.process_user_time().unwrap(),
+            PROCESS_TIMES.process_user_time
+        );
+        assert_eq!(
+            *misc.raw.process_kernel_time().unwrap(),
+            PROCESS_TIMES.process_kernel_time
+        );
+    }
+
+    fn ascii_string_to_utf16(input: &str) -> Vec<u16> {
+        input.chars().map(|c| c as u16).collect()
+    }
+
+    #[test]
+    fn test_misc_info_5() {
+        // MISC_INFO fields
+        const PID: u32 = 0x1234abcd;
+        const PROCESS_TIMES: MiscFieldsProcessTimes = MiscFieldsProcessTimes {
+            process_create_time: 0xf0f0b0b0,
+            process_user_time: 0xf030a020,
+            process_kernel_time: 0xa010b420,
+        };
+
+        // MISC_INFO_2 fields
+        const POWER_INFO: MiscFieldsPowerInfo = MiscFieldsPowerInfo {
+            processor_max_mhz: 0x45873234,
+            processor_current_mhz: 0x2134018a,
+            processor_mhz_limit: 0x3423aead,
+            processor_max_idle_state: 0x123aef12,
+            processor_current_idle_state: 0x1205af3a,
+        };
+
+        // MISC_INFO_3 fields
+        const PROCESS_INTEGRITY_LEVEL: u32 = 0x35603403;
+        const PROCESS_EXECUTE_FLAGS: u32 = 0xa4e09da1;
+        const PROTECTED_PROCESS: u32 = 0x12345678;
+
+        let mut standard_name = [0; 32];
+        let mut daylight_name = [0; 32];
+        let bare_standard_name = ascii_string_to_utf16("Pacific Standard Time");
+        let bare_daylight_name = ascii_string_to_utf16("Pacific Daylight Time");
+        standard_name[..bare_standard_name.len()].copy_from_slice(&bare_standard_name);
+        daylight_name[..bare_daylight_name.len()].copy_from_slice(&bare_daylight_name);
+
+        const TIME_ZONE_ID: u32 = 2;
+        const BIAS: i32 = 2;
+        const STANDARD_BIAS: i32 = 1;
+        const DAYLIGHT_BIAS: i32 = -60;
+        const STANDARD_DATE: md::SYSTEMTIME = md::SYSTEMTIME {
+            year: 0,
+            month: 11,
+            day_of_week: 2,
+            day: 1,
+            hour: 2,
+            minute: 33,
+            second: 51,
+            milliseconds: 123,
+        };
+        const DAYLIGHT_DATE: md::SYSTEMTIME = md::SYSTEMTIME {
+            year: 0,
+            month: 3,
+            day_of_week: 4,
+            day: 2,
+            hour: 3,
+            minute: 41,
+            second: 19,
+            milliseconds: 512,
+        };
+
+        let time_zone = MiscFieldsTimeZone {
+            time_zone_id: TIME_ZONE_ID,
+            time_zone: md::TIME_ZONE_INFORMATION {
+                bias: BIAS,
+                standard_bias: STANDARD_BIAS,
+                daylight_bias: DAYLIGHT_BIAS,
+                daylight_name,
+                standard_name,
+                standard_date: STANDARD_DATE.clone(),
+                daylight_date: DAYLIGHT_DATE.clone(),
+            },
+        };
+
+        // MISC_INFO_4 fields
+        let mut build_string = [0; 260];
+        let mut dbg_bld_str = [0; 40];
+        let bare_build_string = ascii_string_to_utf16("hello");
+        let bare_dbg_bld_str = ascii_string_to_utf16("world");
+        build_string[..bare_build_string.len()].copy_from_slice(&bare_build_string);
+        dbg_bld_str[..bare_dbg_bld_str.len()].copy_from_slice(&bare_dbg_bld_str);
+
+        let build_strings = MiscFieldsBuildString {
+            build_string,
+            dbg_bld_str,
+        };
+
+        // MISC_INFO_5 fields
+        const SIZE_OF_INFO: u32 = mem::size_of::<md::XSTATE_CONFIG_FEATURE_MSC_INFO>() as u32;
+        const CONTEXT_SIZE: u32 = 0x1234523f;
+        const PROCESS_COOKIE: u32 = 0x1234dfe0;
+        const KNOWN_FEATURE_IDX: usize = md::XstateFeatureIndex::LEGACY_SSE as usize;
+        const UNKNOWN_FEATURE_IDX: usize = 39;
+        let mut enabled_features = 0;
+        let mut features = [md::XSTATE_FEATURE::default(); 64];
+        // One known feature and one unknown feature.
+        enabled_features |= 1 << KNOWN_FEATURE_IDX;
+        features[KNOWN_FEATURE_IDX] = md::XSTATE_FEATURE {
+            offset: 0,
+            size: 140,
+        };
+        enabled_features |= 1 << UNKNOWN_FEATURE_IDX;
+        features[UNKNOWN_FEATURE_IDX] = md::XSTATE_FEATURE {
+            offset: 320,
+            size: 1100,
+        };
+        let misc_5 = MiscInfo5Fields {
+            xstate_data: md::XSTATE_CONFIG_FEATURE_MSC_INFO {
+                size_of_info: SIZE_OF_INFO,
+                context_size: CONTEXT_SIZE,
+                enabled_features,
+                features,
+            },
+            process_cookie: Some(PROCESS_COOKIE),
+        };
+
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.process_id = Some(PID);
+        misc.process_times = Some(PROCESS_TIMES);
+        misc.power_info = Some(POWER_INFO);
+        misc.process_integrity_level = Some(PROCESS_INTEGRITY_LEVEL);
+        misc.protected_process = Some(PROTECTED_PROCESS);
+        misc.process_execute_flags = Some(PROCESS_EXECUTE_FLAGS);
+        misc.time_zone = Some(time_zone);
+        misc.build_strings = Some(build_strings);
+        misc.misc_5 = Some(misc_5);
 
-        {
-            // Truncated defaults to anonymous
-            let map = parse(b"10a00-10b00");
-            let map = map.unwrap();
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(misc);
+        let dump = read_synth_dump(dump).unwrap();
+        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
 
-            assert_eq!(map.base_address, 0x10a00);
-            assert_eq!(map.final_address, 0x10b00);
-            assert_eq!(map.kind, AnonymousMap);
+        // MISC_INFO fields
+        assert_eq!(misc.raw.process_id(), Some(&PID));
+        assert_eq!(
+            misc.process_create_time().unwrap(),
+            Utc.timestamp(PROCESS_TIMES.process_create_time as i64, 0)
+        );
+        assert_eq!(
+            *misc.raw.process_user_time().unwrap(),
+            PROCESS_TIMES.process_user_time
+        );
+        assert_eq!(
+            *misc.raw.process_kernel_time().unwrap(),
+            PROCESS_TIMES.process_kernel_time
+        );
 
-            assert!(!map.is_read);
-            assert!(!map.is_write);
-            assert!(!map.is_exec);
-            assert!(!map.is_executable());
-            assert!(!map.is_private);
-            assert!(!map.is_shared);
-        }
+        // MISC_INFO_2 fields
+        assert_eq!(
+            *misc.raw.processor_max_mhz().unwrap(),
+            POWER_INFO.processor_max_mhz,
+        );
+        assert_eq!(
+            *misc.raw.processor_current_mhz().unwrap(),
+            POWER_INFO.processor_current_mhz,
+        );
+        assert_eq!(
+            *misc.raw.processor_mhz_limit().unwrap(),
+            POWER_INFO.processor_mhz_limit,
+        );
+        assert_eq!(
+            *misc.raw.processor_max_idle_state().unwrap(),
+            POWER_INFO.processor_max_idle_state,
+        );
+        assert_eq!(
+            *misc.raw.processor_current_idle_state().unwrap(),
+            POWER_INFO.processor_current_idle_state,
+        );
 
-        {
-            // blank line
-            let map = parse(b"");
-            assert!(map.is_err());
+        // MISC_INFO_3 fields
+        assert_eq!(*misc.raw.time_zone_id().unwrap(), TIME_ZONE_ID);
+        let time_zone = misc.raw.time_zone().unwrap();
+        assert_eq!(time_zone.bias, BIAS);
+        assert_eq!(time_zone.standard_bias, STANDARD_BIAS);
+        assert_eq!(time_zone.daylight_bias, DAYLIGHT_BIAS);
+        assert_eq!(time_zone.standard_date, STANDARD_DATE);
+        assert_eq!(time_zone.daylight_date, DAYLIGHT_DATE);
+        assert_eq!(time_zone.standard_name, standard_name);
+        assert_eq!(time_zone.daylight_name, daylight_name);
 
-            let map = parse(b"   ");
-            assert!(map.is_err());
-        }
+        // MISC_INFO_4 fields
+        assert_eq!(*misc.raw.build_string().unwrap(), build_string,);
+        assert_eq!(*misc.raw.dbg_bld_str().unwrap(), dbg_bld_str,);
+        assert_eq!(misc.build_string().unwrap(), "hello");
+        assert_eq!(misc.dbg_bld_str().unwrap(), "world");
 
-        {
-            // bad addresses
-            let map = parse(b"  -10b00 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
-            assert!(map.is_err());
+        // MISC_INFO_5 fields
+        assert_eq!(*misc.raw.process_cookie().unwrap(), PROCESS_COOKIE,);
 
-            let map = parse(b"  10b00- r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
-            assert!(map.is_err());
+        let xstate = misc.raw.xstate_data().unwrap();
+        assert_eq!(xstate.size_of_info, SIZE_OF_INFO);
+        assert_eq!(xstate.context_size, CONTEXT_SIZE);
+        assert_eq!(xstate.enabled_features, enabled_features);
+        assert_eq!(xstate.features, features);
 
-            let map = parse(b"  10b00 r-xp  10bac9000 fd:05 1196511 /usr/lib64/libtdb1.so  ");
-            assert!(map.is_err());
-        }
+        let mut xstate_iter = xstate.iter();
+        assert_eq!(
+            xstate_iter.next().unwrap(),
+            (KNOWN_FEATURE_IDX, features[KNOWN_FEATURE_IDX]),
+        );
+        assert_eq!(
+            xstate_iter.next().unwrap(),
+            (UNKNOWN_FEATURE_IDX, features[UNKNOWN_FEATURE_IDX]),
+        );
+        assert_eq!(xstate_iter.next(), None);
+        assert_eq!(xstate_iter.next(), None);
 
-        {
-            // bad [stack:<tid>]
-            let map = parse(b"  -10b00 r-xp  10bac9000 fd:05 1196511 [stack:] ");
-            assert!(map.is_err());
+        assert_eq!(
+            misc.xstate_feature(md::XstateFeatureIndex::LEGACY_SSE),
+            Some(&features[KNOWN_FEATURE_IDX])
+        );
+        assert_eq!(
+            misc.xstate_feature(md::XstateFeatureIndex::GSSE_AND_AVX),
+            None
+        );
+    }
 
-            let map = parse(b"  -10b00 r-xp  10bac9000 fd:05 1196511 [stack:a10] ");
-            assert!(map.is_err());
+    #[test]
+    fn test_misc_info_sizes() {
+        let sizes = [
+            md::MINIDUMP_MISC_INFO::size_with(&LE),
+            md::MINIDUMP_MISC_INFO_2::size_with(&LE),
+            md::MINIDUMP_MISC_INFO_3::size_with(&LE),
+            md::MINIDUMP_MISC_INFO_4::size_with(&LE),
+            md::MINIDUMP_MISC_INFO_5::size_with(&LE),
+        ];
+        // Every field is claimed to be valid, so the only thing stopping us
+        // from reading one is the size of the stream.
+        let mut bytes = vec![0xff; sizes[4] + 16];
+        bytes[..4].copy_from_slice(&(sizes[4] as u32).to_le_bytes());
+        bytes[4..8].copy_from_slice(&md::MiscInfoFlags::all().bits().to_le_bytes());
+
+        let version = |len: usize| {
+            let misc = MinidumpMiscInfo::read(&bytes[..len], &[], LE).unwrap();
+            match misc.raw {
+                RawMiscInfo::MiscInfo(_) => 1,
+                RawMiscInfo::MiscInfo2(_) => 2,
+                RawMiscInfo::MiscInfo3(_) => 3,
+                RawMiscInfo::MiscInfo4(_) => 4,
+                RawMiscInfo::MiscInfo5(_) => 5,
+            }
+        };
+        for (i, &size) in sizes.iter().enumerate() {
+            // Exactly the right size, or a bit more, is that version.
+            assert_eq!(version(size), i + 1);
+            assert_eq!(version(size + 8), i + 1);
+            // A single byte short is the previous version.
+            if i > 0 {
+                assert_eq!(version(size - 1), i);
+            }
         }
+        assert!(MinidumpMiscInfo::read(&bytes[..sizes[0] - 1], &[], LE).is_err());
+        assert!(MinidumpMiscInfo::read(&[], &[], LE).is_err());
+
+        // Fields from later versions aren't available in earlier ones.
+        let misc = MinidumpMiscInfo::read(&bytes[..sizes[2]], &[], LE).unwrap();
+        assert!(misc.raw.protected_process().is_some());
+        assert!(misc.raw.time_zone().is_some());
+        assert_eq!(misc.raw.build_string(), None);
+        assert_eq!(misc.build_string(), None);
+
+        let misc = MinidumpMiscInfo::read(&bytes[..sizes[3]], &[], LE).unwrap();
+        assert!(misc.raw.build_string().is_some());
+        assert!(misc.raw.dbg_bld_str().is_some());
+        assert!(misc.raw.xstate_data().is_none());
+        assert_eq!(misc.raw.process_cookie(), None);
+        assert_eq!(
+            misc.xstate_feature(md::XstateFeatureIndex::GSSE_AND_AVX),
+            None
+        );
+
+        let misc = MinidumpMiscInfo::read(&bytes[..sizes[4]], &[], LE).unwrap();
+        assert_eq!(misc.raw.process_cookie(), Some(&0xffffffff));
     }
 
     #[test]
-    fn test_module_list_overlap() {
+    fn test_elf_build_id() {
+        // Add a module with a long ELF build id
         let name1 = DumpString::new("module 1", Endian::Little);
-        let name2 = DumpString::new("module 2", Endian::Little);
-        let name3 = DumpString::new("module 3", Endian::Little);
-        let name4 = DumpString::new("module 4", Endian::Little);
-        let name5 = DumpString::new("module 5", Endian::Little);
-        let cv_record = Section::with_endian(Endian::Little)
-            .D32(md::CvSignature::Pdb70 as u32) // signature
-            // signature, a GUID
-            .D32(0xabcd1234)
-            .D16(0xf00d)
-            .D16(0xbeef)
-            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
-            .D32(1) // age
-            .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
+        const MODULE1_BUILD_ID: &[u8] = &[
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+            0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        ];
+        let cv_record1 = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Elf as u32) // signature
+            .append_bytes(MODULE1_BUILD_ID);
         let module1 = SynthModule::new(
             Endian::Little,
             0x100000000,
@@ -5229,741 +12960,1969 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
             0x34571371,
             Some(&STOCK_VERSION_INFO),
         )
-        .cv_record(&cv_record);
-        // module2 overlaps module1 exactly
+        .cv_record(&cv_record1);
+        // Add a module with a short ELF build id
+        let name2 = DumpString::new("module 2", Endian::Little);
+        const MODULE2_BUILD_ID: &[u8] = &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+        let cv_record2 = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Elf as u32) // signature
+            .append_bytes(MODULE2_BUILD_ID);
         let module2 = SynthModule::new(
             Endian::Little,
-            0x100000000,
+            0x200000000,
             0x4000,
             &name2,
             0xb1054d2a,
             0x34571371,
             Some(&STOCK_VERSION_INFO),
         )
-        .cv_record(&cv_record);
-        // module3 overlaps module1 partially
-        let module3 = SynthModule::new(
-            Endian::Little,
-            0x100000001,
-            0x4000,
-            &name3,
-            0xb1054d2a,
-            0x34571371,
-            Some(&STOCK_VERSION_INFO),
-        )
-        .cv_record(&cv_record);
-        // module4 is fully contained within module1
-        let module4 = SynthModule::new(
-            Endian::Little,
-            0x100000001,
-            0x3000,
-            &name4,
-            0xb1054d2a,
-            0x34571371,
-            Some(&STOCK_VERSION_INFO),
-        )
-        .cv_record(&cv_record);
-        // module5 is cool, though.
-        let module5 = SynthModule::new(
-            Endian::Little,
-            0x100004000,
-            0x4000,
-            &name5,
-            0xb1054d2a,
-            0x34571371,
-            Some(&STOCK_VERSION_INFO),
-        )
-        .cv_record(&cv_record);
+        .cv_record(&cv_record2);
         let dump = SynthMinidump::with_endian(Endian::Little)
             .add_module(module1)
             .add_module(module2)
-            .add_module(module3)
-            .add_module(module4)
-            .add_module(module5)
             .add(name1)
+            .add(cv_record1)
             .add(name2)
-            .add(name3)
-            .add(name4)
-            .add(name5)
-            .add(cv_record);
+            .add(cv_record2);
         let dump = read_synth_dump(dump).unwrap();
         let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
         let modules = module_list.iter().collect::<Vec<_>>();
-        assert_eq!(modules.len(), 5);
+        assert_eq!(modules.len(), 2);
         assert_eq!(modules[0].base_address(), 0x100000000);
-        assert_eq!(modules[0].size(), 0x4000);
         assert_eq!(modules[0].code_file(), "module 1");
-        assert_eq!(modules[1].base_address(), 0x100000000);
-        assert_eq!(modules[1].size(), 0x4000);
-        assert_eq!(modules[1].code_file(), "module 2");
-        assert_eq!(modules[2].base_address(), 0x100000001);
-        assert_eq!(modules[2].size(), 0x4000);
-        assert_eq!(modules[2].code_file(), "module 3");
-        assert_eq!(modules[3].base_address(), 0x100000001);
-        assert_eq!(modules[3].size(), 0x3000);
-        assert_eq!(modules[3].code_file(), "module 4");
-        assert_eq!(modules[4].base_address(), 0x100004000);
-        assert_eq!(modules[4].size(), 0x4000);
-        assert_eq!(modules[4].code_file(), "module 5");
+        // The full build ID.
+        assert_eq!(
+            modules[0].code_identifier(),
+            "000102030405060708090a0b0c0d0e0f1011121314151617"
+        );
+        assert_eq!(modules[0].debug_file().unwrap(), "module 1");
+        // The first 16 bytes of the build ID interpreted as a GUID.
+        assert_eq!(
+            modules[0].debug_identifier().unwrap(),
+            "030201000504070608090A0B0C0D0E0F0"
+        );
 
-        // module_at_address should discard overlapping modules.
-        assert_eq!(module_list.by_addr().count(), 2);
+        assert_eq!(modules[1].base_address(), 0x200000000);
+        assert_eq!(modules[1].code_file(), "module 2");
+        // The full build ID.
+        assert_eq!(modules[1].code_identifier(), "0001020304050607");
+        assert_eq!(modules[1].debug_file().unwrap(), "module 2");
+        // The first 16 bytes of the build ID interpreted as a GUID, padded with
+        // zeroes in this case.
         assert_eq!(
-            module_list
-                .module_at_address(0x100001000)
-                .unwrap()
-                .code_file(),
-            "module 1"
+            modules[1].debug_identifier().unwrap(),
+            "030201000504070600000000000000000"
         );
+    }
+
+    #[test]
+    fn test_module_identifiers_by_os() {
+        let read_module = |platform: md::PlatformId, cv_record: Option<Section>| {
+            let name = DumpString::new("libfoo", Endian::Little);
+            let mut module = SynthModule::new(
+                Endian::Little,
+                0x10000,
+                0x4000,
+                &name,
+                0xb1054d2a,
+                0x34571371,
+                None,
+            );
+            let mut system_info = SystemInfo::new(Endian::Little);
+            system_info.platform_id = platform as u32;
+            let mut dump = SynthMinidump::with_endian(Endian::Little).add_system_info(system_info);
+            if let Some(cv_record) = cv_record {
+                module = module.cv_record(&cv_record);
+                dump = dump.add(cv_record);
+            }
+            let dump = read_synth_dump(dump.add_module(module).add(name)).unwrap();
+            let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let module = module_list.iter().next().unwrap().clone();
+            module
+        };
+        let pdb70 = || {
+            Section::with_endian(Endian::Little)
+                .D32(md::CvSignature::Pdb70 as u32)
+                .D32(0xabcd1234)
+                .D16(0xf00d)
+                .D16(0xbeef)
+                .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+                .D32(0) // age
+                .append_bytes(b"libfoo\0")
+        };
+
+        // Mach-O modules have their UUID in a PDB70 record.
+        for &platform in &[md::PlatformId::MacOs, md::PlatformId::Ios] {
+            let macho = read_module(platform, Some(pdb70()));
+            assert_eq!(macho.code_identifier(), "ABCD1234F00DBEEF0102030405060708");
+            assert_eq!(
+                macho.debug_identifier().unwrap(),
+                "ABCD1234F00DBEEF01020304050607080"
+            );
+        }
+        let pe = read_module(md::PlatformId::VER_PLATFORM_WIN32_NT, Some(pdb70()));
+        assert_eq!(pe.code_identifier(), "B1054D2A4000");
+
+        let pdb20 = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb20 as u32)
+            .D32(0) // offset
+            .D32(0x12345678) // signature
+            .D32(2) // age
+            .append_bytes(b"foo.pdb\0");
+        let pe = read_module(md::PlatformId::VER_PLATFORM_WIN32_NT, Some(pdb20));
+        assert_eq!(pe.code_identifier(), "B1054D2A4000");
+        assert_eq!(pe.debug_file().unwrap(), "foo.pdb");
+        assert_eq!(pe.debug_identifier().unwrap(), "123456782");
+
+        // A 20 byte build id, what GNU ld makes by default.
+        let build_id: Vec<u8> = (0..20).collect();
+        let elf = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Elf as u32)
+            .append_bytes(&build_id);
+        let elf = read_module(md::PlatformId::Linux, Some(elf));
         assert_eq!(
-            module_list
-                .module_at_address(0x100005000)
-                .unwrap()
-                .code_file(),
-            "module 5"
+            elf.code_identifier(),
+            "000102030405060708090a0b0c0d0e0f10111213"
+        );
+        assert_eq!(
+            elf.debug_identifier().unwrap(),
+            "030201000504070608090A0B0C0D0E0F0"
         );
+
+        // Without a CodeView record, only PE modules have an identifier.
+        let elf = read_module(md::PlatformId::Linux, None);
+        assert_eq!(elf.code_identifier(), "");
+        assert_eq!(elf.debug_identifier(), None);
+        let pe = read_module(md::PlatformId::VER_PLATFORM_WIN32_NT, None);
+        assert_eq!(pe.code_identifier(), "B1054D2A4000");
+
+        // A record too short for its signature is treated like a missing one.
+        let truncated = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32)
+            .D32(0xabcd1234);
+        let pe = read_module(md::PlatformId::VER_PLATFORM_WIN32_NT, Some(truncated));
+        assert!(pe.codeview_info.is_none());
+        assert_eq!(pe.debug_identifier(), None);
+        assert_eq!(pe.code_identifier(), "B1054D2A4000");
     }
 
     #[test]
-    fn test_memory_list() {
-        const CONTENTS: &[u8] = b"memory_contents";
-        let memory = Memory::with_section(
-            Section::with_endian(Endian::Little).append_bytes(CONTENTS),
-            0x309d68010bd21b2c,
+    fn test_thread_list_x86() {
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
         );
-        let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
         let dump = read_synth_dump(dump).unwrap();
-        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
-        let regions = memory_list.iter().collect::<Vec<_>>();
-        assert_eq!(regions.len(), 1);
-        assert_eq!(regions[0].base_address, 0x309d68010bd21b2c);
-        assert_eq!(regions[0].size, CONTENTS.len() as u64);
-        assert_eq!(&regions[0].bytes, &CONTENTS);
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        assert_eq!(thread_list.threads.len(), 1);
+        let thread = thread_list.threads.pop().unwrap();
+        assert_eq!(thread.raw.thread_id, 0x1234);
+        let context = thread
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context");
+        match &context.raw {
+            MinidumpRawContext::X86(raw) => {
+                assert_eq!(raw.eip, 0xabcd1234);
+                assert_eq!(raw.esp, 0x1010);
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        let stack = thread.direct_stack().expect("Should have stack memory");
+        assert_eq!(stack.base_address, 0x1000);
+        assert_eq!(stack.size, 0x1000);
     }
 
     #[test]
-    fn test_memory_list_lifetimes() {
-        // A memory list should not own any of the minidump data.
-        const CONTENTS: &[u8] = b"memory_contents";
-        let memory = Memory::with_section(
-            Section::with_endian(Endian::Little).append_bytes(CONTENTS),
-            0x309d68010bd21b2c,
+    fn test_thread_list_amd64() {
+        let context =
+            synth_minidump::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000000010000000,
         );
-        let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
         let dump = read_synth_dump(dump).unwrap();
-        let mem_slices: Vec<&[u8]> = {
-            let mem_list: MinidumpMemoryList<'_> = dump.get_stream().unwrap();
-            mem_list.iter().map(|mem| mem.bytes).collect()
-        };
-        assert_eq!(mem_slices[0], CONTENTS);
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        assert_eq!(thread_list.threads.len(), 1);
+        let thread = thread_list.threads.pop().unwrap();
+        assert_eq!(thread.raw.thread_id, 0x1234);
+        let context = thread
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context");
+        match &context.raw {
+            MinidumpRawContext::Amd64(raw) => {
+                assert_eq!(raw.rip, 0x1234abcd1234abcd);
+                assert_eq!(raw.rsp, 0x1000000010000000);
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        let stack = thread.direct_stack().expect("Should have stack memory");
+        assert_eq!(stack.base_address, 0x1000000010000000);
+        assert_eq!(stack.size, 0x1000);
     }
 
     #[test]
-    fn test_memory_list_overlap() {
-        let memory1 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+    fn test_thread_list_arm64_bigendian() {
+        let context = synth_minidump::arm64_context(Endian::Big, 0x1234abcd1234abcd, 0x1000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Big)
+                .D64(0x0102030405060708)
+                .D32(0x11223344)
+                .D16(0x68)
+                .D16(0x69)
+                .D16(0),
             0x1000,
         );
-        // memory2 overlaps memory1 exactly
-        let memory2 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(1, 0x1000),
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16;
+        let system_info = SystemInfo::new(Endian::Big).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Big, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Big)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.endian, BE);
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let thread = &thread_list.threads[0];
+        assert_eq!(thread.raw.thread_id, 0x1234);
+        let context = thread
+            .context(&system_info, None)
+            .expect("Should have a thread context");
+        assert_eq!(context.get_instruction_pointer(), 0x1234abcd1234abcd);
+        assert_eq!(context.get_stack_pointer(), 0x1000);
+
+        // The stack is read in the minidump's byte order.
+        let stack = thread.direct_stack().expect("Should have stack memory");
+        assert_eq!(stack.endian, BE);
+        assert_eq!(stack.read_u64(0x1000), Some(0x0102030405060708));
+        assert_eq!(stack.read_u32(0x1008), Some(0x11223344));
+        assert_eq!(stack.read_utf16_string(0x100c, 10).unwrap(), "hi");
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        let region = memory.memory_at_address(0x1000).unwrap();
+        assert_eq!(region.read_u64(0x1000), Some(0x0102030405060708));
+    }
+
+    fn read_thread_context(
+        endian: Endian,
+        arch: md::ProcessorArchitecture,
+        context: Section,
+    ) -> MinidumpContext {
+        let stack = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0, 0x100),
             0x1000,
         );
-        // memory3 overlaps memory1 partially
-        let memory3 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(2, 0x1000),
-            0x1001,
-        );
-        // memory4 is fully contained within memory1
-        let memory4 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(3, 0x100),
-            0x1001,
-        );
-        // memory5 is cool, though.
-        let memory5 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(4, 0x1000),
-            0x2000,
+        let system_info = SystemInfo::new(endian).set_processor_architecture(arch as u16);
+        let thread = Thread::new(endian, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(endian)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        thread_list.threads[0]
+            .context(&system_info, None)
+            .expect("Should have a thread context")
+            .into_owned()
+    }
+
+    #[test]
+    fn test_thread_list_ppc_bigendian() {
+        let context = synth_minidump::ppc_context(Endian::Big, 0x1234abcd, 0x1010);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_PPC;
+        let context = read_thread_context(Endian::Big, arch, context);
+        match &context.raw {
+            MinidumpRawContext::Ppc(raw) => {
+                assert_eq!(raw.srr0, 0x1234abcd);
+                assert_eq!(raw.gpr[1], 0x1010);
+                assert_eq!(raw.get_register("r1", &context.valid), Some(0x1010));
+                assert_eq!(raw.get_register("srr0", &context.valid), Some(0x1234abcd));
+                assert_eq!(raw.get_register("lr", &context.valid), Some(0));
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        assert_eq!(context.get_instruction_pointer(), 0x1234abcd);
+        assert_eq!(context.get_stack_pointer(), 0x1010);
+        assert_eq!(context.format_register("srr0"), "0x1234abcd");
+        let registers = context.general_purpose_registers();
+        assert_eq!(registers.len(), 36);
+        assert!(registers.contains(&"r31") && registers.contains(&"ctr"));
+
+        let mut output = Vec::new();
+        context.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("CONTEXT_PPC\n"));
+        assert!(output.contains("  srr0                     = 0x1234abcd\n"));
+        assert!(output.contains("  gpr[ 1]                  = 0x1010\n"));
+    }
+
+    #[test]
+    fn test_thread_list_ppc64() {
+        let context = synth_minidump::ppc64_context(Endian::Little, 0x1234abcd1234abcd, 0x1010);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_PPC64;
+        let mut context = read_thread_context(Endian::Little, arch, context);
+        match &mut context.raw {
+            MinidumpRawContext::Ppc64(raw) => {
+                assert_eq!(raw.srr0, 0x1234abcd1234abcd);
+                assert_eq!(raw.gpr[1], 0x1010);
+                assert_eq!(raw.set_register("lr", 0x5555), Some(()));
+                assert_eq!(raw.set_register("r32", 0), None);
+                assert_eq!(raw.set_register("r01", 0), None);
+                assert_eq!(raw.lr, 0x5555);
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        assert_eq!(context.get_instruction_pointer(), 0x1234abcd1234abcd);
+        assert_eq!(context.get_stack_pointer(), 0x1010);
+        assert_eq!(context.format_register("lr"), "0x0000000000005555");
+
+        let mut output = Vec::new();
+        context.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("CONTEXT_PPC64\n"));
+        assert!(output.contains("  lr                       = 0x5555\n"));
+    }
+
+    fn read_amd64_thread(context: Section) -> (Option<MinidumpContext>, Result<(), ContextError>) {
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
         );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
         let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_memory(memory1)
-            .add_memory(memory2)
-            .add_memory(memory3)
-            .add_memory(memory4)
-            .add_memory(memory5);
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
         let dump = read_synth_dump(dump).unwrap();
-        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
-        let regions = memory_list.iter().collect::<Vec<_>>();
-        assert_eq!(regions.len(), 5);
-        assert_eq!(regions[0].base_address, 0x1000);
-        assert_eq!(regions[0].size, 0x1000);
-        assert_eq!(regions[1].base_address, 0x1000);
-        assert_eq!(regions[1].size, 0x1000);
-        assert_eq!(regions[2].base_address, 0x1001);
-        assert_eq!(regions[2].size, 0x1000);
-        assert_eq!(regions[3].base_address, 0x1001);
-        assert_eq!(regions[3].size, 0x100);
-        assert_eq!(regions[4].base_address, 0x2000);
-        assert_eq!(regions[4].size, 0x1000);
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let thread = &thread_list.threads[0];
+        (
+            thread
+                .context(&system_info, None)
+                .map(|context| context.into_owned()),
+            thread.check_context_cpu(&system_info),
+        )
+    }
 
-        // memory_at_address should discard overlapping regions.
-        assert_eq!(memory_list.by_addr().count(), 2);
-        let m1 = memory_list.memory_at_address(0x1a00).unwrap();
-        assert_eq!(m1.base_address, 0x1000);
-        assert_eq!(m1.size, 0x1000);
-        assert_eq!(m1.bytes, &[0u8; 0x1000][..]);
-        let m2 = memory_list.memory_at_address(0x2a00).unwrap();
-        assert_eq!(m2.base_address, 0x2000);
-        assert_eq!(m2.size, 0x1000);
-        assert_eq!(m2.bytes, &[4u8; 0x1000][..]);
+    #[test]
+    fn test_thread_context_cpu_mismatch() {
+        let mismatch = Err(ContextError::CpuMismatch {
+            context_cpu: md::ContextFlagsCpu::CONTEXT_X86,
+            system_info_cpu: md::ContextFlagsCpu::CONTEXT_AMD64,
+        });
+
+        // x86 flags on a context that's big enough for amd64 are most likely
+        // just corrupt flags, so it's still read the way SystemInfo says.
+        let size = md::CONTEXT_AMD64::size_with(&LE) - md::CONTEXT_X86::size_with(&LE);
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010)
+            .append_repeated(0, size);
+        let (context, check) = read_amd64_thread(context);
+        assert!(matches!(
+            context.expect("Should have a thread context").raw,
+            MinidumpRawContext::Amd64(_)
+        ));
+        assert_eq!(check, mismatch);
+
+        // A whole x86 context is too small to be read as amd64.
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let (context, check) = read_amd64_thread(context);
+        assert!(context.is_none());
+        assert_eq!(check, mismatch);
+
+        // Matching contexts are fine.
+        let context = synth_minidump::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1010);
+        let (context, check) = read_amd64_thread(context);
+        assert!(context.is_some());
+        assert_eq!(check, Ok(()));
     }
 
     #[test]
-    fn test_misc_info() {
-        const PID: u32 = 0x1234abcd;
-        const PROCESS_TIMES: MiscFieldsProcessTimes = MiscFieldsProcessTimes {
-            process_create_time: 0xf0f0b0b0,
-            process_user_time: 0xf030a020,
-            process_kernel_time: 0xa010b420,
+    fn test_infer_system_info() {
+        let infer = |context: Option<Section>| {
+            let mut dump = SynthMinidump::with_endian(Endian::Little);
+            if let Some(context) = context {
+                let stack = Memory::with_section(
+                    Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+                    0x1000,
+                );
+                let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+                dump = dump.add_thread(thread).add(context).add_memory(stack);
+            }
+            let dump = read_synth_dump(dump).unwrap();
+            assert!(dump.get_stream::<MinidumpSystemInfo>().is_err());
+            dump.infer_system_info().map(|system_info| {
+                assert_eq!(system_info.os, Os::Unknown(0));
+                system_info.cpu
+            })
         };
 
+        let amd64 = synth_minidump::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000);
+        assert_eq!(infer(Some(amd64)), Some(Cpu::X86_64));
+        let x86 = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1000);
+        assert_eq!(infer(Some(x86)), Some(Cpu::X86));
+        let arm64 = synth_minidump::arm64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000);
+        assert_eq!(infer(Some(arm64)), Some(Cpu::Arm64));
+        // Flags for a CPU whose context doesn't fit aren't believed.
+        let truncated = Section::with_endian(Endian::Little)
+            .D32(0x1007f) // CONTEXT_X86 | CONTEXT_ALL
+            .append_repeated(0, 0x100);
+        assert_eq!(infer(Some(truncated)), None);
+        assert_eq!(infer(None), None);
+    }
+
+    const XMM0: u128 = 0x000102030405060708090a0b0c0d0e0f;
+    const YMM0_HIGH: u128 = 0x101112131415161718191a1b1c1d1e1f;
+    const YMM15_HIGH: u128 = 0xf0f1f2f3f4f5f6f7f8f9fafbfcfdfeff;
+    const K1: u64 = 0x5555aaaa5555aaaa;
+
+    /// The AVX and AVX-512 opmask features of an XSAVE context.
+    fn avx_and_kmask() -> (Vec<u8>, Vec<u8>) {
+        let mut ymm_high = vec![0; 256];
+        ymm_high[..16].copy_from_slice(&YMM0_HIGH.to_le_bytes());
+        ymm_high[240..].copy_from_slice(&YMM15_HIGH.to_le_bytes());
+        let mut kmask = vec![0; 64];
+        kmask[8..16].copy_from_slice(&K1.to_le_bytes());
+        (ymm_high, kmask)
+    }
+
+    /// An XSAVE header (the start of the XSTATE chunk of a `CONTEXT_EX`).
+    fn xsave_header(mask: u64, compaction_mask: u64) -> Vec<u8> {
+        let mut header = vec![0; 64];
+        header[..8].copy_from_slice(&mask.to_le_bytes());
+        header[8..16].copy_from_slice(&compaction_mask.to_le_bytes());
+        header
+    }
+
+    /// Read an x86-64 thread context with `CONTEXT_HAS_XSTATE`, whose XSTATE
+    /// chunk claims to be `length` bytes of `area`.
+    fn read_xstate_context(area: &[u8], length: u32, misc: Option<MiscStream>) -> MinidumpContext {
+        const CONTEXT_SIZE: i32 = 1232;
+        // The XSTATE chunk doesn't have to immediately follow the CONTEXT_EX.
+        const XSTATE_OFFSET: i32 = 40;
+        let mut context = synth_minidump::amd64_context(Endian::Little, 0x1234, 0x1000)
+            .get_contents()
+            .unwrap();
+        assert_eq!(context.len(), CONTEXT_SIZE as usize);
+        // context_flags: CONTEXT_ALL | CONTEXT_HAS_XSTATE
+        context[48..52].copy_from_slice(&(0x10001fu32 | md::CONTEXT_HAS_XSTATE).to_le_bytes());
+        // xmm0, in float_save
+        context[256 + 160..256 + 176].copy_from_slice(&XMM0.to_le_bytes());
+
+        let total = CONTEXT_SIZE as u32 + XSTATE_OFFSET as u32 + length;
+        let context = Section::with_endian(Endian::Little)
+            .append_bytes(&context)
+            // CONTEXT_EX
+            .D32(-CONTEXT_SIZE as u32)
+            .D32(total)
+            .D32(-CONTEXT_SIZE as u32)
+            .D32(CONTEXT_SIZE as u32)
+            .D32(XSTATE_OFFSET as u32)
+            .D32(length)
+            .append_repeated(0, XSTATE_OFFSET as usize - 24)
+            .append_bytes(area);
+
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let mut dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        if let Some(misc) = misc {
+            dump = dump.add_stream(misc);
+        }
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        thread_list.threads[0]
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context")
+            .into_owned()
+    }
+
+    #[test]
+    fn test_xstate_standard_layout() {
+        // Without a MINIDUMP_MISC_INFO_5, the features are where Intel puts them.
+        let (ymm_high, kmask) = avx_and_kmask();
+        let mut area = xsave_header(1 << 2 | 1 << 5, 0);
+        area.resize(576 - 512, 0);
+        area.extend(&ymm_high);
+        area.resize(1088 - 512, 0);
+        area.extend(&kmask);
+        let context = read_xstate_context(&area, area.len() as u32, None);
+
+        assert_eq!(context.xmm(0), Some(XMM0));
+        assert_eq!(context.ymm(0), Some([XMM0, YMM0_HIGH]));
+        assert_eq!(context.ymm(15), Some([0, YMM15_HIGH]));
+        assert_eq!(context.ymm(16), None);
+        assert_eq!(context.opmask(0), Some(0));
+        assert_eq!(context.opmask(1), Some(K1));
+        // The CPU doesn't seem to have the rest of AVX-512.
+        assert_eq!(context.zmm(0), None);
+        assert_eq!(context.zmm(16), None);
+
+        let registers = context.nonzero_vector_registers();
+        let names = registers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["ymm0", "ymm15", "k1"]);
+        assert_eq!(
+            registers[0].1,
+            "0x101112131415161718191a1b1c1d1e1f000102030405060708090a0b0c0d0e0f"
+        );
+
+        let mut output = Vec::new();
+        context.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  ymm15         = 0xf0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"));
+        assert!(output.contains("  k1            = 0x5555aaaa5555aaaa"));
+    }
+
+    #[test]
+    fn test_xstate_misc_info_layout() {
+        // The features are wherever MINIDUMP_MISC_INFO_5 says, and enabled
+        // features that weren't saved are zero.
+        let (ymm_high, kmask) = avx_and_kmask();
+        let mut features = [md::XSTATE_FEATURE::default(); 64];
+        features[2] = md::XSTATE_FEATURE {
+            offset: 640,
+            size: 256,
+        };
+        features[5] = md::XSTATE_FEATURE {
+            offset: 896,
+            size: 64,
+        };
+        features[6] = md::XSTATE_FEATURE {
+            offset: 960,
+            size: 512,
+        };
         let mut misc = MiscStream::new(Endian::Little);
-        misc.process_id = Some(PID);
-        misc.process_times = Some(PROCESS_TIMES);
-        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(misc);
+        misc.misc_5 = Some(MiscInfo5Fields {
+            xstate_data: md::XSTATE_CONFIG_FEATURE_MSC_INFO {
+                enabled_features: 0b111 | 1 << 5 | 1 << 6,
+                features,
+                ..Default::default()
+            },
+            process_cookie: None,
+        });
+
+        let mut area = xsave_header(1 << 2 | 1 << 5, 0);
+        area.resize(640 - 512, 0);
+        area.extend(&ymm_high);
+        area.extend(&kmask);
+        let context = read_xstate_context(&area, area.len() as u32, Some(misc));
+
+        assert_eq!(context.ymm(0), Some([XMM0, YMM0_HIGH]));
+        assert_eq!(context.opmask(1), Some(K1));
+        assert_eq!(context.zmm(0), Some([XMM0, YMM0_HIGH, 0, 0]));
+        assert_eq!(context.zmm(15), Some([0, YMM15_HIGH, 0, 0]));
+        // zmm16-31 aren't enabled.
+        assert_eq!(context.zmm(16), None);
+        let names = context
+            .nonzero_vector_registers()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["zmm0", "zmm15", "k1"]);
+    }
+
+    #[test]
+    fn test_xstate_compacted_layout() {
+        let (ymm_high, kmask) = avx_and_kmask();
+        let mut area = xsave_header(1 << 2 | 1 << 5, 1 << 63 | 0b111 | 1 << 5);
+        area.extend(&ymm_high);
+        area.extend(&kmask);
+        let context = read_xstate_context(&area, area.len() as u32, None);
+
+        assert_eq!(context.ymm(0), Some([XMM0, YMM0_HIGH]));
+        assert_eq!(context.ymm(15), Some([0, YMM15_HIGH]));
+        assert_eq!(context.opmask(1), Some(K1));
+        assert_eq!(context.zmm(0), None);
+    }
+
+    #[test]
+    fn test_xstate_truncated() {
+        let (ymm_high, _) = avx_and_kmask();
+        let mut area = xsave_header(1 << 2, 0);
+        area.resize(576 - 512, 0);
+        area.extend(&ymm_high);
+
+        // The XSTATE chunk claims to be larger than the context.
+        let context = read_xstate_context(&area, area.len() as u32 + 1, None);
+        assert!(context.xstate.is_none());
+        assert_eq!(context.xmm(0), Some(XMM0));
+        assert_eq!(context.ymm(0), None);
+
+        // The chunk is fine, but the AVX state isn't all there.
+        area.truncate(area.len() - 1);
+        let context = read_xstate_context(&area, area.len() as u32, None);
+        assert_eq!(context.xstate, Some(MinidumpXState::default()));
+        assert_eq!(context.ymm(0), None);
+        assert!(context.nonzero_vector_registers().is_empty());
+    }
+
+    #[test]
+    fn test_context_validity_set_operations() {
+        let some =
+            |regs: &[&'static str]| MinidumpContextValidity::Some(regs.iter().copied().collect());
+        let all = MinidumpContextValidity::All;
+        let a = some(&["eip", "esp", "ebp"]);
+        let b = some(&["esp", "ebx"]);
+
+        assert_eq!(a.intersect(&b), some(&["esp"]));
+        assert_eq!(a.union(&b), some(&["eip", "esp", "ebp", "ebx"]));
+        assert_eq!(a.intersect(&some(&[])), some(&[]));
+        assert_eq!(a.union(&some(&[])), a);
+
+        assert_eq!(a.intersect(&all), a);
+        assert_eq!(all.intersect(&b), b);
+        assert_eq!(all.intersect(&all), all);
+        assert_eq!(a.union(&all), all);
+        assert_eq!(all.union(&b), all);
+    }
+
+    /// 1.0 as an x87 80-bit extended precision number.
+    const ST_ONE: u128 = 0x3fff_8000_0000_0000_0000;
+
+    /// Read a thread with an x86 context with the given `context_flags` and
+    /// `mxcsr`, and some x87 and SSE registers set, from a synthetic minidump.
+    fn read_x86_float_context(context_flags: u32, mxcsr: u32) -> MinidumpContext {
+        let mut context = synth_minidump::x86_context(Endian::Little, 0x1234, 0x1000)
+            .get_contents()
+            .unwrap();
+        context[..4].copy_from_slice(&context_flags.to_le_bytes());
+        // float_save: control_word, status_word, tag_word, and st1.
+        context[28..32].copy_from_slice(&0x027fu32.to_le_bytes());
+        context[32..36].copy_from_slice(&0x3800u32.to_le_bytes());
+        context[36..40].copy_from_slice(&0x3fffu32.to_le_bytes());
+        context[66..76].copy_from_slice(&ST_ONE.to_le_bytes()[..10]);
+        // extended_registers, in the FXSAVE format: mxcsr, xmm0 and xmm7.
+        context[228..232].copy_from_slice(&mxcsr.to_le_bytes());
+        context[364..380].copy_from_slice(&XMM0.to_le_bytes());
+        context[476..492].copy_from_slice(&YMM15_HIGH.to_le_bytes());
+        let context = Section::with_endian(Endian::Little).append_bytes(&context);
+
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
         let dump = read_synth_dump(dump).unwrap();
-        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
-        assert_eq!(misc.raw.process_id(), Some(&PID));
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        thread_list.threads[0]
+            .context(&system_info, None)
+            .expect("Should have a thread context")
+            .into_owned()
+    }
+
+    #[test]
+    fn test_x86_fxsave() {
+        // CONTEXT_ALL, which includes CONTEXT_X86_EXTENDED_REGISTERS.
+        let context = read_x86_float_context(0x1007f, 0x1d84);
+        assert_eq!(context.fpu_control_word(), Some(0x027f));
+        assert_eq!(context.fpu_status_word(), Some(0x3800));
+        assert_eq!(context.fpu_tag_word(), Some(0x3fff));
+        assert_eq!(context.x87_register(0), Some(0));
+        assert_eq!(context.x87_register(1), Some(ST_ONE));
+        assert_eq!(context.x87_register(8), None);
+        assert_eq!(context.mxcsr(), Some(0x1d84));
         assert_eq!(
-            misc.process_create_time().unwrap(),
-            Utc.timestamp(PROCESS_TIMES.process_create_time as i64, 0)
+            context.mxcsr_description().as_deref(),
+            Some("0x1d84 (raised: ZE; unmasked: ZE; rounding: nearest)")
         );
+        assert_eq!(context.xmm(0), Some(XMM0));
+        assert_eq!(context.xmm(7), Some(YMM15_HIGH));
+        // x86 only has 8 XMM registers.
+        assert_eq!(context.xmm(8), None);
+        assert_eq!(context.ymm(0), None);
+
+        let mut output = Vec::new();
+        context.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "  mxcsr                        = 0x1d84 (raised: ZE; unmasked: ZE; rounding: nearest)\n"
+        ));
+        assert!(output
+            .contains("  xmm7                         = 0xf0f1f2f3f4f5f6f7f8f9fafbfcfdfeff\n"));
+
+        let context = read_x86_float_context(0x1007f, 0xffc0);
         assert_eq!(
-            *misc.raw.process_user_time().unwrap(),
-            PROCESS_TIMES.process_user_time
+            context.mxcsr_description().as_deref(),
+            Some("0xffc0 (raised: none; unmasked: none; rounding: toward zero; DAZ; FZ)")
         );
+        let context = read_x86_float_context(0x1007f, 0x403f);
         assert_eq!(
-            *misc.raw.process_kernel_time().unwrap(),
-            PROCESS_TIMES.process_kernel_time
+            context.mxcsr_description().as_deref(),
+            Some("0x403f (raised: IE DE ZE OE UE PE; unmasked: IE DE ZE OE UE PE; rounding: up)")
         );
     }
 
     #[test]
-    fn test_misc_info_large() {
-        const PID: u32 = 0x1234abcd;
-        const PROCESS_TIMES: MiscFieldsProcessTimes = MiscFieldsProcessTimes {
-            process_create_time: 0xf0f0b0b0,
-            process_user_time: 0xf030a020,
-            process_kernel_time: 0xa010b420,
+    fn test_x86_without_fxsave() {
+        // CONTEXT_FULL | CONTEXT_FLOATING_POINT, without the extended registers.
+        let mut context = read_x86_float_context(0x1000f, 0x1d84);
+        assert_eq!(context.fpu_control_word(), Some(0x027f));
+        assert_eq!(context.x87_register(1), Some(ST_ONE));
+        assert_eq!(context.mxcsr(), None);
+        assert_eq!(context.xmm(0), None);
+
+        let mut output = Vec::new();
+        context.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("mxcsr"));
+
+        // Unwinding doesn't recover any of these.
+        context.valid = MinidumpContextValidity::Some(["eip", "esp"].iter().copied().collect());
+        assert_eq!(context.fpu_control_word(), None);
+        assert_eq!(context.x87_register(1), None);
+    }
+
+    /// Read a thread with an ARM64 context that has `v0`, `v31`, `fpsr` and
+    /// `fpcr` set from a synthetic minidump.
+    fn read_arm64_context(endian: Endian) -> MinidumpContext {
+        let mut context = synth_minidump::arm64_context(endian, 0x1234, 0x1000)
+            .get_contents()
+            .unwrap();
+        let to_bytes_128 = match endian {
+            Endian::Little => u128::to_le_bytes,
+            Endian::Big => u128::to_be_bytes,
         };
-        let mut misc = MiscStream::new(Endian::Little);
-        misc.process_id = Some(PID);
-        misc.process_times = Some(PROCESS_TIMES);
-        // Make it larger.
-        misc.pad_to_size = Some(mem::size_of::<md::MINIDUMP_MISC_INFO>() + 32);
-        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(misc);
-        let dump = read_synth_dump(dump).unwrap();
-        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
-        assert_eq!(misc.raw.process_id(), Some(&PID));
-        assert_eq!(
-            misc.process_create_time().unwrap(),
-            Utc.timestamp(PROCESS_TIMES.process_create_time as i64, 0)
+        let to_bytes_32 = match endian {
+            Endian::Little => u32::to_le_bytes,
+            Endian::Big => u32::to_be_bytes,
+        };
+        context[272..288].copy_from_slice(&to_bytes_128(XMM0));
+        context[768..784].copy_from_slice(&to_bytes_128(YMM15_HIGH));
+        context[784..788].copy_from_slice(&to_bytes_32(0x0800_0010));
+        context[788..792].copy_from_slice(&to_bytes_32(0x0300_0000));
+        let context = Section::with_endian(endian).append_bytes(&context);
+
+        let stack = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0, 0x100),
+            0x1000,
         );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16;
+        let system_info = SystemInfo::new(endian).set_processor_architecture(arch);
+        let thread = Thread::new(endian, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(endian)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        thread_list.threads[0]
+            .context(&system_info, None)
+            .expect("Should have a thread context")
+            .into_owned()
+    }
+
+    #[test]
+    fn test_arm64_float_registers() {
+        for &endian in &[Endian::Little, Endian::Big] {
+            let context = read_arm64_context(endian);
+            assert_eq!(context.get_instruction_pointer(), 0x1234);
+            assert_eq!(context.get_stack_pointer(), 0x1000);
+            assert_eq!(context.arm64_vector(0), Some(XMM0));
+            assert_eq!(context.arm64_vector(1), Some(0));
+            assert_eq!(context.arm64_vector(31), Some(YMM15_HIGH));
+            assert_eq!(context.arm64_vector(32), None);
+            assert_eq!(context.fpsr(), Some(0x0800_0010));
+            assert_eq!(context.fpcr(), Some(0x0300_0000));
+            // These are x86-64 registers.
+            assert_eq!(context.xmm(0), None);
+            assert_eq!(context.ymm(0), None);
+
+            let registers = context.nonzero_vector_registers();
+            assert_eq!(
+                registers,
+                [
+                    ("v0".to_string(), format!("{:#034x}", XMM0)),
+                    ("v31".to_string(), format!("{:#034x}", YMM15_HIGH)),
+                    ("fpsr".to_string(), "0x08000010".to_string()),
+                    ("fpcr".to_string(), "0x03000000".to_string()),
+                ]
+            );
+
+            let mut output = Vec::new();
+            context.print(&mut output).unwrap();
+            let output = String::from_utf8(output).unwrap();
+            assert!(output.contains("  float_save.fpsr     = 0x8000010\n"));
+            assert!(output.contains("  float_save.regs[31] = 0xf0f1f2f3f4f5f6f7f8f9fafbfcfdfeff\n"));
+        }
+    }
+
+    #[test]
+    fn test_arm64_float_registers_validity() {
+        // Only some registers are known in unwound contexts, and the float
+        // registers are never among them.
+        let mut context = read_arm64_context(Endian::Little);
+        context.valid = MinidumpContextValidity::Some(["pc", "sp"].iter().copied().collect());
+        assert_eq!(context.arm64_vector(0), None);
+        assert_eq!(context.fpsr(), None);
+        assert_eq!(context.fpcr(), None);
+        assert!(context.nonzero_vector_registers().is_empty());
+    }
+
+    #[test]
+    fn test_crashpad_info_missing() {
+        let dump = SynthMinidump::with_endian(Endian::Little);
+        let dump = read_synth_dump(dump).unwrap();
+
+        assert!(matches!(
+            dump.get_stream::<MinidumpCrashpadInfo>(),
+            Err(Error::StreamNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_crashpad_info_ids() {
+        let report_id = GUID {
+            data1: 1,
+            data2: 2,
+            data3: 3,
+            data4: [4, 5, 6, 7, 8, 9, 10, 11],
+        };
+
+        let client_id = GUID {
+            data1: 11,
+            data2: 10,
+            data3: 9,
+            data4: [8, 7, 6, 5, 4, 3, 2, 1],
+        };
+
+        let crashpad_info = CrashpadInfo::new(Endian::Little)
+            .report_id(report_id)
+            .client_id(client_id);
+
+        let dump = SynthMinidump::with_endian(Endian::Little).add_crashpad_info(crashpad_info);
+        let dump = read_synth_dump(dump).unwrap();
+
+        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+
+        assert_eq!(crashpad_info.raw.report_id, report_id);
+        assert_eq!(crashpad_info.raw.client_id, client_id);
         assert_eq!(
-            *misc.raw.process_user_time().unwrap(),
-            PROCESS_TIMES.process_user_time
+            crashpad_info.report_id().unwrap().to_string(),
+            "00000001-0002-0003-0405-060708090a0b"
         );
         assert_eq!(
-            *misc.raw.process_kernel_time().unwrap(),
-            PROCESS_TIMES.process_kernel_time
+            crashpad_info.client_id().unwrap().to_string(),
+            "0000000b-000a-0009-0807-060504030201"
         );
-    }
 
-    fn ascii_string_to_utf16(input: &str) -> Vec<u16> {
-        input.chars().map(|c| c as u16).collect()
+        // All zeroes means there is no ID.
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_crashpad_info(CrashpadInfo::new(Endian::Little));
+        let dump = read_synth_dump(dump).unwrap();
+        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        assert_eq!(crashpad_info.report_id(), None);
+        assert_eq!(crashpad_info.client_id(), None);
     }
 
     #[test]
-    fn test_misc_info_5() {
-        // MISC_INFO fields
-        const PID: u32 = 0x1234abcd;
-        const PROCESS_TIMES: MiscFieldsProcessTimes = MiscFieldsProcessTimes {
-            process_create_time: 0xf0f0b0b0,
-            process_user_time: 0xf030a020,
-            process_kernel_time: 0xa010b420,
-        };
+    fn test_crashpad_info_annotations() {
+        let module = ModuleCrashpadInfo::new(42, Endian::Little)
+            .add_list_annotation("annotation")
+            .add_simple_annotation("simple", "module")
+            .add_annotation_object("string", AnnotationValue::String("value".to_owned()))
+            .add_annotation_object("invalid", AnnotationValue::Invalid)
+            .add_annotation_object("custom", AnnotationValue::Custom(0x8001, vec![42]));
 
-        // MISC_INFO_2 fields
-        const POWER_INFO: MiscFieldsPowerInfo = MiscFieldsPowerInfo {
-            processor_max_mhz: 0x45873234,
-            processor_current_mhz: 0x2134018a,
-            processor_mhz_limit: 0x3423aead,
-            processor_max_idle_state: 0x123aef12,
-            processor_current_idle_state: 0x1205af3a,
-        };
+        let crashpad_info = CrashpadInfo::new(Endian::Little)
+            .add_module(module)
+            .add_simple_annotation("simple", "info");
 
-        // MISC_INFO_3 fields
-        const PROCESS_INTEGRITY_LEVEL: u32 = 0x35603403;
-        const PROCESS_EXECUTE_FLAGS: u32 = 0xa4e09da1;
-        const PROTECTED_PROCESS: u32 = 0x12345678;
+        let dump = SynthMinidump::with_endian(Endian::Little).add_crashpad_info(crashpad_info);
+        let dump = read_synth_dump(dump).unwrap();
 
-        let mut standard_name = [0; 32];
-        let mut daylight_name = [0; 32];
-        let bare_standard_name = ascii_string_to_utf16("Pacific Standard Time");
-        let bare_daylight_name = ascii_string_to_utf16("Pacific Daylight Time");
-        standard_name[..bare_standard_name.len()].copy_from_slice(&bare_standard_name);
-        daylight_name[..bare_daylight_name.len()].copy_from_slice(&bare_daylight_name);
+        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        let module = &crashpad_info.module_list[0];
 
-        const TIME_ZONE_ID: u32 = 2;
-        const BIAS: i32 = 2;
-        const STANDARD_BIAS: i32 = 1;
-        const DAYLIGHT_BIAS: i32 = -60;
-        const STANDARD_DATE: md::SYSTEMTIME = md::SYSTEMTIME {
-            year: 0,
-            month: 11,
-            day_of_week: 2,
-            day: 1,
-            hour: 2,
-            minute: 33,
-            second: 51,
-            milliseconds: 123,
-        };
-        const DAYLIGHT_DATE: md::SYSTEMTIME = md::SYSTEMTIME {
-            year: 0,
-            month: 3,
-            day_of_week: 4,
-            day: 2,
-            hour: 3,
-            minute: 41,
-            second: 19,
-            milliseconds: 512,
-        };
+        assert_eq!(crashpad_info.simple_annotations["simple"], "info");
+        assert_eq!(module.module_index, 42);
+        assert_eq!(module.list_annotations, vec!["annotation".to_owned()]);
+        assert_eq!(module.simple_annotations["simple"], "module");
+        assert_eq!(
+            module.annotation_objects["string"],
+            MinidumpAnnotation::String("value".to_owned())
+        );
+        assert_eq!(
+            module.annotation_objects["invalid"],
+            MinidumpAnnotation::Invalid
+        );
+    }
 
-        let time_zone = MiscFieldsTimeZone {
-            time_zone_id: TIME_ZONE_ID,
-            time_zone: md::TIME_ZONE_INFORMATION {
-                bias: BIAS,
-                standard_bias: STANDARD_BIAS,
-                daylight_bias: DAYLIGHT_BIAS,
-                daylight_name,
-                standard_name,
-                standard_date: STANDARD_DATE.clone(),
-                daylight_date: DAYLIGHT_DATE.clone(),
-            },
-        };
+    #[test]
+    fn test_crashpad_info_annotation_types() {
+        let annotated = ModuleCrashpadInfo::new(1, Endian::Little)
+            .add_annotation_object("string", AnnotationValue::String(String::new()))
+            .add_annotation_object("invalid", AnnotationValue::Invalid)
+            .add_annotation_object("unknown", AnnotationValue::Custom(0x7, vec![1, 2]))
+            .add_annotation_object("custom", AnnotationValue::Custom(0x8000, vec![3]));
+        let empty = ModuleCrashpadInfo::new(2, Endian::Little);
 
-        // MISC_INFO_4 fields
-        let mut build_string = [0; 260];
-        let mut dbg_bld_str = [0; 40];
-        let bare_build_string = ascii_string_to_utf16("hello");
-        let bare_dbg_bld_str = ascii_string_to_utf16("world");
-        build_string[..bare_build_string.len()].copy_from_slice(&bare_build_string);
-        dbg_bld_str[..bare_dbg_bld_str.len()].copy_from_slice(&bare_dbg_bld_str);
+        let crashpad_info = CrashpadInfo::new(Endian::Little)
+            .add_module(annotated)
+            .add_module(empty);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_crashpad_info(crashpad_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        assert!(crashpad_info.simple_annotations.is_empty());
+        assert_eq!(crashpad_info.module_list.len(), 2);
 
-        let build_strings = MiscFieldsBuildString {
-            build_string,
-            dbg_bld_str,
-        };
+        let objects = &crashpad_info.module_list[0].annotation_objects;
+        assert_eq!(objects.len(), 4);
+        assert_eq!(objects["string"], MinidumpAnnotation::String(String::new()));
+        assert_eq!(objects["invalid"], MinidumpAnnotation::Invalid);
+        assert!(matches!(
+            objects["unknown"],
+            MinidumpAnnotation::Unsupported(md::MINIDUMP_ANNOTATION { ty: 0x7, .. })
+        ));
+        assert!(matches!(
+            objects["custom"],
+            MinidumpAnnotation::UserDefined(md::MINIDUMP_ANNOTATION { ty: 0x8000, .. })
+        ));
 
-        // MISC_INFO_5 fields
-        const SIZE_OF_INFO: u32 = mem::size_of::<md::XSTATE_CONFIG_FEATURE_MSC_INFO>() as u32;
-        const CONTEXT_SIZE: u32 = 0x1234523f;
-        const PROCESS_COOKIE: u32 = 0x1234dfe0;
-        const KNOWN_FEATURE_IDX: usize = md::XstateFeatureIndex::LEGACY_SSE as usize;
-        const UNKNOWN_FEATURE_IDX: usize = 39;
-        let mut enabled_features = 0;
-        let mut features = [md::XSTATE_FEATURE::default(); 64];
-        // One known feature and one unknown feature.
-        enabled_features |= 1 << KNOWN_FEATURE_IDX;
-        features[KNOWN_FEATURE_IDX] = md::XSTATE_FEATURE {
-            offset: 0,
-            size: 140,
-        };
-        enabled_features |= 1 << UNKNOWN_FEATURE_IDX;
-        features[UNKNOWN_FEATURE_IDX] = md::XSTATE_FEATURE {
-            offset: 320,
-            size: 1100,
-        };
-        let misc_5 = MiscInfo5Fields {
-            xstate_data: md::XSTATE_CONFIG_FEATURE_MSC_INFO {
-                size_of_info: SIZE_OF_INFO,
-                context_size: CONTEXT_SIZE,
-                enabled_features,
-                features,
-            },
-            process_cookie: Some(PROCESS_COOKIE),
-        };
+        let empty = &crashpad_info.module_list[1];
+        assert_eq!(empty.module_index, 2);
+        assert!(empty.list_annotations.is_empty());
+        assert!(empty.simple_annotations.is_empty());
+        assert!(empty.annotation_objects.is_empty());
+    }
+
+    #[test]
+    fn test_exception_x86() {
+        // Defaults to x86
+        let system_info = SystemInfo::new(Endian::Little);
+
+        let mut exception = Exception::new(Endian::Little);
 
-        let mut misc = MiscStream::new(Endian::Little);
-        misc.process_id = Some(PID);
-        misc.process_times = Some(PROCESS_TIMES);
-        misc.power_info = Some(POWER_INFO);
-        misc.process_integrity_level = Some(PROCESS_INTEGRITY_LEVEL);
-        misc.protected_process = Some(PROTECTED_PROCESS);
-        misc.process_execute_flags = Some(PROCESS_EXECUTE_FLAGS);
-        misc.time_zone = Some(time_zone);
-        misc.build_strings = Some(build_strings);
-        misc.misc_5 = Some(misc_5);
+        // Check that we clear the erroneous high bits for 32-bit
+        exception.exception_record.exception_address = 0xf0e1_d2c3_b4a5_9687;
+        // FIXME: test other fields too
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_exception(exception);
 
-        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(misc);
         let dump = read_synth_dump(dump).unwrap();
-        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
 
-        // MISC_INFO fields
-        assert_eq!(misc.raw.process_id(), Some(&PID));
+        let system_stream = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let exception_stream = dump.get_stream::<MinidumpException>().unwrap();
         assert_eq!(
-            misc.process_create_time().unwrap(),
-            Utc.timestamp(PROCESS_TIMES.process_create_time as i64, 0)
+            exception_stream.get_crash_address(system_stream.os, system_stream.cpu),
+            0xb4a5_9687
         );
+    }
+
+    #[test]
+    fn test_exception_x64() {
+        // Defaults to x86
+        let system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16);
+
+        let mut exception = Exception::new(Endian::Little);
+
+        // Check that we don't truncate this on 64-bit
+        exception.exception_record.exception_address = 0xf0e1_d2c3_b4a5_9687;
+        // FIXME: test other fields too
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_exception(exception);
+
+        let dump = read_synth_dump(dump).unwrap();
+
+        let system_stream = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let exception_stream = dump.get_stream::<MinidumpException>().unwrap();
         assert_eq!(
-            *misc.raw.process_user_time().unwrap(),
-            PROCESS_TIMES.process_user_time
+            exception_stream.get_crash_address(system_stream.os, system_stream.cpu),
+            0xf0e1_d2c3_b4a5_9687
         );
+    }
+
+    #[test]
+    fn test_exception_message() {
+        let message = |code: u32, params: &[u64], platform_id| {
+            let strings = Memory::with_section(
+                Section::with_endian(Endian::Little)
+                    .append_bytes(b"narrow message\0")
+                    .append_bytes(b"\0")
+                    .append_bytes(b"w\0i\0d\0e\0\0\0"),
+                0x5000,
+            );
+            let mut system_info = SystemInfo::new(Endian::Little);
+            system_info.platform_id = platform_id;
+            let mut exception = Exception::new(Endian::Little);
+            exception.exception_record.exception_code = code;
+            exception.exception_record.number_parameters = params.len() as u32;
+            exception.exception_record.exception_information[..params.len()]
+                .copy_from_slice(params);
+            let dump = SynthMinidump::with_endian(Endian::Little)
+                .add_system_info(system_info)
+                .add_exception(exception)
+                .add_memory(strings);
+            let dump = read_synth_dump(dump).unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let exception = dump.get_stream::<MinidumpException>().unwrap();
+            exception.get_message(system_info.os, &dump.memory().unwrap())
+        };
+        let windows = md::PlatformId::VER_PLATFORM_WIN32_NT as u32;
+        let print = md::NtStatusWindows::DBG_PRINTEXCEPTION_C as u32;
+        let print_wide = md::NtStatusWindows::DBG_PRINTEXCEPTION_WIDE_C as u32;
+
         assert_eq!(
-            *misc.raw.process_kernel_time().unwrap(),
-            PROCESS_TIMES.process_kernel_time
+            message(print, &[15, 0x5000], windows).as_deref(),
+            Some("narrow message")
         );
-
-        // MISC_INFO_2 fields
+        // The length is a limit.
         assert_eq!(
-            *misc.raw.processor_max_mhz().unwrap(),
-            POWER_INFO.processor_max_mhz,
+            message(print, &[6, 0x5000], windows).as_deref(),
+            Some("narrow")
         );
         assert_eq!(
-            *misc.raw.processor_current_mhz().unwrap(),
-            POWER_INFO.processor_current_mhz,
+            message(print_wide, &[5, 0x5010, 0, 0], windows).as_deref(),
+            Some("wide")
         );
+        // Fall back to the narrow copy of a wide message.
         assert_eq!(
-            *misc.raw.processor_mhz_limit().unwrap(),
-            POWER_INFO.processor_mhz_limit,
+            message(print_wide, &[5, 0x9000, 7, 0x5007], windows).as_deref(),
+            Some("message")
         );
+
+        // Missing memory, missing parameters, other exceptions, other OSes.
+        assert_eq!(message(print, &[15, 0x9000], windows), None);
+        assert_eq!(message(print, &[15], windows), None);
+        assert_eq!(message(0xc0000005, &[15, 0x5000], windows), None);
+        let linux = md::PlatformId::Linux as u32;
+        assert_eq!(message(print, &[15, 0x5000], linux), None);
+    }
+
+    fn raw_exception(code: u32, flags: u32, info: &[u64]) -> md::MINIDUMP_EXCEPTION_STREAM {
+        let mut exception_information = [0; 15];
+        exception_information[..info.len()].copy_from_slice(info);
+        md::MINIDUMP_EXCEPTION_STREAM {
+            thread_id: 0,
+            __align: 0,
+            exception_record: md::MINIDUMP_EXCEPTION {
+                exception_code: code,
+                exception_flags: flags,
+                exception_record: 0,
+                exception_address: 0,
+                number_parameters: info.len() as u32,
+                __align: 0,
+                exception_information,
+            },
+            thread_context: md::MINIDUMP_LOCATION_DESCRIPTOR {
+                data_size: 0,
+                rva: 0,
+            },
+        }
+    }
+
+    fn crash_reason(os: Os, cpu: Cpu, code: u32, flags: u32, info: &[u64]) -> String {
+        CrashReason::from_exception(&raw_exception(code, flags, info), os, cpu).to_string()
+    }
+
+    #[test]
+    fn test_crash_reason_windows() {
+        let table: &[(u32, &[u64], &str)] = &[
+            (0xc0000005, &[1, 0x10], "EXCEPTION_ACCESS_VIOLATION_WRITE"),
+            (0xc0000005, &[8, 0x10], "EXCEPTION_ACCESS_VIOLATION_EXEC"),
+            (
+                0xc0000006,
+                &[0, 0x10, 0xc000009c],
+                "EXCEPTION_IN_PAGE_ERROR_READ / STATUS_DEVICE_DATA_ERROR",
+            ),
+            (
+                0xc0000409,
+                &[7],
+                "EXCEPTION_STACK_BUFFER_OVERRUN / FAST_FAIL_FATAL_APP_EXIT",
+            ),
+            (
+                0xc0000409,
+                &[0x1234],
+                "EXCEPTION_STACK_BUFFER_OVERRUN / 0x00001234",
+            ),
+            (0xe06d7363, &[], "Unhandled C++ Exception"),
+            (0xc0000374, &[], "STATUS_HEAP_CORRUPTION"),
+            (
+                0xe0434352,
+                &[],
+                "unknown 0xe0434352 / STATUS_SEVERITY_ERROR / customer facility 0x043 / code 0x4352",
+            ),
+            (
+                0x80029999,
+                &[],
+                "unknown 0x80029999 / STATUS_SEVERITY_WARNING / FACILITY_RPC_RUNTIME / code 0x9999",
+            ),
+            (
+                0xc0ab1234,
+                &[],
+                "unknown 0xc0ab1234 / STATUS_SEVERITY_ERROR / facility 0x0ab / code 0x1234",
+            ),
+            (0x0000abcd, &[], "unknown 0x0000abcd"),
+        ];
+        for &(code, info, expected) in table {
+            assert_eq!(crash_reason(Os::Windows, Cpu::X86, code, 0, info), expected);
+        }
+    }
+
+    #[test]
+    fn test_crash_reason_linux() {
+        let table = [
+            (11, 1, "SIGSEGV / SEGV_MAPERR"),
+            (11, 2, "SIGSEGV / SEGV_ACCERR"),
+            (11, 9, "SIGSEGV / SEGV_MTESERR"),
+            (11, 0xfffffffa, "SIGSEGV / SI_TKILL"),
+            (11, 0x42, "SIGSEGV / 0x00000042"),
+            (7, 1, "SIGBUS / BUS_ADRALN"),
+            (4, 9, "SIGILL / ILL_BADIADDR"),
+            (8, 1, "SIGFPE / FPE_INTDIV"),
+            (5, 1, "SIGTRAP / TRAP_BRKPT"),
+            (31, 1, "SIGSYS / SYS_SECCOMP"),
+            (6, 0, "SIGABRT / SI_USER"),
+            (6, 0xfffffffa, "SIGABRT / SI_TKILL"),
+            (6, 0xffffffff, "SIGABRT / SI_QUEUE"),
+            (0xffffffff, 0, "DUMP_REQUESTED / 0x00000000"),
+        ];
+        for &(code, flags, expected) in &table {
+            assert_eq!(
+                crash_reason(Os::Linux, Cpu::X86_64, code, flags, &[]),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_crash_reason_mac() {
+        let table = [
+            (Cpu::X86_64, 1, 1, "EXC_BAD_ACCESS / KERN_INVALID_ADDRESS"),
+            (Cpu::Arm64, 1, 0x101, "EXC_BAD_ACCESS / EXC_ARM_DA_ALIGN"),
+            (Cpu::Arm64, 1, 0x103, "EXC_BAD_ACCESS / EXC_ARM_SP_ALIGN"),
+            (Cpu::Arm64, 1, 0x105, "EXC_BAD_ACCESS / EXC_ARM_PAC_FAIL"),
+            (Cpu::X86_64, 1, 13, "EXC_BAD_ACCESS / EXC_I386_GPFLT"),
+            (Cpu::Arm64, 3, 2, "EXC_ARITHMETIC / EXC_ARM_FP_DZ"),
+            (Cpu::Arm64, 3, 6, "EXC_ARITHMETIC / EXC_ARM_FP_ID"),
+            (Cpu::X86_64, 3, 1, "EXC_ARITHMETIC / EXC_I386_DIV"),
+            (Cpu::X86_64, 3, 8, "EXC_ARITHMETIC / EXC_I386_SSEEXTERR"),
+            (Cpu::Arm64, 6, 1, "EXC_BREAKPOINT / EXC_ARM_BREAKPOINT"),
+            (
+                Cpu::X86_64,
+                10,
+                11 << 24 | 1 << 20 | 1,
+                "EXC_CRASH / EXC_BAD_ACCESS / SIGSEGV",
+            ),
+            (Cpu::Arm64, 10, 6 << 24, "EXC_CRASH / SIGABRT"),
+            (Cpu::Arm64, 10, 99 << 24, "EXC_CRASH / signal 99"),
+            (Cpu::X86_64, 7, 5, "EXC_SYSCALL / 0x00000005"),
+            (Cpu::X86_64, 0x43507378, 0, "Simulated Exception"),
+        ];
+        for &(cpu, code, flags, expected) in &table {
+            assert_eq!(crash_reason(Os::MacOs, cpu, code, flags, &[]), expected);
+        }
+    }
+
+    #[test]
+    fn test_mac_crash_address() {
+        let exception = |code, address, info: &[u64]| {
+            let mut raw = raw_exception(code, 1, info);
+            raw.exception_record.exception_address = address;
+            MinidumpException {
+                raw,
+                thread_id: 0,
+                context: None,
+                endian: scroll::LE,
+            }
+        };
+        let exc_bad_access = md::ExceptionCodeMac::EXC_BAD_ACCESS as u32;
+        let exc_breakpoint = md::ExceptionCodeMac::EXC_BREAKPOINT as u32;
+
+        // Breakpad puts the subcode in the exception address.
+        let breakpad = exception(exc_bad_access, 0x10, &[]);
+        assert_eq!(breakpad.get_crash_address(Os::MacOs, Cpu::Arm64), 0x10);
+
+        // Otherwise it comes from the exception type, code and subcode.
+        let raw_codes = exception(exc_bad_access, 0, &[1, 1, 0x7fff_0000_1230]);
         assert_eq!(
-            *misc.raw.processor_max_idle_state().unwrap(),
-            POWER_INFO.processor_max_idle_state,
+            raw_codes.get_crash_address(Os::MacOs, Cpu::X86_64),
+            0x7fff_0000_1230
         );
         assert_eq!(
-            *misc.raw.processor_current_idle_state().unwrap(),
-            POWER_INFO.processor_current_idle_state,
+            raw_codes.get_crash_address(Os::Ios, Cpu::Arm64),
+            0x7fff_0000_1230
         );
+        // Except on other OSes, where that's not necessarily what the parameters mean.
+        assert_eq!(raw_codes.get_crash_address(Os::Linux, Cpu::X86_64), 0);
+
+        // Only EXC_BAD_ACCESS has an address for a subcode.
+        let breakpoint = exception(exc_breakpoint, 0, &[6, 1, 0x1000]);
+        assert_eq!(breakpoint.get_crash_address(Os::MacOs, Cpu::Arm64), 0);
+    }
+
+    fn handle_descriptor(
+        section: Section,
+        handle: u64,
+        type_name: Option<&DumpString>,
+        object_name: Option<&DumpString>,
+        object_info_rva: Option<u32>,
+    ) -> Section {
+        let section = section.D64(handle);
+        let section = match type_name {
+            Some(name) => section.D32(name.file_offset()),
+            None => section.D32(0),
+        };
+        let section = match object_name {
+            Some(name) => section.D32(name.file_offset()),
+            None => section.D32(0),
+        };
+        let section = section
+            .D32(0x2) // attributes
+            .D32(0x1f0003) // granted_access
+            .D32(handle as u32 + 1) // handle_count
+            .D32(handle as u32 + 2); // pointer_count
+        match object_info_rva {
+            Some(rva) => section.D32(rva).D32(0),
+            None => section,
+        }
+    }
 
-        // MISC_INFO_3 fields
-        assert_eq!(*misc.raw.time_zone_id().unwrap(), TIME_ZONE_ID);
-        let time_zone = misc.raw.time_zone().unwrap();
-        assert_eq!(time_zone.bias, BIAS);
-        assert_eq!(time_zone.standard_bias, STANDARD_BIAS);
-        assert_eq!(time_zone.daylight_bias, DAYLIGHT_BIAS);
-        assert_eq!(time_zone.standard_date, STANDARD_DATE);
-        assert_eq!(time_zone.daylight_date, DAYLIGHT_DATE);
-        assert_eq!(time_zone.standard_name, standard_name);
-        assert_eq!(time_zone.daylight_name, daylight_name);
+    #[test]
+    fn test_handle_data_stream() {
+        let event = DumpString::new("Event", Endian::Little);
+        let object = DumpString::new("\\BaseNamedObjects\\MyEvent", Endian::Little);
+        let section = Section::with_endian(Endian::Little)
+            .D32(16) // size_of_header
+            .D32(32) // size_of_descriptor
+            .D32(2) // number_of_descriptors
+            .D32(0); // reserved
+        let section = handle_descriptor(section, 0x4, Some(&event), Some(&object), None);
+        let section = handle_descriptor(section, 0x8, None, None, None);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::HandleDataStream as u32,
+                section,
+            })
+            .add(event)
+            .add(object);
+        let dump = read_synth_dump(dump).unwrap();
+        let handles = dump.get_stream::<MinidumpHandleDataStream>().unwrap();
+        assert_eq!(handles.len(), 2);
+        let handles = handles.iter().collect::<Vec<_>>();
 
-        // MISC_INFO_4 fields
-        assert_eq!(*misc.raw.build_string().unwrap(), build_string,);
-        assert_eq!(*misc.raw.dbg_bld_str().unwrap(), dbg_bld_str,);
+        assert!(matches!(
+            handles[0].raw,
+            RawHandleDescriptor::HandleDescriptor(_)
+        ));
+        assert_eq!(handles[0].handle(), 0x4);
+        assert_eq!(handles[0].type_name.as_deref(), Some("Event"));
+        assert_eq!(
+            handles[0].object_name.as_deref(),
+            Some("\\BaseNamedObjects\\MyEvent")
+        );
+        assert_eq!(handles[0].attributes(), 0x2);
+        assert_eq!(handles[0].granted_access(), 0x1f0003);
+        assert_eq!(handles[0].handle_count(), 0x5);
+        assert_eq!(handles[0].pointer_count(), 0x6);
+        assert_eq!(handles[0].object_info_rva(), None);
 
-        // MISC_INFO_5 fields
-        assert_eq!(*misc.raw.process_cookie().unwrap(), PROCESS_COOKIE,);
+        assert_eq!(handles[1].handle(), 0x8);
+        assert_eq!(handles[1].type_name, None);
+        assert_eq!(handles[1].object_name, None);
+    }
 
-        let xstate = misc.raw.xstate_data().unwrap();
-        assert_eq!(xstate.size_of_info, SIZE_OF_INFO);
-        assert_eq!(xstate.context_size, CONTEXT_SIZE);
-        assert_eq!(xstate.enabled_features, enabled_features);
-        assert_eq!(xstate.features, features);
+    #[test]
+    fn test_handle_data_stream_descriptor_2() {
+        let file = DumpString::new("File", Endian::Little);
+        let section = Section::with_endian(Endian::Little)
+            .D32(16) // size_of_header
+            .D32(40) // size_of_descriptor
+            .D32(1) // number_of_descriptors
+            .D32(0); // reserved
+                     // No object name
+        let section = handle_descriptor(section, 0x1c, Some(&file), None, Some(0));
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::HandleDataStream as u32,
+                section,
+            })
+            .add(file);
+        let dump = read_synth_dump(dump).unwrap();
+        let handles = dump.get_stream::<MinidumpHandleDataStream>().unwrap();
+        let handles = handles.iter().collect::<Vec<_>>();
+        assert_eq!(handles.len(), 1);
 
-        let mut xstate_iter = xstate.iter();
+        assert!(matches!(
+            handles[0].raw,
+            RawHandleDescriptor::HandleDescriptor2(_)
+        ));
+        assert_eq!(handles[0].handle(), 0x1c);
+        assert_eq!(handles[0].type_name.as_deref(), Some("File"));
+        assert_eq!(handles[0].object_name, None);
+        assert_eq!(handles[0].handle_count(), 0x1d);
+        assert_eq!(handles[0].object_info_rva(), Some(0));
+    }
+
+    #[test]
+    fn test_handle_data_stream_bad_descriptor_size() {
+        let section = Section::with_endian(Endian::Little)
+            .D32(16) // size_of_header
+            .D32(36) // size_of_descriptor
+            .D32(1) // number_of_descriptors
+            .D32(0) // reserved
+            .append_repeated(0, 36);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::HandleDataStream as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpHandleDataStream>().is_err());
+
+        // A zero-sized descriptor would let any count pass the bounds check.
+        let section = Section::with_endian(Endian::Little)
+            .D32(16) // size_of_header
+            .D32(0) // size_of_descriptor
+            .D32(0xffffffff) // number_of_descriptors
+            .D32(0); // reserved
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::HandleDataStream as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpHandleDataStream>().is_err());
+    }
+
+    #[test]
+    fn test_mac_crash_info_too_many_records() {
+        let section = Section::with_endian(Endian::Little)
+            .D32(MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32)
+            .D32(21) // record_count, more than the header has room for
+            .D32(0) // record_start_size
+            .append_repeated(0, 20 * 8); // records
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpMacCrashInfo>().is_err());
+    }
+
+    /// A MozMacosCrashInfoStream record with the given fixed fields (starting
+    /// at `thread`) followed by `strings`, which must include their NULs.
+    fn mac_crash_info_record(version: u64, fixed: &[u64], strings: &[u8]) -> Section {
+        let stream_type = MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u64;
+        let mut section = Section::with_endian(Endian::Little)
+            .D64(stream_type)
+            .D64(version);
+        for &field in fixed {
+            section = section.D64(field);
+        }
+        section.append_bytes(strings)
+    }
+
+    fn mac_crash_info_dump(
+        record_start_size: u32,
+        records: Vec<Section>,
+    ) -> Minidump<'static, Vec<u8>> {
+        let mut section = Section::with_endian(Endian::Little)
+            .D32(MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32)
+            .D32(records.len() as u32)
+            .D32(record_start_size);
+        for record in &records {
+            section = section.cite_location(record);
+        }
+        section = section.append_repeated(0, (20 - records.len()) * 8);
+        let mut dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32,
+            section,
+        });
+        for record in records {
+            dump = dump.add(record);
+        }
+        read_synth_dump(dump).unwrap()
+    }
+
+    #[test]
+    fn test_mac_crash_info_v4() {
+        let complete = mac_crash_info_record(
+            4,
+            &[0x1234, 0],
+            b"/usr/lib/libobjc.A.dylib\0objc[123]: unrecognized selector\0\0\0extra\0",
+        );
+        // The copy of this one was cut off in the middle of the message.
+        let truncated = mac_crash_info_record(
+            4,
+            &[0, 0x2],
+            b"/usr/lib/libc++abi.dylib\0terminating with uncaught exc",
+        );
+        let dump = mac_crash_info_dump(32, vec![complete, truncated]);
+        let info = dump.get_stream::<MinidumpMacCrashInfo>().unwrap();
+        assert_eq!(info.raw.len(), 2);
+
+        let record = &info.raw[0];
+        assert!(matches!(record, RawMacCrashInfo::V4(..)));
+        assert_eq!(record.version(), Some(&4));
+        assert_eq!(record.thread(), Some(&0x1234));
+        assert_eq!(record.dialog_mode(), None);
+        assert_eq!(record.abort_cause(), None);
+        assert_eq!(record.module_path(), Some("/usr/lib/libobjc.A.dylib"));
+        assert_eq!(record.message(), Some("objc[123]: unrecognized selector"));
+        assert_eq!(record.signature_string(), None);
+        assert_eq!(record.backtrace(), None);
+        assert_eq!(record.message2(), Some("extra"));
         assert_eq!(
-            xstate_iter.next().unwrap(),
-            (KNOWN_FEATURE_IDX, features[KNOWN_FEATURE_IDX]),
+            record.messages(),
+            vec!["objc[123]: unrecognized selector", "extra"]
+        );
+
+        let record = &info.raw[1];
+        assert_eq!(record.thread(), None);
+        assert_eq!(record.dialog_mode(), Some(&0x2));
+        assert_eq!(record.module_path(), Some("/usr/lib/libc++abi.dylib"));
+        assert_eq!(record.message(), Some("terminating with uncaught exc"));
+        assert_eq!(record.signature_string(), None);
+        assert_eq!(record.message2(), None);
+    }
+
+    #[test]
+    fn test_mac_crash_info_v5() {
+        // A record_start_size past the fields we know about, as if there were a
+        // newer field, and the strings cut off before the last one.
+        let record = mac_crash_info_record(
+            5,
+            &[0x5678, 0, 0x6, 0xffff],
+            b"/System/Library/Frameworks/AppKit.framework/AppKit\0\0sig\0frame 0",
         );
+        let dump = mac_crash_info_dump(48, vec![record]);
+        let info = dump.get_stream::<MinidumpMacCrashInfo>().unwrap();
+        assert_eq!(info.raw.len(), 1);
+
+        let record = &info.raw[0];
+        assert!(matches!(record, RawMacCrashInfo::V5(..)));
+        assert_eq!(record.thread(), Some(&0x5678));
+        assert_eq!(record.abort_cause(), Some(&0x6));
         assert_eq!(
-            xstate_iter.next().unwrap(),
-            (UNKNOWN_FEATURE_IDX, features[UNKNOWN_FEATURE_IDX]),
+            record.module_path(),
+            Some("/System/Library/Frameworks/AppKit.framework/AppKit")
         );
-        assert_eq!(xstate_iter.next(), None);
-        assert_eq!(xstate_iter.next(), None);
+        assert_eq!(record.message(), None);
+        assert_eq!(record.signature_string(), Some("sig"));
+        assert_eq!(record.backtrace(), Some("frame 0"));
+        assert_eq!(record.message2(), None);
+        assert_eq!(record.messages(), vec!["sig", "frame 0"]);
+
+        // All the records have to agree on the version.
+        let v4 = mac_crash_info_record(4, &[0, 0], b"\0\0\0\0\0");
+        let v5 = mac_crash_info_record(5, &[0, 0, 0], b"\0\0\0\0\0");
+        let dump = mac_crash_info_dump(40, vec![v4, v5]);
+        assert!(matches!(
+            dump.get_stream::<MinidumpMacCrashInfo>(),
+            Err(Error::VersionMismatch)
+        ));
+    }
+
+    fn handle_operation(
+        section: Section,
+        handle: u64,
+        thread_id: u32,
+        operation_type: u32,
+        backtrace: &[u64],
+        depth: u32,
+    ) -> Section {
+        let mut section = section
+            .D64(handle)
+            .D32(0x1234) // process_id
+            .D32(thread_id)
+            .D32(operation_type)
+            .D32(0) // spare0
+            .D32(depth)
+            .D32(0x10); // index
+        for &address in backtrace {
+            section = section.D64(address);
+        }
+        section.append_repeated(0, (md::AVRF_MAX_TRACES - backtrace.len()) * 8)
     }
 
     #[test]
-    fn test_elf_build_id() {
-        // Add a module with a long ELF build id
-        let name1 = DumpString::new("module 1", Endian::Little);
-        const MODULE1_BUILD_ID: &[u8] = &[
-            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
-            0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
-        ];
-        let cv_record1 = Section::with_endian(Endian::Little)
-            .D32(md::CvSignature::Elf as u32) // signature
-            .append_bytes(MODULE1_BUILD_ID);
-        let module1 = SynthModule::new(
-            Endian::Little,
-            0x100000000,
-            0x4000,
-            &name1,
-            0xb1054d2a,
-            0x34571371,
-            Some(&STOCK_VERSION_INFO),
-        )
-        .cv_record(&cv_record1);
-        // Add a module with a short ELF build id
-        let name2 = DumpString::new("module 2", Endian::Little);
-        const MODULE2_BUILD_ID: &[u8] = &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
-        let cv_record2 = Section::with_endian(Endian::Little)
-            .D32(md::CvSignature::Elf as u32) // signature
-            .append_bytes(MODULE2_BUILD_ID);
-        let module2 = SynthModule::new(
-            Endian::Little,
-            0x200000000,
-            0x4000,
-            &name2,
-            0xb1054d2a,
-            0x34571371,
-            Some(&STOCK_VERSION_INFO),
-        )
-        .cv_record(&cv_record2);
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_module(module1)
-            .add_module(module2)
-            .add(name1)
-            .add(cv_record1)
-            .add(name2)
-            .add(cv_record2);
+    fn test_handle_operation_list() {
+        let open_backtrace = [0x7c801010, 0x7c802020, 0x7c803030];
+        let close_backtrace = [0x7c804040; md::AVRF_MAX_TRACES];
+        let section = Section::with_endian(Endian::Little)
+            .D32(16) // size_of_header
+            .D32(288) // size_of_entry
+            .D32(4) // number_of_entries
+            .D32(0); // reserved
+        let section = handle_operation(section, 0x4, 0x1, 1, &open_backtrace, 3);
+        // Full backtrace
+        let section = handle_operation(section, 0x4, 0x2, 2, &close_backtrace, 32);
+        // Depth claims more addresses than there can be
+        let section = handle_operation(section, 0x8, 0x2, 2, &close_backtrace, 100);
+        // Unknown operation type, empty backtrace
+        let section = handle_operation(section, 0xc, 0x3, 7, &[], 0);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::HandleOperationListStream as u32,
+            section,
+        });
         let dump = read_synth_dump(dump).unwrap();
-        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
-        let modules = module_list.iter().collect::<Vec<_>>();
-        assert_eq!(modules.len(), 2);
-        assert_eq!(modules[0].base_address(), 0x100000000);
-        assert_eq!(modules[0].code_file(), "module 1");
-        // The full build ID.
+        let operation_list = dump.get_stream::<MinidumpHandleOperationList>().unwrap();
+        let operations = operation_list.iter().collect::<Vec<_>>();
+        assert_eq!(operations.len(), 4);
+
+        assert_eq!(operations[0].handle(), 0x4);
+        assert_eq!(operations[0].process_id(), 0x1234);
+        assert_eq!(operations[0].thread_id(), 0x1);
         assert_eq!(
-            modules[0].code_identifier(),
-            "000102030405060708090a0b0c0d0e0f1011121314151617"
+            operations[0].operation_type(),
+            Some(md::HandleTraceOperation::OperationDbOPEN)
         );
-        assert_eq!(modules[0].debug_file().unwrap(), "module 1");
-        // The first 16 bytes of the build ID interpreted as a GUID.
+        assert_eq!(operations[0].backtrace(), &open_backtrace[..]);
+
         assert_eq!(
-            modules[0].debug_identifier().unwrap(),
-            "030201000504070608090A0B0C0D0E0F0"
+            operations[1].operation_type(),
+            Some(md::HandleTraceOperation::OperationDbCLOSE)
         );
+        assert_eq!(operations[1].backtrace(), &close_backtrace[..]);
+        assert_eq!(operations[2].backtrace(), &close_backtrace[..]);
 
-        assert_eq!(modules[1].base_address(), 0x200000000);
-        assert_eq!(modules[1].code_file(), "module 2");
-        // The full build ID.
-        assert_eq!(modules[1].code_identifier(), "0001020304050607");
-        assert_eq!(modules[1].debug_file().unwrap(), "module 2");
-        // The first 16 bytes of the build ID interpreted as a GUID, padded with
-        // zeroes in this case.
+        assert_eq!(operations[3].operation_type(), None);
+        assert_eq!(operations[3].backtrace(), &[] as &[u64]);
+
+        let closers = operation_list
+            .operations_on_handle(0x4)
+            .filter(|op| op.operation_type() == Some(md::HandleTraceOperation::OperationDbCLOSE))
+            .map(|op| op.thread_id())
+            .collect::<Vec<_>>();
+        assert_eq!(closers, vec![0x2]);
+    }
+
+    #[test]
+    fn test_handle_operation_list_bad_entry_size() {
+        let section = Section::with_endian(Endian::Little)
+            .D32(16) // size_of_header
+            .D32(280) // size_of_entry
+            .D32(1) // number_of_entries
+            .D32(0) // reserved
+            .append_repeated(0, 280);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::HandleOperationListStream as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpHandleOperationList>().is_err());
+    }
+
+    #[test]
+    fn test_function_table_list() {
+        let jit_table = SynthFunctionTable::new(
+            Endian::Little,
+            0x1000_0000,
+            0x1000_0200,
+            0x1000_0000,
+            &[(0x0, 0x100, 0x1000), (0x100, 0x200, 0x1010)],
+        );
+        let other_table =
+            SynthFunctionTable::new(Endian::Little, 0x2000_0000, 0x2000_0100, 0x2000_0000, &[]);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_function_table(jit_table)
+            .add_function_table(other_table);
+        let dump = read_synth_dump(dump).unwrap();
+        let function_tables = dump.get_stream::<MinidumpFunctionTableList>().unwrap();
+        let tables = function_tables.iter().collect::<Vec<_>>();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].base_address(), 0x1000_0000);
+        assert_eq!(tables[0].entries.len(), 2);
+        // The second table is read from after the first's padding.
+        assert_eq!(tables[1].base_address(), 0x2000_0000);
+        assert!(tables[1].entries.is_empty());
+
+        let (table, entry) = function_tables.function_at_address(0x1000_0180).unwrap();
+        assert_eq!(table.base_address(), 0x1000_0000);
         assert_eq!(
-            modules[1].debug_identifier().unwrap(),
-            "030201000504070600000000000000000"
+            *entry,
+            md::IMAGE_RUNTIME_FUNCTION_ENTRY {
+                begin_address: 0x100,
+                end_address: 0x200,
+                unwind_info_address: 0x1010,
+            }
         );
+        assert!(function_tables.function_at_address(0x1000_0200).is_none());
+        assert!(function_tables.function_at_address(0x2000_0010).is_none());
     }
 
     #[test]
-    fn test_thread_list_x86() {
+    fn test_function_table_list_bad_entry_size() {
+        let section = Section::with_endian(Endian::Little)
+            .D32(24) // size_of_header
+            .D32(32) // size_of_descriptor
+            .D32(0) // size_of_native_descriptor
+            .D32(8) // size_of_function_entry (ARM64)
+            .D32(0) // number_of_descriptors
+            .D32(0); // size_of_align_pad
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::FunctionTable as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpFunctionTableList>().is_err());
+    }
+
+    #[test]
+    fn test_fuzzed_oom() {
+        // https://github.com/luser/rust-minidump/issues/381
+        let data = b"MDMP\x93\xa7\x00\x00\x00\xffffdYfffff@\n\nfp\n\xbb\xff\xff\xff\n\xff\n";
+        assert!(Minidump::read(data.as_ref()).is_err());
+
+        // https://github.com/getsentry/symbolic/issues/478
+        let data = b"MDMP\x93\xa7\x00\x00\r\x00\x00\x00 \xff\xff\xff\xff\xff\xff\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+        assert!(Minidump::read(data.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        const STREAM_TYPE: u32 = 0x11223344;
         let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
         let stack = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            Section::with_endian(Endian::Little).append_repeated(0xcc, 0x100),
             0x1000,
         );
+        let full_memory = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"memory_contents"),
+            0x7fff_0000_2000,
+        );
         let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
         let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
         let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let thread_name = DumpString::new("MainThread", Endian::Little);
+        let module_name = DumpString::new("single module", Endian::Little);
+        let cv_record = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32) // signature
+            // signature, a GUID
+            .D32(0xabcd1234)
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1) // age
+            .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
+        let module = SynthModule::new(
+            Endian::Little,
+            0x10000,
+            0x1000,
+            &module_name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
         let dump = SynthMinidump::with_endian(Endian::Little)
             .add_thread(thread)
             .add(context)
             .add_memory(stack)
-            .add_system_info(system_info);
+            .add_memory64(full_memory)
+            .add_system_info(system_info)
+            .add_thread_name(ThreadName::new(Endian::Little, 0x1234, Some(&thread_name)))
+            .add(thread_name)
+            .add_module(module)
+            .add(module_name)
+            .add(cv_record)
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            });
         let dump = read_synth_dump(dump).unwrap();
-        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+
+        let mut bytes = Vec::new();
+        dump.write(&mut bytes).unwrap();
+        let dump = Minidump::read(bytes).unwrap();
+
         let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
-        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
-        assert_eq!(thread_list.threads.len(), 1);
-        let mut thread = thread_list.threads.pop().unwrap();
-        assert_eq!(thread.raw.thread_id, 0x1234);
-        let context = thread
-            .context(&system_info, misc_info.as_ref())
-            .expect("Should have a thread context");
-        match &context.raw {
+        assert_eq!(system_info.cpu, Cpu::X86);
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let thread = &thread_list.threads[0];
+        match &thread.context(&system_info, None).unwrap().raw {
             MinidumpRawContext::X86(raw) => {
                 assert_eq!(raw.eip, 0xabcd1234);
                 assert_eq!(raw.esp, 0x1010);
             }
             _ => panic!("Got unexpected raw context type!"),
         }
-        let stack = thread.stack.take().expect("Should have stack memory");
+        let stack = thread.direct_stack().unwrap();
         assert_eq!(stack.base_address, 0x1000);
-        assert_eq!(stack.size, 0x1000);
-    }
-
-    #[test]
-    fn test_thread_list_amd64() {
-        let context =
-            synth_minidump::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000);
-        let stack = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
-            0x1000000010000000,
-        );
-        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
-        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
-        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_thread(thread)
-            .add(context)
-            .add_memory(stack)
-            .add_system_info(system_info);
-        let dump = read_synth_dump(dump).unwrap();
-        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
-        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
-        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
-        assert_eq!(thread_list.threads.len(), 1);
-        let mut thread = thread_list.threads.pop().unwrap();
-        assert_eq!(thread.raw.thread_id, 0x1234);
-        let context = thread
-            .context(&system_info, misc_info.as_ref())
-            .expect("Should have a thread context");
-        match &context.raw {
-            MinidumpRawContext::Amd64(raw) => {
-                assert_eq!(raw.rip, 0x1234abcd1234abcd);
-                assert_eq!(raw.rsp, 0x1000000010000000);
-            }
-            _ => panic!("Got unexpected raw context type!"),
-        }
-        let stack = thread.stack.take().expect("Should have stack memory");
-        assert_eq!(stack.base_address, 0x1000000010000000);
-        assert_eq!(stack.size, 0x1000);
-    }
-
-    #[test]
-    fn test_crashpad_info_missing() {
-        let dump = SynthMinidump::with_endian(Endian::Little);
-        let dump = read_synth_dump(dump).unwrap();
-
-        assert!(matches!(
-            dump.get_stream::<MinidumpCrashpadInfo>(),
-            Err(Error::StreamNotFound)
-        ));
-    }
-
-    #[test]
-    fn test_crashpad_info_ids() {
-        let report_id = GUID {
-            data1: 1,
-            data2: 2,
-            data3: 3,
-            data4: [4, 5, 6, 7, 8, 9, 10, 11],
-        };
+        assert_eq!(stack.bytes, &[0xcc; 0x100][..]);
 
-        let client_id = GUID {
-            data1: 11,
-            data2: 10,
-            data3: 9,
-            data4: [8, 7, 6, 5, 4, 3, 2, 1],
-        };
+        // The stack is only written once.
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].desc.memory.rva, thread.raw.stack.memory.rva);
 
-        let crashpad_info = CrashpadInfo::new(Endian::Little)
-            .report_id(report_id)
-            .client_id(client_id);
+        let memory64_list = dump.get_stream::<MinidumpMemory64List<'_>>().unwrap();
+        let region = memory64_list.memory_at_address(0x7fff_0000_2000).unwrap();
+        assert_eq!(region.bytes, b"memory_contents");
 
-        let dump = SynthMinidump::with_endian(Endian::Little).add_crashpad_info(crashpad_info);
-        let dump = read_synth_dump(dump).unwrap();
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(&*thread_names.get_name(0x1234).unwrap(), "MainThread");
 
-        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let module = module_list.iter().next().unwrap();
+        assert_eq!(module.code_file(), "single module");
+        assert_eq!(module.debug_file().unwrap(), "c:\\foo\\file.pdb");
+        assert_eq!(
+            module.debug_identifier().unwrap(),
+            "ABCD1234F00DBEEF01020304050607081"
+        );
 
-        assert_eq!(crashpad_info.raw.report_id, report_id);
-        assert_eq!(crashpad_info.raw.client_id, client_id);
+        // Unknown streams are copied as-is.
+        assert_eq!(
+            dump.get_raw_stream(STREAM_TYPE).unwrap(),
+            &[0x88, 0x77, 0x66, 0x55]
+        );
     }
 
     #[test]
-    fn test_crashpad_info_annotations() {
-        let module = ModuleCrashpadInfo::new(42, Endian::Little)
-            .add_list_annotation("annotation")
-            .add_simple_annotation("simple", "module")
-            .add_annotation_object("string", AnnotationValue::String("value".to_owned()))
-            .add_annotation_object("invalid", AnnotationValue::Invalid)
-            .add_annotation_object("custom", AnnotationValue::Custom(0x8001, vec![42]));
-
-        let crashpad_info = CrashpadInfo::new(Endian::Little)
-            .add_module(module)
-            .add_simple_annotation("simple", "info");
-
-        let dump = SynthMinidump::with_endian(Endian::Little).add_crashpad_info(crashpad_info);
-        let dump = read_synth_dump(dump).unwrap();
-
-        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
-        let module = &crashpad_info.module_list[0];
-
-        assert_eq!(crashpad_info.simple_annotations["simple"], "info");
-        assert_eq!(module.module_index, 42);
-        assert_eq!(module.list_annotations, vec!["annotation".to_owned()]);
-        assert_eq!(module.simple_annotations["simple"], "module");
+    fn test_write_user_streams() {
+        const CUSTOM_STREAM_TYPE: u32 = 0x4d7a_0001;
+        let mut writer = MinidumpWriter::new(BE);
+        writer
+            .add_user_stream(CUSTOM_STREAM_TYPE, b"custom data")
+            .unwrap();
+        writer.add_user_stream(0x10000, &[]).unwrap();
         assert_eq!(
-            module.annotation_objects["string"],
-            MinidumpAnnotation::String("value".to_owned())
+            writer.add_user_stream(CUSTOM_STREAM_TYPE, b"again"),
+            Err(Error::DuplicateStreamType {
+                stream_type: CUSTOM_STREAM_TYPE
+            })
         );
         assert_eq!(
-            module.annotation_objects["invalid"],
-            MinidumpAnnotation::Invalid
+            writer.add_user_stream(MINIDUMP_STREAM_TYPE::LastReservedStream as u32, b"nope"),
+            Err(Error::ReservedStreamType {
+                stream_type: 0xffff
+            })
         );
+        assert_eq!(
+            writer.add_user_stream(0x8000, b"nope"),
+            Err(Error::ReservedStreamType {
+                stream_type: 0x8000
+            })
+        );
+
+        let mut bytes = Vec::new();
+        writer.finish(0, 0, &mut bytes).unwrap();
+        let dump = Minidump::read(bytes).unwrap();
+        assert_eq!(dump.endian, BE);
+        assert_eq!(dump.all_streams().count(), 2);
+        assert_eq!(
+            dump.get_raw_stream(CUSTOM_STREAM_TYPE).unwrap(),
+            b"custom data"
+        );
+        assert_eq!(dump.get_raw_stream(0x10000u32).unwrap(), &[]);
     }
 
     #[test]
-    fn test_exception_x86() {
-        // Defaults to x86
-        let system_info = SystemInfo::new(Endian::Little);
-
-        let mut exception = Exception::new(Endian::Little);
-
-        // Check that we clear the erroneous high bits for 32-bit
-        exception.exception_record.exception_address = 0xf0e1_d2c3_b4a5_9687;
-        // FIXME: test other fields too
-
+    fn test_write_drops_unwritable_streams() {
+        let crashpad_info = CrashpadInfo::new(Endian::Little);
         let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_system_info(system_info)
-            .add_exception(exception);
-
+            .add_crashpad_info(crashpad_info)
+            .add_system_info(SystemInfo::new(Endian::Little));
         let dump = read_synth_dump(dump).unwrap();
 
-        let system_stream = dump.get_stream::<MinidumpSystemInfo>().unwrap();
-        let exception_stream = dump.get_stream::<MinidumpException>().unwrap();
+        let mut bytes = Vec::new();
+        dump.write(&mut bytes).unwrap();
+        let dump = Minidump::read(bytes).unwrap();
         assert_eq!(
-            exception_stream.get_crash_address(system_stream.os, system_stream.cpu),
-            0xb4a5_9687
+            dump.get_stream::<MinidumpCrashpadInfo>().unwrap_err(),
+            Error::StreamNotFound
         );
+        assert!(dump.get_stream::<MinidumpSystemInfo>().is_ok());
     }
 
-    #[test]
-    fn test_exception_x64() {
-        // Defaults to x86
-        let system_info = SystemInfo::new(Endian::Little)
-            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16);
-
-        let mut exception = Exception::new(Endian::Little);
+    fn scrub_test_minidump() -> SynthMinidump {
+        let context = synth_minidump::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0xcc, 0x100),
+            0x1000,
+        );
+        let heap = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"hunter2"),
+            0x5000,
+        );
+        // A full-memory region that contains the stack.
+        let full_memory = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0xdd, 0x300),
+            0xf00,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_memory(heap)
+            .add_memory64(full_memory)
+            .set_linux_environ(b"PASSWORD=hunter2\0")
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+                section: Section::with_endian(Endian::Little)
+                    .append_bytes(b"/bin/app\0--token=secret\0-v\0"),
+            })
+    }
 
-        // Check that we don't truncate this on 64-bit
-        exception.exception_record.exception_address = 0xf0e1_d2c3_b4a5_9687;
-        // FIXME: test other fields too
+    #[test]
+    fn test_scrubber_drops_streams() {
+        let mut dump = read_synth_dump(scrub_test_minidump()).unwrap();
+        MinidumpScrubber::new(ScrubPolicy::default())
+            .apply(&mut dump)
+            .unwrap();
+        assert_eq!(
+            dump.get_stream::<MinidumpLinuxEnviron>().unwrap_err(),
+            Error::StreamNotFound
+        );
+        assert_eq!(
+            dump.get_raw_stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine),
+            None
+        );
+        // Memory wasn't touched.
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        assert_eq!(memory.memory_at_address(0x5000).unwrap().bytes, b"hunter2");
 
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_system_info(system_info)
-            .add_exception(exception);
+        // Writing the dump leaves the streams out entirely.
+        let mut bytes = Vec::new();
+        dump.write(&mut bytes).unwrap();
+        let dump = Minidump::read(bytes).unwrap();
+        assert!(dump.get_stream::<MinidumpLinuxEnviron>().is_err());
+        assert!(dump.get_stream::<MinidumpThreadList>().is_ok());
+    }
 
-        let dump = read_synth_dump(dump).unwrap();
+    #[test]
+    fn test_scrubber_memory_and_cmdline() {
+        let mut dump = read_synth_dump(scrub_test_minidump()).unwrap();
+        let mut policy = ScrubPolicy {
+            drop_cmdline: false,
+            zero_non_stack_memory: true,
+            ..ScrubPolicy::default()
+        };
+        policy.cmdline_patterns.push(Box::new(|arg| {
+            arg.find("--token=")
+                .map(|start| start + "--token=".len()..arg.len())
+                .into_iter()
+                .collect()
+        }));
+        MinidumpScrubber::new(policy).apply(&mut dump).unwrap();
 
-        let system_stream = dump.get_stream::<MinidumpSystemInfo>().unwrap();
-        let exception_stream = dump.get_stream::<MinidumpException>().unwrap();
         assert_eq!(
-            exception_stream.get_crash_address(system_stream.os, system_stream.cpu),
-            0xf0e1_d2c3_b4a5_9687
+            dump.get_raw_stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine)
+                .unwrap(),
+            b"/bin/app\0--token=******\0-v\0"
         );
+
+        // The heap is zeroed, the stack isn't.
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        assert_eq!(memory.memory_at_address(0x5000).unwrap().bytes, &[0; 7]);
+        assert!(memory
+            .memory_at_address(0x1000)
+            .unwrap()
+            .bytes
+            .iter()
+            .all(|&b| b == 0xcc));
+
+        // Only the part of the full-memory region that's the stack survives.
+        let memory64 = dump.get_stream::<MinidumpMemory64List>().unwrap();
+        let bytes = memory64.memory_at_address(0xf00).unwrap().bytes;
+        assert!(bytes[..0x100].iter().all(|&b| b == 0));
+        assert!(bytes[0x100..0x200].iter().all(|&b| b == 0xdd));
+        assert!(bytes[0x200..].iter().all(|&b| b == 0));
     }
 
     #[test]
-    fn test_fuzzed_oom() {
-        // https://github.com/luser/rust-minidump/issues/381
-        let data = b"MDMP\x93\xa7\x00\x00\x00\xffffdYfffff@\n\nfp\n\xbb\xff\xff\xff\n\xff\n";
-        assert!(Minidump::read(data.as_ref()).is_err());
+    fn test_scrubber_keep_memory() {
+        let mut dump = read_synth_dump(scrub_test_minidump()).unwrap();
+        let policy = ScrubPolicy {
+            keep_memory: Some(vec![0x1080..0x10c0, 0x10c0..0x1100]),
+            ..ScrubPolicy::default()
+        };
+        MinidumpScrubber::new(policy).apply(&mut dump).unwrap();
+
+        // Only the kept part of the stack survives, in the thread list and memory lists.
+        let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        for stack in [
+            threads.threads[0].direct_stack().unwrap().bytes,
+            memory.memory_at_address(0x1000).unwrap().bytes,
+        ] {
+            assert!(stack[..0x80].iter().all(|&b| b == 0));
+            assert!(stack[0x80..].iter().all(|&b| b == 0xcc));
+        }
+        assert_eq!(memory.memory_at_address(0x5000).unwrap().bytes, &[0; 7]);
 
-        // https://github.com/getsentry/symbolic/issues/478
-        let data = b"MDMP\x93\xa7\x00\x00\r\x00\x00\x00 \xff\xff\xff\xff\xff\xff\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
-        assert!(Minidump::read(data.as_ref()).is_err());
+        let memory64 = dump.get_stream::<MinidumpMemory64List>().unwrap();
+        let bytes = memory64.memory_at_address(0xf00).unwrap().bytes;
+        assert!(bytes[..0x180].iter().all(|&b| b == 0));
+        assert!(bytes[0x180..0x200].iter().all(|&b| b == 0xdd));
+        assert!(bytes[0x200..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_scrubber_environ_deny_list() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .set_linux_environ(b"HOME=/home/me\0AWS_SECRET_ACCESS_KEY=abc\0LANG=C");
+        let mut dump = read_synth_dump(dump).unwrap();
+        let policy = ScrubPolicy {
+            drop_environ: false,
+            environ_deny_list: vec!["HOME".into(), "AWS_*".into()],
+            ..ScrubPolicy::default()
+        };
+        MinidumpScrubber::new(policy).apply(&mut dump).unwrap();
+
+        let raw = dump
+            .get_raw_stream(MINIDUMP_STREAM_TYPE::LinuxEnviron)
+            .unwrap();
+        assert_eq!(raw.len(), 46);
+        assert_eq!(&raw[..7], b"LANG=C\0");
+        assert!(raw[7..].iter().all(|&b| b == 0));
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+        assert_eq!(environ.vars().len(), 1);
+        assert_eq!(environ.get("LANG").as_deref(), Some("C"));
     }
 }
@@ -84,7 +84,7 @@ impl fmt::Display for Os {
 ///
 /// This is a slightly nicer layer over the `ProcessorArchitecture` enum defined in
 /// the minidump-common crate.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Cpu {
     X86,
     X86_64,
@@ -11,6 +11,11 @@ use num_traits::cast::FromPrimitive;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
+use synth_minidump::{
+    CrashpadInfo, DumpString, Exception, Memory, MiscStream, Module as SynthModule, SimpleStream,
+    SynthMinidump, SystemInfo, Thread, ThreadName, STOCK_VERSION_INFO,
+};
+use test_assembler::{Endian, Section};
 
 fn get_test_minidump_path(filename: &str) -> PathBuf {
     let mut path = PathBuf::from(file!());
@@ -43,6 +48,157 @@ fn test_minidump_read() {
     let _dump = Minidump::read(buf).unwrap();
 }
 
+#[test]
+fn test_minidump_read_mmap_borrows() {
+    // Everything read from a mapped dump should point into the mapping,
+    // rather than being copied out of it.
+    let path = get_test_minidump_path("test.dmp");
+    let f = File::open(path).unwrap();
+    let mmap = unsafe { Mmap::map(&f).unwrap() };
+    let mapping = mmap.as_ptr_range();
+    let in_mapping = |bytes: &[u8]| {
+        let range = bytes.as_ptr_range();
+        mapping.start <= range.start && range.end <= mapping.end
+    };
+    let dump = Minidump::read(mmap).unwrap();
+
+    for (_, bytes) in dump.raw_streams() {
+        assert!(in_mapping(bytes));
+    }
+
+    let memory_list = dump.get_stream::<MinidumpMemoryList>().unwrap();
+    assert!(memory_list.iter().count() > 0);
+    for memory in memory_list.iter() {
+        assert!(in_mapping(memory.bytes));
+    }
+
+    let memory = dump.memory().unwrap();
+    let thread_list = dump.get_stream::<MinidumpThreadList>().unwrap();
+    for thread in &thread_list.threads {
+        let stack = thread.stack_memory(&memory).unwrap();
+        assert!(in_mapping(stack.bytes));
+    }
+}
+
+#[test]
+fn test_streaming_minidump_open() {
+    let path = get_test_minidump_path("test.dmp");
+    let streaming = StreamingMinidump::open(&path).unwrap();
+    let dump = read_test_minidump().unwrap();
+    assert_eq!(streaming.endian, dump.endian);
+    assert_eq!(streaming.header.stream_count, dump.header.stream_count);
+
+    let modules = streaming.get_stream::<MinidumpModuleList>().unwrap();
+    let expected_modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+    let names = modules.iter().map(|m| m.code_file()).collect::<Vec<_>>();
+    let expected_names = expected_modules
+        .iter()
+        .map(|m| m.code_file())
+        .collect::<Vec<_>>();
+    assert_eq!(names, expected_names);
+
+    assert_eq!(
+        streaming
+            .get_raw_stream(md::MINIDUMP_STREAM_TYPE::SystemInfoStream)
+            .unwrap(),
+        dump.get_raw_stream(md::MINIDUMP_STREAM_TYPE::SystemInfoStream)
+            .unwrap()
+    );
+
+    // This dump has no Memory64List, so everything gets loaded.
+    let loaded = streaming.to_minidump().unwrap();
+    let memory = loaded.memory().unwrap();
+    let expected_memory = dump.memory().unwrap();
+    let threads = loaded.get_stream::<MinidumpThreadList>().unwrap();
+    let expected_threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+    assert_eq!(threads.threads.len(), expected_threads.threads.len());
+    for (thread, expected) in threads.threads.iter().zip(&expected_threads.threads) {
+        assert_eq!(
+            thread.stack_memory(&memory).unwrap().bytes,
+            expected.stack_memory(&expected_memory).unwrap().bytes
+        );
+    }
+}
+
+#[test]
+fn test_minidump_write_round_trip() {
+    let dump = read_test_minidump().unwrap();
+    let mut bytes = Vec::new();
+    dump.write(&mut bytes).unwrap();
+    let written = Minidump::read(bytes).unwrap();
+    // The original has some padding in its stream directory.
+    assert_eq!(written.header.stream_count, 7);
+    assert_eq!(written.header.time_date_stamp, dump.header.time_date_stamp);
+
+    let modules = written.get_stream::<MinidumpModuleList>().unwrap();
+    let expected_modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+    let ids = |modules: &MinidumpModuleList| {
+        modules
+            .iter()
+            .map(|m| {
+                (
+                    m.code_file().to_string(),
+                    m.debug_file().map(|f| f.to_string()),
+                    m.debug_identifier().map(|id| id.to_string()),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&modules), ids(&expected_modules));
+
+    let memory = written.get_stream::<MinidumpMemoryList>().unwrap();
+    let expected_memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+    let regions = memory
+        .iter()
+        .map(|m| (m.base_address, m.bytes))
+        .collect::<Vec<_>>();
+    let expected_regions = expected_memory
+        .iter()
+        .map(|m| (m.base_address, m.bytes))
+        .collect::<Vec<_>>();
+    assert_eq!(regions, expected_regions);
+
+    let system_info = written.get_stream::<MinidumpSystemInfo>().unwrap();
+    let expected_system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+    assert_eq!(
+        system_info.csd_version(),
+        expected_system_info.csd_version()
+    );
+
+    let written_memory = written.memory().unwrap();
+    let expected_unified = dump.memory().unwrap();
+    let threads = written.get_stream::<MinidumpThreadList>().unwrap();
+    let expected_threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+    assert_eq!(threads.threads.len(), expected_threads.threads.len());
+    for (thread, expected) in threads.threads.iter().zip(&expected_threads.threads) {
+        assert_eq!(thread.raw.thread_id, expected.raw.thread_id);
+        let context = thread.context(&system_info, None).unwrap();
+        let expected_context = expected.context(&system_info, None).unwrap();
+        assert_eq!(
+            context.get_instruction_pointer(),
+            expected_context.get_instruction_pointer()
+        );
+        assert_eq!(
+            thread.stack_memory(&written_memory).map(|m| m.bytes),
+            expected.stack_memory(&expected_unified).map(|m| m.bytes)
+        );
+    }
+
+    let exception = written.get_stream::<MinidumpException>().unwrap();
+    let expected_exception = dump.get_stream::<MinidumpException>().unwrap();
+    assert_eq!(exception.thread_id, expected_exception.thread_id);
+    assert!(exception.context(&system_info, None).is_some());
+
+    // Self-contained streams are copied as-is.
+    assert_eq!(
+        written
+            .get_raw_stream(md::MINIDUMP_STREAM_TYPE::MiscInfoStream)
+            .unwrap(),
+        dump.get_raw_stream(md::MINIDUMP_STREAM_TYPE::MiscInfoStream)
+            .unwrap()
+    );
+}
+
 #[test]
 fn test_module_list() {
     let dump = read_test_minidump().unwrap();
@@ -128,6 +284,11 @@ fn test_system_info() {
         system_info.cpu_info().unwrap(),
         "GenuineIntel family 6 model 13 stepping 8"
     );
+    assert_eq!(system_info.cpu_vendor().as_deref(), Some("GenuineIntel"));
+    assert_eq!(system_info.cpu_family_model_stepping(), Some((6, 13, 8)));
+    let features = system_info.cpu_features();
+    assert!(features.contains(&"sse2"));
+    assert!(!features.contains(&"ht"));
     assert_eq!(&system_info.csd_version().unwrap(), "Service Pack 2");
 }
 
@@ -172,6 +333,10 @@ fn test_crashpad_info() {
         data4: [0xBB, 0xF7, 0xB, 0xCF, 0x50, 0x1B, 0xAD, 0x7E],
     };
     assert_eq!(crashpad_info.raw.client_id, client_id);
+    assert_eq!(
+        crashpad_info.report_id().unwrap().to_string(),
+        "42f9de72-518a-43dd-97d7-8ddc328d3662"
+    );
 
     assert_eq!(crashpad_info.simple_annotations["hello"], "world");
     assert_eq!(crashpad_info.module_list.len(), 2);
@@ -218,6 +383,7 @@ fn test_exception() {
         if let MinidumpContext {
             raw: MinidumpRawContext::X86(ref raw),
             ref valid,
+            ..
         } = *ctx
         {
             assert_eq!(raw.eip, 0x40429e);
@@ -236,9 +402,7 @@ fn test_thread_list() {
     let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
     let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
     let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
-    let memory_list = dump
-        .get_stream::<MinidumpMemoryList<'_>>()
-        .unwrap_or_default();
+    let memory = dump.memory().unwrap_or_default();
 
     let threads = &thread_list.threads;
     assert_eq!(threads.len(), 2);
@@ -255,6 +419,7 @@ fn test_thread_list() {
         if let MinidumpContext {
             raw: MinidumpRawContext::X86(ref raw),
             ref valid,
+            ..
         } = *ctx
         {
             assert_eq!(raw.eip, 0x7c90eb94);
@@ -265,7 +430,7 @@ fn test_thread_list() {
     } else {
         panic!("Missing context");
     }
-    if let Some(ref stack) = threads[0].stack_memory(&memory_list) {
+    if let Some(ref stack) = threads[0].stack_memory(&memory) {
         // Try the beginning
         assert_eq!(stack.get_memory_at_address::<u8>(0x12f31c).unwrap(), 0);
         assert_eq!(stack.get_memory_at_address::<u16>(0x12f31c).unwrap(), 0);
@@ -294,3 +459,180 @@ fn test_empty_minidump() {
         Err(e) => assert_eq!(e, Error::MissingHeader),
     }
 }
+
+#[test]
+fn test_truncated_minidump() {
+    let path = get_test_minidump_path("test.dmp");
+    let full = std::fs::read(path).unwrap();
+    assert!(Minidump::read(&full[..]).unwrap().warnings().is_empty());
+    const MEMORY_LIST: u32 = md::MINIDUMP_STREAM_TYPE::MemoryListStream as u32;
+    const BREAKPAD_INFO: u32 = md::MINIDUMP_STREAM_TYPE::BreakpadInfoStream as u32;
+
+    // Cut in the middle of the last memory region, which is also the stack of the second thread.
+    let dump = Minidump::read(&full[..10000]).unwrap();
+    assert_eq!(
+        dump.warnings(),
+        &[MinidumpWarning::TruncatedMemory {
+            index: 2,
+            base_address: 0x97f6e8,
+            expected: 2328,
+            actual: 1011,
+        }]
+    );
+    assert_eq!(
+        dump.warnings()[0].to_string(),
+        "memory region 2 at 0x97f6e8 truncated from 2328 to 1011 bytes"
+    );
+    let memory_list = dump.get_stream::<MinidumpMemoryList>().unwrap();
+    let regions = memory_list.iter().collect::<Vec<_>>();
+    assert_eq!(regions.len(), 3);
+    assert_eq!(regions[2].size, 1011);
+    assert_eq!(regions[2].bytes, &full[8989..10000]);
+    let thread_list = dump.get_stream::<MinidumpThreadList>().unwrap();
+    let memory = dump.memory().unwrap();
+    let stack = thread_list.threads[1].stack_memory(&memory).unwrap();
+    assert_eq!(stack.bytes.len(), 1011);
+
+    // Cut in the middle of the memory list stream itself.
+    let dump = Minidump::read(&full[..5400]).unwrap();
+    assert_eq!(
+        dump.warnings(),
+        &[MinidumpWarning::TruncatedStream {
+            stream_type: MEMORY_LIST,
+            expected: 52,
+            actual: 19,
+        }]
+    );
+    assert!(dump.get_stream::<MinidumpMemoryList>().is_err());
+    assert!(dump.get_stream::<MinidumpThreadList>().is_ok());
+
+    // Cut before the memory list, everything before it is fine.
+    let dump = Minidump::read(&full[..5000]).unwrap();
+    let mut warnings = dump.warnings().to_vec();
+    warnings.sort_by_key(|warning| format!("{:?}", warning));
+    assert_eq!(
+        warnings,
+        &[
+            MinidumpWarning::MissingStream {
+                stream_type: BREAKPAD_INFO,
+            },
+            MinidumpWarning::MissingStream {
+                stream_type: MEMORY_LIST,
+            },
+        ]
+    );
+    assert_eq!(
+        dump.get_stream::<MinidumpMemoryList>().unwrap_err(),
+        Error::StreamNotFound
+    );
+    assert!(dump.get_stream::<MinidumpModuleList>().is_ok());
+    assert!(dump.get_stream::<MinidumpSystemInfo>().is_ok());
+
+    // Cut in the middle of the stream directory.
+    let dump = Minidump::read(&full[..100]).unwrap();
+    assert_eq!(
+        dump.warnings()[0],
+        MinidumpWarning::TruncatedDirectory {
+            expected: 9,
+            read: 5,
+        }
+    );
+    assert!(dump.get_stream::<MinidumpSystemInfo>().is_err());
+
+    // Every cut into the stream directory or past it can be read.
+    for cut in (33..full.len()).step_by(97) {
+        let dump = Minidump::read(&full[..cut]).unwrap();
+        assert!(!dump.warnings().is_empty(), "no warnings at {}", cut);
+        for stream in dump.all_streams() {
+            assert!(stream.location.rva as usize + stream.location.data_size as usize <= cut);
+        }
+        let _ = dump.get_stream::<MinidumpModuleList>();
+        let _ = dump.get_stream::<MinidumpThreadList>();
+        let _ = dump.get_stream::<MinidumpMemoryList>();
+    }
+
+    // Without a header there's nothing to go on.
+    assert_eq!(
+        Minidump::read(&full[..20]).unwrap_err(),
+        Error::MissingHeader
+    );
+}
+
+/// A small synthetic minidump with one of most kinds of stream.
+fn read_print_all_minidump() -> Minidump<'static, Vec<u8>> {
+    let text = |stream_type: md::MINIDUMP_STREAM_TYPE, contents: &[u8]| SimpleStream {
+        stream_type: stream_type as u32,
+        section: Section::with_endian(Endian::Little).append_bytes(contents),
+    };
+    let context = synth_minidump::x86_context(Endian::Little, 0x401234, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D32(0x401100)
+            .D32(0)
+            .D32(0xabcdef01)
+            .D32(0x12345678),
+        0x1010,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let thread_name = DumpString::new("main", Endian::Little);
+    let module_name = DumpString::new("c:\\app\\app.exe", Endian::Little);
+    let module = SynthModule::new(
+        Endian::Little,
+        0x400000,
+        0x10000,
+        &module_name,
+        0x5f3d7c01,
+        0x12345,
+        Some(&STOCK_VERSION_INFO),
+    );
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.process_id = Some(0x4321);
+    let mut exception = Exception::new(Endian::Little);
+    exception.thread_id = 0x1234;
+    exception.exception_record.exception_code = 0xc0000005;
+    exception.exception_record.exception_address = 0x401234;
+    let crashpad_info = CrashpadInfo::new(Endian::Little).add_simple_annotation("channel", "beta");
+    let handles = Section::with_endian(Endian::Little)
+        .D32(16) // size_of_header
+        .D32(32) // size_of_descriptor
+        .D32(0) // number_of_descriptors
+        .D32(0); // reserved
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_stream(misc)
+        .add_thread(thread)
+        .add_thread_name(ThreadName::new(Endian::Little, 0x1234, Some(&thread_name)))
+        .add_module(module)
+        .add_exception(exception)
+        .add_crashpad_info(crashpad_info)
+        .add_stream(SimpleStream {
+            stream_type: md::MINIDUMP_STREAM_TYPE::HandleDataStream as u32,
+            section: handles,
+        })
+        .add_stream(text(
+            md::MINIDUMP_STREAM_TYPE::LinuxCmdLine,
+            b"app\0--flag\0",
+        ))
+        .add_stream(text(
+            md::MINIDUMP_STREAM_TYPE::LinuxProcStatus,
+            b"Name:\tapp\nPid:\t17\n",
+        ))
+        .add_stream(SimpleStream {
+            stream_type: 0x4d7a_1234,
+            section: Section::with_endian(Endian::Little).append_bytes(b"custom stream\x01\x02"),
+        })
+        .add(context)
+        .add(thread_name)
+        .add(module_name)
+        .add_memory(stack);
+    Minidump::read(dump.finish().unwrap()).unwrap()
+}
+
+#[test]
+fn test_print_all() {
+    let dump = read_print_all_minidump();
+    let mut output = Vec::new();
+    dump.print_all(&mut output).unwrap();
+    insta::assert_snapshot!(String::from_utf8(output).unwrap());
+}
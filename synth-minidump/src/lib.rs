@@ -1,14 +1,45 @@
 // Copyright 2016 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
+//! Builders for writing minidumps, originally made for writing test fixtures.
+//!
+//! A [`SynthMinidump`][] collects streams (system info, threads, modules,
+//! memory, an exception, or arbitrary [`SimpleStream`][]s) and the data they
+//! refer to, and lays them out into a minidump with [`SynthMinidump::finish`][].
+//! Files are built with [test_assembler][], so locations can refer to data
+//! that's added later.
+//!
+//! ```
+//! use synth_minidump::*;
+//! use test_assembler::*;
+//!
+//! let context = x86_context(Endian::Little, 0xabcd1234, 0x1010);
+//! let stack = Memory::with_section(
+//!     Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+//!     0x1000,
+//! );
+//! let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+//! let contents = SynthMinidump::with_endian(Endian::Little)
+//!     .add_system_info(SystemInfo::new(Endian::Little))
+//!     .add_thread(thread)
+//!     .add(context)
+//!     .add_memory(stack)
+//!     .finish_validated()
+//!     .unwrap();
+//! # assert_eq!(&contents[..4], b"MDMP");
+//! ```
+//!
+//! [test_assembler]: https://docs.rs/test-assembler
+
 // Some test_assembler types do not have Debug, so be a bit more lenient here.
 #![allow(missing_debug_implementations)]
 
-use encoding::all::UTF_16LE;
+use encoding::all::{UTF_16BE, UTF_16LE};
 use encoding::{EncoderTrap, Encoding};
 use minidump_common::format as md;
 use scroll::ctx::SizeWith;
-use scroll::LE;
+use scroll::{Pread, LE};
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
 use test_assembler::*;
@@ -39,12 +70,18 @@ pub struct SynthMinidump {
     thread_list: Option<ListStream<Thread>>,
     /// List of thread names in this minidump.
     thread_names_list: Option<ListStream<ThreadName>>,
+    /// List of extra info about threads in this minidump.
+    thread_info_list: Option<ExListStream<ThreadInfo>>,
     /// List of memory regions in this minidump.
     memory_list: Option<ListStream<Section>>,
+    /// List of memory regions for the full-memory list in this minidump.
+    memory64_list: Vec<Memory>,
     /// List of extra info about memory regions in this minidump.
     memory_info_list: Option<ExListStream<MemoryInfo>>,
     /// Crashpad extension containing annotations.
     crashpad_info: Option<CrashpadInfo>,
+    /// Dynamically registered function tables.
+    function_tables: Vec<FunctionTable>,
     /// /proc/self/maps string
     linux_maps: Option<SimpleStream>,
     /// /etc/lsb-release string
@@ -190,10 +227,16 @@ impl SynthMinidump {
                 md::MINIDUMP_STREAM_TYPE::ThreadNamesStream,
                 endian,
             )),
+            thread_info_list: Some(ExListStream::new(
+                md::MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
+                mem::size_of::<md::MINIDUMP_THREAD_INFO>(),
+                endian,
+            )),
             memory_list: Some(ListStream::new(
                 md::MINIDUMP_STREAM_TYPE::MemoryListStream,
                 endian,
             )),
+            memory64_list: vec![],
             memory_info_list: Some(ExListStream::new(
                 md::MINIDUMP_STREAM_TYPE::MemoryInfoListStream,
                 mem::size_of::<md::MINIDUMP_MEMORY_INFO>(),
@@ -202,6 +245,7 @@ impl SynthMinidump {
             linux_maps: None,
             linux_lsb_release: None,
             linux_environ: None,
+            function_tables: vec![],
             linux_cpu_info: None,
             linux_proc_status: None,
             crashpad_info: None,
@@ -257,6 +301,15 @@ impl SynthMinidump {
         self.add(memory)
     }
 
+    /// Add `memory` to the full-memory list stream (`Memory64ListStream`).
+    ///
+    /// The regions are all stored back to back at the end of the stream when
+    /// the minidump is finished, as that format requires.
+    pub fn add_memory64(mut self, memory: Memory) -> SynthMinidump {
+        self.memory64_list.push(memory);
+        self
+    }
+
     /// Add `info` to `self`, adding it to the memory info list stream as well.
     pub fn add_memory_info(mut self, info: MemoryInfo) -> SynthMinidump {
         self.memory_info_list = self
@@ -284,12 +337,27 @@ impl SynthMinidump {
         self
     }
 
+    /// Add `thread_info` to `self`, adding it to the thread info list stream as well.
+    pub fn add_thread_info(mut self, thread_info: ThreadInfo) -> SynthMinidump {
+        self.thread_info_list = self
+            .thread_info_list
+            .take()
+            .map(|thread_info_list| thread_info_list.add(thread_info));
+        self
+    }
+
     /// Add crashpad module and annotation extension information.
     pub fn add_crashpad_info(mut self, crashpad_info: CrashpadInfo) -> Self {
         self.crashpad_info = Some(crashpad_info);
         self
     }
 
+    /// Add a dynamically registered function table.
+    pub fn add_function_table(mut self, function_table: FunctionTable) -> SynthMinidump {
+        self.function_tables.push(function_table);
+        self
+    }
+
     /// Set the SystemInfo stream.
     pub fn add_system_info(mut self, system_info: SystemInfo) -> Self {
         self.system_info = Some(system_info);
@@ -348,9 +416,13 @@ impl SynthMinidump {
     }
 
     /// Append `stream` to `self`, setting its location appropriately and adding it to the stream directory.
+    /// Append `stream` to `self`, and add it to the stream directory.
+    ///
+    /// Streams are 4-byte aligned, like in minidumps written by Windows.
     pub fn add_stream<T: Stream>(mut self, stream: T) -> SynthMinidump {
         self.stream_directory = stream.cite_stream_in(self.stream_directory);
         self.stream_count += 1;
+        self.section = self.section.align(4);
         self.add(stream)
     }
 
@@ -400,6 +472,9 @@ impl SynthMinidump {
         // Add thread names stream if any names were added.
         let thread_names = self.thread_names_list.take();
         self = self.finish_list(thread_names);
+        // Add thread info list stream if any thread infos were added.
+        let thread_infos = self.thread_info_list.take();
+        self = self.finish_ex_list(thread_infos);
         // Add crashpad info stream if any.
         if let Some(crashpad_info) = self.crashpad_info.take() {
             self = self.add_stream(crashpad_info);
@@ -425,6 +500,44 @@ impl SynthMinidump {
         if let Some(stream) = self.linux_environ.take() {
             self = self.add_stream(stream);
         }
+        // Add function table stream if any tables were added.
+        let function_tables = mem::take(&mut self.function_tables);
+        if !function_tables.is_empty() {
+            let mut stream = Section::with_endian(self.section.endian)
+                .D32(mem::size_of::<md::MINIDUMP_FUNCTION_TABLE_STREAM>() as u32)
+                .D32(mem::size_of::<md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR>() as u32)
+                .D32(FunctionTable::NATIVE_DESCRIPTOR_SIZE as u32)
+                .D32(mem::size_of::<md::IMAGE_RUNTIME_FUNCTION_ENTRY>() as u32)
+                .D32(function_tables.len() as u32)
+                .D32(0); // size_of_align_pad
+            for table in function_tables {
+                stream = stream.append_section(table.section);
+            }
+            self = self.add_stream(SimpleStream {
+                stream_type: md::MINIDUMP_STREAM_TYPE::FunctionTable as u32,
+                section: stream,
+            });
+        }
+        // Add memory64 list stream (and the memory itself) if any regions were added.
+        // Like Windows does, put the memory after everything else.
+        let memories64 = mem::take(&mut self.memory64_list);
+        if !memories64.is_empty() {
+            let base_rva = Label::new();
+            let mut stream = Section::with_endian(self.section.endian)
+                .D64(memories64.len() as u64)
+                .D64(&base_rva);
+            for memory in &memories64 {
+                stream = stream.D64(memory.address).D64(memory.file_size());
+            }
+            self = self.add_stream(SimpleStream {
+                stream_type: md::MINIDUMP_STREAM_TYPE::Memory64ListStream as u32,
+                section: stream,
+            });
+            self.section = self.section.mark(&base_rva);
+            for memory in memories64 {
+                self = self.add(memory);
+            }
+        }
 
         let SynthMinidump {
             section,
@@ -441,10 +554,203 @@ impl SynthMinidump {
         // Create the stream directory.
         stream_count_label.set_const(stream_count as u64);
         section
+            .align(4)
             .mark(&stream_directory_rva)
             .append_section(stream_directory)
             .get_contents()
     }
+
+    /// Like [`SynthMinidump::finish`][], but also check that the result is a
+    /// well-formed minidump.
+    ///
+    /// [`SynthMinidump::finish`][] will happily produce broken minidumps (which is
+    /// useful for testing how they're handled). This instead makes sure that the
+    /// stream directory and streams are 4-byte aligned, and that every location
+    /// in the stream directory, module list, thread list, memory list and
+    /// exception stream is inside the minidump.
+    pub fn finish_validated(self) -> Result<Vec<u8>, ValidationError> {
+        let endian = match self.section.endian {
+            Endian::Little => scroll::LE,
+            Endian::Big => scroll::BE,
+        };
+        let contents = self.finish().ok_or(ValidationError::Incomplete)?;
+        Validator {
+            contents: &contents,
+            endian,
+        }
+        .validate()?;
+        Ok(contents)
+    }
+}
+
+/// Problems found by [`SynthMinidump::finish_validated`][].
+#[derive(Debug, PartialEq)]
+pub enum ValidationError {
+    /// Some of the contents depend on a `Label` that never got a value.
+    Incomplete,
+    /// The stream directory isn't 4-byte aligned.
+    MisalignedDirectory { rva: u32 },
+    /// A stream isn't 4-byte aligned.
+    MisalignedStream { stream_type: u32, rva: u32 },
+    /// Something refers to data past the end of the minidump.
+    OutOfBounds { what: String, rva: u32, size: u64 },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::Incomplete => {
+                write!(f, "Minidump contents depend on an undefined label")
+            }
+            ValidationError::MisalignedDirectory { rva } => {
+                write!(f, "Stream directory at {:#x} isn't 4-byte aligned", rva)
+            }
+            ValidationError::MisalignedStream { stream_type, rva } => write!(
+                f,
+                "Stream {:#x} at {:#x} isn't 4-byte aligned",
+                stream_type, rva
+            ),
+            ValidationError::OutOfBounds { what, rva, size } => write!(
+                f,
+                "{} at {:#x} ({} bytes) is outside the minidump",
+                what, rva, size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+struct Validator<'a> {
+    contents: &'a [u8],
+    endian: scroll::Endian,
+}
+
+impl<'a> Validator<'a> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let header: md::MINIDUMP_HEADER = self.read("header", 0)?;
+        let rva = header.stream_directory_rva;
+        if rva & 3 != 0 {
+            return Err(ValidationError::MisalignedDirectory { rva });
+        }
+        let entry_size = md::MINIDUMP_DIRECTORY::size_with(&self.endian) as u64;
+        self.check(
+            "stream directory",
+            rva,
+            u64::from(header.stream_count) * entry_size,
+        )?;
+
+        for i in 0..header.stream_count as usize {
+            let entry: md::MINIDUMP_DIRECTORY =
+                self.read("stream directory", rva as usize + i * entry_size as usize)?;
+            let stream_type = entry.stream_type;
+            let location = entry.location;
+            if location.rva & 3 != 0 {
+                return Err(ValidationError::MisalignedStream {
+                    stream_type,
+                    rva: location.rva,
+                });
+            }
+            self.check_location(&format!("stream {:#x}", stream_type), &location)?;
+
+            use md::MINIDUMP_STREAM_TYPE::*;
+            if stream_type == ModuleListStream as u32 {
+                for module in self.read_list::<md::MINIDUMP_MODULE>("module list", &location)? {
+                    self.check_string("module name", module.module_name_rva)?;
+                    self.check_location("module CodeView record", &module.cv_record)?;
+                    self.check_location("module misc record", &module.misc_record)?;
+                }
+            } else if stream_type == ThreadListStream as u32 {
+                for thread in self.read_list::<md::MINIDUMP_THREAD>("thread list", &location)? {
+                    self.check_location("thread stack", &thread.stack.memory)?;
+                    self.check_location("thread context", &thread.thread_context)?;
+                }
+            } else if stream_type == MemoryListStream as u32 {
+                for memory in
+                    self.read_list::<md::MINIDUMP_MEMORY_DESCRIPTOR>("memory list", &location)?
+                {
+                    self.check_location("memory", &memory.memory)?;
+                }
+            } else if stream_type == ExceptionStream as u32 {
+                let exception: md::MINIDUMP_EXCEPTION_STREAM =
+                    self.read("exception", location.rva as usize)?;
+                self.check_location("exception context", &exception.thread_context)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the entries of a list stream (or its header, for lists with an
+    /// unknown number of entries).
+    fn read_list<T>(
+        &self,
+        what: &str,
+        location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+    ) -> Result<Vec<T>, ValidationError>
+    where
+        T: SizeWith<scroll::Endian>
+            + scroll::ctx::TryFromCtx<'a, scroll::Endian, Error = scroll::Error>,
+    {
+        let count: u32 = self.read(what, location.rva as usize)?;
+        let entry_size = T::size_with(&self.endian) as u64;
+        let entries_size = u64::from(count) * entry_size;
+        // Some writers pad the count to 8 bytes.
+        let mut offset = location.rva as u64 + 4;
+        if u64::from(location.data_size) == entries_size + 8 {
+            offset += 4;
+        }
+        self.check(
+            what,
+            location.rva,
+            offset - location.rva as u64 + entries_size,
+        )?;
+        (0..u64::from(count))
+            .map(|i| self.read(what, (offset + i * entry_size) as usize))
+            .collect()
+    }
+
+    fn read<T>(&self, what: &str, offset: usize) -> Result<T, ValidationError>
+    where
+        T: SizeWith<scroll::Endian>
+            + scroll::ctx::TryFromCtx<'a, scroll::Endian, Error = scroll::Error>,
+    {
+        self.contents
+            .pread_with(offset, self.endian)
+            .map_err(|_| ValidationError::OutOfBounds {
+                what: what.to_string(),
+                rva: offset as u32,
+                size: T::size_with(&self.endian) as u64,
+            })
+    }
+
+    /// Check a `MINIDUMP_STRING` (a u32 length followed by that many bytes).
+    fn check_string(&self, what: &str, rva: u32) -> Result<(), ValidationError> {
+        let len: u32 = self.read(what, rva as usize)?;
+        self.check(what, rva, 4 + u64::from(len))
+    }
+
+    fn check_location(
+        &self,
+        what: &str,
+        location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+    ) -> Result<(), ValidationError> {
+        // An empty location is allowed to point anywhere (often it's just 0).
+        if location.data_size == 0 {
+            return Ok(());
+        }
+        self.check(what, location.rva, u64::from(location.data_size))
+    }
+
+    fn check(&self, what: &str, rva: u32, size: u64) -> Result<(), ValidationError> {
+        if u64::from(rva) + size > self.contents.len() as u64 {
+            return Err(ValidationError::OutOfBounds {
+                what: what.to_string(),
+                rva,
+                size,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Default for SynthMinidump {
@@ -763,7 +1069,11 @@ pub struct DumpString {
 impl DumpString {
     /// Create a new `DumpString` with `s` as its contents, using `endian` endianness.
     pub fn new(s: &str, endian: Endian) -> DumpString {
-        let u16_s = UTF_16LE.encode(s, EncoderTrap::Strict).unwrap();
+        let u16_s = match endian {
+            Endian::Little => UTF_16LE.encode(s, EncoderTrap::Strict),
+            Endian::Big => UTF_16BE.encode(s, EncoderTrap::Strict),
+        }
+        .unwrap();
         let section = Section::with_endian(endian)
             .D32(u16_s.len() as u32)
             .append_bytes(&u16_s);
@@ -1049,6 +1359,90 @@ impl From<MemoryInfo> for Section {
     }
 }
 
+/// Extra information about a thread, for the thread info list stream.
+pub struct ThreadInfo {
+    section: Section,
+}
+
+impl ThreadInfo {
+    /// Create a `ThreadInfo` with the given flags, start address, and CPU times.
+    ///
+    /// The remaining fields are zeroed.
+    pub fn new(
+        endian: Endian,
+        thread_id: u32,
+        dump_flags: u32,
+        kernel_time: u64,
+        user_time: u64,
+        start_address: u64,
+    ) -> ThreadInfo {
+        let section = Section::with_endian(endian)
+            .D32(thread_id)
+            .D32(dump_flags)
+            .D32(0) // dump_error
+            .D32(0) // exit_status
+            .D64(0) // create_time
+            .D64(0) // exit_time
+            .D64(kernel_time)
+            .D64(user_time)
+            .D64(start_address)
+            .D64(0); // affinity
+        ThreadInfo { section }
+    }
+}
+
+impl_dumpsection!(ThreadInfo);
+
+impl From<ThreadInfo> for Section {
+    fn from(info: ThreadInfo) -> Self {
+        info.section
+    }
+}
+
+/// A function table registered at runtime, for the FunctionTableStream.
+pub struct FunctionTable {
+    section: Section,
+}
+
+impl FunctionTable {
+    /// The size of the native description of the table, which we just fill with zeroes.
+    ///
+    /// This deliberately isn't a multiple of 8, so tables need padding.
+    pub const NATIVE_DESCRIPTOR_SIZE: usize = 20;
+
+    /// Create a `FunctionTable` covering `minimum_address..maximum_address` from a
+    /// list of `(begin_address, end_address, unwind_info_address)` entries, which are
+    /// relative to `base_address`.
+    pub fn new(
+        endian: Endian,
+        minimum_address: u64,
+        maximum_address: u64,
+        base_address: u64,
+        entries: &[(u32, u32, u32)],
+    ) -> FunctionTable {
+        let size = mem::size_of::<md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR>()
+            + FunctionTable::NATIVE_DESCRIPTOR_SIZE
+            + entries.len() * mem::size_of::<md::IMAGE_RUNTIME_FUNCTION_ENTRY>();
+        let size_of_align_pad = (8 - size % 8) % 8;
+        let mut section = Section::with_endian(endian)
+            .D64(minimum_address)
+            .D64(maximum_address)
+            .D64(base_address)
+            .D32(entries.len() as u32)
+            .D32(size_of_align_pad as u32)
+            .append_repeated(0, FunctionTable::NATIVE_DESCRIPTOR_SIZE);
+        for &(begin_address, end_address, unwind_info_address) in entries {
+            section = section
+                .D32(begin_address)
+                .D32(end_address)
+                .D32(unwind_info_address);
+        }
+        FunctionTable {
+            section: section.append_repeated(0, size_of_align_pad),
+        }
+    }
+}
+
 /// MINIDUMP_MISC_INFO stream.
 ///
 /// Fields that must be initialized together (i.e. because they are guarded
@@ -1357,6 +1751,57 @@ pub fn amd64_context(endian: Endian, rip: u64, rsp: u64) -> Section {
     section
 }
 
+/// Populate a `CONTEXT_ARM64` struct with the given `endian`, `pc`, and `sp`.
+pub fn arm64_context(endian: Endian, pc: u64, sp: u64) -> Section {
+    let section = Section::with_endian(endian)
+        .D32(0x400007) // context_flags: CONTEXT_ARM64_FULL
+        .D32(0) // cpsr
+        .append_repeated(0, mem::size_of::<u64>() * 31) // x0-x30
+        .D64(sp)
+        .D64(pc)
+        .append_repeated(0, mem::size_of::<u128>() * 32) // float_save.regs
+        .D32(0) // float_save.fpsr
+        .D32(0) // float_save.fpcr
+        .append_repeated(0, mem::size_of::<u32>() * 8) // bcr
+        .append_repeated(0, mem::size_of::<u64>() * 8) // bvr
+        .append_repeated(0, mem::size_of::<u32>() * 2) // wcr
+        .append_repeated(0, mem::size_of::<u64>() * 2); // wvr
+    assert_eq!(section.size(), md::CONTEXT_ARM64::size_with(&LE) as u64);
+    section
+}
+
+/// Populate a `CONTEXT_PPC` struct with the given `endian`, `srr0`, and `r1`.
+pub fn ppc_context(endian: Endian, srr0: u32, r1: u32) -> Section {
+    let section = Section::with_endian(endian)
+        .D32(0x20000001) // context_flags: CONTEXT_PPC_FULL
+        .D32(srr0)
+        .D32(0) // srr1
+        .D32(0) // r0
+        .D32(r1)
+        .append_repeated(0, mem::size_of::<u32>() * 30) // r2-r31
+        .append_repeated(0, mem::size_of::<u32>() * 6) // cr,xer,lr,ctr,mq,vrsave
+        .append_repeated(0, md::FLOATING_SAVE_AREA_PPC::size_with(&LE)) // float_save
+        .append_repeated(0, md::VECTOR_SAVE_AREA_PPC::size_with(&LE)); // vector_save
+    assert_eq!(section.size(), md::CONTEXT_PPC::size_with(&LE) as u64);
+    section
+}
+
+/// Populate a `CONTEXT_PPC64` struct with the given `endian`, `srr0`, and `r1`.
+pub fn ppc64_context(endian: Endian, srr0: u64, r1: u64) -> Section {
+    let section = Section::with_endian(endian)
+        .D64(0x01000001) // context_flags: CONTEXT_PPC64_FULL
+        .D64(srr0)
+        .D64(0) // srr1
+        .D64(0) // r0
+        .D64(r1)
+        .append_repeated(0, mem::size_of::<u64>() * 30) // r2-r31
+        .append_repeated(0, mem::size_of::<u64>() * 5) // cr,xer,lr,ctr,vrsave
+        .append_repeated(0, md::FLOATING_SAVE_AREA_PPC::size_with(&LE)) // float_save
+        .append_repeated(0, md::VECTOR_SAVE_AREA_PPC::size_with(&LE)); // vector_save
+    assert_eq!(section.size(), md::CONTEXT_PPC64::size_with(&LE) as u64);
+    section
+}
+
 pub struct SectionRef {
     section: Section,
     data_section: Section,
@@ -1670,6 +2115,10 @@ pub enum CpuInfo {
         feature_information: u32,
         amd_extended_cpu_features: u32,
     },
+    ARMCpuInfo {
+        cpuid: u32,
+        elf_hwcaps: u32,
+    },
 }
 
 impl SystemInfo {
@@ -1710,7 +2159,6 @@ impl From<SystemInfo> for Section {
         let section = info
             .section
             .D16(info.processor_architecture)
-            .D16(info.processor_architecture)
             .D16(info.processor_level)
             .D16(info.processor_revision)
             .D8(info.number_of_processors)
@@ -1736,6 +2184,10 @@ impl From<SystemInfo> for Section {
                 .D32(version_information)
                 .D32(feature_information)
                 .D32(amd_extended_cpu_features),
+            CpuInfo::ARMCpuInfo { cpuid, elf_hwcaps } => section
+                .D32(cpuid)
+                .D32(elf_hwcaps)
+                .append_repeated(0, mem::size_of::<u32>() * 4),
         }
     }
 }
@@ -1875,7 +2327,8 @@ fn test_dump_string() {
         &contents[mem::size_of::<md::MINIDUMP_HEADER>()..],
         &[
             0xa, 0x0, 0x0, 0x0, // length
-            b'h', 0x0, b'e', 0x0, b'l', 0x0, b'l', 0x0, b'o', 0x0
+            b'h', 0x0, b'e', 0x0, b'l', 0x0, b'l', 0x0, b'o', 0x0, // string
+            0x0, 0x0, // padding to align the (empty) stream directory
         ]
     );
 }
@@ -2064,3 +2517,73 @@ fn test_simple_stream_bigendian() {
         ]
     );
 }
+
+#[test]
+fn test_finish_validated() {
+    let name = DumpString::new("module", Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little).append_bytes(b"odd");
+    let module = Module::new(
+        Endian::Little,
+        0x10000,
+        0x1000,
+        &name,
+        0,
+        0,
+        Some(&STOCK_VERSION_INFO),
+    )
+    .cv_record(&cv_record);
+    let context = x86_context(Endian::Little, 0x10010, 0x2000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x11),
+        0x2000,
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_module(module)
+        .add(name)
+        .add(cv_record)
+        .add_thread(thread)
+        .add(context)
+        .add_memory(stack)
+        .set_linux_maps(b"odd length")
+        .set_linux_lsb_release(b"DISTRIB_ID=Ubuntu");
+    let contents = dump.finish_validated().unwrap();
+
+    // The stream directory comes last, and all the streams are aligned.
+    let directory_rva = u32::from_le_bytes(contents[12..16].try_into().unwrap());
+    assert_eq!(directory_rva % 4, 0);
+    for entry in contents[directory_rva as usize..].chunks(12) {
+        let rva = u32::from_le_bytes(entry[8..12].try_into().unwrap());
+        assert_eq!(rva % 4, 0);
+    }
+}
+
+#[test]
+fn test_finish_validated_errors() {
+    // A CodeView record that's never added to the minidump.
+    let name = DumpString::new("module", Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little).append_bytes(b"cv");
+    let module =
+        Module::new(Endian::Little, 0x10000, 0x1000, &name, 0, 0, None).cv_record(&cv_record);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_module(module)
+        .add(name);
+    assert_eq!(
+        dump.finish_validated().unwrap_err(),
+        ValidationError::Incomplete
+    );
+
+    // A module name that's past the end of the minidump.
+    let name = DumpString::new("module", Endian::Little);
+    name.file_offset().set_const(0x1000_0000);
+    let module = Module::new(Endian::Little, 0x10000, 0x1000, &name, 0, 0, None);
+    let dump = SynthMinidump::with_endian(Endian::Little).add_module(module);
+    assert_eq!(
+        dump.finish_validated().unwrap_err(),
+        ValidationError::OutOfBounds {
+            what: String::from("module name"),
+            rva: 0x1000_0000,
+            size: 4,
+        }
+    );
+}